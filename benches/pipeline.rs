@@ -0,0 +1,142 @@
+//! Benchmarks processing events through the stages of the composite device
+//! event pipeline, using a composite device with the default device profile
+//! and a capability map but without any target devices.
+//!
+//! Run with: cargo bench --bench pipeline
+use std::{error::Error, hint::black_box, time::Instant};
+
+use tokio::{net::UnixStream, sync::mpsc};
+use zbus::{connection, Connection, Guid};
+
+use inputplumber::{
+    config::{CapabilityMap, CompositeDeviceConfig, DeviceProfile},
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        composite_device::{
+            pipeline::{CompositeStage, EventFrame, Pipeline},
+            CompositeDevice,
+        },
+        event::{native::NativeEvent, value::InputValue},
+        manager::ManagerCommand,
+    },
+    udev::device::UdevDevice,
+};
+
+/// Number of events to process for each benchmark
+const ITERATIONS: u32 = 1_000_000;
+/// Device profile loaded into the composite device
+const PROFILE_PATH: &str = "rootfs/usr/share/inputplumber/profiles/default.yaml";
+/// Capability map loaded into the composite device
+const CAPABILITY_MAP_PATH: &str =
+    "rootfs/usr/share/inputplumber/capability_maps/onexplayer_type1.yaml";
+
+/// Returns a connection to an in-process DBus peer, so the composite device
+/// can be created without a bus.
+async fn peer_connections() -> Result<(Connection, Connection), Box<dyn Error>> {
+    let (server, client) = UnixStream::pair()?;
+    let server = connection::Builder::unix_stream(server)
+        .server(Guid::generate())?
+        .p2p()
+        .build();
+    let client = connection::Builder::unix_stream(client).p2p().build();
+    let (server, client) = tokio::join!(server, client);
+    Ok((server?, client?))
+}
+
+/// Create a composite device with the given device profile and capability
+/// map
+fn new_device(
+    conn: Connection,
+    manager: mpsc::Sender<ManagerCommand>,
+    profile: Option<DeviceProfile>,
+    capability_map: Option<CapabilityMap>,
+) -> Result<CompositeDevice, Box<dyn Error>> {
+    let config = CompositeDeviceConfig {
+        version: 1,
+        kind: "CompositeDevice".to_string(),
+        name: "Benchmark".to_string(),
+        matches: Vec::new(),
+        single_source: None,
+        maximum_sources: None,
+        capability_map_id: capability_map.as_ref().map(|map| map.id.clone()),
+        source_devices: Vec::new(),
+        target_devices: None,
+        options: None,
+        variables: None,
+    };
+    let device_info = UdevDevice::new_virtual("benchmark", "benchmark");
+    let dbus_path = "/org/shadowblip/InputPlumber/CompositeDeviceBenchmark".to_string();
+    let mut device = CompositeDevice::new(
+        conn,
+        manager,
+        config,
+        device_info,
+        dbus_path,
+        capability_map,
+    )?;
+    if let Some(profile) = profile {
+        device.load_device_profile(profile)?;
+    }
+    Ok(device)
+}
+
+/// Button event that is passed through the device profile
+fn button_event(i: u32) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        InputValue::Bool(i % 2 == 0),
+    )
+}
+
+/// Keyboard event that is part of a chord in the capability map
+fn chord_event(i: u32) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Keyboard(Keyboard::KeyLeftMeta),
+        InputValue::Bool(i % 2 == 0),
+    )
+}
+
+/// Process events created by the given function through the default stages
+/// of the composite device pipeline
+async fn run(
+    name: &str,
+    device: &mut CompositeDevice,
+    new_event: fn(u32) -> NativeEvent,
+) -> Result<(), Box<dyn Error>> {
+    let mut pipeline = Pipeline::new(CompositeStage::default_stages());
+    let mut frames = 0;
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        let frame = EventFrame::new(black_box(new_event(i)));
+        frames += black_box(pipeline.run(device, 0, frame).await?).len();
+    }
+    let elapsed = start.elapsed();
+    let per_event = elapsed.as_nanos() as f64 / ITERATIONS as f64;
+    println!("{name:>10}: {elapsed:?} total, {per_event:.1} ns/event ({frames} frames output)");
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let profile = DeviceProfile::from_yaml_file(PROFILE_PATH.to_string())?;
+        let capability_map = CapabilityMap::from_yaml_file(CAPABILITY_MAP_PATH.to_string())?;
+
+        // Keep the peer connection open until the benchmarks are done
+        let (_peer, conn) = peer_connections().await?;
+        let (manager, _manager_rx) = mpsc::channel(1);
+
+        // Composite device without a profile or capability map
+        let mut device = new_device(conn.clone(), manager.clone(), None, None)?;
+        run("empty", &mut device, button_event).await?;
+
+        // Composite device with a profile and capability map
+        let mut device = new_device(conn, manager, Some(profile), Some(capability_map))?;
+        run("profile", &mut device, button_event).await?;
+        run("chord", &mut device, chord_event).await?;
+
+        Ok::<(), Box<dyn Error>>(())
+    })
+}
@@ -8,8 +8,8 @@ Summary:        InputPlumber is an open source input routing and control daemon
 License:        GPLv3+
 URL:            https://github.com/ShadowBlip/InputPlumber
 
-BuildRequires:  libevdev-devel libiio-devel git make cargo libudev-devel llvm-devel clang-devel
-Requires:       libevdev libiio
+BuildRequires:  libevdev-devel libiio-devel libxkbcommon-devel git make cargo libudev-devel llvm-devel clang-devel
+Requires:       libevdev libiio libxkbcommon
 Recommends:     steam gamescope-session linuxconsoletools
 Provides:       inputplumber
 Conflicts:      hhd
@@ -34,6 +34,7 @@ mkdir -p %{buildroot}/usr/lib/udev/hwdb.d
 mkdir -p %{buildroot}/usr/share/inputplumber/capability_maps
 mkdir -p %{buildroot}/usr/share/inputplumber/devices
 mkdir -p %{buildroot}/usr/share/inputplumber/profiles
+mkdir -p %{buildroot}/usr/share/inputplumber/quirks
 mkdir -p %{buildroot}/usr/share/inputplumber/schema
 
 install -D -m 755 %{_builddir}/InputPlumber/target/release/inputplumber %{buildroot}/usr/bin/inputplumber
@@ -43,6 +44,7 @@ install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/lib/udev/hwdb.d/59-inputp
 install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/share/inputplumber/capability_maps/* %{buildroot}/usr/share/inputplumber/capability_maps/
 install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/share/inputplumber/devices/* %{buildroot}/usr/share/inputplumber/devices/
 install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/share/inputplumber/profiles/* %{buildroot}/usr/share/inputplumber/profiles/
+install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/share/inputplumber/quirks/* %{buildroot}/usr/share/inputplumber/quirks/
 install -D -m 644 %{_builddir}/InputPlumber/rootfs/usr/share/inputplumber/schema/* %{buildroot}/usr/share/inputplumber/schema/
 
 %post
@@ -128,9 +130,11 @@ systemctl disable inputplumber.servce
 /usr/share/inputplumber/profiles/default.yaml
 /usr/share/inputplumber/profiles/mouse_keyboard_wasd.yaml
 /usr/share/inputplumber/profiles/test.yaml
+/usr/share/inputplumber/quirks/50-gamepads.yaml
 /usr/share/inputplumber/schema/capability_map_v1.json
 /usr/share/inputplumber/schema/composite_device_v1.json
 /usr/share/inputplumber/schema/device_profile_v1.json
+/usr/share/inputplumber/schema/device_quirks_v1.json
 
 %changelog
 * Tue Aug 6 2024 William Edwards [0.33.1-0]
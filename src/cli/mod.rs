@@ -1,4 +1,5 @@
 pub mod device;
+pub mod profile;
 pub mod source;
 pub mod target;
 
@@ -6,12 +7,14 @@ use std::error::Error;
 
 use clap::{Parser, Subcommand};
 use device::{handle_device, handle_devices, DeviceCommand, DevicesCommand};
+use profile::{handle_profiles, ProfilesCommand};
 use source::{handle_sources, SourcesCommand};
 use target::{handle_targets, TargetsCommand};
 use zbus::fdo::ObjectManagerProxy;
 use zbus::{names::BusName, Connection};
 
 use crate::constants::{BUS_NAME, BUS_PREFIX};
+use crate::session;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -23,7 +26,15 @@ pub struct Args {
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Start the InputPlumber daemon (default)
-    Run,
+    Run {
+        /// Listen for per-user session instances that manage profiles on
+        /// behalf of their user
+        #[arg(long)]
+        session_broker: bool,
+    },
+    /// Start a per-user session instance that loads the user's profiles
+    /// through the session broker
+    Session,
     /// Manage source input devices
     Sources {
         #[command(subcommand)]
@@ -46,6 +57,11 @@ pub enum Commands {
         #[command(subcommand)]
         cmd: TargetsCommand,
     },
+    /// Convert device profiles from and to other formats
+    Profiles {
+        #[command(subcommand)]
+        cmd: ProfilesCommand,
+    },
 }
 
 pub async fn main_cli(args: Args) -> Result<(), Box<dyn Error>> {
@@ -53,6 +69,11 @@ pub async fn main_cli(args: Args) -> Result<(), Box<dyn Error>> {
         return Ok(());
     };
 
+    // Converting profiles does not need the daemon
+    if let Commands::Profiles { cmd } = cmd {
+        return handle_profiles(cmd);
+    }
+
     // Connect to DBus
     let connection = Connection::system().await?;
     if !is_running(&connection).await {
@@ -60,11 +81,13 @@ pub async fn main_cli(args: Args) -> Result<(), Box<dyn Error>> {
     }
 
     match cmd {
-        Commands::Run => (),
+        Commands::Run { .. } => (),
+        Commands::Session => session::daemon::run().await?,
         Commands::Sources { cmd } => handle_sources(connection, cmd).await?,
         Commands::Device { id: number, cmd } => handle_device(connection, cmd, number).await?,
         Commands::Devices { cmd } => handle_devices(connection, cmd).await?,
         Commands::Targets { cmd } => handle_targets(connection, cmd).await?,
+        Commands::Profiles { .. } => (),
     }
 
     Ok(())
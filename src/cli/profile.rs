@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fs;
+
+use clap::Subcommand;
+
+use crate::config::{
+    steam_input::{export_steam_input, import_steam_input},
+    DeviceProfile,
+};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ProfilesCommand {
+    /// Convert a Steam Input controller config (VDF) into a device profile
+    ImportSteam {
+        /// Path to the Steam Input controller config
+        path: String,
+        /// Path to write the device profile to. Prints it if not set.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Convert a device profile into a Steam Input controller config (VDF)
+    ExportSteam {
+        /// Path to the device profile
+        path: String,
+        /// Path to write the Steam Input controller config to. Prints it if
+        /// not set.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+pub fn handle_profiles(cmd: ProfilesCommand) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        ProfilesCommand::ImportSteam { path, output } => {
+            let content = fs::read_to_string(path)?;
+            let profile = import_steam_input(content.as_str())?;
+            let yaml = serde_yaml::to_string(&profile)?;
+            write_output(output, yaml)?;
+        }
+        ProfilesCommand::ExportSteam { path, output } => {
+            let profile = DeviceProfile::from_yaml_file(path)?;
+            let vdf = export_steam_input(&profile);
+            write_output(output, vdf)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the given content to the given path, or print it if no path is given
+fn write_output(path: Option<String>, content: String) -> Result<(), Box<dyn Error>> {
+    match path {
+        Some(path) => fs::write(path, content)?,
+        None => print!("{content}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Module for resolving virtual capabilities in device profiles
+//!
+//! Capability maps can define named virtual capabilities (aliases) that are
+//! composed of one or more other capabilities, such as "Sprint" meaning the
+//! left stick button. Device profiles can reference these aliases instead of
+//! hardware capabilities, and they are resolved when the profile is loaded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CapabilityConfig, DeviceProfile, LoadError, ProfileMapping};
+
+/// Maximum depth of aliases referencing other aliases
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// A named virtual capability composed of other capabilities
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CapabilityAlias {
+    /// Name used to reference the alias (e.g. "Sprint")
+    pub name: String,
+    /// Capabilities the alias resolves to. These may reference other aliases.
+    pub capabilities: Vec<CapabilityConfig>,
+}
+
+/// Returns a copy of the given device profile with all alias references
+/// replaced by the capabilities they resolve to. An aliased source event that
+/// resolves to multiple capabilities creates a mapping for each capability,
+/// while aliased target events emit every capability.
+pub fn resolve_aliases(
+    profile: &DeviceProfile,
+    aliases: &[CapabilityAlias],
+) -> Result<DeviceProfile, LoadError> {
+    let mut resolved = profile.clone();
+    resolved.mapping = resolve_mappings(&profile.mapping, aliases)?;
+    if let Some(layers) = resolved.layers.as_mut() {
+        for layer in layers.iter_mut() {
+            layer.mapping = resolve_mappings(&layer.mapping, aliases)?;
+            for switches in [layer.momentary.as_mut(), layer.toggle.as_mut()] {
+                let Some(switches) = switches else {
+                    continue;
+                };
+                let mut expanded = Vec::with_capacity(switches.len());
+                for switch in switches.iter() {
+                    expanded.extend(expand(switch, aliases, 0)?);
+                }
+                *switches = expanded;
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Resolve any aliases used in the given profile mappings
+fn resolve_mappings(
+    mappings: &[ProfileMapping],
+    aliases: &[CapabilityAlias],
+) -> Result<Vec<ProfileMapping>, LoadError> {
+    let mut resolved = Vec::with_capacity(mappings.len());
+    for mapping in mappings.iter() {
+        let mut target_events = Vec::new();
+        for target_event in mapping.target_events.iter() {
+            target_events.extend(expand(target_event, aliases, 0)?);
+        }
+
+        let source_events = expand(&mapping.source_event, aliases, 0)?;
+        let count = source_events.len();
+        for (i, source_event) in source_events.into_iter().enumerate() {
+            // Mapping names are used to track mapping state, so they need to
+            // be unique.
+            let name = if count > 1 {
+                format!("{} ({})", mapping.name, i + 1)
+            } else {
+                mapping.name.clone()
+            };
+            resolved.push(ProfileMapping {
+                name,
+                source_event,
+                target_events: target_events.clone(),
+                activator: mapping.activator.clone(),
+            });
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// Expand the given capability config into the capabilities it resolves to
+fn expand(
+    config: &CapabilityConfig,
+    aliases: &[CapabilityAlias],
+    depth: usize,
+) -> Result<Vec<CapabilityConfig>, LoadError> {
+    let Some(name) = config.alias.as_ref() else {
+        return Ok(vec![config.clone()]);
+    };
+    if depth >= MAX_ALIAS_DEPTH {
+        return Err(LoadError::InvalidAlias(format!(
+            "{name} exceeds the maximum alias depth of {MAX_ALIAS_DEPTH}"
+        )));
+    }
+    let Some(alias) = aliases.iter().find(|alias| &alias.name == name) else {
+        return Err(LoadError::InvalidAlias(format!("{name} is not defined")));
+    };
+
+    let mut capabilities = Vec::new();
+    for capability in alias.capabilities.iter() {
+        capabilities.extend(expand(capability, aliases, depth + 1)?);
+    }
+
+    Ok(capabilities)
+}
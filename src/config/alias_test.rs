@@ -0,0 +1,85 @@
+use crate::config::{CapabilityMap, DeviceProfile, LoadError};
+
+use super::alias::resolve_aliases;
+
+const CAPABILITY_MAP: &str = r#"
+version: 1
+kind: CapabilityMap
+name: Test
+id: test
+mapping: []
+aliases:
+  - name: Sprint
+    capabilities:
+      - gamepad:
+          button: LeftStick
+  - name: Interact
+    capabilities:
+      - gamepad:
+          button: South
+      - alias: Sprint
+"#;
+
+fn load_profile(content: &str) -> DeviceProfile {
+    DeviceProfile::from_yaml(content.to_string()).unwrap()
+}
+
+#[test]
+fn test_resolve_aliases() {
+    let map: CapabilityMap = serde_yaml::from_str(CAPABILITY_MAP).unwrap();
+    let aliases = map.aliases.unwrap();
+    let profile = load_profile(
+        r#"
+version: 1
+kind: DeviceProfile
+name: Test
+mapping:
+  - name: Sprint
+    source_event:
+      alias: Sprint
+    target_events:
+      - keyboard: KeyLeftShift
+  - name: Interact
+    source_event:
+      alias: Interact
+    target_events:
+      - alias: Interact
+"#,
+    );
+
+    let resolved = resolve_aliases(&profile, &aliases).unwrap();
+    assert_eq!(resolved.mapping.len(), 3);
+
+    let sprint = &resolved.mapping[0];
+    assert_eq!(sprint.name, "Sprint");
+    let button = sprint.source_event.gamepad.as_ref().unwrap().button.clone();
+    assert_eq!(button, Some("LeftStick".to_string()));
+
+    // Aliases composed of multiple capabilities create a mapping for each
+    // source capability and emit every target capability.
+    let interact = &resolved.mapping[1];
+    assert_eq!(interact.name, "Interact (1)");
+    assert_eq!(interact.target_events.len(), 2);
+    assert!(interact.target_events.iter().all(|e| e.alias.is_none()));
+    assert_eq!(resolved.mapping[2].name, "Interact (2)");
+}
+
+#[test]
+fn test_resolve_undefined_alias() {
+    let profile = load_profile(
+        r#"
+version: 1
+kind: DeviceProfile
+name: Test
+mapping:
+  - name: Jump
+    source_event:
+      alias: Jump
+    target_events:
+      - keyboard: KeySpace
+"#,
+    );
+
+    let result = resolve_aliases(&profile, &[]);
+    assert!(matches!(result, Err(LoadError::InvalidAlias(_))));
+}
@@ -1,16 +1,38 @@
+pub mod alias;
+#[cfg(test)]
+pub mod alias_test;
+pub mod overlay;
+#[cfg(test)]
+pub mod overlay_test;
 pub mod path;
-
-use std::io;
+pub mod profiles;
+#[cfg(test)]
+pub mod profiles_test;
+pub mod quirks;
+#[cfg(test)]
+pub mod quirks_test;
+pub mod steam_input;
+#[cfg(test)]
+pub mod steam_input_test;
+pub mod template;
+#[cfg(test)]
+pub mod template_test;
+
+use std::{collections::HashMap, io};
 
 use ::procfs::CpuInfo;
 use glob_match::glob_match;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    config::alias::CapabilityAlias,
     dmi::data::DMIData,
-    input::event::{native::NativeEvent, value::InputValue},
+    input::{
+        composite_device::activator::Activator,
+        event::{native::NativeEvent, value::InputValue},
+    },
     udev::device::UdevDevice,
 };
 
@@ -21,9 +43,15 @@ pub enum LoadError {
     IoError(#[from] io::Error),
     #[error("Unable to deserialize: {0}")]
     DeserializeError(#[from] serde_yaml::Error),
+    #[error("Undefined template variable: {0}")]
+    UndefinedVariable(String),
+    #[error("Invalid capability alias: {0}")]
+    InvalidAlias(String),
+    #[error("Invalid VDF: {0}")]
+    InvalidVdf(String),
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct DeviceProfile {
     pub version: u32, //useful?
@@ -31,9 +59,163 @@ pub struct DeviceProfile {
     pub name: String, //useful?
     pub target_devices: Option<Vec<String>>,
     pub description: Option<String>,
+    /// Games this profile was made for, shown to users when picking a profile
+    pub games: Option<Vec<String>>,
+    /// Filters applied to motion sensor events before they are translated
+    pub motion_filters: Option<MotionFilters>,
+    /// Detection of leaning (rolling) the device left or right, emitted as
+    /// the "LeanLeft" and "LeanRight" gestures that can be mapped to buttons
+    pub motion_lean: Option<MotionLeanConfig>,
+    /// Translation between the DPad and the left stick
+    pub dpad_stick: Option<DPadStickConfig>,
+    /// Auto-repeat of held DPad buttons for games that do not repeat input
+    pub dpad_repeat: Option<DPadRepeatConfig>,
+    /// Layout that source events are transformed into before they are
+    /// translated. Can be "none" or "joycon_sideways".
+    pub layout: Option<String>,
+    /// Shaped dead zones of the analog sticks, applied after the layout
+    /// transform and before the events are translated
+    pub stick_deadzones: Option<Vec<StickDeadzoneConfig>>,
+    /// Gates that restrict the analog sticks to 4 or 8 directions like an
+    /// arcade stick, applied after the dead zones
+    pub stick_gates: Option<Vec<StickGateConfig>>,
+    /// Overrides for the ranges of absolute axes advertised by target devices
+    pub axis_ranges: Option<Vec<AxisRangeConfig>>,
+    /// Chord that toggles intercept mode while this profile is loaded
+    pub intercept_activation: Option<InterceptActivationConfig>,
+    /// Layers of mappings that can be switched to with source inputs, like
+    /// the layers of programmable keyboards.
+    pub layers: Option<Vec<LayerConfig>>,
+    pub mapping: Vec<ProfileMapping>,
+}
+
+/// A layer of profile mappings that take precedence over the mappings of the
+/// base profile while the layer is active (e.g. a function layer on a macro
+/// pad). Source events without a mapping in the layer fall through to lower
+/// active layers and then to the base profile.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct LayerConfig {
+    /// Unique name of the layer
+    pub name: String,
+    /// Source inputs that activate the layer while they are held
+    pub momentary: Option<Vec<CapabilityConfig>>,
+    /// Source inputs that turn the layer on or off each time they are pressed
+    pub toggle: Option<Vec<CapabilityConfig>>,
+    /// Mappings to use while the layer is active
     pub mapping: Vec<ProfileMapping>,
 }
 
+/// Configures the chord that activates intercept mode, e.g. so overlays can
+/// be opened with a different button combination for a game.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InterceptActivationConfig {
+    /// Capabilities that activate intercept mode when pressed together.
+    /// E.g. ["Gamepad:Button:Guide", "Gamepad:Button:South"]
+    pub chord: Vec<String>,
+    /// Capability to emit when intercept mode is activated. Defaults to
+    /// "Gamepad:Button:Guide".
+    pub target: Option<String>,
+}
+
+/// Overrides the range of an absolute axis advertised by target devices.
+/// Values that are not set are taken from the source device hardware or the
+/// target device defaults.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AxisRangeConfig {
+    /// Name of the evdev axis (e.g. "ABS_X")
+    pub axis: String,
+    pub minimum: Option<i32>,
+    pub maximum: Option<i32>,
+    pub fuzz: Option<i32>,
+    pub flat: Option<i32>,
+    pub resolution: Option<i32>,
+}
+
+/// Dead zone of an analog stick. Games react differently to the shape of the
+/// dead zone, so it can be circular around the center or follow the axes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct StickDeadzoneConfig {
+    /// Name of the stick axis. E.g. "LeftStick" or "RightStick"
+    pub stick: String,
+    /// Shape of the dead zone. Can be "radial", "axial", or "cross". Defaults
+    /// to "radial".
+    pub shape: Option<String>,
+    /// Distance from the center, from 0.0 to 1.0, that is ignored. Defaults
+    /// to 0.1.
+    pub inner: Option<f64>,
+    /// Distance from the center, from 0.0 to 1.0, at which the stick reaches
+    /// its full range. Defaults to 1.0.
+    pub outer: Option<f64>,
+}
+
+/// Emulates the restrictor gate of an arcade stick. Stick positions are
+/// quantized to full deflection in the nearest allowed direction, which is
+/// preferred by arcade and retro games that expect digital directions.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct StickGateConfig {
+    /// Name of the stick axis. E.g. "LeftStick" or "RightStick"
+    pub stick: String,
+    /// Number of directions of the gate. Can be "4way" or "8way". Defaults
+    /// to "8way".
+    pub mode: Option<String>,
+    /// Distance from the center, from 0.0 to 1.0, the stick must be moved
+    /// before a direction is engaged. Defaults to 0.5.
+    pub threshold: Option<f64>,
+    /// Width in degrees of each diagonal direction of an 8-way gate. The
+    /// cardinal directions use the rest of each quadrant. Defaults to 45.
+    pub diagonal_angle: Option<f64>,
+    /// Angle in degrees to rotate the gate by. E.g. a 4-way gate rotated by
+    /// 45 only allows diagonals. Defaults to 0.
+    pub rotation: Option<f64>,
+}
+
+/// Configures translation between the DPad and the left stick for games that
+/// only read one or the other.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DPadStickConfig {
+    /// Initial translation mode. Can be "none", "dpad_to_stick", or
+    /// "stick_to_dpad".
+    pub mode: Option<String>,
+    /// Source gamepad buttons that toggle the translation on and off when
+    /// pressed together.
+    pub toggle_chord: Option<Vec<String>>,
+    /// Snaps near-diagonal DPad and stick input to the nearest cardinal
+    /// direction before it is translated.
+    pub cardinal_snap: Option<CardinalSnapConfig>,
+}
+
+/// Repeats held DPad buttons that are mapped to DPad buttons or keys, for
+/// games that do not implement repeat themselves (e.g. to scroll long lists).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DPadRepeatConfig {
+    /// Time in milliseconds a DPad button must be held before it starts
+    /// repeating. Defaults to 500.
+    pub delay_ms: Option<u64>,
+    /// Number of repeats per second while the button is held. Defaults to 10.
+    pub rate: Option<f64>,
+}
+
+/// Snaps near-diagonal DPad and stick input to the nearest cardinal direction
+/// (e.g. for fighting games or menu navigation).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CardinalSnapConfig {
+    /// Angle in degrees on either side of each cardinal direction within which
+    /// stick input is snapped to that direction. An angle of 45 makes the
+    /// sticks 4-way. Defaults to 15.
+    pub angle: Option<f64>,
+    /// Whether DPad diagonals are corrected to the direction that was pressed
+    /// first. Defaults to true.
+    pub dpad: Option<bool>,
+}
+
 impl DeviceProfile {
     /// Load a [CapabilityProfile] from the given YAML string
     pub fn from_yaml(content: String) -> Result<DeviceProfile, LoadError> {
@@ -49,15 +231,71 @@ impl DeviceProfile {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct ProfileMapping {
     pub name: String,
     pub source_event: CapabilityConfig,
     pub target_events: Vec<CapabilityConfig>,
+    /// How the source event activates the target events. Defaults to
+    /// pressing them while the source event is pressed.
+    pub activator: Option<ActivatorConfig>,
+}
+
+/// Defines how a digital source event activates the target events of a
+/// profile mapping, like the activators of Steam Input.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ActivatorConfig {
+    /// Can be "press", "long_press" to only activate once the source event
+    /// is held, or "double_press" to activate on the second of two quick
+    /// presses. The target events are released with the source event.
+    pub kind: String,
+    /// For "long_press", the time in milliseconds the source event must be
+    /// held. Defaults to 500. For "double_press", the maximum time in
+    /// milliseconds between the presses. Defaults to 300.
+    pub time_ms: Option<u64>,
 }
 
 impl ProfileMapping {
+    /// Returns the activator of this mapping if it is activated by anything
+    /// other than a regular press
+    pub fn activator(&self) -> Option<Activator> {
+        let activator = Activator::new(self.activator.as_ref()?);
+        (activator != Activator::Press).then_some(activator)
+    }
+
+    /// Returns the source axis config if this mapping translates an axis using
+    /// state like sectors, hysteresis, or key repeat.
+    pub fn source_axis_with_digital_state(&self) -> Option<&AxisCapability> {
+        let axis = self.source_event.gamepad.as_ref()?.axis.as_ref()?;
+        if !axis.uses_digital_state() {
+            return None;
+        }
+        Some(axis)
+    }
+
+    /// Returns the source touch motion config if this mapping translates
+    /// touch motion into relative mouse motion.
+    pub fn source_touch_to_mouse_motion(&self) -> Option<&TouchMotionCapability> {
+        let motion = self.source_event.touchpad.as_ref()?.touch.motion.as_ref()?;
+        let is_mouse_motion = self.target_events.iter().any(|target| {
+            target
+                .mouse
+                .as_ref()
+                .is_some_and(|mouse| mouse.motion.is_some())
+        });
+        is_mouse_motion.then_some(motion)
+    }
+
+    /// Returns the click zones of the source touchpad button if this mapping
+    /// determines the clicked mouse button using the finger position.
+    pub fn source_click_zones(&self) -> Option<&Vec<TouchClickZone>> {
+        let touch = &self.source_event.touchpad.as_ref()?.touch;
+        touch.button.as_ref()?;
+        touch.click_zones.as_ref()
+    }
+
     /// Returns true if the given event matches this profile mapping's source
     /// event. This method assumes that the event capability already matches, so
     /// this should only be called when trying to match specific properties of
@@ -67,7 +305,11 @@ impl ProfileMapping {
         if let Some(gamepad) = self.source_event.gamepad.as_ref() {
             // Gamepad Axis
             if let Some(axis) = gamepad.axis.as_ref() {
-                // Axis was defined for source event!
+                // Axis was defined for source event! If the axis needs to
+                // keep track of its state, it always needs to be processed.
+                if axis.uses_digital_state() {
+                    return true;
+                }
                 if let Some(direction) = axis.direction.as_ref() {
                     // A direction was defined!
                     let value = event.get_value();
@@ -101,6 +343,11 @@ impl ProfileMapping {
                 // Gyro was defined for source event!
                 // TODO: this
             }
+            // Gamepad accelerometer
+            else if let Some(_accel) = gamepad.accelerometer.as_ref() {
+                // Accelerometer was defined for source event!
+                return true;
+            }
         }
 
         // Mouse event
@@ -155,7 +402,38 @@ impl ProfileMapping {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Per-user policy used by session instances to decide which profiles should
+/// be loaded on which composite devices.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SessionPolicy {
+    pub version: u32,
+    pub kind: String,
+    pub name: String,
+    pub profiles: Vec<SessionProfile>,
+}
+
+impl SessionPolicy {
+    /// Load a [SessionPolicy] from the given YAML file
+    pub fn from_yaml_file(path: String) -> Result<SessionPolicy, LoadError> {
+        let file = std::fs::File::open(path)?;
+        let policy: SessionPolicy = serde_yaml::from_reader(file)?;
+        Ok(policy)
+    }
+}
+
+/// Profile that should be loaded on composite devices with a matching name
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SessionProfile {
+    /// Glob pattern matching the composite device name
+    pub device: String,
+    /// Path to the profile. Relative paths are resolved from the user's
+    /// profiles directory.
+    pub profile: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct CapabilityMap {
     pub version: u32,
@@ -163,6 +441,11 @@ pub struct CapabilityMap {
     pub name: String,
     pub id: String,
     pub mapping: Vec<CapabilityMapping>,
+    /// Named virtual capabilities that device profiles can reference
+    pub aliases: Option<Vec<CapabilityAlias>>,
+    /// Mappings that translate output events from target devices (like force
+    /// feedback or LED changes) into source-specific actions.
+    pub output: Option<Vec<OutputMapping>>,
     //pub filtered_events: Option<Vec<Capability>>,
 }
 
@@ -181,15 +464,75 @@ impl CapabilityMap {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct CapabilityMapping {
     pub name: String,
     pub source_events: Vec<CapabilityConfig>,
     pub target_event: CapabilityConfig,
+    /// Optional keyboard modifiers to hold while the target event is pressed
+    /// to emit a key combination. Modifiers are pressed in order before the
+    /// target event and released in reverse order after it. E.g.
+    /// ["KeyLeftCtrl", "KeyLeftShift"] with a target of "KeyT" emits
+    /// Ctrl+Shift+T.
+    pub modifiers: Option<Vec<String>>,
+    /// Optional platform conditions that must be met for this mapping to be
+    /// active. E.g. only apply this mapping when the device is docked.
+    pub conditions: Option<PlatformConditions>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Translates an output event sent by a target device into a source-specific
+/// action. E.g. setting the color of a handheld's RGB stick rings when a game
+/// changes the color of a DualSense lightbar.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OutputMapping {
+    pub name: String,
+    pub source_event: OutputCapabilityConfig,
+    pub target_event: OutputTargetConfig,
+    /// Whether the output event should still be sent to source devices.
+    /// Defaults to true.
+    pub passthrough: Option<bool>,
+}
+
+/// Output event sent by a target device
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OutputCapabilityConfig {
+    /// Matches force feedback events
+    pub force_feedback: Option<bool>,
+    /// Matches LED events. Can be "color" or "brightness".
+    pub led: Option<String>,
+}
+
+/// Source-specific action to perform for an output event
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OutputTargetConfig {
+    /// LED to set to the color of the output event
+    pub led: Option<LedOutputConfig>,
+    /// Action to perform when the output event is received
+    pub action: Option<ActionCapability>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct LedOutputConfig {
+    /// Name of the multicolor LED in "/sys/class/leds". E.g. "multicolor:chassis"
+    pub name: String,
+}
+
+/// Defines hardware platform conditions that must *all* be met for a
+/// [CapabilityMapping] to be active.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PlatformConditions {
+    pub tablet_mode: Option<bool>,
+    pub lid_open: Option<bool>,
+    pub docked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct CapabilityConfig {
     pub gamepad: Option<GamepadCapability>,
@@ -198,95 +541,621 @@ pub struct CapabilityConfig {
     pub dbus: Option<String>,
     pub touchpad: Option<TouchpadCapability>,
     pub touchscreen: Option<TouchCapability>,
+    /// Name of a virtual capability defined in the capability map. Aliases
+    /// are resolved when the device profile is loaded.
+    pub alias: Option<String>,
+    /// Action to perform instead of emitting an input event. Only supported
+    /// as the target event of a capability map mapping.
+    pub action: Option<ActionCapability>,
+    /// Text to type using the system keyboard layout when the source event
+    /// is pressed. Only supported as a target event of a profile mapping.
+    pub text: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// An action that is performed when a capability map mapping is activated
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ActionCapability {
+    /// DBus method to call on the system bus. The method must be allow-listed
+    /// in the "dbus" directory of one of the action directories.
+    pub dbus: Option<DBusActionCapability>,
+    /// Name of an executable in one of the allow-listed action directories
+    /// (e.g. "/etc/inputplumber/actions.d") to run.
+    pub command: Option<String>,
+    /// Name of the user to run the command as. Defaults to "nobody".
+    pub run_as: Option<String>,
+    /// Power action to request from systemd-logind for the active session.
+    /// Can be "suspend", "hibernate", "power_off", "reboot", or
+    /// "lock_session".
+    pub power: Option<String>,
+    /// Time in milliseconds the source events must be held before the action
+    /// is run. E.g. to only open the power menu on a long press.
+    pub hold_ms: Option<u64>,
+    /// Minimum time in milliseconds between running the action
+    pub debounce_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DBusActionCapability {
+    pub destination: String,
+    pub path: String,
+    pub interface: String,
+    pub method: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct GamepadCapability {
     pub axis: Option<AxisCapability>,
     pub button: Option<String>,
     pub trigger: Option<TriggerCapability>,
     pub gyro: Option<GyroCapability>,
+    pub accelerometer: Option<AccelerometerCapability>,
+    /// Name of a radial dial. E.g. "LeftDial"
+    pub dial: Option<String>,
+    /// Name of a motion gesture detected from the accelerometer. E.g. "Freefall"
+    pub gesture: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct AxisCapability {
     pub name: String,
     pub direction: Option<String>,
     pub deadzone: Option<f64>,
+    /// Amount the axis must fall back below the deadzone before a translated
+    /// button is released.
+    pub hysteresis: Option<f64>,
+    /// Width in degrees of the angle sector for the axis direction. E.g. 90
+    /// for exclusive 4-way input or 135 to allow diagonals.
+    pub sector_angle: Option<f64>,
+    /// Optional key repeat that scales with the axis deflection.
+    pub repeat: Option<AxisRepeatCapability>,
+    /// Deflection of the full stick from 0.0 - 1.0 where the axis is
+    /// considered 'pressed', regardless of direction. E.g. to sprint when the
+    /// stick is pushed to its outer ring.
+    pub outer_ring: Option<f64>,
+}
+
+impl AxisCapability {
+    /// Returns true if translating this axis into a button requires keeping
+    /// track of the axis state (e.g. for hysteresis, sectors, or key repeat).
+    pub fn uses_digital_state(&self) -> bool {
+        self.hysteresis.is_some()
+            || self.sector_angle.is_some()
+            || self.repeat.is_some()
+            || self.outer_ring.is_some()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AxisRepeatCapability {
+    /// Length of a single repeat cycle in milliseconds
+    pub period_ms: Option<u64>,
+    /// Axis deflection at which the button is held continuously
+    pub hold_threshold: Option<f64>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TriggerCapability {
     pub name: String,
     pub deadzone: Option<f64>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct GyroCapability {
     pub name: String,
     pub direction: Option<String>,
     pub deadzone: Option<f64>,
+    /// Gyro axis to use. When translating to a joystick axis, this selects
+    /// the axis used for horizontal aiming ("yaw" or "roll").
     pub axis: Option<String>,
+    /// Multiplier applied when translating gyro motion into a joystick axis.
+    /// Negative values invert the output.
+    pub sensitivity: Option<f64>,
+    /// Gamepad button that disables gyro output while held, so the device can
+    /// be repositioned without moving the joystick (ratcheting).
+    pub ratchet_button: Option<String>,
+}
+
+/// Device tilt measured by the accelerometer. When translated into a joystick
+/// axis, rolling the device left or right like a steering wheel moves the
+/// horizontal axis.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AccelerometerCapability {
+    pub name: String,
+    /// Tilt angle in degrees that results in full joystick deflection
+    pub max_angle: Option<f64>,
+    /// Tilt angle in degrees around the center that is ignored
+    pub deadzone: Option<f64>,
+    /// Whether to invert the direction of the joystick axis
+    pub invert: Option<bool>,
+}
+
+/// Smoothing filters to apply to motion sensor events to reduce jitter
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MotionFilters {
+    pub gyro: Option<MotionFilterConfig>,
+    pub accelerometer: Option<MotionFilterConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MotionFilterConfig {
+    /// Type of filter to apply. Can be "ema" (exponential moving average) or
+    /// "one_euro".
+    pub kind: String,
+    /// Cutoff frequency in Hz. For the one-euro filter, this is the minimum
+    /// cutoff frequency used when the sensor is moving slowly.
+    pub cutoff: Option<f64>,
+    /// How quickly the one-euro filter cutoff increases with speed. Higher
+    /// values reduce lag during fast movements.
+    pub beta: Option<f64>,
+    /// Cutoff frequency in Hz used by the one-euro filter to smooth the
+    /// estimated speed.
+    pub derivative_cutoff: Option<f64>,
+}
+
+/// Detects leaning the device left or right from its roll, measured by the
+/// accelerometer. A lean is held while the device is rolled past the
+/// threshold away from its neutral angle.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct MotionLeanConfig {
+    /// Roll angle in degrees away from the neutral angle that starts a lean.
+    /// Defaults to 20.
+    pub threshold: Option<f64>,
+    /// Angle in degrees the roll must return below the threshold before the
+    /// lean is released, so it does not flicker at the threshold. Defaults
+    /// to 5.
+    pub hysteresis: Option<f64>,
+    /// Time in seconds a lean can be held before the current angle becomes
+    /// the neutral angle and the lean is released, e.g. when the device is
+    /// held differently. Leans are never recentered if not set.
+    pub recenter_secs: Option<f64>,
+    /// Whether to swap the left and right lean directions
+    pub invert: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct MouseCapability {
     pub button: Option<String>,
     pub motion: Option<MouseMotionCapability>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct MouseMotionCapability {
     pub direction: Option<String>,
     pub speed_pps: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TouchpadCapability {
     pub name: String,
     pub touch: TouchCapability,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TouchCapability {
     pub button: Option<String>,
     pub motion: Option<TouchMotionCapability>,
+    /// Zones that determine which mouse button a touchpad click produces based
+    /// on the position of the finger. The first matching zone is used.
+    pub click_zones: Option<Vec<TouchClickZone>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Area of a touch device that produces the given mouse button when clicked.
+/// Bounds are normalized between 0.0 and 1.0 where (0, 0) is the top-left
+/// corner of the device.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchClickZone {
+    /// Mouse button to emit (e.g. "Left", "Right", "Middle")
+    pub button: String,
+    pub left: Option<f64>,
+    pub top: Option<f64>,
+    pub right: Option<f64>,
+    pub bottom: Option<f64>,
+}
+
+impl TouchClickZone {
+    /// Returns true if the given position is inside the zone
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.left.unwrap_or(0.0)
+            && x <= self.right.unwrap_or(1.0)
+            && y >= self.top.unwrap_or(0.0)
+            && y <= self.bottom.unwrap_or(1.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct TouchMotionCapability {
     pub region: Option<String>,
     pub speed_pps: Option<u64>,
+    /// Stick deflection between 0.0 and 1.0 below which a stick translated
+    /// into an absolute touch position stops touching. Defaults to 0.2.
+    pub deadzone: Option<f64>,
+    /// Edges of the touch device that emit mouse wheel events instead of
+    /// pointer motion when touch motion is translated into mouse motion.
+    pub edge_scroll: Option<TouchEdgeScroll>,
+    /// Curve used to translate the touch pressure into an analog trigger or
+    /// axis value.
+    pub pressure: Option<TouchPressure>,
+    /// Area of the target touch device that a stick translated into an
+    /// absolute touch position is confined to. Defaults to the whole device.
+    pub bounds: Option<TouchBounds>,
+}
+
+/// Rectangle on a touch device that a stick position is mapped into, where
+/// the center of the stick is the center of the rectangle. Bounds are
+/// normalized between 0.0 and 1.0 where (0, 0) is the top-left corner of the
+/// device.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchBounds {
+    pub left: Option<f64>,
+    pub top: Option<f64>,
+    pub right: Option<f64>,
+    pub bottom: Option<f64>,
+}
+
+/// Translates the pressure of a touch into an analog value between 0.0 and
+/// 1.0. Touches from devices that do not report pressure are treated as
+/// fully pressed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchPressure {
+    /// Pressure between 0.0 and 1.0 below which the value is 0.0
+    pub deadzone: Option<f64>,
+    /// Pressure between 0.0 and 1.0 that results in the full value. Defaults
+    /// to 1.0.
+    pub max: Option<f64>,
+    /// Exponent applied to the pressure after the deadzone. Values above 1.0
+    /// make light touches less sensitive. Defaults to 1.0 (linear).
+    pub curve: Option<f64>,
+}
+
+/// Scroll strips along the edges of a touch device. A touch that starts
+/// inside a strip scrolls until the finger is lifted, while touches that start
+/// anywhere else move the pointer. Sizes are normalized between 0.0 and 1.0.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TouchEdgeScroll {
+    /// Width of the vertical scroll strip along the right edge
+    pub right: Option<f64>,
+    /// Height of the horizontal scroll strip along the bottom edge
+    pub bottom: Option<f64>,
+    /// Distance the finger needs to travel to emit one wheel step. Defaults
+    /// to 0.05.
+    pub step: Option<f64>,
 }
 
 /// Defines available options for loading a [CompositeDeviceConfig]
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct CompositeDeviceConfigOptions {
     /// If true, InputPlumber will automatically try to manage the input device.
     /// If this is false, InputPlumber will not try to manage the device unless
     /// an external service enables management of all devices.
     pub auto_manage: Option<bool>,
+    /// Optional list of filters to control which events are routed to which
+    /// kind of target device.
+    pub target_filters: Option<Vec<TargetFilter>>,
+    /// Optional list of target devices to create in split mode. Each entry
+    /// creates a target device of the given kind that only receives the
+    /// events allowed by its filter, so a single physical device can act as
+    /// more than one controller (e.g. each half of a handheld for local
+    /// multiplayer). Replaces 'target_devices' when defined.
+    pub split: Option<Vec<TargetFilter>>,
+    /// Optional settings to control what happens when no input has been
+    /// received from source devices for some time.
+    pub idle: Option<IdleOptions>,
+    /// Optional list of digital source inputs to debounce for hardware with
+    /// worn buttons that register more than one press.
+    pub debounce: Option<Vec<DebounceConfig>>,
+    /// Optional settings to control the order and timing of target device
+    /// creation. Some games bind to the first enumerated gamepad, so this can
+    /// be used to make sure the intended device becomes "player 1".
+    pub target_creation: Option<TargetCreationOptions>,
+    /// Optional settings to compensate for analog stick drift. When defined,
+    /// the resting center of each stick is learned over time and persisted
+    /// for the device.
+    pub drift_compensation: Option<DriftCompensationConfig>,
+    /// Optional policy to set the color of a multicolor LED based on the
+    /// battery level and charging state of the system.
+    pub battery_led: Option<BatteryLedConfig>,
+    /// Optional list of tables to remap the keys written to a kind of target
+    /// device, e.g. to emit the positions of a non-QWERTY layout for games
+    /// that assume positional keys.
+    pub output_remaps: Option<Vec<OutputRemap>>,
+    /// Optional list of settings for individual source inputs, e.g. for
+    /// hardware whose buttons report inverted logic.
+    pub source_inputs: Option<Vec<SourceInputConfig>>,
+    /// Optional settings to play force feedback through an audio sink, for
+    /// devices whose rumble is driven by a haptic speaker instead of motors.
+    pub audio_haptics: Option<AudioHapticsConfig>,
+    /// Optional list of sinks that receive a copy of the events written to
+    /// target devices, e.g. to drive a second machine at the same time.
+    pub tee: Option<Vec<TeeConfig>>,
+    /// Optional changes to apply to the composite device while one of its
+    /// detachable controllers is detached
+    pub detachable: Option<Vec<DetachableConfig>>,
+    /// If true, the composite device keeps running when its last source
+    /// device disconnects, but its target devices are removed so games show
+    /// the controller as disconnected. They are re-created when a source
+    /// device reconnects.
+    pub mirror_disconnect: Option<bool>,
+    /// Optional settings to click automatically when the mouse pointer rests
+    /// in place, for users who cannot press buttons while aiming.
+    pub dwell_click: Option<DwellClickConfig>,
+}
+
+/// Remaps the keyboard events written to a particular kind of target device
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OutputRemap {
+    /// Kind of target device the remap applies to. E.g. "keyboard"
+    pub target: String,
+    /// Keys to replace before they are written to the target device
+    pub keys: Vec<KeyRemap>,
+}
+
+/// Replaces one key with another
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct KeyRemap {
+    /// Key to replace. E.g. "KeyW"
+    pub from: String,
+    /// Key to write instead. E.g. "KeyZ"
+    pub to: String,
+}
+
+/// Defines how the color of an LED reflects the battery level
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct BatteryLedConfig {
+    /// Name of the multicolor LED in "/sys/class/leds". E.g. "multicolor:chassis"
+    pub led: String,
+    /// Name of the battery in "/sys/class/power_supply". Supports globs.
+    /// Defaults to the first battery found.
+    pub battery: Option<String>,
+    /// Colors at battery levels from 0 to 100. Colors between two stops are
+    /// blended. Defaults to red at 0 and green at 100.
+    pub gradient: Option<Vec<BatteryLedStop>>,
+    /// Patterns that take precedence over the gradient. The first matching
+    /// pattern is used.
+    pub patterns: Option<Vec<BatteryLedPattern>>,
+    /// Interval in seconds to read the battery state. Defaults to 30.
+    pub poll_interval_secs: Option<u64>,
+    /// Time in seconds an LED color set by a game overrides the policy. If
+    /// not defined, the policy is disabled once a game sets a color.
+    pub override_timeout_secs: Option<u64>,
+}
+
+/// Color of a [BatteryLedConfig] gradient at the given battery level
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct BatteryLedStop {
+    /// Battery level from 0 to 100
+    pub level: u8,
+    /// RGB color of the LED
+    pub color: [u8; 3],
+}
+
+/// LED color or blink pattern used when the battery state matches. Conditions
+/// that are not defined always match.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct BatteryLedPattern {
+    /// Match if the battery level is below the given level
+    pub below: Option<u8>,
+    /// Match if the battery charging state is the given state
+    pub charging: Option<bool>,
+    /// RGB color of the LED. Defaults to the color of the gradient.
+    pub color: Option<[u8; 3]>,
+    /// Blink the LED, switching it on and off every given milliseconds
+    pub blink_ms: Option<u64>,
+}
+
+/// Defines how analog stick drift should be compensated
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DriftCompensationConfig {
+    /// Maximum distance from the center, from 0.0 to 1.0, that can be learned
+    /// as drift. Sticks further from the center are being used. Defaults to 0.15.
+    pub max_offset: Option<f64>,
+    /// Time in milliseconds a stick must rest before its center is learned.
+    /// Defaults to 1000.
+    pub rest_time_ms: Option<u64>,
+}
+
+/// Defines how force feedback is rendered as a waveform on a PipeWire audio
+/// sink connected to a haptic speaker. The waveform is played in the PipeWire
+/// session of the user of the active session.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AudioHapticsConfig {
+    /// Name of the PipeWire sink node to play on. E.g.
+    /// "alsa_output.pci-0000_04_00.6.analog-stereo". Uses the default sink if
+    /// not defined.
+    pub sink: Option<String>,
+    /// Frequency in Hz of the waveform played for the strong rumble motor.
+    /// Defaults to 100.
+    pub strong_frequency: Option<f64>,
+    /// Frequency in Hz of the waveform played for the weak rumble motor.
+    /// Defaults to 200.
+    pub weak_frequency: Option<f64>,
+    /// Waveform amplitude, from 0.0 to 1.0, of the weakest non-zero rumble
+    /// magnitude. Defaults to 0.1.
+    pub min_amplitude: Option<f64>,
+    /// Waveform amplitude, from 0.0 to 1.0, of the strongest rumble
+    /// magnitude. Defaults to 1.0.
+    pub max_amplitude: Option<f64>,
+}
+
+/// Sends a copy of the events written to the target devices of a composite
+/// device to an additional sink
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TeeConfig {
+    /// Unique name of the sink, used to enable or disable it over DBus
+    pub name: String,
+    /// Address to send events to over UDP. E.g. "192.168.1.20:9355"
+    pub udp: Option<String>,
+    /// Whether events are sent to the sink when the composite device starts.
+    /// Defaults to true.
+    pub enabled: Option<bool>,
+}
+
+/// Reconfigures a composite device while one of its detachable controllers is
+/// detached. E.g. switching to an FPS mouse profile when the right controller
+/// of a Legion Go is detached. The previous configuration is restored when
+/// the controller is attached again.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DetachableConfig {
+    /// Name of the controller, as used by its source devices. E.g. "right"
+    pub controller: String,
+    /// Path to a device profile to stack on top of the loaded profile while
+    /// the controller is detached
+    pub profile_overlay: Option<String>,
+    /// Target devices to use while the controller is detached. E.g.
+    /// ["mouse", "keyboard", "xb360"]
+    pub target_devices: Option<Vec<String>>,
+}
+
+/// Clicks automatically when the mouse pointer rests in place. The dwell
+/// starts once the pointer moves, and a click is emitted when it then stays
+/// within a small radius for the configured time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DwellClickConfig {
+    /// Time in milliseconds the pointer must rest before clicking. Defaults
+    /// to 1000.
+    pub time_ms: Option<u64>,
+    /// Distance in pixels the pointer can move while still resting. Defaults
+    /// to 8.0.
+    pub radius: Option<f64>,
+    /// Click to emit. Can be "left", "right", "double", or "gesture" to
+    /// choose the click by moving the pointer after the dwell: left for a
+    /// left click, right for a right click, up for a double click, or down
+    /// to cancel. Defaults to "left".
+    pub click: Option<String>,
+}
+
+/// Defines the order and timing in which target devices are created
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TargetCreationOptions {
+    /// Kinds of target devices in the order they should be created. E.g.
+    /// ["xb360", "keyboard", "mouse"]. Kinds that are not listed are created
+    /// after the listed ones.
+    pub order: Option<Vec<String>>,
+    /// Time in milliseconds to wait between creating each target device
+    pub delay_ms: Option<u64>,
+    /// If true, target devices are only created once Steam is running
+    pub wait_for_steam: Option<bool>,
+    /// Maximum number of seconds to wait for Steam before creating the target
+    /// devices anyway. Defaults to 30.
+    pub wait_for_steam_timeout_secs: Option<u64>,
+}
+
+impl TargetCreationOptions {
+    /// Sort the given kinds of target devices in the configured order
+    pub fn sort(&self, kinds: &mut [String]) {
+        let Some(order) = self.order.as_ref() else {
+            return;
+        };
+        kinds.sort_by_key(|kind| {
+            order
+                .iter()
+                .position(|ordered| ordered == kind)
+                .unwrap_or(order.len())
+        });
+    }
+
+    /// Returns true if target devices should be created in the background
+    /// with a delay or after waiting for Steam, instead of all at once when
+    /// the composite device starts.
+    pub fn is_staged(&self) -> bool {
+        self.delay_ms.unwrap_or_default() > 0 || self.wait_for_steam.unwrap_or_default()
+    }
+}
+
+/// Defines how a single digital source input should be debounced
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DebounceConfig {
+    /// Source capability to debounce
+    pub capability: CapabilityConfig,
+    /// Minimum time in milliseconds the input must be stable before a state
+    /// change is emitted
+    pub time_ms: u64,
+}
+
+/// Defines settings for a single source input
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SourceInputConfig {
+    /// Source capability the settings apply to
+    pub capability: CapabilityConfig,
+    /// If true, the input reports inverted logic (e.g. a normally-closed
+    /// switch) and its presses and releases are swapped. Analog values are
+    /// reversed, so 0.0 becomes 1.0.
+    pub inverted: Option<bool>,
+}
+
+/// Defines how the composite device should behave when it becomes idle
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct IdleOptions {
+    /// Number of seconds without source input before the device is idle
+    pub timeout_secs: u64,
+    /// Action to perform on target devices when the device becomes idle. Can
+    /// be "none" to only signal the idle state, "suspend" to keep the target
+    /// devices but stop writing events to them until the next input, or
+    /// "destroy" to stop all target devices and re-create them on the next
+    /// input. Defaults to "none".
+    pub action: Option<String>,
+}
+
+/// Defines which events should be routed to a particular kind of target device.
+/// For regular target devices, the filter restricts which events they receive.
+/// For the "dbus" target device, events in the allow list are mirrored to the
+/// DBus target in addition to being routed to the other target devices.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TargetFilter {
+    /// Kind of target device this filter applies to. E.g. "dbus", "xb360"
+    pub target: String,
+    /// If defined, only events matching these capabilities will be sent to
+    /// the target device.
+    pub allow: Option<Vec<CapabilityConfig>>,
+    /// Events matching these capabilities will never be sent to the target
+    /// device.
+    pub deny: Option<Vec<CapabilityConfig>>,
 }
 
 /// Defines a platform match for loading a [CompositeDeviceConfig]
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Match {
     pub dmi_data: Option<DMIMatch>,
 }
 
 /// Match DMI data for loading a [CompositeDevice]
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct DMIMatch {
     pub bios_release: Option<String>,
@@ -300,7 +1169,7 @@ pub struct DMIMatch {
     pub cpu_vendor: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct SourceDevice {
     pub group: String,
@@ -311,9 +1180,50 @@ pub struct SourceDevice {
     pub unique: Option<bool>,
     pub blocked: Option<bool>,
     pub ignore: Option<bool>,
+    pub power: Option<SourcePower>,
+    /// Side of a composite device built from two physical halves (e.g. a pair
+    /// of Joy-Cons) that this source device is on. Can be "both", "left", or
+    /// "right". Left sources only play the strong (left) rumble motor and
+    /// right sources only play the weak (right) rumble motor.
+    pub rumble_side: Option<String>,
+    /// Priority of this source device when more than one source device emits
+    /// the same capability. While a source is using a capability (e.g. a
+    /// button is held), events for it from lower priority sources are dropped,
+    /// and higher priority sources preempt it. Defaults to 0.
+    pub priority: Option<i32>,
+    /// Detachable controller this source device belongs to. Used to detect
+    /// when the controller is attached to or detached from the device.
+    pub detachable: Option<SourceDetachable>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Detachable controller (e.g. one half of a Legion Go) a source device
+/// belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SourceDetachable {
+    /// Name of the controller. E.g. "right"
+    pub controller: String,
+    /// Whether the presence of the source device means the controller is
+    /// attached. Set to false for source devices that only appear while the
+    /// controller is detached (e.g. the FPS mode interface of a Legion Go).
+    /// Defaults to true.
+    pub attached: Option<bool>,
+}
+
+/// Power management quirks for source devices that misbehave with runtime
+/// power management, like some I2C HID controllers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SourcePower {
+    /// Disable runtime autosuspend of the device and its parent bus device
+    pub disable_autosuspend: Option<bool>,
+    /// Feature reports to send to a hidraw device after system resume to
+    /// re-sync its state. Each report is a list of bytes starting with the
+    /// report id.
+    pub resume_feature_reports: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Evdev {
     pub name: Option<String>,
@@ -321,9 +1231,24 @@ pub struct Evdev {
     pub handler: Option<String>,
     pub vendor_id: Option<String>,
     pub product_id: Option<String>,
+    pub long_press: Option<EvdevLongPress>,
+}
+
+/// Splits presses of chassis buttons (e.g. power and volume) into short and
+/// long presses. Short presses are passed through when the button is released,
+/// while long presses emit a separate capability that can be mapped in a
+/// device profile.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct EvdevLongPress {
+    /// Keyboard capabilities to split (e.g. "KeyVolumeUp"). Defaults to the
+    /// power and volume keys.
+    pub keys: Option<Vec<String>>,
+    /// Time in milliseconds a button must be held to be a long press
+    pub threshold_ms: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Hidraw {
     pub vendor_id: Option<u16>,
@@ -331,9 +1256,12 @@ pub struct Hidraw {
     pub interface_num: Option<i32>,
     pub handler: Option<String>,
     pub name: Option<String>,
+    /// Port of a multi-port adapter (e.g. the GameCube controller adapter)
+    /// to match, starting at 1. If unset, every port matches.
+    pub port: Option<u8>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct Udev {
     pub attributes: Option<Vec<UdevAttribute>>,
@@ -346,23 +1274,44 @@ pub struct Udev {
     pub sys_path: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct UdevAttribute {
     pub name: String,
     pub value: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct IIO {
     pub id: Option<String>,
     pub name: Option<String>,
     pub mount_matrix: Option<MountMatrix>,
+    pub sample_rate: Option<IIOSampleRate>,
+    pub poll_interval: Option<IIOPollInterval>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Sampling frequencies in Hz to configure on the IIO device. Values must be
+/// one of the frequencies listed in the device's sampling_frequency_available
+/// attribute.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct IIOSampleRate {
+    pub accel: Option<f64>,
+    pub gyro: Option<f64>,
+}
+
+/// Bounds for how often the IIO device is polled. The poll interval follows
+/// the configured sample rate, clamped between these values.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct IIOPollInterval {
+    pub min_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[allow(clippy::upper_case_acronyms)]
 pub struct MountMatrix {
@@ -372,7 +1321,7 @@ pub struct MountMatrix {
 }
 
 /// Defines a combined device
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct CompositeDeviceConfig {
     pub version: u32,
@@ -385,6 +1334,8 @@ pub struct CompositeDeviceConfig {
     pub source_devices: Vec<SourceDevice>,
     pub target_devices: Option<Vec<String>>,
     pub options: Option<CompositeDeviceConfigOptions>,
+    /// Default values for variables referenced in this config with `${NAME}`
+    pub variables: Option<HashMap<String, String>>,
 }
 
 impl CompositeDeviceConfig {
@@ -394,13 +1345,44 @@ impl CompositeDeviceConfig {
         Ok(device)
     }
 
-    /// Load a [CompositeDevice] from the given YAML file
+    /// Load a [CompositeDevice] from the given YAML file. If the file is a
+    /// template that references variables, they will be resolved first.
     pub fn from_yaml_file(path: String) -> Result<CompositeDeviceConfig, LoadError> {
-        let file = std::fs::File::open(path)?;
-        let device: CompositeDeviceConfig = serde_yaml::from_reader(file)?;
+        let content = std::fs::read_to_string(path)?;
+        let content = if template::is_template(content.as_str()) {
+            template::render_template(content.as_str())?
+        } else {
+            content
+        };
+        let device: CompositeDeviceConfig = serde_yaml::from_str(content.as_str())?;
         Ok(device)
     }
 
+    /// Returns the kinds of target devices to create for this composite
+    /// device. In split mode, one target device is created for each split
+    /// target. The kinds are sorted in the configured creation order.
+    pub fn target_device_types(&self) -> Option<Vec<String>> {
+        let split = self
+            .options
+            .as_ref()
+            .and_then(|options| options.split.as_ref())
+            .filter(|split| !split.is_empty());
+        let mut kinds = match split {
+            Some(split) => split.iter().map(|filter| filter.target.clone()).collect(),
+            None => self.target_devices.clone()?,
+        };
+        if let Some(creation) = self.target_creation() {
+            creation.sort(kinds.as_mut_slice());
+        }
+        Some(kinds)
+    }
+
+    /// Returns the options for the order and timing of target device
+    /// creation, if any are defined.
+    pub fn target_creation(&self) -> Option<&TargetCreationOptions> {
+        self.options.as_ref()?.target_creation.as_ref()
+    }
+
     /// Returns an array of all defined hidraw source devices
     fn _get_hidraw_configs(&self) -> Vec<Hidraw> {
         self.source_devices
@@ -614,6 +1596,14 @@ impl CompositeDeviceConfig {
             }
         }
 
+        if let Some(port) = hidraw_config.port {
+            let dport = device.port();
+            log::trace!("Checking port: {port} against {dport:?}");
+            if dport != Some(port) {
+                return false;
+            }
+        }
+
         true
     }
 
@@ -702,97 +1692,107 @@ impl CompositeDeviceConfig {
 
     /// Returns matches that matched system data.
     pub fn get_valid_matches(&self, data: &DMIData, cpu_info: &CpuInfo) -> Option<Vec<Match>> {
-        let mut matches: Vec<Match> = Vec::new();
-
-        // If there are no match definitions, consider it a match
-        if self.matches.is_empty() {
-            return Some(matches);
-        }
-
-        // Check all match configs for ANY matches.
-        for match_config in self.matches.clone() {
-            let conf = match_config.clone();
-            let mut has_matches = false;
+        find_valid_matches(&self.matches, data, cpu_info)
+    }
+}
 
-            if let Some(dmi_config) = match_config.dmi_data {
-                if let Some(cpu_vendor) = dmi_config.cpu_vendor {
-                    if !glob_match(
-                        cpu_vendor.as_str(),
-                        cpu_info.vendor_id(0).unwrap_or_default(),
-                    ) {
-                        continue;
-                    }
-                    has_matches = true;
-                }
+/// Returns the given matches that matched system data. If the list of matches
+/// is empty, it is considered a match. Returns [None] if nothing matched.
+pub fn find_valid_matches(
+    match_configs: &[Match],
+    data: &DMIData,
+    cpu_info: &CpuInfo,
+) -> Option<Vec<Match>> {
+    let mut matches: Vec<Match> = Vec::new();
+
+    // If there are no match definitions, consider it a match
+    if match_configs.is_empty() {
+        return Some(matches);
+    }
 
-                if let Some(bios_release) = dmi_config.bios_release {
-                    if !glob_match(bios_release.as_str(), data.bios_release.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+    // Check all match configs for ANY matches.
+    for match_config in match_configs.iter().cloned() {
+        let conf = match_config.clone();
+        let mut has_matches = false;
+
+        if let Some(dmi_config) = match_config.dmi_data {
+            if let Some(cpu_vendor) = dmi_config.cpu_vendor {
+                if !glob_match(
+                    cpu_vendor.as_str(),
+                    cpu_info.vendor_id(0).unwrap_or_default(),
+                ) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(bios_vendor) = dmi_config.bios_vendor {
-                    if !glob_match(bios_vendor.as_str(), data.bios_vendor.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(bios_release) = dmi_config.bios_release {
+                if !glob_match(bios_release.as_str(), data.bios_release.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(bios_version) = dmi_config.bios_version {
-                    if !glob_match(bios_version.as_str(), data.bios_version.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(bios_vendor) = dmi_config.bios_vendor {
+                if !glob_match(bios_vendor.as_str(), data.bios_vendor.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(board_name) = dmi_config.board_name {
-                    if !glob_match(board_name.as_str(), data.board_name.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(bios_version) = dmi_config.bios_version {
+                if !glob_match(bios_version.as_str(), data.bios_version.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(product_name) = dmi_config.product_name {
-                    if !glob_match(product_name.as_str(), data.product_name.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(board_name) = dmi_config.board_name {
+                if !glob_match(board_name.as_str(), data.board_name.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(product_version) = dmi_config.product_version {
-                    if !glob_match(product_version.as_str(), data.product_version.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(product_name) = dmi_config.product_name {
+                if !glob_match(product_name.as_str(), data.product_name.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(product_sku) = dmi_config.product_sku {
-                    if !glob_match(product_sku.as_str(), data.product_sku.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(product_version) = dmi_config.product_version {
+                if !glob_match(product_version.as_str(), data.product_version.as_str()) {
+                    continue;
                 }
+                has_matches = true;
+            }
 
-                if let Some(sys_vendor) = dmi_config.sys_vendor {
-                    if !glob_match(sys_vendor.as_str(), data.sys_vendor.as_str()) {
-                        continue;
-                    }
-                    has_matches = true;
+            if let Some(product_sku) = dmi_config.product_sku {
+                if !glob_match(product_sku.as_str(), data.product_sku.as_str()) {
+                    continue;
                 }
+                has_matches = true;
             }
 
-            if !has_matches {
-                continue;
+            if let Some(sys_vendor) = dmi_config.sys_vendor {
+                if !glob_match(sys_vendor.as_str(), data.sys_vendor.as_str()) {
+                    continue;
+                }
+                has_matches = true;
             }
-
-            matches.push(conf);
         }
 
-        if matches.is_empty() {
-            return None;
+        if !has_matches {
+            continue;
         }
 
-        Some(matches)
+        matches.push(conf);
     }
+
+    if matches.is_empty() {
+        return None;
+    }
+
+    Some(matches)
 }
@@ -0,0 +1,30 @@
+//! Module for stacking overlay profiles on top of a base device profile
+//!
+//! An overlay profile only defines the mappings it changes, such as a
+//! game-specific "gyro on" toggle, so the rest of the base profile does not
+//! need to be duplicated.
+
+use std::collections::HashSet;
+
+use crate::{config::DeviceProfile, input::capability::Capability};
+
+/// Returns a copy of the given base profile with the mappings of the given
+/// overlay profile applied on top of it. All base mappings of a source
+/// capability that the overlay maps are replaced by the overlay mappings.
+/// Any other settings of the base profile are kept. Aliases must be resolved
+/// in both profiles before they are combined.
+pub fn apply_overlay(base: &DeviceProfile, overlay: &DeviceProfile) -> DeviceProfile {
+    let overridden: HashSet<Capability> = overlay
+        .mapping
+        .iter()
+        .map(|mapping| mapping.source_event.clone().into())
+        .collect();
+
+    let mut profile = base.clone();
+    profile.mapping.retain(|mapping| {
+        let cap: Capability = mapping.source_event.clone().into();
+        !overridden.contains(&cap)
+    });
+    profile.mapping.extend(overlay.mapping.iter().cloned());
+    profile
+}
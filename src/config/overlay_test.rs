@@ -0,0 +1,76 @@
+use crate::config::DeviceProfile;
+
+use super::overlay::apply_overlay;
+
+const BASE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Base
+mapping:
+  - name: South
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - gamepad:
+          button: East
+  - name: Stick Up
+    source_event:
+      gamepad:
+        axis:
+          name: RightStick
+          direction: up
+    target_events:
+      - keyboard: KeyW
+  - name: Stick Down
+    source_event:
+      gamepad:
+        axis:
+          name: RightStick
+          direction: down
+    target_events:
+      - keyboard: KeyS
+"#;
+
+const OVERLAY: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Gyro On
+mapping:
+  - name: Gyro
+    source_event:
+      gamepad:
+        gyro:
+          name: Gyro1
+    target_events:
+      - gamepad:
+          axis:
+            name: RightStick
+  - name: Right Stick
+    source_event:
+      gamepad:
+        axis:
+          name: RightStick
+    target_events:
+      - gamepad:
+          axis:
+            name: RightStick
+"#;
+
+#[test]
+fn test_apply_overlay() {
+    let base = DeviceProfile::from_yaml(BASE.to_string()).unwrap();
+    let overlay = DeviceProfile::from_yaml(OVERLAY.to_string()).unwrap();
+    let profile = apply_overlay(&base, &overlay);
+
+    // The base profile settings should be kept
+    assert_eq!(profile.name, "Base");
+
+    // All base mappings of overridden source capabilities should be replaced
+    let names: Vec<&str> = profile
+        .mapping
+        .iter()
+        .map(|mapping| mapping.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["South", "Gyro", "Right Stick"]);
+}
@@ -4,6 +4,8 @@ use std::path::PathBuf;
 
 /// Base system fallback path to use if one cannot be found with XDG
 const FALLBACK_BASE_PATH: &str = "/usr/share/inputplumber";
+/// Path to persist state in when running as a system service
+const SYSTEM_STATE_PATH: &str = "/var/lib/inputplumber";
 
 /// Returns the base path for configuration data
 pub fn get_base_path() -> PathBuf {
@@ -30,6 +32,20 @@ pub fn get_profiles_path() -> PathBuf {
     base_path.join("profiles")
 }
 
+/// Returns a list of user and system directories in preference order to find
+/// input profiles.
+/// E.g. ["~/.config/inputplumber/profiles", "/etc/inputplumber/profiles.d", "/usr/share/inputplumber/profiles"]
+pub fn get_profiles_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+    if let Some(user_path) = get_user_config_path() {
+        paths.push(user_path.join("profiles"));
+    }
+    paths.push(PathBuf::from("/etc/inputplumber/profiles.d"));
+    paths.push(get_profiles_path());
+
+    paths
+}
+
 /// Returns a list of directories in preference order to find device configurations.
 /// E.g. ["/etc/inputplumber/devices.d", "/usr/share/inputplumber/devices"]
 pub fn get_devices_paths() -> Vec<PathBuf> {
@@ -53,3 +69,74 @@ pub fn get_capability_maps_paths() -> Vec<PathBuf> {
 
     paths
 }
+
+/// Returns a list of directories in preference order to find allow-listed
+/// commands that can be run by capability map actions.
+/// E.g. ["/etc/inputplumber/actions.d", "/usr/share/inputplumber/actions"]
+pub fn get_actions_paths() -> Vec<PathBuf> {
+    let paths = vec![
+        PathBuf::from("/etc/inputplumber/actions.d"),
+        get_base_path().join("actions"),
+    ];
+
+    paths
+}
+
+/// Returns a list of directories in preference order to find allow-listed
+/// external driver executables that can be run as source devices.
+/// E.g. ["/etc/inputplumber/drivers.d", "/usr/share/inputplumber/drivers"]
+pub fn get_drivers_paths() -> Vec<PathBuf> {
+    let paths = vec![
+        PathBuf::from("/etc/inputplumber/drivers.d"),
+        get_base_path().join("drivers"),
+    ];
+
+    paths
+}
+
+/// Returns a list of directories in preference order to find variables used
+/// to render config templates.
+/// E.g. ["/etc/inputplumber/variables.d", "/usr/share/inputplumber/variables"]
+pub fn get_variables_paths() -> Vec<PathBuf> {
+    let paths = vec![
+        PathBuf::from("/etc/inputplumber/variables.d"),
+        get_base_path().join("variables"),
+    ];
+
+    paths
+}
+
+/// Returns a list of user and system directories in preference order to find
+/// device quirks.
+/// E.g. ["~/.config/inputplumber/quirks", "/etc/inputplumber/quirks.d", "/usr/share/inputplumber/quirks"]
+pub fn get_quirks_paths() -> Vec<PathBuf> {
+    let mut paths = vec![];
+    if let Some(user_path) = get_user_config_path() {
+        paths.push(user_path.join("quirks"));
+    }
+    paths.push(PathBuf::from("/etc/inputplumber/quirks.d"));
+    paths.push(get_base_path().join("quirks"));
+
+    paths
+}
+
+/// Returns the per-user configuration directory used by session instances.
+/// E.g. "~/.config/inputplumber"
+pub fn get_user_config_path() -> Option<PathBuf> {
+    let base_dirs = xdg::BaseDirectories::with_prefix("inputplumber").ok()?;
+    Some(base_dirs.get_config_home())
+}
+
+/// Returns the directory used to persist state such as learned calibration
+/// data. The system instance uses "/var/lib/inputplumber" and session
+/// instances use the user state directory.
+/// E.g. "/var/lib/inputplumber" or "~/.local/state/inputplumber"
+pub fn get_state_path() -> PathBuf {
+    if nix::unistd::geteuid().is_root() {
+        return PathBuf::from(SYSTEM_STATE_PATH);
+    }
+    let Ok(base_dirs) = xdg::BaseDirectories::with_prefix("inputplumber") else {
+        return PathBuf::from(SYSTEM_STATE_PATH);
+    };
+    base_dirs.get_state_home()
+}
@@ -0,0 +1,81 @@
+//! Module for enumerating the input profiles that are available on the system
+//!
+//! Profiles are discovered in the user and system profile directories. If a
+//! profile with the same file name exists in more than one directory, only the
+//! one in the directory with the highest preference is listed.
+
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use super::DeviceProfile;
+
+/// Metadata about an input profile that can be used to present the profile
+/// to users without loading it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileInfo {
+    /// Absolute path to the profile
+    pub path: String,
+    pub name: String,
+    pub description: String,
+    /// Games the profile was made for
+    pub games: Vec<String>,
+}
+
+impl ProfileInfo {
+    /// Create the profile metadata from the given profile and its path
+    pub fn new(path: String, profile: &DeviceProfile) -> Self {
+        Self {
+            path,
+            name: profile.name.clone(),
+            description: profile.description.clone().unwrap_or_default(),
+            games: profile.games.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Returns metadata for all valid profiles found in the given directories.
+/// Directories should be given in preference order.
+pub fn list_profiles(paths: &[PathBuf]) -> Vec<ProfileInfo> {
+    let mut profiles = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in paths.iter() {
+        let files = match fs::read_dir(path) {
+            Ok(files) => files,
+            Err(e) => {
+                log::trace!("Failed to load directory {path:?}: {e}");
+                continue;
+            }
+        };
+        let mut files: Vec<_> = files.filter_map(|r| r.ok()).collect();
+        files.sort_by_key(|dir| dir.file_name());
+
+        // Look at each file in the directory and try to load them
+        for file in files {
+            let filename = file.file_name().to_string_lossy().to_string();
+
+            // Skip any non-yaml files
+            if !filename.ends_with(".yaml") {
+                continue;
+            }
+
+            // Skip profiles overridden by a directory with higher preference
+            if seen.contains(&filename) {
+                log::trace!("Skipping overridden profile: {}", file.path().display());
+                continue;
+            }
+
+            let path = file.path().display().to_string();
+            let profile = match DeviceProfile::from_yaml_file(path.clone()) {
+                Ok(profile) => profile,
+                Err(e) => {
+                    log::warn!("Failed to parse profile '{path}': {e}");
+                    continue;
+                }
+            };
+            seen.insert(filename);
+            profiles.push(ProfileInfo::new(path, &profile));
+        }
+    }
+
+    profiles
+}
@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+
+use super::profiles::list_profiles;
+
+#[test]
+fn test_list_profiles() {
+    let paths = vec![
+        PathBuf::from("./rootfs/usr/share/inputplumber/profiles"),
+        PathBuf::from("./rootfs/usr/share/inputplumber/profiles"),
+        PathBuf::from("./does/not/exist"),
+    ];
+    let profiles = list_profiles(&paths);
+    assert!(!profiles.is_empty(), "should find profiles");
+
+    let default = profiles
+        .iter()
+        .find(|p| p.path.ends_with("default.yaml"))
+        .expect("should find default profile");
+    assert!(!default.name.is_empty());
+
+    // Profiles found in more than one directory should only be listed once
+    let count = profiles
+        .iter()
+        .filter(|p| p.path.ends_with("default.yaml"))
+        .count();
+    assert_eq!(count, 1);
+}
@@ -0,0 +1,126 @@
+//! Module for looking up device quirks
+//!
+//! Some hardware needs special handling, like a stick with an inverted axis,
+//! broken force feedback, or input reports that differ in length from the
+//! spec. Instead of special cases in the drivers, these quirks are described
+//! in data files keyed by vendor id, product id and firmware version. Quirks
+//! files in directories with a higher preference override the shipped ones.
+
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CapabilityConfig, LoadError};
+
+/// A set of device quirks loaded from a quirks file
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DeviceQuirks {
+    pub version: u32,
+    pub kind: String,
+    pub name: String,
+    pub quirks: Vec<DeviceQuirk>,
+}
+
+impl DeviceQuirks {
+    /// Load [DeviceQuirks] from the given YAML file
+    pub fn from_yaml_file(path: String) -> Result<DeviceQuirks, LoadError> {
+        let file = std::fs::File::open(path)?;
+        let quirks: DeviceQuirks = serde_yaml::from_reader(file)?;
+        Ok(quirks)
+    }
+}
+
+/// Quirks of a single device model
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DeviceQuirk {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Firmware versions (the device version number) the quirk applies to.
+    /// If not set, the quirk applies to all firmware versions.
+    pub firmware: Option<Vec<u16>>,
+    /// Axes that the device reports with the wrong direction
+    pub inverted_axes: Option<Vec<InvertedAxis>>,
+    /// If true, force feedback effects are never sent to the device
+    pub disable_force_feedback: Option<bool>,
+    /// Length of the input reports the device actually sends, for devices
+    /// whose reports differ in length from the spec. Reports of this length
+    /// are padded or truncated to the expected length.
+    pub report_length: Option<usize>,
+}
+
+impl DeviceQuirk {
+    /// Returns true if the quirk applies to the device with the given vendor
+    /// id, product id and firmware version.
+    pub fn matches(&self, vendor_id: u16, product_id: u16, version: u16) -> bool {
+        if self.vendor_id != vendor_id || self.product_id != product_id {
+            return false;
+        }
+        self.firmware
+            .as_ref()
+            .is_none_or(|firmware| firmware.contains(&version))
+    }
+}
+
+/// An axis input with one or both directions inverted
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct InvertedAxis {
+    /// Axis capability to correct, e.g. the left stick
+    pub capability: CapabilityConfig,
+    /// If true, the horizontal direction is inverted
+    pub x: Option<bool>,
+    /// If true, the vertical direction is inverted
+    pub y: Option<bool>,
+}
+
+/// Returns the first quirk for the device with the given vendor id, product
+/// id and firmware version found in the given directories. Directories should
+/// be given in preference order.
+pub fn find_device_quirk(
+    paths: &[PathBuf],
+    vendor_id: u16,
+    product_id: u16,
+    version: u16,
+) -> Option<DeviceQuirk> {
+    for path in paths.iter() {
+        let files = match fs::read_dir(path) {
+            Ok(files) => files,
+            Err(e) => {
+                log::trace!("Failed to load directory {path:?}: {e}");
+                continue;
+            }
+        };
+        let mut files: Vec<_> = files.filter_map(|r| r.ok()).collect();
+        files.sort_by_key(|dir| dir.file_name());
+
+        for file in files {
+            let filename = file.file_name().to_string_lossy().to_string();
+
+            // Skip any non-yaml files
+            if !filename.ends_with(".yaml") {
+                continue;
+            }
+
+            let path = file.path().display().to_string();
+            let quirks = match DeviceQuirks::from_yaml_file(path.clone()) {
+                Ok(quirks) => quirks,
+                Err(e) => {
+                    log::warn!("Failed to parse quirks '{path}': {e}");
+                    continue;
+                }
+            };
+            let quirk = quirks
+                .quirks
+                .into_iter()
+                .find(|quirk| quirk.matches(vendor_id, product_id, version));
+            if let Some(quirk) = quirk {
+                log::debug!("Found device quirk for {vendor_id:04x}:{product_id:04x} in {path}");
+                return Some(quirk);
+            }
+        }
+    }
+
+    None
+}
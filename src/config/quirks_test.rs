@@ -0,0 +1,49 @@
+use std::{fs, path::PathBuf};
+
+use super::quirks::find_device_quirk;
+
+#[test]
+fn test_find_device_quirk() {
+    let dir = std::env::temp_dir().join(format!("inputplumber-quirks-{}", std::process::id()));
+    let user_dir = dir.join("user");
+    let system_dir = dir.join("system");
+    fs::create_dir_all(&user_dir).unwrap();
+    fs::create_dir_all(&system_dir).unwrap();
+
+    let system = r#"
+version: 1
+kind: DeviceQuirks
+name: System
+quirks:
+  - vendor_id: 0x1234
+    product_id: 0x5678
+    firmware: [0x0100]
+    disable_force_feedback: true
+  - vendor_id: 0x1234
+    product_id: 0x9abc
+    report_length: 64
+"#;
+    let user = r#"
+version: 1
+kind: DeviceQuirks
+name: User
+quirks:
+  - vendor_id: 0x1234
+    product_id: 0x9abc
+    report_length: 48
+"#;
+    fs::write(system_dir.join("50-system.yaml"), system).unwrap();
+    fs::write(user_dir.join("50-user.yaml"), user).unwrap();
+    let paths = vec![user_dir, system_dir, PathBuf::from("./does/not/exist")];
+
+    // Quirks limited to a firmware version only match that version
+    let quirk = find_device_quirk(&paths, 0x1234, 0x5678, 0x0100).unwrap();
+    assert_eq!(quirk.disable_force_feedback, Some(true));
+    assert!(find_device_quirk(&paths, 0x1234, 0x5678, 0x0200).is_none());
+
+    // Quirks in directories with a higher preference override other quirks
+    let quirk = find_device_quirk(&paths, 0x1234, 0x9abc, 0x0100).unwrap();
+    assert_eq!(quirk.report_length, Some(48));
+
+    fs::remove_dir_all(dir).unwrap();
+}
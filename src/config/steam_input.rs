@@ -0,0 +1,785 @@
+//! Module for converting between device profiles and Steam Input controller
+//! configurations
+//!
+//! Steam Input stores controller configurations in the text based Valve Data
+//! Format (VDF). Only a subset of them can be converted: the button bindings
+//! of the default action set that press controller buttons, keys, or mouse
+//! buttons, using full, long, or double press activators. Other bindings
+//! (e.g. mode shifts, action layers, or trackpad modes) are skipped.
+
+use std::collections::HashSet;
+
+use crate::config::{
+    ActivatorConfig, AxisCapability, CapabilityConfig, DeviceProfile, GamepadCapability, LoadError,
+    MouseCapability, ProfileMapping, TriggerCapability,
+};
+
+/// Deflection of an analog trigger that counts as clicked
+const TRIGGER_CLICK_DEADZONE: f64 = 0.9;
+
+/// Inputs of Steam Input sources that are gamepad buttons, as (source,
+/// input, button)
+const BUTTON_INPUTS: &[(&str, &str, &str)] = &[
+    ("button_diamond", "button_a", "South"),
+    ("button_diamond", "button_b", "East"),
+    ("button_diamond", "button_x", "West"),
+    ("button_diamond", "button_y", "North"),
+    ("dpad", "dpad_north", "DPadUp"),
+    ("dpad", "dpad_south", "DPadDown"),
+    ("dpad", "dpad_west", "DPadLeft"),
+    ("dpad", "dpad_east", "DPadRight"),
+    ("switch", "button_escape", "Start"),
+    ("switch", "button_menu", "Select"),
+    ("switch", "left_bumper", "LeftBumper"),
+    ("switch", "right_bumper", "RightBumper"),
+    ("switch", "button_back_left", "LeftPaddle1"),
+    ("switch", "button_back_right", "RightPaddle1"),
+    ("switch", "button_back_left_upper", "LeftPaddle2"),
+    ("switch", "button_back_right_upper", "RightPaddle2"),
+    ("joystick", "click", "LeftStick"),
+    ("left_joystick", "click", "LeftStick"),
+    ("right_joystick", "click", "RightStick"),
+];
+
+/// Inputs of Steam Input sources in "dpad" mode that are stick directions,
+/// as (input, direction)
+const STICK_DIRECTION_INPUTS: &[(&str, &str)] = &[
+    ("dpad_north", "up"),
+    ("dpad_south", "down"),
+    ("dpad_west", "left"),
+    ("dpad_east", "right"),
+];
+
+/// Steam Input xinput buttons and the gamepad button they press
+const XINPUT_BUTTONS: &[(&str, &str)] = &[
+    ("A", "South"),
+    ("B", "East"),
+    ("X", "West"),
+    ("Y", "North"),
+    ("SHOULDER_LEFT", "LeftBumper"),
+    ("SHOULDER_RIGHT", "RightBumper"),
+    ("START", "Start"),
+    ("SELECT", "Select"),
+    ("DPAD_UP", "DPadUp"),
+    ("DPAD_DOWN", "DPadDown"),
+    ("DPAD_LEFT", "DPadLeft"),
+    ("DPAD_RIGHT", "DPadRight"),
+    ("JOYSTICK_LEFT", "LeftStick"),
+    ("JOYSTICK_RIGHT", "RightStick"),
+];
+
+/// Steam Input xinput buttons and the gamepad trigger they pull
+const XINPUT_TRIGGERS: &[(&str, &str)] = &[
+    ("TRIGGER_LEFT", "LeftTrigger"),
+    ("TRIGGER_RIGHT", "RightTrigger"),
+];
+
+/// Steam Input key names and the key they press. Letters, digits, and
+/// function keys share their name with the key (e.g. "A" and "KeyA").
+const KEYS: &[(&str, &str)] = &[
+    ("SPACE", "KeySpace"),
+    ("RETURN", "KeyEnter"),
+    ("ESCAPE", "KeyEsc"),
+    ("TAB", "KeyTab"),
+    ("BACKSPACE", "KeyBackspace"),
+    ("DELETE", "KeyDelete"),
+    ("INSERT", "KeyInsert"),
+    ("HOME", "KeyHome"),
+    ("END", "KeyEnd"),
+    ("PAGE_UP", "KeyPageUp"),
+    ("PAGE_DOWN", "KeyPageDown"),
+    ("UP_ARROW", "KeyUp"),
+    ("DOWN_ARROW", "KeyDown"),
+    ("LEFT_ARROW", "KeyLeft"),
+    ("RIGHT_ARROW", "KeyRight"),
+    ("LEFT_SHIFT", "KeyLeftShift"),
+    ("RIGHT_SHIFT", "KeyRightShift"),
+    ("LEFT_CONTROL", "KeyLeftCtrl"),
+    ("RIGHT_CONTROL", "KeyRightCtrl"),
+    ("LEFT_ALT", "KeyLeftAlt"),
+    ("RIGHT_ALT", "KeyRightAlt"),
+    ("LWIN", "KeyLeftMeta"),
+    ("RWIN", "KeyRightMeta"),
+    ("CAPSLOCK", "KeyCapslock"),
+    ("PERIOD", "KeyDot"),
+    ("COMMA", "KeyComma"),
+    ("DASH", "KeyMinus"),
+    ("EQUALS", "KeyEqual"),
+    ("SEMICOLON", "KeySemicolon"),
+    ("SINGLE_QUOTE", "KeyApostrophe"),
+    ("FORWARD_SLASH", "KeySlash"),
+    ("BACKSLASH", "KeyBackslash"),
+    ("LEFT_BRACKET", "KeyLeftBrace"),
+    ("RIGHT_BRACKET", "KeyRightBrace"),
+    ("BACK_TICK", "KeyGrave"),
+];
+
+/// Steam Input mouse buttons and the mouse button they press
+const MOUSE_BUTTONS: &[(&str, &str, &str)] = &[
+    ("mouse_button", "LEFT", "Left"),
+    ("mouse_button", "RIGHT", "Right"),
+    ("mouse_button", "MIDDLE", "Middle"),
+    ("mouse_button", "BACK", "Side"),
+    ("mouse_button", "FORWARD", "Extra"),
+    ("mouse_wheel", "SCROLL_UP", "WheelUp"),
+    ("mouse_wheel", "SCROLL_DOWN", "WheelDown"),
+];
+
+/// Steam Input activators, the kind of activator they convert to, and the
+/// name of the setting with their time
+const ACTIVATORS: &[(&str, &str, &str)] = &[
+    ("Full_Press", "press", ""),
+    ("Long_Press", "long_press", "long_press_time"),
+    ("Double_Press", "double_press", "double_tap_time"),
+];
+
+/// Value of a key in a VDF document
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    String(String),
+    /// Keys in a VDF object can be repeated (e.g. "group"), so they are kept
+    /// as an ordered list
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Returns the value of the first entry with the given key
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the values of all entries with the given key
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a VdfValue> {
+        self.entries()
+            .iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the string of the first entry with the given key
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            VdfValue::String(value) => Some(value.as_str()),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    /// Returns the entries of the object, or nothing if this is a string
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::String(_) => &[],
+            VdfValue::Object(entries) => entries.as_slice(),
+        }
+    }
+}
+
+/// Parse the given VDF document into its root object
+pub fn parse_vdf(content: &str) -> Result<VdfValue, LoadError> {
+    let mut tokens = tokenize(content)?.into_iter();
+    let entries = parse_entries(&mut tokens, false)?;
+    Ok(VdfValue::Object(entries))
+}
+
+/// Token of a VDF document
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    String(String),
+    Open,
+    Close,
+}
+
+/// Split the given VDF document into tokens, skipping comments and
+/// conditionals (e.g. "[$WIN32]")
+fn tokenize(content: &str) -> Result<Vec<Token>, LoadError> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => break,
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(LoadError::InvalidVdf("Unterminated string".into())),
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '[' => {
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                let mut value = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::String(value));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse key value entries until the end of the current object
+fn parse_entries(
+    tokens: &mut std::vec::IntoIter<Token>,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>, LoadError> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next() {
+            Some(Token::String(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(token) => {
+                return Err(LoadError::InvalidVdf(format!(
+                    "Unexpected token: {token:?}"
+                )))
+            }
+            None => return Err(LoadError::InvalidVdf("Unterminated object".into())),
+        };
+        let value = match tokens.next() {
+            Some(Token::String(value)) => VdfValue::String(value),
+            Some(Token::Open) => VdfValue::Object(parse_entries(tokens, true)?),
+            _ => return Err(LoadError::InvalidVdf(format!("Missing value of '{key}'"))),
+        };
+        entries.push((key, value));
+    }
+}
+
+/// Write the given entries as a VDF document
+pub fn write_vdf(entries: &[(String, VdfValue)]) -> String {
+    let mut out = String::new();
+    write_entries(&mut out, entries, 0);
+    out
+}
+
+fn write_entries(out: &mut String, entries: &[(String, VdfValue)], depth: usize) {
+    let indent = "\t".repeat(depth);
+    for (key, value) in entries {
+        match value {
+            VdfValue::String(value) => {
+                out.push_str(&format!(
+                    "{indent}\"{}\"\t\t\"{}\"\n",
+                    escape(key),
+                    escape(value)
+                ));
+            }
+            VdfValue::Object(entries) => {
+                out.push_str(&format!("{indent}\"{}\"\n{indent}{{\n", escape(key)));
+                write_entries(out, entries, depth + 1);
+                out.push_str(&format!("{indent}}}\n"));
+            }
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Convert the given Steam Input controller configuration into a device
+/// profile. Bindings that cannot be converted are skipped.
+pub fn import_steam_input(content: &str) -> Result<DeviceProfile, LoadError> {
+    let root = parse_vdf(content)?;
+    let Some(config) = root.get("controller_mappings") else {
+        return Err(LoadError::InvalidVdf(
+            "Missing 'controller_mappings'".into(),
+        ));
+    };
+
+    // Only the bindings of sources that are active in the default action set
+    // are converted.
+    let presets: Vec<&VdfValue> = config.get_all("preset").collect();
+    let preset = presets
+        .iter()
+        .find(|preset| preset.get_str("id") == Some("0"))
+        .or(presets.first());
+    let Some(preset) = preset else {
+        return Err(LoadError::InvalidVdf("Missing 'preset'".into()));
+    };
+    let group_sources = preset
+        .get("group_source_bindings")
+        .map(|bindings| bindings.entries())
+        .unwrap_or_default();
+
+    let mut mappings = Vec::new();
+    let mut names = HashSet::new();
+    for (group_id, binding) in group_sources {
+        let VdfValue::String(binding) = binding else {
+            continue;
+        };
+        let parts: Vec<&str> = binding.split_whitespace().collect();
+        let [source, "active"] = parts.as_slice() else {
+            log::debug!("Skipping group source binding: {binding}");
+            continue;
+        };
+        let group = config
+            .get_all("group")
+            .find(|group| group.get_str("id") == Some(group_id.as_str()));
+        let Some(group) = group else {
+            log::warn!("Missing group {group_id} for source {source}");
+            continue;
+        };
+        let mode = group.get_str("mode").unwrap_or_default();
+        let inputs = group.get("inputs").map(|inputs| inputs.entries());
+        for (input, settings) in inputs.unwrap_or_default() {
+            let Some(source_event) = source_event(source, mode, input) else {
+                log::warn!(
+                    "Skipping unsupported input '{input}' of source '{source}' in mode '{mode}'"
+                );
+                continue;
+            };
+            let is_button = source_event
+                .gamepad
+                .as_ref()
+                .is_some_and(|gamepad| gamepad.button.is_some());
+            let activators = settings
+                .get("activators")
+                .map(|activators| activators.entries());
+            for (name, activator) in activators.unwrap_or_default() {
+                let Some((label, kind, setting)) = ACTIVATORS
+                    .iter()
+                    .find(|(steam_name, _, _)| *steam_name == name.as_str())
+                else {
+                    log::warn!("Skipping unsupported activator '{name}' of input '{input}'");
+                    continue;
+                };
+                if *kind != "press" && !is_button {
+                    log::warn!("Skipping activator '{name}' of non-button input '{input}'");
+                    continue;
+                }
+                let target_events: Vec<CapabilityConfig> = activator
+                    .get("bindings")
+                    .into_iter()
+                    .flat_map(|bindings| bindings.get_all("binding"))
+                    .filter_map(|binding| match binding {
+                        VdfValue::String(binding) => target_event(binding),
+                        VdfValue::Object(_) => None,
+                    })
+                    .collect();
+                if target_events.is_empty() {
+                    continue;
+                }
+                let time_ms = activator
+                    .get("settings")
+                    .and_then(|settings| settings.get_str(setting))
+                    .and_then(|time| time.parse().ok());
+                let activator = (*kind != "press").then(|| ActivatorConfig {
+                    kind: kind.to_string(),
+                    time_ms,
+                });
+
+                let label = label.replace('_', " ");
+                let name = unique_name(&mut names, format!("{source} {input} {label}"));
+                mappings.push(ProfileMapping {
+                    name,
+                    source_event: source_event.clone(),
+                    target_events,
+                    activator,
+                });
+            }
+        }
+    }
+
+    let mut target_devices = vec!["xb360".to_string()];
+    let uses = |f: fn(&CapabilityConfig) -> bool| {
+        mappings
+            .iter()
+            .any(|mapping| mapping.target_events.iter().any(f))
+    };
+    if uses(|target| target.mouse.is_some()) {
+        target_devices.push("mouse".to_string());
+    }
+    if uses(|target| target.keyboard.is_some()) {
+        target_devices.push("keyboard".to_string());
+    }
+
+    let name = config
+        .get_str("title")
+        .unwrap_or("Steam Input Config")
+        .to_string();
+    let description = config
+        .get_str("description")
+        .filter(|description| !description.is_empty())
+        .map(|description| description.to_string());
+    Ok(DeviceProfile {
+        version: 1,
+        kind: "DeviceProfile".to_string(),
+        name,
+        target_devices: Some(target_devices),
+        description,
+        games: None,
+        motion_filters: None,
+        motion_lean: None,
+        dpad_stick: None,
+        dpad_repeat: None,
+        layout: None,
+        stick_deadzones: None,
+        stick_gates: None,
+        axis_ranges: None,
+        intercept_activation: None,
+        layers: None,
+        mapping: mappings,
+    })
+}
+
+/// Convert the given device profile into a Steam Input controller
+/// configuration. Mappings that cannot be converted are skipped.
+pub fn export_steam_input(profile: &DeviceProfile) -> String {
+    // Inputs of each source, in the order the sources are first used
+    let mut sources: Vec<(&str, Vec<(String, VdfValue)>)> = Vec::new();
+    for mapping in profile.mapping.iter() {
+        let Some((source, input)) = steam_input(&mapping.source_event) else {
+            log::warn!(
+                "Skipping mapping '{}' with unsupported source",
+                mapping.name
+            );
+            continue;
+        };
+        let bindings: Vec<(String, VdfValue)> = mapping
+            .target_events
+            .iter()
+            .filter_map(steam_binding)
+            .map(|binding| ("binding".to_string(), VdfValue::String(binding)))
+            .collect();
+        if bindings.is_empty() {
+            log::warn!(
+                "Skipping mapping '{}' with unsupported targets",
+                mapping.name
+            );
+            continue;
+        }
+
+        let kind = mapping
+            .activator
+            .as_ref()
+            .map(|activator| activator.kind.as_str())
+            .unwrap_or("press");
+        let Some((name, _, setting)) = ACTIVATORS.iter().find(|(_, k, _)| *k == kind) else {
+            log::warn!(
+                "Skipping mapping '{}' with unsupported activator",
+                mapping.name
+            );
+            continue;
+        };
+        let mut activator = vec![("bindings".to_string(), VdfValue::Object(bindings))];
+        let time_ms = mapping
+            .activator
+            .as_ref()
+            .and_then(|activator| activator.time_ms);
+        if let Some(time_ms) = time_ms.filter(|_| !setting.is_empty()) {
+            let settings = vec![(setting.to_string(), VdfValue::String(time_ms.to_string()))];
+            activator.push(("settings".to_string(), VdfValue::Object(settings)));
+        }
+        let activator = (name.to_string(), VdfValue::Object(activator));
+
+        let index = match sources.iter().position(|(s, _)| *s == source) {
+            Some(index) => index,
+            None => {
+                sources.push((source, Vec::new()));
+                sources.len() - 1
+            }
+        };
+        let inputs = &mut sources[index].1;
+        match inputs.iter_mut().find(|(i, _)| i == input) {
+            Some((_, VdfValue::Object(settings))) => {
+                if let Some((_, VdfValue::Object(activators))) = settings.first_mut() {
+                    activators.push(activator);
+                }
+            }
+            _ => {
+                let activators = VdfValue::Object(vec![activator]);
+                let settings = VdfValue::Object(vec![("activators".to_string(), activators)]);
+                inputs.push((input.to_string(), settings));
+            }
+        }
+    }
+
+    let mut config = vec![
+        ("version".to_string(), VdfValue::String("3".to_string())),
+        ("title".to_string(), VdfValue::String(profile.name.clone())),
+        (
+            "description".to_string(),
+            VdfValue::String(profile.description.clone().unwrap_or_default()),
+        ),
+        (
+            "controller_type".to_string(),
+            VdfValue::String("controller_xbox360".to_string()),
+        ),
+    ];
+    let mut group_sources = Vec::new();
+    for (id, (source, inputs)) in sources.into_iter().enumerate() {
+        let has_directions = inputs.iter().any(|(input, _)| input.starts_with("dpad_"));
+        let mode = match source {
+            "button_diamond" => "four_buttons",
+            "dpad" => "dpad",
+            "switch" => "switches",
+            "left_trigger" | "right_trigger" => "trigger",
+            _ if has_directions => "dpad",
+            _ => "joystick_move",
+        };
+        let group = vec![
+            ("id".to_string(), VdfValue::String(id.to_string())),
+            ("mode".to_string(), VdfValue::String(mode.to_string())),
+            ("inputs".to_string(), VdfValue::Object(inputs)),
+        ];
+        config.push(("group".to_string(), VdfValue::Object(group)));
+        group_sources.push((id.to_string(), VdfValue::String(format!("{source} active"))));
+    }
+    let preset = vec![
+        ("id".to_string(), VdfValue::String("0".to_string())),
+        ("name".to_string(), VdfValue::String("Default".to_string())),
+        (
+            "group_source_bindings".to_string(),
+            VdfValue::Object(group_sources),
+        ),
+    ];
+    config.push(("preset".to_string(), VdfValue::Object(preset)));
+
+    write_vdf(&[("controller_mappings".to_string(), VdfValue::Object(config))])
+}
+
+/// Returns the source event of the given input of a Steam Input source
+fn source_event(source: &str, mode: &str, input: &str) -> Option<CapabilityConfig> {
+    if let Some((_, _, button)) = BUTTON_INPUTS
+        .iter()
+        .find(|(s, i, _)| *s == source && *i == input)
+    {
+        return Some(gamepad_button(button));
+    }
+
+    let gamepad = match (source, mode) {
+        ("joystick" | "left_joystick" | "right_joystick", "dpad") => {
+            let (_, direction) = STICK_DIRECTION_INPUTS.iter().find(|(i, _)| *i == input)?;
+            let name = if source == "right_joystick" {
+                "RightStick"
+            } else {
+                "LeftStick"
+            };
+            GamepadCapability {
+                axis: Some(AxisCapability {
+                    name: name.to_string(),
+                    direction: Some(direction.to_string()),
+                    deadzone: None,
+                    hysteresis: None,
+                    sector_angle: None,
+                    repeat: None,
+                    outer_ring: None,
+                }),
+                ..Default::default()
+            }
+        }
+        ("left_trigger" | "right_trigger", _) if input == "click" => {
+            let name = if source == "right_trigger" {
+                "RightTrigger"
+            } else {
+                "LeftTrigger"
+            };
+            GamepadCapability {
+                trigger: Some(TriggerCapability {
+                    name: name.to_string(),
+                    deadzone: Some(TRIGGER_CLICK_DEADZONE),
+                }),
+                ..Default::default()
+            }
+        }
+        _ => return None,
+    };
+    Some(CapabilityConfig {
+        gamepad: Some(gamepad),
+        ..Default::default()
+    })
+}
+
+/// Returns the Steam Input source and input of the given source event
+fn steam_input(config: &CapabilityConfig) -> Option<(&'static str, &'static str)> {
+    let gamepad = config.gamepad.as_ref()?;
+    if let Some(button) = gamepad.button.as_ref() {
+        let (source, input, _) = BUTTON_INPUTS
+            .iter()
+            .find(|(_, _, b)| *b == button.as_str())?;
+        return Some((*source, *input));
+    }
+    if let Some(axis) = gamepad.axis.as_ref() {
+        let source = match axis.name.as_str() {
+            "LeftStick" => "joystick",
+            "RightStick" => "right_joystick",
+            _ => return None,
+        };
+        let direction = axis.direction.as_ref()?;
+        let (input, _) = STICK_DIRECTION_INPUTS
+            .iter()
+            .find(|(_, d)| *d == direction.as_str())?;
+        return Some((source, *input));
+    }
+    if let Some(trigger) = gamepad.trigger.as_ref() {
+        let source = match trigger.name.as_str() {
+            "LeftTrigger" => "left_trigger",
+            "RightTrigger" => "right_trigger",
+            _ => return None,
+        };
+        return Some((source, "click"));
+    }
+    None
+}
+
+/// Returns the target event of the given Steam Input binding. E.g.
+/// "key_press SPACE, Jump"
+fn target_event(binding: &str) -> Option<CapabilityConfig> {
+    // Anything after the first comma is the label of the binding
+    let command = binding.split(',').next().unwrap_or_default();
+    let mut parts = command.split_whitespace();
+    let kind = parts.next()?;
+    let name = parts.next()?;
+    let target = match kind {
+        "xinput_button" => {
+            if let Some((_, button)) = XINPUT_BUTTONS.iter().find(|(n, _)| *n == name) {
+                gamepad_button(button)
+            } else {
+                let (_, trigger) = XINPUT_TRIGGERS.iter().find(|(n, _)| *n == name)?;
+                CapabilityConfig {
+                    gamepad: Some(GamepadCapability {
+                        trigger: Some(TriggerCapability {
+                            name: trigger.to_string(),
+                            deadzone: None,
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+            }
+        }
+        "key_press" => CapabilityConfig {
+            keyboard: Some(key_from_steam(name)?),
+            ..Default::default()
+        },
+        "mouse_button" | "mouse_wheel" => {
+            let (_, _, button) = MOUSE_BUTTONS
+                .iter()
+                .find(|(k, n, _)| *k == kind && *n == name)?;
+            CapabilityConfig {
+                mouse: Some(MouseCapability {
+                    button: Some(button.to_string()),
+                    motion: None,
+                }),
+                ..Default::default()
+            }
+        }
+        _ => {
+            log::warn!("Skipping unsupported binding: {binding}");
+            return None;
+        }
+    };
+    Some(target)
+}
+
+/// Returns the Steam Input binding of the given target event
+fn steam_binding(config: &CapabilityConfig) -> Option<String> {
+    if let Some(gamepad) = config.gamepad.as_ref() {
+        if let Some(button) = gamepad.button.as_ref() {
+            let (name, _) = XINPUT_BUTTONS.iter().find(|(_, b)| *b == button.as_str())?;
+            return Some(format!("xinput_button {name}"));
+        }
+        let trigger = gamepad.trigger.as_ref()?;
+        let (name, _) = XINPUT_TRIGGERS.iter().find(|(_, t)| *t == trigger.name)?;
+        return Some(format!("xinput_button {name}"));
+    }
+    if let Some(key) = config.keyboard.as_ref() {
+        return Some(format!("key_press {}", key_to_steam(key)?));
+    }
+    if let Some(button) = config.mouse.as_ref()?.button.as_ref() {
+        let (kind, name, _) = MOUSE_BUTTONS
+            .iter()
+            .find(|(_, _, b)| *b == button.as_str())?;
+        return Some(format!("{kind} {name}"));
+    }
+    None
+}
+
+/// Returns the key of the given Steam Input key name
+fn key_from_steam(name: &str) -> Option<String> {
+    if let Some((_, key)) = KEYS.iter().find(|(n, _)| *n == name) {
+        return Some(key.to_string());
+    }
+    if is_named_like_key(name) {
+        return Some(format!("Key{name}"));
+    }
+    log::warn!("Skipping unsupported key: {name}");
+    None
+}
+
+/// Returns the Steam Input key name of the given key
+fn key_to_steam(key: &str) -> Option<String> {
+    if let Some((name, _)) = KEYS.iter().find(|(_, k)| *k == key) {
+        return Some(name.to_string());
+    }
+    let name = key.strip_prefix("Key")?;
+    is_named_like_key(name).then(|| name.to_string())
+}
+
+/// Returns true if the given Steam Input key name is the name of the key
+/// without its "Key" prefix, like letters, digits, and function keys
+fn is_named_like_key(name: &str) -> bool {
+    let is_letter_or_digit = name.len() == 1
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    let is_function_key = name
+        .strip_prefix('F')
+        .and_then(|number| number.parse::<u8>().ok())
+        .is_some_and(|number| (1..=24).contains(&number));
+    is_letter_or_digit || is_function_key
+}
+
+fn gamepad_button(button: &str) -> CapabilityConfig {
+    CapabilityConfig {
+        gamepad: Some(GamepadCapability {
+            button: Some(button.to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Returns the given mapping name, or the name with a number appended if it
+/// is already used. Mapping names are used to track mapping state, so they
+/// need to be unique.
+fn unique_name(names: &mut HashSet<String>, name: String) -> String {
+    let mut unique = name.clone();
+    let mut count = 1;
+    while names.contains(&unique) {
+        count += 1;
+        unique = format!("{name} ({count})");
+    }
+    names.insert(unique.clone());
+    unique
+}
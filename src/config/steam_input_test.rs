@@ -0,0 +1,232 @@
+use crate::config::{steam_input::VdfValue, LoadError};
+
+use super::steam_input::{export_steam_input, import_steam_input, parse_vdf};
+
+const CONFIG: &str = r#"
+"controller_mappings"
+{
+	"version"		"3"
+	"title"		"Community Layout"
+	"description"		"Jump on A, \"dodge\" on a long press"
+	"controller_type"		"controller_xbox360"
+	"group"
+	{
+		"id"		"0"
+		"mode"		"four_buttons"
+		"inputs"
+		{
+			"button_a"
+			{
+				"activators"
+				{
+					"Full_Press"
+					{
+						"bindings"
+						{
+							"binding"		"key_press SPACE, Jump, "
+						}
+					}
+					"Long_Press"
+					{
+						"bindings"
+						{
+							"binding"		"key_press LEFT_CONTROL, Dodge, "
+							"binding"		"xinput_button B"
+						}
+						"settings"
+						{
+							"long_press_time"		"400"
+						}
+					}
+					"Start_Press"
+					{
+						"bindings"
+						{
+							"binding"		"key_press E"
+						}
+					}
+				}
+			}
+			"button_b"
+			{
+				"activators"
+				{
+					"Double_Press"
+					{
+						"bindings"
+						{
+							"binding"		"mouse_button RIGHT"
+						}
+					}
+				}
+			}
+		}
+	}
+	"group"
+	{
+		"id"		"1"
+		"mode"		"dpad"
+		"inputs"
+		{
+			"dpad_north"
+			{
+				"activators"
+				{
+					"Full_Press"
+					{
+						"bindings"
+						{
+							"binding"		"key_press W"
+						}
+					}
+				}
+			}
+		}
+	}
+	"group"
+	{
+		"id"		"2"
+		"mode"		"trigger"
+		"inputs"
+		{
+			"click"
+			{
+				"activators"
+				{
+					"Full_Press"
+					{
+						"bindings"
+						{
+							"binding"		"mouse_button LEFT"
+						}
+					}
+				}
+			}
+		}
+	}
+	"group"
+	{
+		"id"		"3"
+		"mode"		"switches"
+		"inputs"
+		{
+			"button_escape"
+			{
+				"activators"
+				{
+					"Full_Press"
+					{
+						"bindings"
+						{
+							"binding"		"controller_action SHOW_KEYBOARD"
+						}
+					}
+				}
+			}
+		}
+	}
+	"preset"
+	{
+		"id"		"0"
+		"name"		"Default"
+		"group_source_bindings"
+		{
+			"0"		"button_diamond active"
+			"1"		"joystick active"
+			"2"		"right_trigger active"
+			"3"		"switch inactive"
+		}
+	}
+}
+"#;
+
+#[test]
+fn test_parse_vdf() {
+    let root = parse_vdf("// comment\n\"a\" { \"b\" \"1\" b \"2\" \"c\" [$WIN32] { } }").unwrap();
+    let a = root.get("a").unwrap();
+    assert_eq!(a.get_str("b"), Some("1"));
+    assert_eq!(a.get_all("b").count(), 2);
+    assert_eq!(a.get("c"), Some(&VdfValue::Object(vec![])));
+
+    let result = parse_vdf("\"a\" { \"b\" \"1\"");
+    assert!(matches!(result, Err(LoadError::InvalidVdf(_))));
+}
+
+#[test]
+fn test_import_steam_input() {
+    let profile = import_steam_input(CONFIG).unwrap();
+    assert_eq!(profile.name, "Community Layout");
+    assert_eq!(
+        profile.description.as_deref(),
+        Some("Jump on A, \"dodge\" on a long press")
+    );
+    assert_eq!(
+        profile.target_devices,
+        Some(vec![
+            "xb360".to_string(),
+            "mouse".to_string(),
+            "keyboard".to_string()
+        ])
+    );
+
+    // Unsupported activators, bindings, and inactive sources are skipped
+    let names: Vec<&str> = profile.mapping.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "button_diamond button_a Full Press",
+            "button_diamond button_a Long Press",
+            "button_diamond button_b Double Press",
+            "joystick dpad_north Full Press",
+            "right_trigger click Full Press",
+        ]
+    );
+
+    let jump = &profile.mapping[0];
+    let source = jump.source_event.gamepad.as_ref().unwrap();
+    assert_eq!(source.button.as_deref(), Some("South"));
+    assert_eq!(jump.target_events[0].keyboard.as_deref(), Some("KeySpace"));
+    assert!(jump.activator.is_none());
+
+    let dodge = &profile.mapping[1];
+    let activator = dodge.activator.as_ref().unwrap();
+    assert_eq!(activator.kind, "long_press");
+    assert_eq!(activator.time_ms, Some(400));
+    assert_eq!(dodge.target_events.len(), 2);
+    assert_eq!(
+        dodge.target_events[0].keyboard.as_deref(),
+        Some("KeyLeftCtrl")
+    );
+    let target = dodge.target_events[1].gamepad.as_ref().unwrap();
+    assert_eq!(target.button.as_deref(), Some("East"));
+
+    let forward = &profile.mapping[3];
+    let axis = forward.source_event.gamepad.as_ref().unwrap().axis.as_ref();
+    assert_eq!(axis.unwrap().name, "LeftStick");
+    assert_eq!(axis.unwrap().direction.as_deref(), Some("up"));
+    assert_eq!(forward.target_events[0].keyboard.as_deref(), Some("KeyW"));
+}
+
+#[test]
+fn test_import_missing_mappings() {
+    let result = import_steam_input("\"something_else\" { }");
+    assert!(matches!(result, Err(LoadError::InvalidVdf(_))));
+}
+
+#[test]
+fn test_export_steam_input() {
+    let profile = import_steam_input(CONFIG).unwrap();
+    let exported = export_steam_input(&profile);
+
+    // Converting the export back results in the same profile
+    let reimported = import_steam_input(exported.as_str()).unwrap();
+    assert_eq!(reimported.name, profile.name);
+    assert_eq!(reimported.description, profile.description);
+    assert_eq!(reimported.mapping.len(), profile.mapping.len());
+    for (a, b) in reimported.mapping.iter().zip(profile.mapping.iter()) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.source_event, b.source_event);
+        assert_eq!(a.target_events, b.target_events);
+        assert_eq!(a.activator, b.activator);
+    }
+}
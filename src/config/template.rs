@@ -0,0 +1,148 @@
+//! Module for resolving variables in configuration templates
+//!
+//! Configuration files can reference variables using the `${NAME}` syntax.
+//! Variables are resolved (in increasing order of precedence) from defaults
+//! declared in the `variables` section of the config itself, system DMI data,
+//! and variable override files that match the current system.
+
+use std::{collections::HashMap, fs};
+
+use serde::Deserialize;
+
+use crate::{
+    config::{find_valid_matches, path::get_variables_paths, LoadError, Match},
+    dmi::{data::DMIData, get_cpu_info, get_dmi_data},
+};
+
+/// Defines variables that should be used when rendering configuration templates
+/// on systems that match.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TemplateVariables {
+    pub version: u32,
+    pub kind: String,
+    pub name: String,
+    pub matches: Vec<Match>,
+    pub variables: HashMap<String, String>,
+}
+
+impl TemplateVariables {
+    /// Load [TemplateVariables] from the given YAML file
+    pub fn from_yaml_file(path: String) -> Result<TemplateVariables, LoadError> {
+        let file = fs::File::open(path)?;
+        let variables: TemplateVariables = serde_yaml::from_reader(file)?;
+        Ok(variables)
+    }
+}
+
+/// Only used to read the default variables declared in a config template
+#[derive(Debug, Deserialize, Default)]
+struct TemplateDefaults {
+    variables: Option<HashMap<String, String>>,
+}
+
+/// Returns true if the given configuration content references any variables
+pub fn is_template(content: &str) -> bool {
+    content.contains("${")
+}
+
+/// Render the given configuration template by resolving its variables from
+/// its defaults, DMI data, and any matching variable override files.
+pub fn render_template(content: &str) -> Result<String, LoadError> {
+    let defaults: TemplateDefaults = serde_yaml::from_str(content)?;
+    let mut variables = defaults.variables.unwrap_or_default();
+
+    let dmi_data = get_dmi_data();
+    variables.extend(dmi_variables(&dmi_data));
+    variables.extend(load_override_variables(&dmi_data));
+
+    render(content, &variables)
+}
+
+/// Replace all `${NAME}` references in the given content with the value of
+/// the variable with that name.
+pub fn render(content: &str, variables: &HashMap<String, String>) -> Result<String, LoadError> {
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find("${") {
+        rendered.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(LoadError::UndefinedVariable(rest[start..].to_string()));
+        };
+        let name = &rest[start + 2..start + end];
+        let Some(value) = variables.get(name) else {
+            return Err(LoadError::UndefinedVariable(name.to_string()));
+        };
+        rendered.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+/// Returns variables for the given DMI data (e.g. "DMI_PRODUCT_NAME")
+fn dmi_variables(data: &DMIData) -> HashMap<String, String> {
+    HashMap::from([
+        ("DMI_BIOS_VENDOR".to_string(), data.bios_vendor.clone()),
+        ("DMI_BIOS_VERSION".to_string(), data.bios_version.clone()),
+        ("DMI_BOARD_NAME".to_string(), data.board_name.clone()),
+        ("DMI_BOARD_VENDOR".to_string(), data.board_vendor.clone()),
+        (
+            "DMI_PRODUCT_FAMILY".to_string(),
+            data.product_family.clone(),
+        ),
+        ("DMI_PRODUCT_NAME".to_string(), data.product_name.clone()),
+        ("DMI_PRODUCT_SKU".to_string(), data.product_sku.clone()),
+        (
+            "DMI_PRODUCT_VERSION".to_string(),
+            data.product_version.clone(),
+        ),
+        ("DMI_SYS_VENDOR".to_string(), data.sys_vendor.clone()),
+    ])
+}
+
+/// Returns the variables from all variable override files that match the
+/// current system. Files in directories with a higher preference take
+/// precedence.
+fn load_override_variables(data: &DMIData) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let cpu_info = match get_cpu_info() {
+        Ok(info) => info,
+        Err(e) => {
+            log::warn!("Unable to read CPU info to match template variables: {e:?}");
+            return variables;
+        }
+    };
+
+    // Load paths in reverse so more preferred directories are applied last
+    for path in get_variables_paths().iter().rev() {
+        let Ok(files) = fs::read_dir(path) else {
+            continue;
+        };
+        let mut files: Vec<_> = files.filter_map(|entry| entry.ok()).collect();
+        files.sort_by_key(|entry| entry.file_name());
+
+        for file in files {
+            let file_path = file.path();
+            if file_path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+                continue;
+            }
+            let overrides = match TemplateVariables::from_yaml_file(file_path.display().to_string())
+            {
+                Ok(overrides) => overrides,
+                Err(e) => {
+                    log::warn!("Failed to parse template variables '{file_path:?}': {e}");
+                    continue;
+                }
+            };
+            if find_valid_matches(&overrides.matches, data, &cpu_info).is_none() {
+                continue;
+            }
+            log::debug!("Using template variables from: {}", overrides.name);
+            variables.extend(overrides.variables);
+        }
+    }
+
+    variables
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use crate::config::LoadError;
+
+use super::template::{is_template, render};
+
+#[test]
+fn test_render() {
+    let variables = HashMap::from([
+        ("PRODUCT".to_string(), "Handheld".to_string()),
+        (
+            "IMU_MOUNT_MATRIX".to_string(),
+            "{x: [1, 0, 0], y: [0, -1, 0], z: [0, 0, 1]}".to_string(),
+        ),
+    ]);
+    let content = "name: ${PRODUCT} Gamepad\nmount_matrix: ${IMU_MOUNT_MATRIX}\n";
+    assert!(is_template(content));
+
+    let rendered = render(content, &variables).unwrap();
+    assert_eq!(
+        rendered,
+        "name: Handheld Gamepad\nmount_matrix: {x: [1, 0, 0], y: [0, -1, 0], z: [0, 0, 1]}\n"
+    );
+    assert!(!is_template(rendered.as_str()));
+}
+
+#[test]
+fn test_render_undefined_variable() {
+    let result = render("name: ${MISSING}", &HashMap::new());
+    assert!(matches!(result, Err(LoadError::UndefinedVariable(name)) if name == "MISSING"));
+}
@@ -1,15 +1,24 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, time::Duration};
 
-use zbus::{
-    fdo,
-    zvariant::{self, Value},
-};
+use zbus::{fdo, message::Header, object_server::SignalContext, zvariant, Connection};
 use zbus_macros::interface;
 
-use crate::input::{
-    capability::{Capability, Gamepad, Mouse},
-    composite_device::{client::CompositeDeviceClient, InterceptMode},
-    event::{native::NativeEvent, value::InputValue},
+use crate::{
+    dbus::{
+        interface::{capability_strings, input_value_from_variant},
+        polkit::{
+            check_authorization, ACTION_CONFIGURE_DEVICES, ACTION_INJECT_INPUT, ACTION_INTERCEPT,
+            ACTION_LOAD_PROFILE, ACTION_MANAGE_DEVICES,
+        },
+    },
+    input::{
+        capability::Capability,
+        composite_device::{
+            client::CompositeDeviceClient, dpad_stick::DPadStickMode, radial_menu::RadialMenuStick,
+            InterceptMode,
+        },
+        event::{native::NativeEvent, value::InputValue},
+    },
 };
 
 /// The [CompositeDeviceInterface] provides a DBus interface that can be exposed for managing
@@ -51,8 +60,25 @@ impl CompositeDeviceInterface {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Returns the union of capabilities of all source devices. These are the
+    /// capabilities that can be used as source events in a device profile.
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .composite_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
     /// Stop the composite device and all target devices
-    async fn stop(&self) -> fdo::Result<()> {
+    async fn stop(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         self.composite_device
             .stop()
             .await
@@ -60,7 +86,13 @@ impl CompositeDeviceInterface {
     }
 
     /// Load the device profile from the given path
-    async fn load_profile_path(&self, path: String) -> fdo::Result<()> {
+    async fn load_profile_path(
+        &self,
+        path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_LOAD_PROFILE).await?;
         self.composite_device
             .load_profile_path(path)
             .await
@@ -68,18 +100,192 @@ impl CompositeDeviceInterface {
     }
 
     /// Load the device profile from the given YAML/JSON string
-    async fn load_profile_from_yaml(&self, profile: String) -> fdo::Result<()> {
+    async fn load_profile_from_yaml(
+        &self,
+        profile: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_LOAD_PROFILE).await?;
         self.composite_device
             .load_profile_from_yaml(profile)
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    /// Stack the device profile at the given path on top of the loaded
+    /// profile. Mappings in the overlay replace the mappings of the same
+    /// source inputs in the loaded profile, so temporary toggles (e.g. "gyro
+    /// on") do not require duplicating the whole profile. Pass an empty path
+    /// to remove the overlay.
+    async fn set_profile_overlay(
+        &self,
+        path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_LOAD_PROFILE).await?;
+        self.composite_device
+            .set_profile_overlay(path)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable sending a copy of the events written to target
+    /// devices to the tee sink with the given name, e.g. to stop driving a
+    /// second machine without affecting the local game.
+    async fn set_tee_enabled(
+        &self,
+        name: String,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.composite_device
+            .set_tee_enabled(name, enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the most recent input events received from source devices,
+    /// from oldest to newest. Useful for debugging input issues after they
+    /// happen without needing trace logging enabled.
+    async fn dump_event_history(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<Vec<String>> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT).await?;
+        self.composite_device
+            .get_event_history()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the effective configuration of the composite device as a
+    /// single YAML document. The document combines the device config, its
+    /// capability map, the loaded device profile, and any settings changed at
+    /// runtime. Useful for debugging and for sharing working configs.
+    async fn export_active_config(&self) -> fdo::Result<String> {
+        self.composite_device
+            .export_active_config()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Temporarily release the hidraw source device with the given path (e.g.
+    /// "/dev/hidraw0") so vendor tools like firmware updaters can talk to the
+    /// hardware. The source device is re-attached automatically after the
+    /// given timeout in seconds.
+    async fn enter_maintenance_mode(
+        &self,
+        source_device_path: String,
+        timeout: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        if timeout == 0 {
+            return Err(fdo::Error::InvalidArgs(
+                "Timeout must be greater than zero".to_string(),
+            ));
+        }
+        let timeout = Duration::from_secs(timeout as u64);
+        self.composite_device
+            .enter_maintenance_mode(source_device_path, timeout)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Re-attach the source device with the given path that was released with
+    /// EnterMaintenanceMode before its timeout expires.
+    async fn exit_maintenance_mode(
+        &self,
+        source_device_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.composite_device
+            .exit_maintenance_mode(source_device_path)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Attach the source device with the given path (e.g. "/dev/hidraw0") to
+    /// this composite device. If the source device is used by another
+    /// composite device, it is moved from that device.
+    async fn attach_source_device(
+        &self,
+        source_device_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.composite_device
+            .attach_source_device_path(source_device_path)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Detach the source device with the given path (e.g. "/dev/input/event3")
+    /// from this composite device. The source device is released and stays
+    /// unmanaged until it is attached again or reconnected.
+    async fn remove_source_device(
+        &self,
+        source_device_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.composite_device
+            .remove_source_device_path(source_device_path)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Temporarily intercept input and wait for the next significant physical
+    /// input from any source device, like a button press or a stick pushed
+    /// past half way. Returns the capability string and value of the input,
+    /// or fails if no input is received within the given timeout in seconds.
+    #[zbus(out_args("capability", "value"))]
+    async fn capture_next_input(
+        &self,
+        timeout: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<(String, String)> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT).await?;
+        if timeout == 0 {
+            return Err(fdo::Error::InvalidArgs(
+                "Timeout must be greater than zero".to_string(),
+            ));
+        }
+        let timeout = Duration::from_secs(timeout as u64);
+        let captured = self
+            .composite_device
+            .capture_next_input(timeout)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        let Some(captured) = captured else {
+            return Err(fdo::Error::TimedOut("No input was captured".to_string()));
+        };
+
+        Ok((captured.capability, captured.value))
+    }
+
     /// Set the target input device types the composite device should emulate,
     /// such as ["gamepad", "mouse", "keyboard"]. This method will stop all
     /// current virtual devices for the composite device and create and attach
     /// new target devices.
-    async fn set_target_devices(&self, target_device_types: Vec<String>) -> fdo::Result<()> {
+    async fn set_target_devices(
+        &self,
+        target_device_types: Vec<String>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES).await?;
         self.composite_device
             .set_target_devices(target_device_types)
             .await
@@ -87,66 +293,40 @@ impl CompositeDeviceInterface {
     }
 
     /// Directly write to the composite device's target devices with the given event
-    fn send_event(&self, event: String, value: zvariant::Value<'_>) -> fdo::Result<()> {
+    async fn send_event(
+        &self,
+        event: String,
+        value: zvariant::Value<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INJECT_INPUT).await?;
         let cap = Capability::from_str(event.as_str()).map_err(|_| {
             fdo::Error::Failed(format!(
                 "Failed to parse event string {event} into capability."
             ))
         })?;
 
-        let val = match value {
-            zvariant::Value::Bool(v) => InputValue::Bool(v),
-            zvariant::Value::F64(v) => InputValue::Float(v),
-            zvariant::Value::Array(v) => match v.len() {
-                2 => {
-                    let x_val = v.first().unwrap();
-                    let y_val: &Value = v.get(1).unwrap().unwrap();
-                    let x = f64::try_from(x_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
-                    })?;
-                    let y = f64::try_from(y_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
-                    })?;
-                    InputValue::Vector2 {
-                        x: Some(x),
-                        y: Some(y),
-                    }
-                }
-                3 => {
-                    let x_val = v.first().unwrap();
-                    let y_val: &Value = v.get(1).unwrap().unwrap();
-                    let z_val: &Value = v.get(2).unwrap().unwrap();
-                    let x = f64::try_from(x_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse x value into float.".to_string())
-                    })?;
-                    let y = f64::try_from(y_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse y value into float.".to_string())
-                    })?;
-                    let z = f64::try_from(z_val).map_err(|_| {
-                        fdo::Error::Failed("Failed to parse z value into float.".to_string())
-                    })?;
-                    InputValue::Vector3 {
-                        x: Some(x),
-                        y: Some(y),
-                        z: Some(z),
-                    }
-                }
-                _ => InputValue::None,
-            },
-            _ => InputValue::None,
-        };
+        let val = input_value_from_variant(value)?;
 
         let event = NativeEvent::new(cap, val);
 
         self.composite_device
-            .blocking_write_send_event(event)
+            .write_send_event(event)
+            .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
         Ok(())
     }
 
     /// Directly write to the composite device's target devices with the given button event list
-    async fn send_button_chord(&self, mut events: Vec<String>) -> fdo::Result<()> {
+    async fn send_button_chord(
+        &self,
+        mut events: Vec<String>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INJECT_INPUT).await?;
         // Store built native events to send in a command to the CompositeDevice
         let mut chord: Vec<NativeEvent> = Vec::new();
 
@@ -190,11 +370,56 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// Inject the given event as if it came from a source device. Unlike
+    /// SendEvent, the event is translated by the device profile and goes
+    /// through intercept mode like physical input, so scripts and tests can
+    /// synthesize inputs.
+    async fn inject_event(
+        &self,
+        capability: String,
+        value: zvariant::Value<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INJECT_INPUT).await?;
+        let cap = Capability::from_str(capability.as_str()).map_err(|_| {
+            fdo::Error::InvalidArgs(format!(
+                "Failed to parse event string {capability} into capability."
+            ))
+        })?;
+        let val = input_value_from_variant(value)?;
+        let event = NativeEvent::new(cap, val);
+
+        self.composite_device
+            .handle_event(event)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Returns the chord of capabilities that activates intercept mode while
+    /// in "PASS" mode and the capability emitted when it is activated.
+    #[zbus(out_args("activation_events", "target_event"))]
+    async fn get_intercept_activation(&self) -> fdo::Result<(Vec<String>, String)> {
+        let (activation_caps, target_cap) = self
+            .composite_device
+            .get_intercept_activation()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok((capability_strings(activation_caps), target_cap.to_string()))
+    }
+
+    /// Set the chord of capabilities that activates intercept mode while in
+    /// "PASS" mode and the capability to emit when it is activated. Fails if
+    /// the composite device does not implement all of the activation events.
     async fn set_intercept_activation(
         &self,
         activation_events: Vec<String>,
         target_event: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
     ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT).await?;
         let mut activation_caps: Vec<Capability> = Vec::new();
 
         // Iterate in the given order for press events
@@ -240,27 +465,7 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
-
-        Ok(capability_strings)
+        Ok(capability_strings(capabilities))
     }
 
     /// List of capabilities that all target devices implement
@@ -272,27 +477,7 @@ impl CompositeDeviceInterface {
             .await
             .map_err(|e| fdo::Error::Failed(e.to_string()))?;
 
-        let mut capability_strings = Vec::new();
-        for cap in capabilities {
-            let str = match cap {
-                Capability::Gamepad(gamepad) => match gamepad {
-                    Gamepad::Button(button) => format!("Gamepad:Button:{}", button),
-                    Gamepad::Axis(axis) => format!("Gamepad:Axis:{}", axis),
-                    Gamepad::Trigger(trigger) => format!("Gamepad:Trigger:{}", trigger),
-                    Gamepad::Accelerometer => "Gamepad:Accelerometer".to_string(),
-                    Gamepad::Gyro => "Gamepad:Gyro".to_string(),
-                },
-                Capability::Mouse(mouse) => match mouse {
-                    Mouse::Motion => "Mouse:Motion".to_string(),
-                    Mouse::Button(button) => format!("Mouse:Button:{}", button),
-                },
-                Capability::Keyboard(key) => format!("Keyboard:{}", key),
-                _ => cap.to_string(),
-            };
-            capability_strings.push(str);
-        }
-
-        Ok(capability_strings)
+        Ok(capability_strings(capabilities))
     }
 
     /// List of source devices that this composite device is processing inputs for
@@ -325,7 +510,15 @@ impl CompositeDeviceInterface {
     }
 
     #[zbus(property)]
-    async fn set_intercept_mode(&self, mode: u32) -> zbus::Result<()> {
+    async fn set_intercept_mode(
+        &self,
+        mode: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         let mode = match mode {
             0 => InterceptMode::None,
             1 => InterceptMode::Pass,
@@ -340,6 +533,115 @@ impl CompositeDeviceInterface {
         Ok(())
     }
 
+    /// The mode used to translate between the DPad and the left stick. Can
+    /// be "none", "dpad_to_stick", or "stick_to_dpad".
+    #[zbus(property)]
+    async fn dpad_stick_mode(&self) -> fdo::Result<String> {
+        let mode = self
+            .composite_device
+            .get_dpad_stick_mode()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(mode.to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_dpad_stick_mode(
+        &self,
+        mode: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_LOAD_PROFILE)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
+        let Ok(mode) = DPadStickMode::from_str(mode.as_str()) else {
+            return Err(zbus::Error::FDO(Box::new(fdo::Error::InvalidArgs(
+                format!("Invalid DPad stick mode: {mode}"),
+            ))));
+        };
+        self.composite_device
+            .set_dpad_stick_mode(mode)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// The stick used to select sectors of an on-screen radial menu. Can be
+    /// "none", "left_stick", or "right_stick". While a stick is set, its
+    /// events are not written to the target devices and the
+    /// RadialSectorSelected and RadialSectorConfirmed signals are emitted
+    /// instead.
+    #[zbus(property)]
+    async fn radial_menu_stick(&self) -> fdo::Result<String> {
+        let stick = self
+            .composite_device
+            .get_radial_menu_stick()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(stick.to_string())
+    }
+
+    #[zbus(property)]
+    async fn set_radial_menu_stick(
+        &self,
+        stick: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
+        let Ok(stick) = RadialMenuStick::from_str(stick.as_str()) else {
+            return Err(zbus::Error::FDO(Box::new(fdo::Error::InvalidArgs(
+                format!("Invalid radial menu stick: {stick}"),
+            ))));
+        };
+        self.composite_device
+            .set_radial_menu_stick(stick)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// The player index shown on the player indicators of the source devices,
+    /// starting at 1. A value of 0 means no player index is assigned.
+    #[zbus(property)]
+    async fn player_index(&self) -> fdo::Result<u32> {
+        let index = self
+            .composite_device
+            .get_player_index()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(index.unwrap_or_default() as u32)
+    }
+
+    #[zbus(property)]
+    async fn set_player_index(
+        &self,
+        index: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
+        let Ok(index) = u8::try_from(index) else {
+            return Err(zbus::Error::FDO(Box::new(fdo::Error::InvalidArgs(
+                format!("Invalid player index: {index}"),
+            ))));
+        };
+        let index = (index > 0).then_some(index);
+        self.composite_device
+            .set_player_index(index)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
     /// Target devices that this [CompositeDevice] is managing
     #[zbus(property)]
     async fn target_devices(&self) -> fdo::Result<Vec<String>> {
@@ -352,6 +654,155 @@ impl CompositeDeviceInterface {
         Ok(paths)
     }
 
+    /// Steam app id of the game focused in gamescope, or 0 if no game is
+    /// focused or no gamescope session is running
+    #[zbus(property)]
+    async fn focused_app_id(&self) -> fdo::Result<u32> {
+        let app_id = self
+            .composite_device
+            .get_focused_app()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(app_id.unwrap_or_default())
+    }
+
+    /// Whether or not the composite device is idle because no input has been
+    /// received from source devices within the configured idle timeout
+    #[zbus(property)]
+    async fn idle(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_idle()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Latest normalized values of the sticks and triggers of the source
+    /// devices, keyed by capability (e.g. "Gamepad:Axis:LeftStick" => [x, y]).
+    /// Changes are signaled at most 20 times per second, so settings UIs can
+    /// draw calibration widgets without subscribing to every input event.
+    #[zbus(property)]
+    async fn live_values(&self) -> fdo::Result<HashMap<String, Vec<f64>>> {
+        self.composite_device
+            .get_live_values()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Whether or not events written to target devices are emitted as
+    /// "InputEvent" signals with decoded capability names and values
+    #[zbus(property)]
+    async fn inspector_enabled(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_inspector_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_inspector_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
+        self.composite_device
+            .set_inspector_enabled(enabled)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Whether or not a mapping dry run is active. While active, events are
+    /// not written to target devices and are emitted as "DryRunEvent"
+    /// signals instead.
+    #[zbus(property)]
+    async fn dry_run_enabled(&self) -> fdo::Result<bool> {
+        self.composite_device
+            .get_dry_run_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    #[zbus(property)]
+    async fn set_dry_run_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INTERCEPT)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
+        self.composite_device
+            .set_dry_run_enabled(enabled)
+            .await
+            .map_err(|err| zbus::Error::Failure(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Names of the tee sinks that receive a copy of the events written to
+    /// target devices, and whether each of them is enabled
+    #[zbus(property)]
+    async fn tee_sinks(&self) -> fdo::Result<HashMap<String, bool>> {
+        self.composite_device
+            .get_tee_sinks()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Backend used to emulate keyboard and mouse input. This is "xtest" if
+    /// uinput is unavailable (e.g. in a sandbox) and input is injected into
+    /// the X server instead, otherwise "uinput".
+    #[zbus(property)]
+    async fn input_backend(&self) -> fdo::Result<String> {
+        let backend = self
+            .composite_device
+            .get_input_backend()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(backend.to_string())
+    }
+
+    /// Number of events that could not be sent right away because an event
+    /// channel between source, composite, and target devices was full
+    #[zbus(property)]
+    async fn events_deferred(&self) -> fdo::Result<u64> {
+        Ok(self.composite_device.overload_stats().deferred())
+    }
+
+    /// Number of axis events that were merged into a waiting event of the
+    /// same axis because an event channel was full
+    #[zbus(property)]
+    async fn events_coalesced(&self) -> fdo::Result<u64> {
+        Ok(self.composite_device.overload_stats().coalesced())
+    }
+
+    /// Number of axis events that were dropped because too many events were
+    /// waiting for a full event channel. Button events are never dropped.
+    #[zbus(property)]
+    async fn events_dropped(&self) -> fdo::Result<u64> {
+        Ok(self.composite_device.overload_stats().dropped())
+    }
+
+    /// Number of output events (e.g. LED or rumble updates) that replaced a
+    /// waiting update of the same output because they were sent faster than
+    /// a source device is written to
+    #[zbus(property)]
+    async fn outputs_coalesced(&self) -> fdo::Result<u64> {
+        Ok(self.composite_device.overload_stats().outputs_coalesced())
+    }
+
+    /// Number of output events that were dropped because too many output
+    /// events were waiting to be written to a source device
+    #[zbus(property)]
+    async fn outputs_dropped(&self) -> fdo::Result<u64> {
+        Ok(self.composite_device.overload_stats().outputs_dropped())
+    }
+
     /// Target dbus devices that this [CompositeDevice] is managing
     #[zbus(property)]
     async fn dbus_devices(&self) -> fdo::Result<Vec<String>> {
@@ -363,4 +814,77 @@ impl CompositeDeviceInterface {
 
         Ok(paths)
     }
+
+    /// Emitted when reading from a source device fails, or when a source
+    /// device stops responding and is restarted. The retry strategy is
+    /// "backoff" if reading will be retried after the given number of
+    /// milliseconds, or "detach" if the source device will be detached.
+    #[zbus(signal)]
+    pub async fn source_device_error(
+        ctxt: &SignalContext<'_>,
+        source_id: &str,
+        error: &str,
+        retry_strategy: &str,
+        attempt: u32,
+        max_attempts: u32,
+        retry_in_ms: u64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a source device is detached from the composite device,
+    /// either because it was unplugged or because it failed too many times.
+    #[zbus(signal)]
+    pub async fn source_device_detached(
+        ctxt: &SignalContext<'_>,
+        source_id: &str,
+        reason: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a detachable controller (e.g. one half of a Legion Go) is
+    /// attached to the device
+    #[zbus(signal)]
+    pub async fn controller_attached(
+        ctxt: &SignalContext<'_>,
+        controller: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a detachable controller (e.g. one half of a Legion Go) is
+    /// detached from the device
+    #[zbus(signal)]
+    pub async fn controller_detached(
+        ctxt: &SignalContext<'_>,
+        controller: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted for each event that would have been written to target devices
+    /// while a mapping dry run is active, with the capability it was
+    /// translated from and the capability it was translated to.
+    #[zbus(signal)]
+    pub async fn dry_run_event(
+        ctxt: &SignalContext<'_>,
+        source_id: &str,
+        source_capability: &str,
+        capability: &str,
+        value: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted for each event written to target devices while the inspector
+    /// is enabled, after it has been translated by capability maps and device
+    /// profiles.
+    #[zbus(signal)]
+    pub async fn input_event(
+        ctxt: &SignalContext<'_>,
+        source_id: &str,
+        capability: &str,
+        value: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when the radial menu stick points at a new sector. Sector 0
+    /// points up and the eight sectors are numbered clockwise.
+    #[zbus(signal)]
+    pub async fn radial_sector_selected(ctxt: &SignalContext<'_>, sector: u8) -> zbus::Result<()>;
+
+    /// Emitted when the radial menu stick is released while a sector is
+    /// selected
+    #[zbus(signal)]
+    pub async fn radial_sector_confirmed(ctxt: &SignalContext<'_>, sector: u8) -> zbus::Result<()>;
 }
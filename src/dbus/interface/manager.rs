@@ -1,12 +1,26 @@
-use std::time::Duration;
+use std::{collections::HashMap, str::FromStr, time::Duration};
 
 use tokio::sync::mpsc;
-use zbus::fdo;
+use zbus::{fdo, message::Header, object_server::SignalContext, zvariant, Connection};
 use zbus_macros::interface;
 
 use crate::{
     config::CompositeDeviceConfig,
-    input::{manager::ManagerCommand, target::TargetDeviceTypeId},
+    dbus::{
+        interface::input_value_from_variant,
+        polkit::{
+            check_authorization, ACTION_CONFIGURE_DEVICES, ACTION_INJECT_INPUT,
+            ACTION_MANAGE_DEVICES,
+        },
+    },
+    input::{
+        capability::{Capability, CAPABILITY_VERSION},
+        event::native::NativeEvent,
+        manager::ManagerCommand,
+        source::r#virtual::VirtualScript,
+        target::TargetDeviceTypeId,
+    },
+    logging,
 };
 
 /// The [ManagerInterface] provides a DBus interface that can be exposed for managing
@@ -36,6 +50,13 @@ impl ManagerInterface {
         Ok(VERSION.to_string())
     }
 
+    /// Version of the capability string grammar used for capabilities in
+    /// configs and DBus interfaces (e.g. "Gamepad:Button:South")
+    #[zbus(property)]
+    async fn capability_version(&self) -> fdo::Result<u32> {
+        Ok(CAPABILITY_VERSION)
+    }
+
     #[zbus(property)]
     async fn intercept_mode(&self) -> fdo::Result<String> {
         Ok("InputPlumber".to_string())
@@ -61,7 +82,15 @@ impl ManagerInterface {
         Ok(response)
     }
     #[zbus(property)]
-    async fn set_manage_all_devices(&self, value: bool) -> zbus::Result<()> {
+    async fn set_manage_all_devices(
+        &self,
+        value: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         self.tx
             .send_timeout(
                 ManagerCommand::SetManageAllDevices(value),
@@ -72,6 +101,28 @@ impl ManagerInterface {
         Ok(())
     }
 
+    /// The active log filter directives using the `RUST_LOG` syntax.
+    #[zbus(property)]
+    async fn log_filter(&self) -> fdo::Result<String> {
+        Ok(logging::get_filter())
+    }
+
+    /// Change the log filter at runtime. Filters use the `RUST_LOG` syntax and
+    /// can target modules or the span of a single device. E.g.
+    /// "info,[composite_device{path=/org/shadowblip/InputPlumber/CompositeDevice0}]=debug"
+    async fn set_log_filter(
+        &self,
+        filter: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        logging::set_filter(filter.as_str()).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.log_filter_changed(&ctx).await?;
+        Ok(())
+    }
+
     /// Returns a list of supported target device names. E.g. ["InputPlumber Mouse", "Microsoft
     /// XBox 360 Gamepad"]
     #[zbus(property)]
@@ -89,7 +140,13 @@ impl ManagerInterface {
 
     /// Create a composite device using the give composite device config. The
     /// path should be the absolute path to a composite device configuration file.
-    async fn create_composite_device(&self, config_path: String) -> fdo::Result<String> {
+    async fn create_composite_device(
+        &self,
+        config_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<String> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         let device = CompositeDeviceConfig::from_yaml_file(config_path)
             .map_err(|err| fdo::Error::Failed(err.to_string()))?;
         self.tx
@@ -104,11 +161,22 @@ impl ManagerInterface {
 
     /// Create a target device of the given type. Returns the DBus path to
     /// the created target device.
-    async fn create_target_device(&self, kind: String) -> fdo::Result<String> {
+    async fn create_target_device(
+        &self,
+        kind: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<String> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         let (sender, mut receiver) = mpsc::channel(1);
         self.tx
             .send_timeout(
-                ManagerCommand::CreateTargetDevice { kind, sender },
+                ManagerCommand::CreateTargetDevice {
+                    kind,
+                    axis_ranges: HashMap::new(),
+                    device_id: None,
+                    sender,
+                },
                 Duration::from_millis(500),
             )
             .await
@@ -130,7 +198,13 @@ impl ManagerInterface {
     }
 
     /// Stop the given target device
-    async fn stop_target_device(&self, path: String) -> fdo::Result<()> {
+    async fn stop_target_device(
+        &self,
+        path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         self.tx
             .send_timeout(
                 ManagerCommand::StopTargetDevice { path },
@@ -146,7 +220,10 @@ impl ManagerInterface {
         &self,
         target_path: String,
         composite_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
     ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         let (sender, mut receiver) = mpsc::channel(1);
         self.tx
             .send_timeout(
@@ -172,8 +249,212 @@ impl ManagerInterface {
         Ok(())
     }
 
+    /// Create a virtual source device with the given name that is not backed
+    /// by any hardware. If a script path is given, the device replays the
+    /// events in the script. The device is added to a composite device whose
+    /// config matches it with the "virtual" udev subsystem and the name as
+    /// sys_name. Returns the id of the source device. E.g. "virtual://demo"
+    async fn create_virtual_source_device(
+        &self,
+        name: String,
+        script_path: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<String> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        if !script_path.is_empty() {
+            VirtualScript::from_yaml_file(script_path.as_str())
+                .map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+        }
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::CreateVirtualSourceDevice {
+                    name,
+                    script: script_path,
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.map_err(|e| {
+            let err = format!("Failed to create virtual source device: {e:?}");
+            fdo::Error::Failed(err)
+        })
+    }
+
+    /// Remove the virtual source device with the given id
+    async fn destroy_virtual_source_device(
+        &self,
+        id: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::DestroyVirtualSourceDevice { id, sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.map_err(|e| {
+            let err = format!("Failed to destroy virtual source device: {e:?}");
+            fdo::Error::Failed(err)
+        })
+    }
+
+    /// Emit the given input event from the virtual source device with the
+    /// given id. E.g. SendVirtualSourceEvent("virtual://demo", "Gamepad:Button:South", true)
+    async fn send_virtual_source_event(
+        &self,
+        id: String,
+        event: String,
+        value: zvariant::Value<'_>,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INJECT_INPUT).await?;
+        let Ok(capability) = Capability::from_str(event.as_str()) else {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Invalid capability: {event}"
+            )));
+        };
+        let value = input_value_from_variant(value)?;
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::SendVirtualSourceEvent {
+                    id,
+                    event: NativeEvent::new(capability, value),
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.map_err(|e| fdo::Error::Failed(format!("Failed to send event: {e:?}")))
+    }
+
+    /// Create an external source device with the given name that is
+    /// implemented by a userspace driver. The driver is the name of an
+    /// executable in /etc/inputplumber/drivers.d or
+    /// /usr/share/inputplumber/drivers that speaks the line-delimited JSON
+    /// driver protocol. The device is added to a composite device whose config
+    /// matches it with the "external" udev subsystem and the name as sys_name.
+    /// Returns the id of the source device. E.g. "external://mypad"
+    async fn create_external_source_device(
+        &self,
+        name: String,
+        driver: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<String> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::CreateExternalSourceDevice {
+                    name,
+                    driver,
+                    sender,
+                },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.map_err(|e| {
+            let err = format!("Failed to create external source device: {e:?}");
+            fdo::Error::Failed(err)
+        })
+    }
+
+    /// Remove the external source device with the given id and stop its
+    /// driver
+    async fn destroy_external_source_device(
+        &self,
+        id: String,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::DestroyExternalSourceDevice { id, sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(response) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        response.map_err(|e| {
+            let err = format!("Failed to destroy external source device: {e:?}");
+            fdo::Error::Failed(err)
+        })
+    }
+
+    /// Returns metadata of all input profiles available in the user and system
+    /// profile directories as a list of (path, name, description, games).
+    async fn list_profiles(&self) -> fdo::Result<Vec<(String, String, String, Vec<String>)>> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        self.tx
+            .send_timeout(
+                ManagerCommand::ListProfiles { sender },
+                Duration::from_millis(500),
+            )
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        // Read the response from the manager
+        let Some(profiles) = receiver.recv().await else {
+            return Err(fdo::Error::Failed("No response from manager".to_string()));
+        };
+        let profiles = profiles
+            .into_iter()
+            .map(|p| (p.path, p.name, p.description, p.games))
+            .collect();
+
+        Ok(profiles)
+    }
+
+    /// Emitted when input profiles are added, modified, or removed from the
+    /// profile directories.
+    #[zbus(signal)]
+    pub async fn profiles_changed(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+
     /// Used to prepare InputPlumber for system suspend
-    async fn hook_sleep(&self) -> fdo::Result<()> {
+    async fn hook_sleep(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         let (sender, mut receiver) = mpsc::channel(1);
         self.tx
             .send_timeout(
@@ -192,7 +473,12 @@ impl ManagerInterface {
     }
 
     /// Used to prepare InputPlumber for resume from system suspend
-    async fn hook_wake(&self) -> fdo::Result<()> {
+    async fn hook_wake(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
         let (sender, mut receiver) = mpsc::channel(1);
         self.tx
             .send_timeout(
@@ -2,3 +2,60 @@ pub mod composite_device;
 pub mod manager;
 pub mod source;
 pub mod target;
+
+use zbus::{
+    fdo,
+    zvariant::{self, Value},
+};
+
+use crate::input::{capability::Capability, event::value::InputValue};
+
+/// Returns the given capabilities as strings that can be sent over DBus.
+/// E.g. "Gamepad:Button:South"
+pub fn capability_strings(capabilities: Vec<Capability>) -> Vec<String> {
+    capabilities
+        .into_iter()
+        .map(|cap| cap.to_string())
+        .collect()
+}
+
+/// Parse the given DBus value into an input value. Booleans are button
+/// values, doubles are axis values, and arrays of two or three doubles are
+/// vector values (e.g. stick positions).
+pub fn input_value_from_variant(value: zvariant::Value<'_>) -> fdo::Result<InputValue> {
+    let value = match value {
+        zvariant::Value::Bool(v) => InputValue::Bool(v),
+        zvariant::Value::F64(v) => InputValue::Float(v),
+        zvariant::Value::Array(v) => match floats_from_array(&v)?.as_slice() {
+            [x, y] => InputValue::Vector2 {
+                x: Some(*x),
+                y: Some(*y),
+            },
+            [x, y, z] => InputValue::Vector3 {
+                x: Some(*x),
+                y: Some(*y),
+                z: Some(*z),
+            },
+            _ => InputValue::None,
+        },
+        _ => InputValue::None,
+    };
+
+    Ok(value)
+}
+
+/// Parse the values of the given DBus array of two or three elements into
+/// floats. Arrays of any other length are returned empty.
+fn floats_from_array(array: &zvariant::Array<'_>) -> fdo::Result<Vec<f64>> {
+    if !matches!(array.len(), 2 | 3) {
+        return Ok(Vec::new());
+    }
+    array
+        .iter()
+        .map(|value: &Value| {
+            f64::try_from(value).map_err(|_| {
+                fdo::Error::InvalidArgs(format!("Failed to parse {value:?} into float."))
+            })
+        })
+        .collect()
+}
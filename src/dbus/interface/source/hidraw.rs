@@ -3,30 +3,63 @@ use std::error::Error;
 use zbus::{fdo, Connection};
 use zbus_macros::interface;
 
-use crate::{input::source::hidraw::get_dbus_path, udev::device::UdevDevice};
+use crate::{
+    drivers::device_info::{self, DeviceInfo},
+    input::source::hidraw::get_dbus_path,
+    udev::device::UdevDevice,
+};
 
 /// DBusInterface exposing information about a HIDRaw device
 pub struct SourceHIDRawInterface {
     device: UdevDevice,
+    info: DeviceInfo,
 }
 
 impl SourceHIDRawInterface {
-    pub fn new(device: UdevDevice) -> SourceHIDRawInterface {
-        SourceHIDRawInterface { device }
+    pub fn new(device: UdevDevice, info: DeviceInfo) -> SourceHIDRawInterface {
+        SourceHIDRawInterface { device, info }
     }
 
-    /// Creates a new instance of the source hidraw interface on DBus. Returns
-    /// a structure with information about the source device.
+    /// Query the given device for information only available from feature
+    /// reports. This must be done before a source driver is started for the
+    /// device, so the feature reports do not race with the driver's own
+    /// reads and writes.
+    pub async fn query_device_info(device: &UdevDevice) -> DeviceInfo {
+        let sys_name = device.sysname();
+        let devnode = device.devnode();
+        let vid = device.id_vendor();
+        let pid = device.id_product();
+        let task = tokio::task::spawn_blocking(move || {
+            device_info::query(devnode.as_str(), vid, pid).map_err(|e| e.to_string())
+        });
+        let info = match task.await {
+            Ok(Ok(info)) => info,
+            Ok(Err(e)) => {
+                log::debug!("Failed to query device info for {sys_name}: {e}");
+                DeviceInfo::default()
+            }
+            Err(e) => {
+                log::debug!("Failed to run device info query for {sys_name}: {e}");
+                DeviceInfo::default()
+            }
+        };
+        log::debug!("Got device info for {sys_name}: {info:?}");
+        info
+    }
+
+    /// Creates a new instance of the source hidraw interface on DBus using
+    /// the device information from [SourceHIDRawInterface::query_device_info].
     pub async fn listen_on_dbus(
         conn: Connection,
         sys_name: String,
         device: UdevDevice,
+        info: DeviceInfo,
     ) -> Result<(), Box<dyn Error>> {
         log::debug!("Starting to listen on dbus interface for {sys_name}");
         let path = get_dbus_path(sys_name.clone());
         log::debug!("Got dbus path {path}");
 
-        let iface = SourceHIDRawInterface::new(device);
+        let iface = SourceHIDRawInterface::new(device, info);
         log::debug!("Created interface for {sys_name}");
         tokio::task::spawn(async move {
             log::debug!("Starting dbus interface: {path}");
@@ -53,6 +86,20 @@ impl SourceHIDRawInterface {
         Ok(self.device.devnode())
     }
 
+    /// Firmware version reported by the device. Returns an empty string if
+    /// the firmware version cannot be queried from the device.
+    #[zbus(property)]
+    async fn firmware_version(&self) -> fdo::Result<String> {
+        Ok(self.info.firmware_version.clone().unwrap_or_default())
+    }
+
+    /// Hardware revision reported by the device. Returns an empty string if
+    /// the hardware revision cannot be queried from the device.
+    #[zbus(property)]
+    async fn hardware_version(&self) -> fdo::Result<String> {
+        Ok(self.info.hardware_version.clone().unwrap_or_default())
+    }
+
     #[zbus(property)]
     async fn id_product(&self) -> fdo::Result<String> {
         Ok(format!("{:04x}", self.device.id_product()))
@@ -80,7 +127,11 @@ impl SourceHIDRawInterface {
 
     #[zbus(property)]
     async fn serial_number(&self) -> fdo::Result<String> {
-        Ok(self.device.serial_number())
+        let serial = self.device.serial_number();
+        if !serial.is_empty() {
+            return Ok(serial);
+        }
+        Ok(self.info.serial.clone().unwrap_or_default())
     }
 
     #[zbus(property)]
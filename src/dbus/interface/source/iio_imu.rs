@@ -1,10 +1,14 @@
 use std::error::Error;
 
 use crate::udev::device::{AttributeGetter, AttributeSetter, UdevDevice};
-use zbus::{fdo, Connection};
+use zbus::{fdo, message::Header, Connection};
 use zbus_macros::interface;
 
-use crate::input::source::iio::get_dbus_path;
+use crate::{
+    dbus::polkit::{check_authorization, ACTION_CONFIGURE_DEVICES},
+    drivers::iio_imu::info::find_sample_rate,
+    input::source::iio::get_dbus_path,
+};
 
 /// DBusInterface exposing information about a HIDRaw device
 pub struct SourceIioImuInterface {
@@ -107,11 +111,24 @@ impl SourceIioImuInterface {
         Ok(all_scales)
     }
 
+    /// Set the sampling frequency of the accelerometer. A running source
+    /// device picks up the new sample rate and adjusts how often it polls
+    /// the device to match.
     #[zbus(property)]
-    async fn set_accel_sample_rate(&self, sample_rate: f64) -> zbus::Result<()> {
+    async fn set_accel_sample_rate(
+        &self,
+        sample_rate: f64,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         let Ok(mut dev) = self.device.get_device() else {
             return Ok(());
         };
+        let avail = dev.get_attribute_from_tree("in_accel_sampling_frequency_available");
+        let sample_rate = validate_sample_rate(avail.as_str(), sample_rate)?;
         match dev.set_attribute_on_tree(
             "in_accel_sampling_frequency",
             sample_rate.to_string().as_str(),
@@ -121,11 +138,24 @@ impl SourceIioImuInterface {
         }
     }
 
+    /// Set the sampling frequency of the gyro. A running source device picks
+    /// up the new sample rate and adjusts how often it polls the device to
+    /// match.
     #[zbus(property)]
-    async fn set_angvel_sample_rate(&self, sample_rate: f64) -> zbus::Result<()> {
+    async fn set_angvel_sample_rate(
+        &self,
+        sample_rate: f64,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         let Ok(mut dev) = self.device.get_device() else {
             return Ok(());
         };
+        let avail = dev.get_attribute_from_tree("in_anglvel_sampling_frequency_available");
+        let sample_rate = validate_sample_rate(avail.as_str(), sample_rate)?;
         match dev.set_attribute_on_tree(
             "in_anglvel_sampling_frequency",
             sample_rate.to_string().as_str(),
@@ -188,7 +218,15 @@ impl SourceIioImuInterface {
     }
 
     #[zbus(property)]
-    async fn set_accel_scale(&self, scale: f64) -> zbus::Result<()> {
+    async fn set_accel_scale(
+        &self,
+        scale: f64,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         let Ok(mut dev) = self.device.get_device() else {
             return Ok(());
         };
@@ -199,7 +237,15 @@ impl SourceIioImuInterface {
     }
 
     #[zbus(property)]
-    async fn set_angvel_scale(&self, scale: f64) -> zbus::Result<()> {
+    async fn set_angvel_scale(
+        &self,
+        scale: f64,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> zbus::Result<()> {
+        check_authorization(conn, &hdr, ACTION_CONFIGURE_DEVICES)
+            .await
+            .map_err(|e| zbus::Error::FDO(Box::new(e)))?;
         let Ok(mut dev) = self.device.get_device() else {
             return Ok(());
         };
@@ -209,3 +255,17 @@ impl SourceIioImuInterface {
         }
     }
 }
+
+/// Returns the available sample rate matching the given sample rate from the
+/// given space-separated list of available sample rates.
+fn validate_sample_rate(avail: &str, sample_rate: f64) -> zbus::Result<f64> {
+    let sample_rates_avail: Vec<f64> = avail
+        .split_whitespace()
+        .filter_map(|val| val.parse::<f64>().ok())
+        .collect();
+    find_sample_rate(sample_rates_avail.as_slice(), sample_rate).ok_or_else(|| {
+        zbus::Error::Failure(format!(
+            "Unsupported sample rate {sample_rate}. Available: {sample_rates_avail:?}"
+        ))
+    })
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use zbus::{fdo, message::Header, object_server::SignalContext, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::{
+        interface::capability_strings,
+        polkit::{check_authorization, ACTION_MANAGE_DEVICES},
+    },
+    input::{
+        event::{dbus::Action, dbus_batch::DBusBatchSettings},
+        target::client::TargetDeviceClient,
+    },
+};
+
+/// Maximum batch or throttle interval in milliseconds
+const MAX_INTERVAL_MS: u32 = 1000;
+
+/// The [TargetDBusInterface] provides a DBus interface that can be exposed for managing
+/// a [DBusDevice]. It works by sending command messages to a channel that the
+/// [DBusDevice] is listening on.
+pub struct TargetDBusInterface {
+    target_device: TargetDeviceClient,
+    batch_settings: Arc<Mutex<DBusBatchSettings>>,
+}
+
+impl TargetDBusInterface {
+    pub fn new(
+        target_device: TargetDeviceClient,
+        batch_settings: Arc<Mutex<DBusBatchSettings>>,
+    ) -> TargetDBusInterface {
+        TargetDBusInterface {
+            target_device,
+            batch_settings,
+        }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.DBusDevice")]
+impl TargetDBusInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("DBusDevice".into())
+    }
+
+    /// Returns the list of capabilities that the target device can emit
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .target_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
+    /// Whether or not the target device is emitting input events
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        self.target_device
+            .is_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable the target device. Input events sent to a disabled
+    /// target device are discarded, so it can be temporarily muted without
+    /// recreating the composite device.
+    async fn set_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.target_device
+            .set_enabled(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.enabled_changed(&ctx).await?;
+        Ok(())
+    }
+
+    /// Time in milliseconds to collect input events before they are emitted
+    /// together with the InputEventBatch signal. If zero, events are emitted
+    /// individually with the InputEvent and TouchEvent signals.
+    #[zbus(property)]
+    async fn batch_interval(&self) -> fdo::Result<u32> {
+        let settings = self.batch_settings.lock().unwrap();
+        Ok(settings.batch_interval.as_millis() as u32)
+    }
+
+    /// Set the time in milliseconds to collect input events before they are
+    /// emitted together. A value of zero disables batching.
+    async fn set_batch_interval(
+        &self,
+        interval_ms: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        if interval_ms > MAX_INTERVAL_MS {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Batch interval must be at most {MAX_INTERVAL_MS}ms"
+            )));
+        }
+        {
+            let mut settings = self.batch_settings.lock().unwrap();
+            settings.batch_interval = Duration::from_millis(interval_ms as u64);
+        }
+        self.batch_interval_changed(&ctx).await?;
+        Ok(())
+    }
+
+    /// Minimum time in milliseconds between two events of the same kind,
+    /// mapped by event. E.g. {"ui_touch": 16}
+    #[zbus(property)]
+    async fn throttle_intervals(&self) -> fdo::Result<HashMap<String, u32>> {
+        let settings = self.batch_settings.lock().unwrap();
+        let intervals = settings
+            .throttle_intervals
+            .iter()
+            .map(|(action, interval)| (action.as_string(), interval.as_millis() as u32))
+            .collect();
+        Ok(intervals)
+    }
+
+    /// Set the minimum time in milliseconds between two events of the given
+    /// kind. Events that occur sooner are coalesced so only the latest value
+    /// is emitted, but presses, releases, and touches starting or ending are
+    /// never dropped. A value of zero disables throttling for the event.
+    /// E.g. SetThrottleInterval("ui_touch", 16)
+    async fn set_throttle_interval(
+        &self,
+        event: String,
+        interval_ms: u32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        let Ok(action) = Action::from_str(event.as_str()) else {
+            return Err(fdo::Error::InvalidArgs(format!("Invalid event: {event}")));
+        };
+        if interval_ms > MAX_INTERVAL_MS {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Throttle interval must be at most {MAX_INTERVAL_MS}ms"
+            )));
+        }
+        {
+            let mut settings = self.batch_settings.lock().unwrap();
+            if interval_ms == 0 {
+                settings.throttle_intervals.remove(&action);
+            } else {
+                let interval = Duration::from_millis(interval_ms as u64);
+                settings.throttle_intervals.insert(action, interval);
+            }
+        }
+        self.throttle_intervals_changed(&ctx).await?;
+        Ok(())
+    }
+
+    /// Emitted when an input event occurs
+    #[zbus(signal)]
+    pub async fn input_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        value: f64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted when a touch event occurs.
+    #[zbus(signal)]
+    pub async fn touch_event(
+        ctxt: &SignalContext<'_>,
+        event: String,
+        index: u32,
+        is_touching: bool,
+        pressure: f64,
+        x: f64,
+        y: f64,
+    ) -> zbus::Result<()>;
+
+    /// Emitted with all input and touch events collected during the batch
+    /// interval, in the order they occurred, if batching is enabled. Input
+    /// events are (event, value) and touch events are (event, index,
+    /// is_touching, pressure, x, y).
+    #[zbus(signal)]
+    pub async fn input_event_batch(
+        ctxt: &SignalContext<'_>,
+        input_events: Vec<(String, f64)>,
+        touch_events: Vec<(String, u32, bool, f64, f64, f64)>,
+    ) -> zbus::Result<()>;
+}
@@ -0,0 +1,88 @@
+use zbus::{fdo, message::Header, object_server::SignalContext, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::{
+        interface::capability_strings,
+        polkit::{check_authorization, ACTION_MANAGE_DEVICES},
+    },
+    input::target::client::TargetDeviceClient,
+};
+
+/// The [TargetGamepadInterface] provides a DBus interface that can be exposed for managing
+/// a [GenericGamepad].
+pub struct TargetGamepadInterface {
+    dev_name: String,
+    target_device: TargetDeviceClient,
+}
+
+impl TargetGamepadInterface {
+    pub fn new(dev_name: String, target_device: TargetDeviceClient) -> TargetGamepadInterface {
+        TargetGamepadInterface {
+            dev_name,
+            target_device,
+        }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Gamepad")]
+impl TargetGamepadInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok(self.dev_name.clone())
+    }
+
+    /// Returns the list of capabilities that the target device can emit
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .target_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
+    /// Serial number the target device presents to the host. It stays the
+    /// same each time the target device is created for the same composite
+    /// device, so games recognize the controller when it reconnects. Empty if
+    /// the target device has no serial number.
+    #[zbus(property)]
+    async fn serial(&self) -> fdo::Result<String> {
+        let serial = self
+            .target_device
+            .get_serial()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        Ok(serial.unwrap_or_default())
+    }
+
+    /// Whether or not the target device is emitting input events
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        self.target_device
+            .is_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable the target device. Input events sent to a disabled
+    /// target device are discarded, so it can be temporarily muted without
+    /// recreating the composite device.
+    async fn set_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.target_device
+            .set_enabled(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.enabled_changed(&ctx).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+pub mod dbus;
+pub mod gamepad;
+pub mod keyboard;
+pub mod mouse;
+pub mod remote;
+pub mod touchscreen;
+
+use zbus::{fdo, message::Header, object_server::SignalContext, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::{
+        interface::capability_strings,
+        polkit::{check_authorization, ACTION_MANAGE_DEVICES},
+    },
+    input::target::client::TargetDeviceClient,
+};
+
+/// The [TargetInterface] provides a DBus interface that can be exposed for managing
+/// a target input device.
+pub struct TargetInterface {
+    dev_name: String,
+    target_device: TargetDeviceClient,
+}
+
+impl TargetInterface {
+    pub fn new(dev_name: String, target_device: TargetDeviceClient) -> TargetInterface {
+        TargetInterface {
+            dev_name,
+            target_device,
+        }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Target")]
+impl TargetInterface {
+    /// Name of the DBus device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok(self.dev_name.clone())
+    }
+
+    /// Returns the list of capabilities that the target device can emit
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .target_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
+    /// Whether or not the target device is emitting input events
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        self.target_device
+            .is_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable the target device. Input events sent to a disabled
+    /// target device are discarded, so it can be temporarily muted without
+    /// recreating the composite device.
+    async fn set_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.target_device
+            .set_enabled(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.enabled_changed(&ctx).await?;
+        Ok(())
+    }
+}
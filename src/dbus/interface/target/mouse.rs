@@ -0,0 +1,101 @@
+use zbus::{fdo, message::Header, object_server::SignalContext, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::{
+        interface::capability_strings,
+        polkit::{check_authorization, ACTION_INJECT_INPUT, ACTION_MANAGE_DEVICES},
+    },
+    input::{
+        capability::{Capability, Mouse},
+        event::{native::NativeEvent, value::InputValue},
+        target::client::TargetDeviceClient,
+    },
+};
+
+/// The [TargetMouseInterface] provides a DBus interface that can be exposed for managing
+/// a [MouseDevice]. It works by sending command messages to a channel that the
+/// [MouseDevice] is listening on.
+pub struct TargetMouseInterface {
+    target_device: TargetDeviceClient,
+}
+
+impl TargetMouseInterface {
+    pub fn new(target_device: TargetDeviceClient) -> TargetMouseInterface {
+        TargetMouseInterface { target_device }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Mouse")]
+impl TargetMouseInterface {
+    /// Name of the composite device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Mouse".into())
+    }
+
+    /// Returns the list of capabilities that the target device can emit
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .target_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
+    /// Whether or not the target device is emitting input events
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        self.target_device
+            .is_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable the target device. Input events sent to a disabled
+    /// target device are discarded, so it can be temporarily muted without
+    /// recreating the composite device.
+    async fn set_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.target_device
+            .set_enabled(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.enabled_changed(&ctx).await?;
+        Ok(())
+    }
+
+    /// Move the virtual mouse by the given amount relative to the cursor's
+    /// relative position.
+    async fn move_cursor(
+        &self,
+        x: i32,
+        y: i32,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_INJECT_INPUT).await?;
+        // Create a mouse motion event
+        let value = InputValue::Vector2 {
+            x: Some(x as f64),
+            y: Some(y as f64),
+        };
+        let event = NativeEvent::new(Capability::Mouse(Mouse::Motion), value);
+
+        // Write the event to the virtual mouse
+        self.target_device
+            .write_event(event)
+            .await
+            .map_err(|err| fdo::Error::Failed(err.to_string()))?;
+
+        Ok(())
+    }
+}
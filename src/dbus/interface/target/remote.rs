@@ -0,0 +1,82 @@
+use zbus::{fdo, message::Header, object_server::SignalContext, Connection};
+use zbus_macros::interface;
+
+use crate::{
+    dbus::{
+        interface::capability_strings,
+        polkit::{check_authorization, ACTION_MANAGE_DEVICES},
+    },
+    input::target::client::TargetDeviceClient,
+};
+
+/// The [TargetRemoteInterface] provides a DBus interface that can be exposed
+/// for managing a [RemoteDevice]. Media center shells listen for the remote
+/// control commands it emits.
+pub struct TargetRemoteInterface {
+    target_device: TargetDeviceClient,
+}
+
+impl TargetRemoteInterface {
+    pub fn new(target_device: TargetDeviceClient) -> TargetRemoteInterface {
+        TargetRemoteInterface { target_device }
+    }
+}
+
+#[interface(name = "org.shadowblip.Input.Remote")]
+impl TargetRemoteInterface {
+    /// Name of the remote device
+    #[zbus(property)]
+    async fn name(&self) -> fdo::Result<String> {
+        Ok("Remote".into())
+    }
+
+    /// Returns the list of capabilities that the target device can translate
+    /// into remote commands
+    async fn get_capabilities(&self) -> fdo::Result<Vec<String>> {
+        let capabilities = self
+            .target_device
+            .get_capabilities()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        Ok(capability_strings(capabilities))
+    }
+
+    /// Whether or not the target device is emitting remote commands
+    #[zbus(property)]
+    async fn enabled(&self) -> fdo::Result<bool> {
+        self.target_device
+            .is_enabled()
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Enable or disable the target device. Input events sent to a disabled
+    /// target device are discarded, so it can be temporarily muted without
+    /// recreating the composite device.
+    async fn set_enabled(
+        &self,
+        enabled: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(header)] hdr: Header<'_>,
+        #[zbus(signal_context)] ctx: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        check_authorization(conn, &hdr, ACTION_MANAGE_DEVICES).await?;
+        self.target_device
+            .set_enabled(enabled)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+        self.enabled_changed(&ctx).await?;
+        Ok(())
+    }
+
+    /// Emitted when a remote control command is pressed or released. The
+    /// command is one of "up", "down", "left", "right", "select", "back",
+    /// "home", or "context".
+    #[zbus(signal)]
+    pub async fn command(
+        ctxt: &SignalContext<'_>,
+        command: &str,
+        pressed: bool,
+    ) -> zbus::Result<()>;
+}
@@ -1 +1,2 @@
 pub mod interface;
+pub mod polkit;
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use zbus::{
+    fdo,
+    message::Header,
+    names::BusName,
+    proxy,
+    zvariant::{Type, Value},
+    Connection,
+};
+
+/// Polkit action required to change the intercept mode of a device
+pub const ACTION_INTERCEPT: &str = "org.shadowblip.InputPlumber.intercept";
+/// Polkit action required to create, attach, or stop devices and to perform
+/// administrative tasks like maintenance mode or changing the log filter
+pub const ACTION_MANAGE_DEVICES: &str = "org.shadowblip.InputPlumber.manage-devices";
+/// Polkit action required for the active session to change the target
+/// devices and player index of devices, the sample rates and scales of IMUs,
+/// and whether all devices are managed
+pub const ACTION_CONFIGURE_DEVICES: &str = "org.shadowblip.InputPlumber.configure-devices";
+/// Polkit action required to load device profiles
+pub const ACTION_LOAD_PROFILE: &str = "org.shadowblip.InputPlumber.load-profile";
+/// Polkit action required to inject input events into a device
+pub const ACTION_INJECT_INPUT: &str = "org.shadowblip.InputPlumber.inject-input";
+/// Polkit action required for mappings to suspend, power off, or lock the
+/// active session
+pub const ACTION_POWER: &str = "org.shadowblip.InputPlumber.power";
+
+/// Allow polkit to interact with the user to obtain authorization
+const CHECK_AUTHORIZATION_FLAGS_ALLOW_USER_INTERACTION: u32 = 1;
+
+/// A polkit subject identifying who is requesting authorization
+#[derive(Debug, Serialize, Type)]
+struct Subject<'a> {
+    kind: &'a str,
+    details: HashMap<&'a str, Value<'a>>,
+}
+
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+trait Authority {
+    /// CheckAuthorization method
+    fn check_authorization(
+        &self,
+        subject: &Subject<'_>,
+        action_id: &str,
+        details: HashMap<&str, &str>,
+        flags: u32,
+        cancellation_id: &str,
+    ) -> zbus::Result<(bool, bool, HashMap<String, String>)>;
+}
+
+/// Check with polkit if the sender of the given message is authorized to
+/// perform the given action. Requests from root are always authorized.
+pub async fn check_authorization(
+    conn: &Connection,
+    header: &Header<'_>,
+    action_id: &str,
+) -> fdo::Result<()> {
+    let Some(sender) = header.sender() else {
+        return Err(fdo::Error::AccessDenied(
+            "Unable to determine sender of request".to_string(),
+        ));
+    };
+
+    // Always allow requests from root
+    let dbus = fdo::DBusProxy::new(conn).await?;
+    let uid = dbus
+        .get_connection_unix_user(BusName::from(sender.to_owned()))
+        .await?;
+    if uid == 0 {
+        return Ok(());
+    }
+
+    let subject = Subject {
+        kind: "system-bus-name",
+        details: HashMap::from([("name", Value::from(sender.as_str()))]),
+    };
+    let authority = AuthorityProxy::new(conn).await?;
+    let (is_authorized, _is_challenge, _details) = authority
+        .check_authorization(
+            &subject,
+            action_id,
+            HashMap::new(),
+            CHECK_AUTHORIZATION_FLAGS_ALLOW_USER_INTERACTION,
+            "",
+        )
+        .await
+        .map_err(|e| {
+            log::warn!("Failed to check authorization for {sender} to {action_id}: {e:?}");
+            fdo::Error::AccessDenied(format!("Unable to check authorization: {e}"))
+        })?;
+
+    if !is_authorized {
+        log::info!("Denied {action_id} for {sender} (uid {uid})");
+        return Err(fdo::Error::AccessDenied(format!(
+            "Not authorized to perform {action_id}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check with polkit if the user of the login session with the given id is
+/// authorized to perform the given action. Used for actions triggered by
+/// input instead of a DBus request, so polkit cannot ask for authentication.
+pub async fn check_session_authorization(
+    conn: &Connection,
+    session_id: &str,
+    action_id: &str,
+) -> zbus::Result<bool> {
+    let subject = Subject {
+        kind: "unix-session",
+        details: HashMap::from([("session-id", Value::from(session_id))]),
+    };
+    let authority = AuthorityProxy::new(conn).await?;
+    let (is_authorized, _is_challenge, _details) = authority
+        .check_authorization(&subject, action_id, HashMap::new(), 0, "")
+        .await?;
+
+    Ok(is_authorized)
+}
+
+/// Check with polkit if the process with the given pid and uid is authorized
+/// to perform the given action. Used for requests from local sockets, where
+/// the peer credentials identify the requesting process. Requests from root
+/// are always authorized.
+pub async fn check_process_authorization(
+    conn: &Connection,
+    pid: u32,
+    uid: u32,
+    action_id: &str,
+) -> zbus::Result<bool> {
+    if uid == 0 {
+        return Ok(true);
+    }
+
+    // A start time of zero lets polkit look up the start time of the process,
+    // and the uid makes polkit reject the request if the pid was reused by a
+    // process of another user.
+    let subject = Subject {
+        kind: "unix-process",
+        details: HashMap::from([
+            ("pid", Value::from(pid)),
+            ("start-time", Value::from(0u64)),
+            ("uid", Value::from(uid as i32)),
+        ]),
+    };
+    let authority = AuthorityProxy::new(conn).await?;
+    let (is_authorized, _is_challenge, _details) = authority
+        .check_authorization(&subject, action_id, HashMap::new(), 0, "")
+        .await?;
+
+    Ok(is_authorized)
+}
@@ -0,0 +1,164 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+
+use crate::drivers::{dualsense, steam_deck};
+
+// Source: https://github.com/torvalds/linux/blob/master/drivers/hid/hid-sony.c
+pub const DS4_VID: u16 = 0x054c;
+pub const DS4_PIDS: [u16; 3] = [0x05c4, 0x09cc, 0x0ba0];
+pub const DS4_FEATURE_REPORT_PAIRING_INFO: u8 = 0x81;
+pub const DS4_FEATURE_REPORT_PAIRING_INFO_SIZE: usize = 7;
+pub const DS4_FEATURE_REPORT_FIRMWARE_INFO: u8 = 0xa3;
+pub const DS4_FEATURE_REPORT_FIRMWARE_INFO_SIZE: usize = 49;
+
+// Source: https://github.com/torvalds/linux/blob/master/drivers/hid/hid-playstation.c
+pub const DS5_FEATURE_REPORT_PAIRING_INFO_SIZE: usize = 20;
+pub const DS5_FEATURE_REPORT_FIRMWARE_INFO_SIZE: usize = 64;
+
+/// Steam Deck string attribute for the unit serial number
+pub const DECK_ATTRIB_STR_UNIT_SERIAL: u8 = 0x01;
+/// Size of a Steam Deck feature report including the report ID
+pub const DECK_FEATURE_REPORT_SIZE: usize = 65;
+
+/// Identifying information about a source device that can only be queried
+/// from the device itself using HID feature reports.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceInfo {
+    /// Serial number reported by the device firmware
+    pub serial: Option<String>,
+    /// Firmware version reported by the device
+    pub firmware_version: Option<String>,
+    /// Hardware revision reported by the device
+    pub hardware_version: Option<String>,
+}
+
+/// Returns true if device information can be queried from a device with the
+/// given vendor and product id.
+pub fn is_supported(vid: u16, pid: u16) -> bool {
+    (vid == dualsense::driver::DS5_VID && dualsense::driver::PIDS.contains(&pid))
+        || (vid == DS4_VID && DS4_PIDS.contains(&pid))
+        || (vid == steam_deck::driver::VID && pid == steam_deck::driver::PID)
+}
+
+/// Query the device information from the hidraw device at the given path
+/// using feature reports. Unknown devices will return empty [DeviceInfo].
+pub fn query(path: &str, vid: u16, pid: u16) -> Result<DeviceInfo, Box<dyn Error + Send + Sync>> {
+    if !is_supported(vid, pid) {
+        return Ok(DeviceInfo::default());
+    }
+
+    let c_path = CString::new(path)?;
+    let api = hidapi::HidApi::new()?;
+    let device = api.open_path(&c_path)?;
+
+    if vid == dualsense::driver::DS5_VID && dualsense::driver::PIDS.contains(&pid) {
+        return query_dualsense(&device);
+    }
+    if vid == DS4_VID && DS4_PIDS.contains(&pid) {
+        return query_dualshock4(&device);
+    }
+    query_steam_deck(&device)
+}
+
+/// Query the MAC address and firmware information from a DualSense controller
+fn query_dualsense(device: &HidDevice) -> Result<DeviceInfo, Box<dyn Error + Send + Sync>> {
+    let mut info = DeviceInfo::default();
+
+    let mut buf = [0; DS5_FEATURE_REPORT_PAIRING_INFO_SIZE];
+    buf[0] = dualsense::driver::FEATURE_REPORT_PAIRING_INFO;
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    info.serial = parse_sony_mac_address(&buf[..bytes_read]);
+
+    let mut buf = [0; DS5_FEATURE_REPORT_FIRMWARE_INFO_SIZE];
+    buf[0] = dualsense::driver::FEATURE_REPORT_FIRMWARE_INFO;
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    if let Some((hardware, firmware)) = parse_dualsense_firmware_info(&buf[..bytes_read]) {
+        info.hardware_version = Some(hardware);
+        info.firmware_version = Some(firmware);
+    }
+
+    Ok(info)
+}
+
+/// Query the MAC address and firmware information from a DualShock 4 controller
+fn query_dualshock4(device: &HidDevice) -> Result<DeviceInfo, Box<dyn Error + Send + Sync>> {
+    let mut info = DeviceInfo::default();
+
+    let mut buf = [0; DS4_FEATURE_REPORT_PAIRING_INFO_SIZE];
+    buf[0] = DS4_FEATURE_REPORT_PAIRING_INFO;
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    info.serial = parse_sony_mac_address(&buf[..bytes_read]);
+
+    let mut buf = [0; DS4_FEATURE_REPORT_FIRMWARE_INFO_SIZE];
+    buf[0] = DS4_FEATURE_REPORT_FIRMWARE_INFO;
+    let bytes_read = device.get_feature_report(&mut buf)?;
+    if let Some((hardware, firmware)) = parse_dualshock4_firmware_info(&buf[..bytes_read]) {
+        info.hardware_version = Some(hardware);
+        info.firmware_version = Some(firmware);
+    }
+
+    Ok(info)
+}
+
+/// Query the unit serial number from a Steam Deck controller
+fn query_steam_deck(device: &HidDevice) -> Result<DeviceInfo, Box<dyn Error + Send + Sync>> {
+    let mut buf = [0; DECK_FEATURE_REPORT_SIZE];
+    buf[1] = steam_deck::hid_report::ReportType::GetSerial as u8;
+    buf[2] = 0x15;
+    buf[3] = DECK_ATTRIB_STR_UNIT_SERIAL;
+    device.send_feature_report(&buf)?;
+
+    let mut buf = [0; DECK_FEATURE_REPORT_SIZE];
+    let bytes_read = device.get_feature_report(&mut buf)?;
+
+    Ok(DeviceInfo {
+        serial: parse_steam_deck_serial(&buf[..bytes_read]),
+        ..Default::default()
+    })
+}
+
+/// Parse the MAC address from a Sony pairing info feature report. The address
+/// is stored in reverse byte order after the report ID.
+pub fn parse_sony_mac_address(report: &[u8]) -> Option<String> {
+    let bytes = report.get(1..7)?;
+    if bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    let address: Vec<String> = bytes.iter().rev().map(|b| format!("{b:02x}")).collect();
+    Some(address.join(":"))
+}
+
+/// Parse the hardware and firmware versions from a DualSense firmware info
+/// feature report.
+pub fn parse_dualsense_firmware_info(report: &[u8]) -> Option<(String, String)> {
+    let hardware = u32::from_le_bytes(report.get(24..28)?.try_into().ok()?);
+    let firmware = u32::from_le_bytes(report.get(28..32)?.try_into().ok()?);
+    Some((format!("0x{hardware:08x}"), format!("0x{firmware:08x}")))
+}
+
+/// Parse the hardware and firmware versions from a DualShock 4 firmware info
+/// feature report.
+pub fn parse_dualshock4_firmware_info(report: &[u8]) -> Option<(String, String)> {
+    let hardware = u16::from_le_bytes(report.get(35..37)?.try_into().ok()?);
+    let firmware = u16::from_le_bytes(report.get(41..43)?.try_into().ok()?);
+    Some((format!("0x{hardware:04x}"), format!("0x{firmware:04x}")))
+}
+
+/// Parse the serial number from a Steam Deck string attribute feature report.
+/// E.g. [0x00, 0xae, 0x15, 0x01, 'F', 'V', 'A', ...]
+pub fn parse_steam_deck_serial(report: &[u8]) -> Option<String> {
+    if report.get(1) != Some(&(steam_deck::hid_report::ReportType::GetSerial as u8)) {
+        return None;
+    }
+    if report.get(3) != Some(&DECK_ATTRIB_STR_UNIT_SERIAL) {
+        return None;
+    }
+    let bytes = report.get(4..)?;
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    let serial = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+    if serial.is_empty() {
+        return None;
+    }
+    Some(serial)
+}
@@ -0,0 +1,49 @@
+use crate::drivers::device_info::{
+    parse_dualsense_firmware_info, parse_dualshock4_firmware_info, parse_sony_mac_address,
+    parse_steam_deck_serial,
+};
+
+#[test]
+fn test_parse_sony_mac_address() {
+    let report = [0x09, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x08];
+    let address = parse_sony_mac_address(&report);
+    assert_eq!(address, Some("11:22:33:44:55:66".to_string()));
+
+    let report = [0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(parse_sony_mac_address(&report), None);
+    assert_eq!(parse_sony_mac_address(&[0x09, 0x01]), None);
+}
+
+#[test]
+fn test_parse_firmware_info() {
+    let mut report = [0; 64];
+    report[24..28].copy_from_slice(&0x00000617u32.to_le_bytes());
+    report[28..32].copy_from_slice(&0x01000029u32.to_le_bytes());
+    let versions = parse_dualsense_firmware_info(&report);
+    assert_eq!(
+        versions,
+        Some(("0x00000617".to_string(), "0x01000029".to_string()))
+    );
+
+    let mut report = [0; 49];
+    report[35..37].copy_from_slice(&0x0100u16.to_le_bytes());
+    report[41..43].copy_from_slice(&0x08b4u16.to_le_bytes());
+    let versions = parse_dualshock4_firmware_info(&report);
+    assert_eq!(versions, Some(("0x0100".to_string(), "0x08b4".to_string())));
+
+    assert_eq!(parse_dualsense_firmware_info(&report[..20]), None);
+}
+
+#[test]
+fn test_parse_steam_deck_serial() {
+    let mut report = [0; 65];
+    report[1] = 0xae;
+    report[2] = 0x15;
+    report[3] = 0x01;
+    report[4..14].copy_from_slice(b"FVAA123456");
+    let serial = parse_steam_deck_serial(&report);
+    assert_eq!(serial, Some("FVAA123456".to_string()));
+
+    report[3] = 0x00;
+    assert_eq!(parse_steam_deck_serial(&report), None);
+}
@@ -56,6 +56,9 @@ pub const DS5_ACC_RES_PER_G: u32 = 8192;
 pub const DS5_TOUCHPAD_WIDTH: f64 = 1920.0;
 pub const DS5_TOUCHPAD_HEIGHT: f64 = 1080.0;
 
+// Default lightbar color
+pub const DEFAULT_LED_COLOR: (u8, u8, u8) = (100, 72, 42);
+
 /// PS5 Dualsense controller driver for reading gamepad input
 pub struct Driver {
     state: Option<PackedInputDataReport>,
@@ -64,6 +67,13 @@ pub struct Driver {
     last_touch: Instant,
     device: HidDevice,
     leds_initialized: bool,
+    /// Lightbar color to set once the LEDs are initialized
+    led_color: (u8, u8, u8),
+    /// Player LEDs to set once the LEDs are initialized
+    player_lights: [bool; 5],
+    /// Length of the input reports the device sends, if it differs from
+    /// the expected length
+    report_length: Option<usize>,
 }
 
 impl Driver {
@@ -86,14 +96,34 @@ impl Driver {
             touch_state: [false, false],
             last_touch: Instant::now(),
             leds_initialized: false,
+            led_color: DEFAULT_LED_COLOR,
+            player_lights: [false; 5],
+            report_length: None,
         })
     }
 
+    /// Set the length of the input reports the device sends, for controllers
+    /// that send reports of a different length than the spec. Reports of this
+    /// length are padded with zeros or truncated to the expected length.
+    pub fn set_report_length(&mut self, length: usize) {
+        self.report_length = Some(length);
+    }
+
     /// Poll the device and read input reports
     pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
         // Read data from the device into a buffer
         let mut buf = [0; INPUT_REPORT_BT_SIZE];
-        let bytes_read = self.device.read(&mut buf[..])?;
+        let mut bytes_read = self.device.read(&mut buf[..])?;
+
+        // Resize reports of devices that are known to send reports with a
+        // different length. The buffer is zeroed, so short reports are padded.
+        if self.report_length == Some(bytes_read) {
+            bytes_read = match buf[0] {
+                INPUT_REPORT_USB => INPUT_REPORT_USB_SIZE,
+                INPUT_REPORT_BT => INPUT_REPORT_BT_SIZE,
+                _ => bytes_read,
+            };
+        }
         let slice = &buf[..bytes_read];
 
         // Handle the incoming input report
@@ -145,6 +175,39 @@ impl Driver {
         self.write(state)
     }
 
+    /// Set the player indicator LEDs and lightbar color of the gamepad. If the
+    /// LEDs have not been initialized yet, they will be set once they are.
+    pub fn set_player_indicator(
+        &mut self,
+        lights: [bool; 5],
+        color: (u8, u8, u8),
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.player_lights = lights;
+        self.led_color = color;
+        if !self.leds_initialized {
+            return Ok(());
+        }
+        let (r, g, b) = color;
+        self.set_led_color(r, g, b)?;
+        self.write_player_lights()
+    }
+
+    /// Write the current player indicator LEDs to the gamepad
+    fn write_player_lights(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let [light_1, light_2, light_3, light_4, light_5] = self.player_lights;
+        let state = SetStatePackedOutputData {
+            allow_player_indicators: true,
+            player_light_1: light_1,
+            player_light_2: light_2,
+            player_light_3: light_3,
+            player_light_4: light_4,
+            player_light_5: light_5,
+            ..Default::default()
+        };
+
+        self.write(state)
+    }
+
     /// Use rumble emulation to rumble the gamepad
     pub fn rumble(
         &self,
@@ -178,8 +241,9 @@ impl Driver {
             log::debug!("Initializing LEDs");
             self.reset_lights()?;
             self.leds_initialized = true;
-            // TODO: Remove this after adding LED support
-            self.set_led_color(100, 72, 42)?;
+            let (r, g, b) = self.led_color;
+            self.set_led_color(r, g, b)?;
+            self.write_player_lights()?;
         }
 
         // Print input report for debugging
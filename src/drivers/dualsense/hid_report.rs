@@ -16,7 +16,9 @@ pub enum PackedInputDataReport {
 
 impl PackedInputDataReport {
     pub fn unpack(buf: &[u8], size: usize) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let report_id = buf[0];
+        let Some(report_id) = buf.first().copied() else {
+            return Err("Empty input report".into());
+        };
         match report_id {
             INPUT_REPORT_USB => {
                 // Validate the size of the report
@@ -0,0 +1,165 @@
+//! Fuzzing harness for hidraw report parsers
+//!
+//! Each [FuzzTarget] feeds an arbitrary byte buffer into the report parsers
+//! of a hidraw driver, the same way a (possibly flaky) device would. Parsers
+//! are expected to return errors for malformed reports; any panic is treated
+//! as a failure by the fuzzer. The cargo-fuzz targets in the `fuzz` directory
+//! call these functions.
+
+use std::{collections::BTreeSet, fmt::Debug};
+
+use packed_struct::{PackedStruct, PackedStructSlice};
+
+use super::{
+    dualsense, fts3528, gamecube_adapter, horipad_steam, joycon, lego, legos, opineo, oxp_hid,
+    steam_deck, xpad_uhid, zotac_zone,
+};
+
+/// Function that parses an arbitrary byte buffer as device reports
+pub type FuzzTarget = fn(&[u8]);
+
+/// All available fuzz targets by name
+pub const TARGETS: &[(&str, FuzzTarget)] = &[
+    ("dualsense", fuzz_dualsense),
+    ("fts3528", fuzz_fts3528),
+    ("gamecube_adapter", fuzz_gamecube_adapter),
+    ("horipad_steam", fuzz_horipad_steam),
+    ("joycon", fuzz_joycon),
+    ("lego", fuzz_lego),
+    ("legos", fuzz_legos),
+    ("opineo", fuzz_opineo),
+    ("oxp_hid", fuzz_oxp_hid),
+    ("steam_deck", fuzz_steam_deck),
+    ("xpad_uhid", fuzz_xpad_uhid),
+    ("zotac_zone", fuzz_zotac_zone),
+];
+
+/// Returns the fuzz target with the given name
+pub fn get_target(name: &str) -> Option<FuzzTarget> {
+    TARGETS
+        .iter()
+        .find(|(target, _)| *target == name)
+        .map(|(_, target)| *target)
+}
+
+/// Unpack the given data as a report of type [T]. Drivers read reports into
+/// fixed size buffers, so the data is truncated or zero-padded to the size of
+/// the report like a short or long read would be.
+fn unpack_report<T>(data: &[u8])
+where
+    T: PackedStruct + PackedStructSlice + Debug,
+{
+    let Ok(size) = T::packed_bytes_size(None) else {
+        return;
+    };
+    let mut buf = vec![0; size];
+    let len = data.len().min(size);
+    buf[..len].copy_from_slice(&data[..len]);
+
+    // Formatting exercises any custom Debug implementations of the report
+    if let Ok(report) = T::unpack_from_slice(&buf) {
+        let _ = format!("{report:?}");
+    }
+}
+
+/// Parse the given data as DualSense input reports
+pub fn fuzz_dualsense(data: &[u8]) {
+    use dualsense::{
+        driver::{INPUT_REPORT_BT_SIZE, INPUT_REPORT_USB_SIZE},
+        hid_report::{PackedInputDataReport, USBPackedInputDataReport},
+    };
+
+    // Reports are validated against the number of bytes read
+    for size in [data.len(), INPUT_REPORT_USB_SIZE, INPUT_REPORT_BT_SIZE] {
+        if let Ok(report) = PackedInputDataReport::unpack(data, size) {
+            let _ = format!("{:?}", report.state());
+        }
+    }
+    unpack_report::<USBPackedInputDataReport>(data);
+}
+
+/// Parse the given data as FTS3528 touchscreen input reports
+pub fn fuzz_fts3528(data: &[u8]) {
+    unpack_report::<fts3528::hid_report::PackedInputDataReport>(data);
+}
+
+/// Parse the given data as GameCube adapter input reports for every port
+pub fn fuzz_gamecube_adapter(data: &[u8]) {
+    use gamecube_adapter::driver::{translate, unpack_port, PORTS};
+
+    for port in 1..=PORTS {
+        if let Ok(report) = unpack_port(data, port) {
+            let _ = translate(&report, None);
+        }
+    }
+}
+
+/// Parse the given data as Horipad Steam input reports
+pub fn fuzz_horipad_steam(data: &[u8]) {
+    unpack_report::<horipad_steam::hid_report::PackedInputDataReport>(data);
+}
+
+/// Parse the given data as Nintendo Joy-Con input reports
+pub fn fuzz_joycon(data: &[u8]) {
+    unpack_report::<joycon::hid_report::PackedInputDataReport>(data);
+}
+
+/// Parse the given data as Legion Go input reports
+pub fn fuzz_lego(data: &[u8]) {
+    use lego::hid_report::*;
+
+    unpack_report::<XInputDataReport>(data);
+    unpack_report::<KeyboardDataReport>(data);
+    unpack_report::<DInputDataFullReport>(data);
+    unpack_report::<DInputDataLeftReport>(data);
+    unpack_report::<DInputDataRightReport>(data);
+    unpack_report::<MouseDataReport>(data);
+    unpack_report::<TouchpadDataReport>(data);
+}
+
+/// Parse the given data as Legion Go S input reports
+pub fn fuzz_legos(data: &[u8]) {
+    unpack_report::<legos::hid_report::XInputDataReport>(data);
+    unpack_report::<legos::hid_report::InertialInputDataReport>(data);
+}
+
+/// Parse the given data as OrangePi NEO touchpad input reports
+pub fn fuzz_opineo(data: &[u8]) {
+    unpack_report::<opineo::hid_report::TouchpadDataReport>(data);
+}
+
+/// Parse the given data as a sequence of OneXPlayer keyboard reports and
+/// decode any key bursts.
+pub fn fuzz_oxp_hid(data: &[u8]) {
+    use oxp_hid::burst::{decode_burst, parse_keyboard_report};
+
+    let mut keys = BTreeSet::new();
+    for report in data.chunks(8) {
+        keys.extend(parse_keyboard_report(report));
+        let _ = decode_burst(&keys);
+    }
+}
+
+/// Parse the given data as Steam Deck input reports
+pub fn fuzz_steam_deck(data: &[u8]) {
+    unpack_report::<steam_deck::hid_report::PackedInputDataReport>(data);
+}
+
+/// Parse the given data as Xbox controller input reports
+pub fn fuzz_xpad_uhid(data: &[u8]) {
+    unpack_report::<xpad_uhid::hid_report::XBoxSeriesInputDataReport>(data);
+    unpack_report::<xpad_uhid::hid_report::DInputDataReport>(data);
+}
+
+/// Parse the given data as Zotac Zone vendor input reports
+pub fn fuzz_zotac_zone(data: &[u8]) {
+    use zotac_zone::{driver::translate, hid_report::PackedInputDataReport};
+
+    unpack_report::<PackedInputDataReport>(data);
+    let mut buf = [0; 16];
+    let len = data.len().min(buf.len());
+    buf[..len].copy_from_slice(&data[..len]);
+    if let Ok(report) = PackedInputDataReport::unpack(&buf) {
+        let _ = translate(&report, None);
+    }
+}
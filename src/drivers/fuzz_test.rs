@@ -0,0 +1,28 @@
+use super::fuzz::{get_target, TARGETS};
+
+/// Malformed reports that flaky hardware could send
+fn malformed_reports() -> Vec<Vec<u8>> {
+    let mut reports = vec![Vec::new(), vec![0x01], vec![0x31]];
+    for size in [8, 10, 16, 63, 64, 65, 78, 287, 512] {
+        reports.push(vec![0x00; size]);
+        reports.push(vec![0xff; size]);
+        reports.push((0..size).map(|i| i as u8).collect());
+    }
+    reports
+}
+
+#[test]
+fn test_fuzz_targets_with_malformed_reports() {
+    for (name, target) in TARGETS {
+        for report in malformed_reports() {
+            println!("Fuzzing {name} with {} bytes", report.len());
+            target(report.as_slice());
+        }
+    }
+}
+
+#[test]
+fn test_get_target() {
+    assert!(get_target("dualsense").is_some());
+    assert!(get_target("unknown").is_none());
+}
@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, error::Error, ffi::CString, sync::Mutex};
+
+use hidapi::HidDevice;
+use packed_struct::PackedStructSlice;
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{
+        BinaryInput, ButtonEvent, Event, JoystickEvent, JoystickInput, TriggerEvent, TriggerInput,
+    },
+    hid_report::{PackedPortReport, STICK_CENTER},
+};
+
+// Hardware ID's
+pub const VID: u16 = 0x057e;
+pub const PID: u16 = 0x0337;
+
+// Report IDs
+pub const INPUT_REPORT_ID: u8 = 0x21;
+pub const RUMBLE_REPORT_ID: u8 = 0x11;
+pub const INIT_REPORT_ID: u8 = 0x13;
+
+/// Number of controller ports on the adapter
+pub const PORTS: u8 = 4;
+
+// Input report sizes
+const INPUT_PACKET_SIZE: usize = 37;
+const PORT_REPORT_SIZE: usize = 9;
+const PACKET_SIZE: usize = 64;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+// Input report axis ranges. The sticks physically reach about 100 units
+// from the center.
+pub const STICK_RANGE: f64 = 100.0;
+pub const TRIGGER_MAX: f64 = 255.0;
+
+/// Rumble state of every port by the devnode of the adapter. Each port is
+/// read by its own driver instance, but the rumble of all ports is set with
+/// a single report, so the state has to be shared to avoid ports turning off
+/// each other's rumble.
+static RUMBLE_STATE: Mutex<BTreeMap<String, [bool; PORTS as usize]>> = Mutex::new(BTreeMap::new());
+
+/// Driver for a single port of the official GameCube controller adapter
+/// (WUP-028). The adapter reports all four ports in a single input report,
+/// so a driver instance is created for each port, which only emits events for
+/// the controller connected to that port.
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Devnode of the adapter
+    path: String,
+    /// Port of the adapter, starting at 1
+    port: u8,
+    /// Last state of the port
+    state: Option<PackedPortReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice, port: u8) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        if !(1..=PORTS).contains(&port) {
+            return Err(format!("Invalid GameCube adapter port: {port}").into());
+        }
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID || info.product_id() != PID {
+            return Err(format!("Device '{path}' is not a GameCube adapter").into());
+        }
+
+        // The adapter only sends input reports after it has been initialized
+        device.write(&[INIT_REPORT_ID])?;
+
+        Ok(Self {
+            device,
+            path,
+            port,
+            state: None,
+        })
+    }
+
+    /// Returns the port of the adapter this driver reads, starting at 1
+    pub fn port(&self) -> u8 {
+        self.port
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+        if bytes_read != INPUT_PACKET_SIZE || buf[0] != INPUT_REPORT_ID {
+            log::trace!("Ignoring unknown report: {:?}", &buf[..bytes_read]);
+            return Ok(vec![]);
+        }
+
+        let report = unpack_port(&buf[..INPUT_PACKET_SIZE], self.port)?;
+        let old_state = self.state.replace(report);
+        if old_state.map(|old| old.is_connected()) != Some(report.is_connected()) {
+            let status = if report.is_connected() {
+                "connected"
+            } else {
+                "disconnected"
+            };
+            log::info!("Controller {status} on GameCube adapter port {}", self.port);
+        }
+
+        Ok(translate(&report, old_state.as_ref()))
+    }
+
+    /// Turn the rumble motor of the controller on or off. The controllers
+    /// only have a single motor without intensity control.
+    pub fn rumble(&mut self, enabled: bool) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut rumble_state = RUMBLE_STATE.lock().map_err(|e| e.to_string())?;
+        let ports = rumble_state.entry(self.path.clone()).or_default();
+        let index = self.port as usize - 1;
+        if ports[index] == enabled {
+            return Ok(());
+        }
+        ports[index] = enabled;
+
+        let mut report = [0; PORTS as usize + 1];
+        report[0] = RUMBLE_REPORT_ID;
+        for (i, enabled) in ports.iter().enumerate() {
+            report[i + 1] = *enabled as u8;
+        }
+        self.device.write(&report)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Driver {
+    /// Forget the rumble state of the port so a new driver instance for the
+    /// same devnode starts with the motor off
+    fn drop(&mut self) {
+        let Ok(mut rumble_state) = RUMBLE_STATE.lock() else {
+            return;
+        };
+        let Some(ports) = rumble_state.get_mut(&self.path) else {
+            return;
+        };
+        ports[self.port as usize - 1] = false;
+        if ports.iter().all(|enabled| !enabled) {
+            rumble_state.remove(&self.path);
+        }
+    }
+}
+
+/// Unpack the state of the given port, starting at 1, from the given input
+/// report of the adapter
+pub fn unpack_port(
+    data: &[u8],
+    port: u8,
+) -> Result<PackedPortReport, Box<dyn Error + Send + Sync>> {
+    let start = 1 + (port as usize).saturating_sub(1) * PORT_REPORT_SIZE;
+    let Some(slice) = data.get(start..start + PORT_REPORT_SIZE) else {
+        return Err(format!("Input report is too short for port {port}").into());
+    };
+    Ok(PackedPortReport::unpack_from_slice(slice)?)
+}
+
+/// Translate the given port state into events. Ports without a connected
+/// controller are treated as a controller in its neutral state, so all
+/// inputs are released when a controller is unplugged.
+pub fn translate(state: &PackedPortReport, old_state: Option<&PackedPortReport>) -> Vec<Event> {
+    let mut events = Vec::new();
+    let neutral = PackedPortReport::default();
+    let state = if state.is_connected() {
+        state
+    } else {
+        &neutral
+    };
+    let old = match old_state {
+        Some(old) if old.is_connected() => old,
+        _ => &neutral,
+    };
+
+    let buttons: [(bool, bool, fn(BinaryInput) -> ButtonEvent); 12] = [
+        (state.a, old.a, ButtonEvent::A),
+        (state.b, old.b, ButtonEvent::B),
+        (state.x, old.x, ButtonEvent::X),
+        (state.y, old.y, ButtonEvent::Y),
+        (state.start, old.start, ButtonEvent::Start),
+        (state.z, old.z, ButtonEvent::Z),
+        (state.l, old.l, ButtonEvent::L),
+        (state.r, old.r, ButtonEvent::R),
+        (state.dpad_up, old.dpad_up, ButtonEvent::DPadUp),
+        (state.dpad_right, old.dpad_right, ButtonEvent::DPadRight),
+        (state.dpad_down, old.dpad_down, ButtonEvent::DPadDown),
+        (state.dpad_left, old.dpad_left, ButtonEvent::DPadLeft),
+    ];
+    for (pressed, was_pressed, button) in buttons {
+        if pressed != was_pressed {
+            events.push(Event::Button(button(BinaryInput { pressed })));
+        }
+    }
+
+    if state.stick_x != old.stick_x || state.stick_y != old.stick_y {
+        let value = normalize_stick(state.stick_x, state.stick_y);
+        events.push(Event::Joystick(JoystickEvent::Stick(value)));
+    }
+    if state.c_stick_x != old.c_stick_x || state.c_stick_y != old.c_stick_y {
+        let value = normalize_stick(state.c_stick_x, state.c_stick_y);
+        events.push(Event::Joystick(JoystickEvent::CStick(value)));
+    }
+
+    if state.l_trigger != old.l_trigger {
+        let value = state.l_trigger as f64 / TRIGGER_MAX;
+        events.push(Event::Trigger(TriggerEvent::L(TriggerInput { value })));
+    }
+    if state.r_trigger != old.r_trigger {
+        let value = state.r_trigger as f64 / TRIGGER_MAX;
+        events.push(Event::Trigger(TriggerEvent::R(TriggerInput { value })));
+    }
+
+    events
+}
+
+/// Normalize the given raw stick position. The Y axis is inverted, so up is
+/// negative like other gamepads.
+fn normalize_stick(raw_x: u8, raw_y: u8) -> JoystickInput {
+    let normalize = |raw: u8| ((raw as f64 - STICK_CENTER as f64) / STICK_RANGE).clamp(-1.0, 1.0);
+    JoystickInput {
+        x: normalize(raw_x),
+        y: -normalize(raw_y),
+    }
+}
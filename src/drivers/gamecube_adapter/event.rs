@@ -0,0 +1,74 @@
+/// Events that can be emitted by a controller connected to a port of the
+/// GameCube adapter
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Joystick(JoystickEvent),
+    Trigger(TriggerEvent),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Joystick input is a double (x, y) axis that has already been normalized
+/// to a value between -1.0 and 1.0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JoystickInput {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Trigger input is the analog travel of a shoulder button normalized to a
+/// value between 0.0 and 1.0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriggerInput {
+    pub value: f64,
+}
+
+/// Button events represent binary inputs
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonEvent {
+    /// A Button
+    A(BinaryInput),
+    /// B Button
+    B(BinaryInput),
+    /// X Button
+    X(BinaryInput),
+    /// Y Button
+    Y(BinaryInput),
+    /// Start/Pause button
+    Start(BinaryInput),
+    /// Z button above the right shoulder button
+    Z(BinaryInput),
+    /// Digital click at the end of the left shoulder button travel
+    L(BinaryInput),
+    /// Digital click at the end of the right shoulder button travel
+    R(BinaryInput),
+    /// DPad up
+    DPadUp(BinaryInput),
+    /// DPad right
+    DPadRight(BinaryInput),
+    /// DPad down
+    DPadDown(BinaryInput),
+    /// DPad left
+    DPadLeft(BinaryInput),
+}
+
+/// Joystick events contain the normalized position of the sticks
+#[derive(Clone, Debug, PartialEq)]
+pub enum JoystickEvent {
+    /// Main control stick
+    Stick(JoystickInput),
+    /// Yellow C-stick
+    CStick(JoystickInput),
+}
+
+/// Trigger events contain the analog travel of the shoulder buttons
+#[derive(Clone, Debug, PartialEq)]
+pub enum TriggerEvent {
+    L(TriggerInput),
+    R(TriggerInput),
+}
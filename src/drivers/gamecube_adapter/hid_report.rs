@@ -0,0 +1,104 @@
+//! Reference: https://github.com/ToadKing/wii-u-gc-adapter
+use packed_struct::prelude::*;
+
+/// Neutral position of the sticks in input reports
+pub const STICK_CENTER: u8 = 0x80;
+
+/// State of the controller connected to a single port of the adapter. The
+/// input report of the adapter contains one of these for each port.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "9")]
+pub struct PackedPortReport {
+    // byte 0
+    /// A wireless (WaveBird) controller is connected
+    #[packed_field(bits = "2")]
+    pub wireless: bool,
+    /// A wired controller is connected
+    #[packed_field(bits = "3")]
+    pub wired: bool,
+    /// The second USB cable of the adapter is plugged in, which is required
+    /// to power the rumble motors
+    #[packed_field(bits = "5")]
+    pub rumble_power: bool,
+
+    // byte 1
+    #[packed_field(bits = "8")]
+    pub dpad_up: bool,
+    #[packed_field(bits = "9")]
+    pub dpad_down: bool,
+    #[packed_field(bits = "10")]
+    pub dpad_right: bool,
+    #[packed_field(bits = "11")]
+    pub dpad_left: bool,
+    #[packed_field(bits = "12")]
+    pub y: bool,
+    #[packed_field(bits = "13")]
+    pub x: bool,
+    #[packed_field(bits = "14")]
+    pub b: bool,
+    #[packed_field(bits = "15")]
+    pub a: bool,
+
+    // byte 2
+    #[packed_field(bits = "20")]
+    pub l: bool,
+    #[packed_field(bits = "21")]
+    pub r: bool,
+    #[packed_field(bits = "22")]
+    pub z: bool,
+    #[packed_field(bits = "23")]
+    pub start: bool,
+
+    // byte 3-6
+    /// Stick positions where up and right are positive
+    #[packed_field(bytes = "3")]
+    pub stick_x: u8,
+    #[packed_field(bytes = "4")]
+    pub stick_y: u8,
+    #[packed_field(bytes = "5")]
+    pub c_stick_x: u8,
+    #[packed_field(bytes = "6")]
+    pub c_stick_y: u8,
+
+    // byte 7-8
+    /// Analog travel of the shoulder buttons
+    #[packed_field(bytes = "7")]
+    pub l_trigger: u8,
+    #[packed_field(bytes = "8")]
+    pub r_trigger: u8,
+}
+
+impl PackedPortReport {
+    /// Returns true if a controller is connected to the port
+    pub fn is_connected(&self) -> bool {
+        self.wired || self.wireless
+    }
+}
+
+impl Default for PackedPortReport {
+    fn default() -> Self {
+        Self {
+            wireless: false,
+            wired: false,
+            rumble_power: false,
+            dpad_up: false,
+            dpad_down: false,
+            dpad_right: false,
+            dpad_left: false,
+            y: false,
+            x: false,
+            b: false,
+            a: false,
+            l: false,
+            r: false,
+            z: false,
+            start: false,
+            stick_x: STICK_CENTER,
+            stick_y: STICK_CENTER,
+            c_stick_x: STICK_CENTER,
+            c_stick_y: STICK_CENTER,
+            l_trigger: 0,
+            r_trigger: 0,
+        }
+    }
+}
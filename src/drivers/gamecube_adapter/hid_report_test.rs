@@ -0,0 +1,79 @@
+use std::error::Error;
+
+use packed_struct::PackedStruct;
+
+use super::{
+    driver::{translate, unpack_port},
+    event::{BinaryInput, ButtonEvent, Event, JoystickEvent, JoystickInput},
+    hid_report::PackedPortReport,
+};
+
+#[tokio::test]
+async fn test_gamecube_adapter_port_report() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let report = PackedPortReport {
+        wired: true,
+        a: true,
+        dpad_up: true,
+        start: true,
+        stick_x: 0xff,
+        ..Default::default()
+    };
+    let data = report.pack()?;
+    assert_eq!(data[0], 0x10, "Wired controllers should be 0x10 in byte 0");
+    assert_eq!(
+        data[1], 0b1000_0001,
+        "A and DPad up should be set in byte 1"
+    );
+    assert_eq!(data[2], 0b0000_0001, "Start should be bit 0 of byte 2");
+
+    // Each port should be read from its own part of the input report
+    let mut input = [0; 37];
+    input[0] = 0x21;
+    input[19..28].copy_from_slice(&data);
+    let port = unpack_port(&input, 3)?;
+    assert_eq!(port, report);
+    assert!(!unpack_port(&input, 1)?.is_connected());
+    assert!(unpack_port(&input[..30], 4).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gamecube_adapter_translate() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let old = PackedPortReport {
+        wired: true,
+        ..Default::default()
+    };
+    let report = PackedPortReport {
+        wired: true,
+        b: true,
+        stick_y: 0x80 + 100,
+        ..Default::default()
+    };
+
+    // Pushing the stick up should be negative like other gamepads
+    let events = translate(&report, Some(&old));
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        &events[0],
+        Event::Button(ButtonEvent::B(BinaryInput { pressed: true }))
+    ));
+    assert!(matches!(
+        &events[1],
+        Event::Joystick(JoystickEvent::Stick(value)) if *value == JoystickInput { x: 0.0, y: -1.0 }
+    ));
+
+    // Unplugging a controller should release all of its inputs
+    let events = translate(&PackedPortReport::default(), Some(&report));
+    assert_eq!(events.len(), 2);
+    assert!(matches!(
+        &events[0],
+        Event::Button(ButtonEvent::B(BinaryInput { pressed: false }))
+    ));
+    assert!(matches!(
+        &events[1],
+        Event::Joystick(JoystickEvent::Stick(value)) if *value == JoystickInput::default()
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod driver;
+pub mod event;
+pub mod hid_report;
+#[cfg(test)]
+pub mod hid_report_test;
@@ -0,0 +1,77 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{Event, SwitchMode},
+    report::{translate, InputReport},
+};
+
+// Hardware ID's
+pub const VID: u16 = 0x2f24;
+pub const PID: u16 = 0x0135;
+
+// Interface number of the vendor interface
+pub const VENDOR_IFACE: i32 = 2;
+
+// Input report size
+const PACKET_SIZE: usize = 64;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+/// GPD Win 4 / Win Mini vendor interface driver for reading the position of
+/// the hardware mouse/gamepad switch and the extra buttons
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Last vendor input report
+    state: Option<InputReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID || info.product_id() != PID {
+            return Err(format!("Device '{path}' is not a GPD vendor interface").into());
+        }
+
+        Ok(Self {
+            device,
+            state: None,
+        })
+    }
+
+    /// Returns the last known position of the mouse/gamepad switch
+    pub fn mode(&self) -> Option<SwitchMode> {
+        self.state.map(|state| state.mode)
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        let slice = &buf[..bytes_read];
+        let Some(report) = InputReport::parse(slice) else {
+            log::trace!("Ignoring unknown report: {slice:?}");
+            return Ok(vec![]);
+        };
+        let old_state = self.state.replace(report);
+        if old_state.map(|state| state.mode) != Some(report.mode) {
+            log::info!("GPD mouse/gamepad switch is in {:?} mode", report.mode);
+        }
+
+        Ok(translate(&report, old_state.as_ref()))
+    }
+}
@@ -0,0 +1,32 @@
+/// Events that can be emitted by the GPD vendor interface
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Button(ButtonEvent),
+    /// The hardware mouse/gamepad switch changed position
+    Mode(SwitchMode),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represent binary inputs
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonEvent {
+    /// Left back button (L4)
+    LeftBack(BinaryInput),
+    /// Right back button (R4)
+    RightBack(BinaryInput),
+}
+
+/// Position of the hardware mouse/gamepad switch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwitchMode {
+    /// The controller emits gamepad events
+    #[default]
+    Gamepad,
+    /// The controller emulates a mouse and keyboard
+    Mouse,
+}
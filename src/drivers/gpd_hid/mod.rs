@@ -0,0 +1,5 @@
+pub mod driver;
+pub mod event;
+pub mod report;
+#[cfg(test)]
+pub mod report_test;
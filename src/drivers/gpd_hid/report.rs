@@ -0,0 +1,93 @@
+use super::event::{BinaryInput, ButtonEvent, Event, SwitchMode};
+
+// Report ID
+pub const INPUT_REPORT_ID: u8 = 0x01;
+
+// Input report size
+pub const INPUT_PACKET_SIZE: usize = 8;
+
+// Byte offsets in the input report
+const MODE_OFFSET: usize = 1;
+const BUTTONS_OFFSET: usize = 2;
+
+// Switch positions
+const MODE_GAMEPAD: u8 = 0x00;
+const MODE_MOUSE: u8 = 0x01;
+
+// Button bits
+const LEFT_BACK_BIT: u8 = 0b0000_0001;
+const RIGHT_BACK_BIT: u8 = 0b0000_0010;
+
+/// Vendor input report with the state of the mouse/gamepad switch and the
+/// extra buttons.
+///
+/// | Byte | Description                                  |
+/// |------|----------------------------------------------|
+/// | 0    | Report ID (0x01)                             |
+/// | 1    | Switch position (0x00 gamepad, 0x01 mouse)   |
+/// | 2    | Buttons (bit 0 left back, bit 1 right back)  |
+/// | 3-7  | Reserved                                     |
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputReport {
+    pub mode: SwitchMode,
+    pub left_back: bool,
+    pub right_back: bool,
+}
+
+impl InputReport {
+    /// Parse the given input report. Returns None if the report is not a
+    /// vendor input report.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() != INPUT_PACKET_SIZE || buf[0] != INPUT_REPORT_ID {
+            return None;
+        }
+        let mode = match buf[MODE_OFFSET] {
+            MODE_GAMEPAD => SwitchMode::Gamepad,
+            MODE_MOUSE => SwitchMode::Mouse,
+            value => {
+                log::trace!("Unknown switch position: {value:#04x}");
+                return None;
+            }
+        };
+        let buttons = buf[BUTTONS_OFFSET];
+        Some(Self {
+            mode,
+            left_back: buttons & LEFT_BACK_BIT != 0,
+            right_back: buttons & RIGHT_BACK_BIT != 0,
+        })
+    }
+}
+
+/// Translate the differences between the given input reports into events.
+/// If there is no previous report, the switch position is always emitted so
+/// the initial position is known.
+pub fn translate(report: &InputReport, old: Option<&InputReport>) -> Vec<Event> {
+    let Some(old) = old else {
+        // Compare against a report in the same mode without pressed buttons
+        let mut events = vec![Event::Mode(report.mode)];
+        let released = InputReport {
+            mode: report.mode,
+            ..Default::default()
+        };
+        events.extend(translate(report, Some(&released)));
+        return events;
+    };
+
+    let mut events = Vec::new();
+
+    if report.mode != old.mode {
+        events.push(Event::Mode(report.mode));
+    }
+    if report.left_back != old.left_back {
+        events.push(Event::Button(ButtonEvent::LeftBack(BinaryInput {
+            pressed: report.left_back,
+        })));
+    }
+    if report.right_back != old.right_back {
+        events.push(Event::Button(ButtonEvent::RightBack(BinaryInput {
+            pressed: report.right_back,
+        })));
+    }
+
+    events
+}
@@ -0,0 +1,45 @@
+use super::{
+    event::{BinaryInput, ButtonEvent, Event, SwitchMode},
+    report::{translate, InputReport},
+};
+
+#[test]
+fn test_parse_input_report() {
+    let report = InputReport::parse(&[0x01, 0x01, 0x02, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(report.mode, SwitchMode::Mouse);
+    assert!(!report.left_back);
+    assert!(report.right_back);
+
+    assert!(
+        InputReport::parse(&[0x02, 0x01, 0x02, 0, 0, 0, 0, 0]).is_none(),
+        "Other reports should be ignored"
+    );
+    assert!(InputReport::parse(&[0x01, 0x01]).is_none());
+    assert!(InputReport::parse(&[0x01, 0x07, 0x00, 0, 0, 0, 0, 0]).is_none());
+}
+
+#[test]
+fn test_translate_input_report() {
+    // The initial switch position should always be emitted
+    let report = InputReport::parse(&[0x01, 0x00, 0x01, 0, 0, 0, 0, 0]).unwrap();
+    let events = translate(&report, None);
+    assert_eq!(
+        events,
+        vec![
+            Event::Mode(SwitchMode::Gamepad),
+            Event::Button(ButtonEvent::LeftBack(BinaryInput { pressed: true })),
+        ]
+    );
+
+    // Only changes should be emitted afterwards
+    assert!(translate(&report, Some(&report)).is_empty());
+    let next = InputReport::parse(&[0x01, 0x01, 0x00, 0, 0, 0, 0, 0]).unwrap();
+    let events = translate(&next, Some(&report));
+    assert_eq!(
+        events,
+        vec![
+            Event::Mode(SwitchMode::Mouse),
+            Event::Button(ButtonEvent::LeftBack(BinaryInput { pressed: false })),
+        ]
+    );
+}
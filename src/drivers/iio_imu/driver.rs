@@ -90,6 +90,71 @@ impl Driver {
         })
     }
 
+    /// Set the sampling frequencies of the accelerometer and gyro. Each given
+    /// sample rate must be one of the available sample rates for the device.
+    pub fn set_sample_rates(
+        &mut self,
+        accel: Option<f64>,
+        gyro: Option<f64>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut result = Ok(());
+        if let Some(sample_rate) = accel {
+            result = set_sample_rate(&self.accel, &mut self.accel_info, sample_rate);
+        }
+        if let Some(sample_rate) = gyro {
+            let gyro_result = set_sample_rate(&self.gyro, &mut self.gyro_info, sample_rate);
+            result = result.and(gyro_result);
+        }
+        self.update_sample_delay();
+        result
+    }
+
+    /// Read the sampling frequency of all channels again, in case it was
+    /// changed while the device is running (e.g. over DBus). Returns true if
+    /// any sampling frequency changed.
+    pub fn refresh_sample_rates(&mut self) -> bool {
+        let mut changed = false;
+        let sensors = [
+            (&self.accel, &mut self.accel_info),
+            (&self.gyro, &mut self.gyro_info),
+        ];
+        for (channels, channel_info) in sensors {
+            for (id, channel) in channels.iter() {
+                let Some(info) = channel_info.get_mut(id) else {
+                    continue;
+                };
+                let Ok(sample_rate) = channel.attr_read_float("sampling_frequency") else {
+                    continue;
+                };
+                if sample_rate != info.sample_rate {
+                    log::debug!("Sample rate for channel {id} changed to {sample_rate}");
+                    info.sample_rate = sample_rate;
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            self.update_sample_delay();
+        }
+        changed
+    }
+
+    /// Update the sample delay to match the fastest sampling frequency of
+    /// all channels so no samples are missed.
+    fn update_sample_delay(&mut self) {
+        let sample_rate = self
+            .accel_info
+            .values()
+            .chain(self.gyro_info.values())
+            .map(|info| info.sample_rate)
+            .fold(0.0, f64::max);
+        if sample_rate <= 0.0 {
+            return;
+        }
+        self.sample_delay = Duration::from_secs_f64(1.0 / sample_rate);
+        log::debug!("Updated sample delay to {:?}", self.sample_delay);
+    }
+
     /// Poll the device for data
     pub fn poll(&self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
         let mut events = vec![];
@@ -189,6 +254,32 @@ impl Driver {
     }
 }
 
+/// Write the given sampling frequency to all of the given channels after
+/// validating it against the available sampling frequencies.
+fn set_sample_rate(
+    channels: &HashMap<String, Channel>,
+    channel_info: &mut HashMap<String, AxisInfo>,
+    sample_rate: f64,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    for (id, channel) in channels.iter() {
+        let Some(info) = channel_info.get_mut(id) else {
+            continue;
+        };
+        let Some(sample_rate) = info.find_sample_rate(sample_rate) else {
+            return Err(format!(
+                "Sample rate {sample_rate} is not supported by channel {id}. Available: {:?}",
+                info.sample_rates_avail
+            )
+            .into());
+        };
+        channel.attr_write_float("sampling_frequency", sample_rate)?;
+        log::debug!("Set sample rate for channel {id} to {sample_rate}");
+        info.sample_rate = sample_rate;
+    }
+
+    Ok(())
+}
+
 /// Returns all channels and channel information from the given device matching
 /// the given channel type.
 fn get_channels_with_type(
@@ -81,6 +81,24 @@ pub struct AxisInfo {
     pub scales_avail: Vec<f64>,
 }
 
+impl AxisInfo {
+    /// Returns the available sample rate that matches the given sample rate,
+    /// if the axis supports it.
+    pub fn find_sample_rate(&self, sample_rate: f64) -> Option<f64> {
+        find_sample_rate(self.sample_rates_avail.as_slice(), sample_rate)
+    }
+}
+
+/// Returns the value from the given available sample rates that matches the
+/// given sample rate. Sysfs reports frequencies with limited precision, so
+/// values are compared with a small tolerance.
+pub fn find_sample_rate(sample_rates_avail: &[f64], sample_rate: f64) -> Option<f64> {
+    sample_rates_avail
+        .iter()
+        .find(|avail| (*avail - sample_rate).abs() < 0.001)
+        .copied()
+}
+
 /// Scale and offset information for all axes
 #[derive(Clone, Debug, Default)]
 pub struct AxesInfo {
@@ -0,0 +1,407 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::{types::SizedInteger, PackedStructSlice};
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{
+        BinaryInput, ButtonEvent, Event, InertialEvent, InertialInput, JoystickEvent,
+        JoystickInput, TriggerEvent, TriggerInput,
+    },
+    hid_report::{encode_rumble, PackedInputDataReport, StickCalibration, RUMBLE_NEUTRAL},
+};
+
+// Report IDs
+pub const STANDARD_INPUT_REPORT_ID: u8 = 0x30;
+pub const SUBCOMMAND_REPLY_REPORT_ID: u8 = 0x21;
+pub const SUBCOMMAND_REPORT_ID: u8 = 0x01;
+pub const RUMBLE_REPORT_ID: u8 = 0x10;
+
+// Subcommands
+const SUBCOMMAND_SET_INPUT_REPORT_MODE: u8 = 0x03;
+const SUBCOMMAND_SPI_FLASH_READ: u8 = 0x10;
+const SUBCOMMAND_SET_PLAYER_LIGHTS: u8 = 0x30;
+const SUBCOMMAND_ENABLE_IMU: u8 = 0x40;
+const SUBCOMMAND_ENABLE_VIBRATION: u8 = 0x48;
+
+// SPI flash addresses of the stick calibration
+const SPI_USER_LEFT_STICK_CALIBRATION: u32 = 0x8010;
+const SPI_USER_RIGHT_STICK_CALIBRATION: u32 = 0x801B;
+const SPI_FACTORY_LEFT_STICK_CALIBRATION: u32 = 0x603D;
+const SPI_FACTORY_RIGHT_STICK_CALIBRATION: u32 = 0x6046;
+/// Magic bytes that prefix user stick calibration if it has been set
+const SPI_USER_CALIBRATION_MAGIC: [u8; 2] = [0xB2, 0xA1];
+
+// Input report size
+const PACKET_SIZE: usize = 49;
+// Size of the buffer used to read reports, which can be larger than the
+// standard input report (e.g. when NFC/IR data is included)
+const READ_BUFFER_SIZE: usize = 362;
+// Size of output reports sent to the device
+const OUTPUT_REPORT_SIZE: usize = 49;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+// Timeout waiting for a subcommand reply
+const SUBCOMMAND_TIMEOUT: i32 = 100;
+// Maximum number of reports to read while waiting for a subcommand reply
+const SUBCOMMAND_MAX_READS: usize = 20;
+
+pub const VID: u16 = 0x057E;
+pub const PID_LEFT: u16 = 0x2006;
+pub const PID_RIGHT: u16 = 0x2007;
+pub const PIDS: [u16; 2] = [PID_LEFT, PID_RIGHT];
+
+/// Which Joy-Con of a pair the device is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Which Joy-Con this is
+    side: Side,
+    /// Calibration of the stick read from SPI flash
+    calibration: StickCalibration,
+    /// Counter sent with every output report, which the Joy-Con uses to
+    /// detect duplicate reports
+    packet_counter: u8,
+    /// State for the device
+    state: Option<PackedInputDataReport>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+
+        let info = device.get_device_info()?;
+        let side = match (info.vendor_id(), info.product_id()) {
+            (VID, PID_LEFT) => Side::Left,
+            (VID, PID_RIGHT) => Side::Right,
+            _ => return Err(format!("Device '{path}' is not a Joy-Con").into()),
+        };
+
+        let mut driver = Self {
+            device,
+            side,
+            calibration: StickCalibration::default(),
+            packet_counter: 0,
+            state: None,
+        };
+        driver.initialize()?;
+
+        Ok(driver)
+    }
+
+    /// Returns which Joy-Con of a pair this device is
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Read the stick calibration and switch the Joy-Con to the full input
+    /// report mode with IMU data and rumble enabled.
+    fn initialize(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self.read_stick_calibration() {
+            Ok(calibration) => {
+                log::debug!(
+                    "Read {:?} Joy-Con stick calibration: {calibration:?}",
+                    self.side
+                );
+                self.calibration = calibration;
+            }
+            Err(e) => {
+                log::warn!("Failed to read Joy-Con stick calibration, using defaults: {e:?}");
+            }
+        }
+
+        self.send_subcommand(SUBCOMMAND_ENABLE_IMU, &[0x01])?;
+        self.send_subcommand(SUBCOMMAND_ENABLE_VIBRATION, &[0x01])?;
+        self.send_subcommand(
+            SUBCOMMAND_SET_INPUT_REPORT_MODE,
+            &[STANDARD_INPUT_REPORT_ID],
+        )?;
+
+        // Light the first player LED so the Joy-Con stops flashing
+        self.send_subcommand(SUBCOMMAND_SET_PLAYER_LIGHTS, &[0x01])?;
+
+        Ok(())
+    }
+
+    /// Read the user stick calibration from SPI flash, falling back to the
+    /// factory calibration if the user has not calibrated the stick.
+    fn read_stick_calibration(&mut self) -> Result<StickCalibration, Box<dyn Error + Send + Sync>> {
+        let side = self.side;
+        let (user_address, factory_address) = match side {
+            Side::Left => (
+                SPI_USER_LEFT_STICK_CALIBRATION,
+                SPI_FACTORY_LEFT_STICK_CALIBRATION,
+            ),
+            Side::Right => (
+                SPI_USER_RIGHT_STICK_CALIBRATION,
+                SPI_FACTORY_RIGHT_STICK_CALIBRATION,
+            ),
+        };
+        let parse = |data: &[u8]| -> Option<StickCalibration> {
+            let data: [u8; 9] = data.try_into().ok()?;
+            match side {
+                Side::Left => StickCalibration::from_left_spi(&data),
+                Side::Right => StickCalibration::from_right_spi(&data),
+            }
+        };
+
+        let user = self.read_spi(user_address, 11)?;
+        if user[..2] == SPI_USER_CALIBRATION_MAGIC {
+            if let Some(calibration) = parse(&user[2..]) {
+                return Ok(calibration);
+            }
+        }
+
+        let factory = self.read_spi(factory_address, 9)?;
+        parse(&factory).ok_or_else(|| "No stick calibration found in SPI flash".into())
+    }
+
+    /// Read the given number of bytes from SPI flash at the given address
+    fn read_spi(
+        &mut self,
+        address: u32,
+        size: u8,
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut args = address.to_le_bytes().to_vec();
+        args.push(size);
+        let reply = self.send_subcommand(SUBCOMMAND_SPI_FLASH_READ, &args)?;
+
+        // The reply echos the address and size before the data
+        let data = reply
+            .get(5..5 + size as usize)
+            .ok_or("SPI flash read reply is too short")?;
+        Ok(data.to_vec())
+    }
+
+    /// Send the given subcommand to the Joy-Con and wait for its reply.
+    /// Returns the data of the reply after the subcommand id.
+    fn send_subcommand(
+        &mut self,
+        subcommand: u8,
+        args: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut report = [0; OUTPUT_REPORT_SIZE];
+        report[0] = SUBCOMMAND_REPORT_ID;
+        report[1] = self.next_packet_counter();
+        report[2..6].copy_from_slice(&RUMBLE_NEUTRAL);
+        report[6..10].copy_from_slice(&RUMBLE_NEUTRAL);
+        report[10] = subcommand;
+        let len = args.len().min(OUTPUT_REPORT_SIZE - 11);
+        report[11..11 + len].copy_from_slice(&args[..len]);
+        self.device.write(&report)?;
+
+        // Wait for the reply, ignoring any input reports sent in the meantime
+        let mut buf = [0; READ_BUFFER_SIZE];
+        for _ in 0..SUBCOMMAND_MAX_READS {
+            let bytes_read = self.device.read_timeout(&mut buf[..], SUBCOMMAND_TIMEOUT)?;
+            if bytes_read < 15 || buf[0] != SUBCOMMAND_REPLY_REPORT_ID || buf[14] != subcommand {
+                continue;
+            }
+            // The high bit of the ACK byte is set if the subcommand succeeded
+            if buf[13] & 0x80 == 0 {
+                return Err(format!("Joy-Con rejected subcommand {subcommand:#04x}").into());
+            }
+            return Ok(buf[15..bytes_read].to_vec());
+        }
+
+        Err(format!("Timed out waiting for reply to subcommand {subcommand:#04x}").into())
+    }
+
+    /// Returns the next packet counter for output reports
+    fn next_packet_counter(&mut self) -> u8 {
+        let counter = self.packet_counter;
+        self.packet_counter = (self.packet_counter + 1) & 0x0F;
+        counter
+    }
+
+    /// Rumble the Joy-Con with the given strong and weak magnitudes
+    pub fn rumble(
+        &mut self,
+        strong_magnitude: u16,
+        weak_magnitude: u16,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let strong = strong_magnitude as f64 / u16::MAX as f64;
+        let weak = weak_magnitude as f64 / u16::MAX as f64;
+        let rumble = encode_rumble(weak, strong);
+
+        // Each Joy-Con only uses the rumble data for its own side
+        let mut report = [0; 10];
+        report[0] = RUMBLE_REPORT_ID;
+        report[1] = self.next_packet_counter();
+        report[2..6].copy_from_slice(&rumble);
+        report[6..10].copy_from_slice(&rumble);
+        self.device.write(&report)?;
+
+        Ok(())
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; READ_BUFFER_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+
+        let report_id = buf[0];
+        match report_id {
+            STANDARD_INPUT_REPORT_ID => (),
+            // Late replies to subcommands can be ignored
+            SUBCOMMAND_REPLY_REPORT_ID => return Ok(vec![]),
+            _ => {
+                log::warn!("Got unhandled report_id {report_id}, someone should look into that...");
+                return Ok(vec![]);
+            }
+        }
+        if bytes_read < PACKET_SIZE {
+            return Err(format!("Invalid input report size: {bytes_read}").into());
+        }
+
+        let input_report = PackedInputDataReport::unpack_from_slice(&buf[..PACKET_SIZE])?;
+
+        // Update the state
+        let old_state = self.update_state(input_report);
+
+        // Translate the state into a stream of input events
+        let events = self.translate_events(old_state);
+
+        Ok(events)
+    }
+
+    /// Update the input state
+    fn update_state(
+        &mut self,
+        input_report: PackedInputDataReport,
+    ) -> Option<PackedInputDataReport> {
+        let old_state = self.state;
+        self.state = Some(input_report);
+        old_state
+    }
+
+    /// Translate the state into individual events
+    fn translate_events(&self, old_state: Option<PackedInputDataReport>) -> Vec<Event> {
+        let mut events = Vec::new();
+        let Some(state) = self.state else {
+            return events;
+        };
+
+        // Translate state changes into events if they have changed
+        let Some(old_state) = old_state else {
+            return events;
+        };
+
+        // Binary events
+        let buttons = [
+            (
+                state.a,
+                old_state.a,
+                ButtonEvent::A as fn(BinaryInput) -> ButtonEvent,
+            ),
+            (state.b, old_state.b, ButtonEvent::B),
+            (state.x, old_state.x, ButtonEvent::X),
+            (state.y, old_state.y, ButtonEvent::Y),
+            (state.plus, old_state.plus, ButtonEvent::Plus),
+            (state.minus, old_state.minus, ButtonEvent::Minus),
+            (state.home, old_state.home, ButtonEvent::Home),
+            (state.capture, old_state.capture, ButtonEvent::Capture),
+            (state.l, old_state.l, ButtonEvent::L),
+            (state.zl, old_state.zl, ButtonEvent::ZL),
+            (state.r, old_state.r, ButtonEvent::R),
+            (state.zr, old_state.zr, ButtonEvent::ZR),
+            (state.left_sl, old_state.left_sl, ButtonEvent::LeftSL),
+            (state.left_sr, old_state.left_sr, ButtonEvent::LeftSR),
+            (state.right_sl, old_state.right_sl, ButtonEvent::RightSL),
+            (state.right_sr, old_state.right_sr, ButtonEvent::RightSR),
+            (state.l_stick, old_state.l_stick, ButtonEvent::LStick),
+            (state.r_stick, old_state.r_stick, ButtonEvent::RStick),
+            (state.dpad_up, old_state.dpad_up, ButtonEvent::DPadUp),
+            (state.dpad_down, old_state.dpad_down, ButtonEvent::DPadDown),
+            (state.dpad_left, old_state.dpad_left, ButtonEvent::DPadLeft),
+            (
+                state.dpad_right,
+                old_state.dpad_right,
+                ButtonEvent::DPadRight,
+            ),
+        ];
+        for (pressed, old_pressed, event) in buttons {
+            if pressed != old_pressed {
+                events.push(Event::Button(event(BinaryInput { pressed })));
+            }
+        }
+
+        // Trigger events
+        if state.zl != old_state.zl {
+            let value = if state.zl { 1.0 } else { 0.0 };
+            events.push(Event::Trigger(TriggerEvent::ZL(TriggerInput { value })));
+        }
+        if state.zr != old_state.zr {
+            let value = if state.zr { 1.0 } else { 0.0 };
+            events.push(Event::Trigger(TriggerEvent::ZR(TriggerInput { value })));
+        }
+
+        // Axis events
+        match self.side {
+            Side::Left => {
+                if state.left_stick != old_state.left_stick {
+                    let (raw_x, raw_y) = state.left_stick_raw();
+                    let (x, y) = self.calibration.normalize(raw_x, raw_y);
+                    events.push(Event::Joystick(JoystickEvent::LStick(JoystickInput {
+                        x,
+                        y,
+                    })));
+                }
+            }
+            Side::Right => {
+                if state.right_stick != old_state.right_stick {
+                    let (raw_x, raw_y) = state.right_stick_raw();
+                    let (x, y) = self.calibration.normalize(raw_x, raw_y);
+                    events.push(Event::Joystick(JoystickEvent::RStick(JoystickInput {
+                        x,
+                        y,
+                    })));
+                }
+            }
+        }
+
+        // IMU events
+        for frame in state.imu_frames() {
+            events.push(Event::Inertia(InertialEvent::Accelerometer(
+                InertialInput {
+                    x: frame.accel_x.to_primitive(),
+                    y: frame.accel_y.to_primitive(),
+                    z: frame.accel_z.to_primitive(),
+                },
+            )));
+            events.push(Event::Inertia(InertialEvent::Gyro(InertialInput {
+                x: frame.gyro_x.to_primitive(),
+                y: frame.gyro_y.to_primitive(),
+                z: frame.gyro_z.to_primitive(),
+            })));
+        }
+
+        events
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        // Stop any rumble that is still playing
+        if let Err(e) = self.rumble(0, 0) {
+            log::debug!("Failed to stop Joy-Con rumble: {e:?}");
+        }
+    }
+}
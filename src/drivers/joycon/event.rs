@@ -0,0 +1,108 @@
+/// Events that can be emitted by a Joy-Con
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Inertia(InertialEvent),
+    Joystick(JoystickEvent),
+    Trigger(TriggerEvent),
+}
+
+/// [BinaryInput] contains either pressed or unpressed
+#[derive(Clone, Debug)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represend binary inputs
+#[derive(Clone, Debug)]
+pub enum ButtonEvent {
+    /// A Button
+    A(BinaryInput),
+    /// B Button
+    B(BinaryInput),
+    /// X Button
+    X(BinaryInput),
+    /// Y Button
+    Y(BinaryInput),
+    /// Plus (+) button
+    Plus(BinaryInput),
+    /// Minus (-) button
+    Minus(BinaryInput),
+    /// Home button
+    Home(BinaryInput),
+    /// Capture button
+    Capture(BinaryInput),
+    /// Left shoulder button
+    L(BinaryInput),
+    /// Left trigger button
+    ZL(BinaryInput),
+    /// Right shoulder button
+    R(BinaryInput),
+    /// Right trigger button
+    ZR(BinaryInput),
+    /// SL button on the rail of the left Joy-Con
+    LeftSL(BinaryInput),
+    /// SR button on the rail of the left Joy-Con
+    LeftSR(BinaryInput),
+    /// SL button on the rail of the right Joy-Con
+    RightSL(BinaryInput),
+    /// SR button on the rail of the right Joy-Con
+    RightSR(BinaryInput),
+    /// Z-axis button on the left stick
+    LStick(BinaryInput),
+    /// Z-axis button on the right stick
+    RStick(BinaryInput),
+    /// DPad up
+    DPadUp(BinaryInput),
+    /// DPad right
+    DPadRight(BinaryInput),
+    /// DPad down
+    DPadDown(BinaryInput),
+    /// DPad left
+    DPadLeft(BinaryInput),
+}
+
+/// [InertialInput] represents the state of the IMU (x, y, z) values
+#[derive(Clone, Debug)]
+pub struct InertialInput {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// [InertialEvent] has data from the IMU
+#[derive(Clone, Debug)]
+pub enum InertialEvent {
+    Accelerometer(InertialInput),
+    Gyro(InertialInput),
+}
+
+/// [JoystickInput] is a double (x, y) axis that has already been normalized
+/// to a value between -1.0 and 1.0 using the stick calibration of the Joy-Con.
+#[derive(Clone, Debug)]
+pub struct JoystickInput {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// [JoystickEvent] are events that have (x, y) values indicating how far
+/// left/right (x) and up/down (y) the joystick is off center.
+#[derive(Clone, Debug)]
+pub enum JoystickEvent {
+    LStick(JoystickInput),
+    RStick(JoystickInput),
+}
+
+/// [TriggerInput] is a single (z) axis. Joy-Con triggers are digital, so the
+/// value is either 0.0 or 1.0.
+#[derive(Clone, Debug)]
+pub struct TriggerInput {
+    pub value: f64,
+}
+
+/// [TriggerEvent] contains values indicating how far a trigger is pulled
+#[derive(Clone, Debug)]
+pub enum TriggerEvent {
+    ZL(TriggerInput),
+    ZR(TriggerInput),
+}
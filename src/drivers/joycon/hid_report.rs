@@ -0,0 +1,349 @@
+use packed_struct::prelude::*;
+
+use super::driver::STANDARD_INPUT_REPORT_ID;
+
+/// Center value of an uncalibrated 12-bit stick axis
+const DEFAULT_STICK_CENTER: u16 = 2048;
+/// Range of an uncalibrated 12-bit stick axis from its center
+const DEFAULT_STICK_RANGE: u16 = 1500;
+/// Value of an unset 12-bit calibration value in SPI flash
+const UNSET_CALIBRATION_VALUE: u16 = 0xFFF;
+
+/// Frequency in Hz used for the high band of rumble
+pub const RUMBLE_HIGH_FREQUENCY: f64 = 320.0;
+/// Frequency in Hz used for the low band of rumble
+pub const RUMBLE_LOW_FREQUENCY: f64 = 160.0;
+/// Rumble data that stops all vibration
+pub const RUMBLE_NEUTRAL: [u8; 4] = [0x00, 0x01, 0x40, 0x40];
+
+/// A single sample of the Joy-Con IMU
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq, Default)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "12")]
+pub struct PackedImuFrame {
+    #[packed_field(bytes = "0..=1", endian = "lsb")]
+    pub accel_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "2..=3", endian = "lsb")]
+    pub accel_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "4..=5", endian = "lsb")]
+    pub accel_z: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "6..=7", endian = "lsb")]
+    pub gyro_x: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "8..=9", endian = "lsb")]
+    pub gyro_y: Integer<i16, packed_bits::Bits<16>>,
+    #[packed_field(bytes = "10..=11", endian = "lsb")]
+    pub gyro_z: Integer<i16, packed_bits::Bits<16>>,
+}
+
+/// Joy-Con standard full mode input report (0x30). Both the left and right
+/// Joy-Con send the same report, with only the fields for their own side
+/// populated.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "49")]
+pub struct PackedInputDataReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8, // Report ID (always 0x30)
+
+    // byte 1
+    #[packed_field(bytes = "1")]
+    pub timer: u8,
+
+    // byte 2
+    #[packed_field(bits = "16..=18")]
+    pub battery_level: Integer<u8, packed_bits::Bits<3>>, // 0 (empty) - 4 (full)
+    #[packed_field(bits = "19")]
+    pub charging: bool,
+    #[packed_field(bits = "20..=23")]
+    pub connection_info: Integer<u8, packed_bits::Bits<4>>,
+
+    // byte 3 (right Joy-Con)
+    #[packed_field(bits = "24")]
+    pub zr: bool,
+    #[packed_field(bits = "25")]
+    pub r: bool,
+    #[packed_field(bits = "26")]
+    pub right_sl: bool,
+    #[packed_field(bits = "27")]
+    pub right_sr: bool,
+    #[packed_field(bits = "28")]
+    pub a: bool,
+    #[packed_field(bits = "29")]
+    pub b: bool,
+    #[packed_field(bits = "30")]
+    pub x: bool,
+    #[packed_field(bits = "31")]
+    pub y: bool,
+
+    // byte 4 (shared)
+    #[packed_field(bits = "32")]
+    pub charging_grip: bool,
+    #[packed_field(bits = "34")]
+    pub capture: bool,
+    #[packed_field(bits = "35")]
+    pub home: bool,
+    #[packed_field(bits = "36")]
+    pub l_stick: bool,
+    #[packed_field(bits = "37")]
+    pub r_stick: bool,
+    #[packed_field(bits = "38")]
+    pub plus: bool,
+    #[packed_field(bits = "39")]
+    pub minus: bool,
+
+    // byte 5 (left Joy-Con)
+    #[packed_field(bits = "40")]
+    pub zl: bool,
+    #[packed_field(bits = "41")]
+    pub l: bool,
+    #[packed_field(bits = "42")]
+    pub left_sl: bool,
+    #[packed_field(bits = "43")]
+    pub left_sr: bool,
+    #[packed_field(bits = "44")]
+    pub dpad_left: bool,
+    #[packed_field(bits = "45")]
+    pub dpad_right: bool,
+    #[packed_field(bits = "46")]
+    pub dpad_up: bool,
+    #[packed_field(bits = "47")]
+    pub dpad_down: bool,
+
+    // bytes 6-11, 12-bit packed stick values
+    #[packed_field(bytes = "6..=8")]
+    pub left_stick: [u8; 3],
+    #[packed_field(bytes = "9..=11")]
+    pub right_stick: [u8; 3],
+
+    // byte 12
+    #[packed_field(bytes = "12")]
+    pub vibrator_report: u8,
+
+    // bytes 13-48, three IMU samples taken 5ms apart from oldest to newest
+    #[packed_field(bytes = "13..=24")]
+    pub imu_0: PackedImuFrame,
+    #[packed_field(bytes = "25..=36")]
+    pub imu_1: PackedImuFrame,
+    #[packed_field(bytes = "37..=48")]
+    pub imu_2: PackedImuFrame,
+}
+
+impl PackedInputDataReport {
+    /// Returns the raw 12-bit (x, y) values of the left stick
+    pub fn left_stick_raw(&self) -> (u16, u16) {
+        decode_stick(&self.left_stick)
+    }
+
+    /// Returns the raw 12-bit (x, y) values of the right stick
+    pub fn right_stick_raw(&self) -> (u16, u16) {
+        decode_stick(&self.right_stick)
+    }
+
+    /// Returns the IMU samples of the report from oldest to newest
+    pub fn imu_frames(&self) -> [PackedImuFrame; 3] {
+        [self.imu_0, self.imu_1, self.imu_2]
+    }
+}
+
+impl Default for PackedInputDataReport {
+    fn default() -> Self {
+        Self {
+            report_id: STANDARD_INPUT_REPORT_ID,
+            timer: 0,
+            battery_level: Integer::from_primitive(0),
+            charging: false,
+            connection_info: Integer::from_primitive(0),
+            zr: false,
+            r: false,
+            right_sl: false,
+            right_sr: false,
+            a: false,
+            b: false,
+            x: false,
+            y: false,
+            charging_grip: false,
+            capture: false,
+            home: false,
+            l_stick: false,
+            r_stick: false,
+            plus: false,
+            minus: false,
+            zl: false,
+            l: false,
+            left_sl: false,
+            left_sr: false,
+            dpad_left: false,
+            dpad_right: false,
+            dpad_up: false,
+            dpad_down: false,
+            left_stick: encode_stick(DEFAULT_STICK_CENTER, DEFAULT_STICK_CENTER),
+            right_stick: encode_stick(DEFAULT_STICK_CENTER, DEFAULT_STICK_CENTER),
+            vibrator_report: 0,
+            imu_0: PackedImuFrame::default(),
+            imu_1: PackedImuFrame::default(),
+            imu_2: PackedImuFrame::default(),
+        }
+    }
+}
+
+/// Decode the given 3 bytes into two 12-bit stick values
+pub fn decode_stick(data: &[u8; 3]) -> (u16, u16) {
+    let x = data[0] as u16 | ((data[1] as u16 & 0x0F) << 8);
+    let y = (data[1] as u16 >> 4) | ((data[2] as u16) << 4);
+    (x, y)
+}
+
+/// Encode the given two 12-bit stick values into 3 bytes
+pub fn encode_stick(x: u16, y: u16) -> [u8; 3] {
+    [
+        (x & 0xFF) as u8,
+        (((x >> 8) & 0x0F) | ((y & 0x0F) << 4)) as u8,
+        ((y >> 4) & 0xFF) as u8,
+    ]
+}
+
+/// Calibration of a single stick axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisCalibration {
+    /// Raw value of the axis when the stick is centered
+    pub center: u16,
+    /// Distance of the minimum raw value below the center
+    pub below: u16,
+    /// Distance of the maximum raw value above the center
+    pub above: u16,
+}
+
+impl Default for AxisCalibration {
+    fn default() -> Self {
+        Self {
+            center: DEFAULT_STICK_CENTER,
+            below: DEFAULT_STICK_RANGE,
+            above: DEFAULT_STICK_RANGE,
+        }
+    }
+}
+
+impl AxisCalibration {
+    /// Returns a value between -1.0 and 1.0 for the given raw axis value
+    pub fn normalize(&self, raw_value: u16) -> f64 {
+        let value = raw_value as f64 - self.center as f64;
+        let range = if value >= 0.0 { self.above } else { self.below };
+        if range == 0 {
+            return 0.0;
+        }
+        (value / range as f64).clamp(-1.0, 1.0)
+    }
+}
+
+/// Stick calibration read from the SPI flash of the Joy-Con
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StickCalibration {
+    pub x: AxisCalibration,
+    pub y: AxisCalibration,
+}
+
+impl StickCalibration {
+    /// Parse the 9 byte left stick calibration data from SPI flash. Returns
+    /// [None] if the calibration is unset.
+    pub fn from_left_spi(data: &[u8; 9]) -> Option<Self> {
+        let [x_above, y_above, x_center, y_center, x_below, y_below] = decode_calibration(data)?;
+        Some(Self {
+            x: AxisCalibration {
+                center: x_center,
+                below: x_below,
+                above: x_above,
+            },
+            y: AxisCalibration {
+                center: y_center,
+                below: y_below,
+                above: y_above,
+            },
+        })
+    }
+
+    /// Parse the 9 byte right stick calibration data from SPI flash. Returns
+    /// [None] if the calibration is unset.
+    pub fn from_right_spi(data: &[u8; 9]) -> Option<Self> {
+        let [x_center, y_center, x_below, y_below, x_above, y_above] = decode_calibration(data)?;
+        Some(Self {
+            x: AxisCalibration {
+                center: x_center,
+                below: x_below,
+                above: x_above,
+            },
+            y: AxisCalibration {
+                center: y_center,
+                below: y_below,
+                above: y_above,
+            },
+        })
+    }
+
+    /// Returns the normalized (x, y) values of the given raw stick values.
+    /// Joy-Con sticks report up as positive, so the y axis is inverted to
+    /// match other gamepads where positive values point down.
+    pub fn normalize(&self, raw_x: u16, raw_y: u16) -> (f64, f64) {
+        (self.x.normalize(raw_x), -self.y.normalize(raw_y))
+    }
+}
+
+/// Decode the given 9 bytes of stick calibration data into six 12-bit values.
+/// Returns [None] if the calibration is unset.
+fn decode_calibration(data: &[u8; 9]) -> Option<[u16; 6]> {
+    let d: Vec<u16> = data.iter().map(|byte| *byte as u16).collect();
+    let values = [
+        ((d[1] << 8) & 0xF00) | d[0],
+        (d[2] << 4) | (d[1] >> 4),
+        ((d[4] << 8) & 0xF00) | d[3],
+        (d[5] << 4) | (d[4] >> 4),
+        ((d[7] << 8) & 0xF00) | d[6],
+        (d[8] << 4) | (d[7] >> 4),
+    ];
+    if values.iter().all(|value| *value == UNSET_CALIBRATION_VALUE) {
+        return None;
+    }
+    Some(values)
+}
+
+/// Encode the given rumble amplitudes from 0.0 - 1.0 into the 4 byte rumble
+/// data of a Joy-Con. The high band is used for weak rumble and the low band
+/// for strong rumble.
+pub fn encode_rumble(high_amplitude: f64, low_amplitude: f64) -> [u8; 4] {
+    let high_freq = encode_frequency(RUMBLE_HIGH_FREQUENCY);
+    let low_freq = encode_frequency(RUMBLE_LOW_FREQUENCY);
+    let high_amp = encode_amplitude(high_amplitude);
+    let low_amp = encode_amplitude(low_amplitude);
+
+    let hf = (high_freq.saturating_sub(0x60) * 4) as u16;
+    let lf = low_freq.saturating_sub(0x40) as u8;
+    let hf_amp = (high_amp * 2) as u8;
+    let mut lf_amp = (low_amp / 2 + 0x40) as u16;
+    if low_amp % 2 != 0 {
+        lf_amp |= 0x8000;
+    }
+
+    [
+        (hf & 0xFF) as u8,
+        ((hf >> 8) as u8).wrapping_add(hf_amp),
+        lf.wrapping_add((lf_amp >> 8) as u8),
+        (lf_amp & 0xFF) as u8,
+    ]
+}
+
+/// Encode the given frequency in Hz
+fn encode_frequency(frequency: f64) -> u32 {
+    ((frequency / 10.0).log2() * 32.0).round() as u32
+}
+
+/// Encode the given amplitude from 0.0 - 1.0
+fn encode_amplitude(amplitude: f64) -> u32 {
+    let amplitude = amplitude.clamp(0.0, 1.0);
+    if amplitude <= 0.0 {
+        return 0;
+    }
+    let encoded = if amplitude > 0.23 {
+        (amplitude * 8.7).log2() * 32.0
+    } else {
+        (amplitude * 17.0).log2() * 16.0
+    };
+    encoded.round().max(0.0) as u32
+}
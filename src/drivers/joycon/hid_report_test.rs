@@ -0,0 +1,67 @@
+use std::error::Error;
+
+use packed_struct::{PackedStruct, PackedStructSlice};
+
+use super::hid_report::{
+    decode_stick, encode_rumble, encode_stick, PackedInputDataReport, StickCalibration,
+    RUMBLE_NEUTRAL,
+};
+
+#[tokio::test]
+async fn test_joycon_input_report() -> Result<(), Box<dyn Error>> {
+    let report = PackedInputDataReport {
+        a: true,
+        dpad_left: true,
+        left_stick: encode_stick(3000, 1000),
+        ..Default::default()
+    };
+    let data = report.pack()?;
+    assert_eq!(data[3], 0b0000_1000, "A should be bit 3 of byte 3");
+    assert_eq!(data[5], 0b0000_1000, "DPad left should be bit 3 of byte 5");
+
+    let report = PackedInputDataReport::unpack_from_slice(&data)?;
+    assert!(report.a, "should be pressed");
+    assert!(report.dpad_left, "should be pressed");
+    assert!(!report.b, "should not be pressed");
+    assert_eq!(report.left_stick_raw(), (3000, 1000));
+
+    Ok(())
+}
+
+#[test]
+fn test_stick_encoding() {
+    for (x, y) in [(0, 0), (2048, 2048), (4095, 4095), (0x123, 0xABC)] {
+        assert_eq!(decode_stick(&encode_stick(x, y)), (x, y));
+    }
+}
+
+#[test]
+fn test_stick_calibration() {
+    // Left stick calibration is stored as: x/y above center, x/y center,
+    // x/y below center
+    let mut data = [0; 9];
+    data[0..3].copy_from_slice(&encode_stick(1400, 1300));
+    data[3..6].copy_from_slice(&encode_stick(2000, 2100));
+    data[6..9].copy_from_slice(&encode_stick(1500, 1200));
+    let calibration = StickCalibration::from_left_spi(&data).unwrap();
+    assert_eq!(calibration.x.center, 2000);
+    assert_eq!(calibration.x.above, 1400);
+    assert_eq!(calibration.y.below, 1200);
+
+    // Values should be normalized to the calibrated range, with y inverted
+    assert_eq!(calibration.normalize(2000, 2100), (0.0, -0.0));
+    assert_eq!(calibration.normalize(3400, 900), (1.0, 1.0));
+    assert_eq!(calibration.normalize(500, 3400), (-1.0, -1.0));
+    assert_eq!(calibration.normalize(4095, 0), (1.0, 1.0));
+
+    // Unset calibration should be ignored
+    assert!(StickCalibration::from_right_spi(&[0xFF; 9]).is_none());
+}
+
+#[test]
+fn test_rumble_encoding() {
+    assert_eq!(encode_rumble(0.0, 0.0), RUMBLE_NEUTRAL);
+    let rumble = encode_rumble(1.0, 1.0);
+    assert_ne!(rumble, RUMBLE_NEUTRAL);
+    assert_eq!(rumble[0], RUMBLE_NEUTRAL[0], "frequency should not change");
+}
@@ -1,10 +1,22 @@
+pub mod device_info;
+#[cfg(test)]
+pub mod device_info_test;
 pub mod dualsense;
 pub mod fts3528;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzz;
+#[cfg(test)]
+pub mod fuzz_test;
+pub mod gamecube_adapter;
+pub mod gpd_hid;
 pub mod horipad_steam;
 pub mod iio_imu;
+pub mod joycon;
 pub mod lego;
 pub mod legos;
 pub mod opineo;
+pub mod oxp_hid;
 pub mod rog_ally;
 pub mod steam_deck;
 pub mod xpad_uhid;
+pub mod zotac_zone;
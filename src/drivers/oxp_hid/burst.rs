@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+use super::event::{BinaryInput, ButtonEvent};
+
+// HID keyboard usage IDs
+pub const KEY_D: u8 = 0x07;
+pub const KEY_G: u8 = 0x0a;
+pub const KEY_O: u8 = 0x12;
+pub const KEY_SYSRQ: u8 = 0x46;
+pub const KEY_DELETE: u8 = 0x4c;
+pub const KEY_LEFT_CTRL: u8 = 0xe0;
+pub const KEY_LEFT_ALT: u8 = 0xe2;
+pub const KEY_LEFT_META: u8 = 0xe3;
+pub const KEY_RIGHT_CTRL: u8 = 0xe4;
+pub const KEY_RIGHT_ALT: u8 = 0xe6;
+
+/// Usage ID reported in every key slot when too many keys are pressed
+const KEY_ERROR_ROLLOVER: u8 = 0x01;
+/// Usage ID of the first modifier key (Left Ctrl)
+const MODIFIER_BASE: u8 = 0xe0;
+
+/// Amount of time a burst of keys must be held before it is decoded as a
+/// held button.
+pub const DEBOUNCE_TIME: Duration = Duration::from_millis(30);
+
+/// Returns the list of pressed key usage IDs from a HID boot keyboard report.
+/// E.g. [modifiers, reserved, key1, key2, key3, key4, key5, key6]
+pub fn parse_keyboard_report(report: &[u8]) -> Vec<u8> {
+    let mut keys = Vec::new();
+    let Some(modifiers) = report.first() else {
+        return keys;
+    };
+    for i in 0..8 {
+        if modifiers & (1 << i) != 0 {
+            keys.push(MODIFIER_BASE + i);
+        }
+    }
+    let codes = report.get(2..).unwrap_or_default();
+    for code in codes {
+        if *code == 0 || *code == KEY_ERROR_ROLLOVER {
+            continue;
+        }
+        keys.push(*code);
+    }
+    keys
+}
+
+/// Returns the button event for the given burst of keys
+pub fn decode_burst(keys: &BTreeSet<u8>) -> Option<ButtonEvent> {
+    let value = BinaryInput { pressed: true };
+    let keys: Vec<u8> = keys.iter().copied().collect();
+    match keys.as_slice() {
+        [KEY_D, KEY_LEFT_META] => Some(ButtonEvent::Orange(value)),
+        [KEY_G, KEY_LEFT_META] => Some(ButtonEvent::OrangeLong(value)),
+        [KEY_LEFT_CTRL, KEY_LEFT_ALT, KEY_LEFT_META] => Some(ButtonEvent::Turbo(value)),
+        [KEY_O, KEY_LEFT_META, KEY_RIGHT_CTRL] => Some(ButtonEvent::Keyboard(value)),
+        [KEY_SYSRQ, KEY_LEFT_META] => Some(ButtonEvent::TurboOrange(value)),
+        [KEY_DELETE, KEY_RIGHT_CTRL, KEY_RIGHT_ALT] => Some(ButtonEvent::KeyboardOrange(value)),
+        _ => None,
+    }
+}
+
+/// The firmware emits a single button press as a burst of keyboard reports
+/// that press and release a combination of keys. The [BurstDecoder] collects
+/// these bursts and decodes them into single button events.
+#[derive(Debug, Default)]
+pub struct BurstDecoder {
+    /// All keys that were pressed since the start of the current burst
+    keys: BTreeSet<u8>,
+    /// Timestamp of the first report in the current burst
+    burst_start: Option<Instant>,
+    /// The button that is currently pressed
+    active: Option<ButtonEvent>,
+    /// Whether the active button should be released on the next update
+    release_pending: bool,
+}
+
+impl BurstDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the decoder with the keys from a keyboard report. If no report
+    /// was read, [None] should be passed to handle pending releases and held
+    /// buttons. Returns any decoded button events.
+    pub fn update(&mut self, keys: Option<&[u8]>, now: Instant) -> Vec<ButtonEvent> {
+        let mut events = Vec::new();
+
+        // Buttons with a press and release in the same burst are released on
+        // the following update so the press is not missed.
+        if self.release_pending {
+            self.release_pending = false;
+            if let Some(active) = self.active.take() {
+                events.push(active.with_pressed(false));
+            }
+        }
+
+        match keys {
+            Some(keys) if !keys.is_empty() => {
+                self.burst_start.get_or_insert(now);
+                self.keys.extend(keys);
+            }
+            // All keys were released, so the burst is complete
+            Some(_) => {
+                if let Some(active) = self.active.take() {
+                    events.push(active.with_pressed(false));
+                } else if let Some(button) = decode_burst(&self.keys) {
+                    events.push(button.clone());
+                    self.active = Some(button);
+                    self.release_pending = true;
+                } else if !self.keys.is_empty() {
+                    log::debug!("Unknown key burst: {:?}", self.keys);
+                }
+                self.keys.clear();
+                self.burst_start = None;
+            }
+            None => (),
+        }
+
+        // If the burst is being held, decode it as a held button
+        let Some(burst_start) = self.burst_start else {
+            return events;
+        };
+        if self.active.is_some() || now.duration_since(burst_start) < DEBOUNCE_TIME {
+            return events;
+        }
+        if let Some(button) = decode_burst(&self.keys) {
+            events.push(button.clone());
+            self.active = Some(button);
+        }
+
+        events
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use crate::drivers::oxp_hid::{
+    burst::{parse_keyboard_report, BurstDecoder, KEY_D, KEY_LEFT_META},
+    event::{BinaryInput, ButtonEvent},
+};
+
+#[test]
+fn test_parse_keyboard_report() {
+    let report = [0x08, 0x00, KEY_D, 0x00, 0x00, 0x00, 0x00, 0x00];
+    assert_eq!(parse_keyboard_report(&report), vec![KEY_LEFT_META, KEY_D]);
+
+    let report = [0x00; 8];
+    assert!(parse_keyboard_report(&report).is_empty());
+}
+
+#[test]
+fn test_short_press_burst() {
+    let mut decoder = BurstDecoder::new();
+    let now = Instant::now();
+
+    assert!(decoder.update(Some(&[KEY_LEFT_META][..]), now).is_empty());
+    assert!(decoder
+        .update(Some(&[KEY_LEFT_META, KEY_D][..]), now)
+        .is_empty());
+    assert!(decoder.update(Some(&[KEY_LEFT_META][..]), now).is_empty());
+
+    // Releasing all keys should press the button
+    let events = decoder.update(Some(&[][..]), now);
+    assert_eq!(
+        events,
+        vec![ButtonEvent::Orange(BinaryInput { pressed: true })]
+    );
+
+    // The next update should release the button
+    let events = decoder.update(None, now + Duration::from_millis(8));
+    assert_eq!(
+        events,
+        vec![ButtonEvent::Orange(BinaryInput { pressed: false })]
+    );
+}
+
+#[test]
+fn test_held_burst() {
+    let mut decoder = BurstDecoder::new();
+    let now = Instant::now();
+
+    assert!(decoder
+        .update(Some(&[KEY_LEFT_META, KEY_D][..]), now)
+        .is_empty());
+
+    // Holding the burst past the debounce time should press the button
+    let events = decoder.update(None, now + Duration::from_millis(50));
+    assert_eq!(
+        events,
+        vec![ButtonEvent::Orange(BinaryInput { pressed: true })]
+    );
+    assert!(decoder
+        .update(
+            Some(&[KEY_LEFT_META, KEY_D][..]),
+            now + Duration::from_millis(60)
+        )
+        .is_empty());
+
+    // Releasing all keys should release the button
+    let events = decoder.update(Some(&[][..]), now + Duration::from_millis(70));
+    assert_eq!(
+        events,
+        vec![ButtonEvent::Orange(BinaryInput { pressed: false })]
+    );
+}
@@ -0,0 +1,124 @@
+use std::{error::Error, ffi::CString, time::Instant};
+
+use hidapi::HidDevice;
+
+use crate::{dmi::get_dmi_data, udev::device::UdevDevice};
+
+use super::{
+    burst::{parse_keyboard_report, BurstDecoder},
+    event::Event,
+};
+
+// Hardware ID's
+pub const VID: u16 = 0x1a86;
+pub const PID: u16 = 0xfe00;
+
+// The VID/PID belongs to a generic WCH USB chip that is also used by other
+// devices, so the driver is only bound on systems from these vendors.
+pub const SYS_VENDORS: [&str; 3] = ["ONE-NETBOOK", "ONE-NETBOOK TECHNOLOGY CO., LTD.", "AOKZOE"];
+
+// Report ID
+pub const KEYBOARD_REPORT_ID: u8 = 0x01;
+
+// Input report sizes
+const KEYBOARD_PACKET_SIZE: usize = 8;
+const PACKET_SIZE: usize = 64;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+/// The mode the device firmware is currently in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareMode {
+    /// No reports have been received yet
+    Unknown,
+    /// Extra buttons are emitted as bursts of keyboard scancodes
+    Keyboard,
+    /// Extra buttons are emitted as vendor reports
+    Vendor,
+}
+
+/// Returns true if a device with the given vendor and product id on a system
+/// with the given DMI system vendor is a OneXPlayer/AOKZOE vendor keyboard
+pub fn is_supported(vid: u16, pid: u16, sys_vendor: &str) -> bool {
+    vid == VID && pid == PID && SYS_VENDORS.contains(&sys_vendor.trim())
+}
+
+/// OneXPlayer/AOKZOE vendor keyboard driver for reading the extra buttons
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Detected firmware mode of the device
+    mode: FirmwareMode,
+    /// Decoder for turning bursts of keys into button events
+    decoder: BurstDecoder,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let info = device.get_device_info()?;
+        let sys_vendor = get_dmi_data().sys_vendor;
+        if !is_supported(info.vendor_id(), info.product_id(), sys_vendor.as_str()) {
+            return Err(format!("Device '{path}' is not a OneXPlayer vendor keyboard").into());
+        }
+
+        Ok(Self {
+            device,
+            mode: FirmwareMode::Unknown,
+            decoder: BurstDecoder::new(),
+        })
+    }
+
+    /// Returns the detected firmware mode of the device
+    pub fn mode(&self) -> FirmwareMode {
+        self.mode
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        let slice = &buf[..bytes_read];
+
+        // Keyboard reports may or may not include the report ID
+        let keyboard_report = match bytes_read {
+            0 => None,
+            KEYBOARD_PACKET_SIZE => Some(slice),
+            n if n == KEYBOARD_PACKET_SIZE + 1 && slice[0] == KEYBOARD_REPORT_ID => {
+                Some(&slice[1..])
+            }
+            _ => {
+                self.set_mode(FirmwareMode::Vendor);
+                log::trace!("Ignoring vendor report: {slice:?}");
+                return Ok(vec![]);
+            }
+        };
+        if keyboard_report.is_some() {
+            self.set_mode(FirmwareMode::Keyboard);
+        }
+
+        let keys = keyboard_report.map(parse_keyboard_report);
+        let events = self
+            .decoder
+            .update(keys.as_deref(), Instant::now())
+            .into_iter()
+            .map(Event::Button)
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Update the detected firmware mode
+    fn set_mode(&mut self, mode: FirmwareMode) {
+        if self.mode == mode {
+            return;
+        }
+        log::info!("Detected OneXPlayer firmware mode: {mode:?}");
+        self.mode = mode;
+    }
+}
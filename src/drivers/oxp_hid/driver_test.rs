@@ -0,0 +1,12 @@
+use crate::drivers::oxp_hid::driver::{is_supported, PID, VID};
+
+#[test]
+fn test_is_supported() {
+    assert!(is_supported(VID, PID, "ONE-NETBOOK"));
+    assert!(is_supported(VID, PID, "AOKZOE\n"));
+
+    // The generic WCH IDs should not be bound on other systems
+    assert!(!is_supported(VID, PID, "LENOVO"));
+    assert!(!is_supported(VID, PID, ""));
+    assert!(!is_supported(VID, 0x7523, "ONE-NETBOOK"));
+}
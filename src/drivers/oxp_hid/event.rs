@@ -0,0 +1,43 @@
+/// Events that can be emitted by the OneXPlayer/AOKZOE vendor keyboard
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Button events represent binary inputs
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonEvent {
+    /// Orange/Home button short press
+    Orange(BinaryInput),
+    /// Orange/Home button long press
+    OrangeLong(BinaryInput),
+    /// Turbo button
+    Turbo(BinaryInput),
+    /// Keyboard button
+    Keyboard(BinaryInput),
+    /// Turbo + Orange button combination
+    TurboOrange(BinaryInput),
+    /// Keyboard + Orange button combination
+    KeyboardOrange(BinaryInput),
+}
+
+impl ButtonEvent {
+    /// Returns a copy of the button event with the given pressed state
+    pub fn with_pressed(&self, pressed: bool) -> Self {
+        let value = BinaryInput { pressed };
+        match self {
+            ButtonEvent::Orange(_) => ButtonEvent::Orange(value),
+            ButtonEvent::OrangeLong(_) => ButtonEvent::OrangeLong(value),
+            ButtonEvent::Turbo(_) => ButtonEvent::Turbo(value),
+            ButtonEvent::Keyboard(_) => ButtonEvent::Keyboard(value),
+            ButtonEvent::TurboOrange(_) => ButtonEvent::TurboOrange(value),
+            ButtonEvent::KeyboardOrange(_) => ButtonEvent::KeyboardOrange(value),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+pub mod burst;
+#[cfg(test)]
+pub mod burst_test;
+pub mod driver;
+#[cfg(test)]
+pub mod driver_test;
+pub mod event;
@@ -0,0 +1,156 @@
+use std::{error::Error, ffi::CString};
+
+use hidapi::HidDevice;
+use packed_struct::PackedStruct;
+
+use crate::udev::device::UdevDevice;
+
+use super::{
+    event::{BinaryInput, ButtonEvent, DialEvent, DialInput, Event, TriggerEvent, TriggerInput},
+    hid_report::{PackedInputDataReport, PackedRgbOutputReport},
+};
+
+// Hardware ID's
+pub const VID: u16 = 0x1ee9;
+pub const PID: u16 = 0x1590;
+
+// Report ID
+pub const INPUT_REPORT_ID: u8 = 0x03;
+
+// Input report sizes
+const INPUT_PACKET_SIZE: usize = 16;
+const PACKET_SIZE: usize = 64;
+
+// HID buffer read timeout
+const HID_TIMEOUT: i32 = 10;
+
+// Input report axis ranges
+pub const TRIGGER_MAX: f64 = 255.0;
+
+/// Zotac Zone driver for reading the dials, back buttons, and triggers from
+/// the vendor interface of the controller
+pub struct Driver {
+    /// HIDRAW device instance
+    device: HidDevice,
+    /// Last vendor input report
+    state: Option<PackedInputDataReport>,
+    /// Current color of the LED rings
+    rgb: Option<(u8, u8, u8)>,
+}
+
+impl Driver {
+    pub fn new(udevice: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = udevice.devnode();
+        let cs_path = CString::new(path.clone())?;
+        let api = hidapi::HidApi::new()?;
+        let device = api.open_path(&cs_path)?;
+        let info = device.get_device_info()?;
+        if info.vendor_id() != VID || info.product_id() != PID {
+            return Err(format!("Device '{path}' is not a Zotac Zone controller").into());
+        }
+
+        Ok(Self {
+            device,
+            state: None,
+            rgb: None,
+        })
+    }
+
+    /// Poll the device and read input reports
+    pub fn poll(&mut self) -> Result<Vec<Event>, Box<dyn Error + Send + Sync>> {
+        // Read data from the device into a buffer
+        let mut buf = [0; PACKET_SIZE];
+        let bytes_read = self.device.read_timeout(&mut buf[..], HID_TIMEOUT)?;
+        if bytes_read == 0 {
+            return Ok(vec![]);
+        }
+        if bytes_read != INPUT_PACKET_SIZE || buf[0] != INPUT_REPORT_ID {
+            log::trace!("Ignoring unknown report: {:?}", &buf[..bytes_read]);
+            return Ok(vec![]);
+        }
+
+        let slice = &buf[..INPUT_PACKET_SIZE];
+        let report = PackedInputDataReport::unpack(slice.try_into()?)?;
+        let old_state = self.state.replace(report);
+
+        Ok(translate(&report, old_state.as_ref()))
+    }
+
+    /// Set the color of the LED rings around both sticks
+    pub fn set_rgb(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Box<dyn Error>> {
+        if self.rgb == Some((red, green, blue)) {
+            return Ok(());
+        }
+        let report = PackedRgbOutputReport {
+            red,
+            green,
+            blue,
+            ..Default::default()
+        };
+        self.device.write(&report.pack()?)?;
+        self.rgb = Some((red, green, blue));
+
+        Ok(())
+    }
+}
+
+/// Translate the given report into events. Buttons and triggers only emit
+/// events when they change, while dials emit an event for every report they
+/// were turned in.
+pub fn translate(
+    state: &PackedInputDataReport,
+    old_state: Option<&PackedInputDataReport>,
+) -> Vec<Event> {
+    let mut events = Vec::new();
+    let old = old_state.copied().unwrap_or_default();
+
+    let buttons: [(bool, bool, fn(BinaryInput) -> ButtonEvent); 6] = [
+        (state.m1, old.m1, ButtonEvent::M1),
+        (state.m2, old.m2, ButtonEvent::M2),
+        (
+            state.left_dial_press,
+            old.left_dial_press,
+            ButtonEvent::LeftDial,
+        ),
+        (
+            state.right_dial_press,
+            old.right_dial_press,
+            ButtonEvent::RightDial,
+        ),
+        (
+            state.quick_access,
+            old.quick_access,
+            ButtonEvent::QuickAccess,
+        ),
+        (state.zotac, old.zotac, ButtonEvent::Zotac),
+    ];
+    for (pressed, was_pressed, button) in buttons {
+        if pressed != was_pressed {
+            events.push(Event::Button(button(BinaryInput { pressed })));
+        }
+    }
+
+    if state.left_dial != 0 {
+        let steps = state.left_dial;
+        events.push(Event::Dial(DialEvent::LeftDial(DialInput { steps })));
+    }
+    if state.right_dial != 0 {
+        let steps = state.right_dial;
+        events.push(Event::Dial(DialEvent::RightDial(DialInput { steps })));
+    }
+
+    if state.left_trigger != old.left_trigger {
+        let value = state.left_trigger;
+        events.push(Event::Trigger(TriggerEvent::LeftTrigger(TriggerInput {
+            value,
+        })));
+    }
+    if state.right_trigger != old.right_trigger {
+        let value = state.right_trigger;
+        events.push(Event::Trigger(TriggerEvent::RightTrigger(TriggerInput {
+            value,
+        })));
+    }
+
+    events
+}
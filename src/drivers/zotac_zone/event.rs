@@ -0,0 +1,57 @@
+/// Events that can be emitted by the Zotac Zone controller
+#[derive(Clone, Debug)]
+pub enum Event {
+    Button(ButtonEvent),
+    Dial(DialEvent),
+    Trigger(TriggerEvent),
+}
+
+/// Binary input contain either pressed or unpressed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BinaryInput {
+    pub pressed: bool,
+}
+
+/// Dial input contains the number of steps a dial was turned since the last
+/// report, where positive values are clockwise
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DialInput {
+    pub steps: i8,
+}
+
+/// Trigger input contains non-negative integars
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriggerInput {
+    pub value: u8,
+}
+
+/// Button events represent binary inputs
+#[derive(Clone, Debug, PartialEq)]
+pub enum ButtonEvent {
+    /// Left back button
+    M1(BinaryInput),
+    /// Right back button
+    M2(BinaryInput),
+    /// Pressing the left dial
+    LeftDial(BinaryInput),
+    /// Pressing the right dial
+    RightDial(BinaryInput),
+    /// Quick access button with three dots
+    QuickAccess(BinaryInput),
+    /// Zotac logo button
+    Zotac(BinaryInput),
+}
+
+/// Dial events represent turning one of the radial dials
+#[derive(Clone, Debug, PartialEq)]
+pub enum DialEvent {
+    LeftDial(DialInput),
+    RightDial(DialInput),
+}
+
+/// Trigger events contain positional data about the analog triggers
+#[derive(Clone, Debug, PartialEq)]
+pub enum TriggerEvent {
+    LeftTrigger(TriggerInput),
+    RightTrigger(TriggerInput),
+}
@@ -0,0 +1,112 @@
+//! Reference: Reverse engineered from the vendor interface of the Zotac Zone
+//! controller
+use packed_struct::prelude::*;
+
+/// Header byte of every command sent to the controller
+pub const COMMAND_HEADER: u8 = 0xe1;
+
+/// Commands that can be sent to the controller
+#[derive(PrimitiveEnum_u8, Clone, Copy, PartialEq, Debug, Default)]
+pub enum Command {
+    #[default]
+    SetRgb = 0xad,
+}
+
+/// LED zones around the sticks
+#[derive(PrimitiveEnum_u8, Clone, Copy, PartialEq, Debug, Default)]
+pub enum RgbZone {
+    #[default]
+    Both = 0x00,
+    LeftStick = 0x01,
+    RightStick = 0x02,
+}
+
+/// Lighting effects of the LED zones
+#[derive(PrimitiveEnum_u8, Clone, Copy, PartialEq, Debug, Default)]
+pub enum RgbEffect {
+    #[default]
+    Solid = 0x00,
+    Breathe = 0x01,
+    Off = 0xff,
+}
+
+/// Vendor input report with the controls that are not part of the Xbox
+/// gamepad interface.
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq, Default)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "16")]
+pub struct PackedInputDataReport {
+    // byte 0
+    #[packed_field(bytes = "0")]
+    pub report_id: u8,
+
+    // byte 1
+    #[packed_field(bits = "10")]
+    pub zotac: bool,
+    #[packed_field(bits = "11")]
+    pub quick_access: bool,
+    #[packed_field(bits = "12")]
+    pub right_dial_press: bool,
+    #[packed_field(bits = "13")]
+    pub left_dial_press: bool,
+    #[packed_field(bits = "14")]
+    pub m2: bool,
+    #[packed_field(bits = "15")]
+    pub m1: bool,
+
+    // byte 2-3
+    /// Steps the left dial was turned since the last report
+    #[packed_field(bytes = "2")]
+    pub left_dial: i8,
+    /// Steps the right dial was turned since the last report
+    #[packed_field(bytes = "3")]
+    pub right_dial: i8,
+
+    // byte 4-5
+    /// Trigger travel, which depends on the resistance set with the trigger
+    /// stop switches
+    #[packed_field(bytes = "4")]
+    pub left_trigger: u8,
+    #[packed_field(bytes = "5")]
+    pub right_trigger: u8,
+}
+
+/// Output report to set the color of the LED rings around the sticks
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering = "msb0", size_bytes = "64")]
+pub struct PackedRgbOutputReport {
+    #[packed_field(bytes = "0")]
+    pub report_id: u8,
+    #[packed_field(bytes = "1")]
+    pub header: u8,
+    #[packed_field(bytes = "2", ty = "enum")]
+    pub command: Command,
+    #[packed_field(bytes = "3", ty = "enum")]
+    pub zone: RgbZone,
+    #[packed_field(bytes = "4", ty = "enum")]
+    pub effect: RgbEffect,
+    #[packed_field(bytes = "5")]
+    pub red: u8,
+    #[packed_field(bytes = "6")]
+    pub green: u8,
+    #[packed_field(bytes = "7")]
+    pub blue: u8,
+    /// Brightness in percent
+    #[packed_field(bytes = "8")]
+    pub brightness: u8,
+}
+
+impl Default for PackedRgbOutputReport {
+    fn default() -> Self {
+        Self {
+            report_id: 0x00,
+            header: COMMAND_HEADER,
+            command: Command::SetRgb,
+            zone: RgbZone::Both,
+            effect: RgbEffect::Solid,
+            red: 0,
+            green: 0,
+            blue: 0,
+            brightness: 100,
+        }
+    }
+}
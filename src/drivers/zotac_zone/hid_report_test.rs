@@ -0,0 +1,78 @@
+use std::error::Error;
+
+use packed_struct::PackedStruct;
+
+use super::{
+    driver::translate,
+    event::{BinaryInput, ButtonEvent, DialEvent, DialInput, Event},
+    hid_report::{PackedInputDataReport, PackedRgbOutputReport},
+};
+
+#[tokio::test]
+async fn test_zotac_zone_input_report() -> Result<(), Box<dyn Error>> {
+    let report = PackedInputDataReport {
+        report_id: 0x03,
+        m1: true,
+        left_dial: -2,
+        right_trigger: 200,
+        ..Default::default()
+    };
+    let data = report.pack()?;
+    assert_eq!(data[1], 0b0000_0001, "M1 should be bit 0 of byte 1");
+    assert_eq!(data[2], 0xfe, "Left dial should be a signed byte");
+
+    let report = PackedInputDataReport::unpack(&data)?;
+    assert!(report.m1, "should be pressed");
+    assert!(!report.m2, "should not be pressed");
+    assert_eq!(report.left_dial, -2);
+    assert_eq!(report.right_trigger, 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_zotac_zone_translate() -> Result<(), Box<dyn Error>> {
+    let old = PackedInputDataReport {
+        m1: true,
+        ..Default::default()
+    };
+    let report = PackedInputDataReport {
+        m1: true,
+        right_dial: 1,
+        ..Default::default()
+    };
+
+    // Held buttons should not repeat while dials emit every turn
+    let events = translate(&report, Some(&old));
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        &events[0],
+        Event::Dial(DialEvent::RightDial(DialInput { steps: 1 }))
+    ));
+
+    // Releasing a button should emit an event
+    let events = translate(&PackedInputDataReport::default(), Some(&report));
+    assert!(matches!(
+        &events[0],
+        Event::Button(ButtonEvent::M1(BinaryInput { pressed: false }))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_zotac_zone_rgb_report() -> Result<(), Box<dyn Error>> {
+    let report = PackedRgbOutputReport {
+        red: 0x12,
+        green: 0x34,
+        blue: 0x56,
+        ..Default::default()
+    };
+    let data = report.pack()?;
+    assert_eq!(
+        &data[..9],
+        &[0x00, 0xe1, 0xad, 0x00, 0x00, 0x12, 0x34, 0x56, 100]
+    );
+
+    Ok(())
+}
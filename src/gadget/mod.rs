@@ -0,0 +1,262 @@
+//! USB gadget support using ConfigFS
+//!
+//! When a handheld is connected to another computer or console through a USB
+//! OTG port in peripheral mode, the kernel's USB gadget subsystem can make it
+//! appear as a USB device to the host. The [UsbGadget] creates a HID gadget
+//! through ConfigFS so target devices can write HID input reports to the host
+//! over the `/dev/hidgN` character device.
+//!
+//! This requires the `libcomposite` and `usb_f_hid` kernel modules and a USB
+//! device controller (UDC) that supports peripheral mode.
+
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    os::unix::fs::{symlink, OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+use nix::fcntl::OFlag;
+
+/// Path to the ConfigFS USB gadget directory
+pub const CONFIGFS_GADGET_PATH: &str = "/sys/kernel/config/usb_gadget";
+/// Path to the available USB device controllers
+pub const UDC_PATH: &str = "/sys/class/udc";
+/// Language ID for US English strings
+const LANG_ID: &str = "0x409";
+/// Name of the gadget configuration
+const CONFIG_NAME: &str = "c.1";
+/// Name of the HID gadget function
+const FUNCTION_NAME: &str = "hid.usb0";
+
+/// Describes the USB HID device that a [UsbGadget] presents to the host
+#[derive(Debug, Clone)]
+pub struct HidGadgetConfig {
+    /// Name of the gadget directory in ConfigFS
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Device release number in binary-coded decimal
+    pub bcd_device: u16,
+    pub manufacturer: String,
+    pub product: String,
+    pub serial_number: String,
+    /// HID report descriptor of the device
+    pub report_descriptor: Vec<u8>,
+    /// Size of the largest input or output report
+    pub report_length: u16,
+    /// Maximum power draw in mA
+    pub max_power: u16,
+}
+
+/// A HID device created with the USB gadget ConfigFS interface. The gadget is
+/// unbound and removed when it is dropped.
+#[derive(Debug)]
+pub struct UsbGadget {
+    path: PathBuf,
+    udc: String,
+    hidg: File,
+}
+
+impl UsbGadget {
+    /// Create and bind a new USB HID gadget with the given configuration to
+    /// the first available USB device controller.
+    pub fn new(config: &HidGadgetConfig) -> Result<Self, Box<dyn Error>> {
+        let Some(udc) = get_available_udcs().into_iter().next() else {
+            return Err("No USB device controller available for gadget mode".into());
+        };
+        let path = Path::new(CONFIGFS_GADGET_PATH).join(&config.name);
+        if path.exists() {
+            return Err(format!("USB gadget already exists at {path:?}").into());
+        }
+
+        log::debug!("Creating USB gadget {path:?} on UDC {udc}");
+        if let Err(e) = create_gadget(&path, config) {
+            remove_gadget(&path);
+            return Err(e);
+        }
+
+        // Bind the gadget to the device controller so the host sees it
+        if let Err(e) = fs::write(path.join("UDC"), &udc) {
+            remove_gadget(&path);
+            return Err(format!("Failed to bind USB gadget to {udc}: {e}").into());
+        }
+
+        // Open the HID gadget character device to write reports to
+        let hidg = match open_hidg(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = fs::write(path.join("UDC"), "");
+                remove_gadget(&path);
+                return Err(e);
+            }
+        };
+        log::info!("Created USB gadget {} on {udc}", config.name);
+
+        Ok(Self { path, udc, hidg })
+    }
+
+    /// Returns the name of the USB device controller the gadget is bound to
+    pub fn udc(&self) -> &str {
+        self.udc.as_str()
+    }
+
+    /// Returns true if the gadget is connected to and configured by a host
+    pub fn is_connected(&self) -> bool {
+        let state_path = Path::new(UDC_PATH).join(&self.udc).join("state");
+        fs::read_to_string(state_path)
+            .map(|state| state.trim() == "configured")
+            .unwrap_or(false)
+    }
+
+    /// Write the given HID input report to the host. Reports are dropped if
+    /// the host is not reading them.
+    pub fn write_report(&mut self, report: &[u8]) -> Result<(), Box<dyn Error>> {
+        match self.hidg.write(report) {
+            Ok(_) => Ok(()),
+            // The host is not connected or has not polled the last report
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) if e.raw_os_error() == Some(nix::libc::ESHUTDOWN) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Read a HID output report (e.g. rumble or LEDs) sent from the host
+    /// into the given buffer. Returns the number of bytes read, or zero if no
+    /// report is available.
+    pub fn read_report(&mut self, buf: &mut [u8]) -> Result<usize, Box<dyn Error>> {
+        match self.hidg.read(buf) {
+            Ok(size) => Ok(size),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(0),
+            Err(e) if e.raw_os_error() == Some(nix::libc::ESHUTDOWN) => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for UsbGadget {
+    fn drop(&mut self) {
+        log::debug!("Removing USB gadget {:?}", self.path);
+        if let Err(e) = fs::write(self.path.join("UDC"), "") {
+            log::warn!("Failed to unbind USB gadget {:?}: {e}", self.path);
+        }
+        remove_gadget(&self.path);
+    }
+}
+
+/// Returns the names of USB device controllers that are not bound to a gadget
+pub fn get_available_udcs() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(UDC_PATH) else {
+        return Vec::new();
+    };
+    let bound = get_bound_udcs();
+    let mut udcs: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|udc| !bound.contains(udc))
+        .collect();
+    udcs.sort();
+    udcs
+}
+
+/// Returns the names of USB device controllers already used by a gadget
+fn get_bound_udcs() -> Vec<String> {
+    let Ok(entries) = fs::read_dir(CONFIGFS_GADGET_PATH) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path().join("UDC")).ok())
+        .map(|udc| udc.trim().to_string())
+        .filter(|udc| !udc.is_empty())
+        .collect()
+}
+
+/// Create the ConfigFS directory structure for a HID gadget
+fn create_gadget(path: &Path, config: &HidGadgetConfig) -> Result<(), Box<dyn Error>> {
+    fs::create_dir(path)?;
+    fs::write(path.join("idVendor"), format!("{:#06x}", config.vendor_id))?;
+    fs::write(
+        path.join("idProduct"),
+        format!("{:#06x}", config.product_id),
+    )?;
+    fs::write(
+        path.join("bcdDevice"),
+        format!("{:#06x}", config.bcd_device),
+    )?;
+    fs::write(path.join("bcdUSB"), "0x0200")?;
+
+    let strings = path.join("strings").join(LANG_ID);
+    fs::create_dir_all(&strings)?;
+    fs::write(strings.join("manufacturer"), &config.manufacturer)?;
+    fs::write(strings.join("product"), &config.product)?;
+    fs::write(strings.join("serialnumber"), &config.serial_number)?;
+
+    let function = path.join("functions").join(FUNCTION_NAME);
+    fs::create_dir_all(&function)?;
+    fs::write(function.join("protocol"), "0")?;
+    fs::write(function.join("subclass"), "0")?;
+    fs::write(
+        function.join("report_length"),
+        config.report_length.to_string(),
+    )?;
+    fs::write(function.join("report_desc"), &config.report_descriptor)?;
+
+    let configuration = path.join("configs").join(CONFIG_NAME);
+    let config_strings = configuration.join("strings").join(LANG_ID);
+    fs::create_dir_all(&config_strings)?;
+    fs::write(config_strings.join("configuration"), "InputPlumber HID")?;
+    fs::write(configuration.join("MaxPower"), config.max_power.to_string())?;
+    symlink(&function, configuration.join(FUNCTION_NAME))?;
+
+    Ok(())
+}
+
+/// Remove the ConfigFS directory structure of a gadget. ConfigFS requires
+/// directories to be removed in the reverse order they were created.
+fn remove_gadget(path: &Path) {
+    let configuration = path.join("configs").join(CONFIG_NAME);
+    let paths = [
+        configuration.join(FUNCTION_NAME),
+        configuration.join("strings").join(LANG_ID),
+        configuration.clone(),
+        path.join("functions").join(FUNCTION_NAME),
+        path.join("strings").join(LANG_ID),
+    ];
+    for path in paths {
+        let result = if path.is_symlink() {
+            fs::remove_file(&path)
+        } else {
+            fs::remove_dir(&path)
+        };
+        if let Err(e) = result {
+            if e.kind() != ErrorKind::NotFound {
+                log::debug!("Failed to remove {path:?}: {e}");
+            }
+        }
+    }
+    if let Err(e) = fs::remove_dir(path) {
+        if e.kind() != ErrorKind::NotFound {
+            log::warn!("Failed to remove USB gadget {path:?}: {e}");
+        }
+    }
+}
+
+/// Open the `/dev/hidgN` character device of the HID function of the gadget
+fn open_hidg(path: &Path) -> Result<File, Box<dyn Error>> {
+    // The function's "dev" file contains the "major:minor" device number,
+    // where the minor number matches the hidg device number.
+    let dev = fs::read_to_string(path.join("functions").join(FUNCTION_NAME).join("dev"))?;
+    let Some((_, minor)) = dev.trim().split_once(':') else {
+        return Err(format!("Invalid HID gadget device number: {dev}").into());
+    };
+    let device_path = format!("/dev/hidg{minor}");
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(OFlag::O_NONBLOCK.bits())
+        .open(&device_path)?;
+
+    Ok(file)
+}
@@ -0,0 +1,122 @@
+//! Module for tracking the focused app in gamescope sessions
+//!
+//! Gamescope publishes the Steam app id of the focused window on the root
+//! window of its Xwayland server with the "GAMESCOPE_FOCUSED_APP" atom. The
+//! app id can be used to apply per-game settings and to know which game was
+//! running when an issue occurred.
+
+use std::{env, error::Error, time::Duration};
+
+use tokio::sync::mpsc;
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{AtomEnum, ConnectionExt, Window},
+    rust_connection::RustConnection,
+};
+
+use crate::input::manager::ManagerCommand;
+
+/// Root window property with the app id of the focused app
+const FOCUSED_APP_ATOM: &str = "GAMESCOPE_FOCUSED_APP";
+/// Xwayland displays gamescope is commonly started on, if DISPLAY is not set
+const GAMESCOPE_DISPLAYS: &[&str] = &[":0", ":1"];
+/// How often the focused app should be polled
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Connection to the Xwayland server of a gamescope session
+struct GamescopeConnection {
+    conn: RustConnection,
+    root: Window,
+    focused_app_atom: u32,
+}
+
+impl GamescopeConnection {
+    /// Connect to the Xwayland server of gamescope on the given display. Only
+    /// servers that publish the focused app are accepted.
+    fn connect(display: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let (conn, screen) = x11rb::connect(Some(display))?;
+        let Some(root) = conn.setup().roots.get(screen).map(|screen| screen.root) else {
+            return Err("Unable to find X11 root window".into());
+        };
+        let focused_app_atom = conn
+            .intern_atom(true, FOCUSED_APP_ATOM.as_bytes())?
+            .reply()?
+            .atom;
+        if focused_app_atom == u32::from(AtomEnum::NONE) {
+            return Err(format!("Display {display} is not a gamescope session").into());
+        }
+
+        Ok(Self {
+            conn,
+            root,
+            focused_app_atom,
+        })
+    }
+
+    /// Returns the app id of the focused app. Returns [None] if no app is
+    /// focused.
+    fn get_focused_app(&self) -> Result<Option<u32>, Box<dyn Error + Send + Sync>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.focused_app_atom,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )?
+            .reply()?;
+        let app_id = reply.value32().and_then(|mut values| values.next());
+        Ok(app_id.filter(|app_id| *app_id != 0))
+    }
+}
+
+/// Connect to the first gamescope session that can be found
+fn connect() -> Option<GamescopeConnection> {
+    let displays = match env::var("DISPLAY") {
+        Ok(display) => vec![display],
+        Err(_) => GAMESCOPE_DISPLAYS.iter().map(|d| d.to_string()).collect(),
+    };
+    for display in displays {
+        match GamescopeConnection::connect(display.as_str()) {
+            Ok(conn) => {
+                log::info!("Connected to gamescope session on display {display}");
+                return Some(conn);
+            }
+            Err(e) => log::trace!("Unable to connect to gamescope on {display}: {e}"),
+        }
+    }
+    None
+}
+
+/// Watch for changes to the focused app in gamescope sessions and notify the
+/// manager whenever it changes. Gamescope sessions can start and stop at any
+/// time, so a lost connection is reestablished.
+pub fn watch(cmd_tx: mpsc::Sender<ManagerCommand>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut conn: Option<GamescopeConnection> = None;
+    let mut app_id = None;
+
+    loop {
+        if conn.is_none() {
+            conn = connect();
+        }
+        let new_app_id = match conn.as_ref().map(|conn| conn.get_focused_app()) {
+            Some(Ok(app_id)) => app_id,
+            Some(Err(e)) => {
+                log::debug!("Lost connection to gamescope session: {e}");
+                conn = None;
+                None
+            }
+            None => None,
+        };
+
+        if new_app_id != app_id {
+            log::info!("Focused app changed: {new_app_id:?}");
+            app_id = new_app_id;
+            cmd_tx.blocking_send(ManagerCommand::FocusedAppChanged { app_id })?;
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
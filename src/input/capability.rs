@@ -4,6 +4,14 @@ use crate::config::CapabilityConfig;
 
 use super::event::dbus::Action;
 
+/// Version of the capability string grammar. Capabilities are represented as
+/// colon-separated paths from the capability kind to the specific input (e.g.
+/// "Gamepad:Button:South" or "Touchpad:CenterPad:Button:Press"). Strings can
+/// optionally be prefixed with the grammar version (e.g. "v1:Keyboard:KeyA").
+/// The version must be increased whenever an existing name is changed so
+/// configs, DBus clients, and plugins can detect incompatible names.
+pub const CAPABILITY_VERSION: u32 = 1;
+
 /// A capability describes what kind of input events an input device is capable
 /// of emitting.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -23,18 +31,26 @@ pub enum Capability {
     Touchscreen(Touch),
 }
 
+impl Capability {
+    /// Returns the capability string prefixed with the version of the
+    /// capability string grammar. E.g. "v1:Gamepad:Button:South"
+    pub fn to_versioned_string(&self) -> String {
+        format!("v{CAPABILITY_VERSION}:{self}")
+    }
+}
+
 impl fmt::Display for Capability {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Capability::None => write!(f, "None"),
             Capability::NotImplemented => write!(f, "NotImplemented"),
             Capability::Sync => write!(f, "Sync"),
-            Capability::Gamepad(_) => write!(f, "Gamepad"),
-            Capability::Mouse(_) => write!(f, "Mouse"),
-            Capability::Keyboard(_) => write!(f, "Keyboard"),
-            Capability::DBus(_) => write!(f, "DBus"),
-            Capability::Touchpad(_) => write!(f, "Touchpad"),
-            Capability::Touchscreen(_) => write!(f, "Touchscreen"),
+            Capability::Gamepad(gamepad) => write!(f, "Gamepad:{gamepad}"),
+            Capability::Mouse(mouse) => write!(f, "Mouse:{mouse}"),
+            Capability::Keyboard(key) => write!(f, "Keyboard:{key}"),
+            Capability::DBus(action) => write!(f, "DBus:{}", action.as_str()),
+            Capability::Touchpad(touchpad) => write!(f, "Touchpad:{touchpad}"),
+            Capability::Touchscreen(touch) => write!(f, "Touchscreen:{touch}"),
         }
     }
 }
@@ -43,7 +59,19 @@ impl FromStr for Capability {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(':').collect();
+        let mut parts: Vec<&str> = s.split(':').collect();
+
+        // Strip the optional grammar version
+        if let Some(version) = parts.first().and_then(|part| part.strip_prefix('v')) {
+            let Ok(version) = version.parse::<u32>() else {
+                return Err(());
+            };
+            if version != CAPABILITY_VERSION {
+                return Err(());
+            }
+            parts.remove(0);
+        }
+
         let Some((part, parts)) = parts.split_first() else {
             return Err(());
         };
@@ -116,11 +144,32 @@ impl From<CapabilityConfig> for Capability {
             }
 
             // Gyro
-            if let Some(_gyro_capability) = gamepad.gyro.as_ref() {
-                unimplemented!();
+            if gamepad.gyro.is_some() {
+                return Capability::Gamepad(Gamepad::Gyro);
+            }
+
+            // Accelerometer
+            if gamepad.accelerometer.is_some() {
+                return Capability::Gamepad(Gamepad::Accelerometer);
             }
 
-            // TODO: Accelerometer
+            // Dial
+            if let Some(dial_string) = gamepad.dial.as_ref() {
+                let Ok(dial) = GamepadDial::from_str(dial_string) else {
+                    log::error!("Invalid or unimplemented dial: {dial_string}");
+                    return Capability::NotImplemented;
+                };
+                return Capability::Gamepad(Gamepad::Dial(dial));
+            }
+
+            // Gesture
+            if let Some(gesture_string) = gamepad.gesture.as_ref() {
+                let Ok(gesture) = GamepadGesture::from_str(gesture_string) else {
+                    log::error!("Invalid or unimplemented gesture: {gesture_string}");
+                    return Capability::NotImplemented;
+                };
+                return Capability::Gamepad(Gamepad::Gesture(gesture));
+            }
         }
 
         // Keyboard
@@ -233,16 +282,24 @@ pub enum Gamepad {
     /// Gyro events measure the angular velocity of a device measured
     /// with (x, y, z) values normalized to degrees per second.
     Gyro,
+    /// Radial dials are endless rotary encoders that report the number of
+    /// steps they were turned, where positive values are clockwise.
+    Dial(GamepadDial),
+    /// Motion gestures are detected from accelerometer patterns and behave
+    /// like buttons that are briefly pressed when the gesture is detected.
+    Gesture(GamepadGesture),
 }
 
 impl fmt::Display for Gamepad {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Gamepad::Button(_) => write!(f, "Button"),
-            Gamepad::Axis(_) => write!(f, "Axis"),
-            Gamepad::Trigger(_) => write!(f, "Trigger"),
+            Gamepad::Button(button) => write!(f, "Button:{button}"),
+            Gamepad::Axis(axis) => write!(f, "Axis:{axis}"),
+            Gamepad::Trigger(trigger) => write!(f, "Trigger:{trigger}"),
             Gamepad::Accelerometer => write!(f, "Accelerometer"),
             Gamepad::Gyro => write!(f, "Gyro"),
+            Gamepad::Dial(dial) => write!(f, "Dial:{dial}"),
+            Gamepad::Gesture(gesture) => write!(f, "Gesture:{gesture}"),
         }
     }
 }
@@ -266,6 +323,12 @@ impl FromStr for Gamepad {
             )?)),
             "Accelerometer" => Ok(Gamepad::Accelerometer),
             "Gyro" => Ok(Gamepad::Gyro),
+            "Dial" => Ok(Gamepad::Dial(GamepadDial::from_str(
+                parts.join(":").as_str(),
+            )?)),
+            "Gesture" => Ok(Gamepad::Gesture(GamepadGesture::from_str(
+                parts.join(":").as_str(),
+            )?)),
             _ => Err(()),
         }
     }
@@ -283,7 +346,7 @@ impl fmt::Display for Mouse {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Mouse::Motion => write!(f, "Motion"),
-            Mouse::Button(_) => write!(f, "Button"),
+            Mouse::Button(button) => write!(f, "Button:{button}"),
         }
     }
 }
@@ -390,6 +453,15 @@ pub enum GamepadButton {
     Screenshot,
     /// Dedicated mute button
     Mute,
+    /// Long press of the power button on the device chassis
+    PowerLongPress,
+    /// Long press of the volume down button on the device chassis
+    VolumeDownLongPress,
+    /// Long press of the volume up button on the device chassis
+    VolumeUpLongPress,
+    /// Hardware mouse/gamepad mode switch, held while the switch is in the
+    /// mouse position (e.g. GPD Win 4)
+    MouseModeSwitch,
     /// Directional Pad up
     DPadUp,
     /// Directional Pad down
@@ -398,6 +470,30 @@ pub enum GamepadButton {
     DPadLeft,
     /// Directional Pad right
     DPadRight,
+    /// Hat switch 1 up, e.g. a secondary directional pad
+    Hat1Up,
+    /// Hat switch 1 down, e.g. a secondary directional pad
+    Hat1Down,
+    /// Hat switch 1 left, e.g. a secondary directional pad
+    Hat1Left,
+    /// Hat switch 1 right, e.g. a secondary directional pad
+    Hat1Right,
+    /// Hat switch 2 up, e.g. a secondary directional pad
+    Hat2Up,
+    /// Hat switch 2 down, e.g. a secondary directional pad
+    Hat2Down,
+    /// Hat switch 2 left, e.g. a secondary directional pad
+    Hat2Left,
+    /// Hat switch 2 right, e.g. a secondary directional pad
+    Hat2Right,
+    /// Hat switch 3 up, e.g. a secondary directional pad
+    Hat3Up,
+    /// Hat switch 3 down, e.g. a secondary directional pad
+    Hat3Down,
+    /// Hat switch 3 left, e.g. a secondary directional pad
+    Hat3Left,
+    /// Hat switch 3 right, e.g. a secondary directional pad
+    Hat3Right,
     /// Left shoulder button, Sony L1, Xbox LB
     LeftBumper,
     /// Left top button on AyaNeo devices, inboard of left bumper
@@ -430,6 +526,20 @@ pub enum GamepadButton {
     RightStick,
     /// Touch binary sensor for right stick
     RightStickTouch,
+    /// H-shifter gear 1 on driving wheels
+    ShifterGear1,
+    /// H-shifter gear 2 on driving wheels
+    ShifterGear2,
+    /// H-shifter gear 3 on driving wheels
+    ShifterGear3,
+    /// H-shifter gear 4 on driving wheels
+    ShifterGear4,
+    /// H-shifter gear 5 on driving wheels
+    ShifterGear5,
+    /// H-shifter gear 6 on driving wheels
+    ShifterGear6,
+    /// H-shifter reverse gear on driving wheels
+    ShifterReverse,
 }
 
 impl fmt::Display for GamepadButton {
@@ -441,6 +551,18 @@ impl fmt::Display for GamepadButton {
             GamepadButton::DPadUp => write!(f, "DPadUp"),
             GamepadButton::East => write!(f, "East"),
             GamepadButton::Guide => write!(f, "Guide"),
+            GamepadButton::Hat1Up => write!(f, "Hat1Up"),
+            GamepadButton::Hat1Down => write!(f, "Hat1Down"),
+            GamepadButton::Hat1Left => write!(f, "Hat1Left"),
+            GamepadButton::Hat1Right => write!(f, "Hat1Right"),
+            GamepadButton::Hat2Up => write!(f, "Hat2Up"),
+            GamepadButton::Hat2Down => write!(f, "Hat2Down"),
+            GamepadButton::Hat2Left => write!(f, "Hat2Left"),
+            GamepadButton::Hat2Right => write!(f, "Hat2Right"),
+            GamepadButton::Hat3Up => write!(f, "Hat3Up"),
+            GamepadButton::Hat3Down => write!(f, "Hat3Down"),
+            GamepadButton::Hat3Left => write!(f, "Hat3Left"),
+            GamepadButton::Hat3Right => write!(f, "Hat3Right"),
             GamepadButton::Keyboard => write!(f, "Keyboard"),
             GamepadButton::LeftBumper => write!(f, "LeftBumper"),
             GamepadButton::LeftPaddle1 => write!(f, "LeftPaddle1"),
@@ -450,8 +572,10 @@ impl fmt::Display for GamepadButton {
             GamepadButton::LeftStickTouch => write!(f, "LeftStickTouch"),
             GamepadButton::LeftTop => write!(f, "LeftTop"),
             GamepadButton::LeftTrigger => write!(f, "LeftTrigger"),
+            GamepadButton::MouseModeSwitch => write!(f, "MouseModeSwitch"),
             GamepadButton::Mute => write!(f, "Mute"),
             GamepadButton::North => write!(f, "North"),
+            GamepadButton::PowerLongPress => write!(f, "PowerLongPress"),
             GamepadButton::QuickAccess => write!(f, "QuickAccess"),
             GamepadButton::QuickAccess2 => write!(f, "QuickAccess2"),
             GamepadButton::RightBumper => write!(f, "RightBumper"),
@@ -464,8 +588,17 @@ impl fmt::Display for GamepadButton {
             GamepadButton::RightTrigger => write!(f, "RightTrigger"),
             GamepadButton::Screenshot => write!(f, "Screenshot"),
             GamepadButton::Select => write!(f, "Select"),
+            GamepadButton::ShifterGear1 => write!(f, "ShifterGear1"),
+            GamepadButton::ShifterGear2 => write!(f, "ShifterGear2"),
+            GamepadButton::ShifterGear3 => write!(f, "ShifterGear3"),
+            GamepadButton::ShifterGear4 => write!(f, "ShifterGear4"),
+            GamepadButton::ShifterGear5 => write!(f, "ShifterGear5"),
+            GamepadButton::ShifterGear6 => write!(f, "ShifterGear6"),
+            GamepadButton::ShifterReverse => write!(f, "ShifterReverse"),
             GamepadButton::South => write!(f, "South"),
             GamepadButton::Start => write!(f, "Start"),
+            GamepadButton::VolumeDownLongPress => write!(f, "VolumeDownLongPress"),
+            GamepadButton::VolumeUpLongPress => write!(f, "VolumeUpLongPress"),
             GamepadButton::West => write!(f, "West"),
         }
     }
@@ -482,6 +615,18 @@ impl FromStr for GamepadButton {
             "DPadUp" => Ok(GamepadButton::DPadUp),
             "East" => Ok(GamepadButton::East),
             "Guide" => Ok(GamepadButton::Guide),
+            "Hat1Up" => Ok(GamepadButton::Hat1Up),
+            "Hat1Down" => Ok(GamepadButton::Hat1Down),
+            "Hat1Left" => Ok(GamepadButton::Hat1Left),
+            "Hat1Right" => Ok(GamepadButton::Hat1Right),
+            "Hat2Up" => Ok(GamepadButton::Hat2Up),
+            "Hat2Down" => Ok(GamepadButton::Hat2Down),
+            "Hat2Left" => Ok(GamepadButton::Hat2Left),
+            "Hat2Right" => Ok(GamepadButton::Hat2Right),
+            "Hat3Up" => Ok(GamepadButton::Hat3Up),
+            "Hat3Down" => Ok(GamepadButton::Hat3Down),
+            "Hat3Left" => Ok(GamepadButton::Hat3Left),
+            "Hat3Right" => Ok(GamepadButton::Hat3Right),
             "Keyboard" => Ok(GamepadButton::Keyboard),
             "LeftBumper" => Ok(GamepadButton::LeftBumper),
             "LeftPaddle1" => Ok(GamepadButton::LeftPaddle1),
@@ -491,8 +636,10 @@ impl FromStr for GamepadButton {
             "LeftStickTouch" => Ok(GamepadButton::LeftStickTouch),
             "LeftTop" => Ok(GamepadButton::LeftTop),
             "LeftTrigger" => Ok(GamepadButton::LeftTrigger),
+            "MouseModeSwitch" => Ok(GamepadButton::MouseModeSwitch),
             "Mute" => Ok(GamepadButton::Mute),
             "North" => Ok(GamepadButton::North),
+            "PowerLongPress" => Ok(GamepadButton::PowerLongPress),
             "QuickAccess" => Ok(GamepadButton::QuickAccess),
             "QuickAccess2" => Ok(GamepadButton::QuickAccess2),
             "RightBumper" => Ok(GamepadButton::RightBumper),
@@ -505,8 +652,17 @@ impl FromStr for GamepadButton {
             "RightTrigger" => Ok(GamepadButton::RightTrigger),
             "Screenshot" => Ok(GamepadButton::Screenshot),
             "Select" => Ok(GamepadButton::Select),
+            "ShifterGear1" => Ok(GamepadButton::ShifterGear1),
+            "ShifterGear2" => Ok(GamepadButton::ShifterGear2),
+            "ShifterGear3" => Ok(GamepadButton::ShifterGear3),
+            "ShifterGear4" => Ok(GamepadButton::ShifterGear4),
+            "ShifterGear5" => Ok(GamepadButton::ShifterGear5),
+            "ShifterGear6" => Ok(GamepadButton::ShifterGear6),
+            "ShifterReverse" => Ok(GamepadButton::ShifterReverse),
             "South" => Ok(GamepadButton::South),
             "Start" => Ok(GamepadButton::Start),
+            "VolumeDownLongPress" => Ok(GamepadButton::VolumeDownLongPress),
+            "VolumeUpLongPress" => Ok(GamepadButton::VolumeUpLongPress),
             "West" => Ok(GamepadButton::West),
             _ => Err(()),
         }
@@ -521,6 +677,8 @@ pub enum GamepadAxis {
     Hat1,
     Hat2,
     Hat3,
+    /// Steering wheel rotation on driving wheels
+    Steering,
 }
 
 impl fmt::Display for GamepadAxis {
@@ -532,6 +690,7 @@ impl fmt::Display for GamepadAxis {
             GamepadAxis::Hat1 => write!(f, "Hat1"),
             GamepadAxis::Hat2 => write!(f, "Hat2"),
             GamepadAxis::Hat3 => write!(f, "Hat3"),
+            GamepadAxis::Steering => write!(f, "Steering"),
         }
     }
 }
@@ -547,6 +706,75 @@ impl FromStr for GamepadAxis {
             "Hat1" => Ok(GamepadAxis::Hat1),
             "Hat2" => Ok(GamepadAxis::Hat2),
             "Hat3" => Ok(GamepadAxis::Hat3),
+            "Steering" => Ok(GamepadAxis::Steering),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadDial {
+    LeftDial,
+    RightDial,
+}
+
+impl fmt::Display for GamepadDial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GamepadDial::LeftDial => write!(f, "LeftDial"),
+            GamepadDial::RightDial => write!(f, "RightDial"),
+        }
+    }
+}
+
+impl FromStr for GamepadDial {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LeftDial" => Ok(GamepadDial::LeftDial),
+            "RightDial" => Ok(GamepadDial::RightDial),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadGesture {
+    /// The device is falling, e.g. because it was dropped
+    Freefall,
+    /// The device was picked up after resting
+    Pickup,
+    /// The device was set down and is resting
+    SetDown,
+    /// The device is rolled to the left, held while leaning
+    LeanLeft,
+    /// The device is rolled to the right, held while leaning
+    LeanRight,
+}
+
+impl fmt::Display for GamepadGesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GamepadGesture::Freefall => write!(f, "Freefall"),
+            GamepadGesture::Pickup => write!(f, "Pickup"),
+            GamepadGesture::SetDown => write!(f, "SetDown"),
+            GamepadGesture::LeanLeft => write!(f, "LeanLeft"),
+            GamepadGesture::LeanRight => write!(f, "LeanRight"),
+        }
+    }
+}
+
+impl FromStr for GamepadGesture {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Freefall" => Ok(GamepadGesture::Freefall),
+            "Pickup" => Ok(GamepadGesture::Pickup),
+            "SetDown" => Ok(GamepadGesture::SetDown),
+            "LeanLeft" => Ok(GamepadGesture::LeanLeft),
+            "LeanRight" => Ok(GamepadGesture::LeanRight),
             _ => Err(()),
         }
     }
@@ -560,6 +788,12 @@ pub enum GamepadTrigger {
     RightTrigger,
     RightTouchpadForce,
     RightStickForce,
+    /// Throttle (gas) pedal on driving wheels
+    Throttle,
+    /// Brake pedal on driving wheels
+    Brake,
+    /// Clutch pedal on driving wheels
+    Clutch,
 }
 
 impl fmt::Display for GamepadTrigger {
@@ -571,6 +805,9 @@ impl fmt::Display for GamepadTrigger {
             GamepadTrigger::RightTrigger => write!(f, "RightTrigger"),
             GamepadTrigger::RightTouchpadForce => write!(f, "RightTouchpadForce"),
             GamepadTrigger::RightStickForce => write!(f, "RightStickForce"),
+            GamepadTrigger::Throttle => write!(f, "Throttle"),
+            GamepadTrigger::Brake => write!(f, "Brake"),
+            GamepadTrigger::Clutch => write!(f, "Clutch"),
         }
     }
 }
@@ -586,6 +823,9 @@ impl FromStr for GamepadTrigger {
             "RightTrigger" => Ok(GamepadTrigger::RightTrigger),
             "RightTouchpadForce" => Ok(GamepadTrigger::RightTouchpadForce),
             "RightStickForce" => Ok(GamepadTrigger::RightStickForce),
+            "Throttle" => Ok(GamepadTrigger::Throttle),
+            "Brake" => Ok(GamepadTrigger::Brake),
+            "Clutch" => Ok(GamepadTrigger::Clutch),
             _ => Err(()),
         }
     }
@@ -611,6 +851,8 @@ pub enum Keyboard {
     KeyBack,
     KeyBackslash,
     KeyBackspace,
+    KeyBrightnessDown,
+    KeyBrightnessUp,
     KeyC,
     KeyCalc,
     KeyCapslock,
@@ -778,6 +1020,8 @@ impl fmt::Display for Keyboard {
             Keyboard::KeyBack => write!(f, "KeyBack"),
             Keyboard::KeyBackslash => write!(f, "KeyBackslash"),
             Keyboard::KeyBackspace => write!(f, "KeyBackspace"),
+            Keyboard::KeyBrightnessDown => write!(f, "KeyBrightnessDown"),
+            Keyboard::KeyBrightnessUp => write!(f, "KeyBrightnessUp"),
             Keyboard::KeyC => write!(f, "KeyC"),
             Keyboard::KeyCalc => write!(f, "KeyCalc"),
             Keyboard::KeyCapslock => write!(f, "KeyCapslock"),
@@ -949,6 +1193,8 @@ impl FromStr for Keyboard {
             "KeyBack" => Ok(Keyboard::KeyBack),
             "KeyBackslash" => Ok(Keyboard::KeyBackslash),
             "KeyBackspace" => Ok(Keyboard::KeyBackspace),
+            "KeyBrightnessDown" => Ok(Keyboard::KeyBrightnessDown),
+            "KeyBrightnessUp" => Ok(Keyboard::KeyBrightnessUp),
             "KeyC" => Ok(Keyboard::KeyC),
             "KeyCalc" => Ok(Keyboard::KeyCalc),
             "KeyCapslock" => Ok(Keyboard::KeyCapslock),
@@ -1029,7 +1275,8 @@ impl FromStr for Keyboard {
             "KeyKpPlus" => Ok(Keyboard::KeyKpPlus),
             "KeyKpRightParen" => Ok(Keyboard::KeyKpRightParen),
             "KeyKpSlash" => Ok(Keyboard::KeyKpSlash),
-            "KeyKpdot" => Ok(Keyboard::KeyKpDot),
+            // "KeyKpdot" is accepted for compatibility with older configs
+            "KeyKpDot" | "KeyKpdot" => Ok(Keyboard::KeyKpDot),
             "KeyL" => Ok(Keyboard::KeyL),
             "KeyLeft" => Ok(Keyboard::KeyLeft),
             "KeyLeftAlt" => Ok(Keyboard::KeyLeftAlt),
@@ -1106,12 +1353,23 @@ pub enum Touchpad {
     CenterPad(Touch),
 }
 
+impl Touchpad {
+    /// Returns the name of the touchpad without the touch input. E.g. "LeftPad"
+    pub fn pad_name(&self) -> &'static str {
+        match self {
+            Touchpad::LeftPad(_) => "LeftPad",
+            Touchpad::RightPad(_) => "RightPad",
+            Touchpad::CenterPad(_) => "CenterPad",
+        }
+    }
+}
+
 impl fmt::Display for Touchpad {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Touchpad::LeftPad(_) => write!(f, "LeftPad"),
-            Touchpad::RightPad(_) => write!(f, "RightPad"),
-            Touchpad::CenterPad(_) => write!(f, "CenterPad"),
+            Touchpad::LeftPad(touch) | Touchpad::RightPad(touch) | Touchpad::CenterPad(touch) => {
+                write!(f, "{}:{touch}", self.pad_name())
+            }
         }
     }
 }
@@ -1149,7 +1407,7 @@ impl fmt::Display for Touch {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Touch::Motion => write!(f, "Motion"),
-            Touch::Button(_) => write!(f, "Button"),
+            Touch::Button(button) => write!(f, "Button:{button}"),
         }
     }
 }
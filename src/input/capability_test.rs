@@ -0,0 +1,411 @@
+use std::str::FromStr;
+
+use crate::input::{
+    capability::{
+        Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger, Keyboard, Mouse,
+        MouseButton, Touch, TouchButton, Touchpad, CAPABILITY_VERSION,
+    },
+    event::dbus::Action,
+};
+
+fn gamepad_buttons() -> Vec<GamepadButton> {
+    vec![
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::Start,
+        GamepadButton::Select,
+        GamepadButton::Guide,
+        GamepadButton::QuickAccess,
+        GamepadButton::QuickAccess2,
+        GamepadButton::Keyboard,
+        GamepadButton::Screenshot,
+        GamepadButton::Mute,
+        GamepadButton::PowerLongPress,
+        GamepadButton::VolumeDownLongPress,
+        GamepadButton::VolumeUpLongPress,
+        GamepadButton::MouseModeSwitch,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+        GamepadButton::Hat1Up,
+        GamepadButton::Hat1Down,
+        GamepadButton::Hat1Left,
+        GamepadButton::Hat1Right,
+        GamepadButton::Hat2Up,
+        GamepadButton::Hat2Down,
+        GamepadButton::Hat2Left,
+        GamepadButton::Hat2Right,
+        GamepadButton::Hat3Up,
+        GamepadButton::Hat3Down,
+        GamepadButton::Hat3Left,
+        GamepadButton::Hat3Right,
+        GamepadButton::LeftBumper,
+        GamepadButton::LeftTop,
+        GamepadButton::LeftTrigger,
+        GamepadButton::LeftPaddle1,
+        GamepadButton::LeftPaddle2,
+        GamepadButton::LeftPaddle3,
+        GamepadButton::LeftStick,
+        GamepadButton::LeftStickTouch,
+        GamepadButton::RightBumper,
+        GamepadButton::RightTop,
+        GamepadButton::RightTrigger,
+        GamepadButton::RightPaddle1,
+        GamepadButton::RightPaddle2,
+        GamepadButton::RightPaddle3,
+        GamepadButton::RightStick,
+        GamepadButton::RightStickTouch,
+        GamepadButton::ShifterGear1,
+        GamepadButton::ShifterGear2,
+        GamepadButton::ShifterGear3,
+        GamepadButton::ShifterGear4,
+        GamepadButton::ShifterGear5,
+        GamepadButton::ShifterGear6,
+        GamepadButton::ShifterReverse,
+    ]
+}
+
+fn gamepad_axes() -> Vec<GamepadAxis> {
+    vec![
+        GamepadAxis::LeftStick,
+        GamepadAxis::RightStick,
+        GamepadAxis::Hat0,
+        GamepadAxis::Hat1,
+        GamepadAxis::Hat2,
+        GamepadAxis::Hat3,
+        GamepadAxis::Steering,
+    ]
+}
+
+fn gamepad_triggers() -> Vec<GamepadTrigger> {
+    vec![
+        GamepadTrigger::LeftTrigger,
+        GamepadTrigger::LeftTouchpadForce,
+        GamepadTrigger::LeftStickForce,
+        GamepadTrigger::RightTrigger,
+        GamepadTrigger::RightTouchpadForce,
+        GamepadTrigger::RightStickForce,
+        GamepadTrigger::Throttle,
+        GamepadTrigger::Brake,
+        GamepadTrigger::Clutch,
+    ]
+}
+
+fn mouse_buttons() -> Vec<MouseButton> {
+    vec![
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::WheelUp,
+        MouseButton::WheelDown,
+        MouseButton::WheelLeft,
+        MouseButton::WheelRight,
+        MouseButton::Extra,
+        MouseButton::Side,
+    ]
+}
+
+fn keys() -> Vec<Keyboard> {
+    vec![
+        Keyboard::Key0,
+        Keyboard::Key1,
+        Keyboard::Key102nd,
+        Keyboard::Key2,
+        Keyboard::Key3,
+        Keyboard::Key4,
+        Keyboard::Key5,
+        Keyboard::Key6,
+        Keyboard::Key7,
+        Keyboard::Key8,
+        Keyboard::Key9,
+        Keyboard::KeyA,
+        Keyboard::KeyAgain,
+        Keyboard::KeyApostrophe,
+        Keyboard::KeyB,
+        Keyboard::KeyBack,
+        Keyboard::KeyBackslash,
+        Keyboard::KeyBackspace,
+        Keyboard::KeyBrightnessDown,
+        Keyboard::KeyBrightnessUp,
+        Keyboard::KeyC,
+        Keyboard::KeyCalc,
+        Keyboard::KeyCapslock,
+        Keyboard::KeyComma,
+        Keyboard::KeyCompose,
+        Keyboard::KeyCopy,
+        Keyboard::KeyCut,
+        Keyboard::KeyD,
+        Keyboard::KeyDelete,
+        Keyboard::KeyDot,
+        Keyboard::KeyDown,
+        Keyboard::KeyE,
+        Keyboard::KeyEdit,
+        Keyboard::KeyEjectCD,
+        Keyboard::KeyEnd,
+        Keyboard::KeyEnter,
+        Keyboard::KeyEqual,
+        Keyboard::KeyEsc,
+        Keyboard::KeyF,
+        Keyboard::KeyF1,
+        Keyboard::KeyF10,
+        Keyboard::KeyF11,
+        Keyboard::KeyF12,
+        Keyboard::KeyF13,
+        Keyboard::KeyF14,
+        Keyboard::KeyF15,
+        Keyboard::KeyF16,
+        Keyboard::KeyF17,
+        Keyboard::KeyF18,
+        Keyboard::KeyF19,
+        Keyboard::KeyF2,
+        Keyboard::KeyF20,
+        Keyboard::KeyF21,
+        Keyboard::KeyF22,
+        Keyboard::KeyF23,
+        Keyboard::KeyF24,
+        Keyboard::KeyF3,
+        Keyboard::KeyF4,
+        Keyboard::KeyF5,
+        Keyboard::KeyF6,
+        Keyboard::KeyF7,
+        Keyboard::KeyF8,
+        Keyboard::KeyF9,
+        Keyboard::KeyFind,
+        Keyboard::KeyForward,
+        Keyboard::KeyFront,
+        Keyboard::KeyG,
+        Keyboard::KeyGrave,
+        Keyboard::KeyH,
+        Keyboard::KeyHanja,
+        Keyboard::KeyHelp,
+        Keyboard::KeyHenkan,
+        Keyboard::KeyHiragana,
+        Keyboard::KeyHome,
+        Keyboard::KeyI,
+        Keyboard::KeyInsert,
+        Keyboard::KeyJ,
+        Keyboard::KeyK,
+        Keyboard::KeyKatakana,
+        Keyboard::KeyKatakanaHiragana,
+        Keyboard::KeyKp0,
+        Keyboard::KeyKp1,
+        Keyboard::KeyKp2,
+        Keyboard::KeyKp3,
+        Keyboard::KeyKp4,
+        Keyboard::KeyKp5,
+        Keyboard::KeyKp6,
+        Keyboard::KeyKp7,
+        Keyboard::KeyKp8,
+        Keyboard::KeyKp9,
+        Keyboard::KeyKpAsterisk,
+        Keyboard::KeyKpComma,
+        Keyboard::KeyKpDot,
+        Keyboard::KeyKpEnter,
+        Keyboard::KeyKpEqual,
+        Keyboard::KeyKpJpComma,
+        Keyboard::KeyKpLeftParen,
+        Keyboard::KeyKpMinus,
+        Keyboard::KeyKpPlus,
+        Keyboard::KeyKpRightParen,
+        Keyboard::KeyKpSlash,
+        Keyboard::KeyL,
+        Keyboard::KeyLeft,
+        Keyboard::KeyLeftAlt,
+        Keyboard::KeyLeftBrace,
+        Keyboard::KeyLeftCtrl,
+        Keyboard::KeyLeftMeta,
+        Keyboard::KeyLeftShift,
+        Keyboard::KeyM,
+        Keyboard::KeyMinus,
+        Keyboard::KeyMuhenkan,
+        Keyboard::KeyMute,
+        Keyboard::KeyN,
+        Keyboard::KeyNextSong,
+        Keyboard::KeyNumlock,
+        Keyboard::KeyO,
+        Keyboard::KeyOpen,
+        Keyboard::KeyP,
+        Keyboard::KeyPageDown,
+        Keyboard::KeyPageUp,
+        Keyboard::KeyPaste,
+        Keyboard::KeyPause,
+        Keyboard::KeyPlayPause,
+        Keyboard::KeyPower,
+        Keyboard::KeyPreviousSong,
+        Keyboard::KeyProg1,
+        Keyboard::KeyProps,
+        Keyboard::KeyQ,
+        Keyboard::KeyR,
+        Keyboard::KeyRecord,
+        Keyboard::KeyRefresh,
+        Keyboard::KeyRight,
+        Keyboard::KeyRightAlt,
+        Keyboard::KeyRightBrace,
+        Keyboard::KeyRightCtrl,
+        Keyboard::KeyRightMeta,
+        Keyboard::KeyRightShift,
+        Keyboard::KeyRo,
+        Keyboard::KeyS,
+        Keyboard::KeyScrollDown,
+        Keyboard::KeyScrollLock,
+        Keyboard::KeyScrollUp,
+        Keyboard::KeySemicolon,
+        Keyboard::KeySlash,
+        Keyboard::KeySleep,
+        Keyboard::KeySpace,
+        Keyboard::KeyStop,
+        Keyboard::KeyStopCD,
+        Keyboard::KeySysrq,
+        Keyboard::KeyT,
+        Keyboard::KeyTab,
+        Keyboard::KeyU,
+        Keyboard::KeyUndo,
+        Keyboard::KeyUp,
+        Keyboard::KeyV,
+        Keyboard::KeyVolumeDown,
+        Keyboard::KeyVolumeUp,
+        Keyboard::KeyW,
+        Keyboard::KeyWww,
+        Keyboard::KeyX,
+        Keyboard::KeyY,
+        Keyboard::KeyYen,
+        Keyboard::KeyZ,
+        Keyboard::KeyZenkakuhankaku,
+    ]
+}
+
+fn actions() -> Vec<Action> {
+    vec![
+        Action::None,
+        Action::Guide,
+        Action::Quick,
+        Action::Quick2,
+        Action::Context,
+        Action::Option,
+        Action::Select,
+        Action::Accept,
+        Action::Back,
+        Action::ActOn,
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::L1,
+        Action::L2,
+        Action::L3,
+        Action::R1,
+        Action::R2,
+        Action::R3,
+        Action::VolumeUp,
+        Action::VolumeDown,
+        Action::VolumeMute,
+        Action::Keyboard,
+        Action::Screenshot,
+        Action::Touch,
+    ]
+}
+
+/// Returns every touch input on a touch device
+fn touches() -> Vec<Touch> {
+    let mut touches = vec![Touch::Motion];
+    touches.extend([TouchButton::Touch, TouchButton::Press].map(Touch::Button));
+    touches
+}
+
+/// Returns every capability
+fn capabilities() -> Vec<Capability> {
+    let mut capabilities = vec![
+        Capability::None,
+        Capability::NotImplemented,
+        Capability::Sync,
+        Capability::Gamepad(Gamepad::Accelerometer),
+        Capability::Gamepad(Gamepad::Gyro),
+        Capability::Mouse(Mouse::Motion),
+    ];
+    for button in gamepad_buttons() {
+        capabilities.push(Capability::Gamepad(Gamepad::Button(button)));
+    }
+    for axis in gamepad_axes() {
+        capabilities.push(Capability::Gamepad(Gamepad::Axis(axis)));
+    }
+    for trigger in gamepad_triggers() {
+        capabilities.push(Capability::Gamepad(Gamepad::Trigger(trigger)));
+    }
+    for button in mouse_buttons() {
+        capabilities.push(Capability::Mouse(Mouse::Button(button)));
+    }
+    for key in keys() {
+        capabilities.push(Capability::Keyboard(key));
+    }
+    for action in actions() {
+        capabilities.push(Capability::DBus(action));
+    }
+    for touch in touches() {
+        capabilities.push(Capability::Touchpad(Touchpad::LeftPad(touch.clone())));
+        capabilities.push(Capability::Touchpad(Touchpad::RightPad(touch.clone())));
+        capabilities.push(Capability::Touchpad(Touchpad::CenterPad(touch.clone())));
+        capabilities.push(Capability::Touchscreen(touch));
+    }
+    capabilities
+}
+
+#[test]
+fn test_capability_round_trip() {
+    for cap in capabilities() {
+        let name = cap.to_string();
+        assert_eq!(
+            Capability::from_str(name.as_str()),
+            Ok(cap.clone()),
+            "{name}"
+        );
+        let name = cap.to_versioned_string();
+        assert_eq!(Capability::from_str(name.as_str()), Ok(cap), "{name}");
+    }
+}
+
+#[test]
+fn test_capability_names_are_unique() {
+    let capabilities = capabilities();
+    let mut names: Vec<String> = capabilities.iter().map(|cap| cap.to_string()).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), capabilities.len());
+}
+
+#[test]
+fn test_capability_grammar() {
+    let cases = [
+        (
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            "Gamepad:Button:South",
+        ),
+        (
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            "Gamepad:Axis:LeftStick",
+        ),
+        (Capability::Mouse(Mouse::Motion), "Mouse:Motion"),
+        (Capability::Keyboard(Keyboard::KeyA), "Keyboard:KeyA"),
+        (Capability::DBus(Action::Guide), "DBus:ui_guide"),
+        (
+            Capability::Touchpad(Touchpad::CenterPad(Touch::Button(TouchButton::Press))),
+            "Touchpad:CenterPad:Button:Press",
+        ),
+        (Capability::Touchscreen(Touch::Motion), "Touchscreen:Motion"),
+    ];
+    for (cap, name) in cases {
+        assert_eq!(cap.to_string(), name);
+    }
+
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let name = format!("v{CAPABILITY_VERSION}:Gamepad:Button:South");
+    assert_eq!(cap.to_versioned_string(), name);
+
+    // Unknown versions and names should fail to parse
+    let name = format!("v{}:Gamepad:Button:South", CAPABILITY_VERSION + 1);
+    assert!(Capability::from_str(name.as_str()).is_err());
+    assert!(Capability::from_str("Gamepad:Button:Unknown").is_err());
+    assert!(Capability::from_str("Gamepad").is_err());
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use nix::unistd::User;
+use zbus::Connection;
+
+use crate::{
+    config::{path::get_actions_paths, ActionCapability, DBusActionCapability},
+    session::power::{self, PowerAction},
+};
+
+/// Default minimum time between running the same action
+const DEFAULT_DEBOUNCE_MS: u64 = 500;
+/// User to run commands as if none is configured
+const DEFAULT_RUN_AS: &str = "nobody";
+
+/// Runs actions from capability map mappings. Actions are debounced so holding
+/// or repeatedly pressing a button does not spawn many commands at once.
+#[derive(Debug, Default)]
+pub struct ActionRunner {
+    /// Time each action was last run, keyed by mapping name
+    last_run: HashMap<String, Instant>,
+}
+
+impl ActionRunner {
+    /// Run the given action for the mapping with the given name if it has not
+    /// been run within its debounce time. The action is run in a separate task.
+    pub fn run(&mut self, conn: &Connection, name: &str, action: &ActionCapability) {
+        let now = Instant::now();
+        let debounce = Duration::from_millis(action.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS));
+        if let Some(last_run) = self.last_run.get(name) {
+            if now.duration_since(*last_run) < debounce {
+                log::debug!("Debouncing action for mapping: {name}");
+                return;
+            }
+        }
+        self.last_run.insert(name.to_string(), now);
+
+        if let Some(dbus) = action.dbus.clone() {
+            let conn = conn.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = call_dbus_method(&conn, &dbus).await {
+                    log::error!("Failed to call DBus action {}: {e:?}", dbus.method);
+                }
+            });
+        }
+
+        if let Some(command) = action.command.clone() {
+            let run_as = action
+                .run_as
+                .clone()
+                .unwrap_or_else(|| DEFAULT_RUN_AS.to_string());
+            tokio::task::spawn(async move {
+                if let Err(e) = run_command(&command, &run_as).await {
+                    log::error!("Failed to run command action {command}: {e:?}");
+                }
+            });
+        }
+
+        if let Some(power) = action.power.as_ref() {
+            let power = match power.parse::<PowerAction>() {
+                Ok(power) => power,
+                Err(e) => {
+                    log::error!("Failed to run power action for mapping {name}: {e}");
+                    return;
+                }
+            };
+            let conn = conn.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = power::request(&conn, power).await {
+                    log::error!("Failed to run power action {power}: {e:?}");
+                }
+            });
+        }
+    }
+}
+
+/// Call the given allow-listed DBus method on the system bus
+async fn call_dbus_method(
+    conn: &Connection,
+    action: &DBusActionCapability,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !is_dbus_method_allowed(action) {
+        return Err(format!(
+            "DBus method not allowed by action directories: {}.{} on {}",
+            action.interface, action.method, action.destination
+        )
+        .into());
+    }
+    log::debug!(
+        "Calling DBus action {}.{} on {}",
+        action.interface,
+        action.method,
+        action.destination
+    );
+    conn.call_method(
+        Some(action.destination.as_str()),
+        action.path.as_str(),
+        Some(action.interface.as_str()),
+        action.method.as_str(),
+        &(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Returns true if the given name can be used as a single path component
+/// inside the action directories
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/') && !name.starts_with('.')
+}
+
+/// Returns true if the given DBus method is allow-listed. Only methods with a
+/// file named after the interface and method in a directory named after the
+/// destination inside the "dbus" directory of one of the action directories
+/// can be called.
+/// E.g. "/etc/inputplumber/actions.d/dbus/org.example.Service/org.example.Interface.Method"
+pub fn is_dbus_method_allowed(action: &DBusActionCapability) -> bool {
+    let names = [
+        action.destination.as_str(),
+        action.interface.as_str(),
+        action.method.as_str(),
+    ];
+    if !names.into_iter().all(is_valid_name) {
+        log::warn!("Invalid DBus action: {action:?}");
+        return false;
+    }
+    let method = format!("{}.{}", action.interface, action.method);
+    get_actions_paths()
+        .into_iter()
+        .map(|dir| dir.join("dbus").join(&action.destination).join(&method))
+        .any(|path| path.is_file())
+}
+
+/// Returns the path to the allow-listed command with the given name. Only
+/// executables that exist directly inside one of the action directories can
+/// be run.
+pub fn find_command(name: &str) -> Option<PathBuf> {
+    if !is_valid_name(name) {
+        log::warn!("Invalid action command name: {name}");
+        return None;
+    }
+    get_actions_paths()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Run the allow-listed command with the given name as the given user. The
+/// command is run without arguments and with an empty environment.
+async fn run_command(name: &str, run_as: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(path) = find_command(name) else {
+        return Err(format!("Command not found in action directories: {name}").into());
+    };
+    let Some(user) = User::from_name(run_as)? else {
+        return Err(format!("User not found: {run_as}").into());
+    };
+
+    log::debug!("Running command action {path:?} as {run_as}");
+    let status = tokio::process::Command::new(path)
+        .env_clear()
+        .env("HOME", user.dir)
+        .env("USER", user.name)
+        .uid(user.uid.as_raw())
+        .gid(user.gid.as_raw())
+        .current_dir("/")
+        .status()
+        .await?;
+    if !status.success() {
+        log::warn!("Command action {name} exited with status: {status}");
+    }
+
+    Ok(())
+}
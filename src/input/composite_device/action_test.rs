@@ -0,0 +1,39 @@
+use crate::config::DBusActionCapability;
+
+use super::action::{find_command, is_dbus_method_allowed};
+
+#[test]
+fn test_find_command_invalid_names() {
+    // Names must be a single file inside one of the action directories
+    assert!(find_command("").is_none());
+    assert!(find_command("/").is_none());
+    assert!(find_command("/bin/sh").is_none());
+    assert!(find_command("../../bin/sh").is_none());
+    assert!(find_command(".").is_none());
+    assert!(find_command("..").is_none());
+    assert!(find_command(".hidden").is_none());
+}
+
+#[test]
+fn test_dbus_method_allowed_invalid_names() {
+    let action = DBusActionCapability {
+        destination: "org.freedesktop.login1".to_string(),
+        path: "/org/freedesktop/login1".to_string(),
+        interface: "org.freedesktop.login1.Manager".to_string(),
+        method: "PowerOff".to_string(),
+    };
+
+    // Methods that are not allow-listed cannot be called
+    assert!(!is_dbus_method_allowed(&action));
+
+    // Names must not escape the action directories
+    let mut invalid = action.clone();
+    invalid.destination = "..".to_string();
+    assert!(!is_dbus_method_allowed(&invalid));
+    let mut invalid = action.clone();
+    invalid.interface = "org/freedesktop".to_string();
+    assert!(!is_dbus_method_allowed(&invalid));
+    let mut invalid = action;
+    invalid.method = String::new();
+    assert!(!is_dbus_method_allowed(&invalid));
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use crate::config::ActivatorConfig;
+
+/// Default time a source event must be held for a long press
+const DEFAULT_LONG_PRESS_MS: u64 = 500;
+/// Default maximum time between the presses of a double press
+const DEFAULT_DOUBLE_PRESS_MS: u64 = 300;
+
+/// Defines how a digital source event activates the target events of a
+/// profile mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activator {
+    /// Target events are pressed while the source event is pressed
+    Press,
+    /// Target events are pressed once the source event is held for the given
+    /// time
+    LongPress(Duration),
+    /// Target events are pressed on the second press of the source event, if
+    /// it follows the first press within the given time
+    DoublePress(Duration),
+}
+
+impl Activator {
+    /// Create the activator from the given configuration
+    pub fn new(config: &ActivatorConfig) -> Self {
+        match config.kind.as_str() {
+            "long_press" => {
+                let time = config.time_ms.unwrap_or(DEFAULT_LONG_PRESS_MS);
+                Activator::LongPress(Duration::from_millis(time))
+            }
+            "double_press" => {
+                let time = config.time_ms.unwrap_or(DEFAULT_DOUBLE_PRESS_MS);
+                Activator::DoublePress(Duration::from_millis(time))
+            }
+            "press" => Activator::Press,
+            kind => {
+                log::warn!("Unknown activator kind: {kind}");
+                Activator::Press
+            }
+        }
+    }
+}
+
+/// The result of updating an [ActivatorState]
+#[derive(Debug, Clone, Default)]
+pub struct ActivatorUpdate {
+    /// The new pressed state of the target events, if it changed
+    pub changed: Option<bool>,
+    /// If set, the activator should be re-evaluated after the given duration
+    /// to check if a long press was reached.
+    pub wake_after: Option<Duration>,
+}
+
+/// Tracks the timing of a digital source event to activate the target events
+/// of a profile mapping with a long or double press.
+#[derive(Debug, Clone, Default)]
+pub struct ActivatorState {
+    /// Time the source event was pressed, while it is held
+    pressed_at: Option<Instant>,
+    /// Time of the last press that could be the first of a double press
+    last_press: Option<Instant>,
+    /// Whether the target events are currently pressed
+    active: bool,
+}
+
+impl ActivatorState {
+    /// Update the state with the given pressed state of the source event. If
+    /// no pressed state is given, the timing of a held source event is
+    /// re-evaluated.
+    pub fn update(
+        &mut self,
+        pressed: Option<bool>,
+        activator: &Activator,
+        now: Instant,
+    ) -> ActivatorUpdate {
+        let mut update = ActivatorUpdate::default();
+        match pressed {
+            Some(true) => {
+                if self.pressed_at.is_some() {
+                    return update;
+                }
+                self.pressed_at = Some(now);
+                match activator {
+                    Activator::Press => self.set_active(true, &mut update),
+                    Activator::LongPress(time) => update.wake_after = Some(*time),
+                    Activator::DoublePress(time) => {
+                        let is_double = self
+                            .last_press
+                            .is_some_and(|last| now.duration_since(last) <= *time);
+                        if is_double {
+                            self.last_press = None;
+                            self.set_active(true, &mut update);
+                        } else {
+                            self.last_press = Some(now);
+                        }
+                    }
+                }
+            }
+            Some(false) => {
+                self.pressed_at = None;
+                self.set_active(false, &mut update);
+            }
+            None => {
+                let (Activator::LongPress(time), Some(pressed_at)) = (activator, self.pressed_at)
+                else {
+                    return update;
+                };
+                let held = now.duration_since(pressed_at);
+                if held >= *time {
+                    self.set_active(true, &mut update);
+                } else {
+                    update.wake_after = Some(*time - held);
+                }
+            }
+        }
+        update
+    }
+
+    /// Set whether the target events are pressed, recording the change in
+    /// the given update
+    fn set_active(&mut self, active: bool, update: &mut ActivatorUpdate) {
+        if self.active == active {
+            return;
+        }
+        self.active = active;
+        update.changed = Some(active);
+    }
+}
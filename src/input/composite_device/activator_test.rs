@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use crate::config::ActivatorConfig;
+
+use super::activator::{Activator, ActivatorState};
+
+#[test]
+fn test_activator_config() {
+    let config = ActivatorConfig {
+        kind: "long_press".to_string(),
+        time_ms: None,
+    };
+    assert_eq!(
+        Activator::new(&config),
+        Activator::LongPress(Duration::from_millis(500))
+    );
+    let config = ActivatorConfig {
+        kind: "double_press".to_string(),
+        time_ms: Some(200),
+    };
+    assert_eq!(
+        Activator::new(&config),
+        Activator::DoublePress(Duration::from_millis(200))
+    );
+    let config = ActivatorConfig {
+        kind: "triple_press".to_string(),
+        time_ms: None,
+    };
+    assert_eq!(Activator::new(&config), Activator::Press);
+}
+
+#[test]
+fn test_long_press() {
+    let activator = Activator::LongPress(Duration::from_millis(500));
+    let mut state = ActivatorState::default();
+    let start = Instant::now();
+
+    // Short presses never activate
+    let update = state.update(Some(true), &activator, start);
+    assert_eq!(update.changed, None);
+    assert_eq!(update.wake_after, Some(Duration::from_millis(500)));
+    let update = state.update(Some(false), &activator, start + Duration::from_millis(200));
+    assert_eq!(update.changed, None);
+    let update = state.update(None, &activator, start + Duration::from_millis(500));
+    assert_eq!(update.changed, None);
+
+    // Holding activates once the time is reached, until released
+    let start = start + Duration::from_secs(1);
+    state.update(Some(true), &activator, start);
+    let update = state.update(None, &activator, start + Duration::from_millis(300));
+    assert_eq!(update.changed, None);
+    assert_eq!(update.wake_after, Some(Duration::from_millis(200)));
+    let update = state.update(None, &activator, start + Duration::from_millis(500));
+    assert_eq!(update.changed, Some(true));
+    let update = state.update(Some(false), &activator, start + Duration::from_millis(800));
+    assert_eq!(update.changed, Some(false));
+}
+
+#[test]
+fn test_double_press() {
+    let activator = Activator::DoublePress(Duration::from_millis(300));
+    let mut state = ActivatorState::default();
+    let start = Instant::now();
+
+    // Presses too far apart do not activate
+    let update = state.update(Some(true), &activator, start);
+    assert_eq!(update.changed, None);
+    state.update(Some(false), &activator, start + Duration::from_millis(100));
+    let start = start + Duration::from_millis(400);
+    let update = state.update(Some(true), &activator, start);
+    assert_eq!(update.changed, None);
+    state.update(Some(false), &activator, start + Duration::from_millis(100));
+
+    // The second quick press activates until it is released
+    let update = state.update(Some(true), &activator, start + Duration::from_millis(250));
+    assert_eq!(update.changed, Some(true));
+    let update = state.update(Some(false), &activator, start + Duration::from_millis(400));
+    assert_eq!(update.changed, Some(false));
+
+    // A third press starts over
+    let update = state.update(Some(true), &activator, start + Duration::from_millis(450));
+    assert_eq!(update.changed, None);
+}
@@ -0,0 +1,290 @@
+use std::{
+    error::Error,
+    f64::consts::TAU,
+    io::Write,
+    os::unix::process::CommandExt,
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use nix::unistd::{Uid, User};
+
+use crate::{config::AudioHapticsConfig, session::get_active_session_user};
+
+use super::ff_engine::RumbleMagnitude;
+
+/// Default frequency in Hz of the waveform for the strong rumble motor
+const DEFAULT_STRONG_FREQUENCY: f64 = 100.0;
+/// Default frequency in Hz of the waveform for the weak rumble motor
+const DEFAULT_WEAK_FREQUENCY: f64 = 200.0;
+/// Default amplitude of the weakest non-zero rumble magnitude
+const DEFAULT_MIN_AMPLITUDE: f64 = 0.1;
+/// Default amplitude of the strongest rumble magnitude
+const DEFAULT_MAX_AMPLITUDE: f64 = 1.0;
+/// Sample rate of the rendered waveform
+const SAMPLE_RATE: u32 = 48000;
+/// Duration of the waveform written at a time. Shorter chunks make rumble
+/// changes audible sooner.
+const CHUNK_DURATION: Duration = Duration::from_millis(10);
+/// Time the waveform must be silent before the audio stream is closed
+const IDLE_TIMEOUT: Duration = Duration::from_millis(1000);
+/// PipeWire client used to play raw audio
+const PLAYER: &str = "pw-cat";
+/// Name of the socket of the PipeWire daemon in the runtime directory of a
+/// user
+const PIPEWIRE_SOCKET: &str = "pipewire-0";
+
+/// Amplitudes of the waveform for the strong and weak rumble motors
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Amplitudes {
+    strong: f64,
+    weak: f64,
+}
+
+impl Amplitudes {
+    fn is_zero(&self) -> bool {
+        self.strong == 0.0 && self.weak == 0.0
+    }
+}
+
+/// Returns the waveform amplitude of the given rumble motor magnitude. A
+/// magnitude of zero is silent, while any other magnitude is scaled between
+/// the minimum and maximum amplitude.
+pub fn amplitude(magnitude: u16, min: f64, max: f64) -> f64 {
+    if magnitude == 0 {
+        return 0.0;
+    }
+    min + (max - min) * magnitude as f64 / u16::MAX as f64
+}
+
+/// Renders rumble as a waveform on a PipeWire audio sink, for devices whose
+/// rumble is driven by a haptic speaker instead of motors. The waveform is
+/// rendered on its own thread and streamed to the sink with [PLAYER], which is
+/// only kept running while rumble is playing. Since PipeWire runs in the
+/// session of a user, the player is run as the user of the active session on
+/// the seat and connects to the PipeWire daemon in their runtime directory.
+/// Rumble is silent while no user is logged in.
+#[derive(Debug)]
+pub struct AudioHaptics {
+    min_amplitude: f64,
+    max_amplitude: f64,
+    tx: Sender<Amplitudes>,
+}
+
+impl AudioHaptics {
+    /// Create a new audio haptics backend from the given config
+    pub fn new(config: &AudioHapticsConfig) -> Self {
+        let min_amplitude = config
+            .min_amplitude
+            .unwrap_or(DEFAULT_MIN_AMPLITUDE)
+            .clamp(0.0, 1.0);
+        let max_amplitude = config
+            .max_amplitude
+            .unwrap_or(DEFAULT_MAX_AMPLITUDE)
+            .clamp(min_amplitude, 1.0);
+        let mut waveform = Waveform::new(
+            config.strong_frequency.unwrap_or(DEFAULT_STRONG_FREQUENCY),
+            config.weak_frequency.unwrap_or(DEFAULT_WEAK_FREQUENCY),
+        );
+        let sink = config.sink.clone();
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            log::debug!("Started audio haptics on sink: {sink:?}");
+            render(rx, &mut waveform, sink);
+            log::debug!("Stopped audio haptics");
+        });
+
+        Self {
+            min_amplitude,
+            max_amplitude,
+            tx,
+        }
+    }
+
+    /// Play the given rumble on the audio sink
+    pub fn set_rumble(&self, rumble: RumbleMagnitude) {
+        let amplitudes = Amplitudes {
+            strong: amplitude(rumble.strong, self.min_amplitude, self.max_amplitude),
+            weak: amplitude(rumble.weak, self.min_amplitude, self.max_amplitude),
+        };
+        if let Err(e) = self.tx.send(amplitudes) {
+            log::error!("Failed to send rumble to audio haptics: {e:?}");
+        }
+    }
+}
+
+/// Sine waveform mixed from the frequencies of both rumble motors. The phase
+/// of each frequency is kept between chunks so the waveform stays continuous.
+#[derive(Debug)]
+pub struct Waveform {
+    strong_step: f64,
+    weak_step: f64,
+    strong_phase: f64,
+    weak_phase: f64,
+}
+
+impl Waveform {
+    /// Create a new waveform with the given frequencies in Hz
+    pub fn new(strong_frequency: f64, weak_frequency: f64) -> Self {
+        Self {
+            strong_step: TAU * strong_frequency / SAMPLE_RATE as f64,
+            weak_step: TAU * weak_frequency / SAMPLE_RATE as f64,
+            strong_phase: 0.0,
+            weak_phase: 0.0,
+        }
+    }
+
+    /// Render the next samples of the waveform with the given strong and weak
+    /// amplitudes. The mix is scaled so it never exceeds the largest
+    /// amplitude.
+    pub fn render(&mut self, strong: f64, weak: f64, samples: usize) -> Vec<f32> {
+        let total = strong + weak;
+        let scale = if total > 0.0 {
+            strong.max(weak) / total
+        } else {
+            0.0
+        };
+        (0..samples)
+            .map(|_| {
+                let sample = strong * self.strong_phase.sin() + weak * self.weak_phase.sin();
+                self.strong_phase = (self.strong_phase + self.strong_step) % TAU;
+                self.weak_phase = (self.weak_phase + self.weak_step) % TAU;
+                (sample * scale) as f32
+            })
+            .collect()
+    }
+}
+
+/// Start the audio player that streams raw samples from its stdin to the sink.
+/// The player is run as the user of the active session, since the daemon has
+/// no PipeWire session of its own.
+fn spawn_player(sink: Option<&str>) -> Result<Child, Box<dyn Error + Send + Sync>> {
+    let Some(uid) = get_active_session_user()? else {
+        return Err("No active session to play audio haptics in".into());
+    };
+    let Some(user) = User::from_uid(Uid::from_raw(uid))? else {
+        return Err(format!("No user with uid {uid}").into());
+    };
+    let runtime_dir = format!("/run/user/{uid}");
+    let socket = Path::new(runtime_dir.as_str()).join(PIPEWIRE_SOCKET);
+    if !socket.exists() {
+        return Err(format!("No PipeWire socket for user {}: {socket:?}", user.name).into());
+    }
+
+    let mut command = Command::new(PLAYER);
+    command
+        .args(["--playback", "--raw", "--format", "f32", "--channels", "1"])
+        .args(["--rate", SAMPLE_RATE.to_string().as_str()]);
+    if let Some(sink) = sink {
+        command.args(["--target", sink]);
+    }
+    let child = command
+        .arg("-")
+        .env_clear()
+        .env("HOME", user.dir)
+        .env("XDG_RUNTIME_DIR", runtime_dir.as_str())
+        .env("PIPEWIRE_REMOTE", socket)
+        .uid(user.uid.as_raw())
+        .gid(user.gid.as_raw())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(child)
+}
+
+/// Stop the given audio player
+fn stop_player(mut player: Child) {
+    drop(player.stdin.take());
+    if let Err(e) = player.kill() {
+        log::debug!("Failed to stop audio haptics player: {e:?}");
+    }
+    let _ = player.wait();
+}
+
+/// Render the waveform of the received amplitudes to the sink until the
+/// sender is dropped.
+fn render(rx: Receiver<Amplitudes>, waveform: &mut Waveform, sink: Option<String>) {
+    let chunk_samples = (SAMPLE_RATE as u128 * CHUNK_DURATION.as_millis() / 1000) as usize;
+    let mut amplitudes = Amplitudes::default();
+    let mut player: Option<Child> = None;
+    let mut player_failed = false;
+    let mut silent_since: Option<Instant> = None;
+    let mut next_write = Instant::now();
+
+    loop {
+        // Wait for new amplitudes. While nothing plays, block until rumble
+        // starts, otherwise keep writing chunks on time.
+        let received = if player.is_none() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(next_write.saturating_duration_since(Instant::now()))
+        };
+        match received {
+            Ok(value) => {
+                amplitudes = value;
+                if player.is_some() {
+                    continue;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // Close the stream once the waveform has been silent for a while. A
+        // player that failed to start is retried on the next rumble, e.g.
+        // once a user logged in.
+        let now = Instant::now();
+        if amplitudes.is_zero() {
+            player_failed = false;
+            let since = *silent_since.get_or_insert(now);
+            if now.duration_since(since) >= IDLE_TIMEOUT {
+                if let Some(player) = player.take() {
+                    stop_player(player);
+                }
+                continue;
+            }
+        } else {
+            silent_since = None;
+        }
+
+        if player.is_none() {
+            if amplitudes.is_zero() || player_failed {
+                continue;
+            }
+            match spawn_player(sink.as_deref()) {
+                Ok(child) => player = Some(child),
+                Err(e) => {
+                    log::error!("Failed to start audio haptics with {PLAYER}: {e}");
+                    player_failed = true;
+                    continue;
+                }
+            }
+            next_write = now;
+        }
+
+        let samples = waveform.render(amplitudes.strong, amplitudes.weak, chunk_samples);
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_ne_bytes()).collect();
+        let written = player
+            .as_mut()
+            .and_then(|player| player.stdin.as_mut())
+            .map(|stdin| stdin.write_all(&bytes));
+        if let Some(Err(e)) = written {
+            log::error!("Failed to write audio haptics waveform: {e:?}");
+            if let Some(player) = player.take() {
+                stop_player(player);
+            }
+            continue;
+        }
+        next_write += CHUNK_DURATION;
+    }
+
+    if let Some(player) = player.take() {
+        stop_player(player);
+    }
+}
@@ -0,0 +1,29 @@
+use super::audio_haptics::{amplitude, Waveform};
+
+#[test]
+fn test_amplitude() {
+    assert_eq!(amplitude(0, 0.2, 0.8), 0.0);
+    assert!((amplitude(1, 0.2, 0.8) - 0.2).abs() < 0.001);
+    assert!((amplitude(u16::MAX / 2, 0.2, 0.8) - 0.5).abs() < 0.001);
+    assert!((amplitude(u16::MAX, 0.2, 0.8) - 0.8).abs() < 0.001);
+}
+
+#[test]
+fn test_waveform() {
+    let mut waveform = Waveform::new(100.0, 200.0);
+
+    // A silent waveform renders no signal
+    let samples = waveform.render(0.0, 0.0, 480);
+    assert_eq!(samples.len(), 480);
+    assert!(samples.iter().all(|sample| *sample == 0.0));
+
+    // The mix of both motors never exceeds the largest amplitude
+    let samples = waveform.render(0.5, 0.5, 4800);
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(peak > 0.3 && peak <= 0.5);
+
+    // A single motor plays at its own amplitude
+    let samples = waveform.render(0.0, 0.25, 4800);
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!((peak - 0.25).abs() < 0.01);
+}
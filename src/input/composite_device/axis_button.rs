@@ -0,0 +1,180 @@
+use std::time::{Duration, Instant};
+
+use crate::{config::AxisCapability, input::event::value::angle_distance};
+
+/// Default deflection required to consider the axis direction as 'pressed'
+const DEFAULT_DEADZONE: f64 = 0.3;
+/// Default amount the axis must fall below the deadzone to be 'released'
+const DEFAULT_HYSTERESIS: f64 = 0.05;
+/// Default length of a single key repeat cycle
+const DEFAULT_REPEAT_PERIOD_MS: u64 = 100;
+/// Default deflection where the key is held instead of repeated
+const DEFAULT_HOLD_THRESHOLD: f64 = 0.9;
+/// Minimum fraction of a repeat cycle the key is pressed for, so small
+/// deflections still register as key presses.
+const MIN_REPEAT_DUTY: f64 = 0.1;
+
+/// The result of updating an [AxisButtonState]
+#[derive(Debug, Clone, Default)]
+pub struct AxisButtonUpdate {
+    /// The new pressed state of the button, if it changed
+    pub changed: Option<bool>,
+    /// If set, the axis should be re-evaluated after the given duration to
+    /// continue repeating the button.
+    pub wake_after: Option<Duration>,
+}
+
+/// Tracks the state of an axis direction that is translated into a digital
+/// button or key press. This allows the translation to use angle sectors,
+/// hysteresis, and key repeat that scales with how far the axis is deflected.
+#[derive(Debug, Clone, Default)]
+pub struct AxisButtonState {
+    /// Last known horizontal value of the axis
+    x: f64,
+    /// Last known vertical value of the axis
+    y: f64,
+    /// Whether the axis is currently deflected in the configured direction
+    engaged: bool,
+    /// Whether the translated button is currently pressed
+    pressed: bool,
+    /// Time the current key repeat cycle started
+    repeat_start: Option<Instant>,
+    /// Whether a re-evaluation of the axis has been scheduled for key repeat
+    repeat_scheduled: bool,
+}
+
+impl AxisButtonState {
+    /// Update the state of the axis with the given values. Values that are
+    /// [None] will use the last known value for that axis.
+    pub fn update(
+        &mut self,
+        x: Option<f64>,
+        y: Option<f64>,
+        config: &AxisCapability,
+        now: Instant,
+    ) -> AxisButtonUpdate {
+        if let Some(x) = x {
+            self.x = x;
+        }
+        if let Some(y) = y {
+            self.y = y;
+        }
+
+        // The outer ring can be used without a direction, otherwise a valid
+        // direction is required.
+        let direction = config.direction.as_deref();
+        let direction_angle = match direction {
+            Some(direction) => match direction_angle(direction) {
+                Some(angle) => Some(angle),
+                None => return AxisButtonUpdate::default(),
+            },
+            None if config.outer_ring.is_some() => None,
+            None => return AxisButtonUpdate::default(),
+        };
+        let deadzone = config
+            .outer_ring
+            .or(config.deadzone)
+            .unwrap_or(DEFAULT_DEADZONE);
+        let hysteresis = config.hysteresis.unwrap_or(DEFAULT_HYSTERESIS);
+
+        // Sectors and the outer ring use the full deflection of the axis,
+        // otherwise only the deflection in the configured direction is used.
+        let magnitude = if config.sector_angle.is_some() || config.outer_ring.is_some() {
+            (self.x * self.x + self.y * self.y).sqrt().min(1.0)
+        } else {
+            match direction {
+                Some("left") => -self.x,
+                Some("right") => self.x,
+                Some("up") => -self.y,
+                Some("down") => self.y,
+                _ => 0.0,
+            }
+            .max(0.0)
+        };
+
+        // Check to see if the axis is within the sector for this direction. Once
+        // engaged, the sector is widened to prevent flickering at the edges.
+        let in_sector = match (config.sector_angle, direction_angle) {
+            (Some(sector_angle), Some(direction_angle)) => {
+                let mut half_angle = sector_angle / 2.0;
+                if self.engaged {
+                    half_angle += hysteresis * 90.0;
+                }
+                let angle = self.y.atan2(self.x).to_degrees();
+                angle_distance(angle, direction_angle) <= half_angle
+            }
+            _ => true,
+        };
+
+        let threshold = if self.engaged {
+            deadzone - hysteresis
+        } else {
+            deadzone
+        };
+        self.engaged = in_sector && magnitude >= threshold;
+
+        // Determine if the button should be pressed
+        let (pressed, wake_after) = if !self.engaged {
+            self.repeat_start = None;
+            (false, None)
+        } else if let Some(repeat) = config.repeat.as_ref() {
+            let hold_threshold = repeat.hold_threshold.unwrap_or(DEFAULT_HOLD_THRESHOLD);
+            if magnitude >= hold_threshold || hold_threshold <= deadzone {
+                self.repeat_start = None;
+                (true, None)
+            } else {
+                // Press the button for a portion of each repeat cycle based on
+                // how far the axis is deflected.
+                let period_ms = repeat.period_ms.unwrap_or(DEFAULT_REPEAT_PERIOD_MS).max(1);
+                let period = Duration::from_millis(period_ms);
+                let duty = (magnitude - deadzone) / (hold_threshold - deadzone);
+                let duty = duty.clamp(MIN_REPEAT_DUTY, 1.0);
+                let press_time = period.mul_f64(duty);
+                let start = *self.repeat_start.get_or_insert(now);
+                let elapsed = now.duration_since(start).as_nanos();
+                let phase = Duration::from_nanos((elapsed % period.as_nanos()) as u64);
+                if phase < press_time {
+                    (true, Some(press_time - phase))
+                } else {
+                    (false, Some(period - phase))
+                }
+            }
+        } else {
+            (true, None)
+        };
+
+        let changed = if pressed != self.pressed {
+            self.pressed = pressed;
+            Some(pressed)
+        } else {
+            None
+        };
+
+        AxisButtonUpdate {
+            changed,
+            wake_after,
+        }
+    }
+
+    /// Returns whether or not a re-evaluation has been scheduled for key repeat
+    pub fn is_repeat_scheduled(&self) -> bool {
+        self.repeat_scheduled
+    }
+
+    /// Set whether or not a re-evaluation has been scheduled for key repeat
+    pub fn set_repeat_scheduled(&mut self, scheduled: bool) {
+        self.repeat_scheduled = scheduled;
+    }
+}
+
+/// Returns the angle in degrees of the given direction, where "right" is 0
+/// and "down" is 90 (positive Y values point down).
+fn direction_angle(direction: &str) -> Option<f64> {
+    match direction {
+        "right" => Some(0.0),
+        "down" => Some(90.0),
+        "left" => Some(180.0),
+        "up" => Some(-90.0),
+        _ => None,
+    }
+}
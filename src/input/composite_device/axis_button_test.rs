@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use crate::config::{AxisCapability, AxisRepeatCapability};
+
+use super::axis_button::AxisButtonState;
+
+fn axis_config(direction: &str) -> AxisCapability {
+    AxisCapability {
+        name: "LeftStick".to_string(),
+        direction: Some(direction.to_string()),
+        deadzone: Some(0.3),
+        hysteresis: Some(0.1),
+        sector_angle: None,
+        repeat: None,
+        outer_ring: None,
+    }
+}
+
+#[test]
+fn test_hysteresis() {
+    let config = axis_config("right");
+    let now = Instant::now();
+    let mut state = AxisButtonState::default();
+
+    // Crossing the deadzone should press the button
+    let update = state.update(Some(0.35), Some(0.0), &config, now);
+    assert_eq!(update.changed, Some(true));
+
+    // Falling just below the deadzone should not release the button
+    let update = state.update(Some(0.25), None, &config, now);
+    assert_eq!(update.changed, None);
+
+    // Falling below the deadzone minus hysteresis should release the button
+    let update = state.update(Some(0.15), None, &config, now);
+    assert_eq!(update.changed, Some(false));
+
+    // Rising just below the deadzone should not press the button again
+    let update = state.update(Some(0.25), None, &config, now);
+    assert_eq!(update.changed, None);
+}
+
+#[test]
+fn test_sector_angle() {
+    let mut config = axis_config("up");
+    config.sector_angle = Some(90.0);
+    let now = Instant::now();
+    let mut state = AxisButtonState::default();
+
+    // Pointing mostly to the right should not press "up"
+    let update = state.update(Some(0.8), Some(-0.5), &config, now);
+    assert_eq!(update.changed, None);
+
+    // Pointing mostly up should press "up"
+    let update = state.update(Some(0.5), Some(-0.8), &config, now);
+    assert_eq!(update.changed, Some(true));
+
+    // Pointing slightly outside of the sector should stay pressed because
+    // of hysteresis
+    let update = state.update(Some(0.75), Some(-0.65), &config, now);
+    assert_eq!(update.changed, None);
+
+    // Pointing to the right should release "up"
+    let update = state.update(Some(0.9), Some(0.0), &config, now);
+    assert_eq!(update.changed, Some(false));
+}
+
+#[test]
+fn test_repeat() {
+    let mut config = axis_config("left");
+    config.repeat = Some(AxisRepeatCapability {
+        period_ms: Some(100),
+        hold_threshold: Some(0.9),
+    });
+    let now = Instant::now();
+    let mut state = AxisButtonState::default();
+
+    // A partial deflection should press the button for part of the cycle
+    let update = state.update(Some(-0.6), Some(0.0), &config, now);
+    assert_eq!(update.changed, Some(true));
+    assert!(update.wake_after.is_some());
+
+    // After the press time, the button should be released
+    let update = state.update(None, None, &config, now + Duration::from_millis(60));
+    assert_eq!(update.changed, Some(false));
+    assert_eq!(update.wake_after, Some(Duration::from_millis(40)));
+
+    // The next cycle should press the button again
+    let update = state.update(None, None, &config, now + Duration::from_millis(100));
+    assert_eq!(update.changed, Some(true));
+
+    // A full deflection should hold the button without repeating
+    let update = state.update(Some(-1.0), None, &config, now + Duration::from_millis(120));
+    assert_eq!(update.changed, None);
+    assert_eq!(update.wake_after, None);
+}
+
+#[test]
+fn test_outer_ring() {
+    let mut config = axis_config("up");
+    config.direction = None;
+    config.outer_ring = Some(0.9);
+    config.hysteresis = Some(0.05);
+    let now = Instant::now();
+    let mut state = AxisButtonState::default();
+
+    // Deflection inside the ring should not press the button
+    let update = state.update(Some(0.6), Some(0.6), &config, now);
+    assert_eq!(update.changed, None);
+
+    // Full deflection in any direction should press the button
+    let update = state.update(Some(-0.7), Some(0.7), &config, now);
+    assert_eq!(update.changed, Some(true));
+
+    // Wobbling just inside the ring should not release the button
+    let update = state.update(Some(-0.62), Some(0.62), &config, now);
+    assert_eq!(update.changed, None);
+
+    // Falling well inside the ring should release the button
+    let update = state.update(Some(-0.5), Some(0.5), &config, now);
+    assert_eq!(update.changed, Some(false));
+}
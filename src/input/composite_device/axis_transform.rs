@@ -0,0 +1,112 @@
+use std::{fmt, str::FromStr};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Layout that source events are transformed into before they are translated
+/// by the device profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AxisTransform {
+    /// Events are passed through unchanged
+    #[default]
+    None,
+    /// A single Joy-Con is held sideways as a mini-gamepad. The stick is
+    /// rotated 90 degrees and becomes the left stick, the face buttons (or
+    /// DPad) are rotated, and the SL/SR rail buttons become the shoulders.
+    /// Each Joy-Con is detected from the capabilities it emits.
+    JoyConSideways,
+}
+
+impl fmt::Display for AxisTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AxisTransform::None => write!(f, "none"),
+            AxisTransform::JoyConSideways => write!(f, "joycon_sideways"),
+        }
+    }
+}
+
+impl FromStr for AxisTransform {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(AxisTransform::None),
+            "joycon_sideways" => Ok(AxisTransform::JoyConSideways),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AxisTransform {
+    /// Transform the given event into the layout
+    pub fn transform(&self, event: NativeEvent) -> NativeEvent {
+        match self {
+            AxisTransform::None => event,
+            AxisTransform::JoyConSideways => joycon_sideways(event),
+        }
+    }
+}
+
+/// Transform the given Joy-Con event into the sideways layout. The left
+/// Joy-Con is rotated counter-clockwise and the right Joy-Con clockwise, so
+/// the rail faces up in both cases.
+fn joycon_sideways(event: NativeEvent) -> NativeEvent {
+    let Capability::Gamepad(gamepad) = event.as_capability() else {
+        return event;
+    };
+    let value = event.get_value();
+    let (cap, value) = match gamepad {
+        Gamepad::Axis(GamepadAxis::LeftStick) => {
+            let value = match value {
+                InputValue::Vector2 { x, y } => InputValue::Vector2 {
+                    x: y,
+                    y: x.map(|x| -x),
+                },
+                value => value,
+            };
+            (Gamepad::Axis(GamepadAxis::LeftStick), value)
+        }
+        Gamepad::Axis(GamepadAxis::RightStick) => {
+            let value = match value {
+                InputValue::Vector2 { x, y } => InputValue::Vector2 {
+                    x: y.map(|y| -y),
+                    y: x,
+                },
+                value => value,
+            };
+            (Gamepad::Axis(GamepadAxis::LeftStick), value)
+        }
+        Gamepad::Button(button) => {
+            let button = match button {
+                // Left Joy-Con
+                GamepadButton::DPadUp => GamepadButton::West,
+                GamepadButton::DPadRight => GamepadButton::North,
+                GamepadButton::DPadDown => GamepadButton::East,
+                GamepadButton::DPadLeft => GamepadButton::South,
+                GamepadButton::LeftPaddle1 => GamepadButton::LeftBumper,
+                GamepadButton::LeftPaddle2 => GamepadButton::RightBumper,
+                GamepadButton::Select => GamepadButton::Start,
+                // Right Joy-Con
+                GamepadButton::North => GamepadButton::East,
+                GamepadButton::East => GamepadButton::South,
+                GamepadButton::South => GamepadButton::West,
+                GamepadButton::West => GamepadButton::North,
+                GamepadButton::RightPaddle2 => GamepadButton::LeftBumper,
+                GamepadButton::RightPaddle1 => GamepadButton::RightBumper,
+                GamepadButton::RightStick => GamepadButton::LeftStick,
+                button => button,
+            };
+            (Gamepad::Button(button), value)
+        }
+        gamepad => (gamepad, value),
+    };
+    let cap = Capability::Gamepad(cap);
+
+    match event.get_source_capability() {
+        Some(source_cap) => NativeEvent::new_translated(source_cap, cap, value),
+        None => NativeEvent::new(cap, value),
+    }
+}
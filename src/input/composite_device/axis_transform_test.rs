@@ -0,0 +1,75 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::axis_transform::AxisTransform;
+use super::test_events::{button_event, stick_event};
+
+fn as_stick(event: &NativeEvent) -> (Capability, Option<f64>, Option<f64>) {
+    match event.get_value() {
+        InputValue::Vector2 { x, y } => (event.as_capability(), x, y),
+        _ => (event.as_capability(), None, None),
+    }
+}
+
+#[test]
+fn test_joycon_sideways_stick() {
+    let transform = AxisTransform::JoyConSideways;
+    let left_stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+
+    // Pushing the left Joy-Con stick towards the rail should point up
+    let event = transform.transform(stick_event(GamepadAxis::LeftStick, 1.0, 0.0));
+    assert_eq!(
+        as_stick(&event),
+        (left_stick.clone(), Some(0.0), Some(-1.0))
+    );
+    // Pushing it up (vertically) should point left
+    let event = transform.transform(stick_event(GamepadAxis::LeftStick, 0.0, -1.0));
+    assert_eq!(
+        as_stick(&event),
+        (left_stick.clone(), Some(-1.0), Some(-0.0))
+    );
+
+    // The right Joy-Con stick should become the left stick, with the rail
+    // pointing up.
+    let event = transform.transform(stick_event(GamepadAxis::RightStick, -1.0, 0.0));
+    assert_eq!(
+        as_stick(&event),
+        (left_stick.clone(), Some(-0.0), Some(-1.0))
+    );
+    let event = transform.transform(stick_event(GamepadAxis::RightStick, 0.0, -1.0));
+    assert_eq!(as_stick(&event), (left_stick, Some(1.0), Some(0.0)));
+}
+
+#[test]
+fn test_joycon_sideways_buttons() {
+    let transform = AxisTransform::JoyConSideways;
+    let cases = [
+        (GamepadButton::DPadLeft, GamepadButton::South),
+        (GamepadButton::DPadUp, GamepadButton::West),
+        (GamepadButton::LeftPaddle1, GamepadButton::LeftBumper),
+        (GamepadButton::LeftPaddle2, GamepadButton::RightBumper),
+        (GamepadButton::East, GamepadButton::South),
+        (GamepadButton::North, GamepadButton::East),
+        (GamepadButton::RightPaddle2, GamepadButton::LeftBumper),
+        (GamepadButton::RightPaddle1, GamepadButton::RightBumper),
+        (GamepadButton::Guide, GamepadButton::Guide),
+    ];
+    for (source, target) in cases {
+        let event = transform.transform(button_event(source.clone(), true));
+        assert_eq!(
+            event.as_capability(),
+            Capability::Gamepad(Gamepad::Button(target)),
+            "{source:?} should be remapped"
+        );
+        assert!(event.pressed());
+    }
+
+    // Events should be unchanged without a layout
+    let event = AxisTransform::None.transform(button_event(GamepadButton::DPadLeft, true));
+    assert_eq!(
+        event.as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft))
+    );
+}
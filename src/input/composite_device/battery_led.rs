@@ -0,0 +1,225 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use glob_match::glob_match;
+
+use crate::config::{BatteryLedConfig, BatteryLedPattern, BatteryLedStop};
+
+/// Path to the power supplies of the system
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+/// Default interval to read the battery state
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Color of the LED while a blink pattern switches it off
+const OFF: (u8, u8, u8) = (0, 0, 0);
+
+/// Charge level and charging state of a battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryState {
+    /// Battery level from 0 to 100
+    pub capacity: u8,
+    pub charging: bool,
+}
+
+/// Returns the path to the first battery in "/sys/class/power_supply" whose
+/// name matches the given glob pattern.
+pub fn find_battery(pattern: &str) -> Option<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(POWER_SUPPLY_PATH)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    paths.into_iter().find(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        glob_match(pattern, &name) && kind.trim() == "Battery"
+    })
+}
+
+/// Read the state of the battery at the given power supply path
+pub fn read_battery(path: &Path) -> Result<BatteryState, Box<dyn Error + Send + Sync>> {
+    let capacity: u8 = fs::read_to_string(path.join("capacity"))?.trim().parse()?;
+    let status = fs::read_to_string(path.join("status"))?;
+    let charging = matches!(status.trim(), "Charging" | "Full");
+    Ok(BatteryState {
+        capacity: capacity.min(100),
+        charging,
+    })
+}
+
+/// Sets the color of an LED based on the battery level and charging state,
+/// using a color gradient and optional blink patterns. Games that set an
+/// explicit LED color override the policy.
+#[derive(Debug)]
+pub struct BatteryLedPolicy {
+    /// Name of the multicolor LED in "/sys/class/leds"
+    led: String,
+    /// Glob pattern of the battery name
+    battery: String,
+    /// Path to the battery, once it was found
+    battery_path: Option<PathBuf>,
+    /// Gradient stops sorted by level
+    gradient: Vec<BatteryLedStop>,
+    patterns: Vec<BatteryLedPattern>,
+    poll_interval: Duration,
+    /// Time an explicit LED color overrides the policy, or forever if none
+    override_timeout: Option<Duration>,
+    /// Time an explicit LED color was last set
+    overridden_at: Option<Instant>,
+    /// Time blink patterns are timed from
+    started: Instant,
+}
+
+impl BatteryLedPolicy {
+    /// Create a new policy from the given configuration
+    pub fn new(config: &BatteryLedConfig) -> Self {
+        let mut gradient = config.gradient.clone().unwrap_or_else(|| {
+            vec![
+                BatteryLedStop {
+                    level: 0,
+                    color: [255, 0, 0],
+                },
+                BatteryLedStop {
+                    level: 100,
+                    color: [0, 255, 0],
+                },
+            ]
+        });
+        gradient.sort_by_key(|stop| stop.level);
+
+        Self {
+            led: config.led.clone(),
+            battery: config.battery.clone().unwrap_or_else(|| "*".to_string()),
+            battery_path: None,
+            gradient,
+            patterns: config.patterns.clone().unwrap_or_default(),
+            poll_interval: config
+                .poll_interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_POLL_INTERVAL),
+            override_timeout: config.override_timeout_secs.map(Duration::from_secs),
+            overridden_at: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns the name of the LED controlled by the policy
+    pub fn led(&self) -> &str {
+        self.led.as_str()
+    }
+
+    /// Returns the interval to read the battery state
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Read the current state of the battery
+    pub fn read_state(&mut self) -> Result<BatteryState, Box<dyn Error + Send + Sync>> {
+        if self.battery_path.is_none() {
+            self.battery_path = find_battery(self.battery.as_str());
+        }
+        let Some(path) = self.battery_path.as_ref() else {
+            return Err(format!("No battery found matching '{}'", self.battery).into());
+        };
+        let state = read_battery(path);
+        // The battery may have been removed, so look for it again next time
+        if state.is_err() {
+            self.battery_path = None;
+        }
+        state
+    }
+
+    /// Returns the LED color for the given battery state at the given time,
+    /// and the time until the color changes if the LED is blinking.
+    pub fn color(&self, state: &BatteryState, now: Instant) -> ((u8, u8, u8), Option<Duration>) {
+        let gradient = self.gradient_color(state.capacity);
+        let Some(pattern) = self
+            .patterns
+            .iter()
+            .find(|pattern| pattern_matches(pattern, state))
+        else {
+            return (gradient, None);
+        };
+
+        let color = pattern.color.map(|[r, g, b]| (r, g, b)).unwrap_or(gradient);
+        let Some(blink_ms) = pattern.blink_ms.filter(|ms| *ms > 0) else {
+            return (color, None);
+        };
+
+        // Alternate between the color and off every blink interval
+        let elapsed = now.saturating_duration_since(self.started).as_millis() as u64;
+        let phase = elapsed / blink_ms;
+        let remaining = Duration::from_millis(blink_ms - elapsed % blink_ms);
+        if phase % 2 == 0 {
+            (color, Some(remaining))
+        } else {
+            (OFF, Some(remaining))
+        }
+    }
+
+    /// Returns the color of the gradient at the given battery level
+    fn gradient_color(&self, level: u8) -> (u8, u8, u8) {
+        let Some(first) = self.gradient.first() else {
+            return OFF;
+        };
+        let mut lower = first;
+        for upper in self.gradient.iter() {
+            if upper.level < level {
+                lower = upper;
+                continue;
+            }
+            if upper.level == lower.level {
+                let [r, g, b] = upper.color;
+                return (r, g, b);
+            }
+            let t = (level - lower.level) as f64 / (upper.level - lower.level) as f64;
+            let blend = |i: usize| {
+                let (a, b) = (lower.color[i] as f64, upper.color[i] as f64);
+                (a + (b - a) * t).round() as u8
+            };
+            return (blend(0), blend(1), blend(2));
+        }
+        let [r, g, b] = lower.color;
+        (r, g, b)
+    }
+
+    /// Mark the LED as explicitly set by a game at the given time
+    pub fn set_overridden(&mut self, now: Instant) {
+        self.overridden_at = Some(now);
+    }
+
+    /// Returns true if an explicit LED color currently overrides the policy
+    pub fn is_overridden(&self, now: Instant) -> bool {
+        let Some(overridden_at) = self.overridden_at else {
+            return false;
+        };
+        match self.override_timeout {
+            Some(timeout) => now.saturating_duration_since(overridden_at) < timeout,
+            None => true,
+        }
+    }
+
+    /// Returns true if the policy was overridden until the device is removed
+    pub fn is_overridden_forever(&self) -> bool {
+        self.overridden_at.is_some() && self.override_timeout.is_none()
+    }
+}
+
+/// Returns true if all conditions of the given pattern match the battery state
+fn pattern_matches(pattern: &BatteryLedPattern, state: &BatteryState) -> bool {
+    if let Some(below) = pattern.below {
+        if state.capacity >= below {
+            return false;
+        }
+    }
+    if let Some(charging) = pattern.charging {
+        if charging != state.charging {
+            return false;
+        }
+    }
+    true
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use crate::config::BatteryLedConfig;
+
+use super::battery_led::{BatteryLedPolicy, BatteryState};
+
+const CONFIG: &str = r#"
+led: multicolor:chassis
+gradient:
+  - level: 20
+    color: [255, 0, 0]
+  - level: 80
+    color: [0, 255, 0]
+patterns:
+  - below: 10
+    charging: false
+    blink_ms: 500
+  - charging: true
+    color: [0, 0, 255]
+override_timeout_secs: 10
+"#;
+
+fn state(capacity: u8, charging: bool) -> BatteryState {
+    BatteryState { capacity, charging }
+}
+
+#[test]
+fn test_battery_led_colors() {
+    let config: BatteryLedConfig = serde_yaml::from_str(CONFIG).unwrap();
+    let policy = BatteryLedPolicy::new(&config);
+    let now = Instant::now();
+
+    // Colors should be blended between gradient stops
+    assert_eq!(policy.color(&state(50, false), now), ((128, 128, 0), None));
+    assert_eq!(policy.color(&state(15, false), now).0, (255, 0, 0));
+    assert_eq!(policy.color(&state(90, false), now).0, (0, 255, 0));
+
+    // The first matching pattern should take precedence
+    assert_eq!(policy.color(&state(50, true), now), ((0, 0, 255), None));
+    assert_eq!(policy.color(&state(5, true), now).0, (0, 0, 255));
+
+    // Blinking should alternate between the gradient color and off
+    let (color, wake) = policy.color(&state(5, false), now);
+    assert_eq!(color, (255, 0, 0));
+    assert!(wake.is_some_and(|wake| wake <= Duration::from_millis(500)));
+    let later = now + wake.unwrap();
+    assert_eq!(policy.color(&state(5, false), later).0, (0, 0, 0));
+}
+
+#[test]
+fn test_battery_led_override() {
+    let config: BatteryLedConfig = serde_yaml::from_str(CONFIG).unwrap();
+    let mut policy = BatteryLedPolicy::new(&config);
+    let now = Instant::now();
+    assert!(!policy.is_overridden(now));
+
+    policy.set_overridden(now);
+    assert!(policy.is_overridden(now + Duration::from_secs(5)));
+    assert!(!policy.is_overridden(now + Duration::from_secs(10)));
+    assert!(!policy.is_overridden_forever());
+
+    // Without a timeout the override should never expire
+    let config = BatteryLedConfig {
+        override_timeout_secs: None,
+        ..config
+    };
+    let mut policy = BatteryLedPolicy::new(&config);
+    policy.set_overridden(now);
+    assert!(policy.is_overridden(now + Duration::from_secs(3600)));
+    assert!(policy.is_overridden_forever());
+}
@@ -0,0 +1,119 @@
+use tokio::sync::mpsc;
+
+use crate::{
+    dbus::interface::capability_strings,
+    input::{
+        capability::{Capability, Gamepad, Mouse},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::inspector::format_value;
+
+/// Minimum value an axis or trigger must reach to be captured
+const AXIS_THRESHOLD: f64 = 0.5;
+
+/// A physical input captured with [InputCapture]
+#[derive(Debug, Clone)]
+pub struct CapturedInput {
+    /// Capability string of the physical input. E.g. "Gamepad:Button:South"
+    pub capability: String,
+    /// Human-readable value of the input. E.g. "pressed"
+    pub value: String,
+}
+
+impl CapturedInput {
+    pub fn new(event: &NativeEvent) -> Self {
+        let capability = capability_strings(vec![event.as_capability()])
+            .pop()
+            .unwrap_or_default();
+        Self {
+            capability,
+            value: format_value(&event.get_value()),
+        }
+    }
+}
+
+/// Returns true if the given source event is a deliberate input that should be
+/// captured, such as a button press or a stick pushed past half way. Releases,
+/// small axis movements, and motion sensors are ignored.
+pub fn is_significant(event: &NativeEvent) -> bool {
+    let cap = event.as_capability();
+    if matches!(
+        cap,
+        Capability::Mouse(Mouse::Motion)
+            | Capability::Gamepad(Gamepad::Accelerometer | Gamepad::Gyro)
+    ) {
+        return false;
+    }
+    let past_threshold = |value: Option<f64>| value.is_some_and(|v| v.abs() >= AXIS_THRESHOLD);
+    match event.get_value() {
+        InputValue::Bool(pressed) => pressed,
+        InputValue::Float(value) => past_threshold(Some(value)),
+        InputValue::Vector2 { x, y } => past_threshold(x) || past_threshold(y),
+        InputValue::Touch { is_touching, .. } => is_touching,
+        InputValue::None | InputValue::Vector3 { .. } => false,
+    }
+}
+
+/// Captures the next significant physical input for configurators (e.g. in
+/// "press a button to bind" flows). The captured input is intercepted and not
+/// emitted to target devices until it is released again.
+#[derive(Debug, Default)]
+pub struct InputCapture {
+    /// Channel to send the captured input to while waiting for input
+    waiting: Option<mpsc::Sender<Option<CapturedInput>>>,
+    /// Capability that was captured and is intercepted until it is released
+    held: Option<Capability>,
+}
+
+impl InputCapture {
+    /// Start waiting for the next significant input, which is sent to the
+    /// given channel. Any capture that is already waiting is cancelled.
+    pub fn start(&mut self, sender: mpsc::Sender<Option<CapturedInput>>) {
+        self.cancel();
+        self.waiting = Some(sender);
+    }
+
+    /// Stop waiting for input and send nothing to the waiting channel
+    pub fn cancel(&mut self) {
+        let Some(sender) = self.waiting.take() else {
+            return;
+        };
+        if let Err(e) = sender.try_send(None) {
+            log::debug!("Failed to cancel input capture: {e:?}");
+        }
+    }
+
+    /// Returns true if a capture is waiting for input
+    pub fn is_waiting(&self) -> bool {
+        self.waiting.is_some()
+    }
+
+    /// Process the given source event. Returns true if the event was
+    /// intercepted and should not be processed any further.
+    pub fn process(&mut self, event: &NativeEvent) -> bool {
+        let cap = event.as_capability();
+        if self.held.as_ref() == Some(&cap) {
+            if !is_significant(event) {
+                self.held = None;
+            }
+            return true;
+        }
+
+        if self.waiting.is_none() || !is_significant(event) {
+            return false;
+        }
+        let Some(sender) = self.waiting.take() else {
+            return false;
+        };
+        let captured = CapturedInput::new(event);
+        log::debug!("Captured input: {captured:?}");
+        if let Err(e) = sender.try_send(Some(captured)) {
+            log::debug!("Failed to send captured input: {e:?}");
+        }
+        self.held = Some(cap);
+
+        true
+    }
+}
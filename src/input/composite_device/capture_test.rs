@@ -0,0 +1,39 @@
+use tokio::sync::mpsc;
+
+use crate::input::capability::{GamepadAxis, GamepadButton};
+
+use super::capture::InputCapture;
+use super::test_events::{button_event, stick_event};
+
+#[test]
+fn test_input_capture() {
+    let mut capture = InputCapture::default();
+    let (tx, mut rx) = mpsc::channel(1);
+
+    // Events should pass through when no capture is waiting
+    assert!(!capture.process(&button_event(GamepadButton::South, true)));
+
+    // Releases and small axis movements should not be captured
+    capture.start(tx);
+    assert!(!capture.process(&button_event(GamepadButton::South, false)));
+    assert!(!capture.process(&stick_event(GamepadAxis::LeftStick, 0.2, 0.0)));
+    assert!(capture.is_waiting());
+
+    // The first significant input should be captured and intercepted
+    assert!(capture.process(&stick_event(GamepadAxis::LeftStick, -0.8, 0.0)));
+    assert!(!capture.is_waiting());
+    let captured = rx.try_recv().unwrap().unwrap();
+    assert_eq!(captured.capability, "Gamepad:Axis:LeftStick");
+
+    // The captured input should be intercepted until it is released
+    assert!(!capture.process(&button_event(GamepadButton::East, true)));
+    assert!(capture.process(&stick_event(GamepadAxis::LeftStick, -1.0, 0.0)));
+    assert!(capture.process(&stick_event(GamepadAxis::LeftStick, 0.0, 0.0)));
+    assert!(!capture.process(&stick_event(GamepadAxis::LeftStick, 0.0, 0.0)));
+
+    // Cancelling should send nothing
+    let (tx, mut rx) = mpsc::channel(1);
+    capture.start(tx);
+    capture.cancel();
+    assert!(rx.try_recv().unwrap().is_none());
+}
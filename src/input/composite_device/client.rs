@@ -1,20 +1,33 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::mpsc::{channel, error::SendError, Sender};
+use tokio::sync::mpsc::{
+    channel,
+    error::{SendError, TrySendError},
+    Sender,
+};
 
 use crate::config::CompositeDeviceConfig;
 use crate::input::event::native::NativeEvent;
+use crate::input::source::SourceDeviceError;
 use crate::input::target::client::TargetDeviceClient;
 use crate::input::{capability::Capability, event::Event, output_event::OutputEvent};
+use crate::platform::state::PlatformState;
 use crate::udev::device::UdevDevice;
 
-use super::{CompositeCommand, InterceptMode};
+use super::{
+    capture::CapturedInput, dpad_stick::DPadStickMode, overload::OverloadStats,
+    radial_menu::RadialMenuStick, CompositeCommand, InterceptMode,
+};
 
 /// Possible errors for a composite device client
 #[derive(Error, Debug)]
 pub enum ClientError {
     #[error("failed to send command to device")]
     SendError(SendError<CompositeCommand>),
+    #[error("failed to try to send command to device")]
+    TrySendError(TrySendError<CompositeCommand>),
     #[error("service encountered an error processing the request")]
     ServiceError(Box<dyn std::error::Error>),
     #[error("device no longer exists")]
@@ -27,10 +40,17 @@ impl From<SendError<CompositeCommand>> for ClientError {
     }
 }
 
+impl From<TrySendError<CompositeCommand>> for ClientError {
+    fn from(err: TrySendError<CompositeCommand>) -> Self {
+        Self::TrySendError(err)
+    }
+}
+
 /// A client for a composite device
 #[derive(Debug, Clone)]
 pub struct CompositeDeviceClient {
     tx: Sender<CompositeCommand>,
+    overload_stats: Arc<OverloadStats>,
 }
 
 impl From<Sender<CompositeCommand>> for CompositeDeviceClient {
@@ -41,7 +61,22 @@ impl From<Sender<CompositeCommand>> for CompositeDeviceClient {
 
 impl CompositeDeviceClient {
     pub fn new(tx: Sender<CompositeCommand>) -> Self {
-        Self { tx }
+        Self::new_with_stats(tx, Arc::default())
+    }
+
+    /// Create a new client that records channel overloads in the given stats
+    pub fn new_with_stats(
+        tx: Sender<CompositeCommand>,
+        overload_stats: Arc<OverloadStats>,
+    ) -> Self {
+        Self { tx, overload_stats }
+    }
+
+    /// Returns the counters of how often the event channels of the composite
+    /// device were overloaded. These are read directly instead of through the
+    /// command channel, so they are available even while it is full.
+    pub fn overload_stats(&self) -> &OverloadStats {
+        &self.overload_stats
     }
 
     /// Get the name of the composite device
@@ -62,6 +97,15 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Process the given event from the given device without waiting for
+    /// room in the channel. Fails with [ClientError::TrySendError] if the
+    /// channel is full.
+    pub fn try_process_event(&self, device_id: String, event: Event) -> Result<(), ClientError> {
+        self.tx
+            .try_send(CompositeCommand::ProcessEvent(device_id, event))?;
+        Ok(())
+    }
+
     /// Process the given event from the given device (blocking)
     pub fn blocking_process_event(
         &self,
@@ -73,6 +117,17 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Report an error that occurred reading from the given source device (blocking)
+    pub fn blocking_source_device_error(
+        &self,
+        device_id: String,
+        error: SourceDeviceError,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .blocking_send(CompositeCommand::SourceDeviceError(device_id, error))?;
+        Ok(())
+    }
+
     /// Process the given output event
     pub async fn process_output_event(&self, event: OutputEvent) -> Result<(), ClientError> {
         self.tx
@@ -108,6 +163,20 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Get the effective configuration of the [CompositeDevice] as a single
+    /// YAML document, including its capability map, loaded device profile,
+    /// and any settings changed at runtime.
+    pub async fn export_active_config(&self) -> Result<String, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::ExportActiveConfig(tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return result.map_err(|e| ClientError::ServiceError(e.into()));
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Get the [CompositeDeviceConfig] from the [CompositeDevice] (blocking)
     pub fn blocking_get_config(&self) -> Result<CompositeDeviceConfig, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -148,6 +217,132 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Set the mode used to translate between the DPad and the left stick
+    pub async fn set_dpad_stick_mode(&self, mode: DPadStickMode) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetDPadStickMode(mode))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the mode used to translate between the DPad and the left stick
+    pub async fn get_dpad_stick_mode(&self) -> Result<DPadStickMode, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetDPadStickMode(tx)).await?;
+        if let Some(mode) = rx.recv().await {
+            return Ok(mode);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the stick used to select sectors of an on-screen radial menu
+    pub async fn set_radial_menu_stick(&self, stick: RadialMenuStick) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetRadialMenuStick(stick))
+            .await?;
+        Ok(())
+    }
+
+    /// Get the stick used to select sectors of an on-screen radial menu
+    pub async fn get_radial_menu_stick(&self) -> Result<RadialMenuStick, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetRadialMenuStick(tx))
+            .await?;
+        if let Some(stick) = rx.recv().await {
+            return Ok(stick);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Get the most recent input events received by the composite device,
+    /// formatted as one line per event from oldest to newest.
+    pub async fn get_event_history(&self) -> Result<Vec<String>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetEventHistory(tx)).await?;
+        if let Some(events) = rx.recv().await {
+            return Ok(events);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the backend used to emulate keyboard and mouse input
+    pub async fn get_input_backend(&self) -> Result<InputBackend, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetInputBackend(tx)).await?;
+        if let Some(backend) = rx.recv().await {
+            return Ok(backend);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns true if the input inspector is streaming events as DBus signals
+    pub async fn get_inspector_enabled(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetInspectorEnabled(tx))
+            .await?;
+        if let Some(enabled) = rx.recv().await {
+            return Ok(enabled);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Enable or disable streaming events written to target devices as DBus
+    /// signals.
+    pub async fn set_inspector_enabled(&self, enabled: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetInspectorEnabled(enabled))
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the names of the tee sinks of the composite device and whether
+    /// each of them is enabled
+    pub async fn get_tee_sinks(&self) -> Result<HashMap<String, bool>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetTeeSinks(tx)).await?;
+        if let Some(sinks) = rx.recv().await {
+            return Ok(sinks);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Enable or disable sending a copy of the events written to target
+    /// devices to the tee sink with the given name
+    pub async fn set_tee_enabled(&self, name: String, enabled: bool) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SetTeeEnabled(name, enabled, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns true if a mapping dry run is active
+    pub async fn get_dry_run_enabled(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetDryRunEnabled(tx)).await?;
+        if let Some(enabled) = rx.recv().await {
+            return Ok(enabled);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Start or stop a mapping dry run, which reports events as DBus signals
+    /// instead of writing them to target devices
+    pub async fn set_dry_run_enabled(&self, enabled: bool) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetDryRunEnabled(enabled))
+            .await?;
+        Ok(())
+    }
+
     /// Get the source device paths of the composite device
     pub async fn get_source_device_paths(&self) -> Result<Vec<String>, ClientError> {
         let (tx, mut rx) = channel(1);
@@ -211,6 +406,95 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Stop the given source device because it was detached from the
+    /// composite device. The device is un-hidden if `unhide` is true.
+    pub async fn detach_source_device(
+        &self,
+        device: UdevDevice,
+        unhide: bool,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SourceDeviceDetached(device, unhide))
+            .await?;
+        Ok(())
+    }
+
+    /// Attach the source device with the given path to the composite device,
+    /// detaching it from any other composite device that is using it
+    pub async fn attach_source_device_path(&self, path: String) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::AttachSourceDevice(path, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Detach the source device with the given path from the composite device
+    pub async fn remove_source_device_path(&self, path: String) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::RemoveSourceDevice(path, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Release the hidraw source device with the given path so other programs
+    /// can access it until the given timeout expires
+    pub async fn enter_maintenance_mode(
+        &self,
+        path: String,
+        timeout: Duration,
+    ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::EnterMaintenanceMode(path, timeout, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Wait for the next significant physical input, like a button press.
+    /// Returns nothing if no input is received before the given timeout.
+    pub async fn capture_next_input(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<CapturedInput>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::CaptureNextInput(timeout, tx))
+            .await?;
+        if let Some(captured) = rx.recv().await {
+            return Ok(captured);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Re-attach the source device with the given path that was released for
+    /// maintenance
+    pub async fn exit_maintenance_mode(&self, path: String) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::ExitMaintenanceMode(path))
+            .await?;
+        Ok(())
+    }
+
     /// Set the given target devices on the composite device. This will create
     /// new target devices, attach them to this device, and stop/remove any
     /// existing devices.
@@ -221,6 +505,20 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Notify the composite device that the target device at the given path
+    /// of the given kind stopped responding, so it can be re-created if it is
+    /// attached to this device.
+    pub async fn target_device_stalled(
+        &self,
+        path: String,
+        kind: String,
+    ) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::TargetDeviceStalled(path, kind))
+            .await?;
+        Ok(())
+    }
+
     /// Attach the given target devices to the composite device
     pub async fn attach_target_devices(
         &self,
@@ -242,6 +540,28 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Returns true if no input has been received from source devices within
+    /// the configured idle timeout
+    pub async fn get_idle(&self) -> Result<bool, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetIdle(tx)).await?;
+        if let Some(idle) = rx.recv().await {
+            return Ok(idle);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the latest normalized values of the sticks and triggers of
+    /// the source devices, keyed by capability
+    pub async fn get_live_values(&self) -> Result<HashMap<String, Vec<f64>>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetLiveValues(tx)).await?;
+        if let Some(values) = rx.recv().await {
+            return Ok(values);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Load the device profile from the given path
     pub async fn load_profile_path(&self, path: String) -> Result<(), ClientError> {
         let (tx, mut rx) = channel(1);
@@ -272,6 +592,22 @@ impl CompositeDeviceClient {
         Err(ClientError::ChannelClosed)
     }
 
+    /// Stack the profile at the given path on top of the loaded device
+    /// profile. The overlay is removed if the path is empty.
+    pub async fn set_profile_overlay(&self, path: String) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::SetProfileOverlay(path, tx))
+            .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Write the given event to the appropriate target device.
     pub async fn write_event(&self, event: NativeEvent) -> Result<(), ClientError> {
         self.tx.send(CompositeCommand::WriteEvent(event)).await?;
@@ -317,22 +653,92 @@ impl CompositeDeviceClient {
         Ok(())
     }
 
+    /// Get the events that activate input interception while in "PASS" mode
+    /// and the event that is emitted when it is activated.
+    pub async fn get_intercept_activation(
+        &self,
+    ) -> Result<(Vec<Capability>, Capability), ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx
+            .send(CompositeCommand::GetInterceptActivation(tx))
+            .await?;
+        if let Some(activation) = rx.recv().await {
+            return Ok(activation);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Set the events to look for to activate input interception while in
-    /// "PASS" mode.
+    /// "PASS" mode. Fails if the composite device does not implement all of
+    /// the activation events.
     pub async fn set_intercept_activation(
         &self,
         activation_caps: Vec<Capability>,
         target_cap: Capability,
     ) -> Result<(), ClientError> {
+        let (tx, mut rx) = channel(1);
         self.tx
             .send(CompositeCommand::SetInterceptActivation(
                 activation_caps,
                 target_cap,
+                tx,
             ))
             .await?;
+        if let Some(result) = rx.recv().await {
+            return match result {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ClientError::ServiceError(e.into())),
+            };
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Returns the Steam app id of the game focused in gamescope, if known
+    pub async fn get_focused_app(&self) -> Result<Option<u32>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetFocusedApp(tx)).await?;
+        if let Some(app_id) = rx.recv().await {
+            return Ok(app_id);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
+    /// Set the Steam app id of the game focused in gamescope
+    pub async fn set_focused_app(&self, app_id: Option<u32>) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetFocusedApp(app_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the current hardware platform state used to conditionally apply
+    /// capability mappings.
+    pub async fn set_platform_state(&self, state: PlatformState) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetPlatformState(state))
+            .await?;
+        Ok(())
+    }
+
+    /// Set the player index shown on the player indicators of the source
+    /// devices. A value of [None] clears the indicators.
+    pub async fn set_player_index(&self, index: Option<u8>) -> Result<(), ClientError> {
+        self.tx
+            .send(CompositeCommand::SetPlayerIndex(index))
+            .await?;
         Ok(())
     }
 
+    /// Get the player index of the composite device
+    pub async fn get_player_index(&self) -> Result<Option<u8>, ClientError> {
+        let (tx, mut rx) = channel(1);
+        self.tx.send(CompositeCommand::GetPlayerIndex(tx)).await?;
+        if let Some(index) = rx.recv().await {
+            return Ok(index);
+        }
+        Err(ClientError::ChannelClosed)
+    }
+
     /// Stop the composite device
     pub async fn stop(&self) -> Result<(), ClientError> {
         self.tx.send(CompositeCommand::Stop).await?;
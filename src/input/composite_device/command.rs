@@ -1,4 +1,7 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use tokio::sync::mpsc;
 
@@ -8,40 +11,95 @@ use crate::{
         capability::Capability,
         event::{native::NativeEvent, Event},
         output_event::OutputEvent,
+        source::SourceDeviceError,
         target::client::TargetDeviceClient,
+        xtest::InputBackend,
     },
+    platform::state::PlatformState,
     udev::device::UdevDevice,
 };
 
-use super::InterceptMode;
+use super::{
+    capture::CapturedInput, dpad_stick::DPadStickMode, radial_menu::RadialMenuStick, InterceptMode,
+};
 
 /// CompositeDevice commands define all the different ways to interact with [CompositeDevice]
 /// over a channel. These commands are processed in an asyncronous thread and
 /// dispatched as they come in.
 #[derive(Debug, Clone)]
 pub enum CompositeCommand {
+    ActivatorTimeout(String),
+    AttachSourceDevice(String, mpsc::Sender<Result<(), String>>),
     AttachTargetDevices(HashMap<String, TargetDeviceClient>),
+    AxisButtonRepeat(String),
+    CancelInputCapture,
+    CaptureNextInput(Duration, mpsc::Sender<Option<CapturedInput>>),
+    CheckDebounce(Capability),
+    CheckIdle,
+    ControllerAttachmentChanged(String, bool),
+    DPadRepeat(Capability),
+    DwellClick,
+    EnterMaintenanceMode(String, Duration, mpsc::Sender<Result<(), String>>),
+    ExitMaintenanceMode(String),
+    ExportActiveConfig(mpsc::Sender<Result<String, String>>),
     GetConfig(mpsc::Sender<CompositeDeviceConfig>),
+    GetDPadStickMode(mpsc::Sender<DPadStickMode>),
+    GetDryRunEnabled(mpsc::Sender<bool>),
     GetCapabilities(mpsc::Sender<HashSet<Capability>>),
     GetDBusDevicePaths(mpsc::Sender<Vec<String>>),
+    GetEventHistory(mpsc::Sender<Vec<String>>),
+    GetFocusedApp(mpsc::Sender<Option<u32>>),
+    GetIdle(mpsc::Sender<bool>),
+    GetInputBackend(mpsc::Sender<InputBackend>),
+    GetInspectorEnabled(mpsc::Sender<bool>),
+    GetInterceptActivation(mpsc::Sender<(Vec<Capability>, Capability)>),
     GetInterceptMode(mpsc::Sender<InterceptMode>),
+    GetLiveValues(mpsc::Sender<HashMap<String, Vec<f64>>>),
     GetName(mpsc::Sender<String>),
+    GetPlayerIndex(mpsc::Sender<Option<u8>>),
     GetProfileName(mpsc::Sender<String>),
+    GetRadialMenuStick(mpsc::Sender<RadialMenuStick>),
     GetSourceDevicePaths(mpsc::Sender<Vec<String>>),
     GetTargetCapabilities(mpsc::Sender<HashSet<Capability>>),
     GetTargetDevicePaths(mpsc::Sender<Vec<String>>),
+    GetTeeSinks(mpsc::Sender<HashMap<String, bool>>),
     HandleEvent(NativeEvent),
     LoadProfileFromYaml(String, mpsc::Sender<Result<(), String>>),
     LoadProfilePath(String, mpsc::Sender<Result<(), String>>),
     ProcessEvent(String, Event),
+    ProcessIdleWakeEvents,
     ProcessOutputEvent(OutputEvent),
+    RecreateTargetDevices,
     RemoveRecentEvent(Capability),
-    SetInterceptActivation(Vec<Capability>, Capability),
+    RemoveSourceDevice(String, mpsc::Sender<Result<(), String>>),
+    RunHeldAction(String),
+    SaveDriftCompensation,
+    SetDPadStickMode(DPadStickMode),
+    SetFocusedApp(Option<u32>),
+    SetDryRunEnabled(bool),
+    SetInspectorEnabled(bool),
+    SetRadialMenuStick(RadialMenuStick),
+    SetInterceptActivation(
+        Vec<Capability>,
+        Capability,
+        mpsc::Sender<Result<(), String>>,
+    ),
     SetInterceptMode(InterceptMode),
+    SetPlatformState(PlatformState),
+    SetPlayerIndex(Option<u8>),
+    SetProfileOverlay(String, mpsc::Sender<Result<(), String>>),
     SetTargetDevices(Vec<String>),
+    SetTeeEnabled(String, bool, mpsc::Sender<Result<(), String>>),
+    SignalLiveValues,
     SourceDeviceAdded(UdevDevice),
+    SourceDeviceDetached(UdevDevice, bool),
+    SourceDeviceError(String, SourceDeviceError),
     SourceDeviceRemoved(UdevDevice),
+    SourceDeviceStalled(UdevDevice),
     SourceDeviceStopped(UdevDevice),
+    TargetDeviceStalled(String, String),
+    UpdateBatteryLed,
+    UpdateForceFeedback,
     WriteChordEvent(Vec<NativeEvent>),
     WriteEvent(NativeEvent),
     WriteSendEvent(NativeEvent),
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::DebounceConfig,
+    input::{capability::Capability, event::native::NativeEvent},
+};
+
+/// Result of passing an event through the [Debouncer]
+#[derive(Debug, Default)]
+pub struct DebounceUpdate {
+    /// Event to emit, if the state of the input changed
+    pub event: Option<NativeEvent>,
+    /// If set, the debouncer should be checked again for the input after
+    /// this duration to emit a pending state change.
+    pub wake_after: Option<Duration>,
+}
+
+/// State of a single debounced input
+#[derive(Debug, Clone)]
+struct DebounceState {
+    /// Minimum time the input must be stable before a state change is emitted
+    time: Duration,
+    /// Last state that was emitted
+    emitted: bool,
+    /// Last state reported by the source device
+    current: bool,
+    /// Last event reported by the source device
+    last_event: Option<NativeEvent>,
+    /// Time the source device last changed state
+    changed_at: Instant,
+}
+
+/// Filters state changes of flaky digital inputs (e.g. worn buttons that
+/// double-fire). A state change is only emitted once the input has been stable
+/// for the configured amount of time, so chatter shorter than that is dropped.
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    states: HashMap<Capability, DebounceState>,
+}
+
+impl Debouncer {
+    /// Create a new debouncer from the given configs
+    pub fn new(configs: &[DebounceConfig]) -> Self {
+        let now = Instant::now();
+        let states = configs
+            .iter()
+            .filter(|config| config.time_ms > 0)
+            .map(|config| {
+                let state = DebounceState {
+                    time: Duration::from_millis(config.time_ms),
+                    emitted: false,
+                    current: false,
+                    last_event: None,
+                    changed_at: now,
+                };
+                (config.capability.clone().into(), state)
+            })
+            .filter(|(cap, _)| *cap != Capability::NotImplemented)
+            .collect();
+        Self { states }
+    }
+
+    /// Returns true if no inputs are debounced
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Process the given source event. Events for inputs that are not
+    /// debounced are returned immediately.
+    pub fn process(&mut self, event: NativeEvent, now: Instant) -> DebounceUpdate {
+        let cap = event.as_capability();
+        let Some(state) = self.states.get_mut(&cap) else {
+            return DebounceUpdate {
+                event: Some(event),
+                wake_after: None,
+            };
+        };
+
+        let pressed = event.pressed();
+        if pressed != state.current {
+            state.current = pressed;
+            state.changed_at = now;
+        }
+        state.last_event = Some(event);
+
+        // If the input returned to the emitted state, the pending change was
+        // only noise.
+        if state.current == state.emitted {
+            return DebounceUpdate::default();
+        }
+
+        DebounceUpdate {
+            event: None,
+            wake_after: Some(state.time),
+        }
+    }
+
+    /// Check whether a pending state change of the given input has been
+    /// stable long enough to be emitted.
+    pub fn check(&mut self, cap: &Capability, now: Instant) -> DebounceUpdate {
+        let Some(state) = self.states.get_mut(cap) else {
+            return DebounceUpdate::default();
+        };
+        if state.current == state.emitted {
+            return DebounceUpdate::default();
+        }
+
+        // The input changed again since the check was scheduled
+        let stable_for = now.duration_since(state.changed_at);
+        if stable_for < state.time {
+            return DebounceUpdate {
+                event: None,
+                wake_after: Some(state.time - stable_for),
+            };
+        }
+
+        state.emitted = state.current;
+        DebounceUpdate {
+            event: state.last_event.take(),
+            wake_after: None,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::DebounceConfig,
+    input::capability::{Capability, Gamepad, GamepadButton},
+};
+
+use super::debounce::Debouncer;
+use super::test_events::button_event;
+
+const DEBOUNCE_CONFIG: &str = r#"
+capability:
+  gamepad:
+    button: South
+time_ms: 30
+"#;
+
+#[test]
+fn test_debounce() {
+    let config: DebounceConfig = serde_yaml::from_str(DEBOUNCE_CONFIG).unwrap();
+    let mut debouncer = Debouncer::new(&[config]);
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let start = Instant::now();
+
+    // Inputs that are not debounced should pass through
+    let update = debouncer.process(button_event(GamepadButton::East, true), start);
+    assert!(update.event.is_some());
+    assert!(update.wake_after.is_none());
+
+    // A press should only be emitted once it has been stable
+    let update = debouncer.process(button_event(GamepadButton::South, true), start);
+    assert!(update.event.is_none());
+    assert_eq!(update.wake_after, Some(Duration::from_millis(30)));
+    let update = debouncer.check(&cap, start + Duration::from_millis(30));
+    assert!(update.event.unwrap().pressed(), "should emit press");
+
+    // A release that bounces back to pressed should be dropped
+    let t = start + Duration::from_millis(100);
+    debouncer.process(button_event(GamepadButton::South, false), t);
+    debouncer.process(
+        button_event(GamepadButton::South, true),
+        t + Duration::from_millis(5),
+    );
+    let update = debouncer.check(&cap, t + Duration::from_millis(30));
+    assert!(update.event.is_none(), "bounce should be dropped");
+    assert!(update.wake_after.is_none());
+
+    // A release that changes again before it is stable should be rescheduled
+    debouncer.process(button_event(GamepadButton::South, false), t);
+    debouncer.process(
+        button_event(GamepadButton::South, true),
+        t + Duration::from_millis(5),
+    );
+    debouncer.process(
+        button_event(GamepadButton::South, false),
+        t + Duration::from_millis(10),
+    );
+    let update = debouncer.check(&cap, t + Duration::from_millis(30));
+    assert!(update.event.is_none());
+    assert_eq!(update.wake_after, Some(Duration::from_millis(10)));
+    let update = debouncer.check(&cap, t + Duration::from_millis(40));
+    assert!(!update.event.unwrap().pressed(), "should emit release");
+}
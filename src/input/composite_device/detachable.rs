@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+/// Source device that belongs to a detachable controller
+#[derive(Debug)]
+struct DetachableSource {
+    /// Name of the controller. E.g. "right"
+    controller: String,
+    /// Whether the presence of the source device means the controller is
+    /// attached
+    attached: bool,
+}
+
+/// Tracks whether the detachable controllers of a composite device (e.g. the
+/// halves of a Legion Go or Joy-Cons on their rails) are attached, based on
+/// which of their source devices are present. A controller is attached while
+/// all of its present source devices indicate it is attached, and detached
+/// while none of them are present or any of them only appear while detached.
+#[derive(Debug, Default)]
+pub struct DetachableTracker {
+    /// Present source devices of detachable controllers, keyed by source id
+    sources: HashMap<String, DetachableSource>,
+    /// Whether each controller is attached
+    attached: HashMap<String, bool>,
+}
+
+impl DetachableTracker {
+    /// Record that the source device with the given id of the given controller
+    /// is present. Returns whether the controller is now attached, if its
+    /// state changed.
+    pub fn add_source(
+        &mut self,
+        source_id: &str,
+        controller: &str,
+        attached: bool,
+    ) -> Option<bool> {
+        let source = DetachableSource {
+            controller: controller.to_string(),
+            attached,
+        };
+        self.sources.insert(source_id.to_string(), source);
+        self.update(controller)
+    }
+
+    /// Record that the source device with the given id is no longer present.
+    /// Returns the controller it belongs to and whether that controller is
+    /// now attached, if its state changed.
+    pub fn remove_source(&mut self, source_id: &str) -> Option<(String, bool)> {
+        let source = self.sources.remove(source_id)?;
+        let attached = self.update(source.controller.as_str())?;
+        Some((source.controller, attached))
+    }
+
+    /// Update the state of the given controller from its present source
+    /// devices. Returns the new state if it changed.
+    fn update(&mut self, controller: &str) -> Option<bool> {
+        let mut sources = self
+            .sources
+            .values()
+            .filter(|source| source.controller == controller)
+            .peekable();
+        let attached = sources.peek().is_some() && sources.all(|source| source.attached);
+        let previous = self.attached.insert(controller.to_string(), attached);
+        if previous == Some(attached) {
+            return None;
+        }
+        Some(attached)
+    }
+}
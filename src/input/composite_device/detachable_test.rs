@@ -0,0 +1,31 @@
+use super::detachable::DetachableTracker;
+
+#[test]
+fn test_detachable_tracker() {
+    let mut tracker = DetachableTracker::default();
+
+    // The controller is attached once its source device appears
+    assert_eq!(tracker.add_source("hidraw://1", "right", true), Some(true));
+    assert_eq!(tracker.add_source("evdev://2", "right", true), None);
+
+    // The controller stays attached until all its source devices are gone
+    assert_eq!(tracker.remove_source("hidraw://1"), None);
+    assert_eq!(
+        tracker.remove_source("evdev://2"),
+        Some(("right".to_string(), false))
+    );
+
+    // Source devices that only appear while detached detach the controller
+    assert_eq!(tracker.add_source("hidraw://1", "right", true), Some(true));
+    assert_eq!(
+        tracker.add_source("hidraw://3", "right", false),
+        Some(false)
+    );
+    assert_eq!(
+        tracker.remove_source("hidraw://3"),
+        Some(("right".to_string(), true))
+    );
+
+    // Unknown source devices do not change any controller
+    assert_eq!(tracker.remove_source("hidraw://4"), None);
+}
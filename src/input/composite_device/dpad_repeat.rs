@@ -0,0 +1,114 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    config::DPadRepeatConfig,
+    input::{
+        capability::{Capability, Gamepad},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::dpad_stick::is_dpad;
+
+/// Default time a DPad button must be held before it starts repeating
+const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+/// Default number of repeats per second
+const DEFAULT_RATE: f64 = 10.0;
+
+/// Repeats held DPad buttons for games that do not implement repeat, so long
+/// lists can be scrolled by holding a direction. Only DPad buttons that are
+/// translated into DPad buttons or keyboard keys are repeated. Each repeat
+/// releases and presses the output again.
+#[derive(Debug, Clone)]
+pub struct DPadRepeater {
+    delay: Duration,
+    interval: Duration,
+    /// Held output capabilities with the source capability they were
+    /// translated from
+    held: HashMap<Capability, Capability>,
+}
+
+impl DPadRepeater {
+    /// Create a new repeater from the given configuration
+    pub fn new(config: &DPadRepeatConfig) -> Self {
+        let delay = config
+            .delay_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_DELAY);
+        let rate = config
+            .rate
+            .filter(|rate| *rate > 0.0)
+            .unwrap_or(DEFAULT_RATE);
+        Self {
+            delay,
+            interval: Duration::from_secs_f64(1.0 / rate),
+            held: HashMap::new(),
+        }
+    }
+
+    /// Returns the time a DPad button must be held before it starts repeating
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Returns the time between repeats
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the output capabilities that are currently held
+    pub fn held(&self) -> Vec<Capability> {
+        self.held.keys().cloned().collect()
+    }
+
+    /// Update the repeater with the given translated event. Returns true if
+    /// the output of the event started being held and should be repeated,
+    /// false if it was released, or [None] if the event is not repeated.
+    pub fn process(&mut self, event: &NativeEvent) -> Option<bool> {
+        let cap = event.as_capability();
+        let source_cap = event.get_source_capability().unwrap_or_else(|| cap.clone());
+        if !is_repeatable(&source_cap, &cap) {
+            return None;
+        }
+        if event.pressed() {
+            let started = !self.held.contains_key(&cap);
+            self.held.insert(cap, source_cap);
+            return started.then_some(true);
+        }
+        self.held.remove(&cap).map(|_| false)
+    }
+
+    /// Returns the events to repeat the given held output capability, or no
+    /// events if it is no longer held.
+    pub fn repeat(&self, cap: &Capability) -> Vec<NativeEvent> {
+        let Some(source_cap) = self.held.get(cap) else {
+            return Vec::new();
+        };
+        [false, true]
+            .into_iter()
+            .map(|pressed| {
+                NativeEvent::new_translated(
+                    source_cap.clone(),
+                    cap.clone(),
+                    InputValue::Bool(pressed),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns true if the given source DPad button is translated into a DPad
+/// button or a keyboard key
+fn is_repeatable(source_cap: &Capability, cap: &Capability) -> bool {
+    let Capability::Gamepad(Gamepad::Button(source)) = source_cap else {
+        return false;
+    };
+    if !is_dpad(source) {
+        return false;
+    }
+    match cap {
+        Capability::Gamepad(Gamepad::Button(button)) => is_dpad(button),
+        Capability::Keyboard(_) => true,
+        _ => false,
+    }
+}
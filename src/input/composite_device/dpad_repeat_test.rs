@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::{
+    config::DPadRepeatConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, Keyboard},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::{
+    dpad_repeat::DPadRepeater,
+    test_events::{button_capability, button_event},
+};
+
+fn translated(source: Capability, target: Capability, pressed: bool) -> NativeEvent {
+    NativeEvent::new_translated(source, target, InputValue::Bool(pressed))
+}
+
+#[test]
+fn test_dpad_repeat() {
+    let config = DPadRepeatConfig {
+        delay_ms: Some(400),
+        rate: Some(20.0),
+    };
+    let mut repeater = DPadRepeater::new(&config);
+    assert_eq!(repeater.delay(), Duration::from_millis(400));
+    assert_eq!(repeater.interval(), Duration::from_millis(50));
+
+    // Untranslated DPad buttons should be repeated
+    let up = button_capability(GamepadButton::DPadUp);
+    let event = NativeEvent::new(up.clone(), InputValue::Bool(true));
+    assert_eq!(repeater.process(&event), Some(true));
+    assert_eq!(repeater.process(&event), None, "Already held");
+    let events = repeater.repeat(&up);
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].get_value(), InputValue::Bool(false)));
+    assert!(matches!(events[1].get_value(), InputValue::Bool(true)));
+
+    let event = NativeEvent::new(up.clone(), InputValue::Bool(false));
+    assert_eq!(repeater.process(&event), Some(false));
+    assert!(repeater.repeat(&up).is_empty());
+
+    // DPad buttons translated into keys should be repeated
+    let down = button_capability(GamepadButton::DPadDown);
+    let key = Capability::Keyboard(Keyboard::KeyDown);
+    let event = translated(down.clone(), key.clone(), true);
+    assert_eq!(repeater.process(&event), Some(true));
+    let events = repeater.repeat(&key);
+    assert_eq!(events[1].as_capability(), key);
+    assert_eq!(events[1].get_source_capability(), Some(down));
+}
+
+#[test]
+fn test_dpad_repeat_ignored() {
+    let config = DPadRepeatConfig {
+        delay_ms: None,
+        rate: None,
+    };
+    let mut repeater = DPadRepeater::new(&config);
+
+    // Other buttons should not be repeated
+    let event = button_event(GamepadButton::South, true);
+    assert_eq!(repeater.process(&event), None);
+
+    // DPad buttons translated into other buttons should not be repeated
+    let event = translated(
+        button_capability(GamepadButton::DPadLeft),
+        button_capability(GamepadButton::South),
+        true,
+    );
+    assert_eq!(repeater.process(&event), None);
+
+    // Stick input translated into the DPad should not be repeated
+    let event = translated(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        button_capability(GamepadButton::DPadLeft),
+        true,
+    );
+    assert_eq!(repeater.process(&event), None);
+    assert!(repeater.held().is_empty());
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f64::consts::PI,
+    fmt,
+    str::FromStr,
+};
+
+use crate::{
+    config::CardinalSnapConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Deflection of the left stick required to press a DPad direction
+const STICK_DEADZONE: f64 = 0.5;
+/// Default angle in degrees around each cardinal direction that stick input
+/// is snapped to
+const DEFAULT_SNAP_ANGLE: f64 = 15.0;
+
+/// Mode used to translate between the DPad and the left stick, for games that
+/// only read one or the other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DPadStickMode {
+    /// Events are passed through unchanged
+    #[default]
+    None,
+    /// DPad presses are translated into full deflection left stick values
+    DPadToStick,
+    /// The left stick is translated into DPad presses using 8-way sectors
+    StickToDPad,
+}
+
+impl fmt::Display for DPadStickMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DPadStickMode::None => write!(f, "none"),
+            DPadStickMode::DPadToStick => write!(f, "dpad_to_stick"),
+            DPadStickMode::StickToDPad => write!(f, "stick_to_dpad"),
+        }
+    }
+}
+
+impl FromStr for DPadStickMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(DPadStickMode::None),
+            "dpad_to_stick" => Ok(DPadStickMode::DPadToStick),
+            "stick_to_dpad" => Ok(DPadStickMode::StickToDPad),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Translates DPad button events into left stick events and vice versa
+/// depending on the current [DPadStickMode].
+#[derive(Debug, Clone, Default)]
+pub struct DPadStickTranslator {
+    mode: DPadStickMode,
+    /// DPad buttons currently pressed on the source device
+    dpad_pressed: HashSet<GamepadButton>,
+    /// Last known position of the left stick
+    stick: (f64, f64),
+    /// DPad buttons currently pressed by the left stick
+    stick_dpad: HashSet<GamepadButton>,
+    /// Optional snapping of diagonal input to cardinal directions, applied
+    /// before translation
+    cardinal_snap: Option<CardinalSnap>,
+}
+
+impl DPadStickTranslator {
+    /// Returns the current translation mode
+    pub fn mode(&self) -> DPadStickMode {
+        self.mode
+    }
+
+    /// Set the translation mode. Returns events that release any inputs that
+    /// were held by the previous mode.
+    pub fn set_mode(&mut self, mode: DPadStickMode) -> Vec<NativeEvent> {
+        if mode == self.mode {
+            return Vec::new();
+        }
+        let events = match self.mode {
+            DPadStickMode::None => Vec::new(),
+            DPadStickMode::DPadToStick if !self.dpad_pressed.is_empty() => {
+                vec![stick_event(0.0, 0.0)]
+            }
+            DPadStickMode::DPadToStick => Vec::new(),
+            DPadStickMode::StickToDPad => self
+                .stick_dpad
+                .drain()
+                .map(|button| button_event(button, false))
+                .collect(),
+        };
+        self.mode = mode;
+        self.dpad_pressed.clear();
+        self.stick = (0.0, 0.0);
+        self.stick_dpad.clear();
+
+        events
+    }
+
+    /// Set how diagonal input is snapped to cardinal directions before it is
+    /// translated, or [None] to disable snapping.
+    pub fn set_cardinal_snap(&mut self, config: Option<&CardinalSnapConfig>) {
+        self.cardinal_snap = config.map(CardinalSnap::new);
+    }
+
+    /// Translate the given event based on the current mode
+    pub fn translate(&mut self, event: NativeEvent) -> Vec<NativeEvent> {
+        let events = match self.cardinal_snap.as_mut() {
+            Some(snap) => snap.snap(event),
+            None => vec![event],
+        };
+        events
+            .into_iter()
+            .flat_map(|event| self.translate_event(event))
+            .collect()
+    }
+
+    /// Translate the given event based on the current mode
+    fn translate_event(&mut self, event: NativeEvent) -> Vec<NativeEvent> {
+        match (self.mode, event.as_capability()) {
+            (DPadStickMode::DPadToStick, Capability::Gamepad(Gamepad::Button(button)))
+                if is_dpad(&button) =>
+            {
+                if event.pressed() {
+                    self.dpad_pressed.insert(button);
+                } else {
+                    self.dpad_pressed.remove(&button);
+                }
+                let (x, y) = self.dpad_direction();
+                vec![stick_event(x, y)]
+            }
+            (
+                DPadStickMode::StickToDPad,
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            ) => {
+                if let InputValue::Vector2 { x, y } = event.get_value() {
+                    if let Some(x) = x {
+                        self.stick.0 = x;
+                    }
+                    if let Some(y) = y {
+                        self.stick.1 = y;
+                    }
+                }
+                let pressed = sector_buttons(self.stick.0, self.stick.1);
+
+                let mut events = Vec::new();
+                for button in self.stick_dpad.difference(&pressed) {
+                    events.push(button_event(button.clone(), false));
+                }
+                for button in pressed.difference(&self.stick_dpad) {
+                    events.push(button_event(button.clone(), true));
+                }
+                self.stick_dpad = pressed;
+                events
+            }
+            _ => vec![event],
+        }
+    }
+
+    /// Returns the full deflection stick position for the pressed DPad buttons
+    fn dpad_direction(&self) -> (f64, f64) {
+        let pressed = |button: GamepadButton| self.dpad_pressed.contains(&button) as i8 as f64;
+        let x = pressed(GamepadButton::DPadRight) - pressed(GamepadButton::DPadLeft);
+        let y = pressed(GamepadButton::DPadDown) - pressed(GamepadButton::DPadUp);
+
+        // Keep diagonals on the unit circle
+        let magnitude = x.hypot(y);
+        if magnitude > 1.0 {
+            return (x / magnitude, y / magnitude);
+        }
+        (x, y)
+    }
+}
+
+/// Snaps near-diagonal DPad and stick input to the nearest cardinal
+/// direction. DPad diagonals are corrected to the direction that was pressed
+/// first, while stick positions within the snap angle of a cardinal direction
+/// are rotated onto it.
+#[derive(Debug, Clone, Default)]
+pub struct CardinalSnap {
+    /// Angle in degrees on either side of each cardinal direction that snaps
+    /// to it
+    angle: f64,
+    /// Whether DPad diagonals are corrected
+    dpad: bool,
+    /// DPad buttons held on the source device in the order they were pressed
+    dpad_held: Vec<GamepadButton>,
+    /// DPad buttons currently pressed after correction
+    dpad_pressed: HashSet<GamepadButton>,
+    /// Last known position of each stick
+    sticks: HashMap<GamepadAxis, (f64, f64)>,
+}
+
+impl CardinalSnap {
+    /// Create a new snap from the given config
+    pub fn new(config: &CardinalSnapConfig) -> Self {
+        Self {
+            angle: config.angle.unwrap_or(DEFAULT_SNAP_ANGLE).clamp(0.0, 45.0),
+            dpad: config.dpad.unwrap_or(true),
+            ..Default::default()
+        }
+    }
+
+    /// Snap the given event to a cardinal direction. Events that are not DPad
+    /// or stick events are passed through unchanged.
+    pub fn snap(&mut self, event: NativeEvent) -> Vec<NativeEvent> {
+        match event.as_capability() {
+            Capability::Gamepad(Gamepad::Button(button)) if self.dpad && is_dpad(&button) => {
+                self.snap_dpad(button, event.pressed())
+            }
+            Capability::Gamepad(Gamepad::Axis(
+                axis @ (GamepadAxis::LeftStick | GamepadAxis::RightStick),
+            )) => {
+                let InputValue::Vector2 { x, y } = event.get_value() else {
+                    return vec![event];
+                };
+                let stick = self.sticks.entry(axis.clone()).or_insert((0.0, 0.0));
+                if let Some(x) = x {
+                    stick.0 = x;
+                }
+                if let Some(y) = y {
+                    stick.1 = y;
+                }
+                let (x, y) = snap_to_cardinal(stick.0, stick.1, self.angle);
+                let cap = Capability::Gamepad(Gamepad::Axis(axis));
+                let value = InputValue::Vector2 {
+                    x: Some(x),
+                    y: Some(y),
+                };
+                match event.get_source_capability() {
+                    Some(source_cap) => vec![NativeEvent::new_translated(source_cap, cap, value)],
+                    None => vec![NativeEvent::new(cap, value)],
+                }
+            }
+            _ => vec![event],
+        }
+    }
+
+    /// Update the held DPad buttons and return events for the buttons whose
+    /// corrected state changed. Only the first held direction and its
+    /// opposite direction are pressed.
+    fn snap_dpad(&mut self, button: GamepadButton, pressed: bool) -> Vec<NativeEvent> {
+        self.dpad_held.retain(|held| *held != button);
+        if pressed {
+            self.dpad_held.push(button);
+        }
+
+        let mut wanted = HashSet::new();
+        if let Some(first) = self.dpad_held.first() {
+            let horizontal = is_horizontal(first);
+            for held in self.dpad_held.iter() {
+                if is_horizontal(held) == horizontal {
+                    wanted.insert(held.clone());
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        for button in self.dpad_pressed.difference(&wanted) {
+            events.push(button_event(button.clone(), false));
+        }
+        for button in wanted.difference(&self.dpad_pressed) {
+            events.push(button_event(button.clone(), true));
+        }
+        self.dpad_pressed = wanted;
+        events
+    }
+}
+
+/// Returns the stick position rotated onto the nearest cardinal direction if
+/// it is within the given angle in degrees of it. The deflection of the stick
+/// is preserved.
+pub fn snap_to_cardinal(x: f64, y: f64, angle: f64) -> (f64, f64) {
+    let magnitude = x.hypot(y);
+    if magnitude == 0.0 {
+        return (x, y);
+    }
+    let direction = y.atan2(x).to_degrees();
+    let cardinal = (direction / 90.0).round() * 90.0;
+    if (direction - cardinal).abs() > angle {
+        return (x, y);
+    }
+    let cardinal = cardinal.to_radians();
+    let x = magnitude * cardinal.cos();
+    let y = magnitude * cardinal.sin();
+
+    // Avoid tiny values on the other axis from floating point error
+    let clean = |value: f64| if value.abs() < 1e-9 { 0.0 } else { value };
+    (clean(x), clean(y))
+}
+
+/// Returns true if the given DPad button is a horizontal direction
+fn is_horizontal(button: &GamepadButton) -> bool {
+    matches!(button, GamepadButton::DPadLeft | GamepadButton::DPadRight)
+}
+
+/// Returns true if the given button is a DPad direction
+pub fn is_dpad(button: &GamepadButton) -> bool {
+    matches!(
+        button,
+        GamepadButton::DPadUp
+            | GamepadButton::DPadDown
+            | GamepadButton::DPadLeft
+            | GamepadButton::DPadRight
+    )
+}
+
+/// Returns the DPad buttons for the 8-way sector the given stick position is
+/// in, or no buttons if the stick is inside the deadzone.
+pub fn sector_buttons(x: f64, y: f64) -> HashSet<GamepadButton> {
+    if x.hypot(y) < STICK_DEADZONE {
+        return HashSet::new();
+    }
+
+    // Sectors are 45 degrees wide and centered on each direction, starting
+    // with right and going clockwise since positive y values point down.
+    let angle = y.atan2(x).rem_euclid(2.0 * PI);
+    let sector = (angle / (PI / 4.0)).round() as u8 % 8;
+    let buttons: &[GamepadButton] = match sector {
+        0 => &[GamepadButton::DPadRight],
+        1 => &[GamepadButton::DPadDown, GamepadButton::DPadRight],
+        2 => &[GamepadButton::DPadDown],
+        3 => &[GamepadButton::DPadDown, GamepadButton::DPadLeft],
+        4 => &[GamepadButton::DPadLeft],
+        5 => &[GamepadButton::DPadUp, GamepadButton::DPadLeft],
+        6 => &[GamepadButton::DPadUp],
+        _ => &[GamepadButton::DPadUp, GamepadButton::DPadRight],
+    };
+
+    buttons.iter().cloned().collect()
+}
+
+fn stick_event(x: f64, y: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        },
+    )
+}
+
+fn button_event(button: GamepadButton, pressed: bool) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(button)),
+        InputValue::Bool(pressed),
+    )
+}
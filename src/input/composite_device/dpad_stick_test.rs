@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::CardinalSnapConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton},
+        event::value::InputValue,
+    },
+};
+
+use super::dpad_stick::{sector_buttons, snap_to_cardinal, DPadStickMode, DPadStickTranslator};
+use super::test_events::{button_event, stick};
+
+#[test]
+fn test_dpad_to_stick() {
+    let mut translator = DPadStickTranslator::default();
+    translator.set_mode(DPadStickMode::DPadToStick);
+
+    let events = translator.translate(button_event(GamepadButton::DPadRight, true));
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if x == 1.0 && y == 0.0
+    ));
+
+    // Diagonals should stay on the unit circle
+    let events = translator.translate(button_event(GamepadButton::DPadUp, true));
+    let InputValue::Vector2 {
+        x: Some(x),
+        y: Some(y),
+    } = events[0].get_value()
+    else {
+        panic!("Expected stick value");
+    };
+    assert!((x.hypot(y) - 1.0).abs() < 0.0001);
+    assert!(x > 0.0 && y < 0.0);
+
+    // Changing the mode should recenter the stick
+    let events = translator.set_mode(DPadStickMode::None);
+    assert_eq!(events.len(), 1);
+    let events = translator.translate(button_event(GamepadButton::DPadLeft, true));
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft))
+    );
+}
+
+#[test]
+fn test_stick_to_dpad() {
+    let mut translator = DPadStickTranslator::default();
+    translator.set_mode(DPadStickMode::StickToDPad);
+
+    // Axis values can be updated one component at a time
+    let events = translator.translate(stick(Some(0.1), None));
+    assert!(events.is_empty());
+    let events = translator.translate(stick(None, Some(-1.0)));
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].as_capability(),
+        Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp))
+    );
+    assert!(events[0].pressed());
+
+    // Returning to center releases the button
+    let events = translator.translate(stick(Some(0.0), Some(0.0)));
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].pressed());
+}
+
+#[test]
+fn test_sector_buttons() {
+    assert!(sector_buttons(0.2, 0.2).is_empty());
+    assert_eq!(
+        sector_buttons(0.7, 0.7),
+        HashSet::from([GamepadButton::DPadDown, GamepadButton::DPadRight])
+    );
+    assert_eq!(
+        sector_buttons(-1.0, 0.1),
+        HashSet::from([GamepadButton::DPadLeft])
+    );
+}
+
+#[test]
+fn test_cardinal_snap() {
+    let mut translator = DPadStickTranslator::default();
+    translator.set_cardinal_snap(Some(&CardinalSnapConfig {
+        angle: Some(20.0),
+        dpad: Some(true),
+    }));
+
+    // A perpendicular direction pressed after the first one is suppressed
+    let events = translator.translate(button_event(GamepadButton::DPadRight, true));
+    assert_eq!(events.len(), 1);
+    let events = translator.translate(button_event(GamepadButton::DPadUp, true));
+    assert!(events.is_empty());
+
+    // Releasing the first direction promotes the held one
+    let events = translator.translate(button_event(GamepadButton::DPadRight, false));
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().any(|event| event.as_capability()
+        == Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp))
+        && event.pressed()));
+
+    // Near-cardinal stick positions keep their deflection
+    let events = translator.translate(stick(Some(0.9), Some(0.2)));
+    let InputValue::Vector2 {
+        x: Some(x),
+        y: Some(y),
+    } = events[0].get_value()
+    else {
+        panic!("Expected stick value");
+    };
+    assert!((x - 0.9_f64.hypot(0.2)).abs() < 0.0001);
+    assert_eq!(y, 0.0);
+
+    // Diagonals outside of the snap angle are unchanged
+    assert_eq!(snap_to_cardinal(0.5, 0.5, 20.0), (0.5, 0.5));
+    assert_eq!(snap_to_cardinal(0.0, -0.8, 20.0), (0.0, -0.8));
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::DriftCompensationConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Default maximum distance from the center that can be learned as drift
+const DEFAULT_MAX_OFFSET: f64 = 0.15;
+/// Default time a stick must rest before its center is learned
+const DEFAULT_REST_TIME: Duration = Duration::from_secs(1);
+/// Maximum movement of a stick while it is considered to be resting
+const REST_TOLERANCE: f64 = 0.02;
+/// Weight of new resting samples in the learned center
+const LEARN_RATE: f64 = 0.05;
+/// Minimum change in a learned center before it needs to be saved again
+const SAVE_THRESHOLD: f64 = 0.005;
+
+/// Learned resting center of an analog stick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StickCenter {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Tracked state of a single analog stick
+#[derive(Debug, Default)]
+struct StickState {
+    /// Learned resting center of the stick
+    center: StickCenter,
+    /// Center of the stick when it was last saved
+    saved: StickCenter,
+    /// Last raw position of the stick
+    position: (f64, f64),
+    /// Position and time the stick started resting at
+    resting: Option<((f64, f64), Instant)>,
+}
+
+/// Compensates for analog stick drift by learning the resting center of each
+/// stick over time. The center is only learned while a stick rests close to
+/// the center without moving, so deliberate input is never learned. Stick
+/// values are then offset by the learned center and rescaled so the full
+/// range of the stick can still be reached.
+#[derive(Debug)]
+pub struct DriftCompensator {
+    /// Maximum distance from the center that can be learned as drift
+    max_offset: f64,
+    /// Time a stick must rest before its center is learned
+    rest_time: Duration,
+    /// State of each stick by its axis name
+    sticks: BTreeMap<String, StickState>,
+}
+
+impl DriftCompensator {
+    pub fn new(config: &DriftCompensationConfig) -> Self {
+        Self {
+            max_offset: config.max_offset.unwrap_or(DEFAULT_MAX_OFFSET).abs(),
+            rest_time: config
+                .rest_time_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_REST_TIME),
+            sticks: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the learned center of each stick by its axis name
+    pub fn centers(&self) -> BTreeMap<String, StickCenter> {
+        self.sticks
+            .iter()
+            .map(|(name, state)| (name.clone(), state.center))
+            .collect()
+    }
+
+    /// Set the learned center of each stick, e.g. from a previous session.
+    /// Centers further away than the maximum offset are ignored.
+    pub fn set_centers(&mut self, centers: BTreeMap<String, StickCenter>) {
+        for (name, center) in centers {
+            if center.x.hypot(center.y) > self.max_offset {
+                log::warn!("Ignoring out of range center for {name}: {center:?}");
+                continue;
+            }
+            let state = self.sticks.entry(name).or_default();
+            state.center = center;
+            state.saved = center;
+        }
+    }
+
+    /// Returns true if any learned center changed enough since it was last
+    /// saved, and marks all centers as saved.
+    pub fn take_unsaved(&mut self) -> bool {
+        let mut unsaved = false;
+        for state in self.sticks.values_mut() {
+            let dx = state.center.x - state.saved.x;
+            let dy = state.center.y - state.saved.y;
+            if dx.hypot(dy) >= SAVE_THRESHOLD {
+                unsaved = true;
+                state.saved = state.center;
+            }
+        }
+        unsaved
+    }
+
+    /// Learn from and compensate the drift of the given source event
+    pub fn process(&mut self, event: NativeEvent, now: Instant) -> NativeEvent {
+        let Capability::Gamepad(Gamepad::Axis(axis)) = event.as_capability() else {
+            return event;
+        };
+        if !matches!(axis, GamepadAxis::LeftStick | GamepadAxis::RightStick) {
+            return event;
+        }
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return event;
+        };
+
+        let state = self.sticks.entry(axis.to_string()).or_default();
+        let position = (x.unwrap_or(state.position.0), y.unwrap_or(state.position.1));
+        state.position = position;
+        learn(state, position, now, self.max_offset, self.rest_time);
+
+        let center = state.center;
+        let value = InputValue::Vector2 {
+            x: x.map(|x| compensate(x, center.x)),
+            y: y.map(|y| compensate(y, center.y)),
+        };
+        match event.get_source_capability() {
+            Some(source_cap) => {
+                NativeEvent::new_translated(source_cap, event.as_capability(), value)
+            }
+            None => NativeEvent::new(event.as_capability(), value),
+        }
+    }
+}
+
+/// Update the learned center of the given stick if it has been resting long
+/// enough near the center.
+fn learn(
+    state: &mut StickState,
+    position: (f64, f64),
+    now: Instant,
+    max_offset: f64,
+    rest_time: Duration,
+) {
+    // Sticks far from the center are being used
+    if position.0.hypot(position.1) > max_offset {
+        state.resting = None;
+        return;
+    }
+
+    // Restart resting if the stick moved
+    let (anchor, since) = *state.resting.get_or_insert((position, now));
+    let moved = (position.0 - anchor.0).hypot(position.1 - anchor.1);
+    if moved > REST_TOLERANCE {
+        state.resting = Some((position, now));
+        return;
+    }
+    if now.duration_since(since) < rest_time {
+        return;
+    }
+
+    state.center.x += (position.0 - state.center.x) * LEARN_RATE;
+    state.center.y += (position.1 - state.center.y) * LEARN_RATE;
+}
+
+/// Offset the given stick value by the learned center and rescale it so the
+/// full range from -1.0 to 1.0 can still be reached.
+fn compensate(value: f64, center: f64) -> f64 {
+    let range = if value >= center {
+        1.0 - center
+    } else {
+        1.0 + center
+    };
+    if range <= 0.0 {
+        return value;
+    }
+    ((value - center) / range).clamp(-1.0, 1.0)
+}
+
+/// Load the learned stick centers from the given file
+pub fn load_centers(path: &Path) -> Result<BTreeMap<String, StickCenter>, Box<dyn Error>> {
+    let data = fs::read_to_string(path)?;
+    let centers = serde_yaml::from_str(data.as_str())?;
+    Ok(centers)
+}
+
+/// Save the given learned stick centers to the given file
+pub fn save_centers(
+    path: &Path,
+    centers: &BTreeMap<String, StickCenter>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let data = serde_yaml::to_string(centers)?;
+    fs::write(path, data)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::DriftCompensationConfig,
+    input::{
+        capability::GamepadAxis,
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::drift::DriftCompensator;
+use super::test_events;
+
+fn stick_value(event: NativeEvent) -> (f64, f64) {
+    match event.get_value() {
+        InputValue::Vector2 { x, y } => (x.unwrap_or_default(), y.unwrap_or_default()),
+        value => panic!("Unexpected value: {value:?}"),
+    }
+}
+
+#[test]
+fn test_drift_compensation() {
+    let config = DriftCompensationConfig {
+        max_offset: Some(0.15),
+        rest_time_ms: Some(1000),
+    };
+    let mut compensator = DriftCompensator::new(&config);
+    let start = Instant::now();
+    let at = |ms: u64| start + Duration::from_millis(ms);
+    let stick_event = |x, y| test_events::stick_event(GamepadAxis::LeftStick, x, y);
+
+    // Nothing should be learned before the stick rested long enough
+    let (x, _) = stick_value(compensator.process(stick_event(0.1, 0.0), at(0)));
+    assert_eq!(x, 0.1);
+    compensator.process(stick_event(0.1, 0.0), at(500));
+    assert!(!compensator.take_unsaved());
+
+    // A resting stick should slowly learn its center
+    for ms in (1000..5000).step_by(10) {
+        compensator.process(stick_event(0.1, 0.0), at(ms));
+    }
+    let (x, y) = stick_value(compensator.process(stick_event(0.1, 0.0), at(5000)));
+    assert!(x.abs() < 0.01, "Drift should be compensated: {x}");
+    assert_eq!(y, 0.0);
+    assert!(compensator.take_unsaved());
+    assert!(!compensator.take_unsaved());
+
+    // The full range of the stick should still be reachable
+    let (x, _) = stick_value(compensator.process(stick_event(1.0, 0.0), at(5010)));
+    assert_eq!(x, 1.0);
+    let (x, _) = stick_value(compensator.process(stick_event(-1.0, 0.0), at(5020)));
+    assert_eq!(x, -1.0);
+
+    // Deliberate input should never be learned
+    let center = compensator.centers()["LeftStick"];
+    for ms in (6000..10000).step_by(10) {
+        compensator.process(stick_event(0.5, 0.5), at(ms));
+    }
+    assert_eq!(compensator.centers()["LeftStick"], center);
+}
@@ -0,0 +1,102 @@
+use tokio::sync::mpsc::{self, error::TrySendError};
+use zbus::{object_server::SignalContext, Connection};
+
+use crate::{
+    dbus::interface::{capability_strings, composite_device::CompositeDeviceInterface},
+    input::{capability::Capability, event::native::NativeEvent},
+};
+
+use super::inspector::format_value;
+
+/// Maximum number of dry run events waiting to be emitted. Events are
+/// dropped if the DBus connection cannot keep up.
+const BUFFER_SIZE: usize = 1024;
+
+/// An event that would have been written to target devices, reported by the
+/// [MappingDryRun] instead.
+#[derive(Debug, Clone)]
+pub struct DryRunEvent {
+    /// Id of the source device the event originated from, if known
+    pub source: String,
+    /// Capability string of the event before it was translated. This is the
+    /// same as the capability if the event was not translated.
+    pub source_capability: String,
+    /// Capability string of the event that would be emitted
+    pub capability: String,
+    /// Human-readable value of the event. E.g. "pressed"
+    pub value: String,
+}
+
+impl DryRunEvent {
+    pub fn new(source: &str, event: &NativeEvent) -> Self {
+        let capability = event.as_capability();
+        let source_capability = event
+            .get_source_capability()
+            .unwrap_or_else(|| capability.clone());
+        Self {
+            source: source.to_string(),
+            source_capability: capability_string(source_capability),
+            capability: capability_string(capability),
+            value: format_value(&event.get_value()),
+        }
+    }
+}
+
+/// Returns the capability string of the given capability
+fn capability_string(capability: Capability) -> String {
+    capability_strings(vec![capability])
+        .pop()
+        .unwrap_or_default()
+}
+
+/// Reports what the current bindings would do without emitting anything. While
+/// a dry run is active, events are not written to target devices and are
+/// emitted as DBus signals instead, so configuration tools can show what a
+/// button is bound to (e.g. in "press a button to bind" flows).
+#[derive(Debug)]
+pub struct MappingDryRun {
+    tx: mpsc::Sender<DryRunEvent>,
+}
+
+impl MappingDryRun {
+    /// Start emitting dry run events as signals on the composite device DBus
+    /// interface at the given path. Signals stop being emitted when the dry
+    /// run is dropped.
+    pub fn new(conn: Connection, path: String) -> Self {
+        let (tx, mut rx) = mpsc::channel::<DryRunEvent>(BUFFER_SIZE);
+        tokio::task::spawn(async move {
+            let ctxt = match SignalContext::new(&conn, path.as_str()) {
+                Ok(ctxt) => ctxt,
+                Err(e) => {
+                    log::error!("Failed to create signal context for dry run: {e:?}");
+                    return;
+                }
+            };
+            while let Some(event) = rx.recv().await {
+                let result = CompositeDeviceInterface::dry_run_event(
+                    &ctxt,
+                    event.source.as_str(),
+                    event.source_capability.as_str(),
+                    event.capability.as_str(),
+                    event.value.as_str(),
+                )
+                .await;
+                if let Err(e) = result {
+                    log::debug!("Failed to emit dry run event: {e:?}");
+                }
+            }
+            log::debug!("Mapping dry run stopped for {path}");
+        });
+
+        Self { tx }
+    }
+
+    /// Report the given event from the given source device
+    pub fn report(&self, source: &str, event: &NativeEvent) {
+        match self.tx.try_send(DryRunEvent::new(source, event)) {
+            Ok(_) => (),
+            Err(TrySendError::Full(_)) => log::trace!("Dry run is full, dropping event"),
+            Err(TrySendError::Closed(_)) => log::debug!("Dry run is closed"),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, Keyboard},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::dry_run::DryRunEvent;
+
+#[test]
+fn test_dry_run_event() {
+    // Translated events should report the capability they were translated from
+    let event = NativeEvent::new_translated(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+        Capability::Keyboard(Keyboard::KeySpace),
+        InputValue::Bool(true),
+    );
+    let reported = DryRunEvent::new("evdev://event3", &event);
+    assert_eq!(reported.source, "evdev://event3");
+    assert_eq!(reported.source_capability, "Gamepad:Button:LeftPaddle1");
+    assert_eq!(reported.capability, "Keyboard:KeySpace");
+    assert_eq!(reported.value, "pressed");
+
+    // Events that were not translated should report the same capability
+    let event = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        InputValue::Bool(false),
+    );
+    let reported = DryRunEvent::new("evdev://event3", &event);
+    assert_eq!(reported.source_capability, "Gamepad:Button:South");
+    assert_eq!(reported.capability, "Gamepad:Button:South");
+    assert_eq!(reported.value, "released");
+}
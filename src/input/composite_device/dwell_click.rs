@@ -0,0 +1,233 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::config::DwellClickConfig;
+
+/// Default time the pointer must rest before clicking
+const DEFAULT_TIME: Duration = Duration::from_millis(1000);
+/// Default distance in pixels the pointer can move while still resting
+const DEFAULT_RADIUS: f64 = 8.0;
+
+/// Click emitted by the [DwellClicker]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwellClick {
+    Left,
+    Right,
+    Double,
+}
+
+/// Click the [DwellClicker] is configured to emit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwellClickMode {
+    /// Always emit the given click
+    Click(DwellClick),
+    /// Choose the click by the direction the pointer moves after the dwell
+    Gesture,
+}
+
+impl fmt::Display for DwellClickMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DwellClickMode::Click(DwellClick::Left) => write!(f, "left"),
+            DwellClickMode::Click(DwellClick::Right) => write!(f, "right"),
+            DwellClickMode::Click(DwellClick::Double) => write!(f, "double"),
+            DwellClickMode::Gesture => write!(f, "gesture"),
+        }
+    }
+}
+
+impl FromStr for DwellClickMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(DwellClickMode::Click(DwellClick::Left)),
+            "right" => Ok(DwellClickMode::Click(DwellClick::Right)),
+            "double" => Ok(DwellClickMode::Click(DwellClick::Double)),
+            "gesture" => Ok(DwellClickMode::Gesture),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Action the composite device should take after updating the [DwellClicker]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwellAction {
+    /// (Re)start the dwell timer
+    Wait,
+    /// Cancel the dwell timer
+    Cancel,
+    /// Emit the given click
+    Click(DwellClick),
+}
+
+/// State of the pointer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum DwellState {
+    /// The pointer has not moved since the last click
+    #[default]
+    Idle,
+    /// The pointer is resting, waiting for the dwell time to pass
+    Resting,
+    /// The dwell time passed in gesture mode, waiting for the pointer to move
+    /// in the direction of the click
+    Selecting,
+}
+
+/// Emits a click when the mouse pointer stays within a small radius for some
+/// time, for users who cannot press buttons while aiming. Only movement
+/// beyond the radius restarts the dwell, so hand tremor does not prevent the
+/// click. Mouse motion can either be relative (e.g. from a touchpad), or a
+/// velocity (e.g. from a stick translated into mouse motion), where the
+/// pointer keeps moving until the velocity returns to zero.
+#[derive(Debug)]
+pub struct DwellClicker {
+    time: Duration,
+    radius: f64,
+    mode: DwellClickMode,
+    state: DwellState,
+    /// Distance the pointer moved since the dwell started
+    offset: (f64, f64),
+    /// Current velocity of the pointer
+    velocity: (f64, f64),
+}
+
+impl DwellClicker {
+    /// Create a new dwell clicker from the given configuration
+    pub fn new(config: &DwellClickConfig) -> Self {
+        let mode = config
+            .click
+            .as_ref()
+            .map(|click| {
+                DwellClickMode::from_str(click).unwrap_or_else(|_| {
+                    log::warn!("Unknown dwell click: {click}");
+                    DwellClickMode::Click(DwellClick::Left)
+                })
+            })
+            .unwrap_or(DwellClickMode::Click(DwellClick::Left));
+        let time = config
+            .time_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_TIME);
+        let radius = config.radius.unwrap_or(DEFAULT_RADIUS).max(0.0);
+        Self {
+            time,
+            radius,
+            mode,
+            state: DwellState::default(),
+            offset: (0.0, 0.0),
+            velocity: (0.0, 0.0),
+        }
+    }
+
+    /// Returns the time the pointer must rest before clicking
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Update the dwell with the given relative pointer motion
+    pub fn on_motion(&mut self, dx: f64, dy: f64) -> Option<DwellAction> {
+        self.offset.0 += dx;
+        self.offset.1 += dy;
+        if self.offset.0.hypot(self.offset.1) <= self.radius {
+            return None;
+        }
+        let (x, y) = self.offset;
+        self.offset = (0.0, 0.0);
+
+        if self.state == DwellState::Selecting {
+            self.state = DwellState::Idle;
+            return match select_click(x, y) {
+                Some(click) => Some(DwellAction::Click(click)),
+                None => Some(DwellAction::Cancel),
+            };
+        }
+
+        self.state = DwellState::Resting;
+        if self.is_moving() {
+            return Some(DwellAction::Cancel);
+        }
+        Some(DwellAction::Wait)
+    }
+
+    /// Update the dwell with the given pointer velocity. Axes that are not
+    /// given keep their last velocity.
+    pub fn on_velocity(&mut self, x: Option<f64>, y: Option<f64>) -> Option<DwellAction> {
+        let was_moving = self.is_moving();
+        if let Some(x) = x {
+            self.velocity.0 = x;
+        }
+        if let Some(y) = y {
+            self.velocity.1 = y;
+        }
+
+        if self.is_moving() {
+            // Moving at any velocity leaves the radius, so treat the motion
+            // as a step just outside of it in the same direction.
+            let (x, y) = self.velocity;
+            let scale = (self.radius + 1.0) / x.hypot(y);
+            self.offset = (0.0, 0.0);
+            return self.on_motion(x * scale, y * scale);
+        }
+
+        // The pointer stopped, so the dwell starts from here
+        if was_moving && self.state == DwellState::Resting {
+            return Some(DwellAction::Wait);
+        }
+        None
+    }
+
+    /// Update the dwell once the dwell timer fires
+    pub fn on_dwell(&mut self) -> Option<DwellAction> {
+        if self.is_moving() {
+            return None;
+        }
+        match self.state {
+            DwellState::Idle => None,
+            DwellState::Resting => {
+                self.offset = (0.0, 0.0);
+                match self.mode {
+                    DwellClickMode::Click(click) => {
+                        self.state = DwellState::Idle;
+                        Some(DwellAction::Click(click))
+                    }
+                    DwellClickMode::Gesture => {
+                        // Give up on the gesture if the pointer keeps resting
+                        // for another dwell.
+                        self.state = DwellState::Selecting;
+                        Some(DwellAction::Wait)
+                    }
+                }
+            }
+            DwellState::Selecting => {
+                self.state = DwellState::Idle;
+                None
+            }
+        }
+    }
+
+    /// Reset the dwell, e.g. when the pointer was clicked with a button
+    pub fn reset(&mut self) {
+        self.state = DwellState::Idle;
+        self.offset = (0.0, 0.0);
+    }
+
+    /// Returns true if the pointer is moving with a velocity
+    fn is_moving(&self) -> bool {
+        self.velocity != (0.0, 0.0)
+    }
+}
+
+/// Returns the click for a gesture in the given direction. Moving down
+/// cancels the click.
+fn select_click(x: f64, y: f64) -> Option<DwellClick> {
+    if x.abs() >= y.abs() {
+        if x < 0.0 {
+            return Some(DwellClick::Left);
+        }
+        return Some(DwellClick::Right);
+    }
+    if y < 0.0 {
+        return Some(DwellClick::Double);
+    }
+    None
+}
@@ -0,0 +1,89 @@
+use crate::config::DwellClickConfig;
+
+use super::dwell_click::{DwellAction, DwellClick, DwellClicker};
+
+fn clicker(click: &str) -> DwellClicker {
+    DwellClicker::new(&DwellClickConfig {
+        time_ms: Some(500),
+        radius: Some(5.0),
+        click: Some(click.to_string()),
+    })
+}
+
+#[test]
+fn test_dwell_click() {
+    let mut dwell = clicker("right");
+
+    // Nothing is clicked before the pointer moves
+    assert_eq!(dwell.on_dwell(), None);
+
+    // Moving beyond the radius starts the dwell, while small movements do
+    // not restart it
+    assert_eq!(dwell.on_motion(10.0, 0.0), Some(DwellAction::Wait));
+    assert_eq!(dwell.on_motion(2.0, 2.0), None);
+    assert_eq!(
+        dwell.on_dwell(),
+        Some(DwellAction::Click(DwellClick::Right))
+    );
+
+    // Only one click is emitted until the pointer moves again
+    assert_eq!(dwell.on_dwell(), None);
+    assert_eq!(dwell.on_motion(2.0, 2.0), None);
+    assert_eq!(dwell.on_dwell(), None);
+    assert_eq!(dwell.on_motion(2.0, 2.0), Some(DwellAction::Wait));
+}
+
+#[test]
+fn test_dwell_click_velocity() {
+    let mut dwell = clicker("left");
+
+    // The dwell does not start while the pointer keeps moving
+    assert_eq!(
+        dwell.on_velocity(Some(100.0), None),
+        Some(DwellAction::Cancel)
+    );
+    assert_eq!(dwell.on_dwell(), None);
+    assert_eq!(
+        dwell.on_velocity(None, Some(50.0)),
+        Some(DwellAction::Cancel)
+    );
+
+    // The dwell starts once the pointer stops
+    assert_eq!(
+        dwell.on_velocity(Some(0.0), None),
+        Some(DwellAction::Cancel)
+    );
+    assert_eq!(dwell.on_velocity(None, Some(0.0)), Some(DwellAction::Wait));
+    assert_eq!(dwell.on_dwell(), Some(DwellAction::Click(DwellClick::Left)));
+}
+
+#[test]
+fn test_dwell_click_gesture() {
+    let mut dwell = clicker("gesture");
+
+    // Moving after the dwell selects the click by direction
+    dwell.on_motion(10.0, 0.0);
+    assert_eq!(dwell.on_dwell(), Some(DwellAction::Wait));
+    assert_eq!(
+        dwell.on_motion(0.0, -6.0),
+        Some(DwellAction::Click(DwellClick::Double))
+    );
+
+    dwell.on_motion(10.0, 0.0);
+    dwell.on_dwell();
+    assert_eq!(
+        dwell.on_motion(-6.0, 1.0),
+        Some(DwellAction::Click(DwellClick::Left))
+    );
+
+    // Moving down cancels the click
+    dwell.on_motion(10.0, 0.0);
+    dwell.on_dwell();
+    assert_eq!(dwell.on_motion(0.0, 6.0), Some(DwellAction::Cancel));
+
+    // Resting for another dwell gives up on the gesture
+    dwell.on_motion(10.0, 0.0);
+    dwell.on_dwell();
+    assert_eq!(dwell.on_dwell(), None);
+    assert_eq!(dwell.on_motion(0.0, -6.0), Some(DwellAction::Wait));
+}
@@ -0,0 +1,44 @@
+use serde::Serialize;
+
+use crate::config::{CapabilityMap, CompositeDeviceConfig, DeviceProfile};
+
+/// Settings of a composite device that can be changed at runtime (e.g. over
+/// DBus) and are not part of any config file.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeConfig {
+    /// Current intercept mode. E.g. "None", "Pass", "Always"
+    pub intercept_mode: String,
+    /// Capabilities that activate intercept mode when pressed together
+    pub intercept_activation: Vec<String>,
+    /// Capability emitted when intercept mode is activated
+    pub intercept_target: String,
+    /// Current translation mode between the DPad and the left stick
+    pub dpad_stick_mode: String,
+    /// Player index assigned to the device, if any
+    pub player_index: Option<u8>,
+    /// Names of the active profile layers, from lowest to highest precedence
+    pub active_layers: Vec<String>,
+    /// Kinds of target devices currently in use. E.g. ["xb360", "keyboard"]
+    pub target_devices: Vec<String>,
+    /// Steam app id of the game focused in gamescope, if any
+    pub focused_app_id: Option<u32>,
+}
+
+/// The effective configuration of a composite device, made up of its device
+/// config, capability map, loaded device profile with aliases resolved, and
+/// any runtime changes. Exported as a single document for debugging and for
+/// sharing working configs between users.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveConfig {
+    pub composite_device: CompositeDeviceConfig,
+    pub capability_map: Option<CapabilityMap>,
+    pub profile: Option<DeviceProfile>,
+    pub runtime: RuntimeConfig,
+}
+
+impl ActiveConfig {
+    /// Serialize the active config as YAML
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+}
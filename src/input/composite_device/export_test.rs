@@ -0,0 +1,60 @@
+use crate::config::{CompositeDeviceConfig, DeviceProfile};
+
+use super::export::{ActiveConfig, RuntimeConfig};
+
+const DEVICE_CONFIG: &str = r#"
+version: 1
+kind: CompositeDevice
+name: Test Device
+matches: []
+source_devices:
+  - group: gamepad
+    evdev:
+      name: Test Gamepad
+target_devices:
+  - xb360
+"#;
+
+const PROFILE: &str = r#"
+version: 1
+kind: DeviceProfile
+name: Swap A and B
+mapping:
+  - name: South to East
+    source_event:
+      gamepad:
+        button: South
+    target_events:
+      - gamepad:
+          button: East
+"#;
+
+#[test]
+fn test_export_active_config() {
+    let config = ActiveConfig {
+        composite_device: serde_yaml::from_str::<CompositeDeviceConfig>(DEVICE_CONFIG).unwrap(),
+        capability_map: None,
+        profile: Some(serde_yaml::from_str::<DeviceProfile>(PROFILE).unwrap()),
+        runtime: RuntimeConfig {
+            intercept_mode: "Pass".to_string(),
+            intercept_activation: vec!["Gamepad:Button:Guide".to_string()],
+            intercept_target: "Gamepad:Button:Guide".to_string(),
+            dpad_stick_mode: "none".to_string(),
+            player_index: Some(0),
+            active_layers: vec![],
+            target_devices: vec!["xb360".to_string()],
+            focused_app_id: None,
+        },
+    };
+    let yaml = config.to_yaml().unwrap();
+
+    // The exported profile should load as a regular device profile
+    let document: serde_yaml::Value = serde_yaml::from_str(yaml.as_str()).unwrap();
+    let profile = serde_yaml::from_value::<DeviceProfile>(document["profile"].clone()).unwrap();
+    assert_eq!(profile.name, "Swap A and B");
+    assert_eq!(profile.mapping.len(), 1);
+    let device = document["composite_device"].clone();
+    let device = serde_yaml::from_value::<CompositeDeviceConfig>(device).unwrap();
+    assert_eq!(device.name, "Test Device");
+    assert_eq!(document["runtime"]["intercept_mode"], "Pass");
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use evdev::{FFEffectData, FFEffectKind, FFEnvelope};
+
+/// Maximum force feedback gain
+const MAX_GAIN: u32 = 0xffff;
+/// Maximum magnitude of a level-based (periodic, constant, ramp) effect
+const MAX_LEVEL: u32 = 0x7fff;
+/// Maximum rumble motor magnitude
+const MAX_MAGNITUDE: u32 = 0xffff;
+
+/// Combined rumble motor magnitudes rendered from all playing effects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RumbleMagnitude {
+    pub strong: u16,
+    pub weak: u16,
+}
+
+impl RumbleMagnitude {
+    /// Returns true if both motors are stopped
+    pub fn is_zero(&self) -> bool {
+        self.strong == 0 && self.weak == 0
+    }
+
+    /// Returns the magnitudes to play on a source device on the given side.
+    /// The strong motor is on the left and the weak motor is on the right, so
+    /// each side plays the magnitude of its own motor on all of its motors.
+    pub fn for_side(&self, side: RumbleSide) -> Self {
+        match side {
+            RumbleSide::Both => *self,
+            RumbleSide::Left => Self {
+                strong: self.strong,
+                weak: self.strong,
+            },
+            RumbleSide::Right => Self {
+                strong: self.weak,
+                weak: self.weak,
+            },
+        }
+    }
+}
+
+/// Side of a composite device built from two physical halves (e.g. a pair of
+/// Joy-Cons) that a source device is on. Rumble is routed to each side
+/// instead of playing the full effect on both halves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RumbleSide {
+    /// The source device plays the full effect
+    #[default]
+    Both,
+    /// The source device plays the strong (left) motor
+    Left,
+    /// The source device plays the weak (right) motor
+    Right,
+}
+
+impl FromStr for RumbleSide {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "both" => Ok(RumbleSide::Both),
+            "left" => Ok(RumbleSide::Left),
+            "right" => Ok(RumbleSide::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Returns the given effect routed to a source device on the given side.
+/// Only rumble effects are routed, other effects are returned unchanged.
+pub fn route_effect(data: FFEffectData, side: RumbleSide) -> FFEffectData {
+    let FFEffectKind::Rumble {
+        strong_magnitude,
+        weak_magnitude,
+    } = data.kind
+    else {
+        return data;
+    };
+    let rumble = RumbleMagnitude {
+        strong: strong_magnitude,
+        weak: weak_magnitude,
+    }
+    .for_side(side);
+    FFEffectData {
+        kind: FFEffectKind::Rumble {
+            strong_magnitude: rumble.strong,
+            weak_magnitude: rumble.weak,
+        },
+        ..data
+    }
+}
+
+/// State of a single uploaded effect
+#[derive(Debug, Clone)]
+struct EffectState {
+    data: FFEffectData,
+    /// Time the current playback of the effect should start
+    play_at: Option<Instant>,
+    /// Number of remaining times the effect should be played
+    remaining: i32,
+}
+
+impl EffectState {
+    /// Returns the time the current playback of the effect should stop, or
+    /// [None] if the effect plays forever.
+    fn stop_at(&self) -> Option<Instant> {
+        let length = self.data.replay.length;
+        if length == 0 {
+            return None;
+        }
+        self.play_at
+            .map(|play_at| play_at + Duration::from_millis(length as u64))
+    }
+}
+
+/// Userspace force feedback effect engine that renders periodic, ramp, and
+/// constant effects into rumble motor magnitudes over time, similar to the
+/// kernel's ff-memless driver. This allows games to use these effects with
+/// source devices that only support simple dual-motor rumble.
+#[derive(Debug)]
+pub struct FFEngine {
+    effects: HashMap<i16, EffectState>,
+    gain: u16,
+}
+
+impl Default for FFEngine {
+    fn default() -> Self {
+        Self {
+            effects: HashMap::new(),
+            gain: MAX_GAIN as u16,
+        }
+    }
+}
+
+impl FFEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the given effect should be rendered by the engine
+    /// instead of being uploaded to source devices.
+    pub fn is_emulated(data: &FFEffectData) -> bool {
+        matches!(
+            data.kind,
+            FFEffectKind::Constant { .. }
+                | FFEffectKind::Ramp { .. }
+                | FFEffectKind::Periodic { .. }
+        )
+    }
+
+    /// Returns true if the effect with the given id was uploaded to the engine
+    pub fn contains(&self, id: i16) -> bool {
+        self.effects.contains_key(&id)
+    }
+
+    /// Upload or update the effect with the given id. Updating a playing
+    /// effect keeps it playing.
+    pub fn upload(&mut self, id: i16, data: FFEffectData) {
+        self.effects
+            .entry(id)
+            .and_modify(|state| state.data = data)
+            .or_insert(EffectState {
+                data,
+                play_at: None,
+                remaining: 0,
+            });
+    }
+
+    /// Erase the effect with the given id
+    pub fn erase(&mut self, id: i16) {
+        self.effects.remove(&id);
+    }
+
+    /// Play the effect with the given id the given number of times. A count
+    /// of zero stops the effect.
+    pub fn play(&mut self, id: i16, count: i32, now: Instant) {
+        let Some(state) = self.effects.get_mut(&id) else {
+            return;
+        };
+        if count <= 0 {
+            state.play_at = None;
+            state.remaining = 0;
+            return;
+        }
+        let delay = Duration::from_millis(state.data.replay.delay as u64);
+        state.play_at = Some(now + delay);
+        state.remaining = count;
+    }
+
+    /// Set the overall gain applied to all effects
+    pub fn set_gain(&mut self, gain: u16) {
+        self.gain = gain;
+    }
+
+    /// Returns true if any effect is currently playing or scheduled to play
+    pub fn is_playing(&self) -> bool {
+        self.effects.values().any(|state| state.play_at.is_some())
+    }
+
+    /// Render all playing effects at the given time into combined rumble
+    /// magnitudes. Effects that have finished playing are stopped or
+    /// restarted if they should repeat.
+    pub fn render(&mut self, now: Instant) -> RumbleMagnitude {
+        let gain = self.gain as u32;
+        let mut strong: u32 = 0;
+        let mut weak: u32 = 0;
+
+        for state in self.effects.values_mut() {
+            let Some(play_at) = state.play_at else {
+                continue;
+            };
+
+            // Restart or stop the effect if it has finished playing
+            if let Some(stop_at) = state.stop_at() {
+                if now >= stop_at {
+                    state.remaining -= 1;
+                    if state.remaining <= 0 {
+                        state.play_at = None;
+                        continue;
+                    }
+                    let delay = Duration::from_millis(state.data.replay.delay as u64);
+                    state.play_at = Some(stop_at + delay);
+                    continue;
+                }
+            }
+            if now < play_at {
+                continue;
+            }
+
+            match state.data.kind {
+                FFEffectKind::Rumble {
+                    strong_magnitude,
+                    weak_magnitude,
+                } => {
+                    strong += strong_magnitude as u32 * gain / MAX_GAIN;
+                    weak += weak_magnitude as u32 * gain / MAX_GAIN;
+                }
+                FFEffectKind::Constant { level, envelope } => {
+                    let level = apply_envelope(state, level.unsigned_abs(), &envelope, now);
+                    let level = level as u32 * gain / MAX_LEVEL;
+                    strong += level;
+                    weak += level;
+                }
+                FFEffectKind::Ramp {
+                    start_level,
+                    end_level,
+                    envelope,
+                } => {
+                    let level = ramp_level(state, start_level, end_level, now);
+                    let level = apply_envelope(state, level, &envelope, now);
+                    let level = level as u32 * gain / MAX_LEVEL;
+                    strong += level;
+                    weak += level;
+                }
+                FFEffectKind::Periodic {
+                    magnitude,
+                    envelope,
+                    ..
+                } => {
+                    let level = apply_envelope(state, magnitude.unsigned_abs(), &envelope, now);
+                    let level = level as u32 * gain / MAX_LEVEL;
+                    strong += level;
+                    weak += level;
+                }
+                _ => (),
+            }
+        }
+
+        RumbleMagnitude {
+            strong: strong.min(MAX_MAGNITUDE) as u16,
+            weak: weak.min(MAX_MAGNITUDE) as u16,
+        }
+    }
+}
+
+/// Returns the level of a ramp effect at the given time
+fn ramp_level(state: &EffectState, start_level: i16, end_level: i16, now: Instant) -> u16 {
+    let (Some(play_at), Some(stop_at)) = (state.play_at, state.stop_at()) else {
+        return end_level.unsigned_abs();
+    };
+    let length = stop_at.duration_since(play_at).as_secs_f64();
+    let elapsed = now.saturating_duration_since(play_at).as_secs_f64();
+    let progress = (elapsed / length).clamp(0.0, 1.0);
+    let level = start_level as f64 + (end_level as f64 - start_level as f64) * progress;
+    level.abs() as u16
+}
+
+/// Apply the attack and fade of the given envelope to the given level
+fn apply_envelope(state: &EffectState, level: u16, envelope: &FFEnvelope, now: Instant) -> u16 {
+    let Some(play_at) = state.play_at else {
+        return level;
+    };
+    let attack_length = Duration::from_millis(envelope.attack_length as u64);
+    let fade_length = Duration::from_millis(envelope.fade_length as u64);
+
+    let (envelope_level, time_from_level, time_of_envelope) =
+        if !attack_length.is_zero() && now < play_at + attack_length {
+            let elapsed = now.saturating_duration_since(play_at);
+            (envelope.attack_level, elapsed, attack_length)
+        } else if let Some(stop_at) = state.stop_at().filter(|_| !fade_length.is_zero()) {
+            if now + fade_length < stop_at {
+                return level;
+            }
+            let remaining = stop_at.saturating_duration_since(now);
+            (envelope.fade_level, remaining, fade_length)
+        } else {
+            return level;
+        };
+
+    let progress = time_from_level.as_secs_f64() / time_of_envelope.as_secs_f64();
+    let envelope_level = envelope_level.min(MAX_LEVEL as u16) as f64;
+    let value = envelope_level + (level as f64 - envelope_level) * progress.clamp(0.0, 1.0);
+    value.round() as u16
+}
@@ -0,0 +1,161 @@
+use std::time::{Duration, Instant};
+
+use evdev::{FFEffectData, FFEffectKind, FFEnvelope, FFReplay, FFTrigger, FFWaveform};
+
+use super::ff_engine::{route_effect, FFEngine, RumbleMagnitude, RumbleSide};
+
+fn effect(kind: FFEffectKind, length: u16) -> FFEffectData {
+    FFEffectData {
+        direction: 0,
+        trigger: FFTrigger {
+            button: 0,
+            interval: 0,
+        },
+        replay: FFReplay { length, delay: 0 },
+        kind,
+    }
+}
+
+fn no_envelope() -> FFEnvelope {
+    FFEnvelope {
+        attack_length: 0,
+        attack_level: 0,
+        fade_length: 0,
+        fade_level: 0,
+    }
+}
+
+#[test]
+fn test_periodic_effect() {
+    let mut engine = FFEngine::new();
+    let data = effect(
+        FFEffectKind::Periodic {
+            waveform: FFWaveform::Sine,
+            period: 100,
+            magnitude: 0x7fff,
+            offset: 0,
+            phase: 0,
+            envelope: no_envelope(),
+        },
+        100,
+    );
+    assert!(FFEngine::is_emulated(&data));
+    engine.upload(0, data);
+
+    // Nothing should play until the effect is started
+    let now = Instant::now();
+    assert!(engine.render(now).is_zero());
+
+    engine.play(0, 1, now);
+    let rumble = engine.render(now + Duration::from_millis(10));
+    assert_eq!(rumble.strong, 0xffff);
+    assert_eq!(rumble.weak, 0xffff);
+
+    // The effect should stop after its length
+    let rumble = engine.render(now + Duration::from_millis(150));
+    assert!(rumble.is_zero());
+    assert!(!engine.is_playing());
+}
+
+#[test]
+fn test_constant_effect_with_attack() {
+    let mut engine = FFEngine::new();
+    let data = effect(
+        FFEffectKind::Constant {
+            level: 0x7fff,
+            envelope: FFEnvelope {
+                attack_length: 100,
+                attack_level: 0,
+                fade_length: 0,
+                fade_level: 0,
+            },
+        },
+        0,
+    );
+    engine.upload(0, data);
+
+    let now = Instant::now();
+    engine.play(0, 1, now);
+
+    // Halfway through the attack the effect should be at about half strength
+    let rumble = engine.render(now + Duration::from_millis(50));
+    assert!(rumble.strong > 0x6000 && rumble.strong < 0xa000);
+
+    // Effects without a length play until stopped
+    let rumble = engine.render(now + Duration::from_secs(10));
+    assert_eq!(rumble.strong, 0xffff);
+    engine.play(0, 0, now);
+    assert!(engine.render(now + Duration::from_secs(11)).is_zero());
+}
+
+#[test]
+fn test_gain_and_repeat() {
+    let mut engine = FFEngine::new();
+    let data = effect(
+        FFEffectKind::Ramp {
+            start_level: 0x7fff,
+            end_level: 0x7fff,
+            envelope: no_envelope(),
+        },
+        50,
+    );
+    engine.upload(0, data);
+    engine.set_gain(0x7fff);
+
+    let now = Instant::now();
+    engine.play(0, 2, now);
+    let rumble = engine.render(now + Duration::from_millis(10));
+    assert!(rumble.strong > 0x7000 && rumble.strong < 0x9000);
+
+    // The effect should still be playing for its second repetition
+    engine.render(now + Duration::from_millis(60));
+    assert!(engine.is_playing());
+    assert!(!engine.render(now + Duration::from_millis(70)).is_zero());
+
+    engine.render(now + Duration::from_millis(110));
+    assert!(!engine.is_playing());
+}
+
+#[test]
+fn test_route_effect() {
+    let rumble = effect(
+        FFEffectKind::Rumble {
+            strong_magnitude: 0x8000,
+            weak_magnitude: 0x1000,
+        },
+        0,
+    );
+
+    // Each side should play the magnitude of its own motor
+    let left = route_effect(rumble, RumbleSide::Left);
+    assert!(matches!(
+        left.kind,
+        FFEffectKind::Rumble {
+            strong_magnitude: 0x8000,
+            weak_magnitude: 0x8000,
+        }
+    ));
+    let right = route_effect(rumble, RumbleSide::Right);
+    assert!(matches!(
+        right.kind,
+        FFEffectKind::Rumble {
+            strong_magnitude: 0x1000,
+            weak_magnitude: 0x1000,
+        }
+    ));
+    let both = route_effect(rumble, RumbleSide::Both);
+    assert!(matches!(
+        both.kind,
+        FFEffectKind::Rumble {
+            strong_magnitude: 0x8000,
+            weak_magnitude: 0x1000,
+        }
+    ));
+
+    // A right source should stop when only the strong motor is playing
+    let magnitude = RumbleMagnitude {
+        strong: 0xffff,
+        weak: 0,
+    };
+    assert!(magnitude.for_side(RumbleSide::Right).is_zero());
+}
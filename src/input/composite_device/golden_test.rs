@@ -0,0 +1,292 @@
+//! Regression tests that replay recorded source events through a composite
+//! device and compare the events written to its target devices with the
+//! golden recordings in "tests/golden".
+//!
+//! Each test case is a YAML file with the recorded events in the same format
+//! as the scripts of virtual source devices, the target devices to record,
+//! and an optional device profile and composite device options. The expected
+//! output is stored next to it in a ".golden" file. After an intended change
+//! of behavior, the golden files can be regenerated with:
+//!
+//! ```bash
+//! UPDATE_GOLDEN=1 cargo test golden
+//! ```
+//!
+//! Recorded delays are ignored. Events that are scheduled by the composite
+//! device (e.g. chords) are written after each recorded event in the order
+//! they are due, so the output does not depend on timing.
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    slice,
+    str::FromStr,
+};
+
+use serde::Deserialize;
+use tokio::{net::UnixStream, sync::mpsc};
+use zbus::{connection, Connection, Guid};
+
+use crate::{
+    config::{CompositeDeviceConfig, CompositeDeviceConfigOptions, DeviceProfile},
+    input::{
+        capability::Capability,
+        event::{native::NativeEvent, value::InputValue, Event},
+        source::r#virtual::VirtualScript,
+        target::{client::TargetDeviceClient, command::TargetCommand},
+    },
+    udev::device::UdevDevice,
+};
+
+use super::{command::CompositeCommand, inspector::format_value, CompositeDevice};
+
+/// Directory of the golden test cases relative to the repository
+const GOLDEN_PATH: &str = "tests/golden";
+/// Environment variable to regenerate the golden files from the current output
+const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+/// Size of the channel of recorded target devices. Large enough that events
+/// are never deferred to the target backlog.
+const TARGET_BUFFER_SIZE: usize = 1024;
+/// Maximum number of scheduled batches to run after each recorded event, so
+/// commands that keep scheduling themselves (e.g. key repeat) cannot hang
+const MAX_SCHEDULED_BATCHES: usize = 100;
+
+/// A recorded test case
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct GoldenCase {
+    /// Device profile to load instead of the default profile
+    profile: Option<DeviceProfile>,
+    /// Options of the composite device
+    options: Option<CompositeDeviceConfigOptions>,
+    /// Kinds of target devices (e.g. "xb360") to record, with the
+    /// capabilities each of them accepts
+    targets: BTreeMap<String, Vec<String>>,
+    /// Recorded source events
+    #[serde(flatten)]
+    script: VirtualScript,
+}
+
+/// A target device whose written events are recorded
+struct RecordedTarget {
+    kind: String,
+    rx: mpsc::Receiver<TargetCommand>,
+}
+
+/// Returns a connection to an in-process DBus peer, so composite devices can
+/// be created without a bus.
+async fn peer_connections() -> (Connection, Connection) {
+    let (server, client) = UnixStream::pair().unwrap();
+    let server = connection::Builder::unix_stream(server)
+        .server(Guid::generate())
+        .unwrap()
+        .p2p()
+        .build();
+    let client = connection::Builder::unix_stream(client).p2p().build();
+    let (server, client) = tokio::join!(server, client);
+    (server.unwrap(), client.unwrap())
+}
+
+/// Parse the given capability string. E.g. "Gamepad:Button:South"
+fn parse_capability(capability: &str) -> Result<Capability, String> {
+    Capability::from_str(capability).map_err(|_| format!("Invalid capability: {capability}"))
+}
+
+/// Returns the line written to golden files for the given events
+fn format_events(events: &[NativeEvent]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            let value = format_value(&event.get_value());
+            format!("{} ({value})", event.as_capability())
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Attach a target device of the given kind to the composite device and
+/// answer the queries made while it is attached.
+async fn attach_target(
+    device: &mut CompositeDevice,
+    path: &str,
+    kind: &str,
+    capabilities: Vec<Capability>,
+) -> Result<RecordedTarget, Box<dyn Error>> {
+    let (tx, mut rx) = mpsc::channel(TARGET_BUFFER_SIZE);
+    let targets = HashMap::from([(path.to_string(), TargetDeviceClient::new(tx))]);
+    let answer = async {
+        // The type of the target device is queried last
+        while let Some(cmd) = rx.recv().await {
+            match cmd {
+                TargetCommand::GetCapabilities(sender) => {
+                    sender.send(capabilities.clone()).await.unwrap();
+                }
+                TargetCommand::GetType(sender) => {
+                    sender.send(kind.to_string()).await.unwrap();
+                    break;
+                }
+                _ => (),
+            }
+        }
+    };
+    let (result, _) = tokio::join!(device.attach_target_devices(targets), answer);
+    result?;
+
+    Ok(RecordedTarget {
+        kind: kind.to_string(),
+        rx,
+    })
+}
+
+/// Append the events written to the given target devices to the output
+fn record_targets(targets: &mut [RecordedTarget], output: &mut Vec<String>) {
+    for target in targets.iter_mut() {
+        while let Ok(cmd) = target.rx.try_recv() {
+            let events = match cmd {
+                TargetCommand::WriteEvent(event) => vec![event],
+                TargetCommand::WriteEvents(events) => events,
+                _ => continue,
+            };
+            output.push(format!("{}: {}", target.kind, format_events(&events)));
+        }
+    }
+}
+
+/// Run the scheduled commands of the composite device in the order they are
+/// due, writing each batch as its own input frame.
+async fn run_scheduled(
+    device: &mut CompositeDevice,
+    targets: &mut [RecordedTarget],
+    output: &mut Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    for _ in 0..MAX_SCHEDULED_BATCHES {
+        let Some(deadline) = device.scheduler.next_deadline() else {
+            return Ok(());
+        };
+        for cmd in device.scheduler.poll(deadline) {
+            match cmd {
+                CompositeCommand::WriteEvent(event) => device.write_event(event).await?,
+                CompositeCommand::RemoveRecentEvent(cap) => {
+                    device.translated_recent_events.remove(&cap);
+                }
+                cmd => log::trace!("Skipping scheduled command: {cmd:?}"),
+            }
+        }
+        device.flush_target_frames().await;
+        record_targets(targets, output);
+    }
+    Ok(())
+}
+
+/// Replay the recorded events of the given test case through a composite
+/// device and return the golden output.
+async fn run_case(path: &Path) -> Result<String, Box<dyn Error>> {
+    let case: GoldenCase = serde_yaml::from_str(fs::read_to_string(path)?.as_str())?;
+
+    // Keep the peer connection open until the case is done
+    let (_peer, conn) = peer_connections().await;
+    let (manager, _manager_rx) = mpsc::channel(1);
+    let config = CompositeDeviceConfig {
+        version: 1,
+        kind: "CompositeDevice".to_string(),
+        name: "Golden".to_string(),
+        matches: Vec::new(),
+        single_source: None,
+        maximum_sources: None,
+        capability_map_id: None,
+        source_devices: Vec::new(),
+        target_devices: None,
+        options: case.options,
+        variables: None,
+    };
+    let script = path.to_string_lossy().to_string();
+    let device_info = UdevDevice::new_virtual("golden", script.as_str());
+    let dbus_path = "/org/shadowblip/InputPlumber/CompositeDeviceGolden".to_string();
+    let mut device = CompositeDevice::new(conn, manager, config, device_info, dbus_path, None)?;
+    if let Some(profile) = case.profile {
+        device.load_device_profile(profile)?;
+    }
+
+    let mut targets = Vec::new();
+    for (i, (kind, capabilities)) in case.targets.into_iter().enumerate() {
+        let capabilities = capabilities
+            .iter()
+            .map(|cap| parse_capability(cap.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let path = format!("/org/shadowblip/InputPlumber/devices/target/golden{i}");
+        let target = attach_target(&mut device, path.as_str(), kind.as_str(), capabilities);
+        targets.push(target.await?);
+    }
+
+    let source_id = device
+        .get_source_devices_used()
+        .first()
+        .cloned()
+        .unwrap_or_default();
+    let mut output = Vec::new();
+    for recorded in case.script.events.unwrap_or_default() {
+        let cap = parse_capability(recorded.capability.as_str())?;
+        let value = InputValue::try_from(recorded.value)?;
+        let event = NativeEvent::new(cap, value);
+        output.push(format!("> {}", format_events(slice::from_ref(&event))));
+
+        device
+            .process_event(source_id.clone(), Event::Native(event))
+            .await?;
+        device.flush_target_frames().await;
+        record_targets(targets.as_mut_slice(), &mut output);
+        run_scheduled(&mut device, targets.as_mut_slice(), &mut output).await?;
+    }
+
+    let mut output = output.join("\n");
+    output.push('\n');
+    Ok(output)
+}
+
+/// Returns the paths to all golden test cases
+fn golden_cases(dir: &Path) -> Vec<PathBuf> {
+    let mut cases: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .collect();
+    cases.sort();
+    cases
+}
+
+#[tokio::test]
+async fn test_golden_recordings() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    // Load the default profile from the repository instead of the system
+    env::set_var("XDG_DATA_DIRS", root.join("rootfs/usr/share"));
+
+    let update = env::var_os(UPDATE_ENV).is_some();
+    let mut failures = Vec::new();
+    for case in golden_cases(root.join(GOLDEN_PATH).as_path()) {
+        let output = match run_case(case.as_path()).await {
+            Ok(output) => output,
+            Err(e) => panic!("Failed to run golden test case {case:?}: {e:?}"),
+        };
+        let golden_path = case.with_extension("golden");
+        if update {
+            fs::write(golden_path, output).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(golden_path.as_path()).unwrap_or_default();
+        if output != expected {
+            failures.push(format!(
+                "{golden_path:?}\n--- expected\n{expected}+++ actual\n{output}"
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Target output does not match the golden recordings. If the change is intended, run the tests with {UPDATE_ENV}=1 to update them.\n{}",
+        failures.join("\n")
+    );
+}
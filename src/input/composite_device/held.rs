@@ -0,0 +1,119 @@
+use crate::input::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Inputs that are currently held away from their resting state (e.g. pressed
+/// buttons, pulled triggers or deflected sticks). Used to release everything
+/// that is held on target devices and to replay the held source inputs when
+/// the device profile changes while inputs are held.
+#[derive(Debug, Default)]
+pub struct HeldInputs {
+    /// Latest event of each held input, in the order they were first held
+    events: Vec<NativeEvent>,
+}
+
+impl HeldInputs {
+    /// Returns true if no inputs are held
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Update the held state with the given event. Inputs without a resting
+    /// state, like relative mouse motion or motion sensors, are ignored.
+    pub fn update(&mut self, event: &NativeEvent) {
+        let value = event.get_value();
+        if rest_value(&event.as_capability(), &value).is_none() {
+            return;
+        }
+        let mut event = event.clone();
+        if let Some(i) = self.events.iter().position(|held| same_input(held, &event)) {
+            let held = self.events.remove(i);
+            event.set_value(merge_values(held.get_value(), value));
+        }
+        if !is_at_rest(&event.get_value()) {
+            self.events.push(event);
+        }
+    }
+
+    /// Returns the latest event of each held input
+    pub fn events(&self) -> Vec<NativeEvent> {
+        self.events.clone()
+    }
+
+    /// Returns events that return each held input to its resting state
+    pub fn release_events(&self) -> Vec<NativeEvent> {
+        self.events
+            .iter()
+            .filter_map(|held| {
+                let value = rest_value(&held.as_capability(), &held.get_value())?;
+                let mut event = held.clone();
+                event.set_value(value);
+                Some(event)
+            })
+            .collect()
+    }
+}
+
+/// Returns the resting value of the given input, or None if the input has no
+/// resting state that could be held.
+fn rest_value(cap: &Capability, value: &InputValue) -> Option<InputValue> {
+    if matches!(cap, Capability::Mouse(Mouse::Motion)) {
+        return None;
+    }
+    match value {
+        InputValue::Bool(_) => Some(InputValue::Bool(false)),
+        InputValue::Float(_) => Some(InputValue::Float(0.0)),
+        InputValue::Vector2 { .. } => Some(InputValue::Vector2 {
+            x: Some(0.0),
+            y: Some(0.0),
+        }),
+        InputValue::Touch { index, x, y, .. } => Some(InputValue::Touch {
+            index: *index,
+            is_touching: false,
+            pressure: None,
+            x: *x,
+            y: *y,
+        }),
+        InputValue::None | InputValue::Vector3 { .. } => None,
+    }
+}
+
+/// Returns true if the given value is the resting state of its input
+fn is_at_rest(value: &InputValue) -> bool {
+    match value {
+        InputValue::Bool(pressed) => !pressed,
+        InputValue::Float(value) => *value == 0.0,
+        InputValue::Vector2 { x, y } => {
+            x.unwrap_or_default() == 0.0 && y.unwrap_or_default() == 0.0
+        }
+        InputValue::Touch { is_touching, .. } => !is_touching,
+        InputValue::None | InputValue::Vector3 { .. } => true,
+    }
+}
+
+/// Returns true if both events are for the same input. Each finger of a touch
+/// input is held separately.
+fn same_input(a: &NativeEvent, b: &NativeEvent) -> bool {
+    if a.as_capability() != b.as_capability() {
+        return false;
+    }
+    match (a.get_value(), b.get_value()) {
+        (InputValue::Touch { index: a, .. }, InputValue::Touch { index: b, .. }) => a == b,
+        _ => true,
+    }
+}
+
+/// Returns the new value of an input, keeping the axis components that the
+/// new value does not update.
+fn merge_values(old: InputValue, new: InputValue) -> InputValue {
+    match (old, new) {
+        (InputValue::Vector2 { x: old_x, y: old_y }, InputValue::Vector2 { x, y }) => {
+            InputValue::Vector2 {
+                x: x.or(old_x),
+                y: y.or(old_y),
+            }
+        }
+        (_, new) => new,
+    }
+}
@@ -0,0 +1,66 @@
+use crate::input::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::held::HeldInputs;
+use super::test_events::{button, stick};
+
+#[test]
+fn test_held_buttons() {
+    let mut held = HeldInputs::default();
+    held.update(&button(true));
+    assert_eq!(held.events().len(), 1);
+
+    let releases = held.release_events();
+    assert_eq!(releases.len(), 1);
+    assert!(matches!(releases[0].get_value(), InputValue::Bool(false)));
+
+    held.update(&button(false));
+    assert!(held.is_empty());
+}
+
+#[test]
+fn test_held_stick() {
+    let mut held = HeldInputs::default();
+    held.update(&stick(Some(0.5), None));
+    held.update(&stick(None, Some(-0.5)));
+    let events = held.events();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].get_value(),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y)
+        } if x == 0.5 && y == -0.5
+    ));
+
+    let releases = held.release_events();
+    assert!(matches!(
+        releases[0].get_value(),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y)
+        } if x == 0.0 && y == 0.0
+    ));
+
+    // The stick is only at rest once both axes are centered
+    held.update(&stick(Some(0.0), None));
+    assert!(!held.is_empty());
+    held.update(&stick(None, Some(0.0)));
+    assert!(held.is_empty());
+}
+
+#[test]
+fn test_held_ignores_relative_motion() {
+    let mut held = HeldInputs::default();
+    let motion = NativeEvent::new(
+        Capability::Mouse(Mouse::Motion),
+        InputValue::Vector2 {
+            x: Some(4.0),
+            y: Some(2.0),
+        },
+    );
+    held.update(&motion);
+    assert!(held.is_empty());
+}
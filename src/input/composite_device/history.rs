@@ -0,0 +1,130 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::input::{capability::Capability, event::value::InputValue};
+
+/// Default number of events kept in the event history of a composite device
+pub const DEFAULT_EVENT_HISTORY_SIZE: usize = 256;
+
+/// A single input event received from a source device
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    pub time: SystemTime,
+    pub source: Arc<str>,
+    pub capability: Capability,
+    pub value: InputValue,
+    /// App id of the focused game when the event was received, if known
+    pub app_id: Option<u32>,
+}
+
+impl fmt::Display for EventRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "{}.{:06} {} {:?}: {:?}",
+            time.as_secs(),
+            time.subsec_micros(),
+            self.source,
+            self.capability,
+            self.value
+        )?;
+        if let Some(app_id) = self.app_id {
+            write!(f, " [app {app_id}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Ring buffer of the most recent input events received by a composite
+/// device. The history can be dumped on demand to debug input issues after
+/// they happen, without needing trace logging enabled.
+#[derive(Debug)]
+pub struct EventHistory {
+    events: VecDeque<EventRecord>,
+    capacity: usize,
+    /// App id of the focused game that new events are tagged with
+    app_id: Option<u32>,
+    /// Source device ids shared by the recorded events, so recording an event
+    /// does not allocate
+    sources: Vec<Arc<str>>,
+    /// Wall clock and monotonic time the history was created, used to get
+    /// the wall clock time of events from their monotonic time
+    created: (SystemTime, Instant),
+}
+
+impl Default for EventHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_HISTORY_SIZE)
+    }
+}
+
+impl EventHistory {
+    /// Create a new event history that keeps up to the given number of events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            app_id: None,
+            sources: Vec::new(),
+            created: (SystemTime::now(), Instant::now()),
+        }
+    }
+
+    /// Set the app id of the focused game that new events are tagged with
+    pub fn set_app_id(&mut self, app_id: Option<u32>) {
+        self.app_id = app_id;
+    }
+
+    /// Record the given event that was received at the given time, dropping
+    /// the oldest event if the history is full
+    pub fn push(&mut self, source: &str, capability: Capability, value: InputValue, now: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+        let source = self.intern_source(source);
+        let (created_time, created_instant) = self.created;
+        self.events.push_back(EventRecord {
+            time: created_time + now.saturating_duration_since(created_instant),
+            source,
+            capability,
+            value,
+            app_id: self.app_id,
+        });
+    }
+
+    /// Returns the shared id of the given source device. Ids that are no
+    /// longer used by any recorded event are dropped when a new source is
+    /// added.
+    fn intern_source(&mut self, source: &str) -> Arc<str> {
+        if let Some(id) = self.sources.iter().find(|id| id.as_ref() == source) {
+            return id.clone();
+        }
+        self.sources.retain(|id| Arc::strong_count(id) > 1);
+        let id: Arc<str> = Arc::from(source);
+        self.sources.push(id.clone());
+        id
+    }
+
+    /// Returns the recorded events from oldest to newest
+    pub fn events(&self) -> impl Iterator<Item = &EventRecord> {
+        self.events.iter()
+    }
+
+    /// Returns the number of recorded events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if no events have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Instant;
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    event::value::InputValue,
+};
+
+use super::history::EventHistory;
+
+#[test]
+fn test_event_history_drops_oldest() {
+    let mut history = EventHistory::new(3);
+    assert!(history.is_empty());
+
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    for i in 0..5 {
+        let value = InputValue::Float(i as f64);
+        history.push("evdev://event0", cap.clone(), value, Instant::now());
+    }
+    assert_eq!(history.len(), 3);
+
+    // Only the most recent events should be kept, from oldest to newest
+    let values: Vec<String> = history.events().map(|e| format!("{:?}", e.value)).collect();
+    assert_eq!(values, vec!["Float(2.0)", "Float(3.0)", "Float(4.0)"]);
+
+    let line = history.events().last().unwrap().to_string();
+    assert!(line.contains("evdev://event0 Gamepad(Button(South)): Float(4.0)"));
+
+    // Events are tagged with the focused app
+    history.set_app_id(Some(1091500));
+    let value = InputValue::Float(5.0);
+    history.push("evdev://event0", cap, value, Instant::now());
+    let line = history.events().last().unwrap().to_string();
+    assert!(line.ends_with("Float(5.0) [app 1091500]"));
+}
@@ -0,0 +1,120 @@
+use tokio::sync::mpsc::{self, error::TrySendError};
+use zbus::{object_server::SignalContext, Connection};
+
+use crate::{
+    dbus::interface::{capability_strings, composite_device::CompositeDeviceInterface},
+    input::event::{native::NativeEvent, value::InputValue},
+};
+
+/// Maximum number of inspected events waiting to be emitted. Events are
+/// dropped if the DBus connection cannot keep up.
+const BUFFER_SIZE: usize = 1024;
+
+/// A decoded input event that is emitted by the [InputInspector]
+#[derive(Debug, Clone)]
+pub struct InspectedEvent {
+    /// Id of the source device the event originated from, if known
+    pub source: String,
+    /// Capability string of the event. E.g. "Gamepad:Button:South"
+    pub capability: String,
+    /// Human-readable value of the event. E.g. "x=0.500 y=-0.250"
+    pub value: String,
+}
+
+impl InspectedEvent {
+    pub fn new(source: &str, event: &NativeEvent) -> Self {
+        let capability = capability_strings(vec![event.as_capability()])
+            .pop()
+            .unwrap_or_default();
+        Self {
+            source: source.to_string(),
+            capability,
+            value: format_value(&event.get_value()),
+        }
+    }
+}
+
+/// Streams decoded input events that are written to target devices as DBus
+/// signals, so tools can show live input after it has been translated by
+/// capability maps and device profiles.
+#[derive(Debug)]
+pub struct InputInspector {
+    tx: mpsc::Sender<InspectedEvent>,
+}
+
+impl InputInspector {
+    /// Start emitting inspected events as signals on the composite device
+    /// DBus interface at the given path. Signals stop being emitted when the
+    /// inspector is dropped.
+    pub fn new(conn: Connection, path: String) -> Self {
+        let (tx, mut rx) = mpsc::channel::<InspectedEvent>(BUFFER_SIZE);
+        tokio::task::spawn(async move {
+            let ctxt = match SignalContext::new(&conn, path.as_str()) {
+                Ok(ctxt) => ctxt,
+                Err(e) => {
+                    log::error!("Failed to create signal context for input inspector: {e:?}");
+                    return;
+                }
+            };
+            while let Some(event) = rx.recv().await {
+                let result = CompositeDeviceInterface::input_event(
+                    &ctxt,
+                    event.source.as_str(),
+                    event.capability.as_str(),
+                    event.value.as_str(),
+                )
+                .await;
+                if let Err(e) = result {
+                    log::debug!("Failed to emit inspected input event: {e:?}");
+                }
+            }
+            log::debug!("Input inspector stopped for {path}");
+        });
+
+        Self { tx }
+    }
+
+    /// Emit the given event from the given source device
+    pub fn inspect(&self, source: &str, event: &NativeEvent) {
+        match self.tx.try_send(InspectedEvent::new(source, event)) {
+            Ok(_) => (),
+            Err(TrySendError::Full(_)) => log::trace!("Input inspector is full, dropping event"),
+            Err(TrySendError::Closed(_)) => log::debug!("Input inspector is closed"),
+        }
+    }
+}
+
+/// Returns a human-readable representation of the given input value
+pub fn format_value(value: &InputValue) -> String {
+    let axis = |name: &str, value: &Option<f64>| match value {
+        Some(value) => format!("{name}={value:.3}"),
+        None => format!("{name}=-"),
+    };
+    match value {
+        InputValue::None => "none".to_string(),
+        InputValue::Bool(pressed) => {
+            if *pressed {
+                "pressed".to_string()
+            } else {
+                "released".to_string()
+            }
+        }
+        InputValue::Float(value) => format!("{value:.3}"),
+        InputValue::Vector2 { x, y } => format!("{} {}", axis("x", x), axis("y", y)),
+        InputValue::Vector3 { x, y, z } => {
+            format!("{} {} {}", axis("x", x), axis("y", y), axis("z", z))
+        }
+        InputValue::Touch {
+            index,
+            is_touching,
+            pressure,
+            x,
+            y,
+        } => format!(
+            "index={index} touching={is_touching} {} {} {}",
+            axis("x", x),
+            axis("y", y),
+            axis("pressure", pressure)
+        ),
+    }
+}
@@ -0,0 +1,35 @@
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::inspector::{format_value, InspectedEvent};
+
+#[test]
+fn test_format_value() {
+    assert_eq!(format_value(&InputValue::Bool(true)), "pressed");
+    assert_eq!(format_value(&InputValue::Bool(false)), "released");
+    assert_eq!(format_value(&InputValue::Float(0.5)), "0.500");
+    assert_eq!(
+        format_value(&InputValue::Vector2 {
+            x: Some(-0.25),
+            y: None
+        }),
+        "x=-0.250 y=-"
+    );
+}
+
+#[test]
+fn test_inspected_event() {
+    let event = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 {
+            x: Some(1.0),
+            y: Some(0.0),
+        },
+    );
+    let inspected = InspectedEvent::new("evdev://event3", &event);
+    assert_eq!(inspected.source, "evdev://event3");
+    assert_eq!(inspected.capability, "Gamepad:Axis:LeftStick");
+    assert_eq!(inspected.value, "x=1.000 y=0.000");
+}
@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use crate::{
+    config::SourceInputConfig,
+    input::{
+        capability::Capability,
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Corrects source inputs that report inverted logic, like normally-closed
+/// switches or hall effect triggers that report 1.0 at rest.
+#[derive(Debug, Default)]
+pub struct InputInverter {
+    inverted: HashSet<Capability>,
+}
+
+impl InputInverter {
+    /// Create a new inverter from the given source input configs
+    pub fn new(configs: &[SourceInputConfig]) -> Self {
+        let inverted = configs
+            .iter()
+            .filter(|config| config.inverted.unwrap_or_default())
+            .map(|config| config.capability.clone().into())
+            .filter(|cap| *cap != Capability::NotImplemented)
+            .collect();
+        Self { inverted }
+    }
+
+    /// Returns the given source event with its value inverted if the input
+    /// reports inverted logic. Button presses and releases are swapped and
+    /// analog values are reversed.
+    pub fn process(&self, mut event: NativeEvent) -> NativeEvent {
+        if !self.inverted.contains(&event.as_capability()) {
+            return event;
+        }
+        let value = match event.get_value() {
+            InputValue::Bool(pressed) => InputValue::Bool(!pressed),
+            InputValue::Float(value) => InputValue::Float(1.0 - value),
+            value => {
+                log::trace!("Unable to invert value: {value:?}");
+                value
+            }
+        };
+        event.set_value(value);
+        event
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    config::SourceInputConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::invert::InputInverter;
+
+#[test]
+fn test_invert_inputs() {
+    let configs: Vec<SourceInputConfig> = serde_yaml::from_str(
+        r#"
+- capability:
+    gamepad:
+      button: South
+  inverted: true
+- capability:
+    gamepad:
+      trigger:
+        name: LeftTrigger
+  inverted: true
+- capability:
+    gamepad:
+      button: East
+  inverted: false
+"#,
+    )
+    .unwrap();
+    let inverter = InputInverter::new(configs.as_slice());
+
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let event = inverter.process(NativeEvent::new(south, InputValue::Bool(true)));
+    assert!(matches!(event.get_value(), InputValue::Bool(false)));
+
+    let trigger = Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger));
+    let event = inverter.process(NativeEvent::new(trigger, InputValue::Float(0.25)));
+    assert!(matches!(event.get_value(), InputValue::Float(value) if value == 0.75));
+
+    // Inputs that are not inverted are passed through
+    let east = Capability::Gamepad(Gamepad::Button(GamepadButton::East));
+    let event = inverter.process(NativeEvent::new(east, InputValue::Bool(true)));
+    assert!(matches!(event.get_value(), InputValue::Bool(true)));
+}
@@ -0,0 +1,51 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    config::OutputRemap,
+    input::{
+        capability::{Capability, Keyboard},
+        event::native::NativeEvent,
+    },
+};
+
+/// Table of keys to replace before keyboard events are written to a target
+/// device, e.g. to emit the key positions of a non-QWERTY layout for games
+/// that assume positional keys.
+#[derive(Debug, Clone, Default)]
+pub struct KeyRemapTable {
+    keys: HashMap<Keyboard, Keyboard>,
+}
+
+impl From<&OutputRemap> for KeyRemapTable {
+    fn from(config: &OutputRemap) -> Self {
+        let mut keys = HashMap::with_capacity(config.keys.len());
+        for remap in config.keys.iter() {
+            let (Ok(from), Ok(to)) = (
+                Keyboard::from_str(remap.from.as_str()),
+                Keyboard::from_str(remap.to.as_str()),
+            ) else {
+                log::warn!("Invalid key remap from {} to {}", remap.from, remap.to);
+                continue;
+            };
+            keys.insert(from, to);
+        }
+        Self { keys }
+    }
+}
+
+impl KeyRemapTable {
+    /// Returns the given event with its key replaced if it is in the table
+    pub fn remap(&self, event: NativeEvent) -> NativeEvent {
+        let Capability::Keyboard(key) = event.as_capability() else {
+            return event;
+        };
+        let Some(to) = self.keys.get(&key) else {
+            return event;
+        };
+        let mut remapped = NativeEvent::new(Capability::Keyboard(to.clone()), event.get_value());
+        if let Some(source) = event.get_source_capability() {
+            remapped.set_source_capability(source);
+        }
+        remapped
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    config::OutputRemap,
+    input::{
+        capability::{Capability, Keyboard},
+        event::value::InputValue,
+    },
+};
+
+use super::{key_remap::KeyRemapTable, test_events::key_event};
+
+const CONFIG: &str = r#"
+target: keyboard
+keys:
+  - from: KeyW
+    to: KeyZ
+  - from: KeyZ
+    to: KeyW
+  - from: KeyInvalid
+    to: KeyA
+"#;
+
+#[test]
+fn test_key_remap() {
+    let config: OutputRemap = serde_yaml::from_str(CONFIG).unwrap();
+    let table = KeyRemapTable::from(&config);
+
+    let event = table.remap(key_event(Keyboard::KeyW, true));
+    assert_eq!(event.as_capability(), Capability::Keyboard(Keyboard::KeyZ));
+    assert!(matches!(event.get_value(), InputValue::Bool(true)));
+    let event = table.remap(key_event(Keyboard::KeyZ, false));
+    assert_eq!(event.as_capability(), Capability::Keyboard(Keyboard::KeyW));
+    assert!(matches!(event.get_value(), InputValue::Bool(false)));
+
+    // Keys without a remap should be passed through
+    let event = table.remap(key_event(Keyboard::KeyA, true));
+    assert_eq!(event.as_capability(), Capability::Keyboard(Keyboard::KeyA));
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::{LayerConfig, ProfileMapping},
+    input::{
+        capability::Capability,
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// A single layer of profile mappings
+#[derive(Debug, Clone)]
+struct Layer {
+    name: String,
+    /// Source capabilities that activate the layer while held
+    momentary: Vec<Capability>,
+    /// Source capabilities that turn the layer on or off when pressed
+    toggle: Vec<Capability>,
+    /// Mappings of the layer by source capability
+    mappings: HashMap<Capability, Vec<ProfileMapping>>,
+}
+
+/// Keeps track of the active profile layers and resolves which layer should
+/// translate a source event, like the layers of programmable keyboards (e.g.
+/// QMK). Layers are activated momentarily while a switch is held, or toggled
+/// on and off. The most recently activated layer takes precedence, and source
+/// events without a mapping in any active layer fall through to the base
+/// profile. Buttons are released on the same layer they were pressed on, so
+/// switching layers never leaves keys stuck.
+#[derive(Debug, Default)]
+pub struct LayerStack {
+    /// All layers defined in the profile
+    layers: Vec<Layer>,
+    /// Indices of the active layers, from lowest to highest precedence
+    active: Vec<usize>,
+    /// Momentary switches that are currently held and their layer
+    held: HashMap<Capability, usize>,
+    /// Layer each currently pressed button was translated with. [None] if it
+    /// was translated by the base profile.
+    pressed: HashMap<Capability, Option<usize>>,
+}
+
+impl LayerStack {
+    /// Create a new layer stack from the given layer configs
+    pub fn new(configs: &[LayerConfig]) -> Self {
+        let layers = configs
+            .iter()
+            .map(|config| {
+                let switches = |switches: Option<&Vec<_>>| -> Vec<Capability> {
+                    switches
+                        .map(|switches| switches.iter().cloned().map(Capability::from).collect())
+                        .unwrap_or_default()
+                };
+                let mut mappings: HashMap<Capability, Vec<ProfileMapping>> = HashMap::new();
+                for mapping in config.mapping.iter() {
+                    let cap = mapping.source_event.clone().into();
+                    mappings.entry(cap).or_default().push(mapping.clone());
+                }
+                Layer {
+                    name: config.name.clone(),
+                    momentary: switches(config.momentary.as_ref()),
+                    toggle: switches(config.toggle.as_ref()),
+                    mappings,
+                }
+            })
+            .collect();
+
+        Self {
+            layers,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if no layers are defined
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns the names of the active layers, from lowest to highest
+    /// precedence
+    pub fn active_layers(&self) -> Vec<String> {
+        self.active
+            .iter()
+            .map(|index| self.layers[*index].name.clone())
+            .collect()
+    }
+
+    /// Returns all mappings defined in every layer
+    pub fn mappings(&self) -> impl Iterator<Item = &ProfileMapping> {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.mappings.values().flatten())
+    }
+
+    /// Process the given source event if it is a layer switch. Returns true if
+    /// the event switched layers and should not be translated any further.
+    pub fn process_switch(&mut self, event: &NativeEvent) -> bool {
+        let cap = event.as_capability();
+        let pressed = event.pressed();
+
+        // Momentary switches are released even if the layer was deactivated
+        if !pressed {
+            if let Some(index) = self.held.remove(&cap) {
+                if !self.held.values().any(|held| *held == index) {
+                    self.deactivate(index);
+                }
+                return true;
+            }
+        }
+
+        let momentary = self
+            .layers
+            .iter()
+            .position(|layer| layer.momentary.contains(&cap));
+        if let Some(index) = momentary {
+            if pressed {
+                self.held.insert(cap, index);
+                self.activate(index);
+            }
+            return true;
+        }
+
+        let toggle = self
+            .layers
+            .iter()
+            .position(|layer| layer.toggle.contains(&cap));
+        if let Some(index) = toggle {
+            if pressed {
+                if self.active.contains(&index) {
+                    self.deactivate(index);
+                } else {
+                    self.activate(index);
+                }
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the index of the layer that should translate the given source
+    /// event, or [None] if it should be translated by the base profile.
+    pub fn resolve(&mut self, event: &NativeEvent) -> Option<usize> {
+        let cap = event.as_capability();
+        match event.get_value() {
+            InputValue::Bool(true) => {
+                let layer = self.top_layer_for(&cap);
+                self.pressed.insert(cap, layer);
+                layer
+            }
+            InputValue::Bool(false) => match self.pressed.remove(&cap) {
+                Some(layer) => layer,
+                None => self.top_layer_for(&cap),
+            },
+            _ => self.top_layer_for(&cap),
+        }
+    }
+
+    /// Returns the mappings of the layer with the given index for the given
+    /// source capability
+    pub fn layer_mappings(&self, index: usize, cap: &Capability) -> Option<&Vec<ProfileMapping>> {
+        self.layers.get(index)?.mappings.get(cap)
+    }
+
+    /// Returns the index of the active layer with the highest precedence that
+    /// has a mapping for the given capability
+    fn top_layer_for(&self, cap: &Capability) -> Option<usize> {
+        self.active
+            .iter()
+            .rev()
+            .find(|index| self.layers[**index].mappings.contains_key(cap))
+            .copied()
+    }
+
+    /// Activate the layer with the given index, giving it the highest
+    /// precedence
+    fn activate(&mut self, index: usize) {
+        self.active.retain(|active| *active != index);
+        self.active.push(index);
+        log::debug!("Active profile layers: {:?}", self.active_layers());
+    }
+
+    /// Deactivate the layer with the given index
+    fn deactivate(&mut self, index: usize) {
+        self.active.retain(|active| *active != index);
+        log::debug!("Active profile layers: {:?}", self.active_layers());
+    }
+}
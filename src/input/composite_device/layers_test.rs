@@ -0,0 +1,62 @@
+use crate::{config::LayerConfig, input::capability::Keyboard};
+
+use super::{layers::LayerStack, test_events::key_event};
+
+const LAYERS: &str = r#"
+- name: fn
+  momentary:
+    - keyboard: KeyLeftCtrl
+  mapping:
+    - name: 1 to F1
+      source_event:
+        keyboard: Key1
+      target_events:
+        - keyboard: KeyF1
+- name: numpad
+  toggle:
+    - keyboard: KeyScrollLock
+  mapping:
+    - name: 1 to KP1
+      source_event:
+        keyboard: Key1
+      target_events:
+        - keyboard: KeyKp1
+"#;
+
+#[test]
+fn test_layer_stack() {
+    let configs: Vec<LayerConfig> = serde_yaml::from_str(LAYERS).unwrap();
+    let mut layers = LayerStack::new(configs.as_slice());
+
+    // Keys should be translated by the base profile without active layers
+    assert!(!layers.process_switch(&key_event(Keyboard::Key1, true)));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, true)), None);
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, false)), None);
+
+    // Momentary layers should only be active while the switch is held
+    assert!(layers.process_switch(&key_event(Keyboard::KeyLeftCtrl, true)));
+    assert_eq!(layers.active_layers(), vec!["fn".to_string()]);
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, true)), Some(0));
+
+    // Keys should be released on the layer they were pressed on
+    assert!(layers.process_switch(&key_event(Keyboard::KeyLeftCtrl, false)));
+    assert!(layers.active_layers().is_empty());
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, false)), Some(0));
+
+    // Toggled layers should stay active until toggled again, and the most
+    // recently activated layer should take precedence.
+    assert!(layers.process_switch(&key_event(Keyboard::KeyScrollLock, true)));
+    assert!(layers.process_switch(&key_event(Keyboard::KeyScrollLock, false)));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, true)), Some(1));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, false)), Some(1));
+    layers.process_switch(&key_event(Keyboard::KeyLeftCtrl, true));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, true)), Some(0));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key1, false)), Some(0));
+    layers.process_switch(&key_event(Keyboard::KeyLeftCtrl, false));
+    layers.process_switch(&key_event(Keyboard::KeyScrollLock, true));
+    assert!(layers.active_layers().is_empty());
+
+    // Keys without a mapping in the active layers should fall through
+    layers.process_switch(&key_event(Keyboard::KeyLeftCtrl, true));
+    assert_eq!(layers.resolve(&key_event(Keyboard::Key2, true)), None);
+}
@@ -0,0 +1,83 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::input::{
+    capability::{Capability, Gamepad},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Minimum time between two signals that the live values changed
+pub const LIVE_VALUES_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Latest normalized values of the sticks and triggers of the source devices,
+/// so settings UIs can draw calibration widgets without subscribing to every
+/// input event. Changes are signaled at most once every
+/// [LIVE_VALUES_INTERVAL].
+#[derive(Debug, Default)]
+pub struct LiveValues {
+    /// Values keyed by capability, e.g. "Gamepad:Axis:LeftStick" => [x, y]
+    values: HashMap<String, Vec<f64>>,
+    /// Time the last change was signaled
+    last_signal: Option<Instant>,
+    /// Whether a signal is already waiting to be sent
+    signal_pending: bool,
+}
+
+impl LiveValues {
+    /// Returns the current values keyed by capability. Sticks have an X and
+    /// Y value from -1.0 to 1.0, while triggers have a single value from 0.0
+    /// to 1.0.
+    pub fn values(&self) -> HashMap<String, Vec<f64>> {
+        self.values.clone()
+    }
+
+    /// Update the live values with the given source event. Returns the time
+    /// to wait before signaling the change if a new signal should be
+    /// scheduled.
+    pub fn update(&mut self, event: &NativeEvent, now: Instant) -> Option<Duration> {
+        let cap = event.as_capability();
+        if !matches!(
+            cap,
+            Capability::Gamepad(Gamepad::Axis(_) | Gamepad::Trigger(_))
+        ) {
+            return None;
+        }
+
+        let changed = match event.get_value() {
+            InputValue::Vector2 { x, y } => {
+                let entry = self.values.entry(cap.to_string()).or_insert(vec![0.0; 2]);
+                let old = entry.clone();
+                if let Some(x) = x {
+                    entry[0] = x;
+                }
+                if let Some(y) = y {
+                    entry[1] = y;
+                }
+                *entry != old
+            }
+            InputValue::Float(value) => {
+                let old = self.values.insert(cap.to_string(), vec![value]);
+                old != Some(vec![value])
+            }
+            _ => false,
+        };
+        if !changed || self.signal_pending {
+            return None;
+        }
+
+        self.signal_pending = true;
+        let delay = self
+            .last_signal
+            .map(|last| (last + LIVE_VALUES_INTERVAL).saturating_duration_since(now))
+            .unwrap_or_default();
+        Some(delay)
+    }
+
+    /// Mark the change as signaled at the given time
+    pub fn signaled(&mut self, now: Instant) {
+        self.last_signal = Some(now);
+        self.signal_pending = false;
+    }
+}
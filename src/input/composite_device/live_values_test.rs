@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton, GamepadTrigger},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::live_values::{LiveValues, LIVE_VALUES_INTERVAL};
+use super::test_events::stick;
+
+#[test]
+fn test_live_values() {
+    let mut live = LiveValues::default();
+    let now = Instant::now();
+
+    // The first change should be signaled right away
+    assert_eq!(
+        live.update(&stick(Some(0.5), None), now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(live.update(&stick(None, Some(-0.25)), now), None);
+    let trigger = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        InputValue::Float(0.75),
+    );
+    assert_eq!(live.update(&trigger, now), None);
+
+    let values = live.values();
+    assert_eq!(values["Gamepad:Axis:LeftStick"], vec![0.5, -0.25]);
+    assert_eq!(values["Gamepad:Trigger:RightTrigger"], vec![0.75]);
+
+    // Buttons and unchanged values should be ignored
+    let button = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+        InputValue::Bool(true),
+    );
+    live.signaled(now);
+    assert_eq!(live.update(&button, now), None);
+    assert_eq!(live.update(&stick(Some(0.5), None), now), None);
+
+    // Changes should be signaled at most once every interval
+    let later = now + Duration::from_millis(20);
+    let delay = live.update(&stick(Some(1.0), None), later);
+    assert_eq!(
+        delay,
+        Some(LIVE_VALUES_INTERVAL - Duration::from_millis(20))
+    );
+}
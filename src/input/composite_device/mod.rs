@@ -1,53 +1,308 @@
+pub mod action;
+#[cfg(test)]
+pub mod action_test;
+pub mod activator;
+#[cfg(test)]
+pub mod activator_test;
+pub mod audio_haptics;
+#[cfg(test)]
+pub mod audio_haptics_test;
+pub mod axis_button;
+#[cfg(test)]
+pub mod axis_button_test;
+pub mod axis_transform;
+#[cfg(test)]
+pub mod axis_transform_test;
+pub mod battery_led;
+#[cfg(test)]
+pub mod battery_led_test;
+pub mod capture;
+#[cfg(test)]
+pub mod capture_test;
 pub mod client;
 pub mod command;
+pub mod debounce;
+#[cfg(test)]
+pub mod debounce_test;
+pub mod detachable;
+#[cfg(test)]
+pub mod detachable_test;
+pub mod dpad_repeat;
+#[cfg(test)]
+pub mod dpad_repeat_test;
+pub mod dpad_stick;
+#[cfg(test)]
+pub mod dpad_stick_test;
+pub mod drift;
+#[cfg(test)]
+pub mod drift_test;
+pub mod dry_run;
+#[cfg(test)]
+pub mod dry_run_test;
+pub mod dwell_click;
+#[cfg(test)]
+pub mod dwell_click_test;
+pub mod export;
+#[cfg(test)]
+pub mod export_test;
+pub mod ff_engine;
+#[cfg(test)]
+pub mod ff_engine_test;
+#[cfg(test)]
+pub mod golden_test;
+pub mod held;
+#[cfg(test)]
+pub mod held_test;
+pub mod history;
+#[cfg(test)]
+pub mod history_test;
+pub mod inspector;
+#[cfg(test)]
+pub mod inspector_test;
+pub mod invert;
+#[cfg(test)]
+pub mod invert_test;
+pub mod key_remap;
+#[cfg(test)]
+pub mod key_remap_test;
+pub mod layers;
+#[cfg(test)]
+pub mod layers_test;
+pub mod live_values;
+#[cfg(test)]
+pub mod live_values_test;
+pub mod motion_filter;
+#[cfg(test)]
+pub mod motion_filter_test;
+pub mod motion_gesture;
+#[cfg(test)]
+pub mod motion_gesture_test;
+pub mod motion_lean;
+#[cfg(test)]
+pub mod motion_lean_test;
+pub mod output_map;
+#[cfg(test)]
+pub mod output_map_test;
+pub mod overload;
+#[cfg(test)]
+pub mod overload_test;
+pub mod pipeline;
+#[cfg(test)]
+pub mod pipeline_test;
+pub mod priority;
+#[cfg(test)]
+pub mod priority_test;
+pub mod quirks;
+#[cfg(test)]
+pub mod quirks_test;
+pub mod radial_menu;
+#[cfg(test)]
+pub mod radial_menu_test;
+pub mod scheduler;
+#[cfg(test)]
+pub mod scheduler_test;
+pub mod stick_deadzone;
+#[cfg(test)]
+pub mod stick_deadzone_test;
+pub mod stick_gate;
+#[cfg(test)]
+pub mod stick_gate_test;
+#[cfg(test)]
+pub mod target_filter_test;
+pub mod target_frame;
+#[cfg(test)]
+pub mod target_frame_test;
+pub mod tee;
+#[cfg(test)]
+pub mod tee_test;
+#[cfg(test)]
+pub mod test_events;
+pub mod text;
+#[cfg(test)]
+pub mod text_test;
+pub mod touch_mouse;
+#[cfg(test)]
+pub mod touch_mouse_test;
 
 use std::{
     borrow::Borrow,
     collections::{
         hash_map::{Entry, HashMap},
-        BTreeSet, HashSet,
+        BTreeMap, BTreeSet, HashSet,
     },
     error::Error,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Instant,
 };
 
-use evdev::InputEvent;
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, FFEffectCode, FFEffectData, FFEffectKind, FFReplay, FFTrigger,
+    InputEvent,
+};
 use tokio::{
-    sync::mpsc,
+    sync::mpsc::{self, error::TrySendError},
     task::{JoinHandle, JoinSet},
     time::Duration,
 };
+use tracing::Instrument;
 use zbus::Connection;
 
 use crate::{
     config::{
-        path::get_profiles_path, CapabilityMap, CapabilityMapping, CompositeDeviceConfig,
-        DeviceProfile, ProfileMapping,
+        alias::resolve_aliases,
+        overlay::apply_overlay,
+        path::{get_profiles_path, get_quirks_paths, get_state_path},
+        quirks::find_device_quirk,
+        AxisRangeConfig, CapabilityConfig, CapabilityMap, CapabilityMapping, CompositeDeviceConfig,
+        DeviceProfile, InterceptActivationConfig, ProfileMapping, SourcePower, TargetFilter,
+        TouchClickZone,
     },
     dbus::interface::{
-        composite_device::CompositeDeviceInterface, source::iio_imu::SourceIioImuInterface,
+        capability_strings, composite_device::CompositeDeviceInterface,
+        source::iio_imu::SourceIioImuInterface,
     },
     input::{
-        capability::{Capability, Gamepad, GamepadButton, Mouse},
+        capability::{
+            Capability, Gamepad, GamepadButton, GamepadGesture, Keyboard, Mouse, MouseButton,
+        },
         event::{
+            evdev::abs_axis_from_str,
             native::NativeEvent,
             value::{InputValue, TranslationError},
             Event,
         },
+        output_capability::OutputCapability,
         output_event::UinputOutputEvent,
-        source::{evdev::EventDevice, hidraw::HidRawDevice, iio::IioDevice, SourceDevice},
+        source::{
+            evdev::EventDevice,
+            external::ExternalDevice,
+            hidraw::{bluetooth, HidRawDevice},
+            iio::IioDevice,
+            power,
+            r#virtual::VirtualDevice,
+            SourceDevice, SourceDeviceError,
+        },
+        watchdog::{self, RestartCounter, TaskStalled},
+        xtest::{self, InputBackend, XTestDevice},
     },
+    platform::state::PlatformState,
     udev::{device::UdevDevice, hide_device, unhide_device},
 };
 
-use self::{client::CompositeDeviceClient, command::CompositeCommand};
+use self::{
+    action::ActionRunner,
+    activator::ActivatorState,
+    audio_haptics::AudioHaptics,
+    axis_button::{AxisButtonState, AxisButtonUpdate},
+    axis_transform::AxisTransform,
+    battery_led::BatteryLedPolicy,
+    capture::{CapturedInput, InputCapture},
+    client::CompositeDeviceClient,
+    command::CompositeCommand,
+    debounce::Debouncer,
+    detachable::DetachableTracker,
+    dpad_repeat::DPadRepeater,
+    dpad_stick::{DPadStickMode, DPadStickTranslator},
+    drift::DriftCompensator,
+    dry_run::MappingDryRun,
+    dwell_click::{DwellAction, DwellClick, DwellClicker},
+    export::{ActiveConfig, RuntimeConfig},
+    ff_engine::{route_effect, FFEngine, RumbleMagnitude, RumbleSide},
+    held::HeldInputs,
+    history::EventHistory,
+    inspector::InputInspector,
+    invert::InputInverter,
+    key_remap::KeyRemapTable,
+    layers::LayerStack,
+    live_values::LiveValues,
+    motion_filter::MotionFilter,
+    motion_gesture::{MotionGestureDetector, GESTURE_CAPABILITIES},
+    motion_lean::{MotionLeanDetector, LEAN_CAPABILITIES},
+    output_map::OutputMapper,
+    overload::{OverloadQueue, OverloadStats},
+    pipeline::{CompositeStage, EventFrame, Pipeline},
+    priority::SourcePriority,
+    quirks::SourceQuirks,
+    radial_menu::{RadialMenu, RadialMenuEvent, RadialMenuStick},
+    scheduler::EventScheduler,
+    stick_deadzone::StickDeadzones,
+    stick_gate::StickGates,
+    target_frame::TargetFrames,
+    tee::TeeSink,
+    text::{LayoutNames, TextResolver},
+    touch_mouse::{TouchMouseState, TouchMouseUpdate, DEFAULT_POINTER_PIXELS},
+};
 
 use super::{
-    manager::ManagerCommand, output_event::OutputEvent, source::client::SourceDeviceClient,
-    target::client::TargetDeviceClient,
+    manager::ManagerCommand,
+    output_event::OutputEvent,
+    source::client::SourceDeviceClient,
+    target::{
+        client::{ClientError as TargetClientError, TargetDeviceClient},
+        command::TargetCommand,
+    },
 };
 
 /// Size of the command channel buffer for processing input events and commands.
 const BUFFER_SIZE: usize = 16384;
+/// How often force feedback effects rendered by the [FFEngine] are updated
+const FF_ENGINE_UPDATE_INTERVAL: Duration = Duration::from_millis(8);
+/// Time between key events written when typing text
+const TEXT_KEY_DELAY: Duration = Duration::from_millis(8);
+/// How often events in the backlog of overloaded target devices are retried
+const TARGET_BACKLOG_RETRY: Duration = Duration::from_millis(1);
+/// Scheduler key of the timeout of an input capture
+const INPUT_CAPTURE_KEY: &str = "input_capture";
+/// Prefix of the scheduler key of a capability map action waiting for its
+/// source events to be held long enough
+const HELD_ACTION_KEY_PREFIX: &str = "held_action:";
+/// Prefix of the scheduler key of the key events of text typed by a profile
+/// mapping
+const TEXT_KEY_PREFIX: &str = "text:";
+/// Prefix of the scheduler key of the repeat of an axis to button profile
+/// mapping
+const AXIS_BUTTON_KEY_PREFIX: &str = "axis_button:";
+/// Prefix of the scheduler key of the timeout of a long or double press
+/// profile mapping
+const ACTIVATOR_KEY_PREFIX: &str = "activator:";
+/// Prefix of the scheduler key of the next attempt to attach a source device
+const ATTACH_KEY_PREFIX: &str = "attach:";
+/// Prefix of the scheduler key of the end of maintenance mode of a source
+/// device
+const MAINTENANCE_KEY_PREFIX: &str = "maintenance:";
+/// Prefixes of the scheduler keys of commands scheduled by a profile mapping
+const MAPPING_KEY_PREFIXES: [&str; 3] = [
+    TEXT_KEY_PREFIX,
+    AXIS_BUTTON_KEY_PREFIX,
+    ACTIVATOR_KEY_PREFIX,
+];
+/// Time motion gestures are held down when they are detected
+const GESTURE_PRESS_TIME: Duration = Duration::from_millis(50);
+/// Default time to wait for Steam before creating staged target devices
+const STEAM_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often to check if Steam is running while waiting for it
+const STEAM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Time to wait before saving changes to the learned stick drift
+const DRIFT_SAVE_DELAY: Duration = Duration::from_secs(30);
+/// Scheduler key of the next battery LED color update
+const BATTERY_LED_KEY: &str = "battery_led";
+/// Scheduler key of the next signal that the live stick and trigger values
+/// changed
+const LIVE_VALUES_KEY: &str = "live_values";
+/// Scheduler key of the dwell click timer
+const DWELL_CLICK_KEY: &str = "dwell_click";
+/// Time to hold the mouse button of a dwell click
+const DWELL_CLICK_PRESS_TIME: Duration = Duration::from_millis(50);
+/// Maximum number of source events held while the target devices that were
+/// stopped when idle are re-created
+const IDLE_WAKE_EVENTS_MAX: usize = 64;
+/// Maximum time to hold source events while the target devices that were
+/// stopped when idle are re-created
+const IDLE_WAKE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Scheduler key of the timeout for holding source events while waking
+const IDLE_WAKE_KEY: &str = "idle_wake";
 
 /// The [InterceptMode] defines whether or not inputs should be routed over
 /// DBus instead of to the target devices. This can be used by overlays to
@@ -65,6 +320,57 @@ pub enum InterceptMode {
     GamepadOnly,
 }
 
+/// A [TargetEventFilter] defines which events should be routed to a target
+/// device.
+#[derive(Debug, Clone, Default)]
+struct TargetEventFilter {
+    /// If defined, only these capabilities will be routed to the target
+    allow: Option<HashSet<Capability>>,
+    /// Capabilities that will never be routed to the target
+    deny: HashSet<Capability>,
+}
+
+impl TargetEventFilter {
+    /// Returns true if the given capability should be routed to the target
+    fn allows(&self, cap: &Capability) -> bool {
+        if self.deny.contains(cap) {
+            return false;
+        }
+        match self.allow.as_ref() {
+            Some(allow) => allow.contains(cap),
+            None => true,
+        }
+    }
+
+    /// Returns true if the given capability was explicitly allowed by the
+    /// filter and should be mirrored to the target.
+    fn mirrors(&self, cap: &Capability) -> bool {
+        if self.deny.contains(cap) {
+            return false;
+        }
+        match self.allow.as_ref() {
+            Some(allow) => allow.contains(cap),
+            None => false,
+        }
+    }
+}
+
+impl From<&TargetFilter> for TargetEventFilter {
+    fn from(config: &TargetFilter) -> Self {
+        let to_caps = |configs: &Vec<CapabilityConfig>| -> HashSet<Capability> {
+            configs
+                .iter()
+                .map(|config| config.clone().into())
+                .filter(|cap| *cap != Capability::NotImplemented)
+                .collect()
+        };
+        Self {
+            allow: config.allow.as_ref().map(to_caps),
+            deny: config.deny.as_ref().map(to_caps).unwrap_or_default(),
+        }
+    }
+}
+
 /// A [CompositeDevice] represents any number source input devices that
 /// can translate input to any target devices
 #[derive(Debug)]
@@ -84,9 +390,117 @@ pub struct CompositeDevice {
     /// Name of the currently loaded [DeviceProfile] for the CompositeDevice.
     /// The [DeviceProfile] is used to translate input events.
     device_profile: Option<String>,
+    /// The currently loaded [DeviceProfile] with any aliases resolved
+    device_profile_config: Option<DeviceProfile>,
+    /// The loaded [DeviceProfile] without any overlay applied
+    base_profile: Option<DeviceProfile>,
+    /// Profile whose mappings are stacked on top of the base profile
+    profile_overlay: Option<DeviceProfile>,
     /// Map of profile source events to translate to one or more profile mapping
     /// configs that define how the source event should be translated.
     device_profile_config_map: HashMap<Capability, Vec<ProfileMapping>>,
+    /// Layers of profile mappings that can be switched to with source inputs
+    layers: LayerStack,
+    /// State of profile mappings that translate an axis into a button using
+    /// sectors, hysteresis, or key repeat, keyed by mapping name.
+    axis_button_states: HashMap<String, AxisButtonState>,
+    /// State of profile mappings that are activated by a long or double
+    /// press, keyed by mapping name.
+    activator_states: HashMap<String, ActivatorState>,
+    /// Gamepad buttons on source devices that are currently pressed. Used to
+    /// check for held buttons like the gyro ratchet button.
+    source_buttons_pressed: HashSet<GamepadButton>,
+    /// Last known finger position on each touchpad, keyed by touchpad name.
+    /// Used to determine the click zone of touchpad clicks.
+    touch_positions: HashMap<String, (f64, f64)>,
+    /// Target capabilities pressed by touchpad click zone mappings, keyed by
+    /// mapping name, so the same button is released when the click ends.
+    click_zone_targets: HashMap<String, Vec<Capability>>,
+    /// State of profile mappings that translate touch motion into relative
+    /// mouse motion and edge scrolling, keyed by mapping name.
+    touch_mouse_states: HashMap<String, TouchMouseState>,
+    /// Last known position of each source axis. Used to translate an axis
+    /// into an absolute touch position when an event only updates one of the
+    /// axis components.
+    axis_positions: HashMap<Capability, (f64, f64)>,
+    /// Source inputs that are currently held, as they enter the device
+    /// profile. Replayed through the new mappings when the profile changes.
+    source_held: HeldInputs,
+    /// Inputs that are currently held on target devices. Released when the
+    /// device profile changes so no input gets stuck.
+    target_held: Mutex<HeldInputs>,
+    /// Transforms source events into the layout of the device profile
+    axis_transform: AxisTransform,
+    /// Shaped dead zones of the sticks, as defined by the device profile
+    stick_deadzones: StickDeadzones,
+    /// Arcade stick gates of the sticks, as defined by the device profile
+    stick_gates: StickGates,
+    /// Translates between the DPad and the left stick
+    dpad_stick: DPadStickTranslator,
+    /// Repeats held DPad buttons, if enabled by the device profile
+    dpad_repeat: Option<DPadRepeater>,
+    /// Translates a stick into sector selections of an on-screen radial menu
+    radial_menu: RadialMenu,
+    /// Corrects source inputs that report inverted logic
+    inverter: InputInverter,
+    /// Filters state changes of flaky digital source inputs
+    debouncer: Debouncer,
+    /// Locale-safe id of this device that stays the same across restarts and
+    /// reconnects, used to persist its state
+    stable_id: String,
+    /// Learns and compensates analog stick drift, if enabled
+    drift_compensator: Option<DriftCompensator>,
+    /// Path to persist the learned stick drift of this device to
+    drift_path: PathBuf,
+    /// Whether saving the learned stick drift is already scheduled
+    drift_save_scheduled: bool,
+    /// Commands that should be processed at a later time, such as macro key
+    /// sequences and repeated buttons
+    scheduler: EventScheduler<CompositeCommand>,
+    /// Resolves the keys needed to type text from profile mappings. This is
+    /// loaded from the system keyboard layout the first time text is typed.
+    text_resolver: Option<TextResolver>,
+    /// Most recent input events received from source devices
+    event_history: EventHistory,
+    /// Latest stick and trigger values of the source devices for settings UIs
+    live_values: LiveValues,
+    /// Streams events written to target devices as DBus signals when enabled
+    inspector: Option<InputInspector>,
+    /// Sinks that receive a copy of the events written to target devices,
+    /// e.g. to drive a second machine at the same time
+    tee_sinks: Vec<TeeSink>,
+    /// Reports events as DBus signals instead of writing them to target
+    /// devices when a mapping dry run is active
+    dry_run: Option<MappingDryRun>,
+    /// Id of the source device whose event is currently being processed
+    current_source_id: Option<String>,
+    /// Captures the next physical input for configurators
+    input_capture: InputCapture,
+    /// Detects motion gestures like freefall from the accelerometer
+    motion_gestures: MotionGestureDetector,
+    /// Detects leaning the device left or right, if enabled by the device
+    /// profile
+    motion_lean: Option<MotionLeanDetector>,
+    /// Counters of how often the event channels of this device were
+    /// overloaded, shared with all clients of this device
+    overload_stats: Arc<OverloadStats>,
+    /// Events waiting to be written to target devices whose channels were
+    /// full, keyed by target device path
+    target_backlog: Mutex<HashMap<String, OverloadQueue>>,
+    /// Events of the current input frame waiting to be written together to
+    /// each target device
+    target_frames: Mutex<TargetFrames>,
+    /// Source buttons from the device profile that toggle the DPad/stick
+    /// translation when pressed together, and the mode to toggle to.
+    dpad_stick_toggle: Option<(HashSet<GamepadButton>, DPadStickMode)>,
+    /// Absolute axis ranges reported by source devices
+    source_axis_ranges: HashMap<AbsoluteAxisCode, AbsInfo>,
+    /// Absolute axis range overrides from the device profile
+    axis_range_overrides: Vec<AxisRangeConfig>,
+    /// Smoothing filter applied to gyro events from the device profile
+    gyro_filter: Option<MotionFilter>,
+    /// Smoothing filter applied to accelerometer events from the device profile
+    accel_filter: Option<MotionFilter>,
     /// List of input capabilities that can be translated by the capability map
     translatable_capabilities: Vec<Capability>,
     /// List of currently "pressed" actions used to translate multiple input
@@ -100,10 +514,31 @@ pub struct CompositeDevice {
     /// Keep track of translated events we've emitted so we can send
     /// release events
     emitted_mappings: HashMap<String, CapabilityMapping>,
+    /// Runs actions from capability map mappings that target an action
+    /// instead of an input event.
+    action_runner: ActionRunner,
+    /// Performs the source-specific actions of capability map output mappings
+    output_mapper: OutputMapper,
+    /// Sets the color of an LED based on the battery level, if enabled
+    battery_led: Option<BatteryLedPolicy>,
+    /// Plays force feedback through an audio sink connected to a haptic
+    /// speaker, if enabled
+    audio_haptics: Option<AudioHaptics>,
+    /// Current hardware state of the platform (e.g. tablet mode, lid, dock).
+    /// Capability mappings with conditions are only active if the platform
+    /// state satisfies them.
+    platform_state: PlatformState,
+    /// Steam app id of the game focused in gamescope, if known
+    focused_app_id: Option<u32>,
+    /// Player index assigned to the device, starting at 1. Source devices
+    /// show it using whatever player indicator they support.
+    player_index: Option<u8>,
     /// The DBus path this [CompositeDevice] is listening on
     dbus_path: String,
     /// Mode defining how inputs should be routed
     intercept_mode: InterceptMode,
+    /// Ordered stages that input events are processed through
+    pipeline: Pipeline<CompositeStage>,
     /// Transmit channel for sending commands to this composite device
     tx: mpsc::Sender<CompositeCommand>,
     /// Receiver channel for listening for commands
@@ -116,8 +551,28 @@ pub struct CompositeDevice {
     /// HashSet of source devices that are blocked from passing their input events to target
     /// events.
     source_devices_blocked: HashSet<String>,
+    /// Last error reported by each source device, used as the reason when the
+    /// source device is detached.
+    source_device_errors: HashMap<String, String>,
+    /// Number of times each source device was restarted after it stopped
+    /// responding, mapped by source device id
+    source_device_restarts: RestartCounter,
+    /// Number of times each kind of target device was re-created after it
+    /// stopped responding, mapped by target device kind
+    target_device_restarts: RestartCounter,
+    /// Source devices that were released for maintenance (e.g. firmware
+    /// updates), mapped by source device id. Holds the device to re-attach
+    /// once it has stopped.
+    source_devices_maintenance: HashMap<String, Option<UdevDevice>>,
+    /// Number of failed attempts to attach source devices that are being
+    /// retried (e.g. bluetooth devices that are still reconnecting), mapped
+    /// by source device id.
+    source_device_attach_attempts: HashMap<String, u32>,
     /// Physical device path for source devices. E.g. ["/dev/input/event0"]
     source_device_paths: Vec<String>,
+    /// Feature reports to send to hidraw source devices after system resume,
+    /// mapped by their device path.
+    source_resume_reports: HashMap<String, Vec<Vec<u8>>>,
     /// All currently running source device threads
     source_device_tasks: JoinSet<()>,
     /// Unique identifiers for running source devices. E.g. ["evdev://event0"]
@@ -137,9 +592,26 @@ pub struct CompositeDevice {
     /// List of active target device types (e.g. "deck", "ds5", "xb360") that
     /// were active before system suspend.
     target_devices_suspended: Vec<String>,
+    /// List of active target device types (e.g. "deck", "ds5", "xb360") that
+    /// were stopped when the device became idle.
+    target_devices_idle: Vec<String>,
     /// Map of DBusDevice DBus paths to their respective transmitter channel.
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <Sender>}
     target_dbus_devices: HashMap<String, TargetDeviceClient>,
+    /// Map of target device DBus paths to the filter that defines which events
+    /// should be routed to that target device.
+    /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <TargetEventFilter>}
+    target_filters: HashMap<String, TargetEventFilter>,
+    /// Keys to replace before they are written to target devices, keyed by
+    /// target device path
+    target_key_remaps: HashMap<String, KeyRemapTable>,
+    /// Map of target device DBus paths to the index of the split target in
+    /// the config ('options.split') they are assigned to.
+    /// E.g. {"/org/shadowblip/InputPlumber/devices/target/gamepad1": 1}
+    target_split_index: HashMap<String, usize>,
+    /// Fallback used to emulate keyboard and mouse input with the XTEST
+    /// extension when uinput target devices cannot be created
+    xtest: Option<XTestDevice>,
     /// Set of available Force Feedback effect IDs that are not in use
     /// TODO: Just use the keys from ff_effect_id_source_map to determine next id
     ff_effect_ids: BTreeSet<i16>,
@@ -147,6 +619,38 @@ pub struct CompositeDevice {
     /// This mapping maps the composite device effect ids to source device effect ids.
     /// E.g. {3: {"evdev://event0": 6, "evdev://event1": 2}}
     ff_effect_id_source_map: HashMap<i16, HashMap<String, i16>>,
+    /// Engine that renders force feedback effects which source devices cannot
+    /// play themselves (e.g. periodic effects) into rumble.
+    ff_engine: FFEngine,
+    /// Rumble effect ids uploaded to each source device to play rumble
+    /// rendered by the [FFEngine].
+    /// E.g. {"evdev://event0": 2}
+    ff_engine_source_effect_ids: HashMap<String, i16>,
+    /// Side each source device is on for composite devices built from two
+    /// physical halves. Sources without a side play the full rumble effect.
+    /// E.g. {"evdev://event0": RumbleSide::Left}
+    source_rumble_sides: HashMap<String, RumbleSide>,
+    /// Device quirks of source devices, keyed by source device id
+    source_quirks: HashMap<String, SourceQuirks>,
+    /// Resolves conflicting events from source devices of different priority
+    source_priority: SourcePriority,
+    /// Tracks whether detachable controllers are attached
+    detachable: DetachableTracker,
+    /// Profile overlay that was replaced while a detachable controller is
+    /// detached, keyed by controller name
+    detached_profile_overlays: HashMap<String, Option<DeviceProfile>>,
+    /// Target devices that were replaced while a detachable controller is
+    /// detached, keyed by controller name
+    detached_target_devices: HashMap<String, Vec<String>>,
+    /// Kinds of target devices that were removed when the last source device
+    /// disconnected, to re-create once a source device reconnects
+    disconnected_target_devices: Option<Vec<String>>,
+    /// Clicks automatically when the mouse pointer rests in place
+    dwell_clicker: Option<DwellClicker>,
+    /// Last rumble rendered by the [FFEngine]
+    ff_engine_rumble: RumbleMagnitude,
+    /// Whether or not an update of the [FFEngine] is scheduled
+    ff_engine_update_scheduled: bool,
     /// List of intercept mode activation Capabilities
     intercept_activation_caps: Vec<Capability>,
     /// Capability to send when intercept mode is activated for the first time.
@@ -162,6 +666,16 @@ pub struct CompositeDevice {
     ///  - KEY   - Target capability
     ///  - VALUE - Source capability
     exclusive_inputs: HashMap<Capability, Capability>,
+    /// Time without source input after which the device is considered idle
+    idle_timeout: Option<Duration>,
+    /// Time the last input event was received from a source device
+    last_input_time: Instant,
+    /// Whether or not the device is idle
+    is_idle: bool,
+    /// Source events received while the target devices that were stopped
+    /// when idle are re-created. These are processed once the target devices
+    /// are attached, so the input that woke the device is not lost.
+    idle_wake_events: Option<Vec<(String, NativeEvent)>>,
 }
 
 impl CompositeDevice {
@@ -176,6 +690,68 @@ impl CompositeDevice {
         log::info!("Creating CompositeDevice with config: {}", config.name);
         let (tx, rx) = mpsc::channel(BUFFER_SIZE);
         let name = config.name.clone();
+        let idle_timeout = config
+            .options
+            .as_ref()
+            .and_then(|options| options.idle.as_ref())
+            .map(|idle| Duration::from_secs(idle.timeout_secs));
+        let debouncer = config
+            .options
+            .as_ref()
+            .and_then(|options| options.debounce.as_ref())
+            .map(|configs| Debouncer::new(configs.as_slice()))
+            .unwrap_or_default();
+        let inverter = config
+            .options
+            .as_ref()
+            .and_then(|options| options.source_inputs.as_ref())
+            .map(|configs| InputInverter::new(configs.as_slice()))
+            .unwrap_or_default();
+        let stable_id = stable_id(&config, &device_info);
+        let drift_path = get_state_path()
+            .join("drift")
+            .join(format!("{stable_id}.yaml"));
+        let drift_compensator = config
+            .options
+            .as_ref()
+            .and_then(|options| options.drift_compensation.as_ref())
+            .map(|drift| {
+                let mut compensator = DriftCompensator::new(drift);
+                match drift::load_centers(&drift_path) {
+                    Ok(centers) => compensator.set_centers(centers),
+                    Err(e) => log::debug!("No stick drift loaded from {drift_path:?}: {e}"),
+                }
+                compensator
+            });
+        let battery_led = config
+            .options
+            .as_ref()
+            .and_then(|options| options.battery_led.as_ref())
+            .map(BatteryLedPolicy::new);
+        let audio_haptics = config
+            .options
+            .as_ref()
+            .and_then(|options| options.audio_haptics.as_ref())
+            .map(AudioHaptics::new);
+        let dwell_clicker = config
+            .options
+            .as_ref()
+            .and_then(|options| options.dwell_click.as_ref())
+            .map(DwellClicker::new);
+        let tee_sinks = config
+            .options
+            .as_ref()
+            .and_then(|options| options.tee.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|tee| match TeeSink::new(tee) {
+                Ok(sink) => Some(sink),
+                Err(e) => {
+                    log::error!("Failed to create tee '{}': {e:?}", tee.name);
+                    None
+                }
+            })
+            .collect();
         let mut device = Self {
             conn,
             manager,
@@ -184,18 +760,76 @@ impl CompositeDevice {
             capabilities: HashSet::new(),
             capability_map,
             device_profile: None,
+            device_profile_config: None,
+            base_profile: None,
+            profile_overlay: None,
             device_profile_config_map: HashMap::new(),
+            layers: LayerStack::default(),
+            axis_button_states: HashMap::new(),
+            activator_states: HashMap::new(),
+            source_buttons_pressed: HashSet::new(),
+            touch_positions: HashMap::new(),
+            click_zone_targets: HashMap::new(),
+            touch_mouse_states: HashMap::new(),
+            axis_positions: HashMap::new(),
+            source_held: HeldInputs::default(),
+            target_held: Mutex::default(),
+            axis_transform: AxisTransform::default(),
+            stick_deadzones: StickDeadzones::default(),
+            stick_gates: StickGates::default(),
+            dpad_stick: DPadStickTranslator::default(),
+            dpad_repeat: None,
+            radial_menu: RadialMenu::default(),
+            inverter,
+            debouncer,
+            stable_id,
+            drift_compensator,
+            drift_path,
+            drift_save_scheduled: false,
+            scheduler: EventScheduler::default(),
+            text_resolver: None,
+            event_history: EventHistory::default(),
+            live_values: LiveValues::default(),
+            inspector: None,
+            tee_sinks,
+            dry_run: None,
+            current_source_id: None,
+            input_capture: InputCapture::default(),
+            motion_gestures: MotionGestureDetector::default(),
+            motion_lean: None,
+            overload_stats: Arc::default(),
+            target_backlog: Mutex::default(),
+            target_frames: Mutex::default(),
+            dpad_stick_toggle: None,
+            source_axis_ranges: HashMap::new(),
+            axis_range_overrides: Vec::new(),
+            gyro_filter: None,
+            accel_filter: None,
             translatable_capabilities: Vec::new(),
             translatable_active_inputs: Vec::new(),
             translated_recent_events: HashSet::new(),
             emitted_mappings: HashMap::new(),
+            action_runner: ActionRunner::default(),
+            output_mapper: OutputMapper::default(),
+            battery_led,
+            audio_haptics,
+            platform_state: PlatformState::default(),
+            focused_app_id: None,
+            player_index: None,
             dbus_path,
             intercept_mode: InterceptMode::None,
+            pipeline: Pipeline::new(CompositeStage::default_stages()),
             tx,
             rx,
             source_devices: HashMap::new(),
             source_devices_discovered: Vec::new(),
             source_devices_blocked: HashSet::new(),
+            source_resume_reports: HashMap::new(),
+            source_device_errors: HashMap::new(),
+            source_device_restarts: RestartCounter::default(),
+            target_device_restarts: RestartCounter::default(),
+            source_devices_maintenance: HashMap::new(),
+            source_device_attach_attempts: HashMap::new(),
             source_device_paths: Vec::new(),
             source_device_tasks: JoinSet::new(),
             source_devices_used: Vec::new(),
@@ -203,9 +837,26 @@ impl CompositeDevice {
             target_devices_by_capability: HashMap::new(),
             target_devices_queued: HashSet::new(),
             target_devices_suspended: Vec::new(),
+            target_devices_idle: Vec::new(),
             target_dbus_devices: HashMap::new(),
+            target_filters: HashMap::new(),
+            target_key_remaps: HashMap::new(),
+            target_split_index: HashMap::new(),
+            xtest: None,
             ff_effect_ids: (0..64).collect(),
             ff_effect_id_source_map: HashMap::new(),
+            ff_engine: FFEngine::new(),
+            ff_engine_source_effect_ids: HashMap::new(),
+            source_rumble_sides: HashMap::new(),
+            source_quirks: HashMap::new(),
+            source_priority: SourcePriority::default(),
+            detachable: DetachableTracker::default(),
+            detached_profile_overlays: HashMap::new(),
+            detached_target_devices: HashMap::new(),
+            disconnected_target_devices: None,
+            dwell_clicker,
+            ff_engine_rumble: RumbleMagnitude::default(),
+            ff_engine_update_scheduled: false,
             intercept_activation_caps: vec![Capability::Gamepad(Gamepad::Button(
                 GamepadButton::Guide,
             ))],
@@ -213,6 +864,10 @@ impl CompositeDevice {
             intercept_active_inputs: Vec::new(),
             active_inputs: Vec::new(),
             exclusive_inputs: HashMap::new(),
+            idle_timeout,
+            last_input_time: Instant::now(),
+            is_idle: false,
+            idle_wake_events: None,
         };
 
         // Load the capability map if one was defined
@@ -230,12 +885,10 @@ impl CompositeDevice {
         // If a capability map is defined, add those target capabilities to
         // the hashset of implemented capabilities.
         if let Some(map) = device.capability_map.as_ref() {
-            for mapping in map.mapping.clone() {
-                let cap = mapping.target_event.clone().into();
-                if cap == Capability::NotImplemented {
-                    continue;
-                }
-                device.capabilities.insert(cap);
+            for mapping in map.mapping.iter() {
+                device
+                    .capabilities
+                    .extend(capability_mapping_targets(mapping));
             }
         }
 
@@ -251,6 +904,12 @@ impl CompositeDevice {
         self.dbus_path.as_str()
     }
 
+    /// Return the id of the composite device that stays the same across
+    /// restarts and reconnects
+    pub fn stable_id(&self) -> &str {
+        self.stable_id.as_str()
+    }
+
     /// Creates a new instance of the composite device interface on DBus.
     pub async fn listen_on_dbus(&self) -> Result<JoinHandle<()>, Box<dyn Error>> {
         let conn = self.conn.clone();
@@ -280,14 +939,20 @@ impl CompositeDevice {
         // Start all source devices
         self.run_source_devices().await?;
 
-        // Keep track of all target devices
-        for (path, target) in targets.iter() {
+        // Keep track of all target devices. Targets are added in order of
+        // their path, so split targets are assigned in the order they were
+        // created.
+        let ordered_targets: BTreeMap<&String, &TargetDeviceClient> = targets.iter().collect();
+        for (path, target) in ordered_targets {
             if let Err(e) = target.set_composite_device(self.client()).await {
                 return Err(
                     format!("Failed to set composite device for target device: {:?}", e).into(),
                 );
             }
 
+            // Load any event filter for the kind of target device
+            self.load_target_filter(path, target).await?;
+
             // Query the target device for its capabilities
             let caps = match target.get_capabilities().await {
                 Ok(caps) => caps,
@@ -312,15 +977,43 @@ impl CompositeDevice {
         }
         self.target_devices = targets;
 
+        // Start checking for idle if an idle timeout is configured
+        if let Some(timeout) = self.idle_timeout {
+            self.schedule_idle_check(timeout);
+        }
+
+        // Start setting the LED color from the battery level if enabled
+        if self.battery_led.is_some() {
+            let cmd = CompositeCommand::UpdateBatteryLed;
+            self.schedule(Duration::ZERO, Some(BATTERY_LED_KEY), cmd);
+        }
+
+        // Create any target devices that should be created with a delay or
+        // after Steam has started.
+        self.start_staged_target_devices();
+
         // Loop and listen for command events
         log::debug!("CompositeDevice started");
         let mut buffer = Vec::with_capacity(BUFFER_SIZE);
         'main: loop {
-            let num = self.rx.recv_many(&mut buffer, BUFFER_SIZE).await;
-            if num == 0 {
-                log::warn!("Unable to receive more commands. Channel closed.");
-                break;
+            // Wait for commands or for the next scheduled command to be due.
+            // Target devices with a backlog are retried shortly.
+            let mut deadline = self.scheduler.next_deadline();
+            if self.has_target_backlog() {
+                let retry_at = Instant::now() + TARGET_BACKLOG_RETRY;
+                deadline = Some(deadline.map_or(retry_at, |d| d.min(retry_at)));
+            }
+            tokio::select! {
+                num = self.rx.recv_many(&mut buffer, BUFFER_SIZE) => {
+                    if num == 0 {
+                        log::warn!("Unable to receive more commands. Channel closed.");
+                        break;
+                    }
+                }
+                _ = sleep_until(deadline) => (),
             }
+            self.flush_target_backlog().await;
+            buffer.extend(self.scheduler.poll(Instant::now()));
             let mut devices_removed = false;
             //log::trace!("Received {num} command(s)");
             for cmd in buffer.drain(..) {
@@ -357,16 +1050,61 @@ impl CompositeDevice {
                         }
                     }
                     CompositeCommand::SetInterceptMode(mode) => self.set_intercept_mode(mode).await,
+                    CompositeCommand::SetPlatformState(state) => self.set_platform_state(state),
+                    CompositeCommand::GetFocusedApp(sender) => {
+                        if let Err(e) = sender.send(self.focused_app_id).await {
+                            log::error!("Failed to send focused app: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetFocusedApp(app_id) => {
+                        if self.focused_app_id != app_id {
+                            self.set_focused_app(app_id);
+                            self.signal_focused_app_changed().await;
+                        }
+                    }
+                    CompositeCommand::SetPlayerIndex(index) => self.set_player_index(index).await,
+                    CompositeCommand::GetPlayerIndex(sender) => {
+                        if let Err(e) = sender.send(self.player_index).await {
+                            log::error!("Failed to send player index: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetInterceptMode(sender) => {
                         if let Err(e) = sender.send(self.intercept_mode.clone()).await {
                             log::error!("Failed to send intercept mode: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetLiveValues(sender) => {
+                        if let Err(e) = sender.send(self.live_values.values()).await {
+                            log::error!("Failed to send live values: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetDPadStickMode(sender) => {
+                        if let Err(e) = sender.send(self.dpad_stick.mode()).await {
+                            log::error!("Failed to send dpad stick mode: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetDPadStickMode(mode) => {
+                        self.set_dpad_stick_mode(mode).await
+                    }
+                    CompositeCommand::GetRadialMenuStick(sender) => {
+                        if let Err(e) = sender.send(self.radial_menu.stick()).await {
+                            log::error!("Failed to send radial menu stick: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetRadialMenuStick(stick) => {
+                        self.set_radial_menu_stick(stick).await
+                    }
                     CompositeCommand::GetConfig(sender) => {
                         if let Err(e) = sender.send(self.config.clone()).await {
                             log::error!("Failed to send config: {e:?}");
                         }
                     }
+                    CompositeCommand::ExportActiveConfig(sender) => {
+                        let result = self.export_active_config().await.map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send active config: {e:?}");
+                        }
+                    }
                     CompositeCommand::GetSourceDevicePaths(sender) => {
                         if let Err(e) = sender.send(self.get_source_device_paths()).await {
                             log::error!("Failed to send source device paths: {:?}", e);
@@ -378,23 +1116,126 @@ impl CompositeDevice {
                             log::error!("Failed to send target device paths: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetIdle(sender) => {
+                        if let Err(e) = sender.send(self.is_idle).await {
+                            log::error!("Failed to send idle state: {:?}", e);
+                        }
+                    }
                     CompositeCommand::GetDBusDevicePaths(sender) => {
                         let paths = self.target_dbus_devices.keys().cloned().collect();
                         if let Err(e) = sender.send(paths).await {
                             log::error!("Failed to send dbus device paths: {:?}", e);
                         }
                     }
+                    CompositeCommand::GetInputBackend(sender) => {
+                        let backend = match self.xtest {
+                            Some(_) => InputBackend::XTest,
+                            None => InputBackend::Uinput,
+                        };
+                        if let Err(e) = sender.send(backend).await {
+                            log::error!("Failed to send input backend: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetDryRunEnabled(sender) => {
+                        if let Err(e) = sender.send(self.dry_run.is_some()).await {
+                            log::error!("Failed to send dry run enabled: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetDryRunEnabled(enabled) => {
+                        self.set_dry_run_enabled(enabled);
+                    }
+                    CompositeCommand::GetInspectorEnabled(sender) => {
+                        if let Err(e) = sender.send(self.inspector.is_some()).await {
+                            log::error!("Failed to send inspector enabled: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetInspectorEnabled(enabled) => {
+                        self.set_inspector_enabled(enabled);
+                    }
+                    CompositeCommand::GetTeeSinks(sender) => {
+                        let sinks = self
+                            .tee_sinks
+                            .iter()
+                            .map(|sink| (sink.name().to_string(), sink.enabled()))
+                            .collect();
+                        if let Err(e) = sender.send(sinks).await {
+                            log::error!("Failed to send tee sinks: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetTeeEnabled(name, enabled, sender) => {
+                        let result = self.set_tee_enabled(name.as_str(), enabled);
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send set tee enabled result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::GetEventHistory(sender) => {
+                        let events = self.event_history.events().map(|e| e.to_string()).collect();
+                        if let Err(e) = sender.send(events).await {
+                            log::error!("Failed to send event history: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::EnterMaintenanceMode(path, timeout, sender) => {
+                        let result = self
+                            .enter_maintenance_mode(path, timeout)
+                            .await
+                            .map_err(|e| e.to_string());
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send maintenance mode result: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::CaptureNextInput(timeout, sender) => {
+                        self.capture_next_input(timeout, sender);
+                    }
+                    CompositeCommand::CancelInputCapture => {
+                        log::debug!("Input capture timed out");
+                        self.input_capture.cancel();
+                    }
+                    CompositeCommand::ExitMaintenanceMode(path) => {
+                        if let Err(e) = self.exit_maintenance_mode(path).await {
+                            log::error!("Failed to exit maintenance mode: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::AttachSourceDevice(path, sender) => {
+                        self.request_source_device_change(path, true, sender);
+                    }
+                    CompositeCommand::RemoveSourceDevice(path, sender) => {
+                        self.request_source_device_change(path, false, sender);
+                    }
+                    CompositeCommand::RunHeldAction(name) => {
+                        self.run_held_action(name.as_str());
+                    }
+                    CompositeCommand::SourceDeviceDetached(device, unhide) => {
+                        self.detach_source_device(device, unhide).await;
+                    }
                     CompositeCommand::SourceDeviceAdded(device) => {
                         if let Err(e) = self.on_source_device_added(device).await {
                             log::error!("Failed to add source device: {:?}", e);
                         }
                     }
+                    CompositeCommand::SourceDeviceStalled(device) => {
+                        self.on_source_device_stalled(device).await;
+                    }
                     CompositeCommand::SourceDeviceStopped(device) => {
                         log::debug!("Detected source device stopped: {}", device.devnode());
+                        let id = device.get_id();
+                        // Devices released for maintenance are re-attached
+                        // when maintenance mode ends.
+                        if let Some(released) = self.source_devices_maintenance.get_mut(&id) {
+                            released.get_or_insert(device.clone());
+                            if let Err(e) = self.on_source_device_removed(device).await {
+                                log::error!("Failed to remove source device: {:?}", e);
+                            }
+                            continue;
+                        }
                         if let Err(e) = self.on_source_device_removed(device).await {
                             log::error!("Failed to remove source device: {:?}", e);
                         }
-                        if self.source_devices_used.is_empty() {
+                        let reason = self
+                            .source_device_errors
+                            .remove(&id)
+                            .unwrap_or_else(|| "closed".to_string());
+                        self.signal_source_device_detached(id, reason);
+                        if self.source_devices_used.is_empty() && !self.mirrors_disconnect() {
                             log::debug!(
                                 "No source devices remain. Stopping CompositeDevice {dbus_path}"
                             );
@@ -403,20 +1244,57 @@ impl CompositeDevice {
                     }
                     CompositeCommand::SourceDeviceRemoved(device) => {
                         log::debug!("Detected source device removed: {}", device.devnode());
-                        devices_removed = true;
+                        // Devices in maintenance mode may disconnect while
+                        // their firmware is updated.
+                        if !self
+                            .source_devices_maintenance
+                            .contains_key(&device.get_id())
+                        {
+                            devices_removed = true;
+                        }
+                        // Virtual and external source devices do not stop on
+                        // their own when they are removed.
+                        if matches!(device.subsystem().as_str(), "virtual" | "external") {
+                            if let Some(source) = self.source_devices.get(&device.get_id()) {
+                                if let Err(e) = source.stop().await {
+                                    log::error!("Failed to stop source device: {e:?}");
+                                }
+                            }
+                        }
+                        // Reconnected devices start over with their restarts
+                        self.source_device_restarts.reset(&device.get_id());
                         if let Err(e) = self.on_source_device_removed(device).await {
                             log::error!("Failed to remove source device: {:?}", e);
                         }
                     }
+                    CompositeCommand::SourceDeviceError(device_id, error) => {
+                        log::warn!(
+                            "Source device {device_id} failed (attempt {}/{}): {}",
+                            error.attempt,
+                            error.max_attempts,
+                            error.error
+                        );
+                        self.source_device_errors
+                            .insert(device_id.clone(), error.error.clone());
+                        self.signal_source_device_error(device_id, error);
+                    }
+                    CompositeCommand::TargetDeviceStalled(path, kind) => {
+                        self.on_target_device_stalled(path, kind).await;
+                    }
                     CompositeCommand::SetTargetDevices(target_types) => {
                         if let Err(e) = self.set_target_devices(target_types).await {
                             log::error!("Failed to set target devices: {e:?}");
                         }
                     }
+                    CompositeCommand::RecreateTargetDevices => self.recreate_target_devices().await,
+                    CompositeCommand::SignalLiveValues => self.signal_live_values_changed(),
                     CompositeCommand::AttachTargetDevices(targets) => {
                         if let Err(e) = self.attach_target_devices(targets).await {
                             log::error!("Failed to attach target devices: {e:?}");
                         }
+                        if self.target_devices_queued.is_empty() {
+                            self.process_idle_wake_events().await;
+                        }
                     }
                     CompositeCommand::GetName(sender) => {
                         let name = self.name.clone();
@@ -442,7 +1320,10 @@ impl CompositeDevice {
                             }
                         };
                         let result = match self.load_device_profile(profile) {
-                            Ok(_) => Ok(()),
+                            Ok(_) => {
+                                self.resync_held_inputs().await;
+                                Ok(())
+                            }
                             Err(e) => Err(e.to_string()),
                         };
                         if let Err(e) = sender.send(result).await {
@@ -461,13 +1342,28 @@ impl CompositeDevice {
                             }
                         };
                         let result = match self.load_device_profile(profile) {
-                            Ok(_) => Ok(()),
+                            Ok(_) => {
+                                self.resync_held_inputs().await;
+                                Ok(())
+                            }
                             Err(e) => Err(e.to_string()),
                         };
                         if let Err(e) = sender.send(result).await {
                             log::error!("Failed to send load profile result: {:?}", e);
                         }
                     }
+                    CompositeCommand::SetProfileOverlay(path, sender) => {
+                        log::debug!("Setting profile overlay from path: {path}");
+                        let result = self
+                            .set_profile_overlay_path(path)
+                            .map_err(|e| e.to_string());
+                        if result.is_ok() {
+                            self.resync_held_inputs().await;
+                        }
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send set profile overlay result: {:?}", e);
+                        }
+                    }
                     CompositeCommand::WriteEvent(event) => {
                         if let Err(e) = self.write_event(event).await {
                             log::error!("Failed to write event: {:?}", e);
@@ -488,11 +1384,61 @@ impl CompositeDevice {
                             log::error!("Failed to write event: {:?}", e);
                         }
                     }
+                    CompositeCommand::ActivatorTimeout(name) => {
+                        if let Err(e) = self.process_activator_timeout(name).await {
+                            log::error!("Failed to process activator timeout: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::AxisButtonRepeat(name) => {
+                        if let Err(e) = self.process_axis_button_repeat(name).await {
+                            log::error!("Failed to process axis button repeat: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::DPadRepeat(cap) => {
+                        if let Err(e) = self.process_dpad_repeat(cap).await {
+                            log::error!("Failed to process DPad repeat: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::DwellClick => self.on_dwell_click(),
+                    CompositeCommand::CheckDebounce(cap) => {
+                        if let Err(e) = self.process_debounce_check(cap).await {
+                            log::error!("Failed to process debounced event: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::CheckIdle => self.check_idle().await,
+                    CompositeCommand::ProcessIdleWakeEvents => {
+                        self.process_idle_wake_events().await
+                    }
+                    CompositeCommand::ControllerAttachmentChanged(controller, attached) => {
+                        self.on_controller_attachment_changed(controller, attached)
+                            .await;
+                    }
+                    CompositeCommand::SaveDriftCompensation => {
+                        self.drift_save_scheduled = false;
+                        self.save_drift_compensation();
+                    }
+                    CompositeCommand::UpdateBatteryLed => self.update_battery_led(),
+                    CompositeCommand::UpdateForceFeedback => {
+                        self.ff_engine_update_scheduled = false;
+                        self.update_force_feedback().await;
+                    }
                     CompositeCommand::RemoveRecentEvent(cap) => {
                         self.translated_recent_events.remove(&cap);
                     }
-                    CompositeCommand::SetInterceptActivation(activation_caps, target_cap) => {
-                        self.set_intercept_activation(activation_caps, target_cap)
+                    CompositeCommand::GetInterceptActivation(sender) => {
+                        let activation = (
+                            self.intercept_activation_caps.clone(),
+                            self.intercept_mode_target_cap.clone(),
+                        );
+                        if let Err(e) = sender.send(activation).await {
+                            log::error!("Failed to send intercept activation: {:?}", e);
+                        }
+                    }
+                    CompositeCommand::SetInterceptActivation(caps, target_cap, sender) => {
+                        let result = self.set_intercept_activation(caps, target_cap);
+                        if let Err(e) = sender.send(result).await {
+                            log::error!("Failed to send set intercept activation result: {:?}", e);
+                        }
                     }
                     CompositeCommand::Stop => {
                         log::debug!("Got STOP signal. Stopping CompositeDevice: {dbus_path}");
@@ -515,15 +1461,24 @@ impl CompositeDevice {
                 }
             }
 
+            // Write the events of any input frames that are still open
+            self.flush_target_frames().await;
+
             // If no source devices remain after processing the queue, stop
             // the device.
-            if devices_removed && self.source_devices_used.is_empty() {
+            if devices_removed && self.source_devices_used.is_empty() && !self.mirrors_disconnect()
+            {
                 log::debug!("No source devices remain. Stopping CompositeDevice {dbus_path}");
                 break 'main;
             }
         }
         log::info!("CompositeDevice stopping: {dbus_path}");
 
+        // Persist any stick drift learned since it was last saved
+        if self.drift_save_scheduled {
+            self.save_drift_compensation();
+        }
+
         // Stop all target devices
         log::debug!("Stopping target devices");
         for (path, target) in &self.target_devices {
@@ -545,6 +1500,10 @@ impl CompositeDevice {
                 log::debug!("Skipping unhiding IIO device: {source_path}");
                 continue;
             }
+            if !source_path.starts_with("/dev/") {
+                log::debug!("Skipping unhiding virtual device: {source_path}");
+                continue;
+            }
             log::debug!("Un-hiding device: {}", source_path);
             if let Err(e) = unhide_device(source_path.clone()).await {
                 log::debug!("Unable to unhide device {source_path}: {:?}", e);
@@ -573,7 +1532,7 @@ impl CompositeDevice {
     /// Return a [CompositeDeviceClient] to communicate with the device while it
     /// is running
     pub fn client(&self) -> CompositeDeviceClient {
-        self.tx.clone().into()
+        CompositeDeviceClient::new_with_stats(self.tx.clone(), self.overload_stats.clone())
     }
 
     /// Returns an array of all source devices ids being used by this device.
@@ -581,11 +1540,123 @@ impl CompositeDevice {
         self.source_devices_used.clone()
     }
 
+    /// Sets the current hardware platform state and reloads which capabilities
+    /// can be translated based on the conditions in the capability map.
+    pub fn set_platform_state(&mut self, state: PlatformState) {
+        if self.platform_state == state {
+            return;
+        }
+        log::debug!("Setting platform state to: {state:?}");
+        self.platform_state = state;
+        if self.capability_map.is_none() {
+            return;
+        }
+        if let Err(e) = self.load_capability_map() {
+            log::error!("Failed to reload capability map: {e:?}");
+        }
+    }
+
+    /// Sets the Steam app id of the game focused in gamescope. Events in the
+    /// event history are tagged with the app id.
+    pub fn set_focused_app(&mut self, app_id: Option<u32>) {
+        log::debug!("Setting focused app to: {app_id:?}");
+        self.focused_app_id = app_id;
+        self.event_history.set_app_id(app_id);
+    }
+
+    /// Sets the player index of the device and shows it on all source devices
+    /// that have a player indicator. A value of [None] clears the indicator.
+    pub async fn set_player_index(&mut self, index: Option<u8>) {
+        let index = index.filter(|index| *index > 0);
+        log::debug!("Setting player index to: {index:?}");
+        self.player_index = index;
+        for (id, source) in self.source_devices.iter() {
+            if let Err(e) = source.set_player_index(index).await {
+                log::error!("Failed to set player index on source device {id}: {e:?}");
+            }
+        }
+    }
+
     /// Sets the DBus target devices on the [CompositeDevice].
     pub fn set_dbus_devices(&mut self, devices: HashMap<String, TargetDeviceClient>) {
+        if let Some(filter) = self.get_target_filter("dbus") {
+            for path in devices.keys() {
+                self.target_filters.insert(path.clone(), filter.clone());
+            }
+        }
         self.target_dbus_devices = devices;
     }
 
+    /// Returns the event filter defined in the [CompositeDeviceConfig] for the
+    /// given kind of target device (e.g. "dbus", "xb360").
+    fn get_target_filter(&self, kind: &str) -> Option<TargetEventFilter> {
+        let options = self.config.options.as_ref()?;
+        let filters = options.target_filters.as_ref()?;
+        let filter = filters.iter().find(|filter| filter.target == kind)?;
+        Some(filter.into())
+    }
+
+    /// Assign the given target device to the first split target of the same
+    /// kind that does not have a target device yet and return its filter.
+    /// Returns [None] if the device is not in split mode.
+    fn assign_split_target(&mut self, path: &str, kind: &str) -> Option<TargetEventFilter> {
+        let split = self.config.options.as_ref()?.split.as_ref()?;
+        let index = match self.target_split_index.get(path) {
+            Some(index) => *index,
+            None => split
+                .iter()
+                .enumerate()
+                .filter(|(_, filter)| filter.target == kind)
+                .map(|(index, _)| index)
+                .find(|index| !self.target_split_index.values().any(|used| used == index))?,
+        };
+        let filter = split.get(index)?.into();
+        log::debug!("Assigning target device {path} to split target {index}");
+        self.target_split_index.insert(path.to_string(), index);
+        Some(filter)
+    }
+
+    /// Returns the key remap defined in the [CompositeDeviceConfig] for the
+    /// given kind of target device (e.g. "keyboard").
+    fn get_target_key_remap(&self, kind: &str) -> Option<KeyRemapTable> {
+        let options = self.config.options.as_ref()?;
+        let remaps = options.output_remaps.as_ref()?;
+        let remap = remaps.iter().find(|remap| remap.target == kind)?;
+        Some(remap.into())
+    }
+
+    /// Look up and track the event filter and key remap for the given target
+    /// device based on its kind. In split mode, the filter of the split target
+    /// assigned to the device is used instead.
+    async fn load_target_filter(
+        &mut self,
+        path: &str,
+        target: &TargetDeviceClient,
+    ) -> Result<(), Box<dyn Error>> {
+        let kind = match target.get_type().await {
+            Ok(kind) => kind,
+            Err(e) => {
+                return Err(format!("Failed to request target type: {e:?}").into());
+            }
+        };
+        if let Some(remap) = self.get_target_key_remap(kind.as_str()) {
+            log::debug!("Using key remap for target device {path}: {remap:?}");
+            self.target_key_remaps.insert(path.to_string(), remap);
+        }
+        let filter = match self.assign_split_target(path, kind.as_str()) {
+            Some(filter) => filter,
+            None => {
+                let Some(filter) = self.get_target_filter(kind.as_str()) else {
+                    return Ok(());
+                };
+                filter
+            }
+        };
+        log::debug!("Using event filter for target device {path}: {filter:?}");
+        self.target_filters.insert(path.to_string(), filter);
+        Ok(())
+    }
+
     /// Return a list of source device paths (e.g. /dev/hidraw0, /dev/input/event0)
     /// that this composite device is managing
     fn get_source_device_paths(&self) -> Vec<String> {
@@ -605,6 +1676,11 @@ impl CompositeDevice {
                 log::debug!("Skipping hiding IIO device: {source_path}");
                 continue;
             }
+            // Virtual source devices have no device node to hide
+            if !source_path.starts_with("/dev/") {
+                log::debug!("Skipping hiding virtual device: {source_path}");
+                continue;
+            }
             log::debug!("Hiding device: {}", source_path);
             hide_device(source_path).await?;
         }
@@ -621,6 +1697,11 @@ impl CompositeDevice {
             }
 
             let source_tx = source_device.client();
+            if self.player_index.is_some() {
+                if let Err(e) = source_tx.set_player_index(self.player_index).await {
+                    log::error!("Failed to set player index on source device {device_id}: {e:?}");
+                }
+            }
             self.source_devices.insert(device_id.clone(), source_tx);
             let tx = self.tx.clone();
 
@@ -631,15 +1712,27 @@ impl CompositeDevice {
                 SourceIioImuInterface::listen_on_dbus(self.conn.clone(), device.clone()).await?;
             }
 
-            self.source_device_tasks.spawn(async move {
-                if let Err(e) = source_device.run().await {
-                    log::error!("Failed running device: {:?}", e);
-                }
+            let span = tracing::info_span!("source_device", id = %device_id);
+            let task = async move {
+                let stalled = match source_device.run().await {
+                    Ok(_) => false,
+                    Err(e) => {
+                        log::error!("Failed running device: {:?}", e);
+                        e.is::<TaskStalled>()
+                    }
+                };
                 log::debug!("Source device closed");
-                if let Err(e) = tx.send(CompositeCommand::SourceDeviceStopped(device)).await {
+                // Source devices that stopped responding are restarted
+                let cmd = if stalled {
+                    CompositeCommand::SourceDeviceStalled(device)
+                } else {
+                    CompositeCommand::SourceDeviceStopped(device)
+                };
+                if let Err(e) = tx.send(cmd).await {
                     log::error!("Failed to send device stop command: {:?}", e);
                 }
-            });
+            };
+            self.source_device_tasks.spawn(task.instrument(span));
         }
         log::debug!("All source device tasks started");
         Ok(())
@@ -668,6 +1761,28 @@ impl CompositeDevice {
         let cap = event.as_capability();
         log::trace!("Event capability: {:?}", cap);
 
+        // Sync events mark the end of an input frame from the source device
+        if cap == Capability::Sync {
+            self.flush_target_frames().await;
+            return Ok(());
+        }
+
+        // Correct inputs that report inverted logic (e.g. normally-closed
+        // switches) before anything else looks at their state
+        let event = self.inverter.process(event);
+        let event = match self.source_quirks.get(&device_id) {
+            Some(quirks) => quirks.process(event),
+            None => event,
+        };
+
+        let now = Instant::now();
+        self.event_history
+            .push(&device_id, cap.clone(), event.get_value(), now);
+        if let Some(delay) = self.live_values.update(&event, now) {
+            let cmd = CompositeCommand::SignalLiveValues;
+            self.schedule(delay, Some(LIVE_VALUES_KEY), cmd);
+        }
+
         // Only send valid events to the target device(s)
         if cap == Capability::NotImplemented {
             log::trace!(
@@ -677,81 +1792,312 @@ impl CompositeDevice {
             return Ok(());
         }
 
-        // Check if the event needs to be translated based on the
-        // capability map. Translated events will be re-enqueued, so this will
-        // return early.
-        log::trace!(
-            "Translatable capabilities: {:?}",
-            self.translatable_capabilities
+        // Motion sensors constantly emit events, so they do not count as
+        // activity when checking if the device is idle.
+        let is_motion = matches!(
+            cap,
+            Capability::Gamepad(Gamepad::Accelerometer | Gamepad::Gyro)
         );
-        if self.capability_map.is_some() && self.translatable_capabilities.contains(&cap) {
-            log::trace!("Capability mapping found for event");
-            self.translate_capability(&event).await?;
+        if !is_motion {
+            self.on_source_activity().await;
+        }
+
+        // Hold input until the target devices that were stopped when idle are
+        // attached again. Motion events are dropped since only their latest
+        // values matter.
+        if let Some(events) = self.idle_wake_events.as_mut() {
+            if !is_motion && events.len() < IDLE_WAKE_EVENTS_MAX {
+                events.push((device_id, event));
+            }
             return Ok(());
         }
-        self.handle_event(event).await?;
 
-        Ok(())
-    }
+        // Suspended target devices do not receive any events while idle
+        if self.is_idle && self.idle_action() == "suspend" {
+            return Ok(());
+        }
 
-    /// Process a single output event from a target device.
-    async fn process_output_event(&mut self, event: OutputEvent) -> Result<(), Box<dyn Error>> {
-        //log::trace!("Received output event: {:?}", event);
+        // Drop events that conflict with a higher priority source device
+        if !self.source_priority.is_empty() && !self.source_priority.process(&device_id, &event) {
+            return Ok(());
+        }
 
-        // Handle any output events that need to upload FF effect data
-        if let OutputEvent::Uinput(uinput) = event.borrow() {
-            match uinput {
-                UinputOutputEvent::FFUpload(id, data, target_dev) => {
-                    // If this effect was already uploaded, just return the id
-                    // back to the target device and inform all source devices
-                    // to update the effect with the given data.
-                    if let Some(source_effect_ids) = self.ff_effect_id_source_map.get(id) {
-                        for (source_id, source_effect_id) in source_effect_ids.iter() {
-                            let Some(source) = self.source_devices.get(source_id) else {
-                                continue;
-                            };
-                            log::debug!("Updating effect {source_effect_id} from {source_id}");
-                            if let Err(e) = source.update_effect(*source_effect_id, *data).await {
-                                log::error!("Error updating effect '{id}' on {source_id}: {e:?}");
-                            }
-                        }
-                        target_dev.send(Some(*id))?;
-                        return Ok(());
-                    }
+        // Physical inputs captured for configurators are not processed
+        if self.input_capture.process(&event) {
+            log::trace!("Intercepted captured input: {:?}", event);
+            self.scheduler.cancel_key(INPUT_CAPTURE_KEY);
+            return Ok(());
+        }
 
-                    // Upload the effect data to the source devices
-                    let mut source_effect_ids = HashMap::new();
-                    for (source_id, source) in self.source_devices.iter() {
-                        log::debug!("Uploading effect to {source_id}");
-                        match source.upload_effect(*data).await {
-                            Ok(source_effect_id) => {
-                                // An effect ID of -1 indicates the device does not support
-                                // FF events.
-                                if source_effect_id == -1 {
-                                    continue;
-                                }
-                                log::debug!("Successfully uploaded effect to {source_id} with source effect id {source_effect_id}");
-                                source_effect_ids.insert(source_id.clone(), source_effect_id);
-                            }
-                            Err(e) => {
-                                log::error!("Error uploading effect to {source_id}: {e:?}");
-                            }
-                        }
-                    }
+        // Detect motion gestures and leaning from the accelerometer
+        if cap == Capability::Gamepad(Gamepad::Accelerometer) {
+            let value = event.get_value();
+            if let Some(gesture) = self.motion_gestures.process(&value, now) {
+                self.emit_motion_gesture(gesture).await?;
+            }
+            let leans = self
+                .motion_lean
+                .as_mut()
+                .map(|detector| detector.process(&value, now))
+                .unwrap_or_default();
+            for lean in leans {
+                log::debug!("Detected motion lean: {lean:?}");
+                self.handle_event(lean).await?;
+            }
+        }
 
-                    // If no source devices uploaded the effect, don't bother
-                    // allocating an effect id.
-                    if source_effect_ids.is_empty() {
-                        log::debug!("No source device available to handle FF effect");
-                        target_dev.send(None)?;
-                    }
+        // Keep track of pressed source buttons
+        if let Capability::Gamepad(Gamepad::Button(button)) = &cap {
+            if event.pressed() {
+                self.source_buttons_pressed.insert(button.clone());
+                self.check_dpad_stick_toggle(button).await;
+            } else {
+                self.source_buttons_pressed.remove(button);
+            }
+        }
 
-                    // If upload was successful, return an effect ID
-                    let id = self.ff_effect_ids.iter().next().copied();
-                    if let Some(id) = id {
-                        log::debug!("Uploaded effect with effect id {id}");
-                        self.ff_effect_ids.remove(&id);
-                        self.ff_effect_id_source_map.insert(id, source_effect_ids);
+        // Process the event through the full event pipeline
+        self.current_source_id = Some(device_id);
+        let result = self
+            .run_pipeline(CompositeStage::DriftCompensation, EventFrame::new(event))
+            .await;
+        self.current_source_id = None;
+        result
+    }
+
+    /// Pass the given source event through the drift compensator. Saving the
+    /// learned drift is scheduled if it changed.
+    fn compensate_drift(&mut self, event: NativeEvent) -> NativeEvent {
+        let Some(compensator) = self.drift_compensator.as_mut() else {
+            return event;
+        };
+        let event = compensator.process(event, Instant::now());
+        if compensator.take_unsaved() && !self.drift_save_scheduled {
+            self.drift_save_scheduled = true;
+            let cmd = CompositeCommand::SaveDriftCompensation;
+            self.schedule(DRIFT_SAVE_DELAY, None, cmd);
+        }
+        event
+    }
+
+    /// Save the learned stick drift of this device
+    fn save_drift_compensation(&self) {
+        let Some(compensator) = self.drift_compensator.as_ref() else {
+            return;
+        };
+        let centers = compensator.centers();
+        log::debug!("Saving stick drift to {:?}: {centers:?}", self.drift_path);
+        if let Err(e) = drift::save_centers(&self.drift_path, &centers) {
+            log::error!("Failed to save stick drift to {:?}: {e}", self.drift_path);
+        }
+    }
+
+    /// Set the color of the battery LED from the current battery state and
+    /// schedule the next update.
+    fn update_battery_led(&mut self) {
+        let Some(policy) = self.battery_led.as_mut() else {
+            return;
+        };
+        if policy.is_overridden_forever() {
+            log::debug!("Battery LED color overridden by an explicit LED color");
+            return;
+        }
+        let now = Instant::now();
+        let mut next = policy.poll_interval();
+        if !policy.is_overridden(now) {
+            match policy.read_state() {
+                Ok(state) => {
+                    let (color, wake_after) = policy.color(&state, now);
+                    if let Some(wake_after) = wake_after {
+                        next = next.min(wake_after);
+                    }
+                    let led = policy.led().to_string();
+                    if let Err(e) = self.output_mapper.set_led_color(&led, color) {
+                        log::warn!("Failed to set battery LED color for '{led}': {e:?}");
+                    }
+                }
+                Err(e) => log::debug!("Failed to read battery state: {e}"),
+            }
+        }
+        let cmd = CompositeCommand::UpdateBatteryLed;
+        self.schedule(next, Some(BATTERY_LED_KEY), cmd);
+    }
+
+    /// Pass the given source event through the debouncer. Returns the event
+    /// if its state change should be emitted now. Otherwise a check is
+    /// scheduled to emit the state change once the input is stable.
+    fn debounce_event(&mut self, event: NativeEvent) -> Option<NativeEvent> {
+        let cap = event.as_capability();
+        let update = self.debouncer.process(event, Instant::now());
+        if let Some(wake_after) = update.wake_after {
+            self.schedule(wake_after, None, CompositeCommand::CheckDebounce(cap));
+        }
+        update.event
+    }
+
+    /// Emit the pending state change of the given debounced input if it has
+    /// been stable long enough.
+    async fn process_debounce_check(&mut self, cap: Capability) -> Result<(), Box<dyn Error>> {
+        let update = self.debouncer.check(&cap, Instant::now());
+        if let Some(wake_after) = update.wake_after {
+            self.schedule(wake_after, None, CompositeCommand::CheckDebounce(cap));
+        }
+        let Some(event) = update.event else {
+            return Ok(());
+        };
+        log::trace!("Emitting debounced event: {event:?}");
+        self.run_pipeline(CompositeStage::CapabilityMap, EventFrame::new(event))
+            .await
+    }
+
+    /// Enable or disable streaming events written to target devices as DBus
+    /// signals.
+    fn set_inspector_enabled(&mut self, enabled: bool) {
+        if enabled == self.inspector.is_some() {
+            return;
+        }
+        log::debug!("Setting input inspector enabled: {enabled}");
+        self.inspector =
+            enabled.then(|| InputInspector::new(self.conn.clone(), self.dbus_path.clone()));
+    }
+
+    /// Enable or disable sending events to the tee sink with the given name
+    fn set_tee_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let Some(sink) = self.tee_sinks.iter_mut().find(|sink| sink.name() == name) else {
+            return Err(format!("No tee sink found with name: {name}"));
+        };
+        if sink.enabled() == enabled {
+            return Ok(());
+        }
+        log::debug!("Setting tee '{name}' enabled: {enabled}");
+        sink.set_enabled(enabled);
+        self.signal_tee_sinks_changed();
+        Ok(())
+    }
+
+    /// Enable or disable a mapping dry run. While enabled, events are
+    /// reported as DBus signals instead of being written to target devices.
+    fn set_dry_run_enabled(&mut self, enabled: bool) {
+        if enabled == self.dry_run.is_some() {
+            return;
+        }
+        log::debug!("Setting mapping dry run enabled: {enabled}");
+        self.dry_run =
+            enabled.then(|| MappingDryRun::new(self.conn.clone(), self.dbus_path.clone()));
+    }
+
+    /// Emit the given event to the input inspector if it is enabled
+    fn inspect_event(&self, event: &NativeEvent) {
+        let Some(inspector) = self.inspector.as_ref() else {
+            return;
+        };
+        let source = self.current_source_id.as_deref().unwrap_or_default();
+        inspector.inspect(source, event);
+    }
+
+    /// Process the given frame of events through the event pipeline, starting
+    /// at the given stage.
+    async fn run_pipeline(
+        &mut self,
+        from: CompositeStage,
+        frame: EventFrame,
+    ) -> Result<(), Box<dyn Error>> {
+        // Take the pipeline so its stages can operate on the composite device
+        let mut pipeline = std::mem::take(&mut self.pipeline);
+        let start = pipeline
+            .stages()
+            .iter()
+            .position(|stage| *stage == from)
+            .unwrap_or_default();
+        let result = pipeline.run(self, start, frame).await;
+        self.pipeline = pipeline;
+        result?;
+
+        Ok(())
+    }
+
+    /// Process a single output event from a target device.
+    async fn process_output_event(&mut self, event: OutputEvent) -> Result<(), Box<dyn Error>> {
+        //log::trace!("Received output event: {:?}", event);
+
+        // Handle any output events that need to upload FF effect data
+        if let OutputEvent::Uinput(uinput) = event.borrow() {
+            match uinput {
+                UinputOutputEvent::FFUpload(id, data, target_dev) => {
+                    // Effects that simple rumble devices cannot play are
+                    // rendered by the force feedback engine instead. With
+                    // audio haptics, all effects are rendered so they can be
+                    // played on the audio sink.
+                    if FFEngine::is_emulated(data) || self.audio_haptics.is_some() {
+                        let id = if self.ff_engine.contains(*id) {
+                            Some(*id)
+                        } else {
+                            self.ff_effect_ids.iter().next().copied()
+                        };
+                        if let Some(id) = id {
+                            log::debug!("Uploaded emulated effect with effect id {id}");
+                            self.ff_effect_ids.remove(&id);
+                            self.ff_engine.upload(id, *data);
+                        }
+                        target_dev.send(id)?;
+                        return Ok(());
+                    }
+
+                    // If this effect was already uploaded, just return the id
+                    // back to the target device and inform all source devices
+                    // to update the effect with the given data.
+                    if let Some(source_effect_ids) = self.ff_effect_id_source_map.get(id) {
+                        for (source_id, source_effect_id) in source_effect_ids.iter() {
+                            let Some(source) = self.source_devices.get(source_id) else {
+                                continue;
+                            };
+                            log::debug!("Updating effect {source_effect_id} from {source_id}");
+                            let data = route_effect(*data, self.source_rumble_side(source_id));
+                            if let Err(e) = source.update_effect(*source_effect_id, data).await {
+                                log::error!("Error updating effect '{id}' on {source_id}: {e:?}");
+                            }
+                        }
+                        target_dev.send(Some(*id))?;
+                        return Ok(());
+                    }
+
+                    // Upload the effect data to the source devices
+                    let mut source_effect_ids = HashMap::new();
+                    for (source_id, source) in self.source_devices.iter() {
+                        if self.source_force_feedback_disabled(source_id) {
+                            continue;
+                        }
+                        log::debug!("Uploading effect to {source_id}");
+                        let data = route_effect(*data, self.source_rumble_side(source_id));
+                        match source.upload_effect(data).await {
+                            Ok(source_effect_id) => {
+                                // An effect ID of -1 indicates the device does not support
+                                // FF events.
+                                if source_effect_id == -1 {
+                                    continue;
+                                }
+                                log::debug!("Successfully uploaded effect to {source_id} with source effect id {source_effect_id}");
+                                source_effect_ids.insert(source_id.clone(), source_effect_id);
+                            }
+                            Err(e) => {
+                                log::error!("Error uploading effect to {source_id}: {e:?}");
+                            }
+                        }
+                    }
+
+                    // If no source devices uploaded the effect, don't bother
+                    // allocating an effect id.
+                    if source_effect_ids.is_empty() {
+                        log::debug!("No source device available to handle FF effect");
+                        target_dev.send(None)?;
+                    }
+
+                    // If upload was successful, return an effect ID
+                    let id = self.ff_effect_ids.iter().next().copied();
+                    if let Some(id) = id {
+                        log::debug!("Uploaded effect with effect id {id}");
+                        self.ff_effect_ids.remove(&id);
+                        self.ff_effect_id_source_map.insert(id, source_effect_ids);
                         target_dev.send(Some(id))?;
                     } else {
                         target_dev.send(None)?;
@@ -772,6 +2118,8 @@ impl CompositeDevice {
                         }
                     }
 
+                    self.ff_engine.erase(effect_id);
+
                     // Add the effect ID to list of available effect ids
                     log::debug!("Erased effect with effect id {effect_id}");
                     self.ff_effect_ids.insert(effect_id);
@@ -785,8 +2133,35 @@ impl CompositeDevice {
             return Ok(());
         }
 
+        // Translate the output event using the capability map output mappings
+        if !self.apply_output_mappings(&event) {
+            return Ok(());
+        }
+
+        // Play or stop effects rendered by the force feedback engine
+        if let OutputEvent::Evdev(input_event) = event {
+            if input_event.event_type().0 == evdev::EventType::FORCEFEEDBACK.0 {
+                let code = input_event.code();
+                if code == FFEffectCode::FF_GAIN.0 {
+                    self.ff_engine.set_gain(input_event.value() as u16);
+                } else if self.ff_engine.contains(code as i16) {
+                    let now = Instant::now();
+                    self.ff_engine.play(code as i16, input_event.value(), now);
+                    self.update_force_feedback().await;
+                    return Ok(());
+                }
+            }
+        }
+
         // TODO: Only write the event to devices that are capabile of handling it
+        let is_force_feedback = event
+            .as_capability()
+            .iter()
+            .all(|cap| *cap == OutputCapability::ForceFeedback);
         for (source_id, source) in self.source_devices.iter() {
+            if is_force_feedback && self.source_force_feedback_disabled(source_id) {
+                continue;
+            }
             // If this is a force feedback event, translate the effect id into
             // the source device's effect id.
             if let OutputEvent::Evdev(input_event) = event {
@@ -832,26 +2207,213 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Render force feedback effects played by the [FFEngine] and write the
+    /// resulting rumble to all source devices. Another update is scheduled
+    /// as long as any effects are playing.
+    async fn update_force_feedback(&mut self) {
+        let rumble = self.ff_engine.render(Instant::now());
+        if rumble != self.ff_engine_rumble {
+            log::trace!("Rendered FF rumble: {rumble:?}");
+            self.ff_engine_rumble = rumble;
+            self.write_rumble(rumble).await;
+        }
+
+        if !self.ff_engine.is_playing() || self.ff_engine_update_scheduled {
+            return;
+        }
+        self.ff_engine_update_scheduled = true;
+        self.schedule(
+            FF_ENGINE_UPDATE_INTERVAL,
+            None,
+            CompositeCommand::UpdateForceFeedback,
+        );
+    }
+
+    /// Write the given rumble to all source devices that support force
+    /// feedback by updating and playing a rumble effect on each device.
+    async fn write_rumble(&mut self, rumble: RumbleMagnitude) {
+        if let Some(audio_haptics) = self.audio_haptics.as_ref() {
+            audio_haptics.set_rumble(rumble);
+        }
+
+        let effect = FFEffectData {
+            direction: 0,
+            trigger: FFTrigger {
+                button: 0,
+                interval: 0,
+            },
+            replay: FFReplay {
+                length: 0,
+                delay: 0,
+            },
+            kind: FFEffectKind::Rumble {
+                strong_magnitude: rumble.strong,
+                weak_magnitude: rumble.weak,
+            },
+        };
+
+        for (source_id, source) in self.source_devices.iter() {
+            if self.source_force_feedback_disabled(source_id) {
+                continue;
+            }
+            let side = self.source_rumble_side(source_id);
+            let effect = route_effect(effect, side);
+
+            // Upload a rumble effect to the source device the first time
+            let source_effect_id = match self.ff_engine_source_effect_ids.get(source_id) {
+                Some(source_effect_id) => {
+                    if let Err(e) = source.update_effect(*source_effect_id, effect).await {
+                        log::error!("Error updating rumble effect on {source_id}: {e:?}");
+                        continue;
+                    }
+                    *source_effect_id
+                }
+                None => match source.upload_effect(effect).await {
+                    // An effect ID of -1 indicates the device does not support
+                    // FF events.
+                    Ok(-1) => continue,
+                    Ok(source_effect_id) => {
+                        self.ff_engine_source_effect_ids
+                            .insert(source_id.clone(), source_effect_id);
+                        source_effect_id
+                    }
+                    Err(e) => {
+                        log::error!("Error uploading rumble effect to {source_id}: {e:?}");
+                        continue;
+                    }
+                },
+            };
+
+            let is_zero = rumble.for_side(side).is_zero();
+            let value = if is_zero { 0 } else { 1 };
+            let event = InputEvent::new_now(
+                evdev::EventType::FORCEFEEDBACK.0,
+                source_effect_id as u16,
+                value,
+            );
+            if let Err(e) = source.write_event(OutputEvent::Evdev(event)).await {
+                log::error!("Failed to write rumble to {source_id}: {e:?}");
+            }
+        }
+    }
+
+    /// Returns true if a device quirk disables force feedback for the given
+    /// source device
+    fn source_force_feedback_disabled(&self, source_id: &str) -> bool {
+        self.source_quirks
+            .get(source_id)
+            .is_some_and(|quirks| quirks.force_feedback_disabled())
+    }
+
+    /// Returns the side the given source device is on for routing rumble
+    fn source_rumble_side(&self, source_id: &str) -> RumbleSide {
+        self.source_rumble_sides
+            .get(source_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Translate and write the given event to the appropriate target devices
     async fn handle_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
-        // Check if we need to reverse the event list.
-        let is_pressed = event.pressed();
+        self.run_pipeline(CompositeStage::AxisTransform, EventFrame::new(event))
+            .await
+    }
+
+    /// Emit the given motion gesture as a short button press
+    async fn emit_motion_gesture(&mut self, gesture: GamepadGesture) -> Result<(), Box<dyn Error>> {
+        log::debug!("Detected motion gesture: {gesture}");
+        let cap = Capability::Gamepad(Gamepad::Gesture(gesture));
+        let release = NativeEvent::new(cap.clone(), InputValue::Bool(false));
+        let cmd = CompositeCommand::HandleEvent(release);
+        self.schedule(GESTURE_PRESS_TIME, None, cmd);
+        let press = NativeEvent::new(cap, InputValue::Bool(true));
+        self.handle_event(press).await
+    }
+
+    /// Update the dwell click with the given event that is about to be
+    /// routed to the target devices
+    fn update_dwell_click(&mut self, event: &NativeEvent) {
+        let Some(dwell_clicker) = self.dwell_clicker.as_mut() else {
+            return;
+        };
+        let action = match (event.as_capability(), event.get_value()) {
+            (Capability::Mouse(Mouse::Motion), InputValue::Vector2 { x, y }) => {
+                // Translated motion is a velocity that keeps moving the
+                // pointer, while other motion is relative.
+                if event.is_translated() {
+                    dwell_clicker.on_velocity(x, y)
+                } else {
+                    dwell_clicker.on_motion(x.unwrap_or_default(), y.unwrap_or_default())
+                }
+            }
+            (Capability::Mouse(Mouse::Button(_)), _) => {
+                // The user clicked on their own
+                dwell_clicker.reset();
+                Some(DwellAction::Cancel)
+            }
+            _ => None,
+        };
+        if let Some(action) = action {
+            self.apply_dwell_action(action);
+        }
+    }
+
+    /// Update the dwell click once the pointer rested for the dwell time
+    fn on_dwell_click(&mut self) {
+        let Some(dwell_clicker) = self.dwell_clicker.as_mut() else {
+            return;
+        };
+        if let Some(action) = dwell_clicker.on_dwell() {
+            self.apply_dwell_action(action);
+        }
+    }
+
+    /// Perform the given action of the dwell click
+    fn apply_dwell_action(&mut self, action: DwellAction) {
+        match action {
+            DwellAction::Wait => {
+                let Some(dwell_clicker) = self.dwell_clicker.as_ref() else {
+                    return;
+                };
+                let after = dwell_clicker.time();
+                self.scheduler.cancel_key(DWELL_CLICK_KEY);
+                self.schedule(after, Some(DWELL_CLICK_KEY), CompositeCommand::DwellClick);
+            }
+            DwellAction::Cancel => {
+                self.scheduler.cancel_key(DWELL_CLICK_KEY);
+            }
+            DwellAction::Click(click) => {
+                self.scheduler.cancel_key(DWELL_CLICK_KEY);
+                log::debug!("Emitting dwell click: {click:?}");
+                let (button, count) = match click {
+                    DwellClick::Left => (MouseButton::Left, 1),
+                    DwellClick::Right => (MouseButton::Right, 1),
+                    DwellClick::Double => (MouseButton::Left, 2),
+                };
+                let cap = Capability::Mouse(Mouse::Button(button));
+                for i in 0..count * 2 {
+                    let pressed = i % 2 == 0;
+                    let event = NativeEvent::new(cap.clone(), InputValue::Bool(pressed));
+                    let after = DWELL_CLICK_PRESS_TIME * i;
+                    self.schedule(after, None, CompositeCommand::WriteEvent(event));
+                }
+            }
+        }
+    }
+
+    /// Track active inputs and check the given translated events for
+    /// intercept. Chords are delayed and written separately. Returns the
+    /// events that should be written to the target devices.
+    async fn process_intercept(
+        &mut self,
+        mut events: Vec<NativeEvent>,
+        is_pressed: bool,
+    ) -> Result<Vec<NativeEvent>, Box<dyn Error>> {
         // Check if this is is a single event or multiple events.
         let mut is_chord = false;
         // Track the delay for chord events.
         let mut sleep_time = 0;
 
-        // Translate the event using the device profile.
-        let mut events = if self.device_profile.is_some() {
-            self.translate_event(&event)
-                .await?
-                .into_iter()
-                .filter_map(|event| self.filter_event(event))
-                .collect()
-        } else {
-            vec![event]
-        };
-
         // Check if we need to reverse the event list.
         if events.len() > 1 {
             //log::trace!("Got chord: {events:?}");
@@ -867,6 +2429,7 @@ impl CompositeDevice {
 
         let intercept = self.intercept_mode == InterceptMode::Pass;
 
+        let mut output = Vec::with_capacity(events.len());
         for event in events {
             let cap = event.as_capability();
 
@@ -903,7 +2466,9 @@ impl CompositeDevice {
                     Gamepad::Axis(_)
                     | Gamepad::Trigger(_)
                     | Gamepad::Accelerometer
-                    | Gamepad::Gyro => {}
+                    | Gamepad::Gyro
+                    | Gamepad::Dial(_)
+                    | Gamepad::Gesture(_) => {}
                 },
                 Capability::Mouse(ref t) => match t {
                     Mouse::Motion => {}
@@ -927,22 +2492,20 @@ impl CompositeDevice {
             // through or miss events if they aren't properly
             // timed.
             if is_chord {
-                let tx = self.tx.clone();
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_millis(sleep_time)).await;
-                    if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                        log::error!("Failed to send chord event command: {:?}", e);
-                    }
-                });
+                self.schedule(
+                    Duration::from_millis(sleep_time),
+                    None,
+                    CompositeCommand::WriteEvent(event),
+                );
                 // Increment the sleep time.
                 sleep_time += 80;
                 continue;
             }
 
             // for single events we can emit immediatly without tokio overhead.
-            self.write_event(event).await?;
+            output.push(event);
         }
-        Ok(())
+        Ok(output)
     }
 
     /// Returns true if this is the first event in intercept_activation_caps, or a follow on event
@@ -963,6 +2526,21 @@ impl CompositeDevice {
         log::debug!("No other buttons are pressed and this is not the first in the list. Do not hold input.");
         false
     }
+    /// Smooth gyro and accelerometer events using the filters configured in
+    /// the device profile. Other events are returned unchanged.
+    fn filter_motion_event(&mut self, mut event: NativeEvent) -> NativeEvent {
+        let filter = match event.as_capability() {
+            Capability::Gamepad(Gamepad::Gyro) => self.gyro_filter.as_mut(),
+            Capability::Gamepad(Gamepad::Accelerometer) => self.accel_filter.as_mut(),
+            _ => None,
+        };
+        if let Some(filter) = filter {
+            let value = filter.update(event.get_value(), Instant::now());
+            event.set_value(value);
+        }
+        event
+    }
+
     // Filter out input-cancelling events that do not come from same
     // capability as the initiator
     fn filter_event(&mut self, event: NativeEvent) -> Option<NativeEvent> {
@@ -1002,14 +2580,22 @@ impl CompositeDevice {
 
     /// Writes the given event to the appropriate target device.
     async fn write_event(&self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        // During a mapping dry run, only report what would be emitted
+        if let Some(dry_run) = self.dry_run.as_ref() {
+            let source = self.current_source_id.as_deref().unwrap_or_default();
+            dry_run.report(source, &event);
+            return Ok(());
+        }
+
+        self.inspect_event(&event);
+        self.target_held.lock().unwrap().update(&event);
         let cap = event.as_capability();
 
         // If this event implements the DBus capability, send the event to DBus devices
         if matches!(cap, Capability::DBus(_)) {
             log::trace!("Emit dbus event: {:?}", event);
-            #[allow(clippy::for_kv_map)]
-            for (_, target) in &self.target_dbus_devices {
-                target.write_event(event.clone()).await?;
+            for (path, target) in &self.target_dbus_devices {
+                self.write_target_event(path, target, event.clone()).await?;
             }
             return Ok(());
         }
@@ -1018,8 +2604,8 @@ impl CompositeDevice {
         // target devices.
         if self.intercept_mode == InterceptMode::Always {
             log::trace!("Emit intercepted event: {:?}", event);
-            for target in self.target_dbus_devices.values() {
-                target.write_event(event.clone()).await?;
+            for (path, target) in &self.target_dbus_devices {
+                self.write_target_event(path, target, event.clone()).await?;
             }
             return Ok(());
         }
@@ -1030,19 +2616,53 @@ impl CompositeDevice {
             && matches!(cap, Capability::Gamepad(_))
         {
             log::trace!("Emit intercepted event: {:?}", event);
-            for target in self.target_dbus_devices.values() {
-                target.write_event(event.clone()).await?;
+            for (path, target) in &self.target_dbus_devices {
+                self.write_target_event(path, target, event.clone()).await?;
             }
             return Ok(());
         }
 
+        // Send a copy of the event to any tee sinks
+        for sink in self.tee_sinks.iter() {
+            sink.send(&event);
+        }
+
+        // Mirror the event to any DBus target devices whose filter explicitly
+        // allows it.
+        for (path, target) in self.target_dbus_devices.iter() {
+            let Some(filter) = self.target_filters.get(path) else {
+                continue;
+            };
+            if !filter.mirrors(&cap) {
+                continue;
+            }
+            log::trace!("Emit mirrored event to {path}: {:?}", event);
+            if let Err(e) = self.write_target_event(path, target, event.clone()).await {
+                log::error!("Failed to write mirrored event to: {path}: {e:?}");
+            }
+        }
+
         // Find all target devices capable of handling this event
         let Some(target_paths) = self.target_devices_by_capability.get(&cap) else {
+            // Keyboard and mouse events can be emulated using XTEST if
+            // uinput is unavailable
+            if let Some(xtest) = self.xtest.as_ref() {
+                if matches!(cap, Capability::Keyboard(_) | Capability::Mouse(_)) {
+                    log::trace!("Emit event using XTEST: {:?}", event);
+                    return xtest.write_event(event);
+                }
+            }
             log::trace!("No target devices capable of handling this event: {cap}");
             return Ok(());
         };
         let target_devices: Vec<(&str, &TargetDeviceClient)> = target_paths
             .iter()
+            .filter(|path| {
+                let Some(filter) = self.target_filters.get(*path) else {
+                    return true;
+                };
+                filter.allows(&cap)
+            })
             .filter_map(|path| {
                 let device = self.target_devices.get(path);
                 device.map(|client| (path.as_str(), client))
@@ -1052,45 +2672,156 @@ impl CompositeDevice {
         // Only write the event to devices that are capabile of handling it
         log::trace!("Emit passed event: {:?}", event);
         for (name, target) in target_devices {
-            if let Err(e) = target.write_event(event.clone()).await {
+            let event = match self.target_key_remaps.get(name) {
+                Some(remap) => remap.remap(event.clone()),
+                None => event.clone(),
+            };
+            if let Err(e) = self.write_target_event(name, target, event).await {
                 log::error!("Failed to write event to: {name}: {e:?}");
             }
         }
         Ok(())
     }
 
-    /// Handles writing events that come from the dbus send_event interface
-    async fn write_send_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
-        let cap = event.as_capability();
-        self.is_new_active_event(&cap, event.pressed());
-        // Check to see if the event is in recently translated.
-        // If it is, spawn a task to delay emit the event.
-        let sleep_time = Duration::from_millis(4);
-        let cap = event.as_capability();
-        if self.translated_recent_events.contains(&cap) {
-            log::debug!("Event emitted too quickly. Delaying emission.");
-            let tx = self.tx.clone();
-            tokio::task::spawn(async move {
-                tokio::time::sleep(sleep_time).await;
-                if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                    log::error!("Failed to send delayed event command: {:?}", e);
-                }
-            });
-
-            return Ok(());
+    /// Add the given event to the open input frame of the given target
+    /// device. The frame is written once the source input frame ends, or
+    /// right away if the frame already has a value for the same input.
+    async fn write_target_event(
+        &self,
+        path: &str,
+        target: &TargetDeviceClient,
+        event: NativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        let frame = self.target_frames.lock().unwrap().push(path, event);
+        if let Some(frame) = frame {
+            self.write_target_frame(path, target, frame).await?;
         }
+        Ok(())
+    }
 
-        // Add the event to our list of recently device translated events
-        self.translated_recent_events.insert(event.as_capability());
-
-        // Spawn a task to remove the event from recent translated
-        let tx = self.tx.clone();
-        tokio::task::spawn(async move {
-            tokio::time::sleep(sleep_time).await;
-            if let Err(e) = tx.send(CompositeCommand::RemoveRecentEvent(cap)).await {
-                log::error!("Failed to send remove recent event command: {:?}", e);
+    /// Write the events of all open input frames to their target devices
+    async fn flush_target_frames(&mut self) {
+        if self.target_frames.get_mut().unwrap().is_empty() {
+            return;
+        }
+        let frames = self.target_frames.get_mut().unwrap().take();
+        for (path, events) in frames {
+            let target = self
+                .target_devices
+                .get(&path)
+                .or_else(|| self.target_dbus_devices.get(&path));
+            let Some(target) = target else {
+                log::trace!("Dropping input frame for removed target device: {path}");
+                continue;
+            };
+            if let Err(e) = self.write_target_frame(&path, target, events).await {
+                log::error!("Failed to write events to: {path}: {e:?}");
             }
-        });
+        }
+    }
+
+    /// Write the given events to the given target device as a single input
+    /// frame. If the channel of the target device is full, the events are
+    /// added to the backlog of the target device and written once it catches
+    /// up, so button edges are never lost.
+    async fn write_target_frame(
+        &self,
+        path: &str,
+        target: &TargetDeviceClient,
+        events: Vec<NativeEvent>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Events must wait behind any events in the backlog to keep their order
+        let has_backlog = {
+            let backlog = self.target_backlog.lock().unwrap();
+            backlog.get(path).is_some_and(|queue| !queue.is_empty())
+        };
+        if !has_backlog {
+            match target.write_events(events).await {
+                Err(TargetClientError::TrySendError(TrySendError::Full(
+                    TargetCommand::WriteEvents(events),
+                ))) => {
+                    for event in events {
+                        self.defer_target_event(path, event);
+                    }
+                }
+                result => result?,
+            }
+            return Ok(());
+        }
+        for event in events {
+            self.defer_target_event(path, event);
+        }
+        Ok(())
+    }
+
+    /// Add the given event to the backlog of the given target device
+    fn defer_target_event(&self, path: &str, event: NativeEvent) {
+        log::trace!("Target device {path} is overloaded. Deferring event: {event:?}");
+        let mut backlog = self.target_backlog.lock().unwrap();
+        let queue = backlog.entry(path.to_string()).or_default();
+        queue.push(event, &self.overload_stats);
+    }
+
+    /// Returns true if any target device has events waiting in its backlog
+    fn has_target_backlog(&mut self) -> bool {
+        !self.target_backlog.get_mut().unwrap().is_empty()
+    }
+
+    /// Write the events waiting in the backlog of overloaded target devices
+    /// until their channels are full again.
+    async fn flush_target_backlog(&mut self) {
+        if !self.has_target_backlog() {
+            return;
+        }
+        let mut backlog = std::mem::take(self.target_backlog.get_mut().unwrap());
+        for (path, queue) in backlog.iter_mut() {
+            let target = self
+                .target_devices
+                .get(path)
+                .or_else(|| self.target_dbus_devices.get(path));
+            let Some(target) = target else {
+                queue.clear(&self.overload_stats);
+                continue;
+            };
+            while let Some(event) = queue.pop_front() {
+                match target.write_event(event).await {
+                    Ok(_) => (),
+                    Err(TargetClientError::TrySendError(TrySendError::Full(
+                        TargetCommand::WriteEvent(event),
+                    ))) => {
+                        queue.push_front(event);
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to write event to: {path}: {e:?}");
+                        queue.clear(&self.overload_stats);
+                    }
+                }
+            }
+        }
+        backlog.retain(|_, queue| !queue.is_empty());
+        *self.target_backlog.get_mut().unwrap() = backlog;
+    }
+
+    /// Handles writing events that come from the dbus send_event interface
+    async fn write_send_event(&mut self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        let cap = event.as_capability();
+        self.is_new_active_event(&cap, event.pressed());
+        // Check to see if the event is in recently translated.
+        // If it is, spawn a task to delay emit the event.
+        let sleep_time = Duration::from_millis(4);
+        let cap = event.as_capability();
+        if self.translated_recent_events.contains(&cap) {
+            log::debug!("Event emitted too quickly. Delaying emission.");
+            self.schedule(sleep_time, None, CompositeCommand::WriteEvent(event));
+            return Ok(());
+        }
+
+        // Add the event to our list of recently device translated events
+        self.translated_recent_events.insert(event.as_capability());
+
+        // Schedule the event to be removed from recent translated
+        self.schedule(sleep_time, None, CompositeCommand::RemoveRecentEvent(cap));
 
         //log::trace!("Emitting event: {:?}", event);
         self.write_event(event).await?;
@@ -1099,25 +2830,59 @@ impl CompositeDevice {
     }
 
     // Handles writing chord events that come fron the dbus send_button_chord interface
-    async fn write_chord_events(&self, events: Vec<NativeEvent>) -> Result<(), Box<dyn Error>> {
+    async fn write_chord_events(&mut self, events: Vec<NativeEvent>) -> Result<(), Box<dyn Error>> {
         // Track the delay for chord events.
         let mut sleep_time = 0;
 
         for event in events {
-            let tx = self.tx.clone();
             log::debug!("Send event {:?} at sleep time {sleep_time}", event);
-            tokio::spawn(async move {
-                tokio::time::sleep(Duration::from_millis(sleep_time)).await;
-                if let Err(e) = tx.send(CompositeCommand::WriteEvent(event)).await {
-                    log::error!("Failed to send chord event command: {:?}", e);
-                }
-            });
+            self.schedule(
+                Duration::from_millis(sleep_time),
+                None,
+                CompositeCommand::WriteEvent(event),
+            );
             // Increment the sleep time.
             sleep_time += 80;
         }
         Ok(())
     }
 
+    /// Type the given text from the profile mapping with the given name by
+    /// writing the key sequence needed to produce it under the system keyboard
+    /// layout.
+    fn type_text(&mut self, mapping: &str, text: &str) {
+        if self.text_resolver.is_none() {
+            let names = LayoutNames::from_system();
+            log::debug!("Loading keyboard layout to type text: {names:?}");
+            match TextResolver::from_layout(&names) {
+                Ok(resolver) => self.text_resolver = Some(resolver),
+                Err(e) => {
+                    log::error!("Failed to load keyboard layout to type text: {e:?}");
+                    return;
+                }
+            }
+        }
+        let Some(resolver) = self.text_resolver.as_ref() else {
+            return;
+        };
+        let events = resolver.resolve(text);
+
+        // Delay each key event so applications see every key stroke
+        let key = format!("{TEXT_KEY_PREFIX}{mapping}");
+        for (i, event) in events.into_iter().enumerate() {
+            let after = TEXT_KEY_DELAY * i as u32;
+            let cmd = CompositeCommand::WriteEvent(event);
+            self.schedule(after, Some(key.as_str()), cmd);
+        }
+    }
+
+    /// Schedule the given command to be processed after the given duration.
+    /// Commands scheduled with a key (e.g. the name of a profile mapping
+    /// prefixed by the kind of command) can be cancelled together.
+    fn schedule(&mut self, after: Duration, key: Option<&str>, cmd: CompositeCommand) {
+        self.scheduler.schedule(Instant::now(), after, key, cmd);
+    }
+
     /// Loads the input capabilities to translate from the capability map
     fn load_capability_map(&mut self) -> Result<(), Box<dyn Error>> {
         let Some(map) = self.capability_map.as_ref() else {
@@ -1125,7 +2890,20 @@ impl CompositeDevice {
         };
 
         // Loop over each mapping and try to match source events
+        self.translatable_capabilities.clear();
         for mapping in map.mapping.iter() {
+            // Skip mappings whose platform conditions are not met, unless the
+            // mapping is currently emitted and still needs to be released.
+            if !self.is_mapping_active(mapping)
+                && !self.emitted_mappings.contains_key(&mapping.name)
+            {
+                log::debug!(
+                    "Capability mapping '{}' is inactive with platform state: {:?}",
+                    mapping.name,
+                    self.platform_state
+                );
+                continue;
+            }
             for source_event in mapping.source_events.iter() {
                 let cap = source_event.clone().into();
                 if cap == Capability::NotImplemented {
@@ -1138,6 +2916,72 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Perform the source-specific actions of any capability map output
+    /// mappings that match the given output event. Returns false if the event
+    /// should not be sent to source devices.
+    fn apply_output_mappings(&mut self, event: &OutputEvent) -> bool {
+        let Some(mappings) = self
+            .capability_map
+            .as_ref()
+            .and_then(|map| map.output.as_ref())
+        else {
+            return true;
+        };
+
+        let caps = event.as_capability();
+        let mut passthrough = true;
+        for mapping in mappings.iter() {
+            if !output_map::mapping_matches(mapping, &caps) {
+                continue;
+            }
+            log::trace!("Applying output mapping: {}", mapping.name);
+            if let (Some(led), Some(color)) = (mapping.target_event.led.as_ref(), event.led_color())
+            {
+                if let Err(e) = self.output_mapper.set_led_color(&led.name, color) {
+                    log::warn!("Failed to set LED color for '{}': {e:?}", mapping.name);
+                }
+                // Explicit colors set by games take precedence over the
+                // battery level color.
+                if let Some(policy) = self.battery_led.as_mut() {
+                    if policy.led() == led.name {
+                        policy.set_overridden(Instant::now());
+                    }
+                }
+            }
+            if let Some(action) = mapping.target_event.action.as_ref() {
+                self.action_runner.run(&self.conn, &mapping.name, action);
+            }
+            if !mapping.passthrough.unwrap_or(true) {
+                passthrough = false;
+            }
+        }
+
+        passthrough
+    }
+
+    /// Run the action of the capability map mapping with the given name once
+    /// its source events have been held long enough. The action is skipped if
+    /// the mapping was released in the meantime.
+    fn run_held_action(&mut self, name: &str) {
+        let Some(mapping) = self.emitted_mappings.get(name) else {
+            return;
+        };
+        let Some(action) = mapping.target_event.action.as_ref() else {
+            return;
+        };
+        log::debug!("Running held action for mapping: {name}");
+        self.action_runner.run(&self.conn, name, action);
+    }
+
+    /// Returns true if the platform conditions of the given [CapabilityMapping]
+    /// are satisfied by the current platform state.
+    fn is_mapping_active(&self, mapping: &CapabilityMapping) -> bool {
+        let Some(conditions) = mapping.conditions.as_ref() else {
+            return true;
+        };
+        self.platform_state.satisfies(conditions)
+    }
+
     /// Sets the intercept mode to the given value
     async fn set_intercept_mode(&mut self, mode: InterceptMode) {
         log::debug!("Setting intercept mode to: {:?}", mode);
@@ -1167,7 +3011,10 @@ impl CompositeDevice {
 
     /// Translates the given event into a different event based on the given
     /// [CapabilityMap].
-    async fn translate_capability(&mut self, event: &NativeEvent) -> Result<(), Box<dyn Error>> {
+    async fn translate_capability(
+        &mut self,
+        event: &NativeEvent,
+    ) -> Result<Vec<NativeEvent>, Box<dyn Error>> {
         // Get the capability map to translate input events
         let Some(map) = self.capability_map.as_ref() else {
             return Err("Cannot translate device capability without capability map!".into());
@@ -1188,7 +3035,7 @@ impl CompositeDevice {
                     self.translatable_active_inputs
                 );
             } else {
-                return Ok(());
+                return Ok(vec![]);
             }
         } else if capability_idx.is_some() {
             log::trace!(
@@ -1202,13 +3049,14 @@ impl CompositeDevice {
                 self.translatable_active_inputs
             );
         } else {
-            return Ok(());
+            return Ok(vec![]);
         }
 
         // Keep a list of events to emit. The reason for this is some mapped
         // capabilities may use one or more of the same source capability and
-        // they would release at the same time.
-        let mut emit_queue = Vec::new();
+        // they would release at the same time. Events of a key combination
+        // are queued together so they stay in order.
+        let mut emit_queue: Vec<Vec<NativeEvent>> = Vec::new();
 
         // Loop over each mapping and try to match source events
         for mapping in map.mapping.iter() {
@@ -1232,19 +3080,32 @@ impl CompositeDevice {
 
                 // If no more inputs are being pressed, send a release event.
                 if !has_source_event_pressed {
-                    let cap = mapping.target_event.clone().into();
-                    if cap == Capability::NotImplemented {
+                    // Actions are only run on press. Cancel actions that were
+                    // released before being held long enough.
+                    if mapping.target_event.action.is_some() {
+                        let key = format!("{HELD_ACTION_KEY_PREFIX}{}", mapping.name);
+                        self.scheduler.cancel_key(key.as_str());
+                        self.emitted_mappings.remove(&mapping.name);
                         continue;
                     }
-                    let event = NativeEvent::new(cap, InputValue::Bool(false));
-                    log::trace!("Adding event to emit queue: {:?}", event);
-                    emit_queue.push(event);
+                    // Release the target event before its modifiers
+                    let events: Vec<NativeEvent> = capability_mapping_targets(mapping)
+                        .into_iter()
+                        .rev()
+                        .map(|cap| NativeEvent::new(cap, InputValue::Bool(false)))
+                        .collect();
+                    if events.is_empty() {
+                        continue;
+                    }
+                    log::trace!("Adding events to emit queue: {:?}", events);
+                    emit_queue.push(events);
                     self.emitted_mappings.remove(&mapping.name);
                 }
             }
 
-            // If the event is pressed, check for any matches to send a 'press' event
-            if event.pressed() {
+            // If the event is pressed, check for any matches to send a 'press' event.
+            // Mappings whose platform conditions are not met are skipped.
+            if event.pressed() && self.is_mapping_active(mapping) {
                 let mut is_missing_source_event = false;
                 for source_event in mapping.source_events.iter() {
                     let cap = source_event.clone().into();
@@ -1258,13 +3119,36 @@ impl CompositeDevice {
                 }
 
                 if !is_missing_source_event {
-                    let cap = mapping.target_event.clone().into();
-                    if cap == Capability::NotImplemented {
+                    // Run the action if the mapping targets one instead of
+                    // an input event.
+                    if let Some(action) = mapping.target_event.action.as_ref() {
+                        if !self.emitted_mappings.contains_key(&mapping.name) {
+                            if let Some(hold_ms) = action.hold_ms {
+                                let key = format!("{HELD_ACTION_KEY_PREFIX}{}", mapping.name);
+                                let cmd = CompositeCommand::RunHeldAction(mapping.name.clone());
+                                self.schedule(
+                                    Duration::from_millis(hold_ms),
+                                    Some(key.as_str()),
+                                    cmd,
+                                );
+                            } else {
+                                self.action_runner.run(&self.conn, &mapping.name, action);
+                            }
+                            self.emitted_mappings
+                                .insert(mapping.name.clone(), mapping.clone());
+                        }
+                        continue;
+                    }
+                    // Press the modifiers before the target event
+                    let events: Vec<NativeEvent> = capability_mapping_targets(mapping)
+                        .into_iter()
+                        .map(|cap| NativeEvent::new(cap, InputValue::Bool(true)))
+                        .collect();
+                    if events.is_empty() {
                         continue;
                     }
-                    let event = NativeEvent::new(cap, InputValue::Bool(true));
-                    log::trace!("Adding event to emit queue: {:?}", event);
-                    emit_queue.push(event);
+                    log::trace!("Adding events to emit queue: {:?}", events);
+                    emit_queue.push(events);
                     self.emitted_mappings
                         .insert(mapping.name.clone(), mapping.clone());
                 }
@@ -1274,58 +3158,99 @@ impl CompositeDevice {
         // Emit the translated events. If this translated event has been emitted
         // very recently, delay sending subsequent events of the same type.
         let sleep_time = Duration::from_millis(4);
-        for event in emit_queue {
-            // Check to see if the event is in recently translated.
-            // If it is, spawn a task to delay emit the event.
-            let cap = event.as_capability();
-            if self.translated_recent_events.contains(&cap) {
+        let mut events = Vec::with_capacity(emit_queue.len());
+        for group in emit_queue {
+            // Check to see if any event of the group is in recently translated.
+            // If it is, spawn a task to delay emit the whole group so key
+            // combinations keep their order.
+            let is_recent = group.iter().any(|event| {
+                self.translated_recent_events
+                    .contains(&event.as_capability())
+            });
+            if is_recent {
                 log::debug!("Event emitted too quickly. Delaying emission.");
-                let tx = self.tx.clone();
-                tokio::task::spawn(async move {
-                    tokio::time::sleep(sleep_time).await;
-                    if let Err(e) = tx.send(CompositeCommand::HandleEvent(event)).await {
-                        log::error!("Failed to send delayed event command: {:?}", e);
-                    }
-                });
-
+                for event in group {
+                    self.schedule(sleep_time, None, CompositeCommand::HandleEvent(event));
+                }
                 continue;
             }
 
-            // Add the event to our list of recently device translated events
-            self.translated_recent_events.insert(event.as_capability());
+            for event in group {
+                // Add the event to our list of recently device translated events
+                let cap = event.as_capability();
+                self.translated_recent_events.insert(cap.clone());
 
-            // Spawn a task to remove the event from recent translated
-            let tx = self.tx.clone();
-            tokio::task::spawn(async move {
-                tokio::time::sleep(sleep_time).await;
-                if let Err(e) = tx.send(CompositeCommand::RemoveRecentEvent(cap)).await {
-                    log::error!("Failed to send remove recent event command: {:?}", e);
-                }
-            });
+                // Schedule the event to be removed from recent translated
+                self.schedule(sleep_time, None, CompositeCommand::RemoveRecentEvent(cap));
 
-            log::trace!("Emitting event: {:?}", event);
-            self.handle_event(event).await?;
+                log::trace!("Emitting event: {:?}", event);
+                events.push(event);
+            }
         }
 
-        Ok(())
+        Ok(events)
     }
 
     /// Translates the given event into a Vec of events based on the currently loaded
     /// [DeviceProfile]
     async fn translate_event(
-        &self,
+        &mut self,
         event: &NativeEvent,
     ) -> Result<Vec<NativeEvent>, Box<dyn Error>> {
         // Lookup the profile mapping associated with this event capability. If
         // none is found, return the original un-translated event.
         let source_cap = event.as_capability();
-        if let Some(mappings) = self.device_profile_config_map.get(&source_cap) {
+
+        // Layer switches only change the active layers
+        if !self.layers.is_empty() && self.layers.process_switch(event) {
+            return Ok(vec![]);
+        }
+
+        // Keep track of the finger position on touchpads for click zones
+        if let Capability::Touchpad(touchpad) = &source_cap {
+            if let InputValue::Touch {
+                x: Some(x),
+                y: Some(y),
+                ..
+            } = event.get_value()
+            {
+                self.touch_positions
+                    .insert(touchpad.pad_name().to_string(), (x, y));
+            }
+        }
+
+        // Keep track of the position of axes for absolute touch translation
+        let axis_position = match (&source_cap, event.get_value()) {
+            (Capability::Gamepad(Gamepad::Axis(_)), InputValue::Vector2 { x, y }) => {
+                let position = self
+                    .axis_positions
+                    .entry(source_cap.clone())
+                    .or_insert((0.0, 0.0));
+                if let Some(x) = x {
+                    position.0 = x;
+                }
+                if let Some(y) = y {
+                    position.1 = y;
+                }
+                Some(*position)
+            }
+            _ => None,
+        };
+
+        // Use the mappings of the active layer, if any, instead of the base
+        // profile mappings
+        let mappings = match self.layers.resolve(event) {
+            Some(layer) => self.layers.layer_mappings(layer, &source_cap),
+            None => self.device_profile_config_map.get(&source_cap),
+        };
+        if let Some(mappings) = mappings {
             // Find which mappings in the device profile matches this source event
             let matched_mappings = mappings
                 .iter()
                 .filter(|mapping| mapping.source_matches_properties(event));
 
             let mut events = Vec::new();
+            let mut texts = Vec::new();
             // Based on all found mappings, translate the event
             for mapping in matched_mappings {
                 log::trace!(
@@ -1334,11 +3259,142 @@ impl CompositeDevice {
                     mapping.name
                 );
 
+                // Touchpad clicks with click zones press the mouse button of
+                // the zone the finger is in when the click starts.
+                if let Some(zones) = mapping.source_click_zones() {
+                    let pressed = event.pressed();
+                    let targets = if pressed {
+                        let position = match &source_cap {
+                            Capability::Touchpad(touchpad) => {
+                                self.touch_positions.get(touchpad.pad_name()).copied()
+                            }
+                            _ => None,
+                        };
+                        let targets = click_zone_targets(mapping, zones, position);
+                        self.click_zone_targets
+                            .insert(mapping.name.clone(), targets.clone());
+                        targets
+                    } else {
+                        self.click_zone_targets
+                            .remove(&mapping.name)
+                            .unwrap_or_default()
+                    };
+                    events.extend(targets.into_iter().map(|target_cap| {
+                        NativeEvent::new_translated(
+                            source_cap.clone(),
+                            target_cap,
+                            InputValue::Bool(pressed),
+                        )
+                    }));
+                    continue;
+                }
+
+                // Touch motion translated into mouse motion moves the pointer
+                // relative to the last finger position, or scrolls when the
+                // touch started inside an edge scroll strip.
+                if let Some(motion) = mapping.source_touch_to_mouse_motion() {
+                    let InputValue::Touch {
+                        is_touching, x, y, ..
+                    } = event.get_value()
+                    else {
+                        continue;
+                    };
+                    let pixels = mapping
+                        .target_events
+                        .iter()
+                        .find_map(|target| target.mouse.as_ref()?.motion.as_ref()?.speed_pps)
+                        .unwrap_or(DEFAULT_POINTER_PIXELS);
+                    let state = self
+                        .touch_mouse_states
+                        .entry(mapping.name.clone())
+                        .or_default();
+                    let update = state.update(
+                        is_touching,
+                        x,
+                        y,
+                        pixels as f64,
+                        motion.edge_scroll.as_ref(),
+                    );
+                    events.extend(touch_mouse_events(&source_cap, update));
+                    continue;
+                }
+
+                // Axis to button translations that use sectors, hysteresis,
+                // or key repeat need to keep track of the state of the axis.
+                if let Some(axis) = mapping.source_axis_with_digital_state() {
+                    let (x, y) = match event.get_value() {
+                        InputValue::Vector2 { x, y } => (x, y),
+                        _ => (None, None),
+                    };
+                    let state = self
+                        .axis_button_states
+                        .entry(mapping.name.clone())
+                        .or_default();
+                    let update = state.update(x, y, axis, Instant::now());
+                    if let Some(wake_after) = update.wake_after {
+                        if !state.is_repeat_scheduled() {
+                            state.set_repeat_scheduled(true);
+                            let cmd = CompositeCommand::AxisButtonRepeat(mapping.name.clone());
+                            self.scheduler.schedule(
+                                Instant::now(),
+                                wake_after,
+                                Some(mapping.name.as_str()),
+                                cmd,
+                            );
+                        }
+                    }
+                    events.extend(axis_button_events(&source_cap, mapping, &update));
+                    continue;
+                }
+
+                // Long and double presses need to keep track of the timing
+                // of the source event.
+                if let Some(activator) = mapping.activator() {
+                    let state = self
+                        .activator_states
+                        .entry(mapping.name.clone())
+                        .or_default();
+                    let update = state.update(Some(event.pressed()), &activator, Instant::now());
+                    if let Some(wake_after) = update.wake_after {
+                        let cmd = CompositeCommand::ActivatorTimeout(mapping.name.clone());
+                        self.scheduler.schedule(
+                            Instant::now(),
+                            wake_after,
+                            Some(mapping.name.as_str()),
+                            cmd,
+                        );
+                    }
+                    if let Some(pressed) = update.changed {
+                        events.extend(activator_events(&source_cap, mapping, pressed));
+                    }
+                    continue;
+                }
+
+                // Gyro output is disabled while the ratchet button is held
+                let ratchet_held = self.is_gyro_ratchet_held(mapping);
+
                 // Translate the event into the defined target event(s)
                 for target_event in mapping.target_events.iter() {
+                    // Text is typed once when the source event is pressed
+                    if let Some(text) = target_event.text.as_ref() {
+                        if event.pressed() {
+                            texts.push((mapping.name.clone(), text.clone()));
+                        }
+                        continue;
+                    }
                     // TODO: We can cache this conversion for faster translation
                     let target_cap: Capability = target_event.clone().into();
-                    let result = event.get_value().translate(
+                    // Absolute touch positions need both components of the axis
+                    let value = match (&target_cap, axis_position) {
+                        (Capability::Touchpad(_) | Capability::Touchscreen(_), Some((x, y))) => {
+                            InputValue::Vector2 {
+                                x: Some(x),
+                                y: Some(y),
+                            }
+                        }
+                        _ => event.get_value(),
+                    };
+                    let result = value.translate(
                         &source_cap,
                         &mapping.source_event,
                         &target_cap,
@@ -1378,11 +3434,21 @@ impl CompositeDevice {
                     if matches!(value, InputValue::None) {
                         continue;
                     }
+                    let value = match value {
+                        InputValue::Vector2 { .. } if ratchet_held => InputValue::Vector2 {
+                            x: Some(0.0),
+                            y: Some(0.0),
+                        },
+                        _ => value,
+                    };
 
                     let event = NativeEvent::new_translated(source_cap.clone(), target_cap, value);
                     events.push(event);
                 }
             }
+            for (name, text) in texts {
+                self.type_text(name.as_str(), text.as_str());
+            }
             return Ok(events);
         }
 
@@ -1390,80 +3456,730 @@ impl CompositeDevice {
         Ok(vec![event.clone()])
     }
 
-    /// Executed whenever a source device is added to this [CompositeDevice].
-    async fn on_source_device_added(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
-        if let Err(e) = self.add_source_device(device) {
-            return Err(e.to_string().into());
+    /// Set the mode used to translate between the DPad and the left stick
+    async fn set_dpad_stick_mode(&mut self, mode: DPadStickMode) {
+        if mode == self.dpad_stick.mode() {
+            return;
         }
-        self.run_source_devices().await?;
-
-        // Signal to DBus that source devices have changed
-        self.signal_sources_changed().await;
-
-        log::debug!(
-            "Finished adding source device. All sources: {:?}",
-            self.source_devices_used
-        );
-        Ok(())
+        log::debug!("Setting DPad/stick translation mode: {mode}");
+        for event in self.dpad_stick.set_mode(mode) {
+            if let Err(e) = self.write_event(event).await {
+                log::error!("Failed to release DPad/stick input: {e:?}");
+            }
+        }
+        self.signal_dpad_stick_mode_changed();
     }
 
-    /// Executed whenever a source device is removed from this [CompositeDevice]
-    async fn on_source_device_removed(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
-        let path = device.devnode();
-        let id = device.get_id();
-
-        if let Some(idx) = self.source_device_paths.iter().position(|str| str == &path) {
-            self.source_device_paths.remove(idx);
+    /// Toggle the DPad/stick translation mode if the given source button
+    /// completes the toggle chord from the device profile.
+    async fn check_dpad_stick_toggle(&mut self, button: &GamepadButton) {
+        let Some((chord, mode)) = self.dpad_stick_toggle.as_ref() else {
+            return;
         };
-
-        if let Some(idx) = self.source_devices_used.iter().position(|str| str == &id) {
-            self.source_devices_used.remove(idx);
+        if !chord.contains(button) || !chord.is_subset(&self.source_buttons_pressed) {
+            return;
+        }
+        let mode = if self.dpad_stick.mode() == DPadStickMode::None {
+            *mode
+        } else {
+            DPadStickMode::None
         };
-        self.source_devices_blocked.remove(&id);
+        self.set_dpad_stick_mode(mode).await;
+    }
 
-        // Signal to DBus that source devices have changed
-        self.signal_sources_changed().await;
+    /// Emit a DBus signal when the DPad/stick translation mode changes
+    fn signal_dpad_stick_mode_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
 
-        log::debug!(
-            "Current source device paths: {:?}",
-            self.source_device_paths
-        );
-        log::debug!(
-            "Current source devices used: {:?}",
-            self.source_devices_used
-        );
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
 
-        Ok(())
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .dpad_stick_mode_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send dpad stick mode changed signal: {e:?}");
+            }
+        });
     }
 
-    /// Creates and adds a source device using the given [SourceDeviceInfo]
-    fn add_source_device(
-        &mut self,
-        device: UdevDevice,
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        // Check to see if this source device should be blocked.
-        let mut is_blocked = false;
-        let mut is_blocked_evdev = false;
-        if let Some(source_config) = self.config.get_matching_device(&device) {
-            if let Some(blocked) = source_config.blocked {
-                is_blocked = blocked;
+    /// Set the stick used to select sectors of an on-screen radial menu. While
+    /// a stick is set, its events are not written to the target devices.
+    async fn set_radial_menu_stick(&mut self, stick: RadialMenuStick) {
+        if stick == self.radial_menu.stick() {
+            return;
+        }
+        log::debug!("Setting radial menu stick: {stick}");
+        // Recenter the stick on the target devices before it is consumed
+        if let Some(cap) = stick.capability() {
+            let value = InputValue::Vector2 {
+                x: Some(0.0),
+                y: Some(0.0),
+            };
+            if let Err(e) = self.write_event(NativeEvent::new(cap, value)).await {
+                log::error!("Failed to recenter radial menu stick: {e:?}");
             }
         }
+        self.radial_menu.set_stick(stick);
+        self.signal_radial_menu_stick_changed();
+    }
 
-        let subsystem = device.subsystem();
-
-        let source_device = match subsystem.as_str() {
+    /// Translate events of the radial menu stick into sector selections.
+    /// Returns the events that are not consumed by the radial menu.
+    fn process_radial_menu(&mut self, events: Vec<NativeEvent>) -> Vec<NativeEvent> {
+        if self.radial_menu.stick() == RadialMenuStick::None {
+            return events;
+        }
+        let mut remaining = Vec::with_capacity(events.len());
+        for event in events {
+            if !self.radial_menu.handles(&event) {
+                remaining.push(event);
+                continue;
+            }
+            for menu_event in self.radial_menu.process(&event) {
+                self.signal_radial_menu_event(menu_event);
+            }
+        }
+        remaining
+    }
+
+    /// Emit a DBus signal when the live stick and trigger values change
+    fn signal_live_values_changed(&mut self) {
+        self.live_values.signaled(Instant::now());
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface.live_values_changed(iface_ref.signal_context()).await {
+                log::error!("Failed to send live values changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when the radial menu stick changes
+    fn signal_radial_menu_stick_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .radial_menu_stick_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send radial menu stick changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a sector of the radial menu is selected or
+    /// confirmed
+    fn signal_radial_menu_event(&self, event: RadialMenuEvent) {
+        log::debug!("Radial menu event: {event:?}");
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            let ctx = iface_ref.signal_context();
+            let result = match event {
+                RadialMenuEvent::Selected(sector) => {
+                    CompositeDeviceInterface::radial_sector_selected(ctx, sector).await
+                }
+                RadialMenuEvent::Confirmed(sector) => {
+                    CompositeDeviceInterface::radial_sector_confirmed(ctx, sector).await
+                }
+            };
+            if let Err(e) = result {
+                log::error!("Failed to send radial menu signal: {e:?}");
+            }
+        });
+    }
+
+    /// Returns true if the given mapping translates gyro input and its ratchet
+    /// button is currently held.
+    fn is_gyro_ratchet_held(&self, mapping: &ProfileMapping) -> bool {
+        let Some(button) = mapping
+            .source_event
+            .gamepad
+            .as_ref()
+            .and_then(|gamepad| gamepad.gyro.as_ref())
+            .and_then(|gyro| gyro.ratchet_button.as_ref())
+        else {
+            return false;
+        };
+        let Ok(button) = GamepadButton::from_str(button) else {
+            log::warn!(
+                "Invalid gyro ratchet button in '{}': {button}",
+                mapping.name
+            );
+            return false;
+        };
+        self.source_buttons_pressed.contains(&button)
+    }
+
+    /// Start or stop repeating the output of the given event if it is a held
+    /// DPad button.
+    fn update_dpad_repeat(&mut self, event: &NativeEvent) {
+        let Some(repeater) = self.dpad_repeat.as_mut() else {
+            return;
+        };
+        let Some(held) = repeater.process(event) else {
+            return;
+        };
+        let delay = repeater.delay();
+        let cap = event.as_capability();
+        let key = dpad_repeat_key(&cap);
+        self.scheduler.cancel_key(key.as_str());
+        if held {
+            let cmd = CompositeCommand::DPadRepeat(cap);
+            self.schedule(delay, Some(key.as_str()), cmd);
+        }
+    }
+
+    /// Release and press the given held DPad output again and schedule the
+    /// next repeat.
+    async fn process_dpad_repeat(&mut self, cap: Capability) -> Result<(), Box<dyn Error>> {
+        let Some(repeater) = self.dpad_repeat.as_ref() else {
+            return Ok(());
+        };
+        let events = repeater.repeat(&cap);
+        if events.is_empty() {
+            return Ok(());
+        }
+        let interval = repeater.interval();
+        let key = dpad_repeat_key(&cap);
+        let cmd = CompositeCommand::DPadRepeat(cap);
+        self.schedule(interval, Some(key.as_str()), cmd);
+
+        for event in events {
+            self.run_pipeline(CompositeStage::Intercept, EventFrame::new(event))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Re-evaluate the state of the given axis to button profile mapping to
+    /// continue repeating the button while the axis is deflected.
+    async fn process_axis_button_repeat(&mut self, name: String) -> Result<(), Box<dyn Error>> {
+        let mapping = self
+            .device_profile_config_map
+            .values()
+            .flatten()
+            .chain(self.layers.mappings())
+            .find(|mapping| mapping.name == name)
+            .cloned();
+        let Some(mapping) = mapping else {
+            self.axis_button_states.remove(&name);
+            return Ok(());
+        };
+        let Some(axis) = mapping.source_axis_with_digital_state() else {
+            self.axis_button_states.remove(&name);
+            return Ok(());
+        };
+        let Some(state) = self.axis_button_states.get_mut(&name) else {
+            return Ok(());
+        };
+        state.set_repeat_scheduled(false);
+
+        let update = state.update(None, None, axis, Instant::now());
+        if let Some(wake_after) = update.wake_after {
+            state.set_repeat_scheduled(true);
+            let key = format!("{AXIS_BUTTON_KEY_PREFIX}{name}");
+            let cmd = CompositeCommand::AxisButtonRepeat(name.clone());
+            self.schedule(wake_after, Some(key.as_str()), cmd);
+        }
+        let Some(pressed) = update.changed else {
+            return Ok(());
+        };
+
+        let source_cap: Capability = mapping.source_event.clone().into();
+        let events = axis_button_events(&source_cap, &mapping, &update)
+            .into_iter()
+            .filter_map(|event| self.filter_event(event))
+            .collect();
+        let frame = EventFrame {
+            events,
+            is_pressed: pressed,
+        };
+        self.run_pipeline(CompositeStage::Intercept, frame).await
+    }
+
+    /// Re-evaluate the activator of the profile mapping with the given name
+    /// to check if its source event was held long enough
+    async fn process_activator_timeout(&mut self, name: String) -> Result<(), Box<dyn Error>> {
+        let mapping = self
+            .device_profile_config_map
+            .values()
+            .flatten()
+            .chain(self.layers.mappings())
+            .find(|mapping| mapping.name == name)
+            .cloned();
+        let Some(mapping) = mapping else {
+            self.activator_states.remove(&name);
+            return Ok(());
+        };
+        let Some(activator) = mapping.activator() else {
+            self.activator_states.remove(&name);
+            return Ok(());
+        };
+        let Some(state) = self.activator_states.get_mut(&name) else {
+            return Ok(());
+        };
+
+        let update = state.update(None, &activator, Instant::now());
+        if let Some(wake_after) = update.wake_after {
+            let key = format!("{ACTIVATOR_KEY_PREFIX}{name}");
+            let cmd = CompositeCommand::ActivatorTimeout(name.clone());
+            self.schedule(wake_after, Some(key.as_str()), cmd);
+        }
+        let Some(pressed) = update.changed else {
+            return Ok(());
+        };
+
+        let source_cap: Capability = mapping.source_event.clone().into();
+        let events = activator_events(&source_cap, &mapping, pressed)
+            .into_iter()
+            .filter_map(|event| self.filter_event(event))
+            .collect();
+        let frame = EventFrame {
+            events,
+            is_pressed: pressed,
+        };
+        self.run_pipeline(CompositeStage::Intercept, frame).await
+    }
+
+    /// Executed whenever a source device is added to this [CompositeDevice].
+    async fn on_source_device_added(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
+        // Devices that re-appear during maintenance (e.g. after a firmware
+        // update restarts the controller) are attached when maintenance ends.
+        if let Some(released) = self.source_devices_maintenance.get_mut(&device.get_id()) {
+            log::debug!(
+                "Source device {} is in maintenance mode. Deferring attach.",
+                device.devnode()
+            );
+            *released = Some(device);
+            return Ok(());
+        }
+
+        // Bluetooth devices can fail to attach while they are reconnecting,
+        // so retry them with a backoff before giving up.
+        let id = device.get_id();
+        if let Err(e) = self.add_source_device(device.clone()) {
+            let attempt = self.source_device_attach_attempts.get(&id).unwrap_or(&0) + 1;
+            if !bluetooth::should_retry_attach(&device) || attempt > bluetooth::ATTACH_RETRIES {
+                self.source_device_attach_attempts.remove(&id);
+                return Err(e.to_string().into());
+            }
+            let delay = bluetooth::attach_retry_delay(attempt);
+            log::warn!("Failed to attach bluetooth source device {id}: {e}. Retrying in {delay:?}");
+            self.source_device_attach_attempts
+                .insert(id.clone(), attempt);
+            let key = format!("{ATTACH_KEY_PREFIX}{id}");
+            let cmd = CompositeCommand::SourceDeviceAdded(device);
+            self.schedule(delay, Some(key.as_str()), cmd);
+            return Ok(());
+        }
+        self.source_device_attach_attempts.remove(&id);
+        if let Some(address) = device.bluetooth_address() {
+            log::info!("Attached bluetooth source device {id} with address {address}");
+        }
+        self.run_source_devices().await?;
+
+        // Re-create the target devices that were removed when the last source
+        // device disconnected
+        if let Some(device_types) = self.disconnected_target_devices.take() {
+            log::info!("Source device reconnected, re-creating target devices: {device_types:?}");
+            if let Err(e) = self.set_target_devices(device_types).await {
+                log::error!("Failed to re-create target devices: {e:?}");
+            }
+        }
+
+        // Signal to DBus that source devices have changed
+        self.signal_sources_changed().await;
+
+        log::debug!(
+            "Finished adding source device. All sources: {:?}",
+            self.source_devices_used
+        );
+        Ok(())
+    }
+
+    /// Executed whenever the task of a source device stopped responding and
+    /// was abandoned. The source device is restarted, unless it stalled too
+    /// many times, in which case it is detached.
+    async fn on_source_device_stalled(&mut self, device: UdevDevice) {
+        let id = device.get_id();
+        let attempt = self.source_device_restarts.record(&id, Instant::now());
+        let restart = attempt <= watchdog::MAX_RESTARTS;
+        let error = SourceDeviceError {
+            error: "device stopped responding".to_string(),
+            attempt,
+            max_attempts: watchdog::MAX_RESTARTS + 1,
+            retry_in: restart.then_some(Duration::ZERO),
+        };
+        self.source_device_errors
+            .insert(id.clone(), error.error.clone());
+        self.signal_source_device_error(id.clone(), error);
+
+        if !restart {
+            log::error!("Source device {id} stopped responding too many times. Detaching.");
+            self.source_device_restarts.reset(&id);
+            let cmd = CompositeCommand::SourceDeviceStopped(device);
+            self.schedule(Duration::ZERO, None, cmd);
+            return;
+        }
+
+        log::warn!(
+            "Restarting source device {id} that stopped responding (restart {attempt}/{})",
+            watchdog::MAX_RESTARTS
+        );
+        if let Err(e) = self.on_source_device_removed(device.clone()).await {
+            log::error!("Failed to remove stalled source device: {e:?}");
+        }
+        if let Err(e) = self.on_source_device_added(device.clone()).await {
+            log::error!("Failed to restart source device {id}: {e:?}");
+            let cmd = CompositeCommand::SourceDeviceStopped(device);
+            self.schedule(Duration::ZERO, None, cmd);
+        }
+    }
+
+    /// Executed whenever a source device is removed from this [CompositeDevice]
+    async fn on_source_device_removed(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
+        let path = device.devnode();
+        let id = device.get_id();
+
+        if let Some(idx) = self.source_device_paths.iter().position(|str| str == &path) {
+            self.source_device_paths.remove(idx);
+        };
+        self.source_resume_reports.remove(&path);
+
+        // Stop retrying to attach the device if it disconnected
+        if self.source_device_attach_attempts.remove(&id).is_some() {
+            let key = format!("{ATTACH_KEY_PREFIX}{id}");
+            self.scheduler.cancel_key(key.as_str());
+        }
+
+        if let Some(idx) = self.source_devices_used.iter().position(|str| str == &id) {
+            self.source_devices_used.remove(idx);
+        };
+        self.source_devices_blocked.remove(&id);
+        self.ff_engine_source_effect_ids.remove(&id);
+        self.source_rumble_sides.remove(&id);
+        self.source_quirks.remove(&id);
+        self.source_priority.remove_source(&id);
+        if let Some((controller, attached)) = self.detachable.remove_source(&id) {
+            self.on_controller_attachment_changed(controller, attached)
+                .await;
+        }
+
+        // Remove the target devices when the last source device disconnects,
+        // so games see the controller disconnect too
+        if self.source_devices_used.is_empty()
+            && self.mirrors_disconnect()
+            && self.disconnected_target_devices.is_none()
+        {
+            let device_types = self.get_target_device_types().await;
+            log::info!(
+                "Last source device disconnected, removing target devices: {device_types:?}"
+            );
+            if let Err(e) = self.set_target_devices(Vec::new()).await {
+                log::error!("Failed to remove target devices: {e:?}");
+            }
+            self.disconnected_target_devices = Some(device_types);
+        }
+
+        // Signal to DBus that source devices have changed
+        self.signal_sources_changed().await;
+
+        log::debug!(
+            "Current source device paths: {:?}",
+            self.source_device_paths
+        );
+        log::debug!(
+            "Current source devices used: {:?}",
+            self.source_devices_used
+        );
+
+        Ok(())
+    }
+
+    /// Ask the input manager to attach the source device with the given path
+    /// to this device, or to detach it from this device, and send the result
+    /// to the given channel. Source devices are tracked by the input manager,
+    /// so it performs the change.
+    fn request_source_device_change(
+        &self,
+        device_path: String,
+        attach: bool,
+        sender: mpsc::Sender<Result<(), String>>,
+    ) {
+        let manager = self.manager.clone();
+        let composite_path = self.dbus_path.clone();
+        tokio::task::spawn(async move {
+            let (tx, mut rx) = mpsc::channel(1);
+            let cmd = if attach {
+                ManagerCommand::AttachSourceDevice {
+                    device_path,
+                    composite_path,
+                    sender: tx,
+                }
+            } else {
+                ManagerCommand::DetachSourceDevice {
+                    device_path,
+                    composite_path,
+                    sender: tx,
+                }
+            };
+            let result = match manager.send(cmd).await {
+                Ok(_) => match rx.recv().await {
+                    Some(result) => result.map_err(|e| e.to_string()),
+                    None => Err("Channel closed waiting for response from input manager".into()),
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = sender.send(result).await {
+                log::error!("Failed to send source device change result: {:?}", e);
+            }
+        });
+    }
+
+    /// Stop the given source device that was detached from this device. The
+    /// device is removed from this device once the input manager removes it.
+    async fn detach_source_device(&mut self, device: UdevDevice, unhide: bool) {
+        let id = device.get_id();
+        let path = device.devnode();
+        log::info!("Detaching source device {path}");
+        if let Some(source) = self.source_devices.get(&id) {
+            self.source_device_errors
+                .insert(id.clone(), "detached".to_string());
+            if let Err(e) = source.stop().await {
+                log::error!("Failed to stop detached source device {path}: {e:?}");
+            }
+        }
+        if unhide && path.starts_with("/dev/") {
+            if let Err(e) = unhide_device(path.clone()).await {
+                log::warn!("Unable to unhide device {path}: {e:?}");
+            }
+        }
+    }
+
+    /// Temporarily release the hidraw source device with the given path so
+    /// other programs (like vendor firmware updaters) can access it. The source
+    /// device is stopped and un-hidden, and is re-attached after the given
+    /// timeout or when maintenance mode is exited.
+    async fn enter_maintenance_mode(
+        &mut self,
+        path: String,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.source_device_paths.contains(&path) {
+            return Err(format!("Source device {path} is not managed by this device").into());
+        }
+        let Some(name) = path.strip_prefix("/dev/") else {
+            return Err(format!("Invalid source device path: {path}").into());
+        };
+        let device = UdevDevice::from_devnode("/dev", name);
+        if device.subsystem() != "hidraw" {
+            return Err(format!("Source device {path} is not a hidraw device").into());
+        }
+        let id = device.get_id();
+        if self.source_devices_maintenance.contains_key(&id) {
+            return Err(format!("Source device {path} is already in maintenance mode").into());
+        }
+        let Some(source) = self.source_devices.get(&id) else {
+            return Err(format!("Source device {path} is not running").into());
+        };
+        log::info!("Releasing source device {path} for maintenance for {timeout:?}");
+
+        // Stop the source device so it releases the hidraw device
+        self.source_devices_maintenance.insert(id.clone(), None);
+        if let Err(e) = source.stop().await {
+            self.source_devices_maintenance.remove(&id);
+            return Err(format!("Failed to stop source device {path}: {e:?}").into());
+        }
+        if let Err(e) = unhide_device(path.clone()).await {
+            log::warn!("Unable to unhide device {path}: {e:?}");
+        }
+
+        // Re-attach the device after the timeout
+        let key = format!("{MAINTENANCE_KEY_PREFIX}{path}");
+        let cmd = CompositeCommand::ExitMaintenanceMode(path.clone());
+        self.schedule(timeout, Some(key.as_str()), cmd);
+
+        Ok(())
+    }
+
+    /// Wait for the next significant physical input from any source device and
+    /// send it to the given channel. Nothing is sent if no input is received
+    /// before the given timeout.
+    fn capture_next_input(
+        &mut self,
+        timeout: Duration,
+        sender: mpsc::Sender<Option<CapturedInput>>,
+    ) {
+        log::debug!("Capturing next input for {timeout:?}");
+        self.scheduler.cancel_key(INPUT_CAPTURE_KEY);
+        self.input_capture.start(sender);
+        let cmd = CompositeCommand::CancelInputCapture;
+        self.schedule(timeout, Some(INPUT_CAPTURE_KEY), cmd);
+    }
+
+    /// Re-attach the source device with the given path that was released for
+    /// maintenance.
+    async fn exit_maintenance_mode(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        let key = format!("{MAINTENANCE_KEY_PREFIX}{path}");
+        self.scheduler.cancel_key(key.as_str());
+        let Some(name) = path.strip_prefix("/dev/") else {
+            return Err(format!("Invalid source device path: {path}").into());
+        };
+        let id = UdevDevice::from_devnode("/dev", name).get_id();
+        let Some(device) = self.source_devices_maintenance.remove(&id) else {
+            return Err(format!("Source device {path} is not in maintenance mode").into());
+        };
+        let Some(device) = device else {
+            log::warn!("Source device {path} did not stop for maintenance");
+            return Ok(());
+        };
+        log::info!(
+            "Re-attaching source device {} after maintenance",
+            device.devnode()
+        );
+        self.on_source_device_added(device).await
+    }
+
+    /// Creates and adds a source device using the given [SourceDeviceInfo]
+    fn add_source_device(
+        &mut self,
+        device: UdevDevice,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Check to see if this source device should be blocked.
+        let mut is_blocked = false;
+        let mut is_blocked_evdev = false;
+        if let Some(source_config) = self.config.get_matching_device(&device) {
+            if let Some(blocked) = source_config.blocked {
+                is_blocked = blocked;
+            }
+            if let Some(power) = source_config.power.as_ref() {
+                self.apply_source_power_config(&device, power);
+            }
+            if let Some(side) = source_config.rumble_side.as_ref() {
+                match RumbleSide::from_str(side.as_str()) {
+                    Ok(side) => {
+                        self.source_rumble_sides.insert(device.get_id(), side);
+                    }
+                    Err(_) => log::warn!("Invalid rumble side for source device: {side}"),
+                }
+            }
+            if let Some(priority) = source_config.priority {
+                self.source_priority
+                    .set_priority(device.get_id().as_str(), priority);
+            }
+            if let Some(detachable) = source_config.detachable.as_ref() {
+                let controller = detachable.controller.clone();
+                let attached = detachable.attached.unwrap_or(true);
+                let id = device.get_id();
+                if let Some(attached) = self.detachable.add_source(&id, &controller, attached) {
+                    let cmd = CompositeCommand::ControllerAttachmentChanged(controller, attached);
+                    self.schedule(Duration::ZERO, None, cmd);
+                }
+            }
+        }
+
+        // Look up any quirks of the source hardware
+        let quirk = if device.is_virtual() {
+            None
+        } else {
+            let paths = get_quirks_paths();
+            find_device_quirk(
+                &paths,
+                device.id_vendor(),
+                device.id_product(),
+                device.id_version(),
+            )
+        };
+        if let Some(quirk) = quirk.as_ref() {
+            log::debug!("Applying device quirks to source device: {quirk:?}");
+            self.source_quirks
+                .insert(device.get_id(), SourceQuirks::new(quirk));
+        }
+
+        let subsystem = device.subsystem();
+
+        let source_device = match subsystem.as_str() {
             "input" => {
                 log::debug!("Adding source device: {:?}", device.name());
                 if is_blocked {
                     is_blocked_evdev = true;
                 }
-                let device = EventDevice::new(device, self.client(), is_blocked)?;
+                // Get any defined config for the event device
+                let config = self
+                    .config
+                    .get_matching_device(&device)
+                    .and_then(|device_config| device_config.evdev);
+                let device = EventDevice::new(device, self.client(), is_blocked, config)?;
                 SourceDevice::Event(device)
             }
             "hidraw" => {
                 log::debug!("Adding source device: {:?}", device.name());
-                let device = HidRawDevice::new(device, self.client())?;
+                let report_length = quirk.as_ref().and_then(|quirk| quirk.report_length);
+                let device = HidRawDevice::new(device, self.client(), report_length)?;
                 SourceDevice::HidRaw(device)
             }
             "iio" => {
@@ -1478,6 +4194,16 @@ impl CompositeDevice {
                 let device = IioDevice::new(device, self.client(), config)?;
                 SourceDevice::Iio(device)
             }
+            "virtual" => {
+                log::debug!("Adding virtual source device: {:?}", device.name());
+                let device = VirtualDevice::new(device, self.client())?;
+                SourceDevice::Virtual(device)
+            }
+            "external" => {
+                log::debug!("Adding external source device: {:?}", device.name());
+                let device = ExternalDevice::new(device, self.client()).await?;
+                SourceDevice::External(device)
+            }
             _ => {
                 return Err(format!(
                     "Unspported subsystem: {subsystem}, unable to add source device {}",
@@ -1496,6 +4222,13 @@ impl CompositeDevice {
                 if self.translatable_capabilities.contains(&cap) {
                     continue;
                 }
+                // Motion gestures are detected from the accelerometer
+                if cap == Capability::Gamepad(Gamepad::Accelerometer) {
+                    let gestures = GESTURE_CAPABILITIES.iter().cloned();
+                    self.capabilities.extend(gestures);
+                    let leans = LEAN_CAPABILITIES.iter().cloned();
+                    self.capabilities.extend(leans);
+                }
                 self.capabilities.insert(cap);
             }
         }
@@ -1515,6 +4248,12 @@ impl CompositeDevice {
             }
         };
 
+        // Keep track of the axis ranges of the source hardware so target
+        // devices can advertise matching ranges.
+        for (axis, info) in source_device.get_abs_info() {
+            self.source_axis_ranges.entry(axis).or_insert(info);
+        }
+
         // TODO: Based on the capability map in the config, translate
         // the capabilities.
         // Keep track of the source device
@@ -1526,15 +4265,250 @@ impl CompositeDevice {
         Ok(())
     }
 
-    /// Load the given device profile from the given path
+    /// Apply the power management quirks from the source device config to the
+    /// given source device.
+    fn apply_source_power_config(&mut self, device: &UdevDevice, power: &SourcePower) {
+        if power.disable_autosuspend.unwrap_or(false) {
+            log::debug!(
+                "Disabling autosuspend for source device: {}",
+                device.devnode()
+            );
+            if let Err(e) = power::disable_autosuspend(device) {
+                log::warn!("Failed to disable autosuspend: {e:?}");
+            }
+        }
+
+        let Some(reports) = power.resume_feature_reports.as_ref() else {
+            return;
+        };
+        if device.subsystem() != "hidraw" {
+            log::warn!(
+                "Resume feature reports are only supported for hidraw devices: {}",
+                device.devnode()
+            );
+            return;
+        }
+        self.source_resume_reports
+            .insert(device.devnode(), reports.clone());
+    }
+
+    /// Load the given device profile. Any profile overlay stays applied on top
+    /// of the new profile.
     pub fn load_device_profile(&mut self, profile: DeviceProfile) -> Result<(), Box<dyn Error>> {
         log::debug!("Loading device profile {}", profile.name);
+        let effective = self.apply_profile_overlay(&profile, self.profile_overlay.as_ref())?;
+        self.apply_device_profile(effective)?;
+        self.base_profile = Some(profile);
+        Ok(())
+    }
+
+    /// Release all inputs that are held on target devices and replay the
+    /// held source inputs through the current device profile. Called after
+    /// the device profile changes, so inputs pressed with the old mappings do
+    /// not get stuck and inputs that are still held apply the new mappings.
+    async fn resync_held_inputs(&mut self) {
+        let releases = self.target_held.lock().unwrap().release_events();
+        if !releases.is_empty() {
+            log::debug!("Releasing held target inputs: {releases:?}");
+            for event in releases {
+                if let Err(e) = self.write_event(event).await {
+                    log::error!("Failed to release held input: {e:?}");
+                }
+            }
+            self.active_inputs.clear();
+            self.flush_target_frames().await;
+        }
+
+        if self.source_held.is_empty() {
+            return;
+        }
+        for event in self.source_held.events() {
+            log::debug!("Restoring held source input: {event:?}");
+            let (stage, frame) = (CompositeStage::AxisTransform, EventFrame::new(event));
+            if let Err(e) = self.run_pipeline(stage, frame).await {
+                log::error!("Failed to restore held input: {e:?}");
+            }
+        }
+        self.flush_target_frames().await;
+    }
+
+    /// Stack the profile at the given path on top of the base profile, or
+    /// remove the overlay if the path is empty.
+    fn set_profile_overlay_path(&mut self, path: String) -> Result<(), Box<dyn Error>> {
+        let overlay = if path.is_empty() {
+            None
+        } else {
+            Some(DeviceProfile::from_yaml_file(path)?)
+        };
+        self.set_profile_overlay(overlay)
+    }
+
+    /// Stack the given profile on top of the base profile, or remove the
+    /// overlay if none is given.
+    fn set_profile_overlay(
+        &mut self,
+        overlay: Option<DeviceProfile>,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(base) = self.base_profile.clone() else {
+            return Err("No device profile is loaded".into());
+        };
+        let effective = self.apply_profile_overlay(&base, overlay.as_ref())?;
+        if let Some(overlay) = overlay.as_ref() {
+            log::debug!("Applying profile overlay {} to {}", overlay.name, base.name);
+        }
+        self.apply_device_profile(effective)?;
+        self.profile_overlay = overlay;
+        Ok(())
+    }
+
+    /// Returns the given profile with any virtual capabilities defined in the
+    /// capability map resolved and the given overlay applied on top of it.
+    fn apply_profile_overlay(
+        &self,
+        profile: &DeviceProfile,
+        overlay: Option<&DeviceProfile>,
+    ) -> Result<DeviceProfile, Box<dyn Error>> {
+        let aliases = self
+            .capability_map
+            .as_ref()
+            .and_then(|map| map.aliases.as_deref())
+            .unwrap_or_default();
+        let profile = resolve_aliases(profile, aliases)?;
+        let Some(overlay) = overlay else {
+            return Ok(profile);
+        };
+        let overlay = resolve_aliases(overlay, aliases)?;
+        Ok(apply_overlay(&profile, &overlay))
+    }
+
+    /// Apply the given device profile, whose aliases are already resolved
+    fn apply_device_profile(&mut self, profile: DeviceProfile) -> Result<(), Box<dyn Error>> {
         // Remove all outdated capability mappings.
         log::debug!("Clearing old device profile mappings");
+        let mappings = self.device_profile_config_map.values().flatten();
+        for mapping in mappings.chain(self.layers.mappings()) {
+            for prefix in MAPPING_KEY_PREFIXES {
+                let key = format!("{prefix}{}", mapping.name);
+                self.scheduler.cancel_key(key.as_str());
+            }
+        }
         self.device_profile_config_map.clear();
+        self.axis_button_states.clear();
+        self.activator_states.clear();
+        self.click_zone_targets.clear();
+        self.touch_mouse_states.clear();
 
         // Load and parse the device profile
         self.device_profile = Some(profile.name.clone());
+        self.device_profile_config = Some(profile.clone());
+
+        // Configure the layout source events are transformed into
+        self.axis_transform = profile
+            .layout
+            .as_ref()
+            .map(|layout| {
+                AxisTransform::from_str(layout).unwrap_or_else(|_| {
+                    log::warn!("Unknown profile layout: {layout}");
+                    AxisTransform::None
+                })
+            })
+            .unwrap_or_default();
+
+        // Configure the shaped dead zones of the sticks
+        self.stick_deadzones =
+            StickDeadzones::new(profile.stick_deadzones.as_deref().unwrap_or_default());
+
+        // Configure the arcade stick gates
+        self.stick_gates = StickGates::new(profile.stick_gates.as_deref().unwrap_or_default());
+
+        // Configure translation between the DPad and the left stick
+        let dpad_stick = profile.dpad_stick.as_ref();
+        let mode = dpad_stick
+            .and_then(|config| config.mode.as_ref())
+            .map(|mode| {
+                DPadStickMode::from_str(mode).unwrap_or_else(|_| {
+                    log::warn!("Unknown DPad/stick translation mode: {mode}");
+                    DPadStickMode::None
+                })
+            })
+            .unwrap_or_default();
+        self.dpad_stick_toggle = dpad_stick
+            .and_then(|config| config.toggle_chord.as_ref())
+            .map(|chord| {
+                let buttons = chord
+                    .iter()
+                    .filter_map(|button| GamepadButton::from_str(button).ok())
+                    .collect();
+                let toggle_mode = match mode {
+                    DPadStickMode::None => DPadStickMode::DPadToStick,
+                    mode => mode,
+                };
+                (buttons, toggle_mode)
+            });
+        self.dpad_stick.set_mode(mode);
+        self.dpad_stick
+            .set_cardinal_snap(dpad_stick.and_then(|config| config.cardinal_snap.as_ref()));
+
+        // Configure auto-repeat of held DPad buttons
+        if let Some(repeater) = self.dpad_repeat.take() {
+            for cap in repeater.held() {
+                self.scheduler.cancel_key(dpad_repeat_key(&cap).as_str());
+            }
+        }
+        self.dpad_repeat = profile.dpad_repeat.as_ref().map(DPadRepeater::new);
+
+        // Configure lean detection. A lean held with the old profile is
+        // released, so it is not replayed through the new profile.
+        if let Some(mut detector) = self.motion_lean.take() {
+            for release in detector.release_events() {
+                self.source_held.update(&release);
+            }
+        }
+        self.motion_lean = profile.motion_lean.as_ref().map(MotionLeanDetector::new);
+
+        // Configure the chord that activates intercept mode
+        if let Some(config) = profile.intercept_activation.as_ref() {
+            self.load_intercept_activation(config);
+        }
+
+        // Re-create target devices if the axis ranges they should advertise
+        // have changed.
+        let axis_range_overrides = profile.axis_ranges.clone().unwrap_or_default();
+        if axis_range_overrides != self.axis_range_overrides {
+            self.axis_range_overrides = axis_range_overrides;
+            if !self.target_devices.is_empty() {
+                let tx = self.tx.clone();
+                tokio::task::spawn(async move {
+                    if let Err(e) = tx.send(CompositeCommand::RecreateTargetDevices).await {
+                        log::error!("Failed to send recreate target devices: {e:?}");
+                    }
+                });
+            }
+        }
+
+        // Configure any motion sensor smoothing filters
+        let filters = profile.motion_filters.as_ref();
+        self.gyro_filter = filters
+            .and_then(|filters| filters.gyro.as_ref())
+            .and_then(|config| {
+                let filter = MotionFilter::new(config);
+                if filter.is_none() {
+                    log::warn!("Unknown gyro filter kind: {}", config.kind);
+                }
+                filter
+            });
+        self.accel_filter = filters
+            .and_then(|filters| filters.accelerometer.as_ref())
+            .and_then(|config| {
+                let filter = MotionFilter::new(config);
+                if filter.is_none() {
+                    log::warn!("Unknown accelerometer filter kind: {}", config.kind);
+                }
+                filter
+            });
+
+        // Load the layers of mappings that can be switched to
+        self.layers = LayerStack::new(profile.layers.as_deref().unwrap_or_default());
 
         // Loop through every mapping in the profile, extract the source and target events,
         // and map them into our profile map.
@@ -1589,13 +4563,85 @@ impl CompositeDevice {
         Ok(())
     }
 
+    /// Returns the effective configuration of this device as a single YAML
+    /// document, including the capability map, the loaded device profile,
+    /// and any settings changed at runtime.
+    async fn export_active_config(&self) -> Result<String, Box<dyn Error>> {
+        let mut target_devices = Vec::with_capacity(self.target_devices.len());
+        for target in self.target_devices.values() {
+            match target.get_type().await {
+                Ok(kind) => target_devices.push(kind),
+                Err(e) => log::warn!("Failed to get target device type: {e:?}"),
+            }
+        }
+        target_devices.sort();
+
+        let runtime = RuntimeConfig {
+            intercept_mode: format!("{:?}", self.intercept_mode),
+            intercept_activation: capability_strings(self.intercept_activation_caps.clone()),
+            intercept_target: self.intercept_mode_target_cap.to_string(),
+            dpad_stick_mode: self.dpad_stick.mode().to_string(),
+            player_index: self.player_index,
+            active_layers: self.layers.active_layers(),
+            target_devices,
+            focused_app_id: self.focused_app_id,
+        };
+        let config = ActiveConfig {
+            composite_device: self.config.clone(),
+            capability_map: self.capability_map.clone(),
+            profile: self.device_profile_config.clone(),
+            runtime,
+        };
+
+        Ok(config.to_yaml()?)
+    }
+
+    /// Set the capabilities that activate intercept mode when pressed together
+    /// and the capability to emit when it is activated. Fails if the chord is
+    /// empty or this device does not implement all of its capabilities.
     fn set_intercept_activation(
         &mut self,
         activation_caps: Vec<Capability>,
         target_cap: Capability,
-    ) {
+    ) -> Result<(), String> {
+        if activation_caps.is_empty() {
+            return Err("Intercept activation requires at least one capability".into());
+        }
+        let missing = activation_caps
+            .iter()
+            .find(|cap| !self.capabilities.contains(cap));
+        if let Some(cap) = missing {
+            return Err(format!("Device does not implement capability {cap}"));
+        }
+        log::debug!("Setting intercept activation to {activation_caps:?} -> {target_cap}");
         self.intercept_activation_caps = activation_caps;
         self.intercept_mode_target_cap = target_cap;
+        Ok(())
+    }
+
+    /// Set the intercept activation chord defined in the given profile config
+    fn load_intercept_activation(&mut self, config: &InterceptActivationConfig) {
+        let mut activation_caps = Vec::with_capacity(config.chord.len());
+        for cap in config.chord.iter() {
+            let Ok(cap) = Capability::from_str(cap) else {
+                log::warn!("Invalid intercept activation capability: {cap}");
+                return;
+            };
+            activation_caps.push(cap);
+        }
+        let target_cap = match config.target.as_ref() {
+            Some(target) => match Capability::from_str(target) {
+                Ok(cap) => cap,
+                Err(_) => {
+                    log::warn!("Invalid intercept target capability: {target}");
+                    return;
+                }
+            },
+            None => Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+        };
+        if let Err(e) = self.set_intercept_activation(activation_caps, target_cap) {
+            log::warn!("Unable to set intercept activation from profile: {e}");
+        }
     }
 
     /// Adds or removes the given capability to the active inputs and returns true. If an up event is
@@ -1782,6 +4828,25 @@ impl CompositeDevice {
         Ok(false)
     }
 
+    /// Start emulating keyboard and mouse input with the XTEST extension if
+    /// the given keyboard or mouse target device could not be created because
+    /// uinput is unavailable.
+    fn start_xtest_fallback(&mut self, kind: &str) {
+        if self.xtest.is_some() || !matches!(kind, "keyboard" | "mouse") {
+            return;
+        }
+        if xtest::uinput_available() {
+            return;
+        }
+        match XTestDevice::connect() {
+            Ok(device) => {
+                log::info!("uinput is unavailable. Using XTEST for keyboard and mouse input.");
+                self.xtest = Some(device);
+            }
+            Err(e) => log::warn!("Unable to use XTEST for keyboard and mouse: {e:?}"),
+        }
+    }
+
     /// Set the given target devices on the composite device. This will create
     /// new target devices, attach them to this device, and stop/remove any
     /// existing devices.
@@ -1838,6 +4903,9 @@ impl CompositeDevice {
         for (path, target) in targets_to_stop.clone().into_iter() {
             log::debug!("Stopping old target device: {path}");
             self.target_devices.remove(&path);
+            self.target_filters.remove(&path);
+            self.target_key_remaps.remove(&path);
+            self.target_split_index.remove(&path);
             for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
                 target_devices.remove(&path);
             }
@@ -1846,6 +4914,11 @@ impl CompositeDevice {
             }
         }
 
+        // Create the target devices in the configured order
+        if let Some(creation) = self.config.target_creation() {
+            creation.sort(device_types_to_start.as_mut_slice());
+        }
+
         let composite_path = self.dbus_path.clone();
 
         // Create new target devices using the input manager
@@ -1853,7 +4926,12 @@ impl CompositeDevice {
             log::debug!("Requesting to create device: {kind}");
             let (sender, mut receiver) = mpsc::channel(1);
             self.manager
-                .send(ManagerCommand::CreateTargetDevice { kind, sender })
+                .send(ManagerCommand::CreateTargetDevice {
+                    kind: kind.clone(),
+                    axis_ranges: self.axis_ranges(),
+                    device_id: Some(self.stable_id.clone()),
+                    sender,
+                })
                 .await?;
             let Some(response) = receiver.recv().await else {
                 log::warn!("Channel closed waiting for response from input manager");
@@ -1864,6 +4942,7 @@ impl CompositeDevice {
                 Err(e) => {
                     let err = format!("Failed to create target: {e:?}");
                     log::error!("{err}");
+                    self.start_xtest_fallback(kind.as_str());
                     continue;
                 }
             };
@@ -1886,16 +4965,247 @@ impl CompositeDevice {
                 log::error!("Failed to attach target device: {e:?}");
             }
 
-            // Enqueue the target device to wait for the attachment message from
-            // the input manager to prevent multiple calls to set_target_devices()
-            // from mangling attachment.
-            self.target_devices_queued.insert(target_path);
+            // Enqueue the target device to wait for the attachment message from
+            // the input manager to prevent multiple calls to set_target_devices()
+            // from mangling attachment.
+            self.target_devices_queued.insert(target_path);
+        }
+        // Signal change in target devices to DBus
+        // TODO: Check this
+        //self.signal_targets_changed().await;
+
+        Ok(())
+    }
+
+    /// Create the configured target devices in the background if their
+    /// creation is staged. Targets are created one at a time in the configured
+    /// order, waiting for Steam to start first if configured.
+    fn start_staged_target_devices(&self) {
+        let Some(creation) = self.config.target_creation() else {
+            return;
+        };
+        if !creation.is_staged() {
+            return;
+        }
+        let Some(kinds) = self.config.target_device_types() else {
+            return;
+        };
+        let delay = Duration::from_millis(creation.delay_ms.unwrap_or_default());
+        let steam_timeout = creation.wait_for_steam.unwrap_or_default().then(|| {
+            creation
+                .wait_for_steam_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(STEAM_WAIT_TIMEOUT)
+        });
+
+        let tx = self.tx.clone();
+        tokio::task::spawn(async move {
+            if let Some(timeout) = steam_timeout {
+                wait_for_steam(timeout).await;
+            }
+
+            // Without a delay, all targets can be created in order at once
+            if delay.is_zero() {
+                if let Err(e) = tx.send(CompositeCommand::SetTargetDevices(kinds)).await {
+                    log::error!("Error sending composite device command! {e:?}");
+                }
+                return;
+            }
+
+            // Add one target device at a time to the set of targets
+            for count in 1..=kinds.len() {
+                if count > 1 {
+                    tokio::time::sleep(delay).await;
+                }
+                let kinds = kinds[..count].to_vec();
+                log::debug!("Creating staged target devices: {kinds:?}");
+                if let Err(e) = tx.send(CompositeCommand::SetTargetDevices(kinds)).await {
+                    log::error!("Error sending composite device command! {e:?}");
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Returns the absolute axis ranges that target devices should advertise.
+    /// Ranges reported by source devices are used unless they are overridden
+    /// by the device profile.
+    pub fn axis_ranges(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        let mut ranges = self.source_axis_ranges.clone();
+        for config in self.axis_range_overrides.iter() {
+            let Some(axis) = abs_axis_from_str(config.axis.as_str()) else {
+                log::warn!("Unknown axis in axis range override: {}", config.axis);
+                continue;
+            };
+            let current = ranges.get(&axis);
+            let minimum = config.minimum.or(current.map(|info| info.minimum()));
+            let maximum = config.maximum.or(current.map(|info| info.maximum()));
+            let (Some(minimum), Some(maximum)) = (minimum, maximum) else {
+                log::warn!(
+                    "Axis range override for {} needs a minimum and maximum",
+                    config.axis
+                );
+                continue;
+            };
+            let fuzz = config.fuzz.or(current.map(|info| info.fuzz()));
+            let flat = config.flat.or(current.map(|info| info.flat()));
+            let resolution = config.resolution.or(current.map(|info| info.resolution()));
+            let info = AbsInfo::new(
+                minimum,
+                minimum,
+                maximum,
+                fuzz.unwrap_or_default(),
+                flat.unwrap_or_default(),
+                resolution.unwrap_or_default(),
+            );
+            ranges.insert(axis, info);
+        }
+
+        ranges
+    }
+
+    /// Stop and re-create all running target devices so they advertise the
+    /// current axis ranges.
+    async fn recreate_target_devices(&mut self) {
+        let mut device_types = Vec::new();
+        for target in self.target_devices.values() {
+            match target.get_type().await {
+                Ok(kind) => device_types.push(kind),
+                Err(e) => log::error!("Failed to get target device type: {e:?}"),
+            }
+        }
+        if device_types.is_empty() {
+            return;
+        }
+        log::info!("Re-creating target devices with new axis ranges: {device_types:?}");
+        if let Err(e) = self.set_target_devices(Vec::new()).await {
+            log::error!("Failed to stop target devices: {e:?}");
+            return;
+        }
+        if let Err(e) = self.set_target_devices(device_types).await {
+            log::error!("Failed to re-create target devices: {e:?}");
+        }
+    }
+
+    /// Re-create the target device at the given path after it stopped
+    /// responding, if it is attached to this device. Kinds of target devices
+    /// that keep stalling are not re-created.
+    async fn on_target_device_stalled(&mut self, path: String, kind: String) {
+        if self.target_devices.remove(&path).is_none() {
+            return;
+        }
+        self.target_filters.remove(&path);
+        self.target_key_remaps.remove(&path);
+        self.target_split_index.remove(&path);
+        for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
+            target_devices.remove(&path);
+        }
+
+        let attempt = self.target_device_restarts.record(&kind, Instant::now());
+        if attempt > watchdog::MAX_RESTARTS {
+            log::error!("Target device {kind} stopped responding too many times. Removing it.");
+            return;
+        }
+        log::warn!(
+            "Re-creating target device {kind} at {path} that stopped responding (restart {attempt}/{})",
+            watchdog::MAX_RESTARTS
+        );
+
+        // Keep the other target devices running
+        let mut device_types = vec![kind];
+        device_types.extend(self.get_target_device_types().await);
+        if let Err(e) = self.set_target_devices(device_types).await {
+            log::error!("Failed to re-create target device: {e:?}");
+        }
+    }
+
+    /// Returns true if the target devices should be removed while no source
+    /// devices are connected, instead of stopping the composite device
+    fn mirrors_disconnect(&self) -> bool {
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.mirror_disconnect)
+            .unwrap_or(false)
+    }
+
+    /// Returns the kinds of the running target devices
+    async fn get_target_device_types(&self) -> Vec<String> {
+        let mut device_types = Vec::with_capacity(self.target_devices.len());
+        for target in self.target_devices.values() {
+            match target.get_type().await {
+                Ok(kind) => device_types.push(kind),
+                Err(e) => log::error!("Failed to get target device type: {e:?}"),
+            }
+        }
+        device_types
+    }
+
+    /// Executed whenever a detachable controller is attached or detached.
+    /// While the controller is detached, the profile overlay and target
+    /// devices configured for it are used. The previous configuration is
+    /// restored once it is attached again.
+    async fn on_controller_attachment_changed(&mut self, controller: String, attached: bool) {
+        if attached {
+            log::info!("Detachable controller {controller} was attached");
+        } else {
+            log::info!("Detachable controller {controller} was detached");
+        }
+        self.signal_controller_attachment_changed(controller.clone(), attached);
+
+        let config = self
+            .config
+            .options
+            .as_ref()
+            .and_then(|options| options.detachable.as_ref())
+            .and_then(|configs| configs.iter().find(|c| c.controller == controller))
+            .cloned();
+        let Some(config) = config else {
+            return;
+        };
+
+        // Restore the configuration from before the controller was detached
+        if attached {
+            if let Some(overlay) = self.detached_profile_overlays.remove(&controller) {
+                match self.set_profile_overlay(overlay) {
+                    Ok(_) => self.resync_held_inputs().await,
+                    Err(e) => log::error!("Failed to restore profile overlay: {e:?}"),
+                }
+            }
+            if let Some(device_types) = self.detached_target_devices.remove(&controller) {
+                if let Err(e) = self.set_target_devices(device_types).await {
+                    log::error!("Failed to restore target devices: {e:?}");
+                }
+            }
+            return;
         }
-        // Signal change in target devices to DBus
-        // TODO: Check this
-        //self.signal_targets_changed().await;
 
-        Ok(())
+        if let Some(path) = config.profile_overlay {
+            let previous = self.profile_overlay.clone();
+            let result = match DeviceProfile::from_yaml_file(path) {
+                Ok(overlay) => self.set_profile_overlay(Some(overlay)),
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(_) => {
+                    self.detached_profile_overlays
+                        .entry(controller.clone())
+                        .or_insert(previous);
+                    self.resync_held_inputs().await;
+                }
+                Err(e) => log::error!("Failed to apply profile overlay for {controller}: {e:?}"),
+            }
+        }
+        if let Some(device_types) = config.target_devices {
+            let previous = self.get_target_device_types().await;
+            if let Err(e) = self.set_target_devices(device_types).await {
+                log::error!("Failed to set target devices for {controller}: {e:?}");
+                return;
+            }
+            self.detached_target_devices
+                .entry(controller)
+                .or_insert(previous);
+        }
     }
 
     // Deterimines if a given target device kind is already running
@@ -1955,8 +5265,9 @@ impl CompositeDevice {
     ) -> Result<(), Box<dyn Error>> {
         let dbus_path = self.dbus_path.clone();
 
-        // Keep track of all target devices
-        for (path, target) in targets.into_iter() {
+        // Keep track of all target devices in order of their path
+        let ordered_targets: BTreeMap<String, TargetDeviceClient> = targets.into_iter().collect();
+        for (path, target) in ordered_targets {
             // Query the target device for its capabilities
             let caps = match target.get_capabilities().await {
                 Ok(caps) => caps,
@@ -1973,6 +5284,9 @@ impl CompositeDevice {
             }
             log::debug!("Attached device {path} to {dbus_path}");
 
+            // Load any event filter for the kind of target device
+            self.load_target_filter(&path, &target).await?;
+
             // Add the target device
             self.target_devices_queued.remove(&path);
             self.target_devices.insert(path.clone(), target);
@@ -2062,6 +5376,318 @@ impl CompositeDevice {
         });
     }
 
+    /// Emit a DBus signal when a source device reports an error
+    fn signal_source_device_error(&self, device_id: String, error: SourceDeviceError) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            let retry_in_ms = error.retry_in.map(|d| d.as_millis() as u64).unwrap_or(0);
+            if let Err(e) = CompositeDeviceInterface::source_device_error(
+                iface_ref.signal_context(),
+                device_id.as_str(),
+                error.error.as_str(),
+                error.retry_strategy(),
+                error.attempt,
+                error.max_attempts,
+                retry_in_ms,
+            )
+            .await
+            {
+                log::error!("Failed to send source device error signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a detachable controller is attached or detached
+    fn signal_controller_attachment_changed(&self, controller: String, attached: bool) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            let ctxt = iface_ref.signal_context();
+            let result = if attached {
+                CompositeDeviceInterface::controller_attached(ctxt, controller.as_str()).await
+            } else {
+                CompositeDeviceInterface::controller_detached(ctxt, controller.as_str()).await
+            };
+            if let Err(e) = result {
+                log::error!("Failed to send controller attachment signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a source device is detached
+    fn signal_source_device_detached(&self, device_id: String, reason: String) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            if let Err(e) = CompositeDeviceInterface::source_device_detached(
+                iface_ref.signal_context(),
+                device_id.as_str(),
+                reason.as_str(),
+            )
+            .await
+            {
+                log::error!("Failed to send source device detached signal: {e:?}");
+            }
+        });
+    }
+
+    /// Schedule a check to see if the device has become idle after the given
+    /// duration.
+    fn schedule_idle_check(&mut self, after: Duration) {
+        self.schedule(after, None, CompositeCommand::CheckIdle);
+    }
+
+    /// Check to see if no source input has been received within the idle
+    /// timeout. If so, the device will become idle and the configured idle
+    /// action will be performed.
+    async fn check_idle(&mut self) {
+        let Some(timeout) = self.idle_timeout else {
+            return;
+        };
+        if self.is_idle {
+            return;
+        }
+
+        // Re-schedule the check if input was received since the last check
+        let elapsed = self.last_input_time.elapsed();
+        if elapsed < timeout {
+            self.schedule_idle_check(timeout - elapsed);
+            return;
+        }
+
+        log::info!(
+            "No input received for {timeout:?}. Device is idle: {}",
+            self.dbus_path
+        );
+        self.is_idle = true;
+
+        // Stop all target devices if configured to do so. Suspended target
+        // devices are kept, but no events are written to them while idle.
+        let action = self.idle_action();
+        if action == "suspend" {
+            log::info!("Suspending events to idle target devices");
+        }
+        if action == "destroy" {
+            self.target_devices_idle.clear();
+            for target in self.target_devices.values() {
+                match target.get_type().await {
+                    Ok(kind) => self.target_devices_idle.push(kind),
+                    Err(e) => log::error!("Failed to get target device type: {e:?}"),
+                }
+            }
+            log::info!(
+                "Stopping idle target devices: {:?}",
+                self.target_devices_idle
+            );
+            if let Err(e) = self.set_target_devices(Vec::new()).await {
+                log::error!("Failed to stop idle target devices: {e:?}");
+            }
+        }
+
+        self.signal_idle_changed().await;
+    }
+
+    /// Called when an input event is received from a source device. If the
+    /// device is idle, it will become active and any target devices that were
+    /// stopped will be re-created.
+    async fn on_source_activity(&mut self) {
+        self.last_input_time = Instant::now();
+        if !self.is_idle {
+            return;
+        }
+
+        log::info!("Input received. Device is active: {}", self.dbus_path);
+        self.is_idle = false;
+
+        // Re-create any target devices that were stopped when idle. Input is
+        // held until they are attached.
+        if !self.target_devices_idle.is_empty() {
+            let device_types = std::mem::take(&mut self.target_devices_idle);
+            log::info!("Restoring idle target devices: {device_types:?}");
+            self.idle_wake_events = Some(Vec::new());
+            if let Err(e) = self.set_target_devices(device_types).await {
+                log::error!("Failed to restore idle target devices: {e:?}");
+                self.idle_wake_events = None;
+            }
+
+            // Stop holding input if the target devices are not attached in time
+            let cmd = CompositeCommand::ProcessIdleWakeEvents;
+            self.scheduler.cancel_key(IDLE_WAKE_KEY);
+            self.schedule(IDLE_WAKE_TIMEOUT, Some(IDLE_WAKE_KEY), cmd);
+        }
+
+        if let Some(timeout) = self.idle_timeout {
+            self.schedule_idle_check(timeout);
+        }
+        self.signal_idle_changed().await;
+    }
+
+    /// Returns the action to perform on target devices when the device
+    /// becomes idle. E.g. "none", "suspend", or "destroy"
+    fn idle_action(&self) -> &str {
+        self.config
+            .options
+            .as_ref()
+            .and_then(|options| options.idle.as_ref())
+            .and_then(|idle| idle.action.as_deref())
+            .unwrap_or("none")
+    }
+
+    /// Process the source events that were held while the target devices that
+    /// were stopped when idle were re-created
+    async fn process_idle_wake_events(&mut self) {
+        self.scheduler.cancel_key(IDLE_WAKE_KEY);
+        let Some(events) = self.idle_wake_events.take() else {
+            return;
+        };
+        log::debug!("Processing {} events held while waking", events.len());
+        for (device_id, event) in events {
+            let result = self.process_event(device_id, Event::Native(event)).await;
+            if let Err(e) = result {
+                log::error!("Failed to process event held while waking: {e:?}");
+            }
+        }
+    }
+
+    /// Emit a DBus signal when the idle state changes
+    async fn signal_idle_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the idle changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface.idle_changed(iface_ref.signal_context()).await {
+                log::error!("Failed to send idle changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when a tee sink is enabled or disabled
+    fn signal_tee_sinks_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the tee sinks changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface.tee_sinks_changed(iface_ref.signal_context()).await {
+                log::error!("Failed to send tee sinks changed signal: {e:?}");
+            }
+        });
+    }
+
+    /// Emit a DBus signal when the focused app changes
+    async fn signal_focused_app_changed(&self) {
+        let dbus_path = self.dbus_path.clone();
+        let conn = self.conn.clone();
+
+        tokio::task::spawn(async move {
+            // Get the object instance at the given path so we can send DBus signal
+            // updates
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, CompositeDeviceInterface>(dbus_path.clone())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get DBus interface for composite device to signal: {e:?}"
+                    );
+                    return;
+                }
+            };
+
+            // Emit the focused app changed signal
+            let iface = iface_ref.get().await;
+            if let Err(e) = iface
+                .focused_app_id_changed(iface_ref.signal_context())
+                .await
+            {
+                log::error!("Failed to send focused app changed signal: {e:?}");
+            }
+        });
+    }
+
     /// Called when notified by the input manager that system suspend is about
     /// to happen.
     async fn handle_suspend(&mut self) {
@@ -2098,6 +5724,9 @@ impl CompositeDevice {
         for (path, target) in targets_to_stop.into_iter() {
             log::info!("Stopping target device: {path}");
             self.target_devices.remove(&path);
+            self.target_filters.remove(&path);
+            self.target_key_remaps.remove(&path);
+            self.target_split_index.remove(&path);
             for (_, target_devices) in self.target_devices_by_capability.iter_mut() {
                 target_devices.remove(&path);
             }
@@ -2113,6 +5742,14 @@ impl CompositeDevice {
     /// Called when notified by the input manager that system resume is about
     /// to happen.
     async fn handle_resume(&mut self) {
+        // Re-sync the state of source devices that lose it during suspend
+        for (devnode, reports) in self.source_resume_reports.iter() {
+            log::info!("Re-syncing source device after resume: {devnode}");
+            if let Err(e) = power::send_feature_reports(devnode, reports) {
+                log::error!("Failed to re-sync source device {devnode}: {e:?}");
+            }
+        }
+
         log::info!(
             "Restoring target devices: {:?}",
             self.target_devices_suspended
@@ -2136,3 +5773,214 @@ impl CompositeDevice {
         self.target_devices_suspended.clear();
     }
 }
+
+/// Returns the target capabilities of the given capability map mapping in the
+/// order they should be pressed. The keyboard modifiers of a key combination
+/// come before the target event. Returns an empty list if the target event is
+/// not implemented.
+fn capability_mapping_targets(mapping: &CapabilityMapping) -> Vec<Capability> {
+    let target: Capability = mapping.target_event.clone().into();
+    if target == Capability::NotImplemented {
+        return Vec::new();
+    }
+    let mut targets: Vec<Capability> = mapping
+        .modifiers
+        .iter()
+        .flatten()
+        .filter_map(|modifier| match Keyboard::from_str(modifier.as_str()) {
+            Ok(key) => Some(Capability::Keyboard(key)),
+            Err(_) => {
+                log::warn!(
+                    "Invalid modifier in capability mapping '{}': {modifier}",
+                    mapping.name
+                );
+                None
+            }
+        })
+        .collect();
+    targets.push(target);
+    targets
+}
+
+/// Returns the events to press or release the button targets of the given
+/// profile mapping that is activated by a long or double press
+fn activator_events(
+    source_cap: &Capability,
+    mapping: &ProfileMapping,
+    pressed: bool,
+) -> Vec<NativeEvent> {
+    mapping
+        .target_events
+        .iter()
+        .map(|target_event| -> Capability { target_event.clone().into() })
+        .filter(|target_cap| {
+            let is_button = matches!(
+                target_cap,
+                Capability::Keyboard(_)
+                    | Capability::Gamepad(Gamepad::Button(_))
+                    | Capability::Mouse(Mouse::Button(_))
+            );
+            if !is_button {
+                log::warn!(
+                    "Long and double press activators are only supported for button targets in profile mapping '{}': {:?}",
+                    mapping.name,
+                    target_cap
+                );
+            }
+            is_button
+        })
+        .map(|target_cap| {
+            NativeEvent::new_translated(source_cap.clone(), target_cap, InputValue::Bool(pressed))
+        })
+        .collect()
+}
+
+/// Returns the translated button events for the given axis to button profile
+/// mapping if the state of the button changed.
+fn axis_button_events(
+    source_cap: &Capability,
+    mapping: &ProfileMapping,
+    update: &AxisButtonUpdate,
+) -> Vec<NativeEvent> {
+    let Some(pressed) = update.changed else {
+        return Vec::new();
+    };
+    mapping
+        .target_events
+        .iter()
+        .map(|target_event| -> Capability { target_event.clone().into() })
+        .filter(|target_cap| {
+            let is_button = matches!(
+                target_cap,
+                Capability::Keyboard(_)
+                    | Capability::Gamepad(Gamepad::Button(_))
+                    | Capability::Mouse(Mouse::Button(_))
+            );
+            if !is_button {
+                log::warn!(
+                    "Axis sectors, hysteresis, and repeat are only supported for button targets in profile mapping '{}': {:?}",
+                    mapping.name,
+                    target_cap
+                );
+            }
+            is_button
+        })
+        .map(|target_cap| {
+            NativeEvent::new_translated(source_cap.clone(), target_cap, InputValue::Bool(pressed))
+        })
+        .collect()
+}
+
+/// Returns the target capabilities for a touchpad click at the given finger
+/// position. The mouse button of the first zone containing the position is
+/// used, falling back to the mapping's target events if the click is outside
+/// of every zone.
+fn click_zone_targets(
+    mapping: &ProfileMapping,
+    zones: &[TouchClickZone],
+    position: Option<(f64, f64)>,
+) -> Vec<Capability> {
+    let zone = position.and_then(|(x, y)| zones.iter().find(|zone| zone.contains(x, y)));
+    if let Some(zone) = zone {
+        match MouseButton::from_str(zone.button.as_str()) {
+            Ok(button) => return vec![Capability::Mouse(Mouse::Button(button))],
+            Err(_) => log::warn!(
+                "Invalid click zone button in profile mapping '{}': {}",
+                mapping.name,
+                zone.button
+            ),
+        }
+    }
+    mapping
+        .target_events
+        .iter()
+        .map(|target_event| target_event.clone().into())
+        .collect()
+}
+
+/// Returns the events to emit for the given touch to mouse update. Pointer
+/// motion is emitted as an untranslated relative mouse event so the mouse
+/// target moves by the given number of pixels instead of treating it as a
+/// velocity.
+fn touch_mouse_events(source_cap: &Capability, update: TouchMouseUpdate) -> Vec<NativeEvent> {
+    let mut events = Vec::new();
+    if let Some((x, y)) = update.motion {
+        let value = InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        };
+        events.push(NativeEvent::new(Capability::Mouse(Mouse::Motion), value));
+    }
+    for button in update.wheel {
+        let cap = Capability::Mouse(Mouse::Button(button));
+        for pressed in [true, false] {
+            let value = InputValue::Bool(pressed);
+            events.push(NativeEvent::new_translated(
+                source_cap.clone(),
+                cap.clone(),
+                value,
+            ));
+        }
+    }
+    events
+}
+
+/// Sleep until the given deadline. If no deadline is given, this never
+/// completes.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Returns the stable id of the device with the given config and primary
+/// source device, used to persist its state (e.g. learned stick drift or the
+/// identities of its target devices). Devices are identified by their unique
+/// id or serial number if they have one, or else by the physical path they
+/// are connected at, so each controller of the same kind has its own state.
+/// Any character that is not ASCII alphanumeric is replaced, so the id is
+/// safe to use as a file name in any locale.
+fn stable_id(config: &CompositeDeviceConfig, device: &UdevDevice) -> String {
+    let id = [device.uniq(), device.serial_number(), device.phys()]
+        .into_iter()
+        .find(|id| !id.is_empty())
+        .unwrap_or_default();
+    let name = if id.is_empty() {
+        config.name.clone()
+    } else {
+        format!("{}-{id}", config.name)
+    };
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Returns true if a Steam client process is running
+fn is_steam_running() -> bool {
+    let Ok(processes) = ::procfs::process::all_processes() else {
+        return false;
+    };
+    processes
+        .flatten()
+        .filter_map(|process| process.stat().ok())
+        .any(|stat| stat.comm == "steam")
+}
+
+/// Wait until Steam is running or the given timeout has elapsed
+async fn wait_for_steam(timeout: Duration) {
+    let start = Instant::now();
+    while !is_steam_running() {
+        if start.elapsed() >= timeout {
+            log::warn!("Steam did not start within {timeout:?}. Creating target devices anyway.");
+            return;
+        }
+        tokio::time::sleep(STEAM_POLL_INTERVAL).await;
+    }
+    log::debug!("Steam is running. Creating target devices.");
+}
+
+/// Returns the scheduler key of the repeats of the given DPad output
+fn dpad_repeat_key(cap: &Capability) -> String {
+    format!("dpad_repeat:{cap}")
+}
@@ -0,0 +1,130 @@
+use std::{f64::consts::PI, time::Instant};
+
+use crate::{config::MotionFilterConfig, input::event::value::InputValue};
+
+/// Default cutoff frequency in Hz
+const DEFAULT_CUTOFF: f64 = 1.0;
+/// Default speed coefficient of the one-euro filter
+const DEFAULT_BETA: f64 = 0.007;
+/// Default cutoff frequency in Hz used to smooth the speed of the one-euro filter
+const DEFAULT_DERIVATIVE_CUTOFF: f64 = 1.0;
+/// Time between samples to assume for the first sample or when samples arrive
+/// out of order.
+const DEFAULT_SAMPLE_PERIOD: f64 = 1.0 / 400.0;
+
+/// Returns the smoothing factor of a low-pass filter with the given cutoff
+/// frequency for the given time between samples.
+fn smoothing_factor(cutoff: f64, period: f64) -> f64 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / period)
+}
+
+/// Kind of smoothing filter to apply to a single axis
+#[derive(Debug, Clone, PartialEq)]
+enum FilterKind {
+    /// Exponential moving average with a fixed cutoff frequency
+    Ema { cutoff: f64 },
+    /// One-euro filter that adapts its cutoff frequency to the speed of the
+    /// signal, so slow movements are smoothed while fast movements have
+    /// little lag.
+    OneEuro {
+        min_cutoff: f64,
+        beta: f64,
+        derivative_cutoff: f64,
+    },
+}
+
+/// State of the filter for a single axis
+#[derive(Debug, Clone, Default)]
+struct AxisFilter {
+    /// Last filtered value
+    value: Option<f64>,
+    /// Last filtered speed of the value, used by the one-euro filter
+    speed: f64,
+}
+
+impl AxisFilter {
+    /// Filter the given value using the given time between samples
+    fn update(&mut self, kind: &FilterKind, value: f64, period: f64) -> f64 {
+        let Some(last) = self.value else {
+            self.value = Some(value);
+            return value;
+        };
+
+        let cutoff = match kind {
+            FilterKind::Ema { cutoff } => *cutoff,
+            FilterKind::OneEuro {
+                min_cutoff,
+                beta,
+                derivative_cutoff,
+            } => {
+                let speed = (value - last) / period;
+                let alpha = smoothing_factor(*derivative_cutoff, period);
+                self.speed += alpha * (speed - self.speed);
+                min_cutoff + beta * self.speed.abs()
+            }
+        };
+
+        let alpha = smoothing_factor(cutoff, period);
+        let filtered = last + alpha * (value - last);
+        self.value = Some(filtered);
+        filtered
+    }
+}
+
+/// Smooths [InputValue::Vector3] values from motion sensors like gyroscopes and
+/// accelerometers to reduce jitter.
+#[derive(Debug, Clone)]
+pub struct MotionFilter {
+    kind: FilterKind,
+    axes: [AxisFilter; 3],
+    last_update: Option<Instant>,
+}
+
+impl MotionFilter {
+    /// Create a new filter from the given configuration. Returns [None] if
+    /// the configured filter kind is unknown.
+    pub fn new(config: &MotionFilterConfig) -> Option<Self> {
+        let cutoff = config.cutoff.unwrap_or(DEFAULT_CUTOFF);
+        let kind = match config.kind.as_str() {
+            "ema" => FilterKind::Ema { cutoff },
+            "one_euro" => FilterKind::OneEuro {
+                min_cutoff: cutoff,
+                beta: config.beta.unwrap_or(DEFAULT_BETA),
+                derivative_cutoff: config
+                    .derivative_cutoff
+                    .unwrap_or(DEFAULT_DERIVATIVE_CUTOFF),
+            },
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            axes: Default::default(),
+            last_update: None,
+        })
+    }
+
+    /// Filter the given value received at the given time. Values that are not
+    /// [InputValue::Vector3] are returned unchanged.
+    pub fn update(&mut self, value: InputValue, now: Instant) -> InputValue {
+        let InputValue::Vector3 { x, y, z } = value else {
+            return value;
+        };
+
+        let period = self
+            .last_update
+            .and_then(|last| now.checked_duration_since(last))
+            .map(|elapsed| elapsed.as_secs_f64())
+            .filter(|period| *period > 0.0)
+            .unwrap_or(DEFAULT_SAMPLE_PERIOD);
+        self.last_update = Some(now);
+
+        let [ax, ay, az] = &mut self.axes;
+        InputValue::Vector3 {
+            x: x.map(|value| ax.update(&self.kind, value, period)),
+            y: y.map(|value| ay.update(&self.kind, value, period)),
+            z: z.map(|value| az.update(&self.kind, value, period)),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+use crate::{config::MotionFilterConfig, input::event::value::InputValue};
+
+use super::motion_filter::MotionFilter;
+
+fn filter_config(kind: &str) -> MotionFilterConfig {
+    MotionFilterConfig {
+        kind: kind.to_string(),
+        cutoff: Some(5.0),
+        beta: Some(0.5),
+        derivative_cutoff: Some(1.0),
+    }
+}
+
+fn vector(x: f64) -> InputValue {
+    InputValue::Vector3 {
+        x: Some(x),
+        y: None,
+        z: Some(0.0),
+    }
+}
+
+fn x_value(value: InputValue) -> Option<f64> {
+    match value {
+        InputValue::Vector3 { x, .. } => x,
+        _ => None,
+    }
+}
+
+#[test]
+fn test_unknown_kind() {
+    assert!(MotionFilter::new(&filter_config("median")).is_none());
+}
+
+#[test]
+fn test_ema() {
+    let mut filter = MotionFilter::new(&filter_config("ema")).unwrap();
+    let now = Instant::now();
+
+    // The first sample should pass through unchanged
+    let value = filter.update(vector(0.0), now);
+    assert_eq!(x_value(value), Some(0.0));
+
+    // A step change should be smoothed towards the new value
+    let value = filter.update(vector(10.0), now + Duration::from_millis(10));
+    let x = x_value(value).unwrap();
+    assert!(x > 0.0 && x < 10.0, "unexpected value: {x}");
+
+    // Missing axes should stay missing
+    let value = filter.update(vector(10.0), now + Duration::from_millis(20));
+    assert!(matches!(value, InputValue::Vector3 { y: None, .. }));
+}
+
+#[test]
+fn test_one_euro_adapts_to_speed() {
+    let now = Instant::now();
+    let step = Duration::from_millis(10);
+
+    // Small jitter should be smoothed heavily
+    let mut filter = MotionFilter::new(&filter_config("one_euro")).unwrap();
+    filter.update(vector(0.0), now);
+    let slow = x_value(filter.update(vector(0.1), now + step)).unwrap() / 0.1;
+
+    // Large fast movements should follow the input more closely
+    let mut filter = MotionFilter::new(&filter_config("one_euro")).unwrap();
+    filter.update(vector(0.0), now);
+    let fast = x_value(filter.update(vector(100.0), now + step)).unwrap() / 100.0;
+
+    assert!(fast > slow, "expected {fast} > {slow}");
+}
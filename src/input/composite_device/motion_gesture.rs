@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadGesture},
+    event::value::InputValue,
+};
+
+/// Capabilities of the gestures that can be detected from an accelerometer
+pub const GESTURE_CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Gesture(GamepadGesture::Freefall)),
+    Capability::Gamepad(Gamepad::Gesture(GamepadGesture::Pickup)),
+    Capability::Gamepad(Gamepad::Gesture(GamepadGesture::SetDown)),
+];
+
+/// Fraction of gravity below which the device is considered to be falling
+const FREEFALL_THRESHOLD: f64 = 0.3;
+/// Time the acceleration must stay below the freefall threshold
+const FREEFALL_TIME: Duration = Duration::from_millis(80);
+/// Maximum deviation from gravity, as a fraction of it, while the device is
+/// lying still. Holding a device is never this steady.
+const STILL_TOLERANCE: f64 = 0.02;
+/// Time the device must lie still before it is considered to be set down
+const REST_TIME: Duration = Duration::from_secs(2);
+/// Deviation from gravity, as a fraction of it, that picks up a resting device
+const PICKUP_THRESHOLD: f64 = 0.15;
+/// Weight of new samples in the estimated magnitude of gravity
+const GRAVITY_SMOOTHING: f64 = 0.01;
+
+/// Detects freefall, pickup, and set down gestures from the magnitude of
+/// accelerometer samples. Accelerometer units differ between devices, so all
+/// thresholds are relative to the magnitude of gravity, which is estimated
+/// from the samples.
+#[derive(Debug, Default)]
+pub struct MotionGestureDetector {
+    /// Estimated magnitude of gravity in the units of the accelerometer
+    gravity: Option<f64>,
+    /// Whether the device is resting, if known
+    resting: Option<bool>,
+    /// Time the device started lying still
+    still_since: Option<Instant>,
+    /// Time the device started falling
+    falling_since: Option<Instant>,
+    /// Whether a freefall was already detected for the current fall
+    falling: bool,
+}
+
+impl MotionGestureDetector {
+    /// Process the given accelerometer value. Returns the gesture that was
+    /// detected, if any.
+    pub fn process(&mut self, value: &InputValue, now: Instant) -> Option<GamepadGesture> {
+        let InputValue::Vector3 { x, y, z } = value else {
+            return None;
+        };
+        let (x, y, z) = (x.unwrap_or(0.0), y.unwrap_or(0.0), z.unwrap_or(0.0));
+        let magnitude = (x * x + y * y + z * z).sqrt();
+        let Some(gravity) = self.gravity.filter(|gravity| *gravity > 0.0) else {
+            self.gravity = Some(magnitude);
+            return None;
+        };
+
+        // A falling device measures (almost) no acceleration
+        if magnitude < gravity * FREEFALL_THRESHOLD {
+            let falling_since = *self.falling_since.get_or_insert(now);
+            self.still_since = None;
+            if self.falling || now.duration_since(falling_since) < FREEFALL_TIME {
+                return None;
+            }
+            self.falling = true;
+            self.resting = Some(false);
+            return Some(GamepadGesture::Freefall);
+        }
+        self.falling_since = None;
+        self.falling = false;
+        self.gravity = Some(gravity + (magnitude - gravity) * GRAVITY_SMOOTHING);
+
+        // A resting device only measures gravity
+        let deviation = (magnitude - gravity).abs() / gravity;
+        if deviation < STILL_TOLERANCE {
+            let still_since = *self.still_since.get_or_insert(now);
+            if now.duration_since(still_since) < REST_TIME {
+                return None;
+            }
+            let was_resting = self.resting.replace(true);
+            return (was_resting == Some(false)).then_some(GamepadGesture::SetDown);
+        }
+        self.still_since = None;
+
+        if deviation < PICKUP_THRESHOLD {
+            return None;
+        }
+        let was_resting = self.resting.replace(false);
+        (was_resting == Some(true)).then_some(GamepadGesture::Pickup)
+    }
+}
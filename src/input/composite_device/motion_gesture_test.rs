@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{capability::GamepadGesture, event::value::InputValue};
+
+use super::motion_gesture::MotionGestureDetector;
+
+fn accel(z: f64) -> InputValue {
+    InputValue::Vector3 {
+        x: Some(0.0),
+        y: Some(0.0),
+        z: Some(z),
+    }
+}
+
+#[test]
+fn test_motion_gestures() {
+    let mut detector = MotionGestureDetector::default();
+    let start = Instant::now();
+    let at = |ms: u64| start + Duration::from_millis(ms);
+
+    // Moving the device around should not be detected as a gesture
+    assert_eq!(detector.process(&accel(9.8), at(0)), None);
+    assert_eq!(detector.process(&accel(12.0), at(10)), None);
+    assert_eq!(detector.process(&accel(9.8), at(20)), None);
+
+    // Lying still should be detected as being set down
+    assert_eq!(detector.process(&accel(9.8), at(1000)), None);
+    assert_eq!(
+        detector.process(&accel(9.8), at(2020)),
+        Some(GamepadGesture::SetDown)
+    );
+    assert_eq!(detector.process(&accel(9.8), at(3000)), None);
+
+    // Small bumps should not pick the device up
+    assert_eq!(detector.process(&accel(10.5), at(3010)), None);
+    assert_eq!(
+        detector.process(&accel(12.0), at(3020)),
+        Some(GamepadGesture::Pickup)
+    );
+
+    // Falling should be detected once it lasts long enough
+    assert_eq!(detector.process(&accel(0.5), at(4000)), None);
+    assert_eq!(
+        detector.process(&accel(0.5), at(4100)),
+        Some(GamepadGesture::Freefall)
+    );
+    assert_eq!(detector.process(&accel(0.5), at(4200)), None);
+}
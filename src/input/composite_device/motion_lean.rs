@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::MotionLeanConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadGesture},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Capabilities of the lean gestures that can be detected from an
+/// accelerometer
+pub const LEAN_CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Gesture(GamepadGesture::LeanLeft)),
+    Capability::Gamepad(Gamepad::Gesture(GamepadGesture::LeanRight)),
+];
+
+/// Default roll angle in degrees away from the neutral angle that starts a lean
+const DEFAULT_THRESHOLD: f64 = 20.0;
+/// Default angle in degrees the roll must return below the threshold to
+/// release a lean
+const DEFAULT_HYSTERESIS: f64 = 5.0;
+/// Weight of new samples in the neutral angle while the device is not leaning
+const NEUTRAL_SMOOTHING: f64 = 0.005;
+
+/// Detects leaning the device left or right from the roll angle of the
+/// accelerometer, for lean mechanics in games. The angle the device is
+/// naturally held at slowly becomes the neutral angle, so only deliberate
+/// rolls away from it are detected as leans.
+#[derive(Debug)]
+pub struct MotionLeanDetector {
+    /// Roll angle away from the neutral angle that starts a lean
+    threshold: f64,
+    /// Roll angle away from the neutral angle that releases a lean
+    release: f64,
+    /// Time a lean can be held before the neutral angle is recentered
+    recenter_time: Option<Duration>,
+    invert: bool,
+    /// Neutral roll angle in degrees, once known
+    neutral: Option<f64>,
+    /// Lean that is currently held and the time it started
+    leaning: Option<(GamepadGesture, Instant)>,
+}
+
+impl MotionLeanDetector {
+    /// Create a new lean detector from the given config
+    pub fn new(config: &MotionLeanConfig) -> Self {
+        let threshold = config.threshold.unwrap_or(DEFAULT_THRESHOLD).abs();
+        let hysteresis = config.hysteresis.unwrap_or(DEFAULT_HYSTERESIS).abs();
+        let recenter_time = config
+            .recenter_secs
+            .filter(|secs| *secs > 0.0)
+            .map(Duration::from_secs_f64);
+        Self {
+            threshold,
+            release: (threshold - hysteresis).max(0.0),
+            recenter_time,
+            invert: config.invert.unwrap_or(false),
+            neutral: None,
+            leaning: None,
+        }
+    }
+
+    /// Process the given accelerometer value. Returns the lean gesture events
+    /// to emit, if the lean state changed.
+    pub fn process(&mut self, value: &InputValue, now: Instant) -> Vec<NativeEvent> {
+        let Some(mut roll) = value.accel_roll() else {
+            return vec![];
+        };
+        if self.invert {
+            roll = -roll;
+        }
+        let neutral = *self.neutral.get_or_insert(roll);
+        let offset = roll - neutral;
+
+        if let Some((lean, since)) = self.leaning.clone() {
+            // A lean held for too long means the device is held differently
+            // now, so its current angle becomes the neutral angle.
+            let recenter = self
+                .recenter_time
+                .is_some_and(|time| now.duration_since(since) >= time);
+            let leaning = match lean {
+                GamepadGesture::LeanLeft => offset <= -self.release,
+                _ => offset >= self.release,
+            };
+            if leaning && !recenter {
+                return vec![];
+            }
+            if recenter {
+                self.neutral = Some(roll);
+            }
+            return self.release_events();
+        }
+
+        // Slowly follow the angle the device is held at while not leaning
+        if offset.abs() < self.release {
+            self.neutral = Some(neutral + offset * NEUTRAL_SMOOTHING);
+            return vec![];
+        }
+
+        let lean = if offset <= -self.threshold {
+            GamepadGesture::LeanLeft
+        } else if offset >= self.threshold {
+            GamepadGesture::LeanRight
+        } else {
+            return vec![];
+        };
+        self.leaning = Some((lean.clone(), now));
+        vec![lean_event(lean, true)]
+    }
+
+    /// Release the held lean, if any. Returns the events to emit.
+    pub fn release_events(&mut self) -> Vec<NativeEvent> {
+        let Some((lean, _)) = self.leaning.take() else {
+            return vec![];
+        };
+        vec![lean_event(lean, false)]
+    }
+}
+
+/// Returns a press or release event for the given lean gesture
+fn lean_event(lean: GamepadGesture, pressed: bool) -> NativeEvent {
+    let cap = Capability::Gamepad(Gamepad::Gesture(lean));
+    NativeEvent::new(cap, InputValue::Bool(pressed))
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::MotionLeanConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadGesture},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::motion_lean::MotionLeanDetector;
+
+/// Returns an accelerometer value of a device rolled by the given angle
+fn roll(degrees: f64) -> InputValue {
+    let angle = degrees.to_radians();
+    InputValue::Vector3 {
+        x: Some(angle.sin() * 9.8),
+        y: Some(0.0),
+        z: Some(angle.cos() * 9.8),
+    }
+}
+
+fn is_lean(events: &[NativeEvent], lean: GamepadGesture, pressed: bool) -> bool {
+    let cap = Capability::Gamepad(Gamepad::Gesture(lean));
+    events.len() == 1
+        && events[0].as_capability() == cap
+        && matches!(events[0].get_value(), InputValue::Bool(value) if value == pressed)
+}
+
+#[test]
+fn test_motion_lean() {
+    let config = MotionLeanConfig {
+        threshold: Some(20.0),
+        hysteresis: Some(5.0),
+        recenter_secs: Some(2.0),
+        invert: None,
+    };
+    let mut detector = MotionLeanDetector::new(&config);
+    let start = Instant::now();
+    let at = |ms: u64| start + Duration::from_millis(ms);
+
+    // The angle the device is held at is the neutral angle
+    assert!(detector.process(&roll(10.0), at(0)).is_empty());
+    assert!(detector.process(&roll(25.0), at(10)).is_empty());
+
+    let events = detector.process(&roll(31.0), at(20));
+    assert!(is_lean(&events, GamepadGesture::LeanRight, true));
+
+    // The lean is only released once the roll is back past the hysteresis
+    assert!(detector.process(&roll(26.0), at(30)).is_empty());
+    let events = detector.process(&roll(24.0), at(40));
+    assert!(is_lean(&events, GamepadGesture::LeanRight, false));
+
+    let events = detector.process(&roll(-11.0), at(50));
+    assert!(is_lean(&events, GamepadGesture::LeanLeft, true));
+
+    // Leans held for too long recenter on the current angle
+    assert!(detector.process(&roll(-11.0), at(1000)).is_empty());
+    let events = detector.process(&roll(-11.0), at(2050));
+    assert!(is_lean(&events, GamepadGesture::LeanLeft, false));
+    assert!(detector.process(&roll(-25.0), at(2060)).is_empty());
+}
@@ -0,0 +1,78 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use crate::{
+    config::OutputMapping,
+    input::output_capability::{OutputCapability, LED},
+};
+
+/// Path to LED class devices
+const LEDS_PATH: &str = "/sys/class/leds";
+
+/// Returns true if the source event of the given output mapping matches any of
+/// the given output capabilities.
+pub fn mapping_matches(mapping: &OutputMapping, caps: &[OutputCapability]) -> bool {
+    let source = &mapping.source_event;
+    if source.force_feedback.unwrap_or(false) && caps.contains(&OutputCapability::ForceFeedback) {
+        return true;
+    }
+    let led = match source.led.as_deref() {
+        Some("color") => LED::Color,
+        Some("brightness") => LED::Brightness,
+        Some(led) => {
+            log::warn!("Invalid LED output event in '{}': {led}", mapping.name);
+            return false;
+        }
+        None => return false,
+    };
+    caps.contains(&OutputCapability::LED(led))
+}
+
+/// Performs source-specific actions from the output mappings of a capability
+/// map, such as setting the color of sysfs LEDs.
+#[derive(Debug, Default)]
+pub struct OutputMapper {
+    /// Last color set on each LED, used to skip redundant writes since
+    /// applications tend to send the same LED state repeatedly.
+    led_colors: HashMap<String, (u8, u8, u8)>,
+}
+
+impl OutputMapper {
+    /// Set the color of the multicolor LED with the given name in
+    /// "/sys/class/leds".
+    pub fn set_led_color(
+        &mut self,
+        name: &str,
+        color: (u8, u8, u8),
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.led_colors.get(name) == Some(&color) {
+            return Ok(());
+        }
+        if name.is_empty() || name.contains('/') || name.starts_with('.') {
+            return Err(format!("Invalid LED name: {name}").into());
+        }
+        let path = Path::new(LEDS_PATH).join(name);
+
+        // Intensities are written in the order of the LED's color indices
+        let index = fs::read_to_string(path.join("multi_index"))?;
+        let (red, green, blue) = color;
+        let intensity: Vec<String> = index
+            .split_whitespace()
+            .map(|channel| match channel {
+                "red" => red,
+                "green" => green,
+                "blue" => blue,
+                _ => 0,
+            })
+            .map(|value| value.to_string())
+            .collect();
+        log::debug!("Setting LED {name} color to {color:?}");
+        fs::write(path.join("multi_intensity"), intensity.join(" "))?;
+
+        // Intensities are scaled by the brightness, so use full brightness
+        let max_brightness = fs::read_to_string(path.join("max_brightness"))?;
+        fs::write(path.join("brightness"), max_brightness.trim())?;
+
+        self.led_colors.insert(name.to_string(), color);
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{
+    config::OutputMapping,
+    input::output_capability::{OutputCapability, LED},
+};
+
+use super::output_map::mapping_matches;
+
+fn mapping(content: &str) -> OutputMapping {
+    serde_yaml::from_str(content).unwrap()
+}
+
+#[test]
+fn test_output_mapping_matches() {
+    let lightbar = mapping(
+        r#"
+name: Lightbar
+source_event:
+  led: color
+target_event:
+  led:
+    name: multicolor:chassis
+"#,
+    );
+    assert!(mapping_matches(
+        &lightbar,
+        &[
+            OutputCapability::ForceFeedback,
+            OutputCapability::LED(LED::Color)
+        ]
+    ));
+    assert!(!mapping_matches(
+        &lightbar,
+        &[OutputCapability::ForceFeedback]
+    ));
+
+    let rumble = mapping(
+        r#"
+name: Rumble
+source_event:
+  force_feedback: true
+target_event:
+  action:
+    command: rumble-notify
+passthrough: false
+"#,
+    );
+    assert!(mapping_matches(&rumble, &[OutputCapability::ForceFeedback]));
+    assert!(!mapping_matches(
+        &rumble,
+        &[OutputCapability::LED(LED::Color)]
+    ));
+    assert_eq!(rumble.passthrough, Some(false));
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::input::{
+    capability::{Capability, Gamepad, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Maximum number of events that can wait for a full channel. Axis values are
+/// coalesced, so this is only reached if many different axes are waiting.
+const MAX_PENDING: usize = 256;
+
+/// Counters of how often event channels between source, composite, and target
+/// devices were overloaded. These are useful for debugging input stutters.
+#[derive(Debug, Default)]
+pub struct OverloadStats {
+    /// Events that could not be sent right away because a channel was full
+    deferred: AtomicU64,
+    /// Axis events that were merged into an event that was already waiting
+    coalesced: AtomicU64,
+    /// Axis events that were dropped because too many events were waiting
+    dropped: AtomicU64,
+    /// Output events that replaced a waiting update of the same output
+    outputs_coalesced: AtomicU64,
+    /// Output events that were dropped because too many were waiting
+    outputs_dropped: AtomicU64,
+}
+
+impl OverloadStats {
+    /// Number of events that could not be sent right away
+    pub fn deferred(&self) -> u64 {
+        self.deferred.load(Ordering::Relaxed)
+    }
+
+    /// Number of axis events that were merged into a waiting event
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Number of axis events that were dropped
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of output events that replaced a waiting update of the same
+    /// output
+    pub fn outputs_coalesced(&self) -> u64 {
+        self.outputs_coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Number of output events that were dropped
+    pub fn outputs_dropped(&self) -> u64 {
+        self.outputs_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Count an output event that replaced a waiting update
+    pub fn record_output_coalesced(&self) {
+        OverloadStats::record(&self.outputs_coalesced, 1);
+    }
+
+    /// Count an output event that was dropped
+    pub fn record_output_dropped(&self) {
+        OverloadStats::record(&self.outputs_dropped, 1);
+    }
+
+    fn record(counter: &AtomicU64, count: u64) {
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+/// Events waiting to be sent over a full channel. Button edges are never
+/// dropped and always keep their order, while axis values are coalesced with
+/// a waiting value of the same axis that was queued after the last edge, so
+/// that the latest position is sent once the channel has room again without
+/// moving an axis change past an edge.
+#[derive(Debug, Default)]
+pub struct OverloadQueue {
+    pending: VecDeque<NativeEvent>,
+}
+
+impl OverloadQueue {
+    /// Returns true if no events are waiting
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the number of events waiting
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Add the given event that could not be sent to the end of the queue,
+    /// coalescing it with a waiting event if possible.
+    pub fn push(&mut self, event: NativeEvent, stats: &OverloadStats) {
+        OverloadStats::record(&stats.deferred, 1);
+        if is_edge(&event) {
+            self.pending.push_back(event);
+            return;
+        }
+
+        // Merge axis values into the last waiting event of the same axis, as
+        // long as no edge was queued after it. Otherwise the axis change
+        // would be sent before the edge instead of after it.
+        let cap = event.as_capability();
+        let source_cap = event.get_source_capability();
+        let waiting = self
+            .pending
+            .iter_mut()
+            .rev()
+            .take_while(|pending| !is_edge(pending))
+            .find(|pending| {
+                pending.as_capability() == cap && pending.get_source_capability() == source_cap
+            });
+        if let Some(waiting) = waiting {
+            let value = coalesce(&cap, waiting.get_value(), event.get_value());
+            waiting.set_value(value);
+            OverloadStats::record(&stats.coalesced, 1);
+            return;
+        }
+
+        if self.pending.len() >= MAX_PENDING {
+            OverloadStats::record(&stats.dropped, 1);
+            return;
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Return an event that could not be sent to the front of the queue
+    pub fn push_front(&mut self, event: NativeEvent) {
+        self.pending.push_front(event);
+    }
+
+    /// Take the next event to send from the queue
+    pub fn pop_front(&mut self) -> Option<NativeEvent> {
+        self.pending.pop_front()
+    }
+
+    /// Drop all waiting events, e.g. when the receiver went away
+    pub fn clear(&mut self, stats: &OverloadStats) {
+        OverloadStats::record(&stats.dropped, self.pending.len() as u64);
+        self.pending.clear();
+    }
+}
+
+/// Returns true if the given event is a state change that must never be
+/// dropped or merged, such as a button press or release.
+pub fn is_edge(event: &NativeEvent) -> bool {
+    !matches!(
+        event.get_value(),
+        InputValue::Float(_) | InputValue::Vector2 { .. } | InputValue::Vector3 { .. }
+    )
+}
+
+/// Returns true if values of the given capability are relative to the last
+/// value and must be summed instead of replaced when coalesced.
+fn is_relative(cap: &Capability) -> bool {
+    matches!(
+        cap,
+        Capability::Mouse(Mouse::Motion) | Capability::Gamepad(Gamepad::Dial(_))
+    )
+}
+
+/// Combine a waiting axis value with a newer value of the same axis
+fn coalesce(cap: &Capability, old: InputValue, new: InputValue) -> InputValue {
+    let merge = |old: Option<f64>, new: Option<f64>| {
+        if !is_relative(cap) {
+            return new.or(old);
+        }
+        match (old, new) {
+            (Some(old), Some(new)) => Some(old + new),
+            (old, new) => new.or(old),
+        }
+    };
+    match (old, new) {
+        (InputValue::Float(old), InputValue::Float(new)) => {
+            let value = merge(Some(old), Some(new)).unwrap_or_default();
+            InputValue::Float(value)
+        }
+        (InputValue::Vector2 { x: ox, y: oy }, InputValue::Vector2 { x, y }) => {
+            InputValue::Vector2 {
+                x: merge(ox, x),
+                y: merge(oy, y),
+            }
+        }
+        (
+            InputValue::Vector3 {
+                x: ox,
+                y: oy,
+                z: oz,
+            },
+            InputValue::Vector3 { x, y, z },
+        ) => InputValue::Vector3 {
+            x: merge(ox, x),
+            y: merge(oy, y),
+            z: merge(oz, z),
+        },
+        (_, new) => new,
+    }
+}
@@ -0,0 +1,69 @@
+use crate::input::{
+    capability::{Capability, Mouse},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::overload::{OverloadQueue, OverloadStats};
+use super::test_events::{button, stick};
+
+#[test]
+fn test_overload_queue() {
+    let stats = OverloadStats::default();
+    let mut queue = OverloadQueue::default();
+
+    // Button edges should never be merged
+    queue.push(button(true), &stats);
+    queue.push(stick(Some(0.5), None), &stats);
+    queue.push(button(false), &stats);
+    queue.push(stick(None, Some(-1.0)), &stats);
+    queue.push(stick(Some(0.25), None), &stats);
+    assert_eq!(queue.len(), 4);
+    assert_eq!(stats.deferred(), 5);
+    assert_eq!(stats.coalesced(), 1);
+    assert_eq!(stats.dropped(), 0);
+
+    // Edges should keep their order relative to axis changes, and the latest
+    // axis value after the last edge should be sent
+    let value = queue.pop_front().unwrap().get_value();
+    assert!(matches!(value, InputValue::Bool(true)));
+    let value = queue.pop_front().unwrap().get_value();
+    assert!(matches!(
+        value,
+        InputValue::Vector2 {
+            x: Some(0.5),
+            y: None
+        }
+    ));
+    let value = queue.pop_front().unwrap().get_value();
+    assert!(matches!(value, InputValue::Bool(false)));
+    let value = queue.pop_front().unwrap().get_value();
+    assert!(matches!(
+        value,
+        InputValue::Vector2 {
+            x: Some(0.25),
+            y: Some(-1.0)
+        }
+    ));
+    assert!(queue.is_empty());
+
+    // Relative motion should be summed
+    let motion = |x: f64, y: f64| {
+        NativeEvent::new(
+            Capability::Mouse(Mouse::Motion),
+            InputValue::Vector2 {
+                x: Some(x),
+                y: Some(y),
+            },
+        )
+    };
+    queue.push(motion(2.0, -1.0), &stats);
+    queue.push(motion(3.0, -1.0), &stats);
+    let value = queue.pop_front().unwrap().get_value();
+    assert!(matches!(
+        value,
+        InputValue::Vector2 {
+            x: Some(5.0),
+            y: Some(-2.0)
+        }
+    ));
+}
@@ -0,0 +1,312 @@
+use std::error::Error;
+
+use crate::input::event::native::NativeEvent;
+
+use super::{axis_transform::AxisTransform, CompositeDevice};
+
+/// An [EventFrame] is a group of events that are processed together by the
+/// stages of a [Pipeline]. For example, a single source event that was
+/// translated into a chord of target events.
+#[derive(Debug, Clone)]
+pub struct EventFrame {
+    /// Events in the frame
+    pub events: Vec<NativeEvent>,
+    /// Whether the frame was triggered by a 'pressed' event. This is used
+    /// to determine the order that chords should be emitted in.
+    pub is_pressed: bool,
+}
+
+impl EventFrame {
+    /// Create a new frame from a single event
+    pub fn new(event: NativeEvent) -> Self {
+        Self {
+            is_pressed: event.pressed(),
+            events: vec![event],
+        }
+    }
+}
+
+/// A [Stage] is a single step in an event [Pipeline]. Each stage receives
+/// a frame of events and returns zero or more frames to pass on to the next
+/// stage. Returning no frames stops the frame from being processed further.
+#[allow(async_fn_in_trait)]
+pub trait Stage<C> {
+    /// Name of the stage
+    fn name(&self) -> &str;
+
+    /// Process the given frame of events using the given context
+    async fn process(
+        &mut self,
+        ctx: &mut C,
+        frame: EventFrame,
+    ) -> Result<Vec<EventFrame>, Box<dyn Error>>;
+}
+
+/// A [Pipeline] passes events through an ordered list of stages
+#[derive(Debug)]
+pub struct Pipeline<S> {
+    stages: Vec<S>,
+}
+
+impl<S> Default for Pipeline<S> {
+    fn default() -> Self {
+        Self { stages: Vec::new() }
+    }
+}
+
+impl<S> Pipeline<S> {
+    /// Create a new pipeline with the given ordered stages
+    pub fn new(stages: Vec<S>) -> Self {
+        Self { stages }
+    }
+
+    /// Returns the ordered stages of the pipeline
+    pub fn stages(&self) -> &[S] {
+        self.stages.as_slice()
+    }
+
+    /// Insert the given stage at the given position in the pipeline
+    pub fn insert(&mut self, index: usize, stage: S) {
+        self.stages.insert(index, stage);
+    }
+
+    /// Pass the given frame through every stage of the pipeline, starting
+    /// at the stage with the given index. Each output frame is passed through
+    /// the remaining stages before the next output frame is processed, so
+    /// events are handled in the order they were emitted. Returns the frames
+    /// that were output by the last stage.
+    pub async fn run<C>(
+        &mut self,
+        ctx: &mut C,
+        start: usize,
+        frame: EventFrame,
+    ) -> Result<Vec<EventFrame>, Box<dyn Error>>
+    where
+        S: Stage<C>,
+    {
+        let mut output = Vec::new();
+        let mut queue = vec![(start, frame)];
+        while let Some((index, frame)) = queue.pop() {
+            let Some(stage) = self.stages.get_mut(index) else {
+                output.push(frame);
+                continue;
+            };
+            let frames = stage.process(ctx, frame).await?;
+            if frames.is_empty() {
+                log::trace!("Frame consumed by stage: {}", stage.name());
+            }
+            // Push frames in reverse so they are processed in order
+            queue.extend(frames.into_iter().rev().map(|frame| (index + 1, frame)));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Stages of the [CompositeDevice] event pipeline. Events are first
+/// compensated for stick drift and debounced, then translated by the capability map, transformed into the
+/// profile layout, and translated by the device profile, then checked for
+/// intercept before being routed to target devices. Intercept runs on
+/// translated events so mapped buttons (e.g. a Guide button from a capability
+/// map) can activate intercept mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeStage {
+    /// Compensate analog stick drift
+    DriftCompensation,
+    /// Filter state changes of flaky digital inputs
+    Debounce,
+    /// Translate events using the capability map
+    CapabilityMap,
+    /// Rotate axes and remap buttons into the layout of the device profile,
+    /// then apply the shaped dead zones and arcade gates of the sticks
+    AxisTransform,
+    /// Translate and filter events using the device profile
+    Profile,
+    /// Translate between the DPad and the left stick
+    DPadStick,
+    /// Repeat held DPad buttons that are mapped to DPad buttons or keys
+    DPadRepeat,
+    /// Translate a stick into sector selections of an on-screen radial menu
+    RadialMenu,
+    /// Track active inputs, handle intercept mode, and delay chords
+    Intercept,
+    /// Write events to the target devices, and track the mouse pointer for
+    /// dwell clicks
+    Routing,
+}
+
+impl CompositeStage {
+    /// Returns the default ordered stages of the [CompositeDevice] pipeline
+    pub fn default_stages() -> Vec<Self> {
+        vec![
+            CompositeStage::DriftCompensation,
+            CompositeStage::Debounce,
+            CompositeStage::CapabilityMap,
+            CompositeStage::AxisTransform,
+            CompositeStage::Profile,
+            CompositeStage::DPadStick,
+            CompositeStage::DPadRepeat,
+            CompositeStage::RadialMenu,
+            CompositeStage::Intercept,
+            CompositeStage::Routing,
+        ]
+    }
+}
+
+impl Stage<CompositeDevice> for CompositeStage {
+    fn name(&self) -> &str {
+        match self {
+            CompositeStage::DriftCompensation => "DriftCompensation",
+            CompositeStage::Debounce => "Debounce",
+            CompositeStage::CapabilityMap => "CapabilityMap",
+            CompositeStage::AxisTransform => "AxisTransform",
+            CompositeStage::Profile => "Profile",
+            CompositeStage::DPadStick => "DPadStick",
+            CompositeStage::DPadRepeat => "DPadRepeat",
+            CompositeStage::RadialMenu => "RadialMenu",
+            CompositeStage::Intercept => "Intercept",
+            CompositeStage::Routing => "Routing",
+        }
+    }
+
+    async fn process(
+        &mut self,
+        device: &mut CompositeDevice,
+        frame: EventFrame,
+    ) -> Result<Vec<EventFrame>, Box<dyn Error>> {
+        match self {
+            CompositeStage::DriftCompensation => {
+                if device.drift_compensator.is_none() {
+                    return Ok(vec![frame]);
+                }
+                let events = frame
+                    .events
+                    .into_iter()
+                    .map(|event| device.compensate_drift(event))
+                    .collect();
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::Debounce => {
+                if device.debouncer.is_empty() {
+                    return Ok(vec![frame]);
+                }
+                let events = frame
+                    .events
+                    .into_iter()
+                    .filter_map(|event| device.debounce_event(event))
+                    .collect::<Vec<_>>();
+                if events.is_empty() {
+                    return Ok(vec![]);
+                }
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::CapabilityMap => {
+                // Only single source events can be translated
+                let [event] = frame.events.as_slice() else {
+                    return Ok(vec![frame]);
+                };
+                let cap = event.as_capability();
+                if device.capability_map.is_none()
+                    || !device.translatable_capabilities.contains(&cap)
+                {
+                    return Ok(vec![frame]);
+                }
+                log::trace!("Capability mapping found for event");
+                let events = device.translate_capability(event).await?;
+                Ok(events.into_iter().map(EventFrame::new).collect())
+            }
+            CompositeStage::AxisTransform => {
+                // Keep track of held source inputs to restore them when the
+                // device profile changes
+                for event in frame.events.iter() {
+                    device.source_held.update(event);
+                }
+                if device.axis_transform == AxisTransform::None
+                    && device.stick_deadzones.is_empty()
+                    && device.stick_gates.is_empty()
+                {
+                    return Ok(vec![frame]);
+                }
+                let events = frame
+                    .events
+                    .into_iter()
+                    .map(|event| device.axis_transform.transform(event))
+                    .map(|event| device.stick_deadzones.apply(event))
+                    .map(|event| device.stick_gates.apply(event))
+                    .collect();
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::Profile => {
+                if device.device_profile.is_none() {
+                    return Ok(vec![frame]);
+                }
+                let mut events = Vec::with_capacity(frame.events.len());
+                for event in frame.events {
+                    let event = device.filter_motion_event(event);
+                    let translated = device.translate_event(&event).await?;
+                    events.extend(
+                        translated
+                            .into_iter()
+                            .filter_map(|event| device.filter_event(event)),
+                    );
+                }
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::DPadStick => {
+                let events = frame
+                    .events
+                    .into_iter()
+                    .flat_map(|event| device.dpad_stick.translate(event))
+                    .collect();
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::DPadRepeat => {
+                for event in frame.events.iter() {
+                    device.update_dpad_repeat(event);
+                }
+                Ok(vec![frame])
+            }
+            CompositeStage::RadialMenu => {
+                let events = device.process_radial_menu(frame.events);
+                if events.is_empty() {
+                    return Ok(vec![]);
+                }
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::Intercept => {
+                let events = device
+                    .process_intercept(frame.events, frame.is_pressed)
+                    .await?;
+                Ok(vec![EventFrame {
+                    events,
+                    is_pressed: frame.is_pressed,
+                }])
+            }
+            CompositeStage::Routing => {
+                for event in frame.events {
+                    device.update_dwell_click(&event);
+                    device.write_event(event).await?;
+                }
+                Ok(vec![])
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadButton},
+    composite_device::pipeline::{EventFrame, Pipeline, Stage},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Context used to record which stages processed which events
+#[derive(Debug, Default)]
+struct TestContext {
+    processed: Vec<(String, Capability)>,
+}
+
+#[derive(Debug)]
+enum TestStage {
+    /// Duplicates every frame
+    Split,
+    /// Drops frames with the given capability
+    Drop(Capability),
+    /// Records every frame
+    Record,
+}
+
+impl Stage<TestContext> for TestStage {
+    fn name(&self) -> &str {
+        match self {
+            TestStage::Split => "Split",
+            TestStage::Drop(_) => "Drop",
+            TestStage::Record => "Record",
+        }
+    }
+
+    async fn process(
+        &mut self,
+        ctx: &mut TestContext,
+        frame: EventFrame,
+    ) -> Result<Vec<EventFrame>, Box<dyn Error>> {
+        match self {
+            TestStage::Split => {
+                let event = NativeEvent::new(
+                    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+                    InputValue::Bool(frame.is_pressed),
+                );
+                Ok(vec![frame, EventFrame::new(event)])
+            }
+            TestStage::Drop(cap) => {
+                if frame.events.iter().any(|e| e.as_capability() == *cap) {
+                    return Ok(vec![]);
+                }
+                Ok(vec![frame])
+            }
+            TestStage::Record => {
+                for event in frame.events.iter() {
+                    ctx.processed
+                        .push((self.name().to_string(), event.as_capability()));
+                }
+                Ok(vec![frame])
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_order() -> Result<(), Box<dyn Error>> {
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let east = Capability::Gamepad(Gamepad::Button(GamepadButton::East));
+    let mut pipeline = Pipeline::new(vec![TestStage::Split, TestStage::Record]);
+    let mut ctx = TestContext::default();
+
+    let frame = EventFrame::new(NativeEvent::new(south.clone(), InputValue::Bool(true)));
+    let output = pipeline.run(&mut ctx, 0, frame).await?;
+
+    // Frames should be processed in the order they were emitted
+    assert_eq!(output.len(), 2);
+    assert_eq!(
+        ctx.processed,
+        vec![
+            ("Record".to_string(), south.clone()),
+            ("Record".to_string(), east.clone())
+        ]
+    );
+
+    // Inserted stages should be able to consume frames
+    pipeline.insert(1, TestStage::Drop(east));
+    let mut ctx = TestContext::default();
+    let frame = EventFrame::new(NativeEvent::new(south.clone(), InputValue::Bool(true)));
+    let output = pipeline.run(&mut ctx, 0, frame).await?;
+    assert_eq!(output.len(), 1);
+    assert_eq!(ctx.processed, vec![("Record".to_string(), south.clone())]);
+
+    // Starting at a later stage should skip earlier stages
+    let mut ctx = TestContext::default();
+    let frame = EventFrame::new(NativeEvent::new(south.clone(), InputValue::Bool(true)));
+    let output = pipeline.run(&mut ctx, 2, frame).await?;
+    assert_eq!(output.len(), 1);
+    assert_eq!(ctx.processed, vec![("Record".to_string(), south)]);
+
+    Ok(())
+}
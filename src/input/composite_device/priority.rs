@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::input::{
+    capability::Capability,
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Minimum value an axis or trigger must reach to be considered in use
+const ACTIVE_THRESHOLD: f64 = 0.05;
+
+/// Returns true if the given value means the input is in use, such as a
+/// pressed button, a deflected stick, or a finger on a touchpad.
+fn is_active(value: &InputValue) -> bool {
+    let past_threshold = |value: &Option<f64>| value.is_some_and(|v| v.abs() > ACTIVE_THRESHOLD);
+    match value {
+        InputValue::None => false,
+        InputValue::Bool(pressed) => *pressed,
+        InputValue::Float(value) => value.abs() > ACTIVE_THRESHOLD,
+        InputValue::Vector2 { x, y } => past_threshold(x) || past_threshold(y),
+        InputValue::Vector3 { .. } => true,
+        InputValue::Touch { is_touching, .. } => *is_touching,
+    }
+}
+
+/// Resolves conflicts between source devices of different priority that
+/// emit the same capability. While a source device is using a capability,
+/// events from lower priority sources for that capability are dropped, and a
+/// higher priority source preempts it as soon as it starts using the
+/// capability. E.g. a detachable controller can override the built-in
+/// buttons of a handheld while it is docked. Sources with the same priority
+/// are never filtered.
+#[derive(Debug, Default)]
+pub struct SourcePriority {
+    /// Priority of each source device by id. Sources default to 0.
+    priorities: HashMap<String, i32>,
+    /// Source device currently using each capability and its priority
+    owners: HashMap<Capability, (String, i32)>,
+}
+
+impl SourcePriority {
+    /// Returns true if no source device has a priority configured
+    pub fn is_empty(&self) -> bool {
+        self.priorities.is_empty()
+    }
+
+    /// Set the priority of the source device with the given id
+    pub fn set_priority(&mut self, source_id: &str, priority: i32) {
+        self.priorities.insert(source_id.to_string(), priority);
+    }
+
+    /// Forget the source device with the given id and release every
+    /// capability it was using
+    pub fn remove_source(&mut self, source_id: &str) {
+        self.priorities.remove(source_id);
+        self.owners.retain(|_, (owner, _)| owner != source_id);
+    }
+
+    /// Returns the source device currently using the given capability, if any
+    pub fn owner(&self, cap: &Capability) -> Option<&str> {
+        self.owners.get(cap).map(|(owner, _)| owner.as_str())
+    }
+
+    /// Process the given event from the source device with the given id.
+    /// Returns false if the event conflicts with a higher priority source
+    /// and should be dropped.
+    pub fn process(&mut self, source_id: &str, event: &NativeEvent) -> bool {
+        let cap = event.as_capability();
+        let priority = self.priorities.get(source_id).copied().unwrap_or_default();
+        let active = is_active(&event.get_value());
+
+        let Some((owner, owner_priority)) = self.owners.get(&cap) else {
+            if active {
+                self.owners.insert(cap, (source_id.to_string(), priority));
+            }
+            return true;
+        };
+
+        // The source using the capability releases it once it is neutral
+        if owner == source_id {
+            if !active {
+                self.owners.remove(&cap);
+            }
+            return true;
+        }
+
+        if priority == *owner_priority {
+            return true;
+        }
+        if priority < *owner_priority || !active {
+            log::trace!("Dropping {cap} from {source_id} in use by {owner}");
+            return false;
+        }
+
+        // Preempt the lower priority source
+        log::debug!("Source {source_id} preempted {owner} for {cap}");
+        self.owners.insert(cap, (source_id.to_string(), priority));
+        true
+    }
+}
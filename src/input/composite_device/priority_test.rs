@@ -0,0 +1,31 @@
+use crate::input::capability::{Capability, Gamepad, GamepadButton};
+
+use super::priority::SourcePriority;
+use super::test_events::button;
+
+#[test]
+fn test_source_priority() {
+    let mut priority = SourcePriority::default();
+    priority.set_priority("docked", 10);
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+
+    // Lower priority sources should be dropped while a higher one is in use
+    assert!(priority.process("docked", &button(true)));
+    assert!(!priority.process("builtin", &button(true)));
+    assert!(!priority.process("builtin", &button(false)));
+    assert!(priority.process("docked", &button(false)));
+    assert_eq!(priority.owner(&cap), None);
+
+    // Higher priority sources should preempt lower priority ones
+    assert!(priority.process("builtin", &button(true)));
+    assert_eq!(priority.owner(&cap), Some("builtin"));
+    assert!(priority.process("docked", &button(true)));
+    assert_eq!(priority.owner(&cap), Some("docked"));
+    assert!(!priority.process("builtin", &button(false)));
+
+    // Removing a source should release the capabilities it was using
+    priority.remove_source("docked");
+    assert_eq!(priority.owner(&cap), None);
+    assert!(priority.process("builtin", &button(true)));
+    assert!(priority.process("other", &button(true)));
+}
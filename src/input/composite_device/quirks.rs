@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::quirks::DeviceQuirk,
+    input::{
+        capability::Capability,
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Device quirks of a single source device that are applied to its events
+/// and outputs.
+#[derive(Debug, Default)]
+pub struct SourceQuirks {
+    /// Axes with inverted (x, y) directions
+    inverted_axes: HashMap<Capability, (bool, bool)>,
+    disable_force_feedback: bool,
+}
+
+impl SourceQuirks {
+    /// Create the source quirks from the given device quirk
+    pub fn new(quirk: &DeviceQuirk) -> Self {
+        let inverted_axes = quirk
+            .inverted_axes
+            .iter()
+            .flatten()
+            .map(|axis| {
+                let cap: Capability = axis.capability.clone().into();
+                let directions = (axis.x.unwrap_or_default(), axis.y.unwrap_or_default());
+                (cap, directions)
+            })
+            .filter(|(cap, _)| *cap != Capability::NotImplemented)
+            .collect();
+        Self {
+            inverted_axes,
+            disable_force_feedback: quirk.disable_force_feedback.unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if force feedback should not be sent to the source device
+    pub fn force_feedback_disabled(&self) -> bool {
+        self.disable_force_feedback
+    }
+
+    /// Returns the given source event with its inverted axis directions
+    /// corrected.
+    pub fn process(&self, mut event: NativeEvent) -> NativeEvent {
+        let Some((invert_x, invert_y)) = self.inverted_axes.get(&event.as_capability()) else {
+            return event;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return event;
+        };
+        let x = if *invert_x { x.map(|x| -x) } else { x };
+        let y = if *invert_y { y.map(|y| -y) } else { y };
+        event.set_value(InputValue::Vector2 { x, y });
+        event
+    }
+}
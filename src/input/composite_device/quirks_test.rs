@@ -0,0 +1,57 @@
+use crate::{
+    config::quirks::DeviceQuirk,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::quirks::SourceQuirks;
+
+#[test]
+fn test_inverted_axes() {
+    let quirk: DeviceQuirk = serde_yaml::from_str(
+        r#"
+vendor_id: 0x1234
+product_id: 0x5678
+inverted_axes:
+  - capability:
+      gamepad:
+        axis:
+          name: RightStick
+    y: true
+"#,
+    )
+    .unwrap();
+    let quirks = SourceQuirks::new(&quirk);
+    assert!(!quirks.force_feedback_disabled());
+
+    let right = Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick));
+    let value = InputValue::Vector2 {
+        x: Some(0.5),
+        y: Some(0.25),
+    };
+    let event = quirks.process(NativeEvent::new(right, value));
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y)
+        } if x == 0.5 && y == -0.25
+    ));
+
+    // Axes without quirks are passed through
+    let left = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let value = InputValue::Vector2 {
+        x: Some(0.5),
+        y: Some(0.25),
+    };
+    let event = quirks.process(NativeEvent::new(left, value));
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y)
+        } if x == 0.5 && y == 0.25
+    ));
+}
@@ -0,0 +1,169 @@
+use std::{fmt, str::FromStr};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis},
+    event::{
+        native::NativeEvent,
+        value::{angle_distance, InputValue},
+    },
+};
+
+/// Number of sectors of the radial menu
+pub const SECTORS: u8 = 8;
+/// Deflection of the stick required to select a sector
+const ENTER_THRESHOLD: f64 = 0.6;
+/// Deflection the stick must fall below to leave the selected sector and
+/// confirm it
+const EXIT_THRESHOLD: f64 = 0.4;
+/// Angle in degrees the stick may move past the edge of the selected sector
+/// before a neighbouring sector is selected
+const SECTOR_HYSTERESIS: f64 = 7.5;
+
+/// Analog stick used to select sectors of an on-screen radial menu
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RadialMenuStick {
+    /// The radial menu is disabled and stick events are passed through
+    #[default]
+    None,
+    LeftStick,
+    RightStick,
+}
+
+impl RadialMenuStick {
+    /// Returns the capability of the stick, if any
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            RadialMenuStick::None => None,
+            RadialMenuStick::LeftStick => {
+                Some(Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)))
+            }
+            RadialMenuStick::RightStick => {
+                Some(Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RadialMenuStick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RadialMenuStick::None => write!(f, "none"),
+            RadialMenuStick::LeftStick => write!(f, "left_stick"),
+            RadialMenuStick::RightStick => write!(f, "right_stick"),
+        }
+    }
+}
+
+impl FromStr for RadialMenuStick {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(RadialMenuStick::None),
+            "left_stick" => Ok(RadialMenuStick::LeftStick),
+            "right_stick" => Ok(RadialMenuStick::RightStick),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Events produced by a [RadialMenu]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadialMenuEvent {
+    /// The stick points at the given sector
+    Selected(u8),
+    /// The stick was released while the given sector was selected
+    Confirmed(u8),
+}
+
+/// Translates the angle of an analog stick into the selection of one of eight
+/// sectors of an on-screen radial menu. Sector 0 points up and the sectors
+/// are numbered clockwise. Releasing the stick confirms the selected sector.
+#[derive(Debug, Clone, Default)]
+pub struct RadialMenu {
+    stick: RadialMenuStick,
+    /// Last known position of the stick
+    position: (f64, f64),
+    /// Currently selected sector
+    selected: Option<u8>,
+}
+
+impl RadialMenu {
+    /// Returns the stick used to select sectors
+    pub fn stick(&self) -> RadialMenuStick {
+        self.stick
+    }
+
+    /// Set the stick used to select sectors. Any selection of the previous
+    /// stick is discarded without being confirmed.
+    pub fn set_stick(&mut self, stick: RadialMenuStick) {
+        self.stick = stick;
+        self.position = (0.0, 0.0);
+        self.selected = None;
+    }
+
+    /// Returns true if the given event is consumed by the radial menu
+    pub fn handles(&self, event: &NativeEvent) -> bool {
+        self.stick.capability() == Some(event.as_capability())
+    }
+
+    /// Update the radial menu with the given stick event. Returns the
+    /// selection changes caused by the event.
+    pub fn process(&mut self, event: &NativeEvent) -> Vec<RadialMenuEvent> {
+        if !self.handles(event) {
+            return Vec::new();
+        }
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return Vec::new();
+        };
+        if let Some(x) = x {
+            self.position.0 = x;
+        }
+        if let Some(y) = y {
+            self.position.1 = y;
+        }
+        let (x, y) = self.position;
+        let magnitude = x.hypot(y);
+
+        let Some(selected) = self.selected else {
+            if magnitude < ENTER_THRESHOLD {
+                return Vec::new();
+            }
+            let sector = sector_at(x, y);
+            self.selected = Some(sector);
+            return vec![RadialMenuEvent::Selected(sector)];
+        };
+
+        if magnitude < EXIT_THRESHOLD {
+            self.selected = None;
+            return vec![RadialMenuEvent::Confirmed(selected)];
+        }
+
+        // Only switch sectors once the stick moved far enough past the edge
+        // of the selected sector to avoid flickering at the edges.
+        let half_angle = 180.0 / SECTORS as f64 + SECTOR_HYSTERESIS;
+        if angle_distance(stick_angle(x, y), sector_angle(selected)) <= half_angle {
+            return Vec::new();
+        }
+        let sector = sector_at(x, y);
+        self.selected = Some(sector);
+        vec![RadialMenuEvent::Selected(sector)]
+    }
+}
+
+/// Returns the angle of the given stick position in degrees, where up is 0
+/// and angles increase clockwise (positive Y values point down).
+fn stick_angle(x: f64, y: f64) -> f64 {
+    x.atan2(-y).to_degrees().rem_euclid(360.0)
+}
+
+/// Returns the angle in degrees of the center of the given sector
+fn sector_angle(sector: u8) -> f64 {
+    sector as f64 * 360.0 / SECTORS as f64
+}
+
+/// Returns the sector the given stick position points at
+pub fn sector_at(x: f64, y: f64) -> u8 {
+    let width = 360.0 / SECTORS as f64;
+    ((stick_angle(x, y) / width).round() as u8) % SECTORS
+}
@@ -0,0 +1,45 @@
+use crate::input::capability::GamepadAxis;
+
+use super::radial_menu::{sector_at, RadialMenu, RadialMenuEvent, RadialMenuStick};
+use super::test_events::stick_event;
+
+#[test]
+fn test_radial_menu_sectors() {
+    assert_eq!(sector_at(0.0, -1.0), 0, "Up should be sector 0");
+    assert_eq!(sector_at(0.7, -0.7), 1);
+    assert_eq!(sector_at(1.0, 0.0), 2);
+    assert_eq!(sector_at(0.0, 1.0), 4);
+    assert_eq!(sector_at(-1.0, 0.0), 6);
+    assert_eq!(sector_at(-0.7, -0.7), 7);
+}
+
+#[test]
+fn test_radial_menu() {
+    let mut menu = RadialMenu::default();
+    let stick = |x, y| stick_event(GamepadAxis::RightStick, x, y);
+    assert!(
+        menu.process(&stick(0.0, -1.0)).is_empty(),
+        "Should be disabled"
+    );
+
+    menu.set_stick(RadialMenuStick::RightStick);
+    assert!(menu.handles(&stick(0.0, 0.0)));
+
+    // Small deflections should not select a sector
+    assert!(menu.process(&stick(0.0, -0.5)).is_empty());
+    let events = menu.process(&stick(0.0, -0.8));
+    assert_eq!(events, vec![RadialMenuEvent::Selected(0)]);
+
+    // Moving slightly past the edge of the sector should keep the selection
+    let angle = 25.0_f64.to_radians();
+    assert!(menu.process(&stick(angle.sin(), -angle.cos())).is_empty());
+    let angle = 35.0_f64.to_radians();
+    let events = menu.process(&stick(angle.sin(), -angle.cos()));
+    assert_eq!(events, vec![RadialMenuEvent::Selected(1)]);
+
+    // The selection should be kept until the stick is mostly released
+    assert!(menu.process(&stick(0.35, -0.35)).is_empty());
+    let events = menu.process(&stick(0.0, 0.0));
+    assert_eq!(events, vec![RadialMenuEvent::Confirmed(1)]);
+    assert!(menu.process(&stick(0.0, 0.0)).is_empty());
+}
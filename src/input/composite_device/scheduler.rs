@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+/// Resolution of the timer wheel
+const TICK: Duration = Duration::from_millis(1);
+/// Number of slots in the timer wheel
+const NUM_SLOTS: usize = 256;
+
+/// Identifier of a scheduled item that can be used to cancel it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(u64);
+
+/// An item waiting in the timer wheel
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    id: TimerId,
+    /// Tick at which the item is due
+    deadline: u64,
+    /// Optional key used to cancel a group of items (e.g. all items scheduled
+    /// by a profile mapping)
+    key: Option<String>,
+    value: T,
+}
+
+/// The [EventScheduler] holds items (e.g. synthetic events or commands) that
+/// should be dispatched at a later time, like macro key sequences, repeated
+/// buttons, and long-press checks. Items are stored in a hashed timer wheel
+/// so scheduling and cancelling are cheap regardless of how many items are
+/// waiting.
+///
+/// Items are returned in the order of their deadline. Items with the same
+/// deadline are returned in the order they were scheduled.
+#[derive(Debug)]
+pub struct EventScheduler<T> {
+    /// Time of tick zero
+    start: Instant,
+    /// Last tick that was polled
+    current_tick: u64,
+    next_id: u64,
+    slots: Vec<Vec<Entry<T>>>,
+    len: usize,
+}
+
+impl<T> Default for EventScheduler<T> {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl<T> EventScheduler<T> {
+    /// Create a new scheduler that starts at the given time
+    pub fn new(start: Instant) -> Self {
+        let mut slots = Vec::with_capacity(NUM_SLOTS);
+        slots.resize_with(NUM_SLOTS, Vec::new);
+        Self {
+            start,
+            current_tick: 0,
+            next_id: 0,
+            slots,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of scheduled items
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no items are scheduled
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Schedule the given item to be dispatched after the given duration from
+    /// now. If a key is given, the item can be cancelled with
+    /// [EventScheduler::cancel_key].
+    pub fn schedule(
+        &mut self,
+        now: Instant,
+        after: Duration,
+        key: Option<&str>,
+        value: T,
+    ) -> TimerId {
+        self.schedule_at(now + after, key, value)
+    }
+
+    /// Schedule the given item to be dispatched at the given time
+    pub fn schedule_at(&mut self, deadline: Instant, key: Option<&str>, value: T) -> TimerId {
+        // Round up so items are never dispatched early, and never schedule
+        // an item in a tick that was already polled.
+        let elapsed = deadline.saturating_duration_since(self.start);
+        let deadline = elapsed.as_nanos().div_ceil(TICK.as_nanos()) as u64;
+        let deadline = deadline.max(self.current_tick + 1);
+
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        let entry = Entry {
+            id,
+            deadline,
+            key: key.map(|key| key.to_string()),
+            value,
+        };
+        self.slots[deadline as usize % NUM_SLOTS].push(entry);
+        self.len += 1;
+
+        id
+    }
+
+    /// Cancel the item with the given id. Returns true if the item was
+    /// still scheduled.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        self.remove_where(|entry| entry.id == id) > 0
+    }
+
+    /// Cancel all items scheduled with the given key. Returns the number of
+    /// items that were cancelled.
+    pub fn cancel_key(&mut self, key: &str) -> usize {
+        self.remove_where(|entry| entry.key.as_deref() == Some(key))
+    }
+
+    /// Returns the time the next item is due, if any
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let tick = self
+            .slots
+            .iter()
+            .flatten()
+            .map(|entry| entry.deadline)
+            .min()?;
+        Some(self.start + Duration::from_nanos(TICK.as_nanos() as u64 * tick))
+    }
+
+    /// Returns all items that are due at the given time in the order they
+    /// should be dispatched.
+    pub fn poll(&mut self, now: Instant) -> Vec<T> {
+        let target_tick =
+            (now.saturating_duration_since(self.start).as_nanos() / TICK.as_nanos()) as u64;
+        if target_tick <= self.current_tick {
+            return Vec::new();
+        }
+
+        // Only the slots of the elapsed ticks can contain due items. If more
+        // time than a full rotation has elapsed, every slot is checked.
+        let elapsed = (target_tick - self.current_tick).min(NUM_SLOTS as u64);
+        let mut due = Vec::new();
+        for tick in (self.current_tick + 1)..=(self.current_tick + elapsed) {
+            let slot = &mut self.slots[tick as usize % NUM_SLOTS];
+            let mut i = 0;
+            while i < slot.len() {
+                if slot[i].deadline <= target_tick {
+                    due.push(slot.swap_remove(i));
+                    continue;
+                }
+                i += 1;
+            }
+        }
+        self.current_tick = target_tick;
+        self.len -= due.len();
+
+        due.sort_by_key(|entry| (entry.deadline, entry.id));
+        due.into_iter().map(|entry| entry.value).collect()
+    }
+
+    /// Remove all items that match the given predicate
+    fn remove_where<F>(&mut self, predicate: F) -> usize
+    where
+        F: Fn(&Entry<T>) -> bool,
+    {
+        let mut removed = 0;
+        for slot in self.slots.iter_mut() {
+            let before = slot.len();
+            slot.retain(|entry| !predicate(entry));
+            removed += before - slot.len();
+        }
+        self.len -= removed;
+        removed
+    }
+}
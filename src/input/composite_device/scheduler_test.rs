@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use super::scheduler::EventScheduler;
+
+fn ms(value: u64) -> Duration {
+    Duration::from_millis(value)
+}
+
+#[test]
+fn test_scheduler_ordering() {
+    let start = Instant::now();
+    let mut scheduler = EventScheduler::new(start);
+    scheduler.schedule(start, ms(30), None, "c");
+    scheduler.schedule(start, ms(10), None, "a");
+    scheduler.schedule(start, ms(20), None, "b1");
+    scheduler.schedule(start, ms(20), None, "b2");
+    scheduler.schedule(start, ms(20), None, "b3");
+    assert_eq!(scheduler.len(), 5);
+    assert_eq!(scheduler.next_deadline(), Some(start + ms(10)));
+
+    // Nothing should be dispatched early
+    assert!(scheduler.poll(start + ms(9)).is_empty());
+
+    // Items are dispatched in order of their deadline, then in the order
+    // they were scheduled.
+    assert_eq!(scheduler.poll(start + ms(10)), vec!["a"]);
+    assert_eq!(scheduler.poll(start + ms(35)), vec!["b1", "b2", "b3", "c"]);
+    assert!(scheduler.is_empty());
+    assert_eq!(scheduler.next_deadline(), None);
+}
+
+#[test]
+fn test_scheduler_wraps_around() {
+    let start = Instant::now();
+    let mut scheduler = EventScheduler::new(start);
+
+    // Items further away than one rotation of the wheel share slots with
+    // items that are due sooner.
+    scheduler.schedule(start, ms(1000), None, "later");
+    scheduler.schedule(start, ms(1000 % 256), None, "sooner");
+    assert_eq!(scheduler.poll(start + ms(500)), vec!["sooner"]);
+    assert!(scheduler.poll(start + ms(999)).is_empty());
+    assert_eq!(scheduler.poll(start + ms(1000)), vec!["later"]);
+
+    // Items that were due long ago are still dispatched
+    scheduler.schedule(start + ms(1000), ms(5), None, "missed");
+    assert_eq!(scheduler.poll(start + ms(10_000)), vec!["missed"]);
+
+    // Items scheduled in the past are dispatched on the next poll
+    scheduler.schedule(start, ms(0), None, "past");
+    assert_eq!(scheduler.poll(start + ms(10_001)), vec!["past"]);
+}
+
+#[test]
+fn test_scheduler_cancel() {
+    let start = Instant::now();
+    let mut scheduler = EventScheduler::new(start);
+    let id = scheduler.schedule(start, ms(5), None, 1);
+    scheduler.schedule(start, ms(5), Some("mapping"), 2);
+    scheduler.schedule(start, ms(6), Some("mapping"), 3);
+    scheduler.schedule(start, ms(7), Some("other"), 4);
+
+    assert!(scheduler.cancel(id));
+    assert!(!scheduler.cancel(id), "should already be cancelled");
+    assert_eq!(scheduler.cancel_key("mapping"), 2);
+    assert_eq!(scheduler.len(), 1);
+    assert_eq!(scheduler.poll(start + ms(10)), vec![4]);
+}
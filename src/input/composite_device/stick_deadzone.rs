@@ -0,0 +1,192 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    config::StickDeadzoneConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Default distance from the center that is ignored
+const DEFAULT_INNER: f64 = 0.1;
+/// Default distance from the center at which the stick reaches its full range
+const DEFAULT_OUTER: f64 = 1.0;
+
+/// Shape of the dead zone of an analog stick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeadzoneShape {
+    /// The dead zone is a circle around the center. Movement in any direction
+    /// is treated the same, which suits most 3D games.
+    #[default]
+    Radial,
+    /// Each axis has its own dead zone, forming a square around the center.
+    /// Small movements along one axis are dropped even while the other axis
+    /// is deflected, which suits games with 8-way movement.
+    Axial,
+    /// Each axis has its own dead zone that widens as the other axis is
+    /// deflected, forming a bowtie. Near-cardinal movement snaps onto the
+    /// axis, which suits games that need precise straight movement.
+    Cross,
+}
+
+impl fmt::Display for DeadzoneShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeadzoneShape::Radial => write!(f, "radial"),
+            DeadzoneShape::Axial => write!(f, "axial"),
+            DeadzoneShape::Cross => write!(f, "cross"),
+        }
+    }
+}
+
+impl FromStr for DeadzoneShape {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "radial" | "circular" => Ok(DeadzoneShape::Radial),
+            "axial" => Ok(DeadzoneShape::Axial),
+            "cross" | "bowtie" => Ok(DeadzoneShape::Cross),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Shaped dead zone of a single analog stick. Positions outside of the dead
+/// zone are rescaled so the stick still covers its full range, instead of
+/// jumping from zero to the edge of the dead zone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StickDeadzone {
+    shape: DeadzoneShape,
+    inner: f64,
+    outer: f64,
+}
+
+impl StickDeadzone {
+    /// Create a new dead zone from the given configuration
+    pub fn new(config: &StickDeadzoneConfig) -> Self {
+        let shape = config
+            .shape
+            .as_ref()
+            .map(|shape| {
+                DeadzoneShape::from_str(shape).unwrap_or_else(|_| {
+                    log::warn!("Unknown stick dead zone shape: {shape}");
+                    DeadzoneShape::default()
+                })
+            })
+            .unwrap_or_default();
+        let inner = config.inner.unwrap_or(DEFAULT_INNER).clamp(0.0, 1.0);
+        let outer = config.outer.unwrap_or(DEFAULT_OUTER).clamp(inner, 1.0);
+        Self {
+            shape,
+            inner,
+            outer,
+        }
+    }
+
+    /// Apply the dead zone to the given stick position
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        match self.shape {
+            DeadzoneShape::Radial => {
+                let magnitude = x.hypot(y);
+                if magnitude <= self.inner {
+                    return (0.0, 0.0);
+                }
+                let scale = rescale(magnitude, self.inner, self.outer) / magnitude;
+                (clamp(x * scale), clamp(y * scale))
+            }
+            DeadzoneShape::Axial => (
+                rescale(x, self.inner, self.outer),
+                rescale(y, self.inner, self.outer),
+            ),
+            DeadzoneShape::Cross => {
+                // The dead zone of each axis grows from the inner distance at
+                // the center to twice that at the edge of the other axis.
+                let inner_x = self.inner * (1.0 + y.abs().min(1.0));
+                let inner_y = self.inner * (1.0 + x.abs().min(1.0));
+                (
+                    rescale(x, inner_x, self.outer),
+                    rescale(y, inner_y, self.outer),
+                )
+            }
+        }
+    }
+}
+
+/// Rescale the given value so the range between the inner and outer
+/// distances covers the full range from 0.0 to 1.0, keeping its sign.
+fn rescale(value: f64, inner: f64, outer: f64) -> f64 {
+    let magnitude = value.abs();
+    if magnitude <= inner {
+        return 0.0;
+    }
+    let range = (outer - inner).max(f64::EPSILON);
+    ((magnitude - inner) / range).min(1.0) * value.signum()
+}
+
+fn clamp(value: f64) -> f64 {
+    value.clamp(-1.0, 1.0)
+}
+
+/// Applies the shaped dead zones of the device profile to stick events. The
+/// last position of each stick is tracked, since the shape depends on both
+/// axes while source devices may only report the axis that changed.
+#[derive(Debug, Default)]
+pub struct StickDeadzones {
+    /// Dead zone and last raw position of each configured stick
+    sticks: HashMap<GamepadAxis, (StickDeadzone, (f64, f64))>,
+}
+
+impl StickDeadzones {
+    /// Create the dead zones from the given configurations
+    pub fn new(configs: &[StickDeadzoneConfig]) -> Self {
+        let mut sticks = HashMap::new();
+        for config in configs {
+            let stick = match GamepadAxis::from_str(config.stick.as_str()) {
+                Ok(stick @ (GamepadAxis::LeftStick | GamepadAxis::RightStick)) => stick,
+                _ => {
+                    log::warn!("Invalid stick for dead zone: {}", config.stick);
+                    continue;
+                }
+            };
+            sticks.insert(stick, (StickDeadzone::new(config), (0.0, 0.0)));
+        }
+        Self { sticks }
+    }
+
+    /// Returns true if no stick has a dead zone
+    pub fn is_empty(&self) -> bool {
+        self.sticks.is_empty()
+    }
+
+    /// Apply the dead zone of the stick the given event belongs to, if any
+    pub fn apply(&mut self, event: NativeEvent) -> NativeEvent {
+        let Capability::Gamepad(Gamepad::Axis(axis)) = event.as_capability() else {
+            return event;
+        };
+        let Some((deadzone, position)) = self.sticks.get_mut(&axis) else {
+            return event;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return event;
+        };
+        if let Some(x) = x {
+            position.0 = x;
+        }
+        if let Some(y) = y {
+            position.1 = y;
+        }
+        let (x, y) = deadzone.apply(position.0, position.1);
+        let value = InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        };
+
+        let cap = event.as_capability();
+        match event.get_source_capability() {
+            Some(source_cap) => NativeEvent::new_translated(source_cap, cap, value),
+            None => NativeEvent::new(cap, value),
+        }
+    }
+}
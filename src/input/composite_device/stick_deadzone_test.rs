@@ -0,0 +1,126 @@
+use crate::{
+    config::StickDeadzoneConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::stick_deadzone::{StickDeadzone, StickDeadzones};
+
+fn config(stick: &str, shape: &str) -> StickDeadzoneConfig {
+    StickDeadzoneConfig {
+        stick: stick.to_string(),
+        shape: Some(shape.to_string()),
+        inner: Some(0.2),
+        outer: Some(0.9),
+    }
+}
+
+fn assert_near(actual: (f64, f64), expected: (f64, f64)) {
+    let close = (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9;
+    assert!(close, "expected {expected:?}, got {actual:?}");
+}
+
+#[test]
+fn test_radial_deadzone() {
+    let deadzone = StickDeadzone::new(&config("LeftStick", "radial"));
+
+    // Diagonal positions inside the circle are dropped
+    assert_near(deadzone.apply(0.12, 0.12), (0.0, 0.0));
+
+    // Positions are rescaled along their direction, keeping the angle
+    assert_near(deadzone.apply(0.55, 0.0), (0.5, 0.0));
+    let (x, y) = deadzone.apply(0.3, 0.4);
+    assert!((x.hypot(y) - 3.0 / 7.0).abs() < 1e-9);
+    assert!((y / x - 4.0 / 3.0).abs() < 1e-9);
+
+    // Positions beyond the outer distance reach the full range
+    assert_near(deadzone.apply(0.0, -0.95), (0.0, -1.0));
+}
+
+#[test]
+fn test_axial_deadzone() {
+    let deadzone = StickDeadzone::new(&config("LeftStick", "axial"));
+
+    // Small movement along one axis is dropped while the other is deflected
+    assert_near(deadzone.apply(0.15, 0.9), (0.0, 1.0));
+    assert_near(deadzone.apply(-0.55, 0.1), (-0.5, 0.0));
+
+    // Diagonals outside of both dead zones are kept
+    assert_near(deadzone.apply(0.55, 0.55), (0.5, 0.5));
+}
+
+#[test]
+fn test_cross_deadzone() {
+    let deadzone = StickDeadzone::new(&config("LeftStick", "cross"));
+
+    // The center behaves like an axial dead zone
+    assert_near(deadzone.apply(0.15, 0.0), (0.0, 0.0));
+    assert_near(deadzone.apply(0.55, 0.0), (0.5, 0.0));
+
+    // Near-cardinal movement snaps onto the axis, where an axial dead zone
+    // of the same size would keep the offset
+    assert_near(deadzone.apply(0.3, 0.9), (0.0, 1.0));
+    let axial = StickDeadzone::new(&config("LeftStick", "axial"));
+    assert!(axial.apply(0.3, 0.9).0 > 0.0);
+}
+
+#[test]
+fn test_unknown_shape_is_radial() {
+    let deadzone = StickDeadzone::new(&config("LeftStick", "triangle"));
+    assert_near(deadzone.apply(0.12, 0.12), (0.0, 0.0));
+}
+
+#[test]
+fn test_stick_deadzones() {
+    let mut deadzones = StickDeadzones::new(&[
+        config("LeftStick", "axial"),
+        config("Hat0", "axial"),
+        config("Bogus", "axial"),
+    ]);
+    assert!(!deadzones.is_empty());
+    let left_stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let right_stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick));
+
+    // Sources that only report the changed axis get both axes back, using
+    // the last known position of the other axis
+    let event = deadzones.apply(NativeEvent::new(
+        left_stick.clone(),
+        InputValue::Vector2 {
+            x: Some(0.55),
+            y: None,
+        },
+    ));
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if (x - 0.5).abs() < 1e-9 && y == 0.0
+    ));
+    let event = deadzones.apply(NativeEvent::new(
+        left_stick,
+        InputValue::Vector2 {
+            x: None,
+            y: Some(0.1),
+        },
+    ));
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if (x - 0.5).abs() < 1e-9 && y == 0.0
+    ));
+
+    // Sticks without a dead zone are passed through unchanged
+    let event = deadzones.apply(NativeEvent::new(
+        right_stick,
+        InputValue::Vector2 {
+            x: Some(0.1),
+            y: None,
+        },
+    ));
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 {
+            x: Some(_),
+            y: None
+        }
+    ));
+}
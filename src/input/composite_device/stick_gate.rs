@@ -0,0 +1,219 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    config::StickGateConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Default distance from the center at which a direction is engaged
+const DEFAULT_THRESHOLD: f64 = 0.5;
+/// Default width in degrees of the diagonal directions of an 8-way gate
+const DEFAULT_DIAGONAL_ANGLE: f64 = 45.0;
+/// Angle in degrees the stick must move past the edge of the engaged
+/// direction before another direction is engaged, so the output does not
+/// flicker while the stick rests on the edge between two directions.
+const HYSTERESIS: f64 = 5.0;
+/// Axis values of a direction below this magnitude (about sin 22.5°) are
+/// centered
+const SNAP_THRESHOLD: f64 = 0.3827;
+
+/// Number of directions a stick gate allows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GateMode {
+    /// Only up, down, left, and right, like the gate of classic maze games
+    FourWay,
+    /// The cardinal directions and the diagonals
+    #[default]
+    EightWay,
+}
+
+impl fmt::Display for GateMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GateMode::FourWay => write!(f, "4way"),
+            GateMode::EightWay => write!(f, "8way"),
+        }
+    }
+}
+
+impl FromStr for GateMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4way" | "4-way" => Ok(GateMode::FourWay),
+            "8way" | "8-way" => Ok(GateMode::EightWay),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Restrictor gate of a single analog stick. Positions past the threshold
+/// are quantized to full deflection in the nearest allowed direction, and
+/// positions inside it are centered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StickGate {
+    mode: GateMode,
+    threshold: f64,
+    diagonal_angle: f64,
+    rotation: f64,
+    /// Angle in degrees of the engaged direction relative to the rotation
+    direction: Option<f64>,
+}
+
+impl StickGate {
+    /// Create a new gate from the given configuration
+    pub fn new(config: &StickGateConfig) -> Self {
+        let mode = config
+            .mode
+            .as_ref()
+            .map(|mode| {
+                GateMode::from_str(mode).unwrap_or_else(|_| {
+                    log::warn!("Unknown stick gate mode: {mode}");
+                    GateMode::default()
+                })
+            })
+            .unwrap_or_default();
+        let threshold = config
+            .threshold
+            .unwrap_or(DEFAULT_THRESHOLD)
+            .clamp(0.0, 1.0);
+        let diagonal_angle = config
+            .diagonal_angle
+            .unwrap_or(DEFAULT_DIAGONAL_ANGLE)
+            .clamp(0.0, 90.0);
+        let rotation = config.rotation.unwrap_or_default();
+        Self {
+            mode,
+            threshold,
+            diagonal_angle,
+            rotation,
+            direction: None,
+        }
+    }
+
+    /// Apply the gate to the given stick position
+    pub fn apply(&mut self, x: f64, y: f64) -> (f64, f64) {
+        if x.hypot(y) < self.threshold {
+            self.direction = None;
+            return (0.0, 0.0);
+        }
+        let angle = (y.atan2(x).to_degrees() - self.rotation).rem_euclid(360.0);
+
+        // Keep the engaged direction until the stick clearly leaves it
+        let direction = match self.direction {
+            Some(direction) if self.contains(direction, angle) => direction,
+            _ => self.nearest(angle),
+        };
+        self.direction = Some(direction);
+
+        let output = (direction + self.rotation).to_radians();
+        (snap(output.cos()), snap(output.sin()))
+    }
+
+    /// Returns the allowed direction whose sector contains the given angle
+    fn nearest(&self, angle: f64) -> f64 {
+        match self.mode {
+            GateMode::FourWay => ((angle / 90.0).round() * 90.0).rem_euclid(360.0),
+            GateMode::EightWay => {
+                let quadrant = (angle / 90.0).floor() * 90.0;
+                let offset = angle - quadrant;
+                let cardinal = (90.0 - self.diagonal_angle) / 2.0;
+                if offset < cardinal {
+                    quadrant
+                } else if offset > 90.0 - cardinal {
+                    (quadrant + 90.0).rem_euclid(360.0)
+                } else {
+                    quadrant + 45.0
+                }
+            }
+        }
+    }
+
+    /// Returns true if the given angle is inside the sector of the given
+    /// direction, including the hysteresis
+    fn contains(&self, direction: f64, angle: f64) -> bool {
+        let is_diagonal = (direction / 45.0).round() as i64 % 2 == 1;
+        let width = match (self.mode, is_diagonal) {
+            (GateMode::FourWay, _) => 90.0,
+            (GateMode::EightWay, true) => self.diagonal_angle,
+            (GateMode::EightWay, false) => 90.0 - self.diagonal_angle,
+        };
+        let distance = (angle - direction).rem_euclid(360.0);
+        let distance = distance.min(360.0 - distance);
+        width > 0.0 && distance <= width / 2.0 + HYSTERESIS
+    }
+}
+
+/// Snap the given component of a direction to a full axis value
+fn snap(value: f64) -> f64 {
+    if value.abs() < SNAP_THRESHOLD {
+        return 0.0;
+    }
+    value.signum()
+}
+
+/// Applies the stick gates of the device profile to stick events. The last
+/// position of each stick is tracked, since the direction depends on both
+/// axes while source devices may only report the axis that changed.
+#[derive(Debug, Default)]
+pub struct StickGates {
+    /// Gate and last position of each configured stick
+    sticks: HashMap<GamepadAxis, (StickGate, (f64, f64))>,
+}
+
+impl StickGates {
+    /// Create the gates from the given configurations
+    pub fn new(configs: &[StickGateConfig]) -> Self {
+        let mut sticks = HashMap::new();
+        for config in configs {
+            let stick = match GamepadAxis::from_str(config.stick.as_str()) {
+                Ok(stick @ (GamepadAxis::LeftStick | GamepadAxis::RightStick)) => stick,
+                _ => {
+                    log::warn!("Invalid stick for gate: {}", config.stick);
+                    continue;
+                }
+            };
+            sticks.insert(stick, (StickGate::new(config), (0.0, 0.0)));
+        }
+        Self { sticks }
+    }
+
+    /// Returns true if no stick has a gate
+    pub fn is_empty(&self) -> bool {
+        self.sticks.is_empty()
+    }
+
+    /// Apply the gate of the stick the given event belongs to, if any
+    pub fn apply(&mut self, event: NativeEvent) -> NativeEvent {
+        let Capability::Gamepad(Gamepad::Axis(axis)) = event.as_capability() else {
+            return event;
+        };
+        let Some((gate, position)) = self.sticks.get_mut(&axis) else {
+            return event;
+        };
+        let InputValue::Vector2 { x, y } = event.get_value() else {
+            return event;
+        };
+        if let Some(x) = x {
+            position.0 = x;
+        }
+        if let Some(y) = y {
+            position.1 = y;
+        }
+        let (x, y) = gate.apply(position.0, position.1);
+        let value = InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        };
+
+        let cap = event.as_capability();
+        match event.get_source_capability() {
+            Some(source_cap) => NativeEvent::new_translated(source_cap, cap, value),
+            None => NativeEvent::new(cap, value),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    config::StickGateConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::stick_gate::{StickGate, StickGates};
+
+fn config(stick: &str, mode: &str) -> StickGateConfig {
+    StickGateConfig {
+        stick: stick.to_string(),
+        mode: Some(mode.to_string()),
+        threshold: Some(0.5),
+        diagonal_angle: None,
+        rotation: None,
+    }
+}
+
+/// Returns the stick position at the given angle in degrees and distance
+fn at(angle: f64, distance: f64) -> (f64, f64) {
+    let angle = angle.to_radians();
+    (angle.cos() * distance, angle.sin() * distance)
+}
+
+#[test]
+fn test_eight_way_gate() {
+    let mut gate = StickGate::new(&config("LeftStick", "8way"));
+
+    // Positions inside the threshold are centered
+    assert_eq!(gate.apply(0.3, 0.3), (0.0, 0.0));
+
+    // Positions are quantized to full deflection in the nearest direction
+    let (x, y) = at(10.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 0.0));
+    let (x, y) = at(50.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 1.0));
+    let (x, y) = at(-95.0, 0.8);
+    assert_eq!(gate.apply(x, y), (0.0, -1.0));
+    let (x, y) = at(200.0, 1.0);
+    assert_eq!(gate.apply(x, y), (-1.0, 0.0));
+}
+
+#[test]
+fn test_four_way_gate() {
+    let mut gate = StickGate::new(&config("LeftStick", "4way"));
+
+    // Diagonals snap to the nearest cardinal direction
+    let (x, y) = at(40.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 0.0));
+
+    // The engaged direction is kept on the edge between two directions
+    let (x, y) = at(48.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 0.0));
+    let (x, y) = at(55.0, 0.8);
+    assert_eq!(gate.apply(x, y), (0.0, 1.0));
+    let (x, y) = at(42.0, 0.8);
+    assert_eq!(gate.apply(x, y), (0.0, 1.0));
+
+    // Returning to the center releases the direction
+    assert_eq!(gate.apply(0.0, 0.0), (0.0, 0.0));
+    let (x, y) = at(42.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 0.0));
+}
+
+#[test]
+fn test_gate_angles() {
+    // Narrow diagonals make cardinal directions easier to hit
+    let mut gate = StickGate::new(&StickGateConfig {
+        diagonal_angle: Some(20.0),
+        ..config("LeftStick", "8way")
+    });
+    let (x, y) = at(30.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 0.0));
+    gate.apply(0.0, 0.0);
+    let (x, y) = at(40.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 1.0));
+
+    // A rotated 4-way gate only allows diagonals
+    let mut gate = StickGate::new(&StickGateConfig {
+        rotation: Some(45.0),
+        ..config("LeftStick", "4way")
+    });
+    let (x, y) = at(10.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, 1.0));
+    let (x, y) = at(-60.0, 0.8);
+    assert_eq!(gate.apply(x, y), (1.0, -1.0));
+}
+
+#[test]
+fn test_stick_gates() {
+    let mut gates = StickGates::new(&[config("RightStick", "8way")]);
+    let right_stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick));
+    let left_stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+
+    // Sticks without a gate are passed through
+    let event = NativeEvent::new(
+        left_stick,
+        InputValue::Vector2 {
+            x: Some(0.3),
+            y: Some(0.7),
+        },
+    );
+    let event = gates.apply(event);
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if x == 0.3 && y == 0.7
+    ));
+
+    // Partial updates use the last position of the other axis
+    let event = NativeEvent::new(
+        right_stick.clone(),
+        InputValue::Vector2 {
+            x: Some(0.9),
+            y: None,
+        },
+    );
+    gates.apply(event);
+    let event = NativeEvent::new(
+        right_stick,
+        InputValue::Vector2 {
+            x: None,
+            y: Some(-0.9),
+        },
+    );
+    let event = gates.apply(event);
+    assert!(matches!(
+        event.get_value(),
+        InputValue::Vector2 { x: Some(x), y: Some(y) } if x == 1.0 && y == -1.0
+    ));
+}
@@ -0,0 +1,75 @@
+use crate::{config::TargetFilter, input::capability::GamepadButton};
+
+use super::{test_events::button_capability, TargetEventFilter};
+
+const ALLOW_CONFIG: &str = r#"
+target: xb360
+allow:
+  - gamepad:
+      button: South
+  - gamepad:
+      button: East
+deny:
+  - gamepad:
+      button: East
+"#;
+
+const DENY_CONFIG: &str = r#"
+target: xb360
+deny:
+  - gamepad:
+      button: Guide
+"#;
+
+const DBUS_CONFIG: &str = r#"
+target: dbus
+allow:
+  - gamepad:
+      button: QuickAccess
+"#;
+
+#[test]
+fn test_target_filter_allow() {
+    let config: TargetFilter = serde_yaml::from_str(ALLOW_CONFIG).unwrap();
+    let filter = TargetEventFilter::from(&config);
+
+    // Only capabilities in the allow list should be routed
+    assert!(filter.allows(&button_capability(GamepadButton::South)));
+    assert!(!filter.allows(&button_capability(GamepadButton::North)));
+
+    // Deny should take precedence over allow
+    assert!(!filter.allows(&button_capability(GamepadButton::East)));
+}
+
+#[test]
+fn test_target_filter_deny() {
+    let config: TargetFilter = serde_yaml::from_str(DENY_CONFIG).unwrap();
+    let filter = TargetEventFilter::from(&config);
+
+    // Everything but the denied capabilities should be routed
+    assert!(filter.allows(&button_capability(GamepadButton::South)));
+    assert!(!filter.allows(&button_capability(GamepadButton::Guide)));
+
+    // Nothing is mirrored without an allow list
+    assert!(!filter.mirrors(&button_capability(GamepadButton::South)));
+
+    // An empty filter should route everything
+    let filter = TargetEventFilter::default();
+    assert!(filter.allows(&button_capability(GamepadButton::Guide)));
+    assert!(!filter.mirrors(&button_capability(GamepadButton::Guide)));
+}
+
+#[test]
+fn test_target_filter_mirror() {
+    let config: TargetFilter = serde_yaml::from_str(DBUS_CONFIG).unwrap();
+    let filter = TargetEventFilter::from(&config);
+
+    // Only explicitly allowed capabilities should be mirrored to DBus targets
+    assert!(filter.mirrors(&button_capability(GamepadButton::QuickAccess)));
+    assert!(!filter.mirrors(&button_capability(GamepadButton::South)));
+
+    let config: TargetFilter = serde_yaml::from_str(ALLOW_CONFIG).unwrap();
+    let filter = TargetEventFilter::from(&config);
+    assert!(filter.mirrors(&button_capability(GamepadButton::South)));
+    assert!(!filter.mirrors(&button_capability(GamepadButton::East)));
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use crate::input::event::native::NativeEvent;
+
+/// Events waiting to be written to target devices as part of the current
+/// input frame. Events read from a source device between two SYN_REPORT
+/// events form a frame, and all events a frame produces for a target device
+/// are written together so they land in the same input frame of the target
+/// device (e.g. the X and Y axes of a stick).
+#[derive(Debug, Default)]
+pub struct TargetFrames {
+    /// Open frames keyed by target device path
+    frames: HashMap<String, Vec<NativeEvent>>,
+}
+
+impl TargetFrames {
+    /// Returns true if no events are waiting
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Add the given event to the open frame of the given target device. A
+    /// frame can only carry one value of each input, so if the open frame
+    /// already has an event of the same capability (e.g. a press and release
+    /// of a button), the open frame is returned to be written first and the
+    /// event starts a new frame.
+    pub fn push(&mut self, path: &str, event: NativeEvent) -> Option<Vec<NativeEvent>> {
+        let frame = self.frames.entry(path.to_string()).or_default();
+        let cap = event.as_capability();
+        let conflicts = frame.iter().any(|pending| pending.as_capability() == cap);
+        if !conflicts {
+            frame.push(event);
+            return None;
+        }
+        Some(std::mem::replace(frame, vec![event]))
+    }
+
+    /// Take the open frames of all target devices
+    pub fn take(&mut self) -> Vec<(String, Vec<NativeEvent>)> {
+        self.frames.drain().collect()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::input::event::value::InputValue;
+
+use super::target_frame::TargetFrames;
+use super::test_events::{button, stick};
+
+const TARGET: &str = "/org/shadowblip/InputPlumber/devices/target/gamepad0";
+
+#[test]
+fn test_target_frames() {
+    let mut frames = TargetFrames::default();
+    assert!(frames.is_empty());
+
+    // Different inputs should be written in the same frame
+    assert!(frames.push(TARGET, stick(Some(1.0), None)).is_none());
+    assert!(frames.push(TARGET, button(true)).is_none());
+    assert!(frames.push("other", button(true)).is_none());
+
+    // A second value of the same input should start a new frame
+    let frame = frames.push(TARGET, button(false)).unwrap();
+    assert_eq!(frame.len(), 2);
+    assert!(matches!(frame[1].get_value(), InputValue::Bool(true)));
+
+    let mut taken = frames.take();
+    taken.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(taken.len(), 2);
+    assert_eq!(taken[1].0, TARGET);
+    assert_eq!(taken[1].1.len(), 1);
+    assert!(matches!(taken[1].1[0].get_value(), InputValue::Bool(false)));
+    assert!(frames.is_empty());
+}
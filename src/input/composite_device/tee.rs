@@ -0,0 +1,126 @@
+use std::{
+    error::Error,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use crate::{
+    config::TeeConfig,
+    input::event::{native::NativeEvent, value::InputValue},
+};
+
+/// A sink that receives a copy of the events written to the target devices
+/// of a composite device, so one controller can drive a local game and a
+/// second machine (e.g. a capture or streaming PC) at the same time.
+///
+/// Events are sent over UDP as one datagram per event, each holding a line
+/// with the capability and the value of the event. E.g.
+/// "Gamepad:Axis:LeftStick x=0.5 y=-0.25".
+#[derive(Debug)]
+pub struct TeeSink {
+    name: String,
+    socket: UdpSocket,
+    address: SocketAddr,
+    enabled: bool,
+}
+
+impl TeeSink {
+    /// Create a new tee sink from the given config
+    pub fn new(config: &TeeConfig) -> Result<Self, Box<dyn Error>> {
+        let Some(udp) = config.udp.as_ref() else {
+            return Err(format!("No sink address defined for tee '{}'", config.name).into());
+        };
+        let Some(address) = udp.to_socket_addrs()?.next() else {
+            return Err(format!("Unable to resolve tee address: {udp}").into());
+        };
+        let bind = if address.is_ipv6() {
+            "[::]:0"
+        } else {
+            "0.0.0.0:0"
+        };
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            socket,
+            address,
+            enabled: config.enabled.unwrap_or(true),
+        })
+    }
+
+    /// Returns the name of the sink
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns true if events are sent to the sink
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Set whether or not events are sent to the sink
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Send the given event to the sink, if it is enabled. Events are dropped
+    /// instead of waiting if the sink cannot keep up, so it never delays the
+    /// local target devices.
+    pub fn send(&self, event: &NativeEvent) {
+        if !self.enabled {
+            return;
+        }
+        let line = encode_event(event);
+        match self.socket.send_to(line.as_bytes(), self.address) {
+            Ok(_) => (),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                log::trace!("Tee '{}' is busy, dropping event", self.name);
+            }
+            Err(e) => log::debug!("Failed to send event to tee '{}': {e:?}", self.name),
+        }
+    }
+}
+
+/// Encode the given event as a line with its capability and value. E.g.
+/// "Gamepad:Button:South pressed"
+pub fn encode_event(event: &NativeEvent) -> String {
+    let capability = event.as_capability();
+    format!("{capability} {}\n", encode_value(&event.get_value()))
+}
+
+/// Encode the given input value without losing precision. Missing axis
+/// values are encoded as "-".
+fn encode_value(value: &InputValue) -> String {
+    let axis = |name: &str, value: &Option<f64>| match value {
+        Some(value) => format!("{name}={value}"),
+        None => format!("{name}=-"),
+    };
+    match value {
+        InputValue::None => "none".to_string(),
+        InputValue::Bool(pressed) => {
+            if *pressed {
+                "pressed".to_string()
+            } else {
+                "released".to_string()
+            }
+        }
+        InputValue::Float(value) => value.to_string(),
+        InputValue::Vector2 { x, y } => format!("{} {}", axis("x", x), axis("y", y)),
+        InputValue::Vector3 { x, y, z } => {
+            format!("{} {} {}", axis("x", x), axis("y", y), axis("z", z))
+        }
+        InputValue::Touch {
+            index,
+            is_touching,
+            pressure,
+            x,
+            y,
+        } => format!(
+            "index={index} touching={is_touching} {} {} {}",
+            axis("x", x),
+            axis("y", y),
+            axis("pressure", pressure)
+        ),
+    }
+}
@@ -0,0 +1,48 @@
+use std::{net::UdpSocket, time::Duration};
+
+use crate::{
+    config::TeeConfig,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::tee::{encode_event, TeeSink};
+
+#[test]
+fn test_encode_event() {
+    let cap = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let value = InputValue::Vector2 {
+        x: Some(0.5),
+        y: None,
+    };
+    let line = encode_event(&NativeEvent::new(cap, value));
+    assert_eq!(line, "Gamepad:Axis:LeftStick x=0.5 y=-\n");
+}
+
+#[test]
+fn test_tee_sink() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    let config = TeeConfig {
+        name: "stream".to_string(),
+        udp: Some(receiver.local_addr().unwrap().to_string()),
+        enabled: None,
+    };
+    let mut sink = TeeSink::new(&config).unwrap();
+    assert!(sink.enabled());
+
+    let cap = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    sink.send(&NativeEvent::new(cap.clone(), InputValue::Bool(true)));
+    let mut buf = [0; 128];
+    let len = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"Gamepad:Button:South pressed\n");
+
+    // Disabled sinks do not receive events
+    sink.set_enabled(false);
+    sink.send(&NativeEvent::new(cap, InputValue::Bool(false)));
+    assert!(receiver.recv(&mut buf).is_err());
+}
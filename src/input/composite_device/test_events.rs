@@ -0,0 +1,48 @@
+//! Native event factories shared by the input tests
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, Keyboard},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// South button event with the given state
+pub fn button(pressed: bool) -> NativeEvent {
+    button_event(GamepadButton::South, pressed)
+}
+
+/// Event of the given button with the given state
+pub fn button_event(button: GamepadButton, pressed: bool) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Button(button)),
+        InputValue::Bool(pressed),
+    )
+}
+
+/// Capability of the given button
+pub fn button_capability(button: GamepadButton) -> Capability {
+    Capability::Gamepad(Gamepad::Button(button))
+}
+
+/// Event of the given key with the given state
+pub fn key_event(key: Keyboard, pressed: bool) -> NativeEvent {
+    NativeEvent::new(Capability::Keyboard(key), InputValue::Bool(pressed))
+}
+
+/// Left stick event that only updates the given axes
+pub fn stick(x: Option<f64>, y: Option<f64>) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+        InputValue::Vector2 { x, y },
+    )
+}
+
+/// Event of the given stick at the given position
+pub fn stick_event(axis: GamepadAxis, x: f64, y: f64) -> NativeEvent {
+    NativeEvent::new(
+        Capability::Gamepad(Gamepad::Axis(axis)),
+        InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        },
+    )
+}
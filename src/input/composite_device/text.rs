@@ -0,0 +1,175 @@
+use std::{collections::HashMap, error::Error, fs};
+
+use evdev::{EventType, InputEvent};
+use xkbcommon::xkb;
+
+use crate::input::{
+    capability::{Capability, Keyboard},
+    event::{evdev::EvdevEvent, native::NativeEvent, value::InputValue},
+};
+
+/// Path to the system keyboard layout configuration written by systemd-localed
+const VCONSOLE_CONF_PATH: &str = "/etc/vconsole.conf";
+/// Offset between XKB keycodes and evdev key codes
+const EVDEV_OFFSET: u32 = 8;
+
+/// Modifiers that need to be held to produce a shift level of a key. Levels
+/// follow the usual XKB convention where the second level is reached with
+/// Shift, the third with AltGr, and the fourth with Shift+AltGr.
+const LEVEL_MODIFIERS: &[&[Keyboard]] = &[
+    &[],
+    &[Keyboard::KeyLeftShift],
+    &[Keyboard::KeyRightAlt],
+    &[Keyboard::KeyLeftShift, Keyboard::KeyRightAlt],
+];
+
+/// A key and the modifiers that must be held to type a single character
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyStroke {
+    pub key: Keyboard,
+    pub modifiers: Vec<Keyboard>,
+}
+
+/// Names of the XKB rules, model, layout, variant, and options used to look up
+/// the system keymap. Empty values use the XKB defaults (which can be set with
+/// the `XKB_DEFAULT_*` environment variables).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayoutNames {
+    pub model: String,
+    pub layout: String,
+    pub variant: String,
+    pub options: Option<String>,
+}
+
+impl LayoutNames {
+    /// Read the configured system keyboard layout from "/etc/vconsole.conf"
+    pub fn from_system() -> Self {
+        let Ok(content) = fs::read_to_string(VCONSOLE_CONF_PATH) else {
+            log::debug!("Unable to read {VCONSOLE_CONF_PATH}. Using default keyboard layout.");
+            return Self::default();
+        };
+        Self::from_vconsole_conf(content.as_str())
+    }
+
+    /// Parse the keyboard layout from the given vconsole.conf contents
+    pub fn from_vconsole_conf(content: &str) -> Self {
+        let mut names = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"').to_string();
+            match key.trim() {
+                "XKBMODEL" => names.model = value,
+                "XKBLAYOUT" => names.layout = value,
+                "XKBVARIANT" => names.variant = value,
+                "XKBOPTIONS" => names.options = Some(value),
+                _ => (),
+            }
+        }
+        names
+    }
+}
+
+/// Resolves the key strokes needed to type text under a keyboard layout
+#[derive(Debug, Clone, Default)]
+pub struct TextResolver {
+    keys: HashMap<char, KeyStroke>,
+}
+
+impl TextResolver {
+    /// Create a new resolver from the given character to key stroke map
+    pub fn new(keys: HashMap<char, KeyStroke>) -> Self {
+        Self { keys }
+    }
+
+    /// Create a new resolver using the keymap compiled from the given XKB
+    /// layout names. Only the first layout group of the keymap is used.
+    pub fn from_layout(names: &LayoutNames) -> Result<Self, Box<dyn Error>> {
+        let context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
+        let keymap = xkb::Keymap::new_from_names(
+            &context,
+            "",
+            names.model.as_str(),
+            names.layout.as_str(),
+            names.variant.as_str(),
+            names.options.clone(),
+            xkb::KEYMAP_COMPILE_NO_FLAGS,
+        )
+        .ok_or_else(|| format!("Failed to compile keymap for layout: {names:?}"))?;
+
+        let mut keys = HashMap::new();
+        let min = keymap.min_keycode().raw();
+        let max = keymap.max_keycode().raw();
+        for (level, modifiers) in LEVEL_MODIFIERS.iter().enumerate() {
+            for keycode in min..=max {
+                let Some(key) = keyboard_key(keycode) else {
+                    continue;
+                };
+                let keycode = xkb::Keycode::new(keycode);
+                if keymap.num_levels_for_key(keycode, 0) <= level as u32 {
+                    continue;
+                }
+                for keysym in keymap.key_get_syms_by_level(keycode, 0, level as u32) {
+                    let Some(c) = char::from_u32(xkb::keysym_to_utf32(*keysym)) else {
+                        continue;
+                    };
+                    if c == '\0' {
+                        continue;
+                    }
+                    // Prefer the lowest level and keycode that types a character
+                    keys.entry(c).or_insert_with(|| KeyStroke {
+                        key: key.clone(),
+                        modifiers: modifiers.to_vec(),
+                    });
+                }
+            }
+        }
+        log::debug!("Resolved {} characters from keyboard layout", keys.len());
+
+        Ok(Self::new(keys))
+    }
+
+    /// Returns the key stroke needed to type the given character
+    pub fn key_stroke(&self, c: char) -> Option<&KeyStroke> {
+        // The Return key types a carriage return
+        let c = if c == '\n' { '\r' } else { c };
+        self.keys.get(&c)
+    }
+
+    /// Returns the sequence of keyboard events needed to type the given text.
+    /// Characters that cannot be typed with the keyboard layout are skipped.
+    pub fn resolve(&self, text: &str) -> Vec<NativeEvent> {
+        let mut events = Vec::new();
+        for c in text.chars() {
+            let Some(stroke) = self.key_stroke(c) else {
+                log::warn!("Unable to type character {c:?} with the keyboard layout");
+                continue;
+            };
+            for modifier in stroke.modifiers.iter() {
+                events.push(key_event(modifier.clone(), true));
+            }
+            events.push(key_event(stroke.key.clone(), true));
+            events.push(key_event(stroke.key.clone(), false));
+            for modifier in stroke.modifiers.iter().rev() {
+                events.push(key_event(modifier.clone(), false));
+            }
+        }
+        events
+    }
+}
+
+/// Returns the keyboard key for the given XKB keycode
+fn keyboard_key(keycode: u32) -> Option<Keyboard> {
+    let code = keycode.checked_sub(EVDEV_OFFSET)?;
+    let event = InputEvent::new(EventType::KEY.0, u16::try_from(code).ok()?, 1);
+    match EvdevEvent::from(event).as_capability() {
+        Capability::Keyboard(key) => Some(key),
+        _ => None,
+    }
+}
+
+/// Returns a keyboard event for the given key
+fn key_event(key: Keyboard, pressed: bool) -> NativeEvent {
+    NativeEvent::new(Capability::Keyboard(key), InputValue::Bool(pressed))
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use crate::input::capability::{Capability, Keyboard};
+
+use super::text::{KeyStroke, LayoutNames, TextResolver};
+
+#[test]
+fn test_layout_names() {
+    let content = r#"
+KEYMAP=de-latin1
+XKBLAYOUT="de"
+XKBVARIANT=nodeadkeys
+XKBOPTIONS="compose:ralt"
+"#;
+    let names = LayoutNames::from_vconsole_conf(content);
+    assert_eq!(names.layout, "de");
+    assert_eq!(names.variant, "nodeadkeys");
+    assert_eq!(names.model, "");
+    assert_eq!(names.options, Some("compose:ralt".to_string()));
+}
+
+#[test]
+fn test_resolve_text() {
+    let mut keys = HashMap::new();
+    keys.insert(
+        'a',
+        KeyStroke {
+            key: Keyboard::KeyA,
+            modifiers: vec![],
+        },
+    );
+    keys.insert(
+        'A',
+        KeyStroke {
+            key: Keyboard::KeyA,
+            modifiers: vec![Keyboard::KeyLeftShift],
+        },
+    );
+    keys.insert(
+        '\r',
+        KeyStroke {
+            key: Keyboard::KeyEnter,
+            modifiers: vec![],
+        },
+    );
+    let resolver = TextResolver::new(keys);
+
+    // Characters that cannot be typed should be skipped
+    let events = resolver.resolve("aÄA\n");
+    let events: Vec<(Capability, bool)> = events
+        .into_iter()
+        .map(|event| (event.as_capability(), event.pressed()))
+        .collect();
+    let key = |key: Keyboard, pressed: bool| (Capability::Keyboard(key), pressed);
+    assert_eq!(
+        events,
+        vec![
+            key(Keyboard::KeyA, true),
+            key(Keyboard::KeyA, false),
+            key(Keyboard::KeyLeftShift, true),
+            key(Keyboard::KeyA, true),
+            key(Keyboard::KeyA, false),
+            key(Keyboard::KeyLeftShift, false),
+            key(Keyboard::KeyEnter, true),
+            key(Keyboard::KeyEnter, false),
+        ]
+    );
+}
@@ -0,0 +1,143 @@
+use crate::{config::TouchEdgeScroll, input::capability::MouseButton};
+
+/// Default distance the finger needs to travel to emit one wheel step
+const DEFAULT_SCROLL_STEP: f64 = 0.05;
+/// Default number of pixels the pointer moves when the finger travels across
+/// the full touch device
+pub const DEFAULT_POINTER_PIXELS: u64 = 800;
+
+/// Region of a touch device where a touch started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchRegion {
+    /// The touch moves the pointer
+    Pointer,
+    /// The touch started in the vertical scroll strip along the right edge
+    VerticalScroll,
+    /// The touch started in the horizontal scroll strip along the bottom edge
+    HorizontalScroll,
+}
+
+/// The result of updating a [TouchMouseState]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TouchMouseUpdate {
+    /// Whole pixels the pointer should move
+    pub motion: Option<(f64, f64)>,
+    /// Wheel steps that should be emitted, in order
+    pub wheel: Vec<MouseButton>,
+}
+
+/// Tracks the finger on a touch device that is translated into relative mouse
+/// motion. Touches that start inside an edge scroll strip emit mouse wheel
+/// steps instead of pointer motion until the finger is lifted.
+#[derive(Debug, Clone, Default)]
+pub struct TouchMouseState {
+    /// Last known position of the finger while touching
+    position: Option<(f64, f64)>,
+    /// Region the current touch started in
+    region: Option<TouchRegion>,
+    /// Pointer motion that has not yet produced a whole pixel
+    motion_remainder: (f64, f64),
+    /// Scroll distance that has not yet produced a wheel step
+    scroll_remainder: f64,
+}
+
+impl TouchMouseState {
+    /// Update the state with the given touch values. Values that are [None]
+    /// will use the last known value for that axis. Pointer motion is scaled
+    /// so moving across the full touch device moves the given number of
+    /// pixels.
+    pub fn update(
+        &mut self,
+        is_touching: bool,
+        x: Option<f64>,
+        y: Option<f64>,
+        pixels: f64,
+        edge_scroll: Option<&TouchEdgeScroll>,
+    ) -> TouchMouseUpdate {
+        if !is_touching {
+            *self = Self::default();
+            return TouchMouseUpdate::default();
+        }
+
+        // The first event of a touch determines which region is used
+        let Some((last_x, last_y)) = self.position else {
+            let (Some(x), Some(y)) = (x, y) else {
+                return TouchMouseUpdate::default();
+            };
+            self.position = Some((x, y));
+            self.region = Some(region_at(x, y, edge_scroll));
+            return TouchMouseUpdate::default();
+        };
+        let x = x.unwrap_or(last_x);
+        let y = y.unwrap_or(last_y);
+        self.position = Some((x, y));
+        let (dx, dy) = (x - last_x, y - last_y);
+
+        let mut update = TouchMouseUpdate::default();
+        match self.region.unwrap_or(TouchRegion::Pointer) {
+            TouchRegion::Pointer => {
+                // Keep track of fractional pixels in between updates
+                let x = self.motion_remainder.0 + dx * pixels;
+                let y = self.motion_remainder.1 + dy * pixels;
+                self.motion_remainder = (x.fract(), y.fract());
+                let (x, y) = (x.trunc(), y.trunc());
+                if x != 0.0 || y != 0.0 {
+                    update.motion = Some((x, y));
+                }
+            }
+            TouchRegion::VerticalScroll => {
+                let step = scroll_step(edge_scroll);
+                let steps = self.scroll(dy, step);
+                let button = if steps > 0 {
+                    MouseButton::WheelDown
+                } else {
+                    MouseButton::WheelUp
+                };
+                update.wheel = vec![button; steps.unsigned_abs() as usize];
+            }
+            TouchRegion::HorizontalScroll => {
+                let step = scroll_step(edge_scroll);
+                let steps = self.scroll(dx, step);
+                let button = if steps > 0 {
+                    MouseButton::WheelRight
+                } else {
+                    MouseButton::WheelLeft
+                };
+                update.wheel = vec![button; steps.unsigned_abs() as usize];
+            }
+        }
+
+        update
+    }
+
+    /// Accumulate the given scroll distance and return the number of whole
+    /// wheel steps it produced. Negative steps scroll up or left.
+    fn scroll(&mut self, distance: f64, step: f64) -> i64 {
+        self.scroll_remainder += distance;
+        let steps = (self.scroll_remainder / step).trunc();
+        self.scroll_remainder -= steps * step;
+        steps as i64
+    }
+}
+
+/// Returns the region of the touch device at the given position
+fn region_at(x: f64, y: f64, edge_scroll: Option<&TouchEdgeScroll>) -> TouchRegion {
+    let Some(edge_scroll) = edge_scroll else {
+        return TouchRegion::Pointer;
+    };
+    if edge_scroll.right.is_some_and(|width| x >= 1.0 - width) {
+        return TouchRegion::VerticalScroll;
+    }
+    if edge_scroll.bottom.is_some_and(|height| y >= 1.0 - height) {
+        return TouchRegion::HorizontalScroll;
+    }
+    TouchRegion::Pointer
+}
+
+/// Returns the configured scroll step
+fn scroll_step(edge_scroll: Option<&TouchEdgeScroll>) -> f64 {
+    edge_scroll
+        .and_then(|edge_scroll| edge_scroll.step)
+        .filter(|step| *step > 0.0)
+        .unwrap_or(DEFAULT_SCROLL_STEP)
+}
@@ -0,0 +1,66 @@
+use crate::{config::TouchEdgeScroll, input::capability::MouseButton};
+
+use super::touch_mouse::TouchMouseState;
+
+fn edge_scroll() -> TouchEdgeScroll {
+    TouchEdgeScroll {
+        right: Some(0.125),
+        bottom: Some(0.125),
+        step: Some(0.125),
+    }
+}
+
+#[test]
+fn test_pointer_motion() {
+    let config = edge_scroll();
+    let mut state = TouchMouseState::default();
+
+    // The first touch should not move the pointer
+    let update = state.update(true, Some(0.5), Some(0.5), 64.0, Some(&config));
+    assert_eq!(update.motion, None);
+
+    // Moving the finger should move the pointer relative to the last position
+    let update = state.update(true, Some(0.75), None, 64.0, Some(&config));
+    assert_eq!(update.motion, Some((16.0, 0.0)));
+    assert!(update.wheel.is_empty());
+
+    // Fractional pixels should accumulate
+    let update = state.update(true, None, Some(0.5078125), 64.0, Some(&config));
+    assert_eq!(update.motion, None);
+    let update = state.update(true, None, Some(0.515625), 64.0, Some(&config));
+    assert_eq!(update.motion, Some((0.0, 1.0)));
+
+    // Moving into a scroll strip should keep moving the pointer
+    let update = state.update(true, Some(0.96875), None, 64.0, Some(&config));
+    assert_eq!(update.motion, Some((14.0, 0.0)));
+    assert!(update.wheel.is_empty());
+}
+
+#[test]
+fn test_edge_scroll() {
+    let config = edge_scroll();
+    let mut state = TouchMouseState::default();
+
+    // Touching the right edge should scroll vertically
+    state.update(true, Some(0.9375), Some(0.5), 64.0, Some(&config));
+    let update = state.update(true, None, Some(0.78125), 64.0, Some(&config));
+    assert_eq!(update.motion, None);
+    assert_eq!(update.wheel, vec![MouseButton::WheelDown; 2]);
+    let update = state.update(true, None, Some(0.625), 64.0, Some(&config));
+    assert_eq!(update.wheel, vec![MouseButton::WheelUp]);
+
+    // Lifting the finger should end the scroll
+    state.update(false, None, None, 64.0, Some(&config));
+
+    // Touching the bottom edge should scroll horizontally
+    state.update(true, Some(0.5), Some(0.9375), 64.0, Some(&config));
+    let update = state.update(true, Some(0.375), None, 64.0, Some(&config));
+    assert_eq!(update.wheel, vec![MouseButton::WheelLeft]);
+
+    // Without edge scroll, every touch should move the pointer
+    let mut state = TouchMouseState::default();
+    state.update(true, Some(0.9375), Some(0.9375), 64.0, None);
+    let update = state.update(true, Some(0.875), None, 64.0, None);
+    assert_eq!(update.motion, Some((-4.0, 0.0)));
+    assert!(update.wheel.is_empty());
+}
@@ -213,6 +213,29 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
                 GamepadButton::RightStickTouch => vec![Action::None],
                 GamepadButton::LeftPaddle3 => vec![Action::None],
                 GamepadButton::RightPaddle3 => vec![Action::None],
+                GamepadButton::ShifterGear1 => vec![Action::None],
+                GamepadButton::ShifterGear2 => vec![Action::None],
+                GamepadButton::ShifterGear3 => vec![Action::None],
+                GamepadButton::ShifterGear4 => vec![Action::None],
+                GamepadButton::ShifterGear5 => vec![Action::None],
+                GamepadButton::ShifterGear6 => vec![Action::None],
+                GamepadButton::ShifterReverse => vec![Action::None],
+                GamepadButton::PowerLongPress => vec![Action::None],
+                GamepadButton::VolumeDownLongPress => vec![Action::None],
+                GamepadButton::VolumeUpLongPress => vec![Action::None],
+                GamepadButton::MouseModeSwitch => vec![Action::None],
+                GamepadButton::Hat1Up => vec![Action::None],
+                GamepadButton::Hat1Down => vec![Action::None],
+                GamepadButton::Hat1Left => vec![Action::None],
+                GamepadButton::Hat1Right => vec![Action::None],
+                GamepadButton::Hat2Up => vec![Action::None],
+                GamepadButton::Hat2Down => vec![Action::None],
+                GamepadButton::Hat2Left => vec![Action::None],
+                GamepadButton::Hat2Right => vec![Action::None],
+                GamepadButton::Hat3Up => vec![Action::None],
+                GamepadButton::Hat3Down => vec![Action::None],
+                GamepadButton::Hat3Left => vec![Action::None],
+                GamepadButton::Hat3Right => vec![Action::None],
             },
             Gamepad::Axis(axis) => match axis {
                 GamepadAxis::LeftStick => {
@@ -230,6 +253,9 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
                 GamepadTrigger::RightTrigger => vec![Action::R2],
                 GamepadTrigger::RightTouchpadForce => vec![Action::None],
                 GamepadTrigger::RightStickForce => vec![Action::None],
+                GamepadTrigger::Throttle => vec![Action::None],
+                GamepadTrigger::Brake => vec![Action::None],
+                GamepadTrigger::Clutch => vec![Action::None],
             },
             _ => vec![Action::None],
         },
@@ -396,6 +422,8 @@ fn actions_from_capability(capability: Capability) -> Vec<Action> {
             Keyboard::KeyF23 => vec![Action::None],
             Keyboard::KeyF24 => vec![Action::None],
             Keyboard::KeyProg1 => vec![Action::None],
+            Keyboard::KeyBrightnessDown => vec![Action::None],
+            Keyboard::KeyBrightnessUp => vec![Action::None],
             Keyboard::KeyRecord => vec![Action::None],
         },
         Capability::Touchpad(_) => vec![Action::None],
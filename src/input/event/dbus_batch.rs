@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use super::{
+    dbus::{Action, DBusEvent},
+    value::InputValue,
+};
+
+/// Settings to reduce the number of DBus signals emitted by a DBus target
+/// device, so overlays can receive responsive updates without flooding the
+/// bus.
+#[derive(Debug, Clone, Default)]
+pub struct DBusBatchSettings {
+    /// Time to collect events before they are emitted together in a single
+    /// signal. Events are emitted individually as they occur if zero.
+    pub batch_interval: Duration,
+    /// Minimum time between two events of the same action. Events that occur
+    /// sooner are coalesced, so only the latest value is emitted.
+    pub throttle_intervals: HashMap<Action, Duration>,
+}
+
+impl DBusBatchSettings {
+    /// Returns true if events should be emitted in batches
+    pub fn is_batching(&self) -> bool {
+        !self.batch_interval.is_zero()
+    }
+}
+
+/// Events of the same action are throttled separately for each touch
+type ThrottleKey = (Action, Option<u8>);
+
+/// Collects and throttles the events of a DBus target device based on its
+/// [DBusBatchSettings].
+#[derive(Debug, Default)]
+pub struct DBusEventBatcher {
+    /// Events that are ready to be emitted
+    batch: Vec<DBusEvent>,
+    /// Time the first event of the current batch was added
+    batch_started: Option<Instant>,
+    /// Time each throttled action was last added to a batch and whether it
+    /// was active
+    last_emitted: HashMap<ThrottleKey, (Instant, bool)>,
+    /// Latest throttled events that are waiting for their throttle interval
+    /// to pass
+    pending: HashMap<ThrottleKey, DBusEvent>,
+}
+
+impl DBusEventBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the given event using the given settings. Throttled events that
+    /// change whether an input is active (e.g. a touch being released) are
+    /// never coalesced and are added immediately.
+    pub fn push(&mut self, event: DBusEvent, settings: &DBusBatchSettings, now: Instant) {
+        let Some(interval) = settings.throttle_intervals.get(&event.action) else {
+            self.add_to_batch(event, now);
+            return;
+        };
+        let key = throttle_key(&event);
+        let active = is_active(&event.value);
+
+        // A pending event in another state must be emitted so the state
+        // change is not lost. Otherwise it is replaced by this event.
+        if let Some(pending) = self.pending.remove(&key) {
+            if is_active(&pending.value) != active {
+                self.emit(key.clone(), pending, now);
+            }
+        }
+
+        let is_throttled = match self.last_emitted.get(&key) {
+            Some((last, was_active)) => {
+                *was_active == active && now.duration_since(*last) < *interval
+            }
+            None => false,
+        };
+        if is_throttled {
+            self.pending.insert(key, event);
+            return;
+        }
+        self.emit(key, event, now);
+    }
+
+    /// Returns the events that should be emitted at the given time using the
+    /// given settings. Pending throttled events are added once their
+    /// throttle interval has passed.
+    pub fn poll(&mut self, settings: &DBusBatchSettings, now: Instant) -> Vec<DBusEvent> {
+        let ready: Vec<ThrottleKey> = self
+            .pending
+            .keys()
+            .filter(|key| {
+                let interval = settings.throttle_intervals.get(&key.0).copied();
+                let last = self.last_emitted.get(*key);
+                match (interval, last) {
+                    (Some(interval), Some((last, _))) => now.duration_since(*last) >= interval,
+                    _ => true,
+                }
+            })
+            .cloned()
+            .collect();
+        for key in ready {
+            if let Some(event) = self.pending.remove(&key) {
+                self.emit(key, event, now);
+            }
+        }
+
+        let Some(started) = self.batch_started else {
+            return vec![];
+        };
+        if now.duration_since(started) < settings.batch_interval {
+            return vec![];
+        }
+        self.batch_started = None;
+        std::mem::take(&mut self.batch)
+    }
+
+    /// Add the given throttled event to the current batch
+    fn emit(&mut self, key: ThrottleKey, event: DBusEvent, now: Instant) {
+        self.last_emitted
+            .insert(key, (now, is_active(&event.value)));
+        self.add_to_batch(event, now);
+    }
+
+    /// Add the given event to the current batch
+    fn add_to_batch(&mut self, event: DBusEvent, now: Instant) {
+        self.batch_started.get_or_insert(now);
+        self.batch.push(event);
+    }
+}
+
+/// Returns the key used to throttle the given event
+fn throttle_key(event: &DBusEvent) -> ThrottleKey {
+    let index = match event.value {
+        InputValue::Touch { index, .. } => Some(index),
+        _ => None,
+    };
+    (event.action.clone(), index)
+}
+
+/// Returns true if the given value represents an active input, such as a
+/// pressed button or a touch
+fn is_active(value: &InputValue) -> bool {
+    match value {
+        InputValue::Bool(value) => *value,
+        InputValue::Float(value) => *value > 0.0,
+        InputValue::Touch { is_touching, .. } => *is_touching,
+        _ => false,
+    }
+}
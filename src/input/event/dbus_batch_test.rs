@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use super::{
+    dbus::{Action, DBusEvent},
+    dbus_batch::{DBusBatchSettings, DBusEventBatcher},
+    value::InputValue,
+};
+
+/// Returns a touch event for the given finger and position
+fn touch(index: u8, is_touching: bool, x: f64) -> DBusEvent {
+    DBusEvent::new(
+        Action::Touch,
+        InputValue::Touch {
+            index,
+            is_touching,
+            pressure: None,
+            x: Some(x),
+            y: Some(0.5),
+        },
+    )
+}
+
+/// Returns the x position of the given touch event
+fn touch_x(event: &DBusEvent) -> Option<f64> {
+    match event.value {
+        InputValue::Touch { x, .. } => x,
+        _ => None,
+    }
+}
+
+#[test]
+fn test_no_batching() {
+    let settings = DBusBatchSettings::default();
+    let mut batcher = DBusEventBatcher::new();
+    let now = Instant::now();
+
+    // Without batching, events are emitted as soon as they are polled
+    batcher.push(
+        DBusEvent::new(Action::Accept, InputValue::Bool(true)),
+        &settings,
+        now,
+    );
+    let events = batcher.poll(&settings, now);
+    assert_eq!(events.len(), 1);
+    assert!(batcher.poll(&settings, now).is_empty());
+}
+
+#[test]
+fn test_batching() {
+    let settings = DBusBatchSettings {
+        batch_interval: Duration::from_millis(20),
+        ..Default::default()
+    };
+    let mut batcher = DBusEventBatcher::new();
+    let start = Instant::now();
+
+    batcher.push(
+        DBusEvent::new(Action::Accept, InputValue::Bool(true)),
+        &settings,
+        start,
+    );
+    let later = start + Duration::from_millis(10);
+    batcher.push(
+        DBusEvent::new(Action::Accept, InputValue::Bool(false)),
+        &settings,
+        later,
+    );
+    assert!(batcher.poll(&settings, later).is_empty());
+
+    // All events collected in the interval are emitted together
+    let events = batcher.poll(&settings, start + Duration::from_millis(20));
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].value, InputValue::Bool(true)));
+    assert!(matches!(events[1].value, InputValue::Bool(false)));
+}
+
+#[test]
+fn test_throttling() {
+    let mut settings = DBusBatchSettings::default();
+    settings
+        .throttle_intervals
+        .insert(Action::Touch, Duration::from_millis(50));
+    let mut batcher = DBusEventBatcher::new();
+    let start = Instant::now();
+
+    // The first event is emitted immediately
+    batcher.push(touch(0, true, 0.1), &settings, start);
+    assert_eq!(batcher.poll(&settings, start).len(), 1);
+
+    // Following events are coalesced until the interval passes
+    let time = start + Duration::from_millis(10);
+    batcher.push(touch(0, true, 0.2), &settings, time);
+    batcher.push(touch(0, true, 0.3), &settings, time);
+    assert!(batcher.poll(&settings, time).is_empty());
+    let time = start + Duration::from_millis(50);
+    let events = batcher.poll(&settings, time);
+    assert_eq!(events.len(), 1);
+    assert_eq!(touch_x(&events[0]), Some(0.3));
+
+    // Other fingers are throttled separately
+    batcher.push(touch(1, true, 0.9), &settings, time);
+    assert_eq!(batcher.poll(&settings, time).len(), 1);
+
+    // Releasing a touch is never coalesced
+    let time = time + Duration::from_millis(10);
+    batcher.push(touch(0, true, 0.4), &settings, time);
+    batcher.push(touch(0, false, 0.4), &settings, time);
+    let events = batcher.poll(&settings, time);
+    assert_eq!(events.len(), 2);
+    assert_eq!(touch_x(&events[0]), Some(0.4));
+}
@@ -64,30 +64,23 @@ impl EvdevEvent {
                         InputValue::Bool(true)
                     }
                 }
-                AbsoluteAxisCode::ABS_HAT1X => InputValue::Vector2 {
-                    x: Some(normal_value),
-                    y: None,
-                },
-                AbsoluteAxisCode::ABS_HAT1Y => InputValue::Vector2 {
-                    x: None,
-                    y: Some(normal_value),
-                },
-                AbsoluteAxisCode::ABS_HAT2X => InputValue::Vector2 {
-                    x: Some(normal_value),
-                    y: None,
-                },
-                AbsoluteAxisCode::ABS_HAT2Y => InputValue::Vector2 {
-                    x: None,
-                    y: Some(normal_value),
-                },
-                AbsoluteAxisCode::ABS_HAT3X => InputValue::Vector2 {
-                    x: Some(normal_value),
-                    y: None,
-                },
-                AbsoluteAxisCode::ABS_HAT3Y => InputValue::Vector2 {
-                    x: None,
-                    y: Some(normal_value),
-                },
+                AbsoluteAxisCode::ABS_HAT1X
+                | AbsoluteAxisCode::ABS_HAT1Y
+                | AbsoluteAxisCode::ABS_HAT2X
+                | AbsoluteAxisCode::ABS_HAT2Y
+                | AbsoluteAxisCode::ABS_HAT3X
+                | AbsoluteAxisCode::ABS_HAT3Y => InputValue::Bool(normal_value != 0.0),
+                // Pressure is reported without a position, so it can only be
+                // translated into analog values (e.g. triggers).
+                AbsoluteAxisCode::ABS_PRESSURE | AbsoluteAxisCode::ABS_MT_PRESSURE => {
+                    InputValue::Touch {
+                        index: 0,
+                        is_touching: normal_value > 0.0,
+                        pressure: Some(normal_value),
+                        x: None,
+                        y: None,
+                    }
+                }
                 _ => InputValue::Float(normal_value),
             },
             EventType::RELATIVE => match RelativeAxisCode(code) {
@@ -115,8 +108,15 @@ impl EvdevEvent {
         if let Some(info) = self.abs_info {
             let code = self.event.code();
             match AbsoluteAxisCode(code) {
-                AbsoluteAxisCode::ABS_Z => normalize_unsigned_value(raw_value, info.maximum()),
-                AbsoluteAxisCode::ABS_RZ => normalize_unsigned_value(raw_value, info.maximum()),
+                AbsoluteAxisCode::ABS_Z => {
+                    normalize_unsigned_value(raw_value, info.minimum(), info.maximum())
+                }
+                AbsoluteAxisCode::ABS_RZ => {
+                    normalize_unsigned_value(raw_value, info.minimum(), info.maximum())
+                }
+                AbsoluteAxisCode::ABS_PRESSURE | AbsoluteAxisCode::ABS_MT_PRESSURE => {
+                    normalize_unsigned_value(raw_value, info.minimum(), info.maximum())
+                }
                 _ => normalize_signed_value(raw_value, info.minimum(), info.maximum()),
             }
         } else {
@@ -190,8 +190,8 @@ impl EvdevEvent {
                 KeyCode::KEY_BATTERY => Capability::NotImplemented,
                 KeyCode::KEY_BLUETOOTH => Capability::NotImplemented,
                 KeyCode::KEY_BOOKMARKS => Capability::NotImplemented,
-                KeyCode::KEY_BRIGHTNESSDOWN => Capability::NotImplemented,
-                KeyCode::KEY_BRIGHTNESSUP => Capability::NotImplemented,
+                KeyCode::KEY_BRIGHTNESSDOWN => Capability::Keyboard(Keyboard::KeyBrightnessDown),
+                KeyCode::KEY_BRIGHTNESSUP => Capability::Keyboard(Keyboard::KeyBrightnessUp),
                 KeyCode::KEY_BRIGHTNESS_AUTO => Capability::NotImplemented,
                 KeyCode::KEY_BRIGHTNESS_CYCLE => Capability::NotImplemented,
                 KeyCode::KEY_C => Capability::Keyboard(Keyboard::KeyC),
@@ -440,6 +440,18 @@ impl EvdevEvent {
                 AbsoluteAxisCode::ABS_HAT0Y => {
                     Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0))
                 }
+                AbsoluteAxisCode::ABS_HAT1X | AbsoluteAxisCode::ABS_HAT1Y => {
+                    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat1))
+                }
+                AbsoluteAxisCode::ABS_HAT2X | AbsoluteAxisCode::ABS_HAT2Y => {
+                    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat2))
+                }
+                AbsoluteAxisCode::ABS_HAT3X | AbsoluteAxisCode::ABS_HAT3Y => {
+                    Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat3))
+                }
+                AbsoluteAxisCode::ABS_PRESSURE | AbsoluteAxisCode::ABS_MT_PRESSURE => {
+                    Capability::Touchpad(Touchpad::CenterPad(Touch::Motion))
+                }
                 _ => Capability::NotImplemented,
             },
             EventType::RELATIVE => match RelativeAxisCode(code) {
@@ -511,13 +523,15 @@ impl EvdevEvent {
                 None
             };
 
-            // Get the axis direction if this this an ABS event and we need to
-            // translate binary input into axis input. (e.g. DPad buttons)
-            let axis_direction = if event_type == EventType::ABSOLUTE {
-                Some(axis_direction_from_capability(event.as_capability()))
-            } else {
-                None
-            };
+            // Get the axis direction if this this an ABS or REL event and we
+            // need to translate binary input into axis input. (e.g. DPad buttons
+            // or mouse wheel buttons)
+            let axis_direction =
+                if event_type == EventType::ABSOLUTE || event_type == EventType::RELATIVE {
+                    Some(axis_direction_from_capability(event.as_capability()))
+                } else {
+                    None
+                };
 
             // Get the input value from the event and convert it into an evdev
             // input event.
@@ -541,7 +555,13 @@ fn event_type_from_capability(capability: Capability) -> Option<EventType> {
         Capability::Keyboard(_) => Some(EventType::KEY),
         Capability::Mouse(mouse) => match mouse {
             Mouse::Motion => Some(EventType::RELATIVE),
-            Mouse::Button(_) => Some(EventType::KEY),
+            Mouse::Button(button) => match button {
+                MouseButton::WheelUp
+                | MouseButton::WheelDown
+                | MouseButton::WheelLeft
+                | MouseButton::WheelRight => Some(EventType::RELATIVE),
+                _ => Some(EventType::KEY),
+            },
         },
         Capability::Gamepad(gamepad) => match gamepad {
             Gamepad::Button(button) => match button {
@@ -549,12 +569,26 @@ fn event_type_from_capability(capability: Capability) -> Option<EventType> {
                 GamepadButton::DPadDown => Some(EventType::ABSOLUTE),
                 GamepadButton::DPadLeft => Some(EventType::ABSOLUTE),
                 GamepadButton::DPadRight => Some(EventType::ABSOLUTE),
+                GamepadButton::Hat1Up
+                | GamepadButton::Hat1Down
+                | GamepadButton::Hat1Left
+                | GamepadButton::Hat1Right
+                | GamepadButton::Hat2Up
+                | GamepadButton::Hat2Down
+                | GamepadButton::Hat2Left
+                | GamepadButton::Hat2Right
+                | GamepadButton::Hat3Up
+                | GamepadButton::Hat3Down
+                | GamepadButton::Hat3Left
+                | GamepadButton::Hat3Right => Some(EventType::ABSOLUTE),
                 _ => Some(EventType::KEY),
             },
             Gamepad::Axis(_) => Some(EventType::ABSOLUTE),
             Gamepad::Trigger(_) => Some(EventType::ABSOLUTE),
             Gamepad::Accelerometer => None,
             Gamepad::Gyro => None,
+            Gamepad::Dial(_) => None,
+            Gamepad::Gesture(_) => None,
         },
         _ => None,
     }
@@ -571,12 +605,31 @@ fn axis_direction_from_capability(capability: Capability) -> AxisDirection {
                     GamepadButton::DPadDown => AxisDirection::Positive,
                     GamepadButton::DPadLeft => AxisDirection::Negative,
                     GamepadButton::DPadRight => AxisDirection::Positive,
+                    GamepadButton::Hat1Up | GamepadButton::Hat2Up | GamepadButton::Hat3Up => {
+                        AxisDirection::Negative
+                    }
+                    GamepadButton::Hat1Down | GamepadButton::Hat2Down | GamepadButton::Hat3Down => {
+                        AxisDirection::Positive
+                    }
+                    GamepadButton::Hat1Left | GamepadButton::Hat2Left | GamepadButton::Hat3Left => {
+                        AxisDirection::Negative
+                    }
+                    GamepadButton::Hat1Right
+                    | GamepadButton::Hat2Right
+                    | GamepadButton::Hat3Right => AxisDirection::Positive,
                     _ => AxisDirection::None,
                 }
             } else {
                 AxisDirection::None
             }
         }
+        Capability::Mouse(Mouse::Button(button)) => match button {
+            MouseButton::WheelUp => AxisDirection::Positive,
+            MouseButton::WheelDown => AxisDirection::Negative,
+            MouseButton::WheelLeft => AxisDirection::Negative,
+            MouseButton::WheelRight => AxisDirection::Positive,
+            _ => AxisDirection::None,
+        },
         _ => AxisDirection::None,
     }
 }
@@ -597,6 +650,18 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 GamepadButton::DPadUp => vec![AbsoluteAxisCode::ABS_HAT0Y.0],
                 GamepadButton::East => vec![KeyCode::BTN_EAST.0],
                 GamepadButton::Guide => vec![KeyCode::BTN_MODE.0],
+                GamepadButton::Hat1Up => vec![AbsoluteAxisCode::ABS_HAT1Y.0],
+                GamepadButton::Hat1Down => vec![AbsoluteAxisCode::ABS_HAT1Y.0],
+                GamepadButton::Hat1Left => vec![AbsoluteAxisCode::ABS_HAT1X.0],
+                GamepadButton::Hat1Right => vec![AbsoluteAxisCode::ABS_HAT1X.0],
+                GamepadButton::Hat2Up => vec![AbsoluteAxisCode::ABS_HAT2Y.0],
+                GamepadButton::Hat2Down => vec![AbsoluteAxisCode::ABS_HAT2Y.0],
+                GamepadButton::Hat2Left => vec![AbsoluteAxisCode::ABS_HAT2X.0],
+                GamepadButton::Hat2Right => vec![AbsoluteAxisCode::ABS_HAT2X.0],
+                GamepadButton::Hat3Up => vec![AbsoluteAxisCode::ABS_HAT3Y.0],
+                GamepadButton::Hat3Down => vec![AbsoluteAxisCode::ABS_HAT3Y.0],
+                GamepadButton::Hat3Left => vec![AbsoluteAxisCode::ABS_HAT3X.0],
+                GamepadButton::Hat3Right => vec![AbsoluteAxisCode::ABS_HAT3X.0],
                 GamepadButton::Keyboard => vec![],
                 GamepadButton::LeftBumper => vec![KeyCode::BTN_TL.0],
                 GamepadButton::LeftPaddle1 => vec![KeyCode::BTN_TRIGGER_HAPPY7.0],
@@ -619,10 +684,21 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 GamepadButton::RightTrigger => vec![KeyCode::BTN_TR2.0],
                 GamepadButton::Screenshot => vec![KeyCode::KEY_RECORD.0],
                 GamepadButton::Mute => vec![],
+                GamepadButton::PowerLongPress => vec![],
+                GamepadButton::VolumeDownLongPress => vec![],
+                GamepadButton::VolumeUpLongPress => vec![],
+                GamepadButton::MouseModeSwitch => vec![],
                 GamepadButton::Select => vec![KeyCode::BTN_SELECT.0],
                 GamepadButton::South => vec![KeyCode::BTN_SOUTH.0],
                 GamepadButton::Start => vec![KeyCode::BTN_START.0],
                 GamepadButton::West => vec![KeyCode::BTN_WEST.0],
+                GamepadButton::ShifterGear1 => vec![KeyCode::BTN_TRIGGER_HAPPY11.0],
+                GamepadButton::ShifterGear2 => vec![KeyCode::BTN_TRIGGER_HAPPY12.0],
+                GamepadButton::ShifterGear3 => vec![KeyCode::BTN_TRIGGER_HAPPY13.0],
+                GamepadButton::ShifterGear4 => vec![KeyCode::BTN_TRIGGER_HAPPY14.0],
+                GamepadButton::ShifterGear5 => vec![KeyCode::BTN_TRIGGER_HAPPY15.0],
+                GamepadButton::ShifterGear6 => vec![KeyCode::BTN_TRIGGER_HAPPY16.0],
+                GamepadButton::ShifterReverse => vec![KeyCode::BTN_TRIGGER_HAPPY17.0],
             },
             Gamepad::Axis(axis) => match axis {
                 GamepadAxis::LeftStick => {
@@ -643,6 +719,7 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 GamepadAxis::Hat3 => {
                     vec![AbsoluteAxisCode::ABS_HAT3X.0, AbsoluteAxisCode::ABS_HAT3Y.0]
                 }
+                GamepadAxis::Steering => vec![AbsoluteAxisCode::ABS_X.0],
             },
             Gamepad::Trigger(trigg) => match trigg {
                 GamepadTrigger::LeftTrigger => {
@@ -655,9 +732,14 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 }
                 GamepadTrigger::RightTouchpadForce => vec![],
                 GamepadTrigger::RightStickForce => vec![],
+                GamepadTrigger::Throttle => vec![AbsoluteAxisCode::ABS_Z.0],
+                GamepadTrigger::Brake => vec![AbsoluteAxisCode::ABS_RZ.0],
+                GamepadTrigger::Clutch => vec![AbsoluteAxisCode::ABS_Y.0],
             },
             Gamepad::Accelerometer => vec![],
             Gamepad::Gyro => vec![],
+            Gamepad::Dial(_) => vec![],
+            Gamepad::Gesture(_) => vec![],
         },
         Capability::Mouse(mouse) => match mouse {
             Mouse::Motion => vec![RelativeAxisCode::REL_X.0, RelativeAxisCode::REL_Y.0],
@@ -665,10 +747,10 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
                 MouseButton::Left => vec![KeyCode::BTN_LEFT.0],
                 MouseButton::Right => vec![KeyCode::BTN_RIGHT.0],
                 MouseButton::Middle => vec![KeyCode::BTN_MIDDLE.0],
-                MouseButton::WheelUp => vec![],
-                MouseButton::WheelDown => vec![],
-                MouseButton::WheelLeft => vec![],
-                MouseButton::WheelRight => vec![],
+                MouseButton::WheelUp => vec![RelativeAxisCode::REL_WHEEL.0],
+                MouseButton::WheelDown => vec![RelativeAxisCode::REL_WHEEL.0],
+                MouseButton::WheelLeft => vec![RelativeAxisCode::REL_HWHEEL.0],
+                MouseButton::WheelRight => vec![RelativeAxisCode::REL_HWHEEL.0],
                 MouseButton::Extra => vec![KeyCode::BTN_EXTRA.0],
                 MouseButton::Side => vec![KeyCode::BTN_SIDE.0],
             },
@@ -692,6 +774,8 @@ fn event_codes_from_capability(capability: Capability) -> Vec<u16> {
             Keyboard::KeyBack => vec![KeyCode::KEY_BACK.0],
             Keyboard::KeyBackslash => vec![KeyCode::KEY_BACKSLASH.0],
             Keyboard::KeyBackspace => vec![KeyCode::KEY_BACKSPACE.0],
+            Keyboard::KeyBrightnessDown => vec![KeyCode::KEY_BRIGHTNESSDOWN.0],
+            Keyboard::KeyBrightnessUp => vec![KeyCode::KEY_BRIGHTNESSUP.0],
             Keyboard::KeyC => vec![KeyCode::KEY_C.0],
             Keyboard::KeyCalc => vec![KeyCode::KEY_CALC.0],
             Keyboard::KeyCapslock => vec![KeyCode::KEY_CAPSLOCK.0],
@@ -900,7 +984,14 @@ fn input_event_from_value(
             // the minimum and maximum values for that axis depending on the
             // axis direction. This is typically done for DPad button input that
             // needs to be translated to an ABS_HAT axis input.
-            if axis_info.is_some() && axis_direction.is_some() {
+            if event_type == EventType::RELATIVE {
+                // Wheel buttons emit a single wheel step when pressed
+                match axis_direction {
+                    Some(AxisDirection::Positive) if value == 1 => Some(1),
+                    Some(AxisDirection::Negative) if value == 1 => Some(-1),
+                    _ => None,
+                }
+            } else if axis_info.is_some() && axis_direction.is_some() {
                 let info = axis_info.unwrap();
                 let direction = axis_direction.unwrap();
                 match direction {
@@ -981,9 +1072,10 @@ fn normalize_signed_value(raw_value: i32, min: i32, max: i32) -> f64 {
 }
 
 // Returns a value between 0.0 and 1.0 based on the given value with its
-// maximum.
-fn normalize_unsigned_value(raw_value: i32, max: i32) -> f64 {
-    raw_value as f64 / max as f64
+// minimum and maximum.
+fn normalize_unsigned_value(raw_value: i32, min: i32, max: i32) -> f64 {
+    let range = (max - min).max(1) as f64;
+    ((raw_value - min) as f64 / range).clamp(0.0, 1.0)
 }
 
 /// De-normalizes the given value from -1.0 - 1.0 into a real value based on the
@@ -1011,13 +1103,72 @@ fn denormalize_signed_value(normal_value: f64, axis_info: AbsInfo) -> i32 {
 }
 
 /// De-normalizes the given value from 0.0 - 1.0 into a real value based on
-/// the maximum axis range.
+/// the minimum and maximum axis range.
 fn denormalize_unsigned_value(normal_value: f64, axis_info: Option<AbsInfo>) -> i32 {
-    // TODO: this better
     let Some(axis_info) = axis_info else {
         return normal_value as i32;
     };
-    (normal_value * axis_info.maximum() as f64).round() as i32
+    let range = (axis_info.maximum() - axis_info.minimum()) as f64;
+    axis_info.minimum() + (normal_value.clamp(0.0, 1.0) * range).round() as i32
+}
+
+/// Replace the ranges of axes in the given target axis map with the given
+/// ranges, typically reported by source hardware or overridden in a device
+/// profile. Axes that the target does not implement are ignored so targets
+/// never advertise axes they cannot emit.
+pub fn apply_axis_ranges(
+    axis_map: &mut HashMap<AbsoluteAxisCode, AbsInfo>,
+    ranges: &HashMap<AbsoluteAxisCode, AbsInfo>,
+) {
+    for (axis, info) in axis_map.iter_mut() {
+        let Some(range) = ranges.get(axis) else {
+            continue;
+        };
+        if range.maximum() <= range.minimum() {
+            log::warn!("Ignoring invalid range for axis {axis:?}: {range:?}");
+            continue;
+        }
+        let value = info.value().clamp(range.minimum(), range.maximum());
+        *info = AbsInfo::new(
+            value,
+            range.minimum(),
+            range.maximum(),
+            range.fuzz(),
+            range.flat(),
+            range.resolution(),
+        );
+    }
+}
+
+/// Returns the absolute axis code with the given name (e.g. "ABS_X")
+pub fn abs_axis_from_str(name: &str) -> Option<AbsoluteAxisCode> {
+    let axis = match name {
+        "ABS_X" => AbsoluteAxisCode::ABS_X,
+        "ABS_Y" => AbsoluteAxisCode::ABS_Y,
+        "ABS_Z" => AbsoluteAxisCode::ABS_Z,
+        "ABS_RX" => AbsoluteAxisCode::ABS_RX,
+        "ABS_RY" => AbsoluteAxisCode::ABS_RY,
+        "ABS_RZ" => AbsoluteAxisCode::ABS_RZ,
+        "ABS_THROTTLE" => AbsoluteAxisCode::ABS_THROTTLE,
+        "ABS_RUDDER" => AbsoluteAxisCode::ABS_RUDDER,
+        "ABS_WHEEL" => AbsoluteAxisCode::ABS_WHEEL,
+        "ABS_GAS" => AbsoluteAxisCode::ABS_GAS,
+        "ABS_BRAKE" => AbsoluteAxisCode::ABS_BRAKE,
+        "ABS_HAT0X" => AbsoluteAxisCode::ABS_HAT0X,
+        "ABS_HAT0Y" => AbsoluteAxisCode::ABS_HAT0Y,
+        "ABS_HAT1X" => AbsoluteAxisCode::ABS_HAT1X,
+        "ABS_HAT1Y" => AbsoluteAxisCode::ABS_HAT1Y,
+        "ABS_HAT2X" => AbsoluteAxisCode::ABS_HAT2X,
+        "ABS_HAT2Y" => AbsoluteAxisCode::ABS_HAT2Y,
+        "ABS_HAT3X" => AbsoluteAxisCode::ABS_HAT3X,
+        "ABS_HAT3Y" => AbsoluteAxisCode::ABS_HAT3Y,
+        "ABS_PRESSURE" => AbsoluteAxisCode::ABS_PRESSURE,
+        "ABS_DISTANCE" => AbsoluteAxisCode::ABS_DISTANCE,
+        "ABS_TILT_X" => AbsoluteAxisCode::ABS_TILT_X,
+        "ABS_TILT_Y" => AbsoluteAxisCode::ABS_TILT_Y,
+        _ => return None,
+    };
+    Some(axis)
 }
 
 /// The AxisDirection is used to determine if a button value should be mapped
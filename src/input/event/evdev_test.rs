@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use evdev::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadTrigger},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+use super::evdev::{apply_axis_ranges, EvdevEvent};
+
+#[test]
+fn test_apply_axis_ranges() {
+    let mut axis_map = HashMap::from([
+        (
+            AbsoluteAxisCode::ABS_X,
+            AbsInfo::new(0, -32768, 32767, 16, 128, 1),
+        ),
+        (AbsoluteAxisCode::ABS_Z, AbsInfo::new(0, 0, 255, 0, 0, 1)),
+    ]);
+    let ranges = HashMap::from([
+        (
+            AbsoluteAxisCode::ABS_X,
+            AbsInfo::new(512, 0, 1023, 4, 32, 0),
+        ),
+        (AbsoluteAxisCode::ABS_RX, AbsInfo::new(0, 0, 1023, 0, 0, 0)),
+        (AbsoluteAxisCode::ABS_Z, AbsInfo::new(0, 10, 10, 0, 0, 0)),
+    ]);
+    apply_axis_ranges(&mut axis_map, &ranges);
+
+    // Ranges should be replaced and clamped to the new range
+    let x = axis_map.get(&AbsoluteAxisCode::ABS_X).unwrap();
+    assert_eq!((x.value(), x.minimum(), x.maximum()), (0, 0, 1023));
+    assert_eq!((x.fuzz(), x.flat()), (4, 32));
+
+    // Axes the target doesn't implement and invalid ranges should be ignored
+    assert!(!axis_map.contains_key(&AbsoluteAxisCode::ABS_RX));
+    assert_eq!(
+        axis_map.get(&AbsoluteAxisCode::ABS_Z).unwrap().maximum(),
+        255
+    );
+}
+
+#[test]
+fn test_trigger_range_with_minimum() {
+    // Source trigger reporting from 100 to 1100
+    let mut event = EvdevEvent::from(InputEvent::new(
+        EventType::ABSOLUTE.0,
+        AbsoluteAxisCode::ABS_Z.0,
+        600,
+    ));
+    event.set_abs_info(AbsInfo::new(0, 100, 1100, 0, 0, 0));
+    assert_eq!(event.get_normalized_value(), 0.5);
+
+    // Target trigger reporting from -255 to 255
+    let axis_map = HashMap::from([(AbsoluteAxisCode::ABS_Z, AbsInfo::new(0, -255, 255, 0, 0, 1))]);
+    let native = NativeEvent::new(
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+        InputValue::Float(0.5),
+    );
+    let events = EvdevEvent::from_native_event(native, axis_map);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_input_event().value(), 0);
+}
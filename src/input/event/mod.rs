@@ -1,7 +1,14 @@
 pub mod dbus;
+pub mod dbus_batch;
+#[cfg(test)]
+pub mod dbus_batch_test;
 pub mod evdev;
+#[cfg(test)]
+pub mod evdev_test;
 pub mod native;
 pub mod value;
+#[cfg(test)]
+pub mod value_test;
 
 /// Events are events that flow from source devices to target devices
 #[derive(Debug, Clone)]
@@ -55,6 +55,11 @@ impl NativeEvent {
         self.value.clone()
     }
 
+    /// Set the value of this event
+    pub fn set_value(&mut self, value: InputValue) {
+        self.value = value;
+    }
+
     /// Returns true if this event is a translated event and has a source
     /// capability defined.
     pub fn is_translated(&self) -> bool {
@@ -77,34 +82,22 @@ impl NativeEvent {
     }
 
     pub fn from_evdev_raw(event: EvdevEvent, hat_state: Option<i32>) -> NativeEvent {
-        // If this is a Dpad input, figure out with button this event is for
+        // If this is a hat switch input (e.g. DPad), figure out which button
+        // this event is for.
         let capability = if let Some(old_state) = hat_state {
             let axis = AbsoluteAxisCode(event.as_input_event().code());
             let value = event.as_input_event().value();
 
-            match axis {
-                AbsoluteAxisCode::ABS_HAT0X => match value {
-                    -1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
-                    1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
-                    0 => match old_state {
-                        -1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
-                        1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
-                        _ => Capability::NotImplemented,
-                    },
-                    _ => Capability::NotImplemented,
-                },
-                AbsoluteAxisCode::ABS_HAT0Y => match value {
-                    -1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
-                    1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
-                    0 => match old_state {
-                        -1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
-                        1 => Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            match hat_buttons(axis) {
+                Some((negative, positive)) => {
+                    let state = if value == 0 { old_state } else { value };
+                    match state {
+                        -1 => Capability::Gamepad(Gamepad::Button(negative)),
+                        1 => Capability::Gamepad(Gamepad::Button(positive)),
                         _ => Capability::NotImplemented,
-                    },
-                    _ => Capability::NotImplemented,
-                },
-
-                _ => Capability::NotImplemented,
+                    }
+                }
+                None => Capability::NotImplemented,
             }
         } else {
             event.as_capability()
@@ -120,6 +113,23 @@ impl NativeEvent {
     }
 }
 
+/// Returns the buttons for the negative and positive directions of the given
+/// hat switch axis. E.g. ABS_HAT0X returns (DPadLeft, DPadRight).
+pub fn hat_buttons(axis: AbsoluteAxisCode) -> Option<(GamepadButton, GamepadButton)> {
+    let buttons = match axis {
+        AbsoluteAxisCode::ABS_HAT0X => (GamepadButton::DPadLeft, GamepadButton::DPadRight),
+        AbsoluteAxisCode::ABS_HAT0Y => (GamepadButton::DPadUp, GamepadButton::DPadDown),
+        AbsoluteAxisCode::ABS_HAT1X => (GamepadButton::Hat1Left, GamepadButton::Hat1Right),
+        AbsoluteAxisCode::ABS_HAT1Y => (GamepadButton::Hat1Up, GamepadButton::Hat1Down),
+        AbsoluteAxisCode::ABS_HAT2X => (GamepadButton::Hat2Left, GamepadButton::Hat2Right),
+        AbsoluteAxisCode::ABS_HAT2Y => (GamepadButton::Hat2Up, GamepadButton::Hat2Down),
+        AbsoluteAxisCode::ABS_HAT3X => (GamepadButton::Hat3Left, GamepadButton::Hat3Right),
+        AbsoluteAxisCode::ABS_HAT3Y => (GamepadButton::Hat3Up, GamepadButton::Hat3Down),
+        _ => return None,
+    };
+    Some(buttons)
+}
+
 impl From<EvdevEvent> for NativeEvent {
     /// Convert the [EvdevEvent] into a [NativeEvent]
     fn from(item: EvdevEvent) -> Self {
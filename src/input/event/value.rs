@@ -1,10 +1,26 @@
 use crate::{
-    config::CapabilityConfig,
+    config::{CapabilityConfig, TouchPressure},
     input::capability::{Capability, Gamepad, Mouse, Touch, Touchpad},
 };
 
 use super::dbus::Action;
 
+/// Gyro rotation rate in the units reported by source devices that results
+/// in full joystick deflection at a sensitivity of 1.0.
+const GYRO_FULL_DEFLECTION_RATE: f64 = 500.0;
+
+/// Default device tilt in degrees that results in full joystick deflection
+/// when translating accelerometer tilt into an axis.
+const DEFAULT_TILT_MAX_ANGLE: f64 = 30.0;
+
+/// Default device tilt in degrees around the center that is ignored when
+/// translating accelerometer tilt into an axis.
+const DEFAULT_TILT_DEADZONE: f64 = 3.0;
+
+/// Default stick deflection below which an axis translated into an absolute
+/// touch position stops touching.
+const DEFAULT_AXIS_TOUCH_DEADZONE: f64 = 0.2;
+
 /// Possible errors while doing input value translation
 pub enum TranslationError {
     /// Translation not yet implemented
@@ -79,6 +95,21 @@ impl InputValue {
         }
     }
 
+    /// Returns the roll angle in degrees of an accelerometer value, computed
+    /// from the direction of gravity. The angle is independent of the units
+    /// reported by the device.
+    pub fn accel_roll(&self) -> Option<f64> {
+        let InputValue::Vector3 {
+            x: Some(x),
+            y: Some(y),
+            z: Some(z),
+        } = self
+        else {
+            return None;
+        };
+        Some(x.atan2(y.hypot(*z)).to_degrees())
+    }
+
     // TODO: Implement all possible translations. We're currently missing many difficult but
     // posible translations.
     /// Translates the input value based on the source and target capabilities
@@ -108,7 +139,7 @@ impl InputValue {
             Capability::Gamepad(gamepad) => {
                 match gamepad {
                     // Gamepad Button -> ...
-                    Gamepad::Button(_) => {
+                    Gamepad::Button(_) | Gamepad::Gesture(_) => {
                         match target_cap {
                             // Gamepad Button -> None
                             Capability::None => Ok(InputValue::None),
@@ -130,6 +161,8 @@ impl InputValue {
                                 Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                                 // Gamepad Button -> Gyro
                                 Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                                Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
+                                Gamepad::Gesture(_) => Err(TranslationError::NotImplemented),
                             },
                             // Gamepad Button -> Mouse
                             Capability::Mouse(mouse) => match mouse {
@@ -178,6 +211,8 @@ impl InputValue {
                                 Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                                 // Axis -> Gyro
                                 Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                                Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
+                                Gamepad::Gesture(_) => Err(TranslationError::NotImplemented),
                             },
                             // Axis -> Mouse
                             Capability::Mouse(mouse) => match mouse {
@@ -191,12 +226,22 @@ impl InputValue {
                             Capability::Keyboard(_) => self.translate_axis_to_button(source_config),
                             // Axis -> Touchpad
                             Capability::Touchpad(touch) => match touch {
-                                Touchpad::LeftPad(_) => Err(TranslationError::NotImplemented),
-                                Touchpad::RightPad(_) => Err(TranslationError::NotImplemented),
-                                Touchpad::CenterPad(_) => Err(TranslationError::NotImplemented),
+                                // Axis -> Touchpad Motion
+                                Touchpad::LeftPad(Touch::Motion)
+                                | Touchpad::RightPad(Touch::Motion)
+                                | Touchpad::CenterPad(Touch::Motion) => {
+                                    self.translate_axis_to_touch(target_config)
+                                }
+                                // Axis -> Touchpad Button
+                                _ => Err(TranslationError::NotImplemented),
                             },
                             // Axis -> Touchscreen
-                            Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
+                            Capability::Touchscreen(touch) => match touch {
+                                // Axis -> Touchscreen Motion
+                                Touch::Motion => self.translate_axis_to_touch(target_config),
+                                // Axis -> Touchscreen Button
+                                Touch::Button(_) => Err(TranslationError::NotImplemented),
+                            },
                         }
                     }
                     // Trigger -> ...
@@ -223,6 +268,8 @@ impl InputValue {
                             Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                             // Trigger -> Gyro
                             Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                            Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
+                            Gamepad::Gesture(_) => Err(TranslationError::NotImplemented),
                         },
                         // Trigger -> Mouse
                         Capability::Mouse(mouse) => match mouse {
@@ -243,9 +290,27 @@ impl InputValue {
                         Capability::Touchscreen(_) => Err(TranslationError::NotImplemented),
                     },
                     // Accelerometer -> ...
-                    Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
+                    Gamepad::Accelerometer => match target_cap {
+                        // Accelerometer -> Axis
+                        Capability::Gamepad(Gamepad::Axis(_)) => {
+                            self.translate_accel_to_axis(source_config)
+                        }
+                        _ => Err(TranslationError::NotImplemented),
+                    },
                     // Gyro -> ...
-                    Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                    Gamepad::Gyro => match target_cap {
+                        // Gyro -> Axis
+                        Capability::Gamepad(Gamepad::Axis(_)) => {
+                            self.translate_gyro_to_axis(source_config)
+                        }
+                        _ => Err(TranslationError::NotImplemented),
+                    },
+                    // Dial -> ...
+                    Gamepad::Dial(_) => match target_cap {
+                        // Dial -> Dial
+                        Capability::Gamepad(Gamepad::Dial(_)) => Ok(self.clone()),
+                        _ => Err(TranslationError::NotImplemented),
+                    },
                 }
             }
 
@@ -269,6 +334,8 @@ impl InputValue {
                     Gamepad::Trigger(_) => Err(TranslationError::NotImplemented),
                     Gamepad::Accelerometer => Err(TranslationError::NotImplemented),
                     Gamepad::Gyro => Err(TranslationError::NotImplemented),
+                    Gamepad::Dial(_) => Err(TranslationError::NotImplemented),
+                    Gamepad::Gesture(_) => Err(TranslationError::NotImplemented),
                 },
                 // Keyboard Key -> Mouse
                 Capability::Mouse(mouse) => match mouse {
@@ -300,7 +367,9 @@ impl InputValue {
                             _ => Err(TranslationError::NotImplemented),
                         },
                         // Touchpad Motion -> Gamepad
-                        Capability::Gamepad(_) => Err(TranslationError::NotImplemented),
+                        Capability::Gamepad(gamepad) => {
+                            self.translate_touch_pressure(source_config, gamepad, target_config)
+                        }
                         // Touchpad Motion -> Mouse
                         Capability::Mouse(mouse) => match mouse {
                             // TODO:
@@ -338,7 +407,11 @@ impl InputValue {
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
                     },
-                    Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Button(_) => match target_cap {
+                        // Touchpad Button -> Mouse Button
+                        Capability::Mouse(Mouse::Button(_)) => Ok(self.clone()),
+                        _ => Err(TranslationError::NotImplemented),
+                    },
                 },
                 // RightPad -> ...
                 Touchpad::RightPad(touch) => match touch {
@@ -355,7 +428,9 @@ impl InputValue {
                             _ => Err(TranslationError::NotImplemented),
                         },
                         // Touchpad Motion -> Gamepad
-                        Capability::Gamepad(_) => Err(TranslationError::NotImplemented),
+                        Capability::Gamepad(gamepad) => {
+                            self.translate_touch_pressure(source_config, gamepad, target_config)
+                        }
                         // Touchpad Motion -> Mouse
                         Capability::Mouse(mouse) => match mouse {
                             // TODO:
@@ -393,7 +468,11 @@ impl InputValue {
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
                     },
-                    Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Button(_) => match target_cap {
+                        // Touchpad Button -> Mouse Button
+                        Capability::Mouse(Mouse::Button(_)) => Ok(self.clone()),
+                        _ => Err(TranslationError::NotImplemented),
+                    },
                 },
                 // CenterPad -> ...
                 Touchpad::CenterPad(touch) => match touch {
@@ -410,7 +489,9 @@ impl InputValue {
                             _ => Err(TranslationError::NotImplemented),
                         },
                         // Touchpad Motion -> Gamepad
-                        Capability::Gamepad(_) => Err(TranslationError::NotImplemented),
+                        Capability::Gamepad(gamepad) => {
+                            self.translate_touch_pressure(source_config, gamepad, target_config)
+                        }
                         // Touchpad Motion -> Mouse
                         Capability::Mouse(mouse) => match mouse {
                             // TODO:
@@ -448,7 +529,11 @@ impl InputValue {
                             Touch::Button(_) => Err(TranslationError::NotImplemented),
                         },
                     },
-                    Touch::Button(_) => Err(TranslationError::NotImplemented),
+                    Touch::Button(_) => match target_cap {
+                        // Touchpad Button -> Mouse Button
+                        Capability::Mouse(Mouse::Button(_)) => Ok(self.clone()),
+                        _ => Err(TranslationError::NotImplemented),
+                    },
                 },
             },
 
@@ -468,7 +553,9 @@ impl InputValue {
                         _ => Err(TranslationError::NotImplemented),
                     },
                     // Touchscreen Motion -> Gamepad ...
-                    Capability::Gamepad(_) => Err(TranslationError::NotImplemented),
+                    Capability::Gamepad(gamepad) => {
+                        self.translate_touch_pressure(source_config, gamepad, target_config)
+                    }
                     // Touchscreen Motion -> Mouse
                     Capability::Mouse(mouse) => match mouse {
                         // TODO:
@@ -514,7 +601,102 @@ impl InputValue {
         }
     }
 
-    /// Translate the axis value into mouse motion
+    /// Translate the pressure of the touch value into an analog trigger,
+    /// axis, or button value using the pressure curve of the source config.
+    /// Touches without pressure information are treated as fully pressed.
+    fn translate_touch_pressure(
+        &self,
+        source_config: &CapabilityConfig,
+        target_gamepad: &Gamepad,
+        target_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let (is_touching, pressure) = match self {
+            InputValue::Touch {
+                is_touching,
+                pressure,
+                ..
+            } => (*is_touching, *pressure),
+            _ => {
+                return Err(TranslationError::ImpossibleTranslation(
+                    "Only touch values can be translated from touch motion".to_string(),
+                ))
+            }
+        };
+        let touch = source_config
+            .touchpad
+            .as_ref()
+            .map(|touchpad| &touchpad.touch)
+            .or(source_config.touchscreen.as_ref());
+        let curve = touch
+            .and_then(|touch| touch.motion.as_ref())
+            .and_then(|motion| motion.pressure.as_ref());
+
+        let value = if is_touching {
+            pressure_curve(pressure.unwrap_or(1.0), curve)
+        } else {
+            0.0
+        };
+
+        match target_gamepad {
+            // Touch Motion -> Gamepad Trigger
+            Gamepad::Trigger(_) => Ok(InputValue::Float(value)),
+            // Touch Motion -> Gamepad Axis
+            Gamepad::Axis(_) => InputValue::Float(value).translate_button_to_axis(target_config),
+            // Touch Motion -> Gamepad Button
+            Gamepad::Button(_) => Ok(InputValue::Bool(value > 0.0)),
+            _ => Err(TranslationError::NotImplemented),
+        }
+    }
+
+    /// Translate the axis value into an absolute touch position, where the
+    /// center of the stick is the center of the touch device. The touch is
+    /// released when the stick is inside the deadzone.
+    fn translate_axis_to_touch(
+        &self,
+        target_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let (x, y) = match self {
+            InputValue::Vector2 { x, y } => (*x, *y),
+            _ => {
+                return Err(TranslationError::ImpossibleTranslation(
+                    "Only Vector2 axis values can be translated to touch".to_string(),
+                ))
+            }
+        };
+        let motion = target_config
+            .touchpad
+            .as_ref()
+            .map(|touchpad| &touchpad.touch)
+            .or(target_config.touchscreen.as_ref())
+            .and_then(|touch| touch.motion.as_ref());
+        let deadzone = motion
+            .and_then(|motion| motion.deadzone)
+            .unwrap_or(DEFAULT_AXIS_TOUCH_DEADZONE);
+
+        // Map the stick into the configured bounds of the touch device
+        let bounds = motion.and_then(|motion| motion.bounds.as_ref());
+        let (left, right) = bounds
+            .map(|bounds| (bounds.left.unwrap_or(0.0), bounds.right.unwrap_or(1.0)))
+            .unwrap_or((0.0, 1.0));
+        let (top, bottom) = bounds
+            .map(|bounds| (bounds.top.unwrap_or(0.0), bounds.bottom.unwrap_or(1.0)))
+            .unwrap_or((0.0, 1.0));
+
+        let magnitude = x.unwrap_or(0.0).hypot(y.unwrap_or(0.0));
+        let to_touch = |value: f64, min: f64, max: f64| {
+            let value = ((value + 1.0) / 2.0).clamp(0.0, 1.0);
+            (min + value * (max - min)).clamp(0.0, 1.0)
+        };
+
+        Ok(InputValue::Touch {
+            index: 0,
+            is_touching: magnitude > deadzone,
+            pressure: None,
+            x: x.map(|x| to_touch(x, left, right)),
+            y: y.map(|y| to_touch(y, top, bottom)),
+        })
+    }
+
     fn translate_axis_to_mouse_motion(
         &self,
         _source_config: &CapabilityConfig,
@@ -851,6 +1033,92 @@ impl InputValue {
         }
     }
 
+    /// Translate the gyro value into a joystick axis value. The horizontal axis
+    /// uses either the yaw or roll of the gyro, and the vertical axis uses the
+    /// pitch.
+    fn translate_gyro_to_axis(
+        &self,
+        source_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let Some(gyro) = source_config
+            .gamepad
+            .as_ref()
+            .and_then(|gamepad| gamepad.gyro.as_ref())
+        else {
+            return Err(TranslationError::InvalidSourceConfig(
+                "No gyro config to translate gyro to axis".to_string(),
+            ));
+        };
+
+        // Gyro values are (pitch, yaw, roll)
+        let InputValue::Vector3 {
+            x: pitch,
+            y: yaw,
+            z: roll,
+        } = self
+        else {
+            return Ok(InputValue::None);
+        };
+        let horizontal = match gyro.axis.as_deref() {
+            Some("roll") => roll,
+            Some("yaw") | None => yaw,
+            Some(axis) => {
+                return Err(TranslationError::InvalidSourceConfig(format!(
+                    "Invalid gyro axis to translate gyro to axis: {axis}"
+                )))
+            }
+        };
+
+        // Turning left or tilting back results in positive values, which need
+        // to be inverted to move the joystick left or up.
+        let scale = -gyro.sensitivity.unwrap_or(1.0) / GYRO_FULL_DEFLECTION_RATE;
+        let x = horizontal.map(|value| (value * scale).clamp(-1.0, 1.0));
+        let y = pitch.map(|value| (value * scale).clamp(-1.0, 1.0));
+
+        Ok(InputValue::Vector2 { x, y })
+    }
+
+    /// Translate the accelerometer value into a horizontal joystick axis value
+    /// using the roll of the device, computed from the direction of gravity.
+    /// This allows steering by tilting the device like a steering wheel.
+    fn translate_accel_to_axis(
+        &self,
+        source_config: &CapabilityConfig,
+    ) -> Result<InputValue, TranslationError> {
+        let Some(accel) = source_config
+            .gamepad
+            .as_ref()
+            .and_then(|gamepad| gamepad.accelerometer.as_ref())
+        else {
+            return Err(TranslationError::InvalidSourceConfig(
+                "No accelerometer config to translate accelerometer to axis".to_string(),
+            ));
+        };
+
+        let Some(roll) = self.accel_roll() else {
+            return Ok(InputValue::None);
+        };
+        let max_angle = accel.max_angle.unwrap_or(DEFAULT_TILT_MAX_ANGLE);
+        let deadzone = accel.deadzone.unwrap_or(DEFAULT_TILT_DEADZONE);
+        if max_angle <= deadzone {
+            return Err(TranslationError::InvalidSourceConfig(
+                "Accelerometer max angle must be larger than its deadzone".to_string(),
+            ));
+        }
+
+        // Scale the tilt outside of the deadzone to the range of the axis
+        let magnitude = ((roll.abs() - deadzone) / (max_angle - deadzone)).clamp(0.0, 1.0);
+        let mut value = magnitude.copysign(roll);
+        if accel.invert.unwrap_or(false) {
+            value = -value;
+        }
+
+        Ok(InputValue::Vector2 {
+            x: Some(value),
+            y: None,
+        })
+    }
+
     /// Translate the trigger value into an axis value based on the given config
     fn translate_trigger_to_axis(
         &self,
@@ -939,3 +1207,27 @@ impl InputValue {
         }
     }
 }
+
+/// Apply the given pressure curve to the pressure of a touch, returning a
+/// value between 0.0 and 1.0.
+pub fn pressure_curve(pressure: f64, curve: Option<&TouchPressure>) -> f64 {
+    let deadzone = curve.and_then(|c| c.deadzone).unwrap_or(0.0);
+    let max = curve.and_then(|c| c.max).unwrap_or(1.0);
+    let exponent = curve.and_then(|c| c.curve).unwrap_or(1.0);
+    if pressure <= deadzone || max <= deadzone {
+        return 0.0;
+    }
+
+    let value = ((pressure - deadzone) / (max - deadzone)).clamp(0.0, 1.0);
+    value.powf(exponent)
+}
+
+/// Returns the smallest distance in degrees between the two given angles
+pub fn angle_distance(a: f64, b: f64) -> f64 {
+    let distance = (a - b).rem_euclid(360.0);
+    if distance > 180.0 {
+        360.0 - distance
+    } else {
+        distance
+    }
+}
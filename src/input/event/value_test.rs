@@ -0,0 +1,168 @@
+use crate::{
+    config::CapabilityConfig,
+    input::capability::{Capability, Gamepad, GamepadAxis, GamepadTrigger, Touch, Touchpad},
+};
+
+use super::value::{angle_distance, InputValue};
+
+const TILT_SOURCE: &str = r#"
+gamepad:
+  accelerometer:
+    name: Accelerometer1
+    max_angle: 30.0
+    deadzone: 5.0
+"#;
+
+const LEFT_STICK_TARGET: &str = r#"
+gamepad:
+  axis:
+    name: LeftStick
+"#;
+
+/// Translate an accelerometer reading with the device rolled by the given
+/// angle in degrees into the left stick x value.
+fn translate_tilt(degrees: f64) -> Option<f64> {
+    let source_config: CapabilityConfig = serde_yaml::from_str(TILT_SOURCE).unwrap();
+    let target_config: CapabilityConfig = serde_yaml::from_str(LEFT_STICK_TARGET).unwrap();
+    let source_cap: Capability = source_config.clone().into();
+    let target_cap = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    assert_eq!(source_cap, Capability::Gamepad(Gamepad::Accelerometer));
+
+    let radians = degrees.to_radians();
+    let value = InputValue::Vector3 {
+        x: Some(9.8 * radians.sin()),
+        y: Some(0.0),
+        z: Some(9.8 * radians.cos()),
+    };
+    let result = value
+        .translate(&source_cap, &source_config, &target_cap, &target_config)
+        .unwrap();
+    let InputValue::Vector2 { x, y } = result else {
+        panic!("Expected Vector2 value, got {result:?}");
+    };
+    assert_eq!(y, None);
+    x
+}
+
+#[test]
+fn test_accel_tilt_to_axis() {
+    // Tilt inside the deadzone should be centered
+    assert_eq!(translate_tilt(0.0), Some(0.0));
+    assert_eq!(translate_tilt(4.0).map(f64::abs), Some(0.0));
+
+    // Tilt should be scaled from the deadzone to the max angle
+    let half = translate_tilt(17.5).unwrap();
+    assert!((half - 0.5).abs() < 0.001);
+    let half = translate_tilt(-17.5).unwrap();
+    assert!((half + 0.5).abs() < 0.001);
+
+    // Tilt beyond the max angle should be clamped
+    assert_eq!(translate_tilt(60.0), Some(1.0));
+    assert_eq!(translate_tilt(-60.0), Some(-1.0));
+}
+
+const TOUCH_PRESSURE_SOURCE: &str = r#"
+touchpad:
+  name: RightPad
+  touch:
+    motion:
+      pressure:
+        deadzone: 0.2
+        curve: 2.0
+"#;
+
+const RIGHT_TRIGGER_TARGET: &str = r#"
+gamepad:
+  trigger:
+    name: RightTrigger
+"#;
+
+#[test]
+fn test_touch_pressure_to_trigger() {
+    let source_config: CapabilityConfig = serde_yaml::from_str(TOUCH_PRESSURE_SOURCE).unwrap();
+    let target_config: CapabilityConfig = serde_yaml::from_str(RIGHT_TRIGGER_TARGET).unwrap();
+    let source_cap: Capability = source_config.clone().into();
+    let target_cap: Capability = target_config.clone().into();
+    assert_eq!(
+        source_cap,
+        Capability::Touchpad(Touchpad::RightPad(Touch::Motion))
+    );
+    assert_eq!(
+        target_cap,
+        Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger))
+    );
+
+    let translate = |is_touching: bool, pressure: Option<f64>| {
+        let value = InputValue::Touch {
+            index: 0,
+            is_touching,
+            pressure,
+            x: Some(0.5),
+            y: Some(0.5),
+        };
+        match value.translate(&source_cap, &source_config, &target_cap, &target_config) {
+            Ok(InputValue::Float(value)) => value,
+            _ => panic!("Touch pressure should translate to a trigger value"),
+        }
+    };
+
+    // Pressure inside the deadzone should not press the trigger
+    assert_eq!(translate(true, Some(0.1)), 0.0);
+    // Pressure should follow the curve after the deadzone
+    assert!((translate(true, Some(0.6)) - 0.25).abs() < 1e-9);
+    // Devices without pressure should fully press the trigger while touching
+    assert_eq!(translate(true, None), 1.0);
+    // Lifting the finger should release the trigger
+    assert_eq!(translate(false, Some(1.0)), 0.0);
+}
+
+const MOUSE_REGION_TARGET: &str = r#"
+touchscreen:
+  motion:
+    deadzone: 0.1
+    bounds:
+      left: 0.25
+      top: 0.5
+      right: 0.75
+      bottom: 1.0
+"#;
+
+#[test]
+fn test_axis_to_touchscreen_bounds() {
+    let source_config: CapabilityConfig = serde_yaml::from_str(LEFT_STICK_TARGET).unwrap();
+    let target_config: CapabilityConfig = serde_yaml::from_str(MOUSE_REGION_TARGET).unwrap();
+    let source_cap: Capability = source_config.clone().into();
+    let target_cap: Capability = target_config.clone().into();
+    assert_eq!(target_cap, Capability::Touchscreen(Touch::Motion));
+
+    let translate = |x: f64, y: f64| {
+        let value = InputValue::Vector2 {
+            x: Some(x),
+            y: Some(y),
+        };
+        match value.translate(&source_cap, &source_config, &target_cap, &target_config) {
+            Ok(InputValue::Touch {
+                is_touching,
+                x: Some(x),
+                y: Some(y),
+                ..
+            }) => (is_touching, x, y),
+            _ => panic!("Stick should translate to a touch position"),
+        }
+    };
+
+    // The centered stick should be in the center of the bounds
+    assert_eq!(translate(0.0, 0.0), (false, 0.5, 0.75));
+    // Full deflection should reach the edges of the bounds
+    assert_eq!(translate(-1.0, 0.0), (true, 0.25, 0.75));
+    assert_eq!(translate(1.0, 1.0), (true, 0.75, 1.0));
+    assert_eq!(translate(0.0, -1.0), (true, 0.5, 0.5));
+}
+
+#[test]
+fn test_angle_distance() {
+    assert_eq!(angle_distance(10.0, 350.0), 20.0);
+    assert_eq!(angle_distance(350.0, 10.0), 20.0);
+    assert_eq!(angle_distance(-90.0, 90.0), 180.0);
+    assert_eq!(angle_distance(45.0, 45.0), 0.0);
+}
@@ -4,20 +4,27 @@ use std::error::Error;
 use std::fs;
 use std::time::Duration;
 
+use ::evdev::{AbsInfo, AbsoluteAxisCode};
 use ::procfs::CpuInfo;
 use ::udev::MonitorBuilder;
+use inotify::WatchMask;
 use mio::{Events, Interest, Poll, Token};
 use thiserror::Error;
 use tokio::sync::mpsc;
 use tokio::task;
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 use zbus::fdo::ManagedObjects;
+use zbus::object_server::SignalContext;
 use zbus::zvariant::ObjectPath;
 use zbus::Connection;
 
 use crate::bluetooth::device1::Device1Proxy;
 use crate::config::path::get_capability_maps_paths;
 use crate::config::path::get_devices_paths;
+use crate::config::path::get_profiles_paths;
+use crate::config::profiles::list_profiles;
+use crate::config::profiles::ProfileInfo;
 use crate::config::CapabilityMap;
 use crate::config::CompositeDeviceConfig;
 use crate::config::SourceDevice;
@@ -33,12 +40,22 @@ use crate::dbus::interface::source::udev::SourceUdevDeviceInterface;
 use crate::dmi::data::DMIData;
 use crate::dmi::get_cpu_info;
 use crate::dmi::get_dmi_data;
+use crate::gamescope;
 use crate::input::composite_device::CompositeDevice;
+use crate::input::event::native::NativeEvent;
+use crate::input::event::Event;
 use crate::input::source::evdev;
+use crate::input::source::external::find_driver;
 use crate::input::source::hidraw;
+use crate::input::source::hidraw::bluetooth;
 use crate::input::source::iio;
+use crate::input::target::identity;
 use crate::input::target::TargetDevice;
 use crate::input::target::TargetDeviceTypeId;
+use crate::input::watchdog::TaskStalled;
+use crate::platform;
+use crate::platform::state::PlatformState;
+use crate::session::broker::SessionBroker;
 use crate::udev;
 use crate::udev::device::AttributeGetter;
 use crate::udev::device::UdevDevice;
@@ -52,6 +69,35 @@ use crate::watcher::WatchEvent;
 const DEV_PATH: &str = "/dev";
 const INPUT_PATH: &str = "/dev/input";
 const BUFFER_SIZE: usize = 20480;
+/// Time to wait for a source device to be released by its previous composite
+/// device before it is attached to another one
+const SOURCE_MOVE_DELAY: Duration = Duration::from_millis(500);
+/// Target device types that are assigned a player slot
+const PLAYER_SLOT_TARGET_TYPES: &[&str] = &[
+    "deck",
+    "ds5",
+    "ds5-edge",
+    "ds5-gadget",
+    "gamepad",
+    "hori-steam",
+    "xb360",
+    "xbox-elite",
+    "xbox-series",
+];
+
+/// Target device types that present a unique id or serial number to the host,
+/// which is kept stable for each composite device
+const IDENTITY_TARGET_TYPES: &[&str] = &[
+    "deck",
+    "ds5",
+    "ds5-usb",
+    "ds5-bt",
+    "ds5-edge",
+    "ds5-edge-usb",
+    "ds5-edge-bt",
+    "ds5-gadget",
+    "hori-steam",
+];
 
 #[derive(Error, Debug)]
 pub enum ManagerError {
@@ -59,6 +105,12 @@ pub enum ManagerError {
     CreateTargetDeviceFailed(String),
     #[error("failed to attach target device")]
     AttachTargetDeviceFailed(String),
+    #[error("failed to manage virtual source device")]
+    VirtualSourceDeviceFailed(String),
+    #[error("failed to manage external source device")]
+    ExternalSourceDeviceFailed(String),
+    #[error("failed to change source devices: {0}")]
+    SourceDeviceChangeFailed(String),
 }
 
 /// Manager commands define all the different ways to interact with [Manager]
@@ -72,11 +124,17 @@ pub enum ManagerCommand {
     DeviceRemoved {
         device: UdevDevice,
     },
+    RetrySourceDeviceAdded {
+        device: UdevDevice,
+    },
     CreateCompositeDevice {
         config: CompositeDeviceConfig,
     },
     CreateTargetDevice {
         kind: String,
+        axis_ranges: HashMap<AbsoluteAxisCode, AbsInfo>,
+        /// Stable id of the composite device the target is created for
+        device_id: Option<String>,
         sender: mpsc::Sender<Result<String, ManagerError>>,
     },
     StopTargetDevice {
@@ -90,6 +148,10 @@ pub enum ManagerCommand {
     TargetDeviceStopped {
         path: String,
     },
+    TargetDeviceStalled {
+        path: String,
+        kind: String,
+    },
     CompositeDeviceStopped(String),
     GetManageAllDevices {
         sender: mpsc::Sender<bool>,
@@ -101,6 +163,53 @@ pub enum ManagerCommand {
     SystemWake {
         sender: mpsc::Sender<()>,
     },
+    PlatformStateChanged {
+        state: PlatformState,
+    },
+    FocusedAppChanged {
+        app_id: Option<u32>,
+    },
+    GetCompositeDevice {
+        path: String,
+        sender: mpsc::Sender<Option<CompositeDeviceClient>>,
+    },
+    ListProfiles {
+        sender: mpsc::Sender<Vec<ProfileInfo>>,
+    },
+    ProfilesChanged,
+    CreateVirtualSourceDevice {
+        name: String,
+        script: String,
+        sender: mpsc::Sender<Result<String, ManagerError>>,
+    },
+    DestroyVirtualSourceDevice {
+        id: String,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
+    SendVirtualSourceEvent {
+        id: String,
+        event: NativeEvent,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
+    CreateExternalSourceDevice {
+        name: String,
+        driver: String,
+        sender: mpsc::Sender<Result<String, ManagerError>>,
+    },
+    DestroyExternalSourceDevice {
+        id: String,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
+    AttachSourceDevice {
+        device_path: String,
+        composite_path: String,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
+    DetachSourceDevice {
+        device_path: String,
+        composite_path: String,
+        sender: mpsc::Sender<Result<(), ManagerError>>,
+    },
 }
 
 /// Manages input devices
@@ -154,9 +263,43 @@ pub struct Manager {
     /// Mapping of target devices to their respective handles
     /// E.g. {"/org/shadowblip/InputPlumber/devices/target/dbus0": <Handle>}
     target_devices: HashMap<String, TargetDeviceClient>,
+    /// Player slots assigned to gamepad composite devices, starting at 1
+    /// E.g. {"/org/shadowblip/InputPlumber/CompositeDevice0": 1}
+    player_slots: HashMap<String, u8>,
+    /// Bluetooth addresses of bluetooth source devices. Unlike the source
+    /// device id, the address stays the same when the device reconnects.
+    /// E.g. {"hidraw://hidraw3": "aa:bb:cc:dd:ee:ff"}
+    bluetooth_addresses: HashMap<String, String>,
+    /// Player slots of bluetooth devices that disconnected, so the device is
+    /// assigned the same player slot if it reconnects.
+    /// E.g. {"aa:bb:cc:dd:ee:ff": 2}
+    bluetooth_player_slots: HashMap<String, u8>,
+    /// Number of failed attempts to attach source devices that are being
+    /// retried, mapped by source device id.
+    /// E.g. {"hidraw://hidraw3": 1}
+    source_device_attach_attempts: HashMap<String, u32>,
+    /// Number of ports of multi-port adapters, whose ports are managed as
+    /// separate source devices, mapped by the id of the adapter.
+    /// E.g. {"hidraw://hidraw3": 4}
+    multi_port_devices: HashMap<String, u8>,
+    /// Virtual source devices created over DBus, mapped by their id.
+    /// E.g. {"virtual://demo": <UdevDevice>}
+    virtual_devices: HashMap<String, UdevDevice>,
+    /// External source devices created over DBus, mapped by their id.
+    /// E.g. {"external://mypad": <UdevDevice>}
+    external_devices: HashMap<String, UdevDevice>,
     /// Defines whether or not InputPlumber should try to automatically manage all
     /// input devices that have a [CompositeDeviceConfig] definition
     manage_all_devices: bool,
+    /// Current hardware state of the platform (e.g. tablet mode, lid, dock)
+    /// used to conditionally apply capability mappings.
+    platform_state: PlatformState,
+    /// Steam app id of the game focused in gamescope, if known
+    focused_app_id: Option<u32>,
+    /// Metadata of all input profiles found in the profile directories
+    profiles: Vec<ProfileInfo>,
+    /// Whether or not to listen for per-user session instances
+    session_broker: bool,
 }
 
 impl Manager {
@@ -192,10 +335,27 @@ impl Manager {
             used_configs: HashMap::new(),
             composite_device_sources: HashMap::new(),
             composite_device_targets: HashMap::new(),
+            player_slots: HashMap::new(),
+            bluetooth_addresses: HashMap::new(),
+            bluetooth_player_slots: HashMap::new(),
+            source_device_attach_attempts: HashMap::new(),
+            multi_port_devices: HashMap::new(),
+            virtual_devices: HashMap::new(),
+            external_devices: HashMap::new(),
             manage_all_devices: false,
+            platform_state: PlatformState::default(),
+            focused_app_id: None,
+            profiles: list_profiles(&get_profiles_paths()),
+            session_broker: false,
         }
     }
 
+    /// Enable or disable the session broker, which allows per-user session
+    /// instances to load profiles on behalf of their user.
+    pub fn set_session_broker(&mut self, enabled: bool) {
+        self.session_broker = enabled;
+    }
+
     /// Starts listening for [Command] messages to be sent from clients and
     /// dispatch those events.
     pub async fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -228,8 +388,16 @@ impl Manager {
         let _ = tokio::join!(
             Self::discover_all_devices(&cmd_tx_all_devices),
             Self::watch_iio_devices(self.tx.clone()),
+            Self::watch_platform_state(self.tx.clone()),
+            Self::watch_focused_app(self.tx.clone()),
+            Self::watch_profiles(self.tx.clone()),
             Self::watch_devnodes(self.tx.clone(), &mut watcher_rx),
-            Self::listen_on_dbus(dbus_for_listen_on_dbus, self.tx.clone()),
+            Self::listen_on_dbus(dbus_for_listen_on_dbus.clone(), self.tx.clone()),
+            Self::listen_on_session_broker(
+                self.session_broker,
+                dbus_for_listen_on_dbus,
+                self.tx.clone()
+            ),
             self.events_loop()
         );
 
@@ -252,10 +420,22 @@ impl Manager {
                         log::error!("Error handling stopped composite device: {:?}", e);
                     }
                 }
-                ManagerCommand::CreateTargetDevice { kind, sender } => {
+                ManagerCommand::CreateTargetDevice {
+                    kind,
+                    axis_ranges,
+                    device_id,
+                    sender,
+                } => {
                     // Create the target device
                     log::debug!("Got request to create target device: {kind}");
-                    let device = match self.create_and_start_target_device(kind.as_str()).await {
+                    let device = match self
+                        .create_and_start_target_device(
+                            kind.as_str(),
+                            &axis_ranges,
+                            device_id.as_deref(),
+                        )
+                        .await
+                    {
                         Ok(device) => device,
                         Err(err) => {
                             if let Err(e) = sender.send(Err(err)).await {
@@ -330,6 +510,21 @@ impl Manager {
                     log::debug!("Target device stopped: {path}");
                     self.target_devices.remove(&path);
                 }
+                ManagerCommand::TargetDeviceStalled { path, kind } => {
+                    // The stalled target device keeps its path, since its
+                    // DBus interface cannot be removed while it is stuck.
+                    log::warn!("Target device {path} stopped responding");
+                    for composite_device in self.composite_devices.values() {
+                        let result = composite_device
+                            .target_device_stalled(path.clone(), kind.clone())
+                            .await;
+                        if let Err(e) = result {
+                            log::error!(
+                                "Failed to notify composite device of stalled target: {e:?}"
+                            );
+                        }
+                    }
+                }
                 ManagerCommand::DeviceAdded { device } => {
                     let dev_name = device.name();
                     let dev_sysname = device.sysname();
@@ -343,6 +538,16 @@ impl Manager {
                         log::error!("Error removing device: {e}");
                     }
                 }
+                ManagerCommand::RetrySourceDeviceAdded { device } => {
+                    // Skip the retry if the device was removed in the meantime
+                    let id = device.get_id();
+                    if !self.source_device_attach_attempts.contains_key(&id) {
+                        continue;
+                    }
+                    if let Err(e) = self.attach_source_device(id.clone(), device).await {
+                        log::error!("Error adding source device {id}: {e}");
+                    }
+                }
                 ManagerCommand::SetManageAllDevices(manage_all_devices) => {
                     log::debug!("Setting management of all devices to: {manage_all_devices}");
                     if self.manage_all_devices == manage_all_devices {
@@ -429,6 +634,129 @@ impl Manager {
                         log::info!("Finished preparing for system resume");
                     });
                 }
+                ManagerCommand::GetCompositeDevice { path, sender } => {
+                    let device = self.composite_devices.get(&path).cloned();
+                    if let Err(e) = sender.send(device).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::PlatformStateChanged { state } => {
+                    log::debug!("Platform state changed: {state:?}");
+                    if self.platform_state == state {
+                        continue;
+                    }
+                    self.platform_state = state.clone();
+
+                    // Inform all composite devices of the new platform state
+                    for (path, device) in self.composite_devices.iter() {
+                        if let Err(e) = device.set_platform_state(state.clone()).await {
+                            log::error!("Failed to set platform state on {path}: {e:?}");
+                        }
+                    }
+                }
+                ManagerCommand::FocusedAppChanged { app_id } => {
+                    log::debug!("Focused app changed: {app_id:?}");
+                    if self.focused_app_id == app_id {
+                        continue;
+                    }
+                    self.focused_app_id = app_id;
+
+                    // Inform all composite devices of the new focused app
+                    for (path, device) in self.composite_devices.iter() {
+                        if let Err(e) = device.set_focused_app(app_id).await {
+                            log::error!("Failed to set focused app on {path}: {e:?}");
+                        }
+                    }
+                }
+                ManagerCommand::ListProfiles { sender } => {
+                    if let Err(e) = sender.send(self.profiles.clone()).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::ProfilesChanged => {
+                    if let Err(e) = self.on_profiles_changed().await {
+                        log::error!("Failed to reload profiles: {e:?}");
+                    }
+                }
+                ManagerCommand::CreateVirtualSourceDevice {
+                    name,
+                    script,
+                    sender,
+                } => {
+                    let result = self
+                        .create_virtual_source_device(name.as_str(), script.as_str())
+                        .await
+                        .map_err(|e| ManagerError::VirtualSourceDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::DestroyVirtualSourceDevice { id, sender } => {
+                    let result = self
+                        .destroy_virtual_source_device(id.as_str())
+                        .await
+                        .map_err(|e| ManagerError::VirtualSourceDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::SendVirtualSourceEvent { id, event, sender } => {
+                    let result = self
+                        .send_virtual_source_event(id, event)
+                        .await
+                        .map_err(|e| ManagerError::VirtualSourceDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::CreateExternalSourceDevice {
+                    name,
+                    driver,
+                    sender,
+                } => {
+                    let result = self
+                        .create_external_source_device(name.as_str(), driver.as_str())
+                        .await
+                        .map_err(|e| ManagerError::ExternalSourceDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::DestroyExternalSourceDevice { id, sender } => {
+                    let result = self
+                        .destroy_external_source_device(id.as_str())
+                        .await
+                        .map_err(|e| ManagerError::ExternalSourceDeviceFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::AttachSourceDevice {
+                    device_path,
+                    composite_path,
+                    sender,
+                } => {
+                    let result = self
+                        .attach_source_device_to(device_path.as_str(), composite_path.as_str())
+                        .await
+                        .map_err(|e| ManagerError::SourceDeviceChangeFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
+                ManagerCommand::DetachSourceDevice {
+                    device_path,
+                    composite_path,
+                    sender,
+                } => {
+                    let result = self
+                        .detach_source_device(device_path.as_str(), composite_path.as_str())
+                        .await
+                        .map_err(|e| ManagerError::SourceDeviceChangeFailed(e.to_string()));
+                    if let Err(e) = sender.send(result).await {
+                        log::error!("Failed to send response: {e:?}");
+                    }
+                }
             }
         }
 
@@ -463,7 +791,7 @@ impl Manager {
         // Create a composite device to manage these devices
         log::info!("Found matching source device for: {:?}", config.name);
         let config = config.clone();
-        let device = CompositeDevice::new(
+        let mut device = CompositeDevice::new(
             self.dbus.clone(),
             self.tx.clone(),
             config,
@@ -471,6 +799,8 @@ impl Manager {
             self.next_composite_dbus_path()?,
             capability_map,
         )?;
+        device.set_platform_state(self.platform_state.clone());
+        device.set_focused_app(self.focused_app_id);
 
         // Check to see if there's already a CompositeDevice for
         // these source devices.
@@ -487,14 +817,26 @@ impl Manager {
     }
 
     /// Create target input device to emulate based on the given device type.
-    async fn create_target_device(&mut self, kind: &str) -> Result<TargetDevice, Box<dyn Error>> {
+    /// Absolute axes of the target device will use the given axis ranges if
+    /// they are defined. Target devices created for the composite device with
+    /// the given stable id keep the same unique id and serial number.
+    async fn create_target_device(
+        &mut self,
+        kind: &str,
+        axis_ranges: &HashMap<AbsoluteAxisCode, AbsInfo>,
+        device_id: Option<&str>,
+    ) -> Result<TargetDevice, Box<dyn Error>> {
         log::trace!("Creating target device: {kind}");
         let Ok(target_id) = TargetDeviceTypeId::try_from(kind) else {
             return Err("Invalid target device ID".to_string().into());
         };
+        let identity = device_id
+            .filter(|_| IDENTITY_TARGET_TYPES.contains(&kind))
+            .map(|device_id| identity::load_or_create(device_id, kind));
 
         // Create the target device to emulate based on the kind
-        let device = TargetDevice::from_type_id(target_id, self.dbus.clone())?;
+        let device =
+            TargetDevice::from_type_id(target_id, self.dbus.clone(), axis_ranges, identity)?;
 
         Ok(device)
     }
@@ -520,12 +862,28 @@ impl Manager {
             target_devices.insert(path.clone(), client.clone());
             self.target_devices.insert(path.clone(), client.clone());
 
-            // Run the target device
+            // Run the target device. Target devices that stop responding are
+            // re-created by the composite device they are attached to.
+            let tx = self.tx.clone();
             tokio::spawn(async move {
-                if let Err(e) = target.run(path.clone()).await {
-                    log::error!("Failed to run target device {path}: {e:?}");
-                }
+                let stalled_kind = match target.run(path.clone()).await {
+                    Ok(_) => None,
+                    Err(e) => {
+                        log::error!("Failed to run target device {path}: {e:?}");
+                        e.downcast_ref::<TaskStalled>()
+                            .map(|stalled| stalled.name.clone())
+                    }
+                };
                 log::debug!("Target device closed at: {path}");
+                let Some(kind) = stalled_kind else {
+                    return;
+                };
+                if let Err(e) = tx
+                    .send(ManagerCommand::TargetDeviceStalled { path, kind })
+                    .await
+                {
+                    log::error!("Failed to send target device stalled: {e:?}");
+                }
             });
         }
 
@@ -551,9 +909,14 @@ impl Manager {
     async fn create_and_start_target_device(
         &mut self,
         kind: &str,
+        axis_ranges: &HashMap<AbsoluteAxisCode, AbsInfo>,
+        device_id: Option<&str>,
     ) -> Result<HashMap<String, TargetDeviceClient>, ManagerError> {
         // Create the target device
-        let device = match self.create_target_device(kind).await {
+        let device = match self
+            .create_target_device(kind, axis_ranges, device_id)
+            .await
+        {
             Ok(device) => device,
             Err(e) => {
                 let err = format!("Error creating target device: {e:?}");
@@ -590,10 +953,11 @@ impl Manager {
         log::debug!(
             "Starting CompositeDevice at {composite_path} with the following sources: {source_device_ids:?}"
         );
-        for id in source_device_ids {
+        for id in source_device_ids.iter() {
             self.source_devices_used
                 .insert(id.clone(), composite_path.clone());
-            self.source_devices.insert(id, source_device.clone());
+            self.source_devices
+                .insert(id.clone(), source_device.clone());
         }
 
         if !self.composite_device_sources.contains_key(&composite_path) {
@@ -616,7 +980,9 @@ impl Manager {
 
         // Create a DBus target device
         log::debug!("Creating target devices for {composite_path}");
-        let dbus_device = self.create_target_device("dbus").await?;
+        let dbus_device = self
+            .create_target_device("dbus", &HashMap::new(), None)
+            .await?;
         let dbus_devices = self.start_target_devices(vec![dbus_device]).await?;
         let dbus_paths = dbus_devices.keys();
         for dbus_path in dbus_paths {
@@ -624,11 +990,26 @@ impl Manager {
         }
         device.set_dbus_devices(dbus_devices);
 
-        // Create target devices based on the configuration
+        // Only gamepads are assigned a player slot
+        let is_gamepad = target_types.as_ref().is_some_and(|types| {
+            types
+                .iter()
+                .any(|kind| PLAYER_SLOT_TARGET_TYPES.contains(&kind.as_str()))
+        });
+
+        // Create target devices based on the configuration using the axis
+        // ranges of the source devices. Staged target devices are created by
+        // the composite device once it is running.
+        let axis_ranges = device.axis_ranges();
         let mut target_devices = Vec::new();
-        if let Some(target_devices_config) = target_types {
+        let is_staged = config
+            .target_creation()
+            .is_some_and(|creation| creation.is_staged());
+        if let Some(target_devices_config) = target_types.filter(|_| !is_staged) {
             for kind in target_devices_config {
-                let device = self.create_target_device(kind.as_str()).await?;
+                let device = self
+                    .create_target_device(kind.as_str(), &axis_ranges, Some(device.stable_id()))
+                    .await?;
                 target_devices.push(device);
             }
         }
@@ -640,6 +1021,17 @@ impl Manager {
             target_device_paths.push(target_path.clone());
         }
 
+        // Assign the lowest free player slot to gamepads, unless a bluetooth
+        // device is reconnecting and its previous slot is still free.
+        if is_gamepad {
+            let slot = self
+                .reserved_player_slot(&source_device_ids)
+                .unwrap_or_else(|| self.next_player_slot());
+            log::debug!("Assigning player slot {slot} to {composite_path}");
+            self.player_slots.insert(composite_path.clone(), slot);
+            device.set_player_index(Some(slot)).await;
+        }
+
         // Add the device to our maps
         self.composite_devices
             .insert(composite_path.clone(), client);
@@ -650,10 +1042,12 @@ impl Manager {
             .insert(composite_path.clone(), target_device_paths);
         log::trace!("Used target devices: {:?}", self.composite_device_targets);
 
-        // Run the device
+        // Run the device. Logs from the device and its source devices are
+        // recorded in a span so they can be filtered per device.
         let composite_path = String::from(device.dbus_path());
+        let span = tracing::info_span!("composite_device", path = %composite_path);
         let tx = self.tx.clone();
-        Ok(tokio::spawn(async move {
+        let task = async move {
             if let Err(e) = device.run(targets).await {
                 log::error!("Error running {composite_path}: {}", e.to_string());
             }
@@ -669,7 +1063,8 @@ impl Manager {
                     e.to_string()
                 );
             }
-        }))
+        };
+        Ok(tokio::spawn(task.instrument(span)))
     }
 
     /// Called when a composite device stops running
@@ -718,10 +1113,35 @@ impl Manager {
         log::debug!("Used config removed: {}", path);
         self.composite_device_targets.remove(&path);
         log::debug!("Used target devices: {:?}", self.composite_device_targets);
+        if let Some(slot) = self.player_slots.remove(&path) {
+            log::debug!("Released player slot {slot} from {path}");
+        }
 
         Ok(())
     }
 
+    /// Returns the lowest player slot that is not assigned to a composite
+    /// device
+    fn next_player_slot(&self) -> u8 {
+        let mut slot = 1;
+        while self.player_slots.values().any(|used| *used == slot) {
+            slot += 1;
+        }
+        slot
+    }
+
+    /// Returns the player slot that a bluetooth device with one of the given
+    /// source device ids was assigned before it disconnected, if that slot is
+    /// still free.
+    fn reserved_player_slot(&self, source_device_ids: &[String]) -> Option<u8> {
+        source_device_ids
+            .iter()
+            .filter_map(|id| self.bluetooth_addresses.get(id))
+            .filter_map(|address| self.bluetooth_player_slots.get(address))
+            .find(|slot| !self.player_slots.values().any(|used| used == *slot))
+            .copied()
+    }
+
     /// Called when any source device is added. This method will load all
     /// device configurations to check and see if any configuration matches
     /// the input devices on the system. If a match is found, a [CompositeDevice]
@@ -740,12 +1160,17 @@ impl Manager {
             log::debug!("Checking if existing composite device {composite_device:?} with config {:?} is missing device: {id:?}", config.name);
 
             // If the CompositeDevice only allows a single source device, skip its
-            // consideration.
-            if config.single_source.unwrap_or(false) {
+            // consideration. Devices that mirror disconnects keep running
+            // without source devices, so their source can reconnect.
+            let has_sources = self
+                .composite_device_sources
+                .get(composite_device)
+                .is_some_and(|sources| !sources.is_empty());
+            if config.single_source.unwrap_or(false) && has_sources {
                 log::trace!("{:?} is a single source device. Skipping.", config.name);
                 continue;
             }
-            if config.maximum_sources.unwrap_or(0) == 1 {
+            if config.maximum_sources.unwrap_or(0) == 1 && has_sources {
                 log::trace!("{:?} is a single source device. Skipping.", config.name);
                 continue;
             }
@@ -885,7 +1310,7 @@ impl Manager {
                     .await?;
 
                 // Get the target input devices from the config
-                let target_devices_config = config.target_devices.clone();
+                let target_devices_config = config.target_device_types();
 
                 // Create the composite deivce
                 self.start_composite_device(
@@ -906,6 +1331,43 @@ impl Manager {
         Ok(())
     }
 
+    /// Add the given source device, retrying with a backoff if a bluetooth
+    /// device fails to attach while it is still reconnecting.
+    async fn attach_source_device(
+        &mut self,
+        id: String,
+        device: UdevDevice,
+    ) -> Result<(), Box<dyn Error>> {
+        let result = self
+            .on_source_device_added(id.clone(), device.clone())
+            .await;
+        let Err(e) = result else {
+            self.source_device_attach_attempts.remove(&id);
+            return Ok(());
+        };
+        let attempt = self.source_device_attach_attempts.get(&id).unwrap_or(&0) + 1;
+        if !bluetooth::should_retry_attach(&device) || attempt > bluetooth::ATTACH_RETRIES {
+            self.source_device_attach_attempts.remove(&id);
+            return Err(e);
+        }
+        let delay = bluetooth::attach_retry_delay(attempt);
+        log::warn!("Failed to attach bluetooth source device {id}: {e}. Retrying in {delay:?}");
+        self.source_device_attach_attempts.insert(id, attempt);
+
+        let tx = self.tx.clone();
+        task::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let result = tx
+                .send(ManagerCommand::RetrySourceDeviceAdded { device })
+                .await;
+            if let Err(e) = result {
+                log::error!("Unable to send command: {:?}", e);
+            }
+        });
+
+        Ok(())
+    }
+
     /// Called when any source device is removed
     async fn on_source_device_removed(
         &mut self,
@@ -914,11 +1376,21 @@ impl Manager {
     ) -> Result<(), Box<dyn Error>> {
         let dev_name = device.name();
         log::debug!("Source device removed: {dev_name}");
+        self.source_device_attach_attempts.remove(&id);
+        let address = self.bluetooth_addresses.remove(&id);
         let Some(composite_device_path) = self.source_devices_used.get(&id) else {
             log::debug!("Source device not being managed by a composite device");
             return Ok(());
         };
 
+        // Remember the player slot of bluetooth devices in case they reconnect
+        if let Some(address) = address {
+            if let Some(slot) = self.player_slots.get(composite_device_path) {
+                log::debug!("Reserving player slot {slot} for bluetooth device {address}");
+                self.bluetooth_player_slots.insert(address, *slot);
+            }
+        }
+
         let Some(client) = self.composite_devices.get(composite_device_path) else {
             return Err(format!("CompostiteDevice {} not found", composite_device_path).into());
         };
@@ -946,6 +1418,233 @@ impl Manager {
         Ok(())
     }
 
+    /// Create a virtual source device with the given name that replays the
+    /// script at the given path, if any. The device is added like any other
+    /// source device, so a composite device config must match it. Returns
+    /// the id of the created source device.
+    async fn create_virtual_source_device(
+        &mut self,
+        name: &str,
+        script: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let is_valid_name = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if name.is_empty() || !name.chars().all(is_valid_name) {
+            return Err(format!("Invalid virtual source device name: {name}").into());
+        }
+        let device = UdevDevice::new_virtual(name, script);
+        let id = device.get_id();
+        if self.virtual_devices.contains_key(&id) {
+            return Err(format!("Virtual source device {id} already exists").into());
+        }
+
+        log::info!("Creating virtual source device: {id}");
+        self.on_source_device_added(id.clone(), device.clone())
+            .await?;
+        if !self.source_devices_used.contains_key(&id) {
+            return Err(format!("No composite device config matches {id}").into());
+        }
+        self.virtual_devices.insert(id.clone(), device);
+
+        Ok(id)
+    }
+
+    /// Remove the virtual source device with the given id from its composite
+    /// device
+    async fn destroy_virtual_source_device(&mut self, id: &str) -> Result<(), Box<dyn Error>> {
+        let Some(device) = self.virtual_devices.remove(id) else {
+            return Err(format!("Virtual source device {id} not found").into());
+        };
+        log::info!("Destroying virtual source device: {id}");
+        self.on_source_device_removed(device, id.to_string()).await
+    }
+
+    /// Send the given event to the composite device as if it was emitted by
+    /// the virtual source device with the given id
+    async fn send_virtual_source_event(
+        &self,
+        id: String,
+        event: NativeEvent,
+    ) -> Result<(), Box<dyn Error>> {
+        if !self.virtual_devices.contains_key(&id) {
+            return Err(format!("Virtual source device {id} not found").into());
+        }
+        let Some(composite_path) = self.source_devices_used.get(&id) else {
+            return Err(format!("Virtual source device {id} is not attached").into());
+        };
+        let Some(client) = self.composite_devices.get(composite_path) else {
+            return Err(format!("CompositeDevice {composite_path} not found").into());
+        };
+        client.process_event(id, Event::Native(event)).await?;
+
+        Ok(())
+    }
+
+    /// Create an external source device with the given name that is
+    /// implemented by the allow-listed driver executable with the given name.
+    /// The device is added like any other source device, so a composite device
+    /// config must match it. Returns the id of the created source device.
+    async fn create_external_source_device(
+        &mut self,
+        name: &str,
+        driver: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let is_valid_name = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if name.is_empty() || !name.chars().all(is_valid_name) {
+            return Err(format!("Invalid external source device name: {name}").into());
+        }
+        if find_driver(driver).is_none() {
+            return Err(format!("External driver not found: {driver}").into());
+        }
+        let device = UdevDevice::new_external(name, driver);
+        let id = device.get_id();
+        if self.external_devices.contains_key(&id) {
+            return Err(format!("External source device {id} already exists").into());
+        }
+
+        log::info!("Creating external source device: {id}");
+        self.on_source_device_added(id.clone(), device.clone())
+            .await?;
+        if !self.source_devices_used.contains_key(&id) {
+            return Err(format!("No composite device config matches {id}").into());
+        }
+        self.external_devices.insert(id.clone(), device);
+
+        Ok(id)
+    }
+
+    /// Remove the external source device with the given id from its composite
+    /// device, which stops its driver
+    async fn destroy_external_source_device(&mut self, id: &str) -> Result<(), Box<dyn Error>> {
+        let Some(device) = self.external_devices.remove(id) else {
+            return Err(format!("External source device {id} not found").into());
+        };
+        log::info!("Destroying external source device: {id}");
+        self.on_source_device_removed(device, id.to_string()).await
+    }
+
+    /// Detach the source device with the given path (e.g. "/dev/hidraw0")
+    /// from the given composite device. The source device is released and
+    /// stays unmanaged until it is attached again or reconnected.
+    async fn detach_source_device(
+        &mut self,
+        device_path: &str,
+        composite_path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let device = find_source_device(device_path)?;
+        let id = device.get_id();
+        if self.source_devices_used.get(&id).map(|path| path.as_str()) != Some(composite_path) {
+            return Err(
+                format!("Source device {device_path} is not attached to {composite_path}").into(),
+            );
+        }
+        let source_count = self
+            .composite_device_sources
+            .get(composite_path)
+            .map(|sources| sources.len())
+            .unwrap_or_default();
+        if source_count <= 1 {
+            return Err(format!(
+                "Cannot detach the last source device of {composite_path}. Stop the composite device instead."
+            )
+            .into());
+        }
+
+        log::info!("Detaching source device {id} from {composite_path}");
+        self.release_source_device(device, id, true).await
+    }
+
+    /// Attach the source device with the given path (e.g. "/dev/hidraw0") to
+    /// the given composite device. If the source device is used by another
+    /// composite device, it is detached from it first.
+    async fn attach_source_device_to(
+        &mut self,
+        device_path: &str,
+        composite_path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let device = find_source_device(device_path)?;
+        let id = device.get_id();
+        let Some(client) = self.composite_devices.get(composite_path).cloned() else {
+            return Err(format!("CompositeDevice {composite_path} not found").into());
+        };
+
+        // Release the device from the composite device that is using it
+        let previous = self.source_devices_used.get(&id).cloned();
+        if let Some(previous) = previous.as_ref() {
+            if previous == composite_path {
+                return Err(format!(
+                    "Source device {device_path} is already attached to {composite_path}"
+                )
+                .into());
+            }
+            log::info!("Moving source device {id} from {previous} to {composite_path}");
+            self.release_source_device(device.clone(), id.clone(), false)
+                .await?;
+        } else {
+            log::info!("Attaching source device {id} to {composite_path}");
+        }
+
+        // Use the matching source config of the composite device, if any, to
+        // keep track of the source device.
+        let source_device = self
+            .used_configs
+            .get(composite_path)
+            .and_then(|config| config.get_matching_device(&device))
+            .unwrap_or_else(|| SourceDevice {
+                group: "gamepad".to_string(),
+                evdev: None,
+                hidraw: None,
+                iio: None,
+                udev: None,
+                unique: None,
+                blocked: None,
+                ignore: None,
+                power: None,
+                rumble_side: None,
+                priority: None,
+                detachable: None,
+            });
+        self.source_devices_used
+            .insert(id.clone(), composite_path.to_string());
+        self.composite_device_sources
+            .entry(composite_path.to_string())
+            .or_default()
+            .push(source_device.clone());
+        self.source_devices.insert(id, source_device);
+
+        // Give the previous composite device time to release the device
+        if previous.is_none() {
+            return self.add_device_to_composite_device(device, &client).await;
+        }
+        task::spawn(async move {
+            tokio::time::sleep(SOURCE_MOVE_DELAY).await;
+            if let Err(e) = client.add_source_device(device).await {
+                log::error!("Failed to attach moved source device: {e:?}");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Stop the given source device in the composite device that is using it
+    /// and stop tracking it as used.
+    async fn release_source_device(
+        &mut self,
+        device: UdevDevice,
+        id: String,
+        unhide: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(composite_path) = self.source_devices_used.get(&id) else {
+            return Err(
+                format!("Source device {id} is not being managed by a composite device").into(),
+            );
+        };
+        let Some(client) = self.composite_devices.get(composite_path) else {
+            return Err(format!("CompostiteDevice {composite_path} not found").into());
+        };
+        client.detach_source_device(device.clone(), unhide).await?;
+        self.on_source_device_removed(device, id).await
+    }
+
     /// Called when a new device is detected by udev
     async fn on_device_added(&mut self, device: UdevDevice) -> Result<(), Box<dyn Error>> {
         let dev_path = device.devpath();
@@ -1034,6 +1733,10 @@ impl Manager {
                     log::trace!("{dev_name} ({dev_sysname}) is a real device - {dev_path}");
                 }
 
+                if let Some(address) = device.bluetooth_address() {
+                    self.bluetooth_addresses.insert(id.clone(), address);
+                }
+
                 // Signal that a source device was added
                 log::debug!("Spawning task to add source device: {id}");
                 self.on_source_device_added(id.clone(), device).await?;
@@ -1047,6 +1750,10 @@ impl Manager {
 
                 log::debug!("hidraw device added: {dev_name} ({dev_sysname})");
 
+                // Query feature reports before any source driver is started for
+                // the device, since the driver owns the device afterwards.
+                let info = SourceHIDRawInterface::query_device_info(&dev).await;
+
                 // Create a DBus interface for the event device
                 let conn = self.dbus.clone();
                 let path = hidraw::get_dbus_path(sys_name.clone());
@@ -1064,7 +1771,8 @@ impl Manager {
                     if let Err(e) = result {
                         log::error!("Error creating source udev dbus interface: {e:?}");
                     }
-                    let result = SourceHIDRawInterface::listen_on_dbus(conn, sysname, dev).await;
+                    let result =
+                        SourceHIDRawInterface::listen_on_dbus(conn, sysname, dev, info).await;
                     if let Err(e) = result {
                         log::error!("Error creating source evdev dbus interface: {e:?}");
                     }
@@ -1132,9 +1840,33 @@ impl Manager {
                     log::trace!("{dev_name} ({dev_sysname})  is a real device -{dev_path}");
                 }
 
+                if let Some(address) = device.bluetooth_address() {
+                    self.bluetooth_addresses.insert(id.clone(), address);
+                }
+
+                // Multi-port adapters are split into a source device for each
+                // port, so each port can be used by a different composite device.
+                if let Some(ports) = hidraw::get_ports(&device) {
+                    log::debug!(
+                        "Adding {ports} ports of {dev_name} ({dev_sysname}) as source devices"
+                    );
+                    self.multi_port_devices.insert(id.clone(), ports);
+                    for port in 1..=ports {
+                        let device = device.clone().with_port(port);
+                        let port_id = device.get_id();
+                        let path = hidraw::get_dbus_path(sys_name.clone());
+                        self.source_device_dbus_paths.insert(port_id.clone(), path);
+                        if let Err(e) = self.attach_source_device(port_id.clone(), device).await {
+                            log::error!("Failed to add source device {port_id}: {e:?}");
+                        }
+                    }
+                    log::debug!("Finished adding hidraw device {id}");
+                    return Ok(());
+                }
+
                 // Signal that a source device was added
                 log::debug!("Spawing task to add source device: {id}");
-                self.on_source_device_added(id.clone(), device).await?;
+                self.attach_source_device(id.clone(), device).await?;
                 log::debug!("Finished adding hidraw device {id}");
             }
 
@@ -1253,6 +1985,20 @@ impl Manager {
         }
         log::debug!("Device ID: {id}");
 
+        // Remove the source device of each port of multi-port adapters
+        if let Some(ports) = self.multi_port_devices.remove(&id) {
+            self.source_device_dbus_paths.remove(&id);
+            for port in 1..=ports {
+                let device = device.clone().with_port(port);
+                let port_id = device.get_id();
+                if let Err(e) = self.on_source_device_removed(device, port_id.clone()).await {
+                    log::debug!("Failed to remove source device {port_id}: {e:?}");
+                }
+                self.source_device_dbus_paths.remove(&port_id);
+            }
+            return Ok(());
+        }
+
         // Signal that a source device was removed
         self.on_source_device_removed(device, id).await?;
 
@@ -1338,6 +2084,81 @@ impl Manager {
         })
     }
 
+    /// Watch for changes in hardware platform state (e.g. tablet mode, lid, dock)
+    fn watch_platform_state(
+        cmd_tx: mpsc::Sender<ManagerCommand>,
+    ) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + std::marker::Send + Sync>>> {
+        task::spawn_blocking(move || {
+            log::info!("Started platform state watcher thread");
+            platform::watch(cmd_tx)
+        })
+    }
+
+    /// Watch for changes to the game focused in gamescope sessions
+    fn watch_focused_app(
+        cmd_tx: mpsc::Sender<ManagerCommand>,
+    ) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + std::marker::Send + Sync>>> {
+        task::spawn_blocking(move || {
+            log::info!("Started focused app watcher thread");
+            gamescope::watch(cmd_tx)
+        })
+    }
+
+    /// Watch the profile directories for changes and notify the manager when
+    /// profiles are added, modified, or removed.
+    async fn watch_profiles(cmd_tx: mpsc::Sender<ManagerCommand>) {
+        let (watcher_tx, mut watcher_rx) = mpsc::channel(BUFFER_SIZE);
+        let mask = WatchMask::CREATE
+            | WatchMask::DELETE
+            | WatchMask::CLOSE_WRITE
+            | WatchMask::MOVED_TO
+            | WatchMask::MOVED_FROM;
+        for path in get_profiles_paths() {
+            if !path.exists() {
+                continue;
+            }
+            let tx = watcher_tx.clone();
+            tokio::task::spawn_blocking(move || {
+                log::info!("Started profile watcher thread for {path:?}");
+                watcher::watch_with_mask(path.display().to_string(), mask, tx)
+            });
+        }
+        drop(watcher_tx);
+
+        while let Some(event) = watcher_rx.recv().await {
+            let name = match event {
+                WatchEvent::Create { name, .. } => name,
+                WatchEvent::Modify { name, .. } => name,
+                WatchEvent::Delete { name, .. } => name,
+            };
+            if !name.ends_with(".yaml") {
+                continue;
+            }
+            log::debug!("Profile changed: {name}");
+            if let Err(e) = cmd_tx.send(ManagerCommand::ProfilesChanged).await {
+                log::error!("Failed to send profiles changed command: {e:?}");
+                break;
+            }
+        }
+    }
+
+    /// Reload the list of available profiles and signal DBus clients that
+    /// the list has changed.
+    async fn on_profiles_changed(&mut self) -> Result<(), Box<dyn Error>> {
+        let task = task::spawn_blocking(|| list_profiles(&get_profiles_paths()));
+        let profiles = task.await?;
+        if profiles == self.profiles {
+            return Ok(());
+        }
+        self.profiles = profiles;
+
+        let manager_path = format!("{}/Manager", BUS_PREFIX);
+        let ctxt = SignalContext::new(&self.dbus, manager_path)?;
+        ManagerInterface::profiles_changed(&ctxt).await?;
+
+        Ok(())
+    }
+
     /// Watch for appearance and disappearence of devices is /dev and associate the corresponding udev device
     async fn watch_devnodes(
         cmd_tx: mpsc::Sender<ManagerCommand>,
@@ -1579,6 +2400,22 @@ impl Manager {
         })
     }
 
+    /// Listen for connections from per-user session instances if the session
+    /// broker is enabled
+    async fn listen_on_session_broker(
+        enabled: bool,
+        dbus: Connection,
+        tx: mpsc::Sender<ManagerCommand>,
+    ) {
+        if !enabled {
+            return;
+        }
+        let broker = SessionBroker::new(dbus, tx);
+        if let Err(e) = broker.run().await {
+            log::error!("Session broker stopped: {e:?}");
+        }
+    }
+
     async fn add_device_to_composite_device(
         &self,
         device: UdevDevice,
@@ -1588,3 +2425,21 @@ impl Manager {
         Ok(())
     }
 }
+
+/// Returns the udev device of the source device with the given device path
+/// (e.g. "/dev/input/event3" or "/dev/hidraw0")
+fn find_source_device(path: &str) -> Result<UdevDevice, Box<dyn Error>> {
+    let Some(name) = path.strip_prefix("/dev/") else {
+        return Err(format!("Invalid source device path: {path}").into());
+    };
+    let (subsystem, sysname) = match name.strip_prefix("input/") {
+        Some(sysname) => ("input".to_string(), sysname),
+        None => (UdevDevice::from_devnode(DEV_PATH, name).subsystem(), name),
+    };
+    if subsystem.is_empty() {
+        return Err(format!("Unsupported source device path: {path}").into());
+    }
+    let device = ::udev::Device::from_subsystem_sysname(subsystem, sysname.to_string())
+        .map_err(|e| format!("Source device {path} not found: {e}"))?;
+    Ok(device.into())
+}
@@ -1,9 +1,20 @@
 //pub mod device;
 pub mod capability;
+#[cfg(test)]
+pub mod capability_test;
 pub mod composite_device;
 pub mod event;
 pub mod manager;
 pub mod output_capability;
 pub mod output_event;
+pub mod player_indicator;
+#[cfg(test)]
+pub mod player_indicator_test;
 pub mod source;
 pub mod target;
+pub mod watchdog;
+#[cfg(test)]
+pub mod watchdog_test;
+pub mod xtest;
+#[cfg(test)]
+pub mod xtest_test;
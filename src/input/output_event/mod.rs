@@ -7,7 +7,7 @@ use crate::drivers::{
     steam_deck::hid_report::{PackedHapticReport, PackedRumbleReport, PadSide},
 };
 
-use super::output_capability::{Haptic, OutputCapability};
+use super::output_capability::{Haptic, OutputCapability, LED};
 
 /// Output events are events that flow from target devices back to source devices
 #[derive(Debug, Clone)]
@@ -21,7 +21,7 @@ pub enum OutputEvent {
 
 impl OutputEvent {
     /// Returns the capability of the output event
-    fn as_capability(&self) -> Vec<OutputCapability> {
+    pub fn as_capability(&self) -> Vec<OutputCapability> {
         match self {
             OutputEvent::Evdev(event) => match event.destructure() {
                 evdev::EventSummary::Synchronization(_, _, _) => {
@@ -54,11 +54,17 @@ impl OutputEvent {
                 UinputOutputEvent::FFErase(_) => vec![OutputCapability::ForceFeedbackErase],
             },
             OutputEvent::DualSense(report) => {
+                let mut caps = Vec::new();
                 if report.use_rumble_not_haptics {
-                    vec![OutputCapability::ForceFeedback]
-                } else {
-                    vec![OutputCapability::NotImplemented]
+                    caps.push(OutputCapability::ForceFeedback);
+                }
+                if report.allow_led_color {
+                    caps.push(OutputCapability::LED(LED::Color));
                 }
+                if caps.is_empty() {
+                    caps.push(OutputCapability::NotImplemented);
+                }
+                caps
             }
             OutputEvent::SteamDeckHaptics(packed_haptic_report) => {
                 match packed_haptic_report.side {
@@ -73,6 +79,16 @@ impl OutputEvent {
             OutputEvent::SteamDeckRumble(_) => vec![OutputCapability::ForceFeedback],
         }
     }
+
+    /// Returns the RGB color of the output event if it sets an LED color
+    pub fn led_color(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            OutputEvent::DualSense(report) if report.allow_led_color => {
+                Some((report.led_red, report.led_green, report.led_blue))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,159 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Number of player LEDs on a DualSense controller
+pub const DUALSENSE_PLAYER_LIGHTS: usize = 5;
+
+/// Lightbar colors used for each player, following the colors used by
+/// PlayStation consoles.
+const COLOR_PRESETS: &[(u8, u8, u8)] = &[
+    (0, 0, 255),     // Blue
+    (255, 0, 0),     // Red
+    (0, 255, 0),     // Green
+    (255, 0, 255),   // Pink
+    (255, 128, 0),   // Orange
+    (0, 255, 255),   // Teal
+    (255, 255, 255), // White
+];
+
+/// Returns the DualSense player LEDs to light for the given player index,
+/// from left to right. Players are numbered starting at 1 and the patterns
+/// match the ones used by PlayStation consoles.
+pub fn dualsense_player_lights(player: Option<u8>) -> [bool; DUALSENSE_PLAYER_LIGHTS] {
+    match player {
+        None | Some(0) => [false; 5],
+        Some(1) => [false, false, true, false, false],
+        Some(2) => [false, true, false, true, false],
+        Some(3) => [true, false, true, false, true],
+        Some(4) => [true, true, false, true, true],
+        Some(_) => [true; 5],
+    }
+}
+
+/// Returns the command to write to the LED ring of an Xbox 360 controller
+/// (handled by the xpad kernel driver) for the given player index. The ring
+/// only has four quadrants, so players above four wrap around.
+pub fn xpad_led_command(player: Option<u8>) -> u8 {
+    match player {
+        None | Some(0) => 0,
+        // Commands 6-9 turn on the quadrant for players 1-4
+        Some(player) => 6 + (player - 1) % 4,
+    }
+}
+
+/// Returns the RGB color preset for the given player index
+pub fn color_preset(player: Option<u8>) -> (u8, u8, u8) {
+    match player {
+        None | Some(0) => (0, 0, 0),
+        Some(player) => COLOR_PRESETS[(player as usize - 1) % COLOR_PRESETS.len()],
+    }
+}
+
+/// Kind of LED class device that can indicate the player index
+#[derive(Debug, Clone, PartialEq)]
+enum IndicatorLed {
+    /// LED ring of an Xbox 360 controller
+    Xpad(PathBuf),
+    /// Single player LED with the given position, starting at 1 (e.g.
+    /// "input5:white:player-2" from the hid-playstation driver)
+    Player(PathBuf, usize),
+    /// Multicolor LED that is set to the color preset of the player
+    Color(PathBuf),
+}
+
+/// Shows the player index using the LED class devices in "/sys/class/leds"
+/// that belong to a source device. This supports the player LEDs and RGB
+/// lightbars of controllers handled by kernel drivers.
+#[derive(Debug, Clone, Default)]
+pub struct SysfsPlayerIndicator {
+    leds: Vec<IndicatorLed>,
+}
+
+impl SysfsPlayerIndicator {
+    /// Find the LEDs of the device that owns the input event device with the
+    /// given syspath.
+    pub fn from_input_syspath(syspath: &str) -> Self {
+        // LEDs are registered on the device that owns the input device
+        // (e.g. "/sys/devices/.../0003:054C:0CE6.0001/input/input5/event5")
+        let Some(parent) = Path::new(syspath).ancestors().nth(3) else {
+            return Self::default();
+        };
+        Self::from_leds_dir(parent.join("leds").as_path())
+    }
+
+    /// Find the LEDs that can indicate the player index in the given directory
+    pub fn from_leds_dir(path: &Path) -> Self {
+        let Ok(entries) = fs::read_dir(path) else {
+            return Self::default();
+        };
+        let mut leds = Vec::new();
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if name.starts_with("xpad") {
+                leds.push(IndicatorLed::Xpad(path));
+            } else if let Some(position) = player_led_position(name.as_str()) {
+                leds.push(IndicatorLed::Player(path, position));
+            } else if path.join("multi_intensity").exists() {
+                leds.push(IndicatorLed::Color(path));
+            }
+        }
+        log::debug!("Found player indicator LEDs: {leds:?}");
+        Self { leds }
+    }
+
+    /// Returns true if no LEDs can indicate the player index
+    pub fn is_empty(&self) -> bool {
+        self.leds.is_empty()
+    }
+
+    /// Show the given player index on all LEDs
+    pub fn set_player(&self, player: Option<u8>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let lights = dualsense_player_lights(player);
+        for led in self.leds.iter() {
+            match led {
+                IndicatorLed::Xpad(path) => {
+                    let command = xpad_led_command(player);
+                    fs::write(path.join("brightness"), command.to_string())?;
+                }
+                IndicatorLed::Player(path, position) => {
+                    let on = lights.get(position - 1).copied().unwrap_or_default();
+                    fs::write(path.join("brightness"), if on { "1" } else { "0" })?;
+                }
+                IndicatorLed::Color(path) => {
+                    // Intensities are written in the order of the LED's color
+                    // indices
+                    let (red, green, blue) = color_preset(player);
+                    let index = fs::read_to_string(path.join("multi_index"))?;
+                    let intensity: Vec<String> = index
+                        .split_whitespace()
+                        .map(|channel| match channel {
+                            "red" => red,
+                            "green" => green,
+                            "blue" => blue,
+                            _ => 0,
+                        })
+                        .map(|value| value.to_string())
+                        .collect();
+                    fs::write(path.join("multi_intensity"), intensity.join(" "))?;
+                    let max_brightness = fs::read_to_string(path.join("max_brightness"))?;
+                    fs::write(path.join("brightness"), max_brightness.trim())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the position of the player LED with the given LED class name
+/// (e.g. "input5:white:player-2" or "0005:057E:2009.0001:green:player-1")
+fn player_led_position(name: &str) -> Option<usize> {
+    let (_, position) = name.rsplit_once(":player-")?;
+    let position = position.parse().ok()?;
+    (1..=DUALSENSE_PLAYER_LIGHTS)
+        .contains(&position)
+        .then_some(position)
+}
@@ -0,0 +1,62 @@
+use std::fs;
+
+use super::player_indicator::{
+    color_preset, dualsense_player_lights, xpad_led_command, SysfsPlayerIndicator,
+};
+
+#[test]
+fn test_dualsense_player_lights() {
+    assert_eq!(dualsense_player_lights(None), [false; 5]);
+    assert_eq!(
+        dualsense_player_lights(Some(1)),
+        [false, false, true, false, false]
+    );
+    assert_eq!(
+        dualsense_player_lights(Some(4)),
+        [true, true, false, true, true]
+    );
+    assert_eq!(dualsense_player_lights(Some(9)), [true; 5]);
+}
+
+#[test]
+fn test_xpad_led_command() {
+    assert_eq!(xpad_led_command(None), 0);
+    assert_eq!(xpad_led_command(Some(1)), 6);
+    assert_eq!(xpad_led_command(Some(4)), 9);
+    assert_eq!(xpad_led_command(Some(5)), 6);
+}
+
+#[test]
+fn test_color_preset() {
+    assert_eq!(color_preset(None), (0, 0, 0));
+    assert_eq!(color_preset(Some(1)), (0, 0, 255));
+    assert_eq!(color_preset(Some(2)), (255, 0, 0));
+    assert_eq!(color_preset(Some(8)), color_preset(Some(1)));
+}
+
+#[test]
+fn test_sysfs_player_indicator() {
+    let dir = std::env::temp_dir().join(format!("inputplumber-leds-{}", std::process::id()));
+    let player_1 = dir.join("input5:white:player-1");
+    let player_3 = dir.join("input5:white:player-3");
+    let rgb = dir.join("input5:rgb:indicator");
+    for led in [&player_1, &player_3, &rgb] {
+        fs::create_dir_all(led).unwrap();
+    }
+    fs::write(rgb.join("multi_index"), "red green blue\n").unwrap();
+    fs::write(rgb.join("multi_intensity"), "0 0 0\n").unwrap();
+    fs::write(rgb.join("max_brightness"), "255\n").unwrap();
+
+    let indicator = SysfsPlayerIndicator::from_leds_dir(dir.as_path());
+    assert!(!indicator.is_empty());
+    indicator.set_player(Some(1)).unwrap();
+
+    let brightness = |path: &std::path::Path| fs::read_to_string(path.join("brightness")).unwrap();
+    assert_eq!(brightness(&player_1), "0");
+    assert_eq!(brightness(&player_3), "1");
+    assert_eq!(brightness(&rgb), "255");
+    let intensity = fs::read_to_string(rgb.join("multi_intensity")).unwrap();
+    assert_eq!(intensity, "0 0 255");
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -101,6 +101,13 @@ impl SourceDeviceClient {
         }
     }
 
+    /// Show the given player index on the source device. A value of [None]
+    /// clears the player indicator.
+    pub async fn set_player_index(&self, index: Option<u8>) -> Result<(), ClientError> {
+        self.tx.send(SourceCommand::SetPlayerIndex(index)).await?;
+        Ok(())
+    }
+
     /// Stop the source device.
     pub async fn stop(&self) -> Result<(), ClientError> {
         self.tx.send(SourceCommand::Stop).await?;
@@ -15,5 +15,6 @@ pub enum SourceCommand {
     ),
     UpdateEffect(i16, FFEffectData),
     EraseEffect(i16, Sender<Result<(), Box<dyn Error + Send + Sync>>>),
+    SetPlayerIndex(Option<u8>),
     Stop,
 }
@@ -1,12 +1,16 @@
 pub mod blocked;
 pub mod gamepad;
+pub mod long_press;
+#[cfg(test)]
+pub mod long_press_test;
+pub mod wheel;
 
 use std::{collections::HashMap, error::Error, time::Duration};
 
-use evdev::{Device, EventType};
+use evdev::{AbsInfo, AbsoluteAxisCode, Device, EventType};
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
+    config, constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
     udev::device::UdevDevice,
 };
 
@@ -78,6 +82,7 @@ impl EventDevice {
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
         is_blocked: bool,
+        config: Option<config::Evdev>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = EventDevice::get_driver_type(&device_info, is_blocked);
 
@@ -93,13 +98,22 @@ impl EventDevice {
                 Ok(Self::Blocked(source_device))
             }
             DriverType::Gamepad => {
-                let device = GamepadEventDevice::new(device_info.clone())?;
+                let long_press = config.and_then(|config| config.long_press);
+                let device = GamepadEventDevice::new(device_info.clone(), long_press)?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
                 Ok(Self::Gamepad(source_device))
             }
         }
     }
 
+    /// Returns the absolute axis ranges reported by the device hardware
+    pub fn get_abs_info(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        match self {
+            EventDevice::Blocked(source_driver) => source_driver.get_abs_info(),
+            EventDevice::Gamepad(source_driver) => source_driver.get_abs_info(),
+        }
+    }
+
     /// Return the driver type for the given vendor and product
     fn get_driver_type(device: &UdevDevice, is_blocked: bool) -> DriverType {
         // TODO: add implmentations for other classes of evdev devices (e.g.
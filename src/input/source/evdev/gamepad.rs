@@ -1,25 +1,36 @@
 use std::fmt::Debug;
-use std::{collections::HashMap, error::Error, os::fd::AsRawFd};
+use std::{collections::HashMap, error::Error, os::fd::AsRawFd, time::Instant};
 
 use evdev::{
     AbsInfo, AbsoluteAxisCode, Device, EventType, FFEffect, FFEffectData, FFEffectKind, FFReplay,
-    FFTrigger, InputEvent,
+    FFTrigger, InputEvent, SynchronizationCode,
 };
 use nix::fcntl::{FcntlArg, OFlag};
 use packed_struct::types::SizedInteger;
 
 use crate::drivers::steam_deck::hid_report::PackedRumbleReport;
 use crate::{
+    config::EvdevLongPress,
     drivers::dualsense::hid_report::SetStatePackedOutputData,
     input::{
-        capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
-        event::{evdev::EvdevEvent, native::NativeEvent},
+        capability::{Capability, Gamepad},
+        event::{
+            evdev::EvdevEvent,
+            native::{hat_buttons, NativeEvent},
+            value::InputValue,
+        },
         output_event::OutputEvent,
+        player_indicator::SysfsPlayerIndicator,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
     udev::device::UdevDevice,
 };
 
+use super::{
+    long_press::LongPressFilter,
+    wheel::{find_layout, WheelLayout},
+};
+
 /// Source device implementation for evdev gamepads
 pub struct GamepadEventDevice {
     device: Device,
@@ -28,12 +39,21 @@ pub struct GamepadEventDevice {
     ff_effects_dualsense: Option<i16>,
     ff_effects_deck: Option<i16>,
     hat_state: HashMap<AbsoluteAxisCode, i32>,
+    wheel_layout: Option<&'static WheelLayout>,
+    player_indicator: SysfsPlayerIndicator,
+    long_press: Option<LongPressFilter>,
 }
 
 impl GamepadEventDevice {
     /// Create a new [Gamepad] source device from the given udev info
-    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new(
+        device_info: UdevDevice,
+        long_press: Option<EvdevLongPress>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let path = device_info.devnode();
+        let wheel_layout = find_layout(device_info.id_vendor(), device_info.id_product());
+        let player_indicator = SysfsPlayerIndicator::from_input_syspath(&device_info.syspath());
+        let long_press = long_press.as_ref().map(LongPressFilter::new);
         log::debug!("Opening device at: {}", path);
         let mut device = Device::open(path.clone())?;
         device.grab()?;
@@ -59,6 +79,9 @@ impl GamepadEventDevice {
             ff_effects_dualsense: None,
             ff_effects_deck: None,
             hat_state: HashMap::new(),
+            wheel_layout,
+            player_indicator,
+            long_press,
         })
     }
 
@@ -66,8 +89,13 @@ impl GamepadEventDevice {
     fn translate(&mut self, event: InputEvent) -> Option<NativeEvent> {
         log::trace!("Received event: {:?}", event);
 
-        // Block Sync events, we create these at the target anyway and they waste processing.
+        // Sync reports end an input frame and are passed on so the events of
+        // the frame are written to target devices together. Other Sync events
+        // are created at the target anyway and they waste processing.
         if event.event_type() == EventType::SYNCHRONIZATION {
+            if event.code() == SynchronizationCode::SYN_REPORT.0 {
+                return Some(NativeEvent::new(Capability::Sync, InputValue::None));
+            }
             log::trace!("Holding Sync event from propagating through the processing stack.");
             return None;
         }
@@ -80,11 +108,18 @@ impl GamepadEventDevice {
             None
         };
 
+        // Driving wheels translate their axes and shifter into wheel capabilities
+        if let Some(layout) = self.wheel_layout {
+            if let Some(native_event) = layout.translate(&event, abs_info) {
+                return Some(native_event);
+            }
+        }
+
         let state = if event.event_type() == EventType::ABSOLUTE {
             let axis = AbsoluteAxisCode(event.code());
 
-            let state = match axis {
-                AbsoluteAxisCode::ABS_HAT0X | AbsoluteAxisCode::ABS_HAT0Y => {
+            let state = match hat_buttons(axis) {
+                Some(_) => {
                     let value = event.value();
                     let last_value = *self.hat_state.get(&axis).unwrap_or(&0);
                     self.hat_state
@@ -113,6 +148,21 @@ impl GamepadEventDevice {
         Some(native_event)
     }
 
+    /// Split chassis button presses in the given events into short and long
+    /// presses and add any long presses that are now due.
+    fn poll_long_press(&mut self, events: Vec<NativeEvent>) -> Vec<NativeEvent> {
+        let Some(long_press) = self.long_press.as_mut() else {
+            return events;
+        };
+        let now = Instant::now();
+        let mut filtered: Vec<NativeEvent> = events
+            .into_iter()
+            .flat_map(|event| long_press.filter(event, now))
+            .collect();
+        filtered.extend(long_press.poll(now));
+        filtered
+    }
+
     /// Process dualsense force feedback output reports
     fn process_dualsense_ff(
         &mut self,
@@ -252,8 +302,8 @@ impl SourceInputDevice for GamepadEventDevice {
             let events = match result {
                 Ok(events) => events,
                 Err(err) => match err.kind() {
-                    // Do nothing if this would block
-                    std::io::ErrorKind::WouldBlock => return Ok(vec![]),
+                    // Only check for long presses if this would block
+                    std::io::ErrorKind::WouldBlock => return Ok(self.poll_long_press(vec![])),
                     _ => {
                         log::trace!("Failed to fetch events: {:?}", err);
                         let msg = format!("Failed to fetch events: {:?}", err);
@@ -272,7 +322,7 @@ impl SourceInputDevice for GamepadEventDevice {
             .filter_map(|e| self.translate(e))
             .collect();
 
-        Ok(native_events)
+        Ok(self.poll_long_press(native_events))
     }
 
     /// Returns the possible input events this device is capable of emitting
@@ -291,6 +341,13 @@ impl SourceInputDevice for GamepadEventDevice {
                         continue;
                     };
                     for key in keys.iter() {
+                        if let Some(cap) = self
+                            .wheel_layout
+                            .and_then(|layout| layout.capability(event, key.0))
+                        {
+                            capabilities.push(cap);
+                            continue;
+                        }
                         let input_event = InputEvent::new(event.0, key.0, 0);
                         let evdev_event = EvdevEvent::from(input_event);
                         let cap = evdev_event.as_capability();
@@ -313,23 +370,22 @@ impl SourceInputDevice for GamepadEventDevice {
                         continue;
                     };
                     for axis in abs.iter() {
+                        if let Some(cap) = self
+                            .wheel_layout
+                            .and_then(|layout| layout.capability(event, axis.0))
+                        {
+                            capabilities.push(cap);
+                            continue;
+                        }
+                        // Hat switches are exposed as directional buttons
+                        if let Some((negative, positive)) = hat_buttons(axis) {
+                            capabilities.push(Capability::Gamepad(Gamepad::Button(negative)));
+                            capabilities.push(Capability::Gamepad(Gamepad::Button(positive)));
+                            continue;
+                        }
                         let input_event = InputEvent::new(event.0, axis.0, 0);
                         let evdev_event = EvdevEvent::from(input_event);
                         let cap = evdev_event.as_capability();
-                        if cap == Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0)) {
-                            capabilities
-                                .push(Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)));
-                            capabilities.push(Capability::Gamepad(Gamepad::Button(
-                                GamepadButton::DPadDown,
-                            )));
-                            capabilities.push(Capability::Gamepad(Gamepad::Button(
-                                GamepadButton::DPadLeft,
-                            )));
-                            capabilities.push(Capability::Gamepad(Gamepad::Button(
-                                GamepadButton::DPadRight,
-                            )));
-                            continue;
-                        }
                         capabilities.push(cap);
                     }
                 }
@@ -346,8 +402,18 @@ impl SourceInputDevice for GamepadEventDevice {
             }
         }
 
+        // Chassis buttons can also emit long presses
+        if let Some(long_press) = self.long_press.as_ref() {
+            capabilities.extend(long_press.capabilities());
+        }
+
         Ok(capabilities)
     }
+
+    /// Returns the absolute axis ranges reported by the device
+    fn get_abs_info(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        self.axes_info.clone()
+    }
 }
 
 impl SourceOutputDevice for GamepadEventDevice {
@@ -445,6 +511,17 @@ impl SourceOutputDevice for GamepadEventDevice {
         self.ff_effects.remove(&effect_id);
         Ok(())
     }
+
+    /// Show the player index using the LEDs exposed by the kernel driver
+    fn set_player_index(&mut self, index: Option<u8>) -> Result<(), OutputError> {
+        if self.player_indicator.is_empty() {
+            log::trace!("Device does not have a player indicator");
+            return Ok(());
+        }
+        log::debug!("Setting player index to {index:?}");
+        self.player_indicator.set_player(index)?;
+        Ok(())
+    }
 }
 
 impl Debug for GamepadEventDevice {
@@ -454,6 +531,7 @@ impl Debug for GamepadEventDevice {
             .field("ff_effects", &self.ff_effects)
             .field("ff_effects_dualsense", &self.ff_effects_dualsense)
             .field("hat_state", &self.hat_state)
+            .field("wheel_layout", &self.wheel_layout)
             .finish()
     }
 }
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::EvdevLongPress,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        event::{native::NativeEvent, value::InputValue},
+    },
+};
+
+/// Default time a button must be held to be a long press
+const DEFAULT_THRESHOLD: Duration = Duration::from_millis(800);
+
+/// Returns the capability emitted for a long press of the given chassis key
+fn long_press_capability(key: &Keyboard) -> Option<Capability> {
+    let button = match key {
+        Keyboard::KeyPower => GamepadButton::PowerLongPress,
+        Keyboard::KeyVolumeDown => GamepadButton::VolumeDownLongPress,
+        Keyboard::KeyVolumeUp => GamepadButton::VolumeUpLongPress,
+        _ => return None,
+    };
+    Some(Capability::Gamepad(Gamepad::Button(button)))
+}
+
+/// State of a chassis key that is being held
+#[derive(Debug, Clone)]
+struct HeldKey {
+    pressed_at: Instant,
+    /// Whether the long press capability was already emitted
+    is_long_press: bool,
+}
+
+/// The [LongPressFilter] splits presses of chassis buttons (e.g. power and
+/// volume) into short and long presses. Presses are held back until the
+/// button is either released, which passes the short press through as a
+/// press and release of the original key, or held past the threshold, which
+/// emits the long press capability (e.g. "VolumeUpLongPress") until the
+/// button is released.
+#[derive(Debug, Clone)]
+pub struct LongPressFilter {
+    threshold: Duration,
+    keys: Vec<Keyboard>,
+    held: HashMap<Keyboard, HeldKey>,
+}
+
+impl LongPressFilter {
+    /// Create a new filter from the given config
+    pub fn new(config: &EvdevLongPress) -> Self {
+        let keys = match config.keys.as_ref() {
+            Some(keys) => keys
+                .iter()
+                .filter_map(|key| match Keyboard::from_str(key) {
+                    Ok(key) if long_press_capability(&key).is_some() => Some(key),
+                    _ => {
+                        log::warn!("Long press is not supported for key: {key}");
+                        None
+                    }
+                })
+                .collect(),
+            None => vec![
+                Keyboard::KeyPower,
+                Keyboard::KeyVolumeDown,
+                Keyboard::KeyVolumeUp,
+            ],
+        };
+        let threshold = config
+            .threshold_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_THRESHOLD);
+
+        Self {
+            threshold,
+            keys,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Returns the long press capabilities this filter can emit
+    pub fn capabilities(&self) -> Vec<Capability> {
+        self.keys.iter().filter_map(long_press_capability).collect()
+    }
+
+    /// Filter the given event at the given time. Events for keys that are not
+    /// split are passed through unchanged.
+    pub fn filter(&mut self, event: NativeEvent, now: Instant) -> Vec<NativeEvent> {
+        let Capability::Keyboard(key) = event.as_capability() else {
+            return vec![event];
+        };
+        if !self.keys.contains(&key) {
+            return vec![event];
+        }
+
+        if event.pressed() {
+            // Ignore key repeats while the key is being held
+            self.held.entry(key).or_insert(HeldKey {
+                pressed_at: now,
+                is_long_press: false,
+            });
+            return vec![];
+        }
+
+        let Some(held) = self.held.remove(&key) else {
+            return vec![];
+        };
+        if held.is_long_press {
+            let Some(cap) = long_press_capability(&key) else {
+                return vec![];
+            };
+            return vec![NativeEvent::new(cap, InputValue::Bool(false))];
+        }
+
+        // Pass the short press through
+        let cap = Capability::Keyboard(key);
+        vec![
+            NativeEvent::new(cap.clone(), InputValue::Bool(true)),
+            NativeEvent::new(cap, InputValue::Bool(false)),
+        ]
+    }
+
+    /// Returns the long press events for keys that have been held past the
+    /// threshold at the given time.
+    pub fn poll(&mut self, now: Instant) -> Vec<NativeEvent> {
+        let mut events = Vec::new();
+        for (key, held) in self.held.iter_mut() {
+            if held.is_long_press || now.duration_since(held.pressed_at) < self.threshold {
+                continue;
+            }
+            held.is_long_press = true;
+            let Some(cap) = long_press_capability(key) else {
+                continue;
+            };
+            log::debug!("Detected long press of {key}");
+            events.push(NativeEvent::new(cap, InputValue::Bool(true)));
+        }
+        events
+    }
+}
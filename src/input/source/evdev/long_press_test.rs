@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::EvdevLongPress,
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        composite_device::test_events::key_event,
+        event::native::NativeEvent,
+    },
+};
+
+use super::long_press::LongPressFilter;
+
+fn summary(events: Vec<NativeEvent>) -> Vec<(Capability, bool)> {
+    events
+        .into_iter()
+        .map(|event| (event.as_capability(), event.pressed()))
+        .collect()
+}
+
+#[test]
+fn test_short_press() {
+    let config = EvdevLongPress {
+        keys: None,
+        threshold_ms: Some(500),
+    };
+    let mut filter = LongPressFilter::new(&config);
+    let start = Instant::now();
+
+    // The press should be held back until the key is released
+    let events = filter.filter(key_event(Keyboard::KeyVolumeUp, true), start);
+    assert!(events.is_empty());
+    assert!(filter.poll(start + Duration::from_millis(100)).is_empty());
+
+    let events = filter.filter(
+        key_event(Keyboard::KeyVolumeUp, false),
+        start + Duration::from_millis(200),
+    );
+    let key = Capability::Keyboard(Keyboard::KeyVolumeUp);
+    assert_eq!(summary(events), vec![(key.clone(), true), (key, false)]);
+
+    // Other keys should pass through unchanged
+    let events = filter.filter(key_event(Keyboard::KeyA, true), start);
+    assert_eq!(
+        summary(events),
+        vec![(Capability::Keyboard(Keyboard::KeyA), true)]
+    );
+}
+
+#[test]
+fn test_long_press() {
+    let config = EvdevLongPress {
+        keys: Some(vec!["KeyPower".to_string()]),
+        threshold_ms: Some(500),
+    };
+    let mut filter = LongPressFilter::new(&config);
+    let long_press = Capability::Gamepad(Gamepad::Button(GamepadButton::PowerLongPress));
+    assert_eq!(filter.capabilities(), vec![long_press.clone()]);
+    let start = Instant::now();
+
+    filter.filter(key_event(Keyboard::KeyPower, true), start);
+    let events = filter.poll(start + Duration::from_millis(500));
+    assert_eq!(summary(events), vec![(long_press.clone(), true)]);
+
+    // The long press should only be emitted once
+    assert!(filter.poll(start + Duration::from_millis(600)).is_empty());
+
+    let events = filter.filter(
+        key_event(Keyboard::KeyPower, false),
+        start + Duration::from_millis(700),
+    );
+    assert_eq!(summary(events), vec![(long_press, false)]);
+
+    // Keys that are not configured should pass through unchanged
+    let events = filter.filter(key_event(Keyboard::KeyVolumeUp, true), start);
+    assert_eq!(
+        summary(events),
+        vec![(Capability::Keyboard(Keyboard::KeyVolumeUp), true)]
+    );
+}
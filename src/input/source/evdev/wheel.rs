@@ -0,0 +1,131 @@
+use evdev::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    event::{native::NativeEvent, value::InputValue},
+};
+
+/// Describes how the axes and buttons of a driving wheel are laid out, so they
+/// can be translated into wheel capabilities instead of generic gamepad ones.
+#[derive(Debug)]
+pub struct WheelLayout {
+    /// Axis used for steering
+    pub steering: AbsoluteAxisCode,
+    /// Axis used for the throttle (gas) pedal
+    pub throttle: AbsoluteAxisCode,
+    /// Axis used for the brake pedal
+    pub brake: AbsoluteAxisCode,
+    /// Axis used for the clutch pedal, if the wheel has one
+    pub clutch: Option<AbsoluteAxisCode>,
+    /// Whether pedals report their maximum value when released
+    pub inverted_pedals: bool,
+    /// Keys reported by the H-shifter for each gear
+    pub gears: &'static [(KeyCode, GamepadButton)],
+}
+
+/// Layout used by Logitech wheels with the hid-logitech driver
+const LOGITECH_LAYOUT: WheelLayout = WheelLayout {
+    steering: AbsoluteAxisCode::ABS_X,
+    throttle: AbsoluteAxisCode::ABS_Z,
+    brake: AbsoluteAxisCode::ABS_RZ,
+    clutch: Some(AbsoluteAxisCode::ABS_Y),
+    inverted_pedals: true,
+    gears: &[
+        (KeyCode::BTN_BASE, GamepadButton::ShifterGear1),
+        (KeyCode::BTN_BASE2, GamepadButton::ShifterGear2),
+        (KeyCode::BTN_BASE3, GamepadButton::ShifterGear3),
+        (KeyCode::BTN_BASE4, GamepadButton::ShifterGear4),
+        (KeyCode::BTN_TRIGGER_HAPPY1, GamepadButton::ShifterGear5),
+        (KeyCode::BTN_TRIGGER_HAPPY2, GamepadButton::ShifterGear6),
+        (KeyCode::BTN_TRIGGER_HAPPY3, GamepadButton::ShifterReverse),
+    ],
+};
+
+/// Known driving wheels identified by vendor and product id
+const WHEELS: &[(u16, u16, &WheelLayout)] = &[
+    // Logitech G25
+    (0x046d, 0xc299, &LOGITECH_LAYOUT),
+    // Logitech Driving Force GT
+    (0x046d, 0xc29a, &LOGITECH_LAYOUT),
+    // Logitech G27
+    (0x046d, 0xc29b, &LOGITECH_LAYOUT),
+    // Logitech G29
+    (0x046d, 0xc24f, &LOGITECH_LAYOUT),
+    // Logitech G920
+    (0x046d, 0xc262, &LOGITECH_LAYOUT),
+    // Logitech G923 (PlayStation)
+    (0x046d, 0xc266, &LOGITECH_LAYOUT),
+    // Logitech G923 (Xbox)
+    (0x046d, 0xc26e, &LOGITECH_LAYOUT),
+];
+
+/// Returns the wheel layout for the device with the given vendor and product
+/// id if it is a known driving wheel.
+pub fn find_layout(vendor_id: u16, product_id: u16) -> Option<&'static WheelLayout> {
+    WHEELS
+        .iter()
+        .find(|(vid, pid, _)| *vid == vendor_id && *pid == product_id)
+        .map(|(_, _, layout)| *layout)
+}
+
+impl WheelLayout {
+    /// Returns the wheel capability for the given event type and code, or
+    /// [None] if the event is not part of the wheel layout.
+    pub fn capability(&self, event_type: EventType, code: u16) -> Option<Capability> {
+        match event_type {
+            EventType::ABSOLUTE => {
+                let axis = AbsoluteAxisCode(code);
+                if axis == self.steering {
+                    return Some(Capability::Gamepad(Gamepad::Axis(GamepadAxis::Steering)));
+                }
+                let trigger = if axis == self.throttle {
+                    GamepadTrigger::Throttle
+                } else if axis == self.brake {
+                    GamepadTrigger::Brake
+                } else if Some(axis) == self.clutch {
+                    GamepadTrigger::Clutch
+                } else {
+                    return None;
+                };
+                Some(Capability::Gamepad(Gamepad::Trigger(trigger)))
+            }
+            EventType::KEY => self
+                .gears
+                .iter()
+                .find(|(key, _)| key.0 == code)
+                .map(|(_, button)| Capability::Gamepad(Gamepad::Button(button.clone()))),
+            _ => None,
+        }
+    }
+
+    /// Translate the given evdev event into a [NativeEvent] if it is part of
+    /// the wheel layout.
+    pub fn translate(&self, event: &InputEvent, abs_info: Option<&AbsInfo>) -> Option<NativeEvent> {
+        let capability = self.capability(event.event_type(), event.code())?;
+        let value = match &capability {
+            Capability::Gamepad(Gamepad::Axis(_)) => {
+                let info = abs_info?;
+                let range = (info.maximum() - info.minimum()).max(1) as f64;
+                let value = (event.value() - info.minimum()) as f64 / range;
+                InputValue::Vector2 {
+                    x: Some(value * 2.0 - 1.0),
+                    y: None,
+                }
+            }
+            Capability::Gamepad(Gamepad::Trigger(_)) => {
+                let info = abs_info?;
+                let range = (info.maximum() - info.minimum()).max(1) as f64;
+                let value = (event.value() - info.minimum()) as f64 / range;
+                let value = if self.inverted_pedals {
+                    1.0 - value
+                } else {
+                    value
+                };
+                InputValue::Float(value.clamp(0.0, 1.0))
+            }
+            _ => InputValue::Bool(event.value() != 0),
+        };
+
+        Some(NativeEvent::new(capability, value))
+    }
+}
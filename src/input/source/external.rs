@@ -0,0 +1,579 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Debug,
+    io::{BufRead, BufReader, Write},
+    os::unix::process::CommandExt,
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TryRecvError, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use evdev::{FFEffectData, FFEffectKind};
+use nix::unistd::User;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::path::get_drivers_paths,
+    input::{
+        capability::Capability,
+        composite_device::{client::CompositeDeviceClient, overload::OverloadQueue},
+        event::{native::NativeEvent, value::InputValue},
+        output_event::OutputEvent,
+    },
+    udev::device::UdevDevice,
+};
+
+use super::{
+    client::SourceDeviceClient, r#virtual::VirtualScriptValue, InputError, OutputError,
+    SourceDeviceCompatible, SourceDriver, SourceInputDevice, SourceOutputDevice,
+};
+
+/// Time to wait for an external driver to send its "hello" message
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+/// User that external drivers are run as
+const DRIVER_USER: &str = "nobody";
+/// Maximum number of input events from the driver that can wait to be polled.
+/// Further events wait in an [OverloadQueue] until there is room again.
+const EVENT_CHANNEL_SIZE: usize = 256;
+/// Maximum number of messages that can wait to be written to the driver
+const HOST_CHANNEL_SIZE: usize = 32;
+/// Time the driver is given to exit after it was asked to stop before it is
+/// killed
+const STOP_TIMEOUT: Duration = Duration::from_millis(500);
+/// Interval at which a stopping driver is checked for having exited
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Message sent by an external driver to InputPlumber. Each message is a
+/// single line of JSON on the stdout of the driver. E.g.
+/// ```json
+/// {"type": "hello", "capabilities": ["Gamepad:Button:South", "Gamepad:Axis:LeftStick"]}
+/// {"type": "event", "capability": "Gamepad:Button:South", "value": true}
+/// {"type": "event", "capability": "Gamepad:Axis:LeftStick", "value": [0.0, -1.0]}
+/// ```
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriverMessage {
+    /// First message of the driver, defining the capabilities of the device
+    Hello { capabilities: Vec<String> },
+    /// Input event emitted by the device
+    Event {
+        /// Capability of the event. E.g. "Gamepad:Button:South"
+        capability: String,
+        value: VirtualScriptValue,
+    },
+}
+
+impl DriverMessage {
+    /// Parse the given line of JSON sent by an external driver
+    pub fn parse(line: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let message = serde_json::from_str(line)?;
+        Ok(message)
+    }
+}
+
+/// Message sent by InputPlumber to an external driver. Each message is
+/// written as a single line of JSON to the stdin of the driver. E.g.
+/// ```json
+/// {"type": "rumble", "strong": 65535, "weak": 0}
+/// {"type": "player_index", "index": 1}
+/// {"type": "stop"}
+/// ```
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostMessage {
+    /// Set the rumble motors to the given magnitudes. A magnitude of 0 stops
+    /// the motor.
+    Rumble { strong: u16, weak: u16 },
+    /// Show the given player index on the device. A value of [None] (null)
+    /// clears the indicator.
+    PlayerIndex { index: Option<u8> },
+    /// The device is being stopped and the driver should exit
+    Stop,
+}
+
+impl HostMessage {
+    /// Returns the message as a single line of JSON
+    pub fn to_json(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let line = serde_json::to_string(self)?;
+        Ok(line)
+    }
+}
+
+/// Returns the path to the allow-listed external driver with the given name.
+/// Only executables that exist directly inside one of the driver directories
+/// can be run.
+pub fn find_driver(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        log::warn!("Invalid external driver name: {name}");
+        return None;
+    }
+    get_drivers_paths()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Translate the given event message from an external driver into a native
+/// event
+fn translate_event(
+    capability: &str,
+    value: VirtualScriptValue,
+) -> Result<NativeEvent, Box<dyn Error + Send + Sync>> {
+    let Ok(capability) = Capability::from_str(capability) else {
+        return Err(format!("Invalid capability: {capability}").into());
+    };
+    let value = InputValue::try_from(value)?;
+    Ok(NativeEvent::new(capability, value))
+}
+
+/// Source device implementation for an external userspace driver. The driver
+/// is spawned as a child process that writes [DriverMessage] lines to its
+/// stdout and reads [HostMessage] lines from its stdin. This allows drivers to
+/// be written in any language without linking against InputPlumber.
+pub struct ExternalSource {
+    path: PathBuf,
+    child: Child,
+    tx: Option<SyncSender<HostMessage>>,
+    rx: Receiver<NativeEvent>,
+    backlog: Arc<Mutex<OverloadQueue>>,
+    capabilities: Vec<Capability>,
+    ff_evdev_effects: HashMap<i16, FFEffectData>,
+}
+
+impl ExternalSource {
+    /// Spawn the external driver at the given path as an unprivileged user
+    /// and wait for it to send the capabilities of the device. This blocks
+    /// until the driver sends its hello, so it must not be called from an
+    /// async task.
+    pub fn new(
+        path: PathBuf,
+        composite_device: CompositeDeviceClient,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let Some(user) = User::from_name(DRIVER_USER)? else {
+            return Err(format!("User not found: {DRIVER_USER}").into());
+        };
+        log::debug!("Starting external driver {path:?} as {DRIVER_USER}");
+        let mut child = Command::new(&path)
+            .env_clear()
+            .env("HOME", user.dir)
+            .env("USER", user.name)
+            .uid(user.uid.as_raw())
+            .gid(user.gid.as_raw())
+            .current_dir("/")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err("Failed to open external driver stdio".into());
+        };
+
+        // Read and write messages on separate threads so polling does not
+        // block on the driver. The event channel disconnects when the driver
+        // exits.
+        let (hello_tx, hello_rx) = mpsc::channel();
+        let (event_tx, rx) = mpsc::sync_channel(EVENT_CHANNEL_SIZE);
+        let backlog = Arc::new(Mutex::new(OverloadQueue::default()));
+        let reader = DriverReader {
+            driver: path.clone(),
+            hello_tx: Some(hello_tx),
+            tx: event_tx,
+            backlog: backlog.clone(),
+            composite_device,
+        };
+        thread::spawn(move || reader.run(stdout));
+        let (tx, host_rx) = mpsc::sync_channel(HOST_CHANNEL_SIZE);
+        let driver = path.clone();
+        thread::spawn(move || write_messages(driver, stdin, host_rx));
+
+        let mut source = Self {
+            path,
+            child,
+            tx: Some(tx),
+            rx,
+            backlog,
+            capabilities: Vec::new(),
+            ff_evdev_effects: HashMap::new(),
+        };
+        let capabilities = match hello_rx.recv_timeout(HELLO_TIMEOUT) {
+            Ok(DriverMessage::Hello { capabilities }) => capabilities,
+            Ok(message) => {
+                return Err(format!("Expected hello from external driver, got: {message:?}").into())
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                return Err("Timed out waiting for hello from external driver".into())
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("External driver exited before sending hello".into())
+            }
+        };
+        for capability in capabilities {
+            let Ok(capability) = Capability::from_str(capability.as_str()) else {
+                return Err(
+                    format!("Invalid capability from external driver: {capability}").into(),
+                );
+            };
+            source.capabilities.push(capability);
+        }
+
+        Ok(source)
+    }
+
+    /// Queue the given message to be written to the stdin of the driver
+    fn send(&mut self, message: HostMessage) -> Result<(), OutputError> {
+        let Some(tx) = self.tx.as_ref() else {
+            return Err(format!("External driver {:?} was stopped", self.path).into());
+        };
+        match tx.try_send(message) {
+            Ok(_) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                Err(format!("External driver {:?} is not reading its input", self.path).into())
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                Err(format!("External driver {:?} closed its input", self.path).into())
+            }
+        }
+    }
+
+    /// Returns the next available evdev effect id
+    fn next_ff_effect_id(&self) -> i16 {
+        const MAX: i16 = 2096;
+        let mut i = 0;
+        loop {
+            if !self.ff_evdev_effects.contains_key(&i) {
+                return i;
+            }
+            i += 1;
+            if i > MAX {
+                return -1;
+            }
+        }
+    }
+
+    /// Process the given evdev force feedback event.
+    fn process_evdev_ff(&mut self, input_event: evdev::InputEvent) -> Result<(), OutputError> {
+        // Get the code (effect id) and value of the event
+        let (code, value) =
+            if let evdev::EventSummary::ForceFeedback(_, code, value) = input_event.destructure() {
+                (code, value)
+            } else {
+                log::debug!("Unhandled evdev output event: {:?}", input_event);
+                return Ok(());
+            };
+
+        // Find the effect data for this event
+        let effect_id = code.0 as i16;
+        let Some(effect_data) = self.ff_evdev_effects.get(&effect_id) else {
+            log::warn!("No effect id found: {}", code.0);
+            return Ok(());
+        };
+
+        // The value determines if the effect should be playing or not.
+        if value == 0 {
+            return self.send(HostMessage::Rumble { strong: 0, weak: 0 });
+        }
+
+        // Only rumble effects are supported
+        let FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } = effect_data.kind
+        else {
+            log::trace!("Unsupported effect kind: {:?}", effect_data.kind);
+            return Ok(());
+        };
+        self.send(HostMessage::Rumble {
+            strong: strong_magnitude,
+            weak: weak_magnitude,
+        })
+    }
+
+    /// Stop the driver process if it is still running. The driver is asked to
+    /// stop and its input is closed, and it is only killed if it does not exit
+    /// within [STOP_TIMEOUT].
+    fn terminate(&mut self) {
+        if let Ok(Some(_)) = self.child.try_wait() {
+            return;
+        }
+        log::debug!("Stopping external driver: {:?}", self.path);
+        let _ = self.send(HostMessage::Stop);
+        self.tx = None;
+
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+        log::debug!("Killing external driver that did not stop: {:?}", self.path);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl SourceInputDevice for ExternalSource {
+    /// Poll the input events sent by the driver
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let mut events = Vec::new();
+        let mut exited = false;
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    exited = true;
+                    break;
+                }
+            }
+        }
+
+        // Events that did not fit into the channel were sent after all events
+        // in the channel
+        let mut backlog = self.backlog.lock().unwrap();
+        while let Some(event) = backlog.pop_front() {
+            events.push(event);
+        }
+        drop(backlog);
+
+        if exited && events.is_empty() {
+            return Err(format!("External driver {:?} exited", self.path).into());
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.capabilities.clone())
+    }
+}
+
+impl SourceOutputDevice for ExternalSource {
+    /// Write the given output event to the driver. Only rumble is supported.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        match event {
+            OutputEvent::Evdev(input_event) => self.process_evdev_ff(input_event),
+            OutputEvent::SteamDeckRumble(_) => Ok(()),
+            OutputEvent::DualSense(_) => Ok(()),
+            OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::SteamDeckHaptics(_) => Ok(()),
+        }
+    }
+
+    /// Upload the given force feedback effect data to the source device. Returns
+    /// a device-specific id of the uploaded effect if it is successful.
+    fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
+        log::debug!("Uploading FF effect data");
+        let id = self.next_ff_effect_id();
+        if id == -1 {
+            return Err("Maximum FF effects uploaded".into());
+        }
+        self.ff_evdev_effects.insert(id, effect);
+
+        Ok(id)
+    }
+
+    /// Update the effect with the given id using the given effect data.
+    fn update_effect(&mut self, effect_id: i16, effect: FFEffectData) -> Result<(), OutputError> {
+        log::debug!("Updating FF effect data with id {effect_id}");
+        self.ff_evdev_effects.insert(effect_id, effect);
+        Ok(())
+    }
+
+    /// Erase the effect with the given id from the source device.
+    fn erase_effect(&mut self, effect_id: i16) -> Result<(), OutputError> {
+        log::debug!("Erasing FF effect data");
+        self.ff_evdev_effects.remove(&effect_id);
+        Ok(())
+    }
+
+    /// Show the given player index on the device
+    fn set_player_index(&mut self, index: Option<u8>) -> Result<(), OutputError> {
+        self.send(HostMessage::PlayerIndex { index })
+    }
+
+    /// Stop the driver process
+    fn stop(&mut self) -> Result<(), OutputError> {
+        self.terminate();
+        Ok(())
+    }
+}
+
+impl Drop for ExternalSource {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+impl Debug for ExternalSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalSource")
+            .field("path", &self.path)
+            .field("capabilities", &self.capabilities)
+            .finish()
+    }
+}
+
+/// Reads the messages of an external driver from its stdout on a separate
+/// thread. The first message is sent to the hello channel, and the events that
+/// follow are translated and sent to the event channel. While the event
+/// channel is full, events wait in the backlog, where axis values are
+/// coalesced and button edges are never dropped.
+struct DriverReader {
+    driver: PathBuf,
+    hello_tx: Option<Sender<DriverMessage>>,
+    tx: SyncSender<NativeEvent>,
+    backlog: Arc<Mutex<OverloadQueue>>,
+    composite_device: CompositeDeviceClient,
+}
+
+impl DriverReader {
+    /// Read messages from the given stdout until the driver closes it or the
+    /// source is dropped
+    fn run(mut self, stdout: ChildStdout) {
+        let driver = self.driver.clone();
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message = match DriverMessage::parse(line.as_str()) {
+                Ok(message) => message,
+                Err(e) => {
+                    log::warn!("Invalid message from external driver {driver:?}: {e}");
+                    continue;
+                }
+            };
+            if let Some(hello_tx) = self.hello_tx.take() {
+                if hello_tx.send(message).is_err() {
+                    break;
+                }
+                continue;
+            }
+            match message {
+                DriverMessage::Hello { .. } => {
+                    log::debug!("Ignoring repeated hello from external driver {driver:?}")
+                }
+                DriverMessage::Event { capability, value } => {
+                    let event = match translate_event(capability.as_str(), value) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            log::warn!("Invalid event from external driver {driver:?}: {e}");
+                            continue;
+                        }
+                    };
+                    if !self.send(event) {
+                        break;
+                    }
+                }
+            }
+        }
+        log::debug!("External driver {driver:?} closed its output");
+    }
+
+    /// Send the given event to the event channel, or to the backlog if the
+    /// channel is full. Returns false if the source was dropped.
+    fn send(&self, event: NativeEvent) -> bool {
+        let stats = self.composite_device.overload_stats();
+        let mut backlog = self.backlog.lock().unwrap();
+
+        // Events must wait behind any events in the backlog to keep their order
+        if !backlog.is_empty() {
+            backlog.push(event, stats);
+            return true;
+        }
+        match self.tx.try_send(event) {
+            Ok(_) => true,
+            Err(TrySendError::Full(event)) => {
+                backlog.push(event, stats);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Write the messages received from the given channel to the stdin of the
+/// driver until the channel is closed, which also closes the stdin. Writes
+/// block while the driver is not reading its input, so they are done on their
+/// own thread instead of the poll thread.
+fn write_messages(driver: PathBuf, mut stdin: ChildStdin, rx: Receiver<HostMessage>) {
+    for message in rx {
+        let line = match message.to_json() {
+            Ok(line) => line,
+            Err(e) => {
+                log::warn!("Failed to serialize message for external driver {driver:?}: {e}");
+                continue;
+            }
+        };
+        if let Err(e) = writeln!(stdin, "{line}") {
+            log::debug!("Failed to write to external driver {driver:?}: {e}");
+            break;
+        }
+    }
+}
+
+/// [ExternalDevice] represents a source device that is implemented by an
+/// external userspace driver. The devnode of the device is the name of an
+/// allow-listed driver executable.
+#[derive(Debug)]
+pub struct ExternalDevice {
+    driver: SourceDriver<ExternalSource>,
+}
+
+impl ExternalDevice {
+    /// Create a new [ExternalDevice] associated with the given device and
+    /// composite device. The driver is started on a blocking thread, since
+    /// it may take a while to send the capabilities of the device.
+    pub async fn new(
+        device_info: UdevDevice,
+        composite_device: CompositeDeviceClient,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let name = device_info.devnode();
+        let Some(path) = find_driver(name.as_str()) else {
+            return Err(format!("External driver not found in driver directories: {name}").into());
+        };
+        let client = composite_device.clone();
+        let device =
+            tokio::task::spawn_blocking(move || ExternalSource::new(path, client)).await??;
+        let driver = SourceDriver::new(composite_device, device, device_info);
+        Ok(Self { driver })
+    }
+}
+
+impl SourceDeviceCompatible for ExternalDevice {
+    fn get_device_ref(&self) -> &UdevDevice {
+        self.driver.info_ref()
+    }
+
+    fn get_id(&self) -> String {
+        self.driver.get_id()
+    }
+
+    fn client(&self) -> SourceDeviceClient {
+        self.driver.client()
+    }
+
+    async fn run(self) -> Result<(), Box<dyn Error>> {
+        self.driver.run().await
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        self.driver.get_capabilities()
+    }
+
+    fn get_device_path(&self) -> String {
+        self.driver.get_device_path()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::input::source::r#virtual::VirtualScriptValue;
+
+use super::external::{find_driver, DriverMessage, HostMessage};
+
+#[test]
+fn test_parse_driver_messages() {
+    let message =
+        DriverMessage::parse(r#"{"type":"hello","capabilities":["Gamepad:Button:South"]}"#)
+            .unwrap();
+    assert_eq!(
+        message,
+        DriverMessage::Hello {
+            capabilities: vec!["Gamepad:Button:South".to_string()]
+        }
+    );
+
+    let message = DriverMessage::parse(
+        r#"{"type": "event", "capability": "Gamepad:Axis:LeftStick", "value": [0.0, -1.0]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        message,
+        DriverMessage::Event {
+            capability: "Gamepad:Axis:LeftStick".to_string(),
+            value: VirtualScriptValue::Vector(vec![0.0, -1.0]),
+        }
+    );
+
+    // Unknown message types and malformed lines are rejected
+    assert!(DriverMessage::parse(r#"{"type": "unknown"}"#).is_err());
+    assert!(DriverMessage::parse(r#"{"type": "event""#).is_err());
+}
+
+#[test]
+fn test_host_messages() {
+    let message = HostMessage::Rumble {
+        strong: 65535,
+        weak: 0,
+    };
+    assert_eq!(
+        message.to_json().unwrap(),
+        r#"{"type":"rumble","strong":65535,"weak":0}"#
+    );
+    let message = HostMessage::PlayerIndex { index: None };
+    assert_eq!(
+        message.to_json().unwrap(),
+        r#"{"type":"player_index","index":null}"#
+    );
+    assert_eq!(HostMessage::Stop.to_json().unwrap(), r#"{"type":"stop"}"#);
+}
+
+#[test]
+fn test_find_driver() {
+    // Paths outside of the driver directories are never allowed
+    assert!(find_driver("../../bin/sh").is_none());
+    assert!(find_driver("/bin/sh").is_none());
+    assert!(find_driver(".hidden").is_none());
+}
@@ -1,25 +1,36 @@
+pub mod bluetooth;
 pub mod dualsense;
 pub mod fts3528;
+pub mod gamecube_adapter;
+pub mod gpd_hid;
 pub mod horipad_steam;
+pub mod joycon;
 pub mod lego_dinput_combined;
 pub mod lego_dinput_split;
 pub mod lego_fps_mode;
 pub mod lego_xinput;
 pub mod legos;
 pub mod opineo;
+pub mod oxp_hid;
 pub mod rog_ally;
 pub mod steam_deck;
 pub mod xpad_uhid;
+pub mod zotac_zone;
 
 use std::{error::Error, time::Duration};
 
+use gamecube_adapter::GameCubeAdapter;
+use gpd_hid::GpdHid;
 use horipad_steam::HoripadSteam;
+use joycon::JoyCon;
+use oxp_hid::OneXPlayerHid;
 use rog_ally::RogAlly;
 use xpad_uhid::XpadUhid;
+use zotac_zone::ZotacZone;
 
 use crate::{
-    constants::BUS_SOURCES_PREFIX, drivers, input::composite_device::client::CompositeDeviceClient,
-    udev::device::UdevDevice,
+    constants::BUS_SOURCES_PREFIX, dmi::get_dmi_data, drivers,
+    input::composite_device::client::CompositeDeviceClient, udev::device::UdevDevice,
 };
 
 use self::{
@@ -36,16 +47,21 @@ enum DriverType {
     Unknown,
     DualSense,
     Fts3528Touchscreen,
+    GameCubeAdapter,
+    GpdHid,
     HoripadSteam,
+    JoyCon,
     LegionGoDCombined,
     LegionGoDSplit,
     LegionGoFPS,
     LegionGoS,
     LegionGoX,
+    OneXPlayerHid,
     OrangePiNeo,
     RogAlly,
     SteamDeck,
     XpadUhid,
+    ZotacZone,
 }
 
 /// [HidRawDevice] represents an input device using the hidraw subsystem.
@@ -53,16 +69,21 @@ enum DriverType {
 pub enum HidRawDevice {
     DualSense(SourceDriver<DualSenseController>),
     Fts3528Touchscreen(SourceDriver<Fts3528Touchscreen>),
+    GameCubeAdapter(SourceDriver<GameCubeAdapter>),
+    GpdHid(SourceDriver<GpdHid>),
     HoripadSteam(SourceDriver<HoripadSteam>),
+    JoyCon(SourceDriver<JoyCon>),
     LegionGoDCombined(SourceDriver<LegionControllerDCombined>),
     LegionGoDSplit(SourceDriver<LegionControllerDSplit>),
     LegionGoFPS(SourceDriver<LegionControllerFPS>),
     LegionGoS(SourceDriver<LegionSController>),
     LegionGoX(SourceDriver<LegionControllerX>),
+    OneXPlayerHid(SourceDriver<OneXPlayerHid>),
     OrangePiNeo(SourceDriver<OrangePiNeoTouchpad>),
     RogAlly(SourceDriver<RogAlly>),
     SteamDeck(SourceDriver<DeckController>),
     XpadUhid(SourceDriver<XpadUhid>),
+    ZotacZone(SourceDriver<ZotacZone>),
 }
 
 impl SourceDeviceCompatible for HidRawDevice {
@@ -70,16 +91,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.info_ref(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.info_ref(),
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.info_ref(),
+            HidRawDevice::GpdHid(source_driver) => source_driver.info_ref(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.info_ref(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.info_ref(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.info_ref(),
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.info_ref(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.info_ref(),
             HidRawDevice::RogAlly(source_driver) => source_driver.info_ref(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.info_ref(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.info_ref(),
+            HidRawDevice::ZotacZone(source_driver) => source_driver.info_ref(),
         }
     }
 
@@ -87,16 +113,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.get_id(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_id(),
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.get_id(),
+            HidRawDevice::GpdHid(source_driver) => source_driver.get_id(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_id(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_id(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_id(),
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.get_id(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_id(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_id(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_id(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_id(),
+            HidRawDevice::ZotacZone(source_driver) => source_driver.get_id(),
         }
     }
 
@@ -104,16 +135,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.client(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.client(),
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.client(),
+            HidRawDevice::GpdHid(source_driver) => source_driver.client(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.client(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.client(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.client(),
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.client(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.client(),
             HidRawDevice::RogAlly(source_driver) => source_driver.client(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.client(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.client(),
+            HidRawDevice::ZotacZone(source_driver) => source_driver.client(),
         }
     }
 
@@ -121,16 +157,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.run().await,
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.run().await,
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.run().await,
+            HidRawDevice::GpdHid(source_driver) => source_driver.run().await,
             HidRawDevice::HoripadSteam(source_driver) => source_driver.run().await,
+            HidRawDevice::JoyCon(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoS(source_driver) => source_driver.run().await,
             HidRawDevice::LegionGoX(source_driver) => source_driver.run().await,
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.run().await,
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.run().await,
             HidRawDevice::RogAlly(source_driver) => source_driver.run().await,
             HidRawDevice::SteamDeck(source_driver) => source_driver.run().await,
             HidRawDevice::XpadUhid(source_driver) => source_driver.run().await,
+            HidRawDevice::ZotacZone(source_driver) => source_driver.run().await,
         }
     }
 
@@ -140,16 +181,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::GpdHid(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_capabilities(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_capabilities(),
+            HidRawDevice::ZotacZone(source_driver) => source_driver.get_capabilities(),
         }
     }
 
@@ -157,16 +203,21 @@ impl SourceDeviceCompatible for HidRawDevice {
         match self {
             HidRawDevice::DualSense(source_driver) => source_driver.get_device_path(),
             HidRawDevice::Fts3528Touchscreen(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::GameCubeAdapter(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::GpdHid(source_driver) => source_driver.get_device_path(),
             HidRawDevice::HoripadSteam(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::JoyCon(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoDCombined(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoDSplit(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoFPS(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoS(source_driver) => source_driver.get_device_path(),
             HidRawDevice::LegionGoX(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::OneXPlayerHid(source_driver) => source_driver.get_device_path(),
             HidRawDevice::OrangePiNeo(source_driver) => source_driver.get_device_path(),
             HidRawDevice::RogAlly(source_driver) => source_driver.get_device_path(),
             HidRawDevice::SteamDeck(source_driver) => source_driver.get_device_path(),
             HidRawDevice::XpadUhid(source_driver) => source_driver.get_device_path(),
+            HidRawDevice::ZotacZone(source_driver) => source_driver.get_device_path(),
         }
     }
 }
@@ -174,10 +225,12 @@ impl SourceDeviceCompatible for HidRawDevice {
 impl HidRawDevice {
     /// Create a new [HidRawDevice] associated with the given device and
     /// composite device. The appropriate driver will be selected based on
-    /// the provided device.
+    /// the provided device. An optional report length from the device quirks
+    /// is used for devices whose input reports differ in length from the spec.
     pub fn new(
         device_info: UdevDevice,
         composite_device: CompositeDeviceClient,
+        report_length: Option<usize>,
     ) -> Result<Self, Box<dyn Error + Send + Sync>> {
         let driver_type = HidRawDevice::get_driver_type(&device_info);
 
@@ -188,7 +241,7 @@ impl HidRawDevice {
                     poll_rate: Duration::from_millis(1),
                     buffer_size: 2048,
                 };
-                let device = DualSenseController::new(device_info.clone())?;
+                let device = DualSenseController::new(device_info.clone(), report_length)?;
                 let source_device =
                     SourceDriver::new_with_options(composite_device, device, device_info, options);
                 Ok(Self::DualSense(source_device))
@@ -228,6 +281,11 @@ impl HidRawDevice {
                 let source_device = SourceDriver::new(composite_device, device, device_info);
                 Ok(Self::LegionGoS(source_device))
             }
+            DriverType::OneXPlayerHid => {
+                let device = OneXPlayerHid::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::OneXPlayerHid(source_device))
+            }
             DriverType::OrangePiNeo => {
                 let device = OrangePiNeoTouchpad::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
@@ -238,6 +296,16 @@ impl HidRawDevice {
                 let source_device = SourceDriver::new(composite_device, device, device_info);
                 Ok(Self::Fts3528Touchscreen(source_device))
             }
+            DriverType::GameCubeAdapter => {
+                let device = GameCubeAdapter::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::GameCubeAdapter(source_device))
+            }
+            DriverType::GpdHid => {
+                let device = GpdHid::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::GpdHid(source_device))
+            }
             DriverType::XpadUhid => {
                 let device = XpadUhid::new(device_info.clone())?;
                 let source_device = SourceDriver::new(composite_device, device, device_info);
@@ -258,6 +326,21 @@ impl HidRawDevice {
                 let source_device = SourceDriver::new(composite_device, device, device_info);
                 Ok(Self::HoripadSteam(source_device))
             }
+            DriverType::JoyCon => {
+                let options = SourceDriverOptions {
+                    poll_rate: Duration::from_millis(1),
+                    buffer_size: 2048,
+                };
+                let device = JoyCon::new(device_info.clone())?;
+                let source_device =
+                    SourceDriver::new_with_options(composite_device, device, device_info, options);
+                Ok(Self::JoyCon(source_device))
+            }
+            DriverType::ZotacZone => {
+                let device = ZotacZone::new(device_info.clone())?;
+                let source_device = SourceDriver::new(composite_device, device, device_info);
+                Ok(Self::ZotacZone(source_device))
+            }
         }
     }
 
@@ -314,6 +397,26 @@ impl HidRawDevice {
             return DriverType::LegionGoS;
         }
 
+        // OneXPlayer/AOKZOE vendor keyboard
+        if vid == drivers::oxp_hid::driver::VID && pid == drivers::oxp_hid::driver::PID {
+            let sys_vendor = get_dmi_data().sys_vendor;
+            if !drivers::oxp_hid::driver::is_supported(vid, pid, sys_vendor.as_str()) {
+                log::debug!("Ignoring OneXPlayer vendor keyboard IDs on system from {sys_vendor}");
+                return DriverType::Unknown;
+            }
+            log::info!("Detected OneXPlayer vendor keyboard");
+            return DriverType::OneXPlayerHid;
+        }
+
+        // GPD Win 4 / Win Mini vendor interface
+        if vid == drivers::gpd_hid::driver::VID
+            && pid == drivers::gpd_hid::driver::PID
+            && device.interface_number() == drivers::gpd_hid::driver::VENDOR_IFACE
+        {
+            log::info!("Detected GPD vendor interface");
+            return DriverType::GpdHid;
+        }
+
         // OrangePi NEO
         if vid == drivers::opineo::driver::VID && pid == drivers::opineo::driver::PID {
             log::info!("Detected OrangePi NEO");
@@ -351,12 +454,47 @@ impl HidRawDevice {
             return DriverType::HoripadSteam;
         }
 
+        // Nintendo Joy-Con
+        if vid == drivers::joycon::driver::VID && drivers::joycon::driver::PIDS.contains(&pid) {
+            log::info!("Detected Nintendo Joy-Con");
+            return DriverType::JoyCon;
+        }
+
+        // Zotac Zone
+        if vid == drivers::zotac_zone::driver::VID && pid == drivers::zotac_zone::driver::PID {
+            log::info!("Detected Zotac Zone");
+            return DriverType::ZotacZone;
+        }
+
+        // GameCube controller adapter
+        if vid == drivers::gamecube_adapter::driver::VID
+            && pid == drivers::gamecube_adapter::driver::PID
+        {
+            log::info!("Detected GameCube adapter");
+            return DriverType::GameCubeAdapter;
+        }
+
         // Unknown
         log::warn!("No driver for hidraw interface found. VID: {vid}, PID: {pid}");
         DriverType::Unknown
     }
 }
 
+/// Returns the number of ports if the given device is a multi-port adapter,
+/// where the controller connected to each port should be managed as a
+/// separate source device (e.g. the GameCube controller adapter).
+pub fn get_ports(device: &UdevDevice) -> Option<u8> {
+    let vid = device.id_vendor();
+    let pid = device.id_product();
+    if vid == drivers::gamecube_adapter::driver::VID
+        && pid == drivers::gamecube_adapter::driver::PID
+    {
+        return Some(drivers::gamecube_adapter::driver::PORTS);
+    }
+
+    None
+}
+
 /// Returns the DBus path for a [HIDRawDevice] from a device path (E.g. /dev/hidraw0)
 pub fn get_dbus_path(device_name: String) -> String {
     format!("{}/{}", BUS_SOURCES_PREFIX, device_name)
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::udev::device::UdevDevice;
+
+/// Number of times attaching a bluetooth hidraw source device is retried
+pub const ATTACH_RETRIES: u32 = 5;
+/// Delay before the first retry. The delay doubles with each attempt.
+const ATTACH_RETRY_DELAY: Duration = Duration::from_millis(250);
+/// Maximum delay between retries
+const ATTACH_RETRY_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Returns true if a failure to attach the given source device may be
+/// transient and should be retried. Bluetooth LE controllers using HID over
+/// GATT (HOG) are re-created through uhid every time they reconnect, and the
+/// hidraw device can appear before bluez has resubscribed to all of the input
+/// reports. Opening or initializing the device can fail until it settles.
+pub fn should_retry_attach(device: &UdevDevice) -> bool {
+    device.subsystem() == "hidraw" && device.is_bluetooth()
+}
+
+/// Returns how long to wait before the given retry attempt, starting at 1
+pub fn attach_retry_delay(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    ATTACH_RETRY_DELAY
+        .saturating_mul(factor)
+        .min(ATTACH_RETRY_MAX_DELAY)
+}
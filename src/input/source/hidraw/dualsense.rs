@@ -15,6 +15,7 @@ use crate::{
         },
         event::{native::NativeEvent, value::InputValue},
         output_event::OutputEvent,
+        player_indicator,
         source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
     },
     udev::device::UdevDevice,
@@ -33,9 +34,17 @@ pub struct DualSenseController {
 
 impl DualSenseController {
     /// Create a new DualSense controller source device with the given udev
-    /// device information
-    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let driver = Driver::new(device_info.devnode())?;
+    /// device information. Input reports of the given length are resized to
+    /// the expected length, for controllers that send reports of a
+    /// different length.
+    pub fn new(
+        device_info: UdevDevice,
+        report_length: Option<usize>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut driver = Driver::new(device_info.devnode())?;
+        if let Some(length) = report_length {
+            driver.set_report_length(length);
+        }
         Ok(Self {
             driver,
             ff_evdev_effects: HashMap::new(),
@@ -204,6 +213,18 @@ impl SourceOutputDevice for DualSenseController {
         self.ff_evdev_effects.remove(&effect_id);
         Ok(())
     }
+
+    /// Show the player index on the player LEDs and lightbar
+    fn set_player_index(&mut self, index: Option<u8>) -> Result<(), OutputError> {
+        log::debug!("Setting player index to {index:?}");
+        let lights = player_indicator::dualsense_player_lights(index);
+        let color = match index {
+            Some(_) => player_indicator::color_preset(index),
+            None => dualsense::driver::DEFAULT_LED_COLOR,
+        };
+        self.driver.set_player_indicator(lights, color)?;
+        Ok(())
+    }
 }
 
 impl Debug for DualSenseController {
@@ -0,0 +1,240 @@
+use std::{collections::HashMap, error::Error, fmt::Debug};
+
+use evdev::{FFEffectData, FFEffectKind};
+use packed_struct::types::SizedInteger;
+
+use crate::{
+    drivers::gamecube_adapter::{driver::Driver, event},
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+        output_event::OutputEvent,
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Source device implementation for a single port of the GameCube controller
+/// adapter
+pub struct GameCubeAdapter {
+    driver: Driver,
+    ff_evdev_effects: HashMap<i16, FFEffectData>,
+}
+
+impl GameCubeAdapter {
+    /// Create a new GameCube adapter source device with the given udev
+    /// device information. The device must have the port to read set.
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let Some(port) = device_info.port() else {
+            return Err("GameCube adapter source devices require a port".into());
+        };
+        let driver = Driver::new(device_info, port)?;
+        Ok(Self {
+            driver,
+            ff_evdev_effects: HashMap::new(),
+        })
+    }
+
+    /// Returns the next available evdev effect id
+    fn next_ff_effect_id(&self) -> i16 {
+        const MAX: i16 = 2096;
+        let mut i = 0;
+        loop {
+            if !self.ff_evdev_effects.contains_key(&i) {
+                return i;
+            }
+            i += 1;
+            if i > MAX {
+                return -1;
+            }
+        }
+    }
+
+    /// Process the given evdev force feedback event.
+    fn process_evdev_ff(&mut self, input_event: evdev::InputEvent) -> Result<(), Box<dyn Error>> {
+        // Get the code (effect id) and value of the event
+        let (code, value) =
+            if let evdev::EventSummary::ForceFeedback(_, code, value) = input_event.destructure() {
+                (code, value)
+            } else {
+                log::debug!("Unhandled evdev output event: {:?}", input_event);
+                return Ok(());
+            };
+
+        // Find the effect data for this event
+        let effect_id = code.0 as i16;
+        let Some(effect_data) = self.ff_evdev_effects.get(&effect_id) else {
+            log::warn!("No effect id found: {}", code.0);
+            return Ok(());
+        };
+
+        // The value determines if the effect should be playing or not.
+        if value == 0 {
+            log::trace!("Stopping rumble");
+            if let Err(e) = self.driver.rumble(false) {
+                log::debug!("Failed to stop rumble: {:?}", e);
+            }
+            return Ok(());
+        }
+
+        // Only rumble effects are supported
+        let FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude,
+        } = effect_data.kind
+        else {
+            log::trace!("Unsupported effect kind: {:?}", effect_data.kind);
+            return Ok(());
+        };
+        let enabled = strong_magnitude > 0 || weak_magnitude > 0;
+        if let Err(e) = self.driver.rumble(enabled) {
+            let err = format!("Failed to do rumble: {:?}", e);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
+
+impl SourceInputDevice for GameCubeAdapter {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for GameCubeAdapter {
+    /// Write the given output event to the source device. Only rumble is
+    /// supported, which turns the single motor of the controller on or off.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        match event {
+            OutputEvent::Evdev(input_event) => Ok(self.process_evdev_ff(input_event)?),
+            OutputEvent::SteamDeckRumble(report) => {
+                let enabled =
+                    report.left_speed.to_primitive() > 0 || report.right_speed.to_primitive() > 0;
+                Ok(self.driver.rumble(enabled)?)
+            }
+            OutputEvent::DualSense(_) => Ok(()),
+            OutputEvent::Uinput(_) => Ok(()),
+            OutputEvent::SteamDeckHaptics(_) => Ok(()),
+        }
+    }
+
+    /// Upload the given force feedback effect data to the source device. Returns
+    /// a device-specific id of the uploaded effect if it is successful.
+    fn upload_effect(&mut self, effect: FFEffectData) -> Result<i16, OutputError> {
+        log::debug!("Uploading FF effect data");
+        let id = self.next_ff_effect_id();
+        if id == -1 {
+            return Err("Maximum FF effects uploaded".into());
+        }
+        self.ff_evdev_effects.insert(id, effect);
+
+        Ok(id)
+    }
+
+    /// Update the effect with the given id using the given effect data.
+    fn update_effect(&mut self, effect_id: i16, effect: FFEffectData) -> Result<(), OutputError> {
+        log::debug!("Updating FF effect data with id {effect_id}");
+        self.ff_evdev_effects.insert(effect_id, effect);
+        Ok(())
+    }
+
+    /// Erase the effect with the given id from the source device.
+    fn erase_effect(&mut self, effect_id: i16) -> Result<(), OutputError> {
+        log::debug!("Erasing FF effect data");
+        self.ff_evdev_effects.remove(&effect_id);
+        Ok(())
+    }
+}
+
+impl Debug for GameCubeAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameCubeAdapter")
+            .field("port", &self.driver.port())
+            .field("ff_evdev_effects", &self.ff_evdev_effects)
+            .finish()
+    }
+}
+
+/// Translate the given events into native events
+fn translate_events(events: Vec<event::Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given event into a native event
+fn translate_event(event: event::Event) -> NativeEvent {
+    match event {
+        event::Event::Button(button) => {
+            let (button, value) = match button {
+                event::ButtonEvent::A(value) => (GamepadButton::South, value),
+                event::ButtonEvent::B(value) => (GamepadButton::West, value),
+                event::ButtonEvent::X(value) => (GamepadButton::East, value),
+                event::ButtonEvent::Y(value) => (GamepadButton::North, value),
+                event::ButtonEvent::Start(value) => (GamepadButton::Start, value),
+                event::ButtonEvent::Z(value) => (GamepadButton::RightBumper, value),
+                event::ButtonEvent::L(value) => (GamepadButton::LeftTrigger, value),
+                event::ButtonEvent::R(value) => (GamepadButton::RightTrigger, value),
+                event::ButtonEvent::DPadUp(value) => (GamepadButton::DPadUp, value),
+                event::ButtonEvent::DPadDown(value) => (GamepadButton::DPadDown, value),
+                event::ButtonEvent::DPadLeft(value) => (GamepadButton::DPadLeft, value),
+                event::ButtonEvent::DPadRight(value) => (GamepadButton::DPadRight, value),
+            };
+            NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(button)),
+                InputValue::Bool(value.pressed),
+            )
+        }
+        event::Event::Joystick(axis) => {
+            let (axis, value) = match axis {
+                event::JoystickEvent::Stick(value) => (GamepadAxis::LeftStick, value),
+                event::JoystickEvent::CStick(value) => (GamepadAxis::RightStick, value),
+            };
+            NativeEvent::new(
+                Capability::Gamepad(Gamepad::Axis(axis)),
+                InputValue::Vector2 {
+                    x: Some(value.x),
+                    y: Some(value.y),
+                },
+            )
+        }
+        event::Event::Trigger(trigger) => {
+            let (trigger, value) = match trigger {
+                event::TriggerEvent::L(value) => (GamepadTrigger::LeftTrigger, value),
+                event::TriggerEvent::R(value) => (GamepadTrigger::RightTrigger, value),
+            };
+            NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(trigger)),
+                InputValue::Float(value.value),
+            )
+        }
+    }
+}
+
+/// List of all capabilities that a GameCube controller implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+    Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+];
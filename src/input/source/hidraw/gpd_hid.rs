@@ -0,0 +1,85 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::gpd_hid::{
+        driver::Driver,
+        event::{ButtonEvent, Event, SwitchMode},
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadButton},
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// GPD Win 4 / Win Mini vendor interface source device implementation
+pub struct GpdHid {
+    driver: Driver,
+}
+
+impl GpdHid {
+    /// Create a new GPD source device with the given udev device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self { driver })
+    }
+}
+
+impl SourceInputDevice for GpdHid {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for GpdHid {}
+
+impl Debug for GpdHid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpdHid")
+            .field("mode", &self.driver.mode())
+            .finish()
+    }
+}
+
+/// Translate the given GPD events into native events
+fn translate_events(events: Vec<Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given GPD event into a native event
+fn translate_event(event: Event) -> NativeEvent {
+    match event {
+        Event::Button(button) => match button {
+            ButtonEvent::LeftBack(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::RightBack(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+        // The switch is held as a button while in mouse mode so profiles can
+        // use it to activate a layer.
+        Event::Mode(mode) => NativeEvent::new(
+            Capability::Gamepad(Gamepad::Button(GamepadButton::MouseModeSwitch)),
+            InputValue::Bool(mode == SwitchMode::Mouse),
+        ),
+    }
+}
+
+/// List of all capabilities that the GPD driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::MouseModeSwitch)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+];
@@ -0,0 +1,99 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::oxp_hid::{
+        driver::Driver,
+        event::{ButtonEvent, Event},
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, Keyboard},
+        event::{native::NativeEvent, value::InputValue},
+        source::{InputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// OneXPlayer/AOKZOE vendor keyboard source device implementation
+pub struct OneXPlayerHid {
+    driver: Driver,
+}
+
+impl OneXPlayerHid {
+    /// Create a new OneXPlayer source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self { driver })
+    }
+}
+
+impl SourceInputDevice for OneXPlayerHid {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for OneXPlayerHid {}
+
+impl Debug for OneXPlayerHid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OneXPlayerHid")
+            .field("mode", &self.driver.mode())
+            .finish()
+    }
+}
+
+/// Translate the given OneXPlayer events into native events
+fn translate_events(events: Vec<Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given OneXPlayer event into a native event
+fn translate_event(event: Event) -> NativeEvent {
+    match event {
+        Event::Button(button) => match button {
+            ButtonEvent::Orange(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::OrangeLong(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::Turbo(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::Keyboard(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Keyboard)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::TurboOrange(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::KeyboardOrange(value) => NativeEvent::new(
+                Capability::Keyboard(Keyboard::KeyF13),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the OneXPlayer driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Keyboard)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::Screenshot)),
+    Capability::Keyboard(Keyboard::KeyF13),
+];
@@ -0,0 +1,142 @@
+use std::{error::Error, fmt::Debug};
+
+use crate::{
+    drivers::zotac_zone::{
+        driver::{self, Driver},
+        event::{ButtonEvent, DialEvent, Event, TriggerEvent},
+    },
+    input::{
+        capability::{Capability, Gamepad, GamepadButton, GamepadDial, GamepadTrigger},
+        event::{native::NativeEvent, value::InputValue},
+        output_event::OutputEvent,
+        player_indicator,
+        source::{InputError, OutputError, SourceInputDevice, SourceOutputDevice},
+    },
+    udev::device::UdevDevice,
+};
+
+/// Zotac Zone vendor controller source device implementation
+pub struct ZotacZone {
+    driver: Driver,
+}
+
+impl ZotacZone {
+    /// Create a new Zotac Zone source device with the given udev
+    /// device information
+    pub fn new(device_info: UdevDevice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let driver = Driver::new(device_info)?;
+        Ok(Self { driver })
+    }
+}
+
+impl SourceInputDevice for ZotacZone {
+    /// Poll the given input device for input events
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        let events = self.driver.poll()?;
+        let native_events = translate_events(events);
+        Ok(native_events)
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES.into())
+    }
+}
+
+impl SourceOutputDevice for ZotacZone {
+    /// Write the given output event to the source device. Only LED colors are
+    /// supported, which are shown on the LED rings around the sticks.
+    fn write_event(&mut self, event: OutputEvent) -> Result<(), OutputError> {
+        log::trace!("Received output event: {:?}", event);
+        let Some((red, green, blue)) = event.led_color() else {
+            return Ok(());
+        };
+        self.driver.set_rgb(red, green, blue)?;
+        Ok(())
+    }
+
+    /// Show the player index on the LED rings
+    fn set_player_index(&mut self, index: Option<u8>) -> Result<(), OutputError> {
+        log::debug!("Setting player index to {index:?}");
+        let (red, green, blue) = player_indicator::color_preset(index);
+        self.driver.set_rgb(red, green, blue)?;
+        Ok(())
+    }
+}
+
+impl Debug for ZotacZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZotacZone").finish()
+    }
+}
+
+/// Translate the given Zotac Zone events into native events
+fn translate_events(events: Vec<Event>) -> Vec<NativeEvent> {
+    events.into_iter().map(translate_event).collect()
+}
+
+/// Translate the given Zotac Zone event into a native event
+fn translate_event(event: Event) -> NativeEvent {
+    match event {
+        Event::Button(button) => match button {
+            ButtonEvent::M1(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::M2(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::LeftDial(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::RightDial(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::QuickAccess(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+                InputValue::Bool(value.pressed),
+            ),
+            ButtonEvent::Zotac(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+                InputValue::Bool(value.pressed),
+            ),
+        },
+        Event::Dial(dial) => match dial {
+            DialEvent::LeftDial(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Dial(GamepadDial::LeftDial)),
+                InputValue::Float(value.steps as f64),
+            ),
+            DialEvent::RightDial(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Dial(GamepadDial::RightDial)),
+                InputValue::Float(value.steps as f64),
+            ),
+        },
+        Event::Trigger(trigger) => match trigger {
+            TriggerEvent::LeftTrigger(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+                InputValue::Float(value.value as f64 / driver::TRIGGER_MAX),
+            ),
+            TriggerEvent::RightTrigger(value) => NativeEvent::new(
+                Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+                InputValue::Float(value.value as f64 / driver::TRIGGER_MAX),
+            ),
+        },
+    }
+}
+
+/// List of all capabilities that the Zotac Zone driver implements
+pub const CAPABILITIES: &[Capability] = &[
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::LeftPaddle2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::QuickAccess2)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle1)),
+    Capability::Gamepad(Gamepad::Button(GamepadButton::RightPaddle2)),
+    Capability::Gamepad(Gamepad::Dial(GamepadDial::LeftDial)),
+    Capability::Gamepad(Gamepad::Dial(GamepadDial::RightDial)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+    Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+];
@@ -3,13 +3,16 @@ pub mod accel_gyro_3d_new;
 pub mod bmi_imu;
 pub mod bmi_imu_new;
 
-use std::error::Error;
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
 
 use glob_match::glob_match;
 
 use crate::{
-    config, constants::BUS_SOURCES_PREFIX, input::composite_device::client::CompositeDeviceClient,
-    udev::device::UdevDevice,
+    config, constants::BUS_SOURCES_PREFIX, drivers::iio_imu::driver::Driver,
+    input::composite_device::client::CompositeDeviceClient, udev::device::UdevDevice,
 };
 
 use self::{accel_gyro_3d_new::AccelGyro3dImu, bmi_imu_new::BmiImu};
@@ -124,6 +127,82 @@ impl IioDevice {
     }
 }
 
+/// Interval at which the sample rates of a running IIO device are read
+/// again, so changes made over DBus update how often the device is polled
+const SAMPLE_RATE_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks how often an IIO IMU should be polled. Once a sample rate or poll
+/// interval is configured, or the sample rate changes while the device is
+/// running, the device is polled at its sample rate, clamped to the
+/// configured poll interval.
+#[derive(Debug)]
+struct ImuPollRate {
+    min: Option<Duration>,
+    max: Option<Duration>,
+    /// Whether the poll rate follows the sample rate of the device
+    follows_sample_rate: bool,
+    last_refresh: Instant,
+}
+
+impl ImuPollRate {
+    /// Set the sample rates of the given driver from the given IIO config
+    /// and return the poll rate to use for it
+    fn new(driver: &mut Driver, config: Option<&config::IIO>) -> Self {
+        let sample_rate = config.and_then(|config| config.sample_rate.as_ref());
+        if let Some(sample_rate) = sample_rate {
+            if let Err(e) = driver.set_sample_rates(sample_rate.accel, sample_rate.gyro) {
+                log::warn!("Failed to set IIO sample rate: {e:?}");
+            }
+        }
+        let interval = config.and_then(|config| config.poll_interval.as_ref());
+        let to_duration = |ms: f64| Duration::from_secs_f64(ms.max(0.0) / 1000.0);
+        let min = interval.and_then(|interval| interval.min_ms);
+        let max = interval.and_then(|interval| interval.max_ms);
+        let poll_rate = Self {
+            min: min.map(to_duration),
+            max: max.map(to_duration),
+            follows_sample_rate: sample_rate.is_some() || interval.is_some(),
+            last_refresh: Instant::now(),
+        };
+        if let Some(rate) = poll_rate.get(driver) {
+            log::debug!("Using poll rate of {rate:?} for IIO device");
+        }
+        poll_rate
+    }
+
+    /// Read the sample rates of the given driver again if the refresh
+    /// interval has passed
+    fn refresh(&mut self, driver: &mut Driver) {
+        if self.last_refresh.elapsed() < SAMPLE_RATE_REFRESH_INTERVAL {
+            return;
+        }
+        self.last_refresh = Instant::now();
+        if !driver.refresh_sample_rates() {
+            return;
+        }
+        self.follows_sample_rate = true;
+        if let Some(rate) = self.get(driver) {
+            log::debug!("Using poll rate of {rate:?} for IIO device");
+        }
+    }
+
+    /// Returns how long to wait between polls of the given driver, or [None]
+    /// if the default poll rate should be used
+    fn get(&self, driver: &Driver) -> Option<Duration> {
+        if !self.follows_sample_rate {
+            return None;
+        }
+        let mut poll_rate = driver.sample_delay;
+        if let Some(max) = self.max {
+            poll_rate = poll_rate.min(max);
+        }
+        if let Some(min) = self.min {
+            poll_rate = poll_rate.max(min);
+        }
+        Some(poll_rate)
+    }
+}
+
 /// Returns the DBus path for an [IIODevice] from a device id (E.g. iio:device0)
 pub fn get_dbus_path(id: String) -> String {
     let name = id.replace(':', "_");
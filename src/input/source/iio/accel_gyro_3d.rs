@@ -151,6 +151,7 @@ fn receive_commands(
         match rx.try_recv() {
             Ok(cmd) => match cmd {
                 SourceCommand::EraseEffect(_, _) => (),
+                SourceCommand::SetPlayerIndex(_) => (),
                 SourceCommand::UpdateEffect(_, _) => (),
                 SourceCommand::UploadEffect(_, _) => (),
                 SourceCommand::WriteEvent(_) => (),
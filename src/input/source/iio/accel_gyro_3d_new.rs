@@ -1,4 +1,4 @@
-use std::{error::Error, f64::consts::PI, fmt::Debug};
+use std::{error::Error, f64::consts::PI, fmt::Debug, time::Duration};
 
 use crate::{
     config,
@@ -11,8 +11,11 @@ use crate::{
     udev::device::UdevDevice,
 };
 
+use super::ImuPollRate;
+
 pub struct AccelGyro3dImu {
     driver: Driver,
+    poll_rate: ImuPollRate,
 }
 
 impl AccelGyro3dImu {
@@ -40,15 +43,17 @@ impl AccelGyro3dImu {
 
         let id = device_info.sysname();
         let name = device_info.name();
-        let driver = Driver::new(id, name, mount_matrix)?;
+        let mut driver = Driver::new(id, name, mount_matrix)?;
+        let poll_rate = ImuPollRate::new(&mut driver, config.as_ref());
 
-        Ok(Self { driver })
+        Ok(Self { driver, poll_rate })
     }
 }
 
 impl SourceInputDevice for AccelGyro3dImu {
     /// Poll the given input device for input events
     fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        self.poll_rate.refresh(&mut self.driver);
         let events = self.driver.poll()?;
         let native_events = translate_events(events);
         Ok(native_events)
@@ -58,6 +63,11 @@ impl SourceInputDevice for AccelGyro3dImu {
     fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
         Ok(CAPABILITIES.into())
     }
+
+    /// Returns how long to wait between polls to match the device sample rate
+    fn poll_rate(&self) -> Option<Duration> {
+        self.poll_rate.get(&self.driver)
+    }
 }
 
 impl SourceOutputDevice for AccelGyro3dImu {}
@@ -154,6 +154,7 @@ fn receive_commands(
                 SourceCommand::UploadEffect(_, _) => (),
                 SourceCommand::UpdateEffect(_, _) => (),
                 SourceCommand::EraseEffect(_, _) => (),
+                SourceCommand::SetPlayerIndex(_) => (),
                 SourceCommand::Stop => return Err("Device stopped".into()),
             },
             Err(e) => match e {
@@ -1,38 +1,66 @@
 use std::{
+    collections::HashMap,
     error::Error,
     sync::{Arc, Mutex, MutexGuard},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use ::evdev::FFEffectData;
+use ::evdev::{AbsInfo, AbsoluteAxisCode, FFEffectData};
 use thiserror::Error;
-use tokio::sync::mpsc::{self, error::TryRecvError};
+use tokio::sync::mpsc::{
+    self,
+    error::{TryRecvError, TrySendError},
+};
 
 use crate::udev::device::UdevDevice;
 
 use self::{
-    client::SourceDeviceClient, command::SourceCommand, evdev::EventDevice, hidraw::HidRawDevice,
-    iio::IioDevice,
+    client::SourceDeviceClient, command::SourceCommand, evdev::EventDevice,
+    external::ExternalDevice, hidraw::HidRawDevice, iio::IioDevice, output_queue::OutputQueue,
+    r#virtual::VirtualDevice,
 };
 
 use super::{
     capability::Capability,
-    composite_device::client::CompositeDeviceClient,
+    composite_device::{
+        client::{ClientError, CompositeDeviceClient},
+        command::CompositeCommand,
+        overload::{is_edge, OverloadQueue, OverloadStats},
+    },
     event::{native::NativeEvent, Event},
     output_event::OutputEvent,
+    watchdog::{self, Heartbeat, STALL_TIMEOUT},
 };
 
 pub mod client;
 pub mod command;
 pub mod evdev;
+pub mod external;
+#[cfg(test)]
+pub mod external_test;
 pub mod hidraw;
 pub mod iio;
+pub mod output_queue;
+#[cfg(test)]
+pub mod output_queue_test;
+pub mod power;
+pub mod r#virtual;
+#[cfg(test)]
+pub mod virtual_test;
 
 /// Size of the [SourceCommand] buffer for receiving output events
 const BUFFER_SIZE: usize = 2048;
 /// Default poll rate (2.5ms/400Hz)
 const POLL_RATE: Duration = Duration::from_micros(2500);
+/// Minimum time between two output events written to a source device
+/// (4ms/250Hz), so bursts of LED and rumble updates do not overwhelm it
+const OUTPUT_INTERVAL: Duration = Duration::from_millis(4);
+/// Maximum number of times to retry polling a source device after an error
+/// before the device is detached.
+const MAX_POLL_ATTEMPTS: u32 = 3;
+/// Time to wait before the first retry. Doubled for each following retry.
+const POLL_RETRY_DELAY: Duration = Duration::from_millis(100);
 
 /// Possible errors for a source device client
 #[derive(Error, Debug)]
@@ -98,6 +126,32 @@ impl From<Box<dyn Error + Send + Sync>> for OutputError {
     }
 }
 
+/// Describes an error that occurred while reading from a source device and
+/// whether reading will be retried.
+#[derive(Debug, Clone)]
+pub struct SourceDeviceError {
+    /// Description of the error
+    pub error: String,
+    /// Number of consecutive failed attempts, starting at 1
+    pub attempt: u32,
+    /// Maximum number of attempts before the device is detached
+    pub max_attempts: u32,
+    /// Time to wait before retrying, or [None] if the device will be detached
+    pub retry_in: Option<Duration>,
+}
+
+impl SourceDeviceError {
+    /// Returns the retry strategy used for this error. Either "backoff" if
+    /// reading will be retried, or "detach" if the device will be detached.
+    pub fn retry_strategy(&self) -> &str {
+        if self.retry_in.is_some() {
+            "backoff"
+        } else {
+            "detach"
+        }
+    }
+}
+
 /// A [SourceInputDevice] is a device implementation that is capable of emitting
 /// input events.
 pub trait SourceInputDevice {
@@ -106,6 +160,19 @@ pub trait SourceInputDevice {
 
     /// Returns the possible input events this device is capable of emitting
     fn get_capabilities(&self) -> Result<Vec<Capability>, InputError>;
+
+    /// Returns the absolute axis ranges reported by the device hardware, if
+    /// any. These are used to set up target devices with matching ranges.
+    fn get_abs_info(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        HashMap::new()
+    }
+
+    /// Returns how long to wait between polls if it should differ from the
+    /// poll rate in the [SourceDriverOptions]. This is checked after every
+    /// poll, so devices can change their poll rate while running.
+    fn poll_rate(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// A [SourceOutputDevice] is a device implementation that can handle output events
@@ -144,6 +211,14 @@ pub trait SourceOutputDevice {
         Ok(())
     }
 
+    /// Show the given player index on the source device using whatever
+    /// player indicator the device supports (e.g. player LEDs or a colored
+    /// lightbar). A value of [None] clears the indicator.
+    fn set_player_index(&mut self, index: Option<u8>) -> Result<(), OutputError> {
+        let _ = index;
+        Ok(())
+    }
+
     /// Stop the source device.
     fn stop(&mut self) -> Result<(), OutputError> {
         Ok(())
@@ -224,6 +299,11 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
         self.implementation.lock().unwrap().get_capabilities()
     }
 
+    /// Returns the absolute axis ranges reported by the device hardware
+    pub fn get_abs_info(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        self.implementation.lock().unwrap().get_abs_info()
+    }
+
     /// Returns the path to the device (e.g. "/dev/input/event0")
     pub fn get_device_path(&self) -> String {
         self.device_info.devnode()
@@ -239,54 +319,192 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
         &self.device_info
     }
 
-    /// Run the source device, consuming the device.
+    /// Run the source device, consuming the device. If the device stops
+    /// responding (e.g. a hidraw read that never returns), it is abandoned and
+    /// a [watchdog::TaskStalled] error is returned so it can be restarted.
     pub async fn run(self) -> Result<(), Box<dyn Error>> {
         let device_id = self.get_id();
-
-        // Spawn a blocking task to run the source device.
-        let task =
+        let heartbeat = Arc::new(Heartbeat::new());
+        let task_heartbeat = heartbeat.clone();
+
+        // Spawn a blocking task to run the source device. The blocking task
+        // runs on another thread, so it needs to enter the current span for
+        // its logs to be associated with this device.
+        let span = tracing::Span::current();
+        let task_device_id = device_id.clone();
+        let mut task =
             tokio::task::spawn_blocking(move || -> Result<(), Box<dyn Error + Send + Sync>> {
+                let _span = span.enter();
+                let device_id = task_device_id;
+                let heartbeat = task_heartbeat;
                 let mut rx = self.rx;
                 let mut implementation = self.implementation.lock().unwrap();
+                let mut attempt = 0;
+                let mut backlog = OverloadQueue::default();
+                let mut outputs = OutputQueue::new(OUTPUT_INTERVAL);
                 loop {
-                    // Poll the implementation for events
-                    let events = implementation.poll()?;
-                    for event in events.into_iter() {
-                        let event = Event::Native(event);
-                        let result = self
-                            .composite_device
-                            .blocking_process_event(device_id.clone(), event);
-                        if let Err(e) = result {
-                            return Err(e.to_string().into());
+                    // Poll the implementation for events. Errors are reported
+                    // to the composite device and retried with an increasing
+                    // delay before giving up.
+                    heartbeat.beat("poll");
+                    let result = implementation.poll();
+
+                    // Stop if the watchdog gave up on this task while it was
+                    // stuck, since a new instance may already be running.
+                    if heartbeat.is_abandoned() {
+                        log::warn!("Abandoned source device {device_id} resumed. Stopping.");
+                        break;
+                    }
+                    let events = match result {
+                        Ok(events) => {
+                            attempt = 0;
+                            events
+                        }
+                        Err(e) => {
+                            attempt += 1;
+                            let retry_in = (attempt < MAX_POLL_ATTEMPTS)
+                                .then(|| POLL_RETRY_DELAY * 2u32.pow(attempt - 1));
+                            let error = SourceDeviceError {
+                                error: e.to_string(),
+                                attempt,
+                                max_attempts: MAX_POLL_ATTEMPTS,
+                                retry_in,
+                            };
+                            log::warn!("Failed to poll source device {device_id}: {error:?}");
+                            if let Err(e) = self
+                                .composite_device
+                                .blocking_source_device_error(device_id.clone(), error)
+                            {
+                                return Err(e.to_string().into());
+                            }
+                            let Some(retry_in) = retry_in else {
+                                return Err(e.into());
+                            };
+                            thread::sleep(retry_in);
+                            continue;
                         }
+                    };
+                    heartbeat.beat("send");
+                    let result =
+                        Self::send_events(&self.composite_device, &device_id, &mut backlog, events);
+                    if let Err(e) = result {
+                        return Err(e.to_string().into());
                     }
 
                     // Receive commands/output events
-                    if let Err(e) = SourceDriver::receive_commands(&mut rx, &mut implementation) {
+                    heartbeat.beat("commands");
+                    let stats = self.composite_device.overload_stats();
+                    let result = SourceDriver::receive_commands(
+                        &mut rx,
+                        &mut implementation,
+                        &mut outputs,
+                        stats,
+                    );
+                    if let Err(e) = result {
                         log::debug!("Error receiving commands: {:?}", e);
                         break;
                     }
 
+                    // Write queued output events as the rate limit allows
+                    heartbeat.beat("output");
+                    if let Err(e) = SourceDriver::write_outputs(&mut outputs, &mut implementation) {
+                        log::debug!("Error writing output events: {:?}", e);
+                        break;
+                    }
+
                     // Sleep for the configured duration
-                    thread::sleep(self.options.poll_rate);
+                    let poll_rate = implementation.poll_rate();
+                    thread::sleep(poll_rate.unwrap_or(self.options.poll_rate));
                 }
 
                 Ok(())
             });
 
-        // Wait for the device to finish running.
-        if let Err(e) = task.await? {
-            return Err(e.to_string().into());
+        // Wait for the device to finish running, unless it stops sending
+        // heartbeats. A stuck blocking thread cannot be cancelled, so it is
+        // abandoned and stops on its own if it ever resumes.
+        tokio::select! {
+            result = &mut task => {
+                if let Err(e) = result? {
+                    return Err(e.to_string().into());
+                }
+            }
+            stalled = watchdog::supervise(&device_id, &heartbeat, STALL_TIMEOUT) => {
+                heartbeat.abandon();
+                log::error!(
+                    "Source device stopped responding after {} heartbeats: {stalled}",
+                    heartbeat.beats()
+                );
+                return Err(stalled.into());
+            }
         }
 
         Ok(())
     }
 
+    /// Send the given events to the composite device after any events waiting
+    /// in the backlog. If the channel of the composite device is full, button
+    /// edges wait for room so they are never dropped, while axis values are
+    /// kept in the backlog and coalesced until the next poll.
+    fn send_events(
+        composite_device: &CompositeDeviceClient,
+        device_id: &str,
+        backlog: &mut OverloadQueue,
+        events: Vec<NativeEvent>,
+    ) -> Result<(), ClientError> {
+        while let Some(event) = backlog.pop_front() {
+            if let Some(event) = Self::send_event(composite_device, device_id, event)? {
+                backlog.push_front(event);
+                break;
+            }
+        }
+
+        let stats = composite_device.overload_stats();
+        for event in events {
+            // Events must wait behind any events in the backlog to keep their order
+            if !backlog.is_empty() {
+                backlog.push(event, stats);
+                continue;
+            }
+            if let Some(event) = Self::send_event(composite_device, device_id, event)? {
+                backlog.push(event, stats);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send the given event to the composite device. Returns the event back if
+    /// it is an axis value or the end of an input frame and the channel is
+    /// full.
+    fn send_event(
+        composite_device: &CompositeDeviceClient,
+        device_id: &str,
+        event: NativeEvent,
+    ) -> Result<Option<NativeEvent>, ClientError> {
+        let device_id = device_id.to_string();
+        if is_edge(&event) && event.as_capability() != Capability::Sync {
+            composite_device.blocking_process_event(device_id, Event::Native(event))?;
+            return Ok(None);
+        }
+        match composite_device.try_process_event(device_id, Event::Native(event)) {
+            Ok(_) => Ok(None),
+            Err(ClientError::TrySendError(TrySendError::Full(CompositeCommand::ProcessEvent(
+                _,
+                Event::Native(event),
+            )))) => Ok(Some(event)),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Read commands sent to this device from the channel until it is
-    /// empty.
+    /// empty. Output events are added to the given queue to be written as
+    /// the rate limit allows.
     fn receive_commands(
         rx: &mut mpsc::Receiver<SourceCommand>,
         implementation: &mut MutexGuard<'_, T>,
+        outputs: &mut OutputQueue,
+        stats: &OverloadStats,
     ) -> Result<(), Box<dyn Error>> {
         const MAX_COMMANDS: u8 = 64;
         let mut commands_processed = 0;
@@ -322,7 +540,12 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
                     }
                     SourceCommand::WriteEvent(event) => {
                         log::trace!("Received output event: {:?}", event);
-                        implementation.write_event(event)?;
+                        outputs.push(event, stats);
+                    }
+                    SourceCommand::SetPlayerIndex(index) => {
+                        if let Err(e) = implementation.set_player_index(index) {
+                            log::warn!("Failed to set player index: {e:?}");
+                        }
                     }
                     SourceCommand::Stop => {
                         implementation.stop()?;
@@ -345,6 +568,18 @@ impl<T: SourceInputDevice + SourceOutputDevice + Send + 'static> SourceDriver<T>
             }
         }
     }
+
+    /// Write the queued output events that are ready to the device
+    fn write_outputs(
+        outputs: &mut OutputQueue,
+        implementation: &mut MutexGuard<'_, T>,
+    ) -> Result<(), Box<dyn Error>> {
+        while let Some(event) = outputs.pop_ready(Instant::now()) {
+            log::trace!("Writing output event: {:?}", event);
+            implementation.write_event(event)?;
+        }
+        Ok(())
+    }
 }
 
 pub(crate) trait SourceDeviceCompatible {
@@ -373,6 +608,8 @@ pub enum SourceDevice {
     Event(EventDevice),
     HidRaw(HidRawDevice),
     Iio(IioDevice),
+    Virtual(VirtualDevice),
+    External(ExternalDevice),
 }
 
 impl SourceDevice {
@@ -382,6 +619,8 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_device_ref(),
             SourceDevice::HidRaw(device) => device.get_device_ref(),
             SourceDevice::Iio(device) => device.get_device_ref(),
+            SourceDevice::Virtual(device) => device.get_device_ref(),
+            SourceDevice::External(device) => device.get_device_ref(),
         }
     }
 
@@ -391,6 +630,8 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_id(),
             SourceDevice::HidRaw(device) => device.get_id(),
             SourceDevice::Iio(device) => device.get_id(),
+            SourceDevice::Virtual(device) => device.get_id(),
+            SourceDevice::External(device) => device.get_id(),
         }
     }
 
@@ -400,6 +641,8 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.client(),
             SourceDevice::HidRaw(device) => device.client(),
             SourceDevice::Iio(device) => device.client(),
+            SourceDevice::Virtual(device) => device.client(),
+            SourceDevice::External(device) => device.client(),
         }
     }
 
@@ -409,6 +652,8 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.run().await,
             SourceDevice::HidRaw(device) => device.run().await,
             SourceDevice::Iio(device) => device.run().await,
+            SourceDevice::Virtual(device) => device.run().await,
+            SourceDevice::External(device) => device.run().await,
         }
     }
 
@@ -418,6 +663,19 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_capabilities(),
             SourceDevice::HidRaw(device) => device.get_capabilities(),
             SourceDevice::Iio(device) => device.get_capabilities(),
+            SourceDevice::Virtual(device) => device.get_capabilities(),
+            SourceDevice::External(device) => device.get_capabilities(),
+        }
+    }
+
+    /// Returns the absolute axis ranges reported by the source device hardware
+    pub fn get_abs_info(&self) -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        match self {
+            SourceDevice::Event(device) => device.get_abs_info(),
+            SourceDevice::HidRaw(_) => HashMap::new(),
+            SourceDevice::Iio(_) => HashMap::new(),
+            SourceDevice::Virtual(_) => HashMap::new(),
+            SourceDevice::External(_) => HashMap::new(),
         }
     }
 
@@ -427,6 +685,8 @@ impl SourceDevice {
             SourceDevice::Event(device) => device.get_device_path(),
             SourceDevice::HidRaw(device) => device.get_device_path(),
             SourceDevice::Iio(device) => device.get_device_path(),
+            SourceDevice::Virtual(device) => device.get_device_path(),
+            SourceDevice::External(device) => device.get_device_path(),
         }
     }
 }
@@ -0,0 +1,94 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use evdev::EventType;
+
+use crate::input::{composite_device::overload::OverloadStats, output_event::OutputEvent};
+
+/// Maximum number of output events that can wait to be written. Redundant
+/// updates are coalesced, so this is only reached if many different outputs
+/// are waiting.
+const MAX_PENDING: usize = 64;
+
+/// Output events waiting to be written to a source device. Games can send
+/// LED and rumble updates faster than some hardware accepts them, so writes
+/// are rate limited and an update that replaces a waiting update of the same
+/// output (e.g. a newer rumble strength) is merged into it instead of being
+/// queued.
+#[derive(Debug)]
+pub struct OutputQueue {
+    /// Minimum time between two writes to the device
+    interval: Duration,
+    /// Time the next event may be written at
+    next_write: Option<Instant>,
+    pending: VecDeque<OutputEvent>,
+}
+
+impl OutputQueue {
+    /// Create a new queue that writes at most one event per interval
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_write: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Add the given event to the queue, replacing a waiting event that it
+    /// makes redundant.
+    pub fn push(&mut self, event: OutputEvent, stats: &OverloadStats) {
+        if let Some(waiting) = self.pending.iter_mut().find(|w| replaces(&event, w)) {
+            *waiting = event;
+            stats.record_output_coalesced();
+            return;
+        }
+
+        // Force feedback uploads and erases wait for a reply, so they are
+        // never dropped.
+        if self.pending.len() >= MAX_PENDING && !matches!(event, OutputEvent::Uinput(_)) {
+            stats.record_output_dropped();
+            return;
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Take the next event to write if the rate limit allows a write at the
+    /// given time.
+    pub fn pop_ready(&mut self, now: Instant) -> Option<OutputEvent> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        if self.next_write.is_some_and(|next| now < next) {
+            return None;
+        }
+
+        // Keep the cadence of a busy queue, but do not allow a burst of writes
+        // after the queue was idle.
+        let start = self
+            .next_write
+            .filter(|next| *next + self.interval > now)
+            .unwrap_or(now);
+        self.next_write = Some(start + self.interval);
+        self.pending.pop_front()
+    }
+}
+
+/// Returns true if the given event overrides everything the waiting event
+/// would write, so only the newer event needs to be written.
+fn replaces(event: &OutputEvent, waiting: &OutputEvent) -> bool {
+    match (event, waiting) {
+        (OutputEvent::Evdev(event), OutputEvent::Evdev(waiting)) => {
+            let kind = event.event_type();
+            (kind == EventType::FORCEFEEDBACK || kind == EventType::LED)
+                && kind == waiting.event_type()
+                && event.code() == waiting.code()
+        }
+        (OutputEvent::DualSense(_), OutputEvent::DualSense(_)) => {
+            event.as_capability() == waiting.as_capability()
+        }
+        (OutputEvent::SteamDeckRumble(_), OutputEvent::SteamDeckRumble(_)) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use evdev::{EventType, InputEvent};
+
+use crate::input::{composite_device::overload::OverloadStats, output_event::OutputEvent};
+
+use super::output_queue::OutputQueue;
+
+const INTERVAL: Duration = Duration::from_millis(4);
+
+fn rumble(effect_id: u16, value: i32) -> OutputEvent {
+    OutputEvent::Evdev(InputEvent::new(
+        EventType::FORCEFEEDBACK.0,
+        effect_id,
+        value,
+    ))
+}
+
+/// Take all events from the queue, one per interval
+fn drain(queue: &mut OutputQueue) -> Vec<OutputEvent> {
+    let mut now = Instant::now();
+    let mut events = Vec::new();
+    while let Some(event) = queue.pop_ready(now) {
+        events.push(event);
+        now += INTERVAL;
+    }
+    events
+}
+
+#[test]
+fn test_output_queue_coalesce() {
+    let stats = OverloadStats::default();
+    let mut queue = OutputQueue::new(INTERVAL);
+    queue.push(rumble(0, 1), &stats);
+    queue.push(rumble(1, 1), &stats);
+    queue.push(rumble(0, 0), &stats);
+    assert_eq!(stats.outputs_coalesced(), 1);
+
+    // The newer update of effect 0 replaces the waiting one in place
+    let events = drain(&mut queue);
+    assert_eq!(events.len(), 2);
+    let OutputEvent::Evdev(event) = &events[0] else {
+        panic!("Expected an evdev output event");
+    };
+    assert_eq!(event.code(), 0);
+    assert_eq!(event.value(), 0);
+}
+
+#[test]
+fn test_output_queue_rate_limit() {
+    let stats = OverloadStats::default();
+    let mut queue = OutputQueue::new(INTERVAL);
+    queue.push(rumble(0, 1), &stats);
+    queue.push(rumble(1, 1), &stats);
+
+    let now = Instant::now();
+    assert!(queue.pop_ready(now).is_some());
+    assert!(queue.pop_ready(now).is_none());
+    assert!(queue.pop_ready(now + INTERVAL).is_some());
+    assert!(queue.pop_ready(now + INTERVAL * 2).is_none());
+}
+
+#[test]
+fn test_output_queue_overflow() {
+    let stats = OverloadStats::default();
+    let mut queue = OutputQueue::new(INTERVAL);
+    for effect_id in 0..100 {
+        queue.push(rumble(effect_id, 1), &stats);
+    }
+    assert_eq!(stats.outputs_dropped(), 36);
+    assert_eq!(drain(&mut queue).len(), 64);
+}
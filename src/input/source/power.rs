@@ -0,0 +1,65 @@
+//! Power management quirks for source devices
+//!
+//! Some handheld controllers are connected over I2C HID and drop input
+//! reports when the kernel runtime suspends them, or lose their configuration
+//! after system suspend. These helpers can be enabled for matching source
+//! devices with the `power` option of a source device config.
+
+use std::{
+    error::Error,
+    ffi::{CString, OsStr},
+};
+
+use crate::udev::device::UdevDevice;
+
+/// Subsystems of the physical bus devices that runtime power management is
+/// disabled for. Parents above the bus device are left untouched.
+const BUS_SUBSYSTEMS: &[&str] = &["i2c", "usb", "spi", "serio"];
+
+/// Disable runtime power management (autosuspend) of the given device and all
+/// of its parents up to and including its bus device by setting their
+/// `power/control` attribute to "on". USB interfaces do not support runtime
+/// power management themselves, so the walk continues to the USB device.
+pub fn disable_autosuspend(device: &UdevDevice) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut current = Some(device.get_device()?);
+    while let Some(mut dev) = current {
+        let control = dev.attribute_value("power/control");
+        let has_control = control.is_some();
+        if control.is_some_and(|value| value != OsStr::new("on")) {
+            log::debug!("Disabling autosuspend on {:?}", dev.syspath());
+            dev.set_attribute_value(OsStr::new("power/control"), OsStr::new("on"))
+                .map_err(|e| {
+                    format!("Failed to disable autosuspend on {:?}: {e}", dev.syspath())
+                })?;
+        }
+
+        let is_bus_device = dev
+            .subsystem()
+            .and_then(|subsystem| subsystem.to_str())
+            .is_some_and(|subsystem| BUS_SUBSYSTEMS.contains(&subsystem));
+        if is_bus_device && has_control {
+            break;
+        }
+        current = dev.parent();
+    }
+
+    Ok(())
+}
+
+/// Send the given feature reports to the hidraw device at the given path to
+/// re-initialize it, e.g. after system resume. Each report starts with its
+/// report id.
+pub fn send_feature_reports(
+    devnode: &str,
+    reports: &[Vec<u8>],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let path = CString::new(devnode)?;
+    let api = hidapi::HidApi::new_without_enumerate()?;
+    let device = api.open_path(&path)?;
+    for report in reports {
+        log::debug!("Sending feature report to {devnode}: {report:02x?}");
+        device.send_feature_report(report.as_slice())?;
+    }
+
+    Ok(())
+}
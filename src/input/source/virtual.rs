@@ -0,0 +1,254 @@
+use std::{
+    error::Error,
+    fmt::Debug,
+    fs,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    input::{
+        capability::Capability,
+        composite_device::client::CompositeDeviceClient,
+        event::{native::NativeEvent, value::InputValue},
+    },
+    udev::device::UdevDevice,
+};
+
+use super::{
+    client::SourceDeviceClient, InputError, SourceDeviceCompatible, SourceDriver,
+    SourceInputDevice, SourceOutputDevice,
+};
+
+/// Value of a scripted event. Booleans are button values, numbers are axis
+/// values, and lists of two or three numbers are vector values (e.g. stick
+/// positions).
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum VirtualScriptValue {
+    Bool(bool),
+    Float(f64),
+    Vector(Vec<f64>),
+}
+
+impl TryFrom<VirtualScriptValue> for InputValue {
+    type Error = String;
+
+    fn try_from(value: VirtualScriptValue) -> Result<Self, Self::Error> {
+        match value {
+            VirtualScriptValue::Bool(value) => Ok(InputValue::Bool(value)),
+            VirtualScriptValue::Float(value) => Ok(InputValue::Float(value)),
+            VirtualScriptValue::Vector(values) => match values.as_slice() {
+                [x, y] => Ok(InputValue::Vector2 {
+                    x: Some(*x),
+                    y: Some(*y),
+                }),
+                [x, y, z] => Ok(InputValue::Vector3 {
+                    x: Some(*x),
+                    y: Some(*y),
+                    z: Some(*z),
+                }),
+                _ => Err(format!("Invalid vector value: {values:?}")),
+            },
+        }
+    }
+}
+
+/// A single event of a [VirtualScript]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct VirtualScriptEvent {
+    /// Capability of the event. E.g. "Gamepad:Button:South"
+    pub capability: String,
+    pub value: VirtualScriptValue,
+    /// Time in milliseconds to wait after the previous event
+    pub delay_ms: Option<u64>,
+}
+
+/// Script of events replayed by a virtual source device. E.g.
+/// ```yaml
+/// capabilities:
+///   - Gamepad:Button:Start
+/// repeat: true
+/// events:
+///   - capability: Gamepad:Button:South
+///     value: true
+///     delay_ms: 500
+///   - capability: Gamepad:Axis:LeftStick
+///     value: [0.0, -1.0]
+///     delay_ms: 100
+/// ```
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct VirtualScript {
+    /// Capabilities of the device in addition to the ones used in the events,
+    /// such as capabilities that are only sent over DBus.
+    pub capabilities: Option<Vec<String>>,
+    /// Start from the first event again after the last event was replayed
+    pub repeat: Option<bool>,
+    pub events: Option<Vec<VirtualScriptEvent>>,
+}
+
+impl VirtualScript {
+    /// Load a [VirtualScript] from the given YAML file
+    pub fn from_yaml_file(path: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let data = fs::read_to_string(path)?;
+        let script = serde_yaml::from_str(data.as_str())?;
+        Ok(script)
+    }
+}
+
+/// Source device implementation that replays the events of a [VirtualScript]
+/// instead of reading from hardware. Used to test composite and target devices
+/// without real devices and for demo setups.
+pub struct ScriptedSource {
+    /// Events to replay with the delay before each of them
+    events: Vec<(Duration, NativeEvent)>,
+    capabilities: Vec<Capability>,
+    repeat: bool,
+    /// Index of the next event to replay
+    next: usize,
+    /// Time the last event was due, or the time replay started
+    last: Option<Instant>,
+}
+
+impl ScriptedSource {
+    /// Create a new scripted source from the given script
+    pub fn new(script: VirtualScript) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let parse_capability = |capability: &str| {
+            Capability::from_str(capability)
+                .map_err(|_| format!("Invalid capability in script: {capability}"))
+        };
+
+        let mut capabilities = Vec::new();
+        for capability in script.capabilities.unwrap_or_default() {
+            capabilities.push(parse_capability(capability.as_str())?);
+        }
+
+        let mut events = Vec::new();
+        for event in script.events.unwrap_or_default() {
+            let capability = parse_capability(event.capability.as_str())?;
+            let value = InputValue::try_from(event.value)?;
+            let delay = Duration::from_millis(event.delay_ms.unwrap_or_default());
+            if !capabilities.contains(&capability) {
+                capabilities.push(capability.clone());
+            }
+            events.push((delay, NativeEvent::new(capability, value)));
+        }
+
+        Ok(Self {
+            events,
+            capabilities,
+            repeat: script.repeat.unwrap_or(false),
+            next: 0,
+            last: None,
+        })
+    }
+
+    /// Returns the events that are due at the given time. Each event is
+    /// replayed at most once per call, even if the script repeats.
+    pub fn poll_at(&mut self, now: Instant) -> Vec<NativeEvent> {
+        let mut events = Vec::new();
+        let mut last = *self.last.get_or_insert(now);
+        while events.len() < self.events.len() {
+            if self.next >= self.events.len() {
+                if !self.repeat {
+                    break;
+                }
+                self.next = 0;
+            }
+            let (delay, event) = &self.events[self.next];
+            let due = last + *delay;
+            if now < due {
+                break;
+            }
+            events.push(event.clone());
+            last = due;
+            self.next += 1;
+            if self.next == self.events.len() && !self.repeat {
+                log::debug!("Finished replaying virtual source device script");
+            }
+        }
+        self.last = Some(last);
+
+        events
+    }
+}
+
+impl SourceInputDevice for ScriptedSource {
+    /// Poll the script for events that are due
+    fn poll(&mut self) -> Result<Vec<NativeEvent>, InputError> {
+        Ok(self.poll_at(Instant::now()))
+    }
+
+    /// Returns the possible input events this device is capable of emitting
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(self.capabilities.clone())
+    }
+}
+
+impl SourceOutputDevice for ScriptedSource {}
+
+impl Debug for ScriptedSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptedSource")
+            .field("capabilities", &self.capabilities)
+            .field("repeat", &self.repeat)
+            .finish()
+    }
+}
+
+/// [VirtualDevice] represents a source device that is not backed by any
+/// hardware. Virtual devices replay the script at the path of their devnode,
+/// if any, and can be sent events over DBus.
+#[derive(Debug)]
+pub struct VirtualDevice {
+    driver: SourceDriver<ScriptedSource>,
+}
+
+impl VirtualDevice {
+    /// Create a new [VirtualDevice] associated with the given device and
+    /// composite device
+    pub fn new(
+        device_info: UdevDevice,
+        composite_device: CompositeDeviceClient,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = device_info.devnode();
+        let script = if path.is_empty() {
+            VirtualScript::default()
+        } else {
+            VirtualScript::from_yaml_file(path.as_str())?
+        };
+        let device = ScriptedSource::new(script)?;
+        let driver = SourceDriver::new(composite_device, device, device_info);
+        Ok(Self { driver })
+    }
+}
+
+impl SourceDeviceCompatible for VirtualDevice {
+    fn get_device_ref(&self) -> &UdevDevice {
+        self.driver.info_ref()
+    }
+
+    fn get_id(&self) -> String {
+        self.driver.get_id()
+    }
+
+    fn client(&self) -> SourceDeviceClient {
+        self.driver.client()
+    }
+
+    async fn run(self) -> Result<(), Box<dyn Error>> {
+        self.driver.run().await
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        self.driver.get_capabilities()
+    }
+
+    fn get_device_path(&self) -> String {
+        self.driver.get_device_path()
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton},
+    event::value::InputValue,
+};
+
+use super::{
+    r#virtual::{ScriptedSource, VirtualScript},
+    SourceInputDevice,
+};
+
+const SCRIPT: &str = r#"
+capabilities:
+  - Gamepad:Button:Start
+repeat: true
+events:
+  - capability: Gamepad:Button:South
+    value: true
+    delay_ms: 100
+  - capability: Gamepad:Button:South
+    value: false
+  - capability: Gamepad:Axis:LeftStick
+    value: [0.0, -1.0]
+    delay_ms: 50
+"#;
+
+#[test]
+fn test_scripted_source() {
+    let script: VirtualScript = serde_yaml::from_str(SCRIPT).unwrap();
+    let mut source = ScriptedSource::new(script).unwrap();
+
+    // Capabilities should include the ones used by the events
+    let south = Capability::Gamepad(Gamepad::Button(GamepadButton::South));
+    let stick = Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick));
+    let capabilities = source.get_capabilities().unwrap();
+    assert!(capabilities.contains(&Capability::Gamepad(Gamepad::Button(GamepadButton::Start))));
+    assert!(capabilities.contains(&south));
+    assert!(capabilities.contains(&stick));
+
+    // Events should only be replayed once they are due
+    let start = Instant::now();
+    assert!(source.poll_at(start).is_empty());
+    let events = source.poll_at(start + Duration::from_millis(100));
+    assert_eq!(events.len(), 2, "Events without a delay should follow");
+    assert_eq!(events[0].as_capability(), south);
+    assert!(matches!(events[1].get_value(), InputValue::Bool(false)));
+
+    // Delays should be relative to the previous event
+    assert!(source
+        .poll_at(start + Duration::from_millis(140))
+        .is_empty());
+    let events = source.poll_at(start + Duration::from_millis(150));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].as_capability(), stick);
+
+    // Repeating scripts should start over
+    let events = source.poll_at(start + Duration::from_millis(250));
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].get_value(), InputValue::Bool(true)));
+}
+
+#[test]
+fn test_scripted_source_invalid() {
+    let script: VirtualScript = serde_yaml::from_str(
+        r#"
+events:
+  - capability: Gamepad:Button:Invalid
+    value: true
+"#,
+    )
+    .unwrap();
+    assert!(ScriptedSource::new(script).is_err());
+
+    let script: VirtualScript = serde_yaml::from_str(
+        r#"
+events:
+  - capability: Gamepad:Axis:LeftStick
+    value: [1.0]
+"#,
+    )
+    .unwrap();
+    assert!(ScriptedSource::new(script).is_err());
+}
@@ -0,0 +1,32 @@
+use tokio::sync::mpsc::Sender;
+
+use crate::input::{
+    capability::Capability, composite_device::client::CompositeDeviceClient,
+    event::native::NativeEvent,
+};
+
+/// A [TargetCommand] is a message that can be sent to a [TargetDevice] over
+/// a channel.
+#[derive(Debug, Clone)]
+pub enum TargetCommand {
+    /// Write the given event to the target device
+    WriteEvent(NativeEvent),
+    /// Write the given events to the target device as a single input frame
+    WriteEvents(Vec<NativeEvent>),
+    /// Set the given composite device on the target device
+    SetCompositeDevice(CompositeDeviceClient),
+    /// Return the input capabilities of the target device
+    GetCapabilities(Sender<Vec<Capability>>),
+    /// Return the type of target input device
+    GetType(Sender<String>),
+    /// Return the serial number the target device presents to the host
+    GetSerial(Sender<Option<String>>),
+    /// Clear all local state on the target device
+    ClearState,
+    /// Enable or disable writing input events to the target device
+    SetEnabled(bool),
+    /// Return whether or not the target device is enabled
+    GetEnabled(Sender<bool>),
+    /// Stop the target device
+    Stop,
+}
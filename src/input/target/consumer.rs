@@ -0,0 +1,127 @@
+use std::{collections::HashMap, error::Error};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, InputEvent, KeyCode,
+};
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::TargetInterface,
+    input::{
+        capability::{Capability, Keyboard},
+        event::{evdev::EvdevEvent, native::NativeEvent},
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Consumer control usages that can be emitted by the [ConsumerControlDevice]
+const CAPABILITIES: &[Keyboard] = &[
+    Keyboard::KeyMute,
+    Keyboard::KeyVolumeDown,
+    Keyboard::KeyVolumeUp,
+    Keyboard::KeyBrightnessDown,
+    Keyboard::KeyBrightnessUp,
+    Keyboard::KeyPlayPause,
+    Keyboard::KeyNextSong,
+    Keyboard::KeyPreviousSong,
+    Keyboard::KeyStopCD,
+];
+
+/// The [ConsumerControlDevice] is a virtual device that only emits consumer
+/// control usages like volume, brightness, and media keys. Desktops handle
+/// these keys natively, so they can be used to map chassis buttons or chords
+/// to system controls without emulating a full keyboard.
+#[derive(Debug)]
+pub struct ConsumerControlDevice {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+}
+
+impl ConsumerControlDevice {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let device = ConsumerControlDevice::create_virtual_device()?;
+        Ok(Self {
+            device,
+            axis_map: HashMap::new(),
+        })
+    }
+
+    /// Translate the given native event into an evdev event
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device() -> Result<VirtualDevice, Box<dyn Error>> {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::KEY_MUTE);
+        keys.insert(KeyCode::KEY_VOLUMEDOWN);
+        keys.insert(KeyCode::KEY_VOLUMEUP);
+        keys.insert(KeyCode::KEY_BRIGHTNESSDOWN);
+        keys.insert(KeyCode::KEY_BRIGHTNESSUP);
+        keys.insert(KeyCode::KEY_PLAYPAUSE);
+        keys.insert(KeyCode::KEY_NEXTSONG);
+        keys.insert(KeyCode::KEY_PREVIOUSSONG);
+        keys.insert(KeyCode::KEY_STOPCD);
+
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Consumer Control")
+            .with_keys(&keys)?
+            .build()?;
+
+        Ok(device)
+    }
+}
+
+impl TargetInputDevice for ConsumerControlDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        tokio::task::spawn(async move {
+            let iface = TargetInterface::new("Consumer Control".to_string(), client);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        if let Err(e) = self.device.emit(evdev_events.as_slice()) {
+            return Err(e.to_string().into());
+        }
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(CAPABILITIES
+            .iter()
+            .map(|key| Capability::Keyboard(key.clone()))
+            .collect())
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for ConsumerControlDevice {}
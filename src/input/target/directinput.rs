@@ -0,0 +1,229 @@
+use std::{collections::HashMap, error::Error};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, EventType, InputEvent, KeyCode, UinputAbsSetup,
+};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    event::{
+        evdev::{apply_axis_ranges, EvdevEvent},
+        native::NativeEvent,
+        value::InputValue,
+    },
+};
+
+use super::{InputError, TargetInputDevice, TargetOutputDevice};
+
+/// Analog trigger values at or above this threshold press the trigger button
+const TRIGGER_THRESHOLD: f64 = 0.5;
+
+/// The [DirectInputDevice] is a generic DirectInput-style joystick, like the
+/// PlayStation Classic controller or the Logitech Dual Action. It has no xpad
+/// quirks: buttons use the plain BTN_JOYSTICK range, the sticks use
+/// ABS_X/ABS_Y and ABS_Z/ABS_RZ, and the DPad is a hat. Legacy and native
+/// Linux games that only know generic joysticks often handle this layout
+/// better than xpad devices.
+#[derive(Debug)]
+pub struct DirectInputDevice {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+}
+
+impl DirectInputDevice {
+    pub fn new(axis_ranges: &HashMap<AbsoluteAxisCode, AbsInfo>) -> Result<Self, Box<dyn Error>> {
+        let mut axis_map = DirectInputDevice::get_abs_info();
+        apply_axis_ranges(&mut axis_map, axis_ranges);
+        let device = DirectInputDevice::create_virtual_device(&axis_map)?;
+        Ok(Self { device, axis_map })
+    }
+
+    /// Return a hashmap of ABS information for this virtual device. This information
+    /// is used to denormalize input event values. The right stick is
+    /// denormalized as ABS_RX/ABS_RY and emitted as ABS_Z/ABS_RZ.
+    fn get_abs_info() -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        let mut axes_info = HashMap::new();
+
+        let joystick_setup = AbsInfo::new(128, 0, 255, 0, 15, 1);
+        axes_info.insert(AbsoluteAxisCode::ABS_X, joystick_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_Y, joystick_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_RX, joystick_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_RY, joystick_setup);
+
+        let dpad_setup = AbsInfo::new(0, -1, 1, 0, 0, 1);
+        axes_info.insert(AbsoluteAxisCode::ABS_HAT0X, dpad_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_HAT0Y, dpad_setup);
+
+        axes_info
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(
+        axis_map: &HashMap<AbsoluteAxisCode, AbsInfo>,
+    ) -> Result<VirtualDevice, Box<dyn Error>> {
+        // Setup Key inputs
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::BTN_TRIGGER);
+        keys.insert(KeyCode::BTN_THUMB);
+        keys.insert(KeyCode::BTN_THUMB2);
+        keys.insert(KeyCode::BTN_TOP);
+        keys.insert(KeyCode::BTN_TOP2);
+        keys.insert(KeyCode::BTN_PINKIE);
+        keys.insert(KeyCode::BTN_BASE);
+        keys.insert(KeyCode::BTN_BASE2);
+        keys.insert(KeyCode::BTN_BASE3);
+        keys.insert(KeyCode::BTN_BASE4);
+        keys.insert(KeyCode::BTN_BASE5);
+        keys.insert(KeyCode::BTN_BASE6);
+        keys.insert(KeyCode::BTN_DEAD);
+
+        // Setup ABS inputs
+        let Some(left_setup) = axis_map.get(&AbsoluteAxisCode::ABS_X) else {
+            return Err("No axis information for ABS_X".to_string().into());
+        };
+        let abs_x = UinputAbsSetup::new(AbsoluteAxisCode::ABS_X, *left_setup);
+        let abs_y = UinputAbsSetup::new(AbsoluteAxisCode::ABS_Y, *left_setup);
+        let Some(right_setup) = axis_map.get(&AbsoluteAxisCode::ABS_RX) else {
+            return Err("No axis information for ABS_RX".to_string().into());
+        };
+        let abs_z = UinputAbsSetup::new(AbsoluteAxisCode::ABS_Z, *right_setup);
+        let abs_rz = UinputAbsSetup::new(AbsoluteAxisCode::ABS_RZ, *right_setup);
+        let Some(dpad_setup) = axis_map.get(&AbsoluteAxisCode::ABS_HAT0X) else {
+            return Err("No axis information for ABS_HAT0X".to_string().into());
+        };
+        let abs_hat0x = UinputAbsSetup::new(AbsoluteAxisCode::ABS_HAT0X, *dpad_setup);
+        let abs_hat0y = UinputAbsSetup::new(AbsoluteAxisCode::ABS_HAT0Y, *dpad_setup);
+
+        // Build the device
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber DirectInput Gamepad")
+            .with_keys(&keys)?
+            .with_absolute_axis(&abs_x)?
+            .with_absolute_axis(&abs_y)?
+            .with_absolute_axis(&abs_z)?
+            .with_absolute_axis(&abs_rz)?
+            .with_absolute_axis(&abs_hat0x)?
+            .with_absolute_axis(&abs_hat0y)?
+            .build()?;
+
+        Ok(device)
+    }
+
+    /// Translate the given native event into evdev events
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        let cap = event.as_capability();
+        let pressed = match event.get_value() {
+            InputValue::Float(value) => value >= TRIGGER_THRESHOLD,
+            value => value.pressed(),
+        };
+        match cap {
+            Capability::Gamepad(Gamepad::Button(ref button)) => {
+                if let Some(code) = joystick_button(button) {
+                    return vec![InputEvent::new(EventType::KEY.0, code.0, pressed as i32)];
+                }
+            }
+            // Analog triggers are reported as buttons
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)) => {
+                return vec![InputEvent::new(
+                    EventType::KEY.0,
+                    KeyCode::BTN_BASE.0,
+                    pressed as i32,
+                )];
+            }
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)) => {
+                return vec![InputEvent::new(
+                    EventType::KEY.0,
+                    KeyCode::BTN_BASE2.0,
+                    pressed as i32,
+                )];
+            }
+            _ => (),
+        }
+
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| {
+                let event = event.as_input_event();
+                let code = match AbsoluteAxisCode(event.code()) {
+                    AbsoluteAxisCode::ABS_RX => AbsoluteAxisCode::ABS_Z,
+                    AbsoluteAxisCode::ABS_RY => AbsoluteAxisCode::ABS_RZ,
+                    _ => return event,
+                };
+                InputEvent::new(event.event_type().0, code.0, event.value())
+            })
+            .collect()
+    }
+}
+
+impl TargetInputDevice for DirectInputDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        self.device.emit(evdev_events.as_slice())?;
+        Ok(())
+    }
+
+    fn write_events(&mut self, events: Vec<NativeEvent>) -> Result<(), InputError> {
+        // Emit all events of the frame with a single sync report
+        let evdev_events: Vec<InputEvent> = events
+            .into_iter()
+            .flat_map(|event| self.translate_event(event))
+            .collect();
+        if !evdev_events.is_empty() {
+            self.device.emit(evdev_events.as_slice())?;
+        }
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::RightTrigger)),
+        ])
+    }
+}
+
+impl TargetOutputDevice for DirectInputDevice {}
+
+/// Returns the joystick button code for the given gamepad button. Face
+/// buttons follow the numbering of common DirectInput pads, where button 1
+/// is the left face button.
+fn joystick_button(button: &GamepadButton) -> Option<KeyCode> {
+    let code = match button {
+        GamepadButton::West => KeyCode::BTN_TRIGGER,
+        GamepadButton::South => KeyCode::BTN_THUMB,
+        GamepadButton::East => KeyCode::BTN_THUMB2,
+        GamepadButton::North => KeyCode::BTN_TOP,
+        GamepadButton::LeftBumper => KeyCode::BTN_TOP2,
+        GamepadButton::RightBumper => KeyCode::BTN_PINKIE,
+        GamepadButton::LeftTrigger => KeyCode::BTN_BASE,
+        GamepadButton::RightTrigger => KeyCode::BTN_BASE2,
+        GamepadButton::Select => KeyCode::BTN_BASE3,
+        GamepadButton::Start => KeyCode::BTN_BASE4,
+        GamepadButton::LeftStick => KeyCode::BTN_BASE5,
+        GamepadButton::RightStick => KeyCode::BTN_BASE6,
+        GamepadButton::Guide => KeyCode::BTN_DEAD,
+        _ => return None,
+    };
+    Some(code)
+}
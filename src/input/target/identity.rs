@@ -0,0 +1,144 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::config::path::get_state_path;
+
+/// Stable identity of a virtual target device, presented to the host as its
+/// unique id (uniq) and serial number. Games and Steam use these to recognize
+/// a controller that reconnects (e.g. after suspend or a profile change), so
+/// the identity is derived from the composite device the target belongs to
+/// and persisted, instead of being randomly generated every time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetIdentity {
+    /// Locally administered MAC-style address
+    address: [u8; 6],
+}
+
+impl TargetIdentity {
+    /// Derive the identity of the given kind of target device from the given
+    /// composite device id
+    pub fn derive(device_id: &str, kind: &str) -> Self {
+        let hash = fnv1a(format!("{device_id}/{kind}").as_bytes());
+        let bytes = hash.to_be_bytes();
+        let mut address = [0; 6];
+        address.copy_from_slice(&bytes[..6]);
+        // Mark the address as locally administered and unicast so it never
+        // collides with the address of real hardware.
+        address[0] = (address[0] | 0x02) & 0xfe;
+        Self { address }
+    }
+
+    /// Returns the address bytes in display order
+    pub fn address(&self) -> [u8; 6] {
+        self.address
+    }
+
+    /// Returns the unique id of the device, formatted like a Bluetooth
+    /// address. E.g. "e2:47:3a:d6:e7:74"
+    pub fn uniq(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the serial number of the device, as 12 uppercase hexadecimal
+    /// characters. E.g. "E2473AD6E774"
+    pub fn serial(&self) -> String {
+        self.address
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect()
+    }
+}
+
+impl fmt::Display for TargetIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self
+            .address
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        write!(f, "{}", parts.join(":"))
+    }
+}
+
+impl FromStr for TargetIdentity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() != 6 {
+            return Err(format!("Invalid target device identity: {s}"));
+        }
+        let mut address = [0; 6];
+        for (byte, part) in address.iter_mut().zip(parts) {
+            *byte = u8::from_str_radix(part, 16)
+                .map_err(|_| format!("Invalid target device identity: {s}"))?;
+        }
+        Ok(Self { address })
+    }
+}
+
+/// Returns the identity of the given kind of target device for the composite
+/// device with the given id. The identity is loaded if it was persisted
+/// before, otherwise it is derived from the id and persisted.
+pub fn load_or_create(device_id: &str, kind: &str) -> TargetIdentity {
+    let path = identity_path(device_id);
+    let mut identities = load_identities(&path).unwrap_or_default();
+    if let Some(identity) = identities.get(kind) {
+        match TargetIdentity::from_str(identity) {
+            Ok(identity) => return identity,
+            Err(e) => log::warn!("Ignoring persisted identity in {path:?}: {e}"),
+        }
+    }
+
+    let identity = TargetIdentity::derive(device_id, kind);
+    log::debug!("Created identity {identity} for {kind} target of {device_id}");
+    identities.insert(kind.to_string(), identity.to_string());
+    if let Err(e) = save_identities(&path, &identities) {
+        log::error!("Failed to save target device identity to {path:?}: {e}");
+    }
+    identity
+}
+
+/// Returns the path to persist the target device identities of the composite
+/// device with the given id to
+fn identity_path(device_id: &str) -> PathBuf {
+    get_state_path()
+        .join("targets")
+        .join(format!("{device_id}.yaml"))
+}
+
+/// Load the persisted identities by target device kind from the given file
+fn load_identities(path: &Path) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let data = fs::read_to_string(path)?;
+    let identities = serde_yaml::from_str(data.as_str())?;
+    Ok(identities)
+}
+
+/// Save the given identities by target device kind to the given file
+fn save_identities(
+    path: &Path,
+    identities: &BTreeMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let data = serde_yaml::to_string(identities)?;
+    fs::write(path, data)?;
+    Ok(())
+}
+
+/// 64-bit FNV-1a hash, which unlike the standard library hasher is
+/// guaranteed to give the same result across releases
+fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
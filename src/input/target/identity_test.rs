@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+use super::identity::TargetIdentity;
+
+#[test]
+fn test_derive_identity() {
+    let identity = TargetIdentity::derive("Xbox_Controller-usb_0000_00_14_0_2", "xb360");
+
+    // The same device and kind should always get the same identity
+    let again = TargetIdentity::derive("Xbox_Controller-usb_0000_00_14_0_2", "xb360");
+    assert_eq!(identity, again);
+
+    // Other devices of the same kind and other kinds should differ
+    let other = TargetIdentity::derive("Xbox_Controller-usb_0000_00_14_0_3", "xb360");
+    assert_ne!(identity, other);
+    let other = TargetIdentity::derive("Xbox_Controller-usb_0000_00_14_0_2", "ds5");
+    assert_ne!(identity, other);
+
+    // Addresses should be locally administered and unicast
+    let address = identity.address();
+    assert_eq!(address[0] & 0x02, 0x02);
+    assert_eq!(address[0] & 0x01, 0x00);
+}
+
+#[test]
+fn test_identity_round_trip() {
+    let identity = TargetIdentity::derive("Xbox_Controller", "xb360");
+    let uniq = identity.uniq();
+    assert_eq!(uniq.len(), 17);
+    assert_eq!(TargetIdentity::from_str(uniq.as_str()), Ok(identity));
+    assert_eq!(identity.serial(), uniq.replace(':', "").to_uppercase());
+
+    let identity = TargetIdentity::from_str("e2:47:3a:d6:e7:74").unwrap();
+    assert_eq!(identity.to_string(), "e2:47:3a:d6:e7:74");
+    assert_eq!(identity.serial(), "E2473AD6E774");
+
+    // Invalid identities should be rejected
+    assert!(TargetIdentity::from_str("").is_err());
+    assert!(TargetIdentity::from_str("e2:47:3a:d6:e7").is_err());
+    assert!(TargetIdentity::from_str("e2:47:3a:d6:e7:zz").is_err());
+}
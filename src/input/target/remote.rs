@@ -0,0 +1,316 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+use zbus::Connection;
+
+use crate::{
+    dbus::interface::target::remote::TargetRemoteInterface,
+    input::{
+        capability::{Capability, Gamepad, GamepadAxis, GamepadButton, Keyboard},
+        event::{dbus::Action, native::NativeEvent, value::InputValue},
+    },
+};
+
+use super::{client::TargetDeviceClient, InputError, TargetInputDevice, TargetOutputDevice};
+
+/// The threshold for axis inputs to be considered "pressed"
+const AXIS_THRESHOLD: f64 = 0.60;
+
+/// Remote control command emitted by a [RemoteDevice]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemoteCommand {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+    Home,
+    Context,
+}
+
+impl RemoteCommand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RemoteCommand::Up => "up",
+            RemoteCommand::Down => "down",
+            RemoteCommand::Left => "left",
+            RemoteCommand::Right => "right",
+            RemoteCommand::Select => "select",
+            RemoteCommand::Back => "back",
+            RemoteCommand::Home => "home",
+            RemoteCommand::Context => "context",
+        }
+    }
+}
+
+impl fmt::Display for RemoteCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Returns the remote command the given button capability triggers, if any.
+/// Buttons of gamepads, keyboards, and DBus actions all map onto the same
+/// commands so the layout of the source device does not matter.
+fn command_from_button(capability: &Capability) -> Option<RemoteCommand> {
+    let command = match capability {
+        Capability::Gamepad(Gamepad::Button(button)) => match button {
+            GamepadButton::DPadUp => RemoteCommand::Up,
+            GamepadButton::DPadDown => RemoteCommand::Down,
+            GamepadButton::DPadLeft => RemoteCommand::Left,
+            GamepadButton::DPadRight => RemoteCommand::Right,
+            GamepadButton::South => RemoteCommand::Select,
+            GamepadButton::East => RemoteCommand::Back,
+            GamepadButton::Guide => RemoteCommand::Home,
+            GamepadButton::North | GamepadButton::Start => RemoteCommand::Context,
+            _ => return None,
+        },
+        Capability::Keyboard(key) => match key {
+            Keyboard::KeyUp => RemoteCommand::Up,
+            Keyboard::KeyDown => RemoteCommand::Down,
+            Keyboard::KeyLeft => RemoteCommand::Left,
+            Keyboard::KeyRight => RemoteCommand::Right,
+            Keyboard::KeyEnter | Keyboard::KeyKpEnter | Keyboard::KeySpace => RemoteCommand::Select,
+            Keyboard::KeyEsc | Keyboard::KeyBackspace | Keyboard::KeyBack => RemoteCommand::Back,
+            Keyboard::KeyHome | Keyboard::KeyLeftMeta | Keyboard::KeyRightMeta => {
+                RemoteCommand::Home
+            }
+            Keyboard::KeyCompose | Keyboard::KeyProps => RemoteCommand::Context,
+            _ => return None,
+        },
+        Capability::DBus(action) => match action {
+            Action::Up => RemoteCommand::Up,
+            Action::Down => RemoteCommand::Down,
+            Action::Left => RemoteCommand::Left,
+            Action::Right => RemoteCommand::Right,
+            Action::Accept => RemoteCommand::Select,
+            Action::Back => RemoteCommand::Back,
+            Action::Guide => RemoteCommand::Home,
+            Action::Context => RemoteCommand::Context,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// The [RemoteDevice] is a virtual input device that translates input from
+/// any source layout into remote control commands (navigate, select, back,
+/// home, and context), which are emitted as DBus signals. Media center shells
+/// can use it to consume one consistent interface regardless of whether a
+/// gamepad, keyboard, or IR remote is used.
+#[derive(Debug)]
+pub struct RemoteDevice {
+    conn: Connection,
+    dbus_path: Option<String>,
+    /// Last known position of each axis
+    axes: HashMap<Capability, (f64, f64)>,
+    /// Source capabilities holding each remote command
+    held: HashMap<RemoteCommand, HashSet<Capability>>,
+}
+
+impl RemoteDevice {
+    // Create a new [RemoteDevice] instance.
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn,
+            dbus_path: None,
+            axes: HashMap::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    /// Translate the given native event into remote commands that were
+    /// pressed or released. A command stays pressed while any source
+    /// capability is holding it.
+    fn translate_event(&mut self, event: NativeEvent) -> Vec<(RemoteCommand, bool)> {
+        let capability = event.as_capability();
+        let states = match (&capability, event.get_value()) {
+            (
+                Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick | GamepadAxis::Hat0)),
+                InputValue::Vector2 { x, y },
+            ) => {
+                let position = self.axes.entry(capability.clone()).or_insert((0.0, 0.0));
+                if let Some(x) = x {
+                    position.0 = x;
+                }
+                if let Some(y) = y {
+                    position.1 = y;
+                }
+                let (x, y) = *position;
+                vec![
+                    (RemoteCommand::Left, x < -AXIS_THRESHOLD),
+                    (RemoteCommand::Right, x > AXIS_THRESHOLD),
+                    (RemoteCommand::Up, y < -AXIS_THRESHOLD),
+                    (RemoteCommand::Down, y > AXIS_THRESHOLD),
+                ]
+            }
+            (_, value) => {
+                let Some(command) = command_from_button(&capability) else {
+                    return vec![];
+                };
+                let pressed = match value {
+                    InputValue::Bool(pressed) => pressed,
+                    InputValue::Float(value) => value > AXIS_THRESHOLD,
+                    _ => return vec![],
+                };
+                vec![(command, pressed)]
+            }
+        };
+
+        let mut changed = Vec::new();
+        for (command, pressed) in states {
+            let holders = self.held.entry(command).or_default();
+            let was_pressed = !holders.is_empty();
+            if pressed {
+                holders.insert(capability.clone());
+            } else {
+                holders.remove(&capability);
+            }
+            let is_pressed = !holders.is_empty();
+            if was_pressed != is_pressed {
+                changed.push((command, is_pressed));
+            }
+        }
+
+        changed
+    }
+
+    /// Emits the given remote command as a DBus signal
+    fn write_command(&self, command: RemoteCommand, pressed: bool) -> Result<(), Box<dyn Error>> {
+        // Signals can only be sent if there is a DBus path reference.
+        let Some(path) = self.dbus_path.clone() else {
+            return Err("No dbus path exists to send commands to".into());
+        };
+
+        let conn = self.conn.clone();
+        tokio::task::spawn(async move {
+            let iface_ref = match conn
+                .object_server()
+                .interface::<_, TargetRemoteInterface>(path.as_str())
+                .await
+            {
+                Ok(iface) => iface,
+                Err(e) => {
+                    log::error!("Failed to get interface: {e:?}");
+                    return;
+                }
+            };
+            let result = TargetRemoteInterface::command(
+                iface_ref.signal_context(),
+                command.as_str(),
+                pressed,
+            )
+            .await;
+            if let Err(e) = result {
+                log::error!("Failed to send remote command: {e:?}");
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl TargetInputDevice for RemoteDevice {
+    fn start_dbus_interface(&mut self, dbus: Connection, path: String, client: TargetDeviceClient) {
+        log::debug!("Starting dbus interface: {path}");
+        self.dbus_path = Some(path.clone());
+        tokio::task::spawn(async move {
+            let iface = TargetRemoteInterface::new(client);
+            if let Err(e) = dbus.object_server().at(path.clone(), iface).await {
+                log::debug!("Failed to start dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Started dbus interface on {path}");
+            };
+        });
+    }
+
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Got event to emit: {:?}", event);
+        for (command, pressed) in self.translate_event(event) {
+            log::trace!("Writing remote command: {command} pressed={pressed}");
+            self.write_command(command, pressed)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        let capabilities = vec![
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::LeftStick)),
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::Hat0)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Keyboard(Keyboard::KeyUp),
+            Capability::Keyboard(Keyboard::KeyDown),
+            Capability::Keyboard(Keyboard::KeyLeft),
+            Capability::Keyboard(Keyboard::KeyRight),
+            Capability::Keyboard(Keyboard::KeyEnter),
+            Capability::Keyboard(Keyboard::KeyKpEnter),
+            Capability::Keyboard(Keyboard::KeySpace),
+            Capability::Keyboard(Keyboard::KeyEsc),
+            Capability::Keyboard(Keyboard::KeyBackspace),
+            Capability::Keyboard(Keyboard::KeyBack),
+            Capability::Keyboard(Keyboard::KeyHome),
+            Capability::Keyboard(Keyboard::KeyLeftMeta),
+            Capability::Keyboard(Keyboard::KeyRightMeta),
+            Capability::Keyboard(Keyboard::KeyCompose),
+            Capability::Keyboard(Keyboard::KeyProps),
+            Capability::DBus(Action::Up),
+            Capability::DBus(Action::Down),
+            Capability::DBus(Action::Left),
+            Capability::DBus(Action::Right),
+            Capability::DBus(Action::Accept),
+            Capability::DBus(Action::Back),
+            Capability::DBus(Action::Guide),
+            Capability::DBus(Action::Context),
+        ];
+
+        Ok(capabilities)
+    }
+
+    /// Release all held commands
+    fn clear_state(&mut self) {
+        let held: Vec<RemoteCommand> = self
+            .held
+            .iter()
+            .filter(|(_, holders)| !holders.is_empty())
+            .map(|(command, _)| *command)
+            .collect();
+        self.held.clear();
+        self.axes.clear();
+        for command in held {
+            if let Err(e) = self.write_command(command, false) {
+                log::debug!("Failed to release remote command {command}: {e:?}");
+            }
+        }
+    }
+
+    fn stop_dbus_interface(&mut self, dbus: Connection, path: String) {
+        log::debug!("Stopping dbus interface for {path}");
+        tokio::task::spawn(async move {
+            let result = dbus
+                .object_server()
+                .remove::<TargetRemoteInterface, String>(path.clone())
+                .await;
+            if let Err(e) = result {
+                log::error!("Failed to stop dbus interface {path}: {e:?}");
+            } else {
+                log::debug!("Stopped dbus interface for {path}");
+            };
+        });
+    }
+}
+
+impl TargetOutputDevice for RemoteDevice {}
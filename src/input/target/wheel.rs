@@ -0,0 +1,165 @@
+use std::{collections::HashMap, error::Error};
+
+use evdev::{
+    uinput::{VirtualDevice, VirtualDeviceBuilder},
+    AbsInfo, AbsoluteAxisCode, AttributeSet, InputEvent, KeyCode, UinputAbsSetup,
+};
+
+use crate::input::{
+    capability::{Capability, Gamepad, GamepadAxis, GamepadButton, GamepadTrigger},
+    event::{
+        evdev::{apply_axis_ranges, EvdevEvent},
+        native::NativeEvent,
+    },
+};
+
+use super::{InputError, TargetInputDevice, TargetOutputDevice};
+
+/// The [WheelDevice] is a generic driving wheel with a steering axis, throttle,
+/// brake, and clutch pedals, and an H-shifter. The axis layout matches common
+/// wheels like the Logitech G29 so games recognize it without extra setup.
+#[derive(Debug)]
+pub struct WheelDevice {
+    device: VirtualDevice,
+    axis_map: HashMap<AbsoluteAxisCode, AbsInfo>,
+}
+
+impl WheelDevice {
+    pub fn new(axis_ranges: &HashMap<AbsoluteAxisCode, AbsInfo>) -> Result<Self, Box<dyn Error>> {
+        let mut axis_map = WheelDevice::get_abs_info();
+        apply_axis_ranges(&mut axis_map, axis_ranges);
+        let device = WheelDevice::create_virtual_device(&axis_map)?;
+        Ok(Self { device, axis_map })
+    }
+
+    /// Return a hashmap of ABS information for this virtual device. This information
+    /// is used to denormalize input event values.
+    fn get_abs_info() -> HashMap<AbsoluteAxisCode, AbsInfo> {
+        let mut axes_info = HashMap::new();
+
+        let steering_setup = AbsInfo::new(0, -32768, 32767, 0, 0, 1);
+        axes_info.insert(AbsoluteAxisCode::ABS_X, steering_setup);
+
+        let pedal_setup = AbsInfo::new(0, 0, 255, 0, 0, 1);
+        axes_info.insert(AbsoluteAxisCode::ABS_Y, pedal_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_Z, pedal_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_RZ, pedal_setup);
+
+        let dpad_setup = AbsInfo::new(0, -1, 1, 0, 0, 1);
+        axes_info.insert(AbsoluteAxisCode::ABS_HAT0X, dpad_setup);
+        axes_info.insert(AbsoluteAxisCode::ABS_HAT0Y, dpad_setup);
+
+        axes_info
+    }
+
+    /// Create the virtual device to emulate
+    fn create_virtual_device(
+        axis_map: &HashMap<AbsoluteAxisCode, AbsInfo>,
+    ) -> Result<VirtualDevice, Box<dyn Error>> {
+        // Setup Key inputs
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::BTN_SOUTH);
+        keys.insert(KeyCode::BTN_EAST);
+        keys.insert(KeyCode::BTN_NORTH);
+        keys.insert(KeyCode::BTN_WEST);
+        keys.insert(KeyCode::BTN_TL);
+        keys.insert(KeyCode::BTN_TR);
+        keys.insert(KeyCode::BTN_TL2);
+        keys.insert(KeyCode::BTN_TR2);
+        keys.insert(KeyCode::BTN_SELECT);
+        keys.insert(KeyCode::BTN_START);
+        keys.insert(KeyCode::BTN_MODE);
+        keys.insert(KeyCode::BTN_THUMBL);
+        keys.insert(KeyCode::BTN_THUMBR);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY11);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY12);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY13);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY14);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY15);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY16);
+        keys.insert(KeyCode::BTN_TRIGGER_HAPPY17);
+
+        // Setup ABS inputs
+        let Some(steering_setup) = axis_map.get(&AbsoluteAxisCode::ABS_X) else {
+            return Err("No axis information for ABS_X".to_string().into());
+        };
+        let abs_x = UinputAbsSetup::new(AbsoluteAxisCode::ABS_X, *steering_setup);
+        let Some(pedal_setup) = axis_map.get(&AbsoluteAxisCode::ABS_Z) else {
+            return Err("No axis information for ABS_Z".to_string().into());
+        };
+        let abs_y = UinputAbsSetup::new(AbsoluteAxisCode::ABS_Y, *pedal_setup);
+        let abs_z = UinputAbsSetup::new(AbsoluteAxisCode::ABS_Z, *pedal_setup);
+        let abs_rz = UinputAbsSetup::new(AbsoluteAxisCode::ABS_RZ, *pedal_setup);
+        let Some(dpad_setup) = axis_map.get(&AbsoluteAxisCode::ABS_HAT0X) else {
+            return Err("No axis information for ABS_HAT0X".to_string().into());
+        };
+        let abs_hat0x = UinputAbsSetup::new(AbsoluteAxisCode::ABS_HAT0X, *dpad_setup);
+        let abs_hat0y = UinputAbsSetup::new(AbsoluteAxisCode::ABS_HAT0Y, *dpad_setup);
+
+        // Build the device
+        let device = VirtualDeviceBuilder::new()?
+            .name("InputPlumber Wheel")
+            .with_keys(&keys)?
+            .with_absolute_axis(&abs_x)?
+            .with_absolute_axis(&abs_y)?
+            .with_absolute_axis(&abs_z)?
+            .with_absolute_axis(&abs_rz)?
+            .with_absolute_axis(&abs_hat0x)?
+            .with_absolute_axis(&abs_hat0y)?
+            .build()?;
+
+        Ok(device)
+    }
+
+    /// Translate the given native event into an evdev event
+    fn translate_event(&self, event: NativeEvent) -> Vec<InputEvent> {
+        EvdevEvent::from_native_event(event, self.axis_map.clone())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect()
+    }
+}
+
+impl TargetInputDevice for WheelDevice {
+    fn write_event(&mut self, event: NativeEvent) -> Result<(), InputError> {
+        log::trace!("Received event: {event:?}");
+        let evdev_events = self.translate_event(event);
+        self.device.emit(evdev_events.as_slice())?;
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Result<Vec<Capability>, InputError> {
+        Ok(vec![
+            Capability::Gamepad(Gamepad::Axis(GamepadAxis::Steering)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadDown)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadLeft)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadRight)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::DPadUp)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::East)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Guide)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::LeftTrigger)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::North)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightBumper)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightStick)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::RightTrigger)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Select)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear1)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear2)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear3)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear4)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear5)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterGear6)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::ShifterReverse)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::South)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::Start)),
+            Capability::Gamepad(Gamepad::Button(GamepadButton::West)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::Brake)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::Clutch)),
+            Capability::Gamepad(Gamepad::Trigger(GamepadTrigger::Throttle)),
+        ])
+    }
+}
+
+impl TargetOutputDevice for WheelDevice {}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+/// Time without a heartbeat after which a device task is considered stalled
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum number of times a stalled device task is restarted before the
+/// device is given up on
+pub const MAX_RESTARTS: u32 = 3;
+/// Time a restarted device task must keep beating without stalling again
+/// before its earlier stalls are forgotten
+pub const RESTART_RESET_TIME: Duration = Duration::from_secs(60);
+
+/// Error returned by a device task that stopped sending heartbeats and was
+/// abandoned by its supervisor
+#[derive(Error, Debug)]
+#[error("{name} stalled for {stalled_for:?} while in stage '{stage}'")]
+pub struct TaskStalled {
+    /// Name of the task. E.g. the id of a source device or the kind of a
+    /// target device.
+    pub name: String,
+    /// Time since the last heartbeat of the task
+    pub stalled_for: Duration,
+    /// Stage of its loop the task was last seen in
+    pub stage: &'static str,
+}
+
+/// Heartbeat shared between a device task running on a blocking thread and
+/// the supervisor watching it. The task beats on every iteration of its loop,
+/// and records the stage it is in so a stall can be diagnosed.
+#[derive(Debug)]
+pub struct Heartbeat {
+    start: Instant,
+    /// Time of the last beat in milliseconds since start
+    last_beat: AtomicU64,
+    /// Total number of beats
+    beats: AtomicU64,
+    /// Stage of the loop the task is in
+    stage: Mutex<&'static str>,
+    /// Set by the supervisor once it has given up on the task
+    abandoned: AtomicBool,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heartbeat {
+    /// Create a new heartbeat that starts beating now
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_beat: AtomicU64::new(0),
+            beats: AtomicU64::new(0),
+            stage: Mutex::new("start"),
+            abandoned: AtomicBool::new(false),
+        }
+    }
+
+    /// Signal that the task is alive and entering the given stage of its loop
+    pub fn beat(&self, stage: &'static str) {
+        let elapsed = self.start.elapsed().as_millis() as u64;
+        self.last_beat.store(elapsed, Ordering::Relaxed);
+        self.beats.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut current) = self.stage.lock() {
+            *current = stage;
+        }
+    }
+
+    /// Returns the time since the last beat at the given time
+    pub fn since_last_beat(&self, now: Instant) -> Duration {
+        let last_beat = Duration::from_millis(self.last_beat.load(Ordering::Relaxed));
+        now.duration_since(self.start).saturating_sub(last_beat)
+    }
+
+    /// Returns the total number of beats
+    pub fn beats(&self) -> u64 {
+        self.beats.load(Ordering::Relaxed)
+    }
+
+    /// Returns the stage of the loop the task was last seen in
+    pub fn stage(&self) -> &'static str {
+        self.stage.lock().map(|stage| *stage).unwrap_or("unknown")
+    }
+
+    /// Give up on the task. A task that was stuck must stop as soon as it
+    /// notices, since a replacement may already be running.
+    pub fn abandon(&self) {
+        self.abandoned.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the supervisor gave up on the task
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned.load(Ordering::Relaxed)
+    }
+}
+
+/// Counts how often device tasks stalled, mapped by task name. Stalls are
+/// forgotten once a task has been healthy for [RESTART_RESET_TIME], so a
+/// device that only stalls once in a while is never given up on.
+#[derive(Debug, Default)]
+pub struct RestartCounter {
+    /// Number of stalls and the time of the last stall of each task
+    stalls: HashMap<String, (u32, Instant)>,
+}
+
+impl RestartCounter {
+    /// Record a stall of the task with the given name at the given time.
+    /// Returns the number of times the task stalled since it was last healthy.
+    pub fn record(&mut self, name: &str, now: Instant) -> u32 {
+        let (count, last_stall) = self.stalls.entry(name.to_string()).or_insert((0, now));
+        if now.saturating_duration_since(*last_stall) >= RESTART_RESET_TIME {
+            *count = 0;
+        }
+        *count += 1;
+        *last_stall = now;
+        *count
+    }
+
+    /// Forget the stalls of the task with the given name, e.g. when its
+    /// device was reconnected
+    pub fn reset(&mut self, name: &str) {
+        self.stalls.remove(name);
+    }
+}
+
+/// Watch the heartbeat of the task with the given name and complete once it
+/// has not beaten for the given timeout. Returns the error describing the
+/// stall.
+pub async fn supervise(name: &str, heartbeat: &Heartbeat, timeout: Duration) -> TaskStalled {
+    let interval = timeout / 4;
+    loop {
+        tokio::time::sleep(interval).await;
+        let stalled_for = heartbeat.since_last_beat(Instant::now());
+        if stalled_for >= timeout {
+            return TaskStalled {
+                name: name.to_string(),
+                stalled_for,
+                stage: heartbeat.stage(),
+            };
+        }
+    }
+}
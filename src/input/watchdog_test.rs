@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+use super::watchdog::{supervise, Heartbeat, RestartCounter, RESTART_RESET_TIME};
+
+#[test]
+fn test_heartbeat() {
+    let heartbeat = Heartbeat::new();
+    heartbeat.beat("poll");
+    heartbeat.beat("send");
+    assert_eq!(heartbeat.beats(), 2);
+    assert_eq!(heartbeat.stage(), "send");
+
+    let later = Instant::now() + Duration::from_secs(6);
+    assert!(heartbeat.since_last_beat(later) >= Duration::from_secs(5));
+    assert!(heartbeat.since_last_beat(Instant::now()) < Duration::from_secs(5));
+
+    assert!(!heartbeat.is_abandoned());
+    heartbeat.abandon();
+    assert!(heartbeat.is_abandoned());
+}
+
+#[tokio::test]
+async fn test_supervise() {
+    let heartbeat = Heartbeat::new();
+    heartbeat.beat("poll");
+    let stalled = supervise("xb360", &heartbeat, Duration::from_millis(40)).await;
+    assert_eq!(stalled.name, "xb360");
+    assert!(stalled.stalled_for >= Duration::from_millis(40));
+    assert_eq!(stalled.stage, "poll");
+}
+
+#[test]
+fn test_restart_counter() {
+    let mut restarts = RestartCounter::default();
+    let start = Instant::now();
+    assert_eq!(restarts.record("xb360", start), 1);
+    assert_eq!(restarts.record("xb360", start + Duration::from_secs(10)), 2);
+    assert_eq!(restarts.record("ds5", start + Duration::from_secs(10)), 1);
+
+    // Stalls should be forgotten after the task was healthy long enough
+    let later = start + Duration::from_secs(10) + RESTART_RESET_TIME;
+    assert_eq!(restarts.record("xb360", later), 1);
+
+    // Stalls should be forgotten when the device reconnects
+    restarts.reset("ds5");
+    assert_eq!(restarts.record("ds5", later), 1);
+}
@@ -0,0 +1,210 @@
+use std::{collections::HashMap, error::Error, fmt, fs::OpenOptions};
+
+use evdev::{EventType, InputEvent, KeyCode, RelativeAxisCode};
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xproto::{
+            Window, BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+            MOTION_NOTIFY_EVENT,
+        },
+        xtest::ConnectionExt,
+    },
+    rust_connection::RustConnection,
+};
+
+use super::event::{evdev::EvdevEvent, native::NativeEvent};
+
+/// Path to the uinput device used to create virtual input devices
+const UINPUT_PATH: &str = "/dev/uinput";
+/// Offset between evdev key codes and X11 keycodes
+const X11_KEYCODE_OFFSET: u16 = 8;
+
+/// Backend used to emulate keyboard and mouse input on the desktop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputBackend {
+    /// Virtual input devices are created with uinput
+    #[default]
+    Uinput,
+    /// Input is injected into the X server with the XTEST extension
+    XTest,
+}
+
+impl fmt::Display for InputBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputBackend::Uinput => write!(f, "uinput"),
+            InputBackend::XTest => write!(f, "xtest"),
+        }
+    }
+}
+
+/// Returns true if virtual input devices can be created with uinput. This can
+/// be false inside of containers or sandboxes (e.g. Flatpak) where access to
+/// "/dev/uinput" is blocked.
+pub fn uinput_available() -> bool {
+    OpenOptions::new().write(true).open(UINPUT_PATH).is_ok()
+}
+
+/// A single input faked with the XTEST extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FakeInput {
+    /// X11 event type (e.g. KeyPress or MotionNotify)
+    pub kind: u8,
+    /// Keycode or button of the event. For motion events, this is 1 if the
+    /// motion is relative to the current pointer position.
+    pub detail: u8,
+    pub x: i16,
+    pub y: i16,
+}
+
+impl FakeInput {
+    fn new(kind: u8, detail: u8) -> Self {
+        Self {
+            kind,
+            detail,
+            x: 0,
+            y: 0,
+        }
+    }
+}
+
+/// Returns the X11 pointer button for the given evdev button
+fn pointer_button(code: KeyCode) -> Option<u8> {
+    match code {
+        KeyCode::BTN_LEFT => Some(1),
+        KeyCode::BTN_MIDDLE => Some(2),
+        KeyCode::BTN_RIGHT => Some(3),
+        KeyCode::BTN_SIDE => Some(8),
+        KeyCode::BTN_EXTRA => Some(9),
+        _ => None,
+    }
+}
+
+/// Translate the given evdev events into XTEST inputs. Relative motion in the
+/// given events is combined into a single motion input and wheel steps are
+/// emitted as a press and release of the X11 wheel buttons.
+pub fn fake_inputs(events: &[InputEvent]) -> Vec<FakeInput> {
+    let mut inputs = Vec::new();
+    let mut motion = (0, 0);
+    for event in events {
+        match event.event_type() {
+            EventType::KEY => {
+                let pressed = event.value() != 0;
+                let code = KeyCode::new(event.code());
+                let input = match pointer_button(code) {
+                    Some(button) if pressed => FakeInput::new(BUTTON_PRESS_EVENT, button),
+                    Some(button) => FakeInput::new(BUTTON_RELEASE_EVENT, button),
+                    None => {
+                        // X11 keycodes are limited to a single byte
+                        let Ok(keycode) = u8::try_from(event.code() + X11_KEYCODE_OFFSET) else {
+                            continue;
+                        };
+                        if pressed {
+                            FakeInput::new(KEY_PRESS_EVENT, keycode)
+                        } else {
+                            FakeInput::new(KEY_RELEASE_EVENT, keycode)
+                        }
+                    }
+                };
+                inputs.push(input);
+            }
+            EventType::RELATIVE => {
+                let value = event.value();
+                let button = match RelativeAxisCode(event.code()) {
+                    RelativeAxisCode::REL_X => {
+                        motion.0 += value;
+                        continue;
+                    }
+                    RelativeAxisCode::REL_Y => {
+                        motion.1 += value;
+                        continue;
+                    }
+                    RelativeAxisCode::REL_WHEEL if value > 0 => 4,
+                    RelativeAxisCode::REL_WHEEL if value < 0 => 5,
+                    RelativeAxisCode::REL_HWHEEL if value < 0 => 6,
+                    RelativeAxisCode::REL_HWHEEL if value > 0 => 7,
+                    _ => continue,
+                };
+                inputs.push(FakeInput::new(BUTTON_PRESS_EVENT, button));
+                inputs.push(FakeInput::new(BUTTON_RELEASE_EVENT, button));
+            }
+            _ => (),
+        }
+    }
+
+    if motion != (0, 0) {
+        let clamp = |value: i32| value.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        inputs.push(FakeInput {
+            kind: MOTION_NOTIFY_EVENT,
+            detail: 1,
+            x: clamp(motion.0),
+            y: clamp(motion.1),
+        });
+    }
+
+    inputs
+}
+
+/// Emulates keyboard and mouse input using the XTEST extension of the X
+/// server. This is used as a fallback when virtual input devices cannot be
+/// created with uinput.
+pub struct XTestDevice {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl fmt::Debug for XTestDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XTestDevice")
+            .field("root", &self.root)
+            .finish()
+    }
+}
+
+impl XTestDevice {
+    /// Connect to the X server set in the DISPLAY environment variable
+    pub fn connect() -> Result<Self, Box<dyn Error>> {
+        let (conn, screen) = x11rb::connect(None)?;
+        let Some(root) = conn.setup().roots.get(screen).map(|screen| screen.root) else {
+            return Err("Unable to find X11 root window".into());
+        };
+
+        // Ensure the XTEST extension is supported by the server
+        let version = conn.xtest_get_version(2, 2)?.reply()?;
+        log::debug!(
+            "Connected to XTEST {}.{}",
+            version.major_version,
+            version.minor_version
+        );
+
+        Ok(Self { conn, root })
+    }
+
+    /// Write the given native event to the X server
+    pub fn write_event(&self, event: NativeEvent) -> Result<(), Box<dyn Error>> {
+        let events: Vec<InputEvent> = EvdevEvent::from_native_event(event, HashMap::new())
+            .into_iter()
+            .map(|event| event.as_input_event())
+            .collect();
+        let inputs = fake_inputs(events.as_slice());
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        for input in inputs {
+            self.conn.xtest_fake_input(
+                input.kind,
+                input.detail,
+                x11rb::CURRENT_TIME,
+                self.root,
+                input.x,
+                input.y,
+                0,
+            )?;
+        }
+        self.conn.flush()?;
+
+        Ok(())
+    }
+}
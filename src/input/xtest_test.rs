@@ -0,0 +1,59 @@
+use evdev::{EventType, InputEvent, KeyCode, RelativeAxisCode};
+use x11rb::protocol::xproto::{
+    BUTTON_PRESS_EVENT, BUTTON_RELEASE_EVENT, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+    MOTION_NOTIFY_EVENT,
+};
+
+use super::xtest::{fake_inputs, FakeInput};
+
+fn input(kind: u8, detail: u8) -> FakeInput {
+    FakeInput {
+        kind,
+        detail,
+        x: 0,
+        y: 0,
+    }
+}
+
+#[test]
+fn test_fake_inputs() {
+    // Keys should use X11 keycodes
+    let events = [
+        InputEvent::new(EventType::KEY.0, KeyCode::KEY_A.0, 1),
+        InputEvent::new(EventType::KEY.0, KeyCode::KEY_A.0, 0),
+    ];
+    assert_eq!(
+        fake_inputs(&events),
+        vec![input(KEY_PRESS_EVENT, 38), input(KEY_RELEASE_EVENT, 38)]
+    );
+
+    // Mouse buttons should use pointer buttons
+    let events = [InputEvent::new(EventType::KEY.0, KeyCode::BTN_RIGHT.0, 1)];
+    assert_eq!(fake_inputs(&events), vec![input(BUTTON_PRESS_EVENT, 3)]);
+
+    // Wheel steps should click the wheel buttons
+    let events = [InputEvent::new(
+        EventType::RELATIVE.0,
+        RelativeAxisCode::REL_WHEEL.0,
+        -1,
+    )];
+    assert_eq!(
+        fake_inputs(&events),
+        vec![input(BUTTON_PRESS_EVENT, 5), input(BUTTON_RELEASE_EVENT, 5)]
+    );
+
+    // Relative motion should be combined into a single input
+    let events = [
+        InputEvent::new(EventType::RELATIVE.0, RelativeAxisCode::REL_X.0, 4),
+        InputEvent::new(EventType::RELATIVE.0, RelativeAxisCode::REL_Y.0, -2),
+    ];
+    assert_eq!(
+        fake_inputs(&events),
+        vec![FakeInput {
+            kind: MOTION_NOTIFY_EVENT,
+            detail: 1,
+            x: 4,
+            y: -2,
+        }]
+    );
+}
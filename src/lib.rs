@@ -5,7 +5,12 @@ pub mod constants;
 pub mod dbus;
 pub mod dmi;
 pub mod drivers;
+pub mod gadget;
+pub mod gamescope;
 pub mod iio;
 pub mod input;
+pub mod logging;
+pub mod platform;
+pub mod session;
 pub mod udev;
 pub mod watcher;
@@ -0,0 +1,96 @@
+//! Logging and tracing setup
+//!
+//! Log output is handled by a `tracing` subscriber. Messages from the `log`
+//! macros used throughout the codebase are forwarded to the subscriber, so
+//! they are recorded inside of the `tracing` span of the device or task that
+//! emitted them. This allows filtering logs for a single device, e.g.:
+//!
+//! ```text
+//! info,[composite_device{path=/org/shadowblip/InputPlumber/CompositeDevice0}]=debug
+//! ```
+//!
+//! The log filter can be changed at runtime with [set_filter].
+
+use std::{
+    error::Error,
+    sync::{Mutex, OnceLock},
+};
+
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter,
+    Registry,
+};
+
+/// Log filter used if none is configured
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Handle used to replace the log filter at runtime
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+/// Currently active log filter directives
+static FILTER: Mutex<String> = Mutex::new(String::new());
+
+/// Initialize logging using the given log filter directives. Uses the
+/// [DEFAULT_FILTER] if the given filter is invalid.
+pub fn init(filter: &str) {
+    let (env_filter, filter) = match EnvFilter::try_new(filter) {
+        Ok(env_filter) => (env_filter, filter),
+        Err(e) => {
+            eprintln!("Invalid log filter '{filter}', using '{DEFAULT_FILTER}': {e}");
+            (EnvFilter::new(DEFAULT_FILTER), DEFAULT_FILTER)
+        }
+    };
+    let max_level = env_filter.max_level_hint();
+    let (filter_layer, handle) = reload::Layer::new(env_filter);
+    if let Err(e) = tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(fmt::layer())
+        .try_init()
+    {
+        eprintln!("Failed to initialize logging: {e}");
+        return;
+    }
+
+    // Skip log records the filter would discard before they are forwarded to
+    // the subscriber. The max level is raised again when the filter changes.
+    set_log_max_level(max_level);
+
+    let _ = FILTER_HANDLE.set(handle);
+    *FILTER.lock().unwrap() = filter.to_string();
+}
+
+/// Returns the currently active log filter directives
+pub fn get_filter() -> String {
+    FILTER.lock().unwrap().clone()
+}
+
+/// Replace the active log filter with the given filter directives. Filters
+/// use the `RUST_LOG` syntax and can target modules or spans. E.g.
+/// "info,inputplumber::input::composite_device=debug".
+pub fn set_filter(filter: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let env_filter = EnvFilter::try_new(filter)?;
+    let Some(handle) = FILTER_HANDLE.get() else {
+        return Err("Logging has not been initialized".into());
+    };
+    let max_level = env_filter.max_level_hint();
+    handle.reload(env_filter)?;
+    set_log_max_level(max_level);
+    log::info!("Log filter changed to: {filter}");
+    *FILTER.lock().unwrap() = filter.to_string();
+
+    Ok(())
+}
+
+/// Set the maximum level of records emitted by the `log` macros from the
+/// given level hint of the log filter. Filters without a hint (e.g. filters
+/// on field values) may enable any level.
+fn set_log_max_level(hint: Option<LevelFilter>) {
+    let level = match hint.unwrap_or(LevelFilter::TRACE) {
+        LevelFilter::OFF => log::LevelFilter::Off,
+        LevelFilter::ERROR => log::LevelFilter::Error,
+        LevelFilter::WARN => log::LevelFilter::Warn,
+        LevelFilter::INFO => log::LevelFilter::Info,
+        LevelFilter::DEBUG => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    log::set_max_level(level);
+}
@@ -17,25 +17,26 @@ mod constants;
 mod dbus;
 mod dmi;
 mod drivers;
+mod gadget;
+mod gamescope;
 mod iio;
 mod input;
+mod logging;
+mod platform;
+mod session;
 mod udev;
 mod watcher;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let log_level = match env::var("LOG_LEVEL") {
-        Ok(value) => value,
-        Err(_) => "info".to_string(),
-    };
-    env::set_var("RUST_LOG", log_level);
-    env_logger::init();
+    let log_filter = env::var("LOG_LEVEL").unwrap_or_else(|_| logging::DEFAULT_FILTER.to_string());
+    logging::init(&log_filter);
     const VERSION: &str = env!("CARGO_PKG_VERSION");
 
     // If there are any subcommands, run as a CLI client instead.
     let args = cli::Args::parse();
     if let Some(cmd) = args.cmd.as_ref() {
-        if !matches!(cmd, cli::Commands::Run) {
+        if !matches!(cmd, cli::Commands::Run { .. }) {
             cli::main_cli(args).await?;
             return Ok(());
         }
@@ -56,6 +57,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     // Create an InputManager instance
     let mut input_manager = Manager::new(connection.clone());
+    if let Some(cli::Commands::Run { session_broker }) = args.cmd {
+        input_manager.set_session_broker(session_broker);
+    }
 
     let (ctrl_c_result, input_man_result, request_name_result) = tokio::join!(
         // Setup CTRL+C handler
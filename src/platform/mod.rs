@@ -0,0 +1,150 @@
+use std::{error::Error, fs, time::Duration};
+
+use evdev::{Device, SwitchCode};
+use tokio::sync::mpsc;
+
+use crate::input::manager::ManagerCommand;
+
+use self::state::PlatformState;
+
+pub mod state;
+#[cfg(test)]
+pub mod state_test;
+
+/// Path to the ACPI lid button state(s)
+const LID_PATH: &str = "/proc/acpi/button/lid";
+/// Path to ACPI dock stations
+const DOCK_PATH: &str = "/sys/devices/platform";
+/// How often the platform state should be polled
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Returns the current [PlatformState] of the system by reading switch state
+/// from evdev devices that expose switches (SW_TABLET_MODE, SW_LID, SW_DOCK),
+/// falling back to ACPI for lid and dock state.
+pub fn get_platform_state(switch_devices: &[Device]) -> PlatformState {
+    let mut state = PlatformState::default();
+
+    // Query the state of any switch devices
+    for device in switch_devices {
+        let Some(supported) = device.supported_switches() else {
+            continue;
+        };
+        let switches = match device.get_switch_state() {
+            Ok(switches) => switches,
+            Err(e) => {
+                log::trace!("Failed to read switch state: {e:?}");
+                continue;
+            }
+        };
+        if supported.contains(SwitchCode::SW_TABLET_MODE) {
+            state.tablet_mode = Some(switches.contains(SwitchCode::SW_TABLET_MODE));
+        }
+        if supported.contains(SwitchCode::SW_LID) {
+            // SW_LID is set when the lid is closed
+            state.lid_open = Some(!switches.contains(SwitchCode::SW_LID));
+        }
+        if supported.contains(SwitchCode::SW_DOCK) {
+            state.docked = Some(switches.contains(SwitchCode::SW_DOCK));
+        }
+    }
+
+    if state.lid_open.is_none() {
+        state.lid_open = get_acpi_lid_open();
+    }
+    if state.docked.is_none() {
+        state.docked = get_acpi_docked();
+    }
+
+    state
+}
+
+/// Returns all evdev devices that are capable of reporting the state of
+/// platform switches.
+fn get_switch_devices() -> Vec<Device> {
+    evdev::enumerate()
+        .filter_map(|(path, device)| {
+            let switches = device.supported_switches()?;
+            let is_platform_switch = switches.contains(SwitchCode::SW_TABLET_MODE)
+                || switches.contains(SwitchCode::SW_LID)
+                || switches.contains(SwitchCode::SW_DOCK);
+            if !is_platform_switch {
+                return None;
+            }
+            log::debug!("Found platform switch device: {}", path.display());
+            Some(device)
+        })
+        .collect()
+}
+
+/// Returns whether or not the lid is open from ACPI. E.g.
+/// "/proc/acpi/button/lid/LID0/state"
+fn get_acpi_lid_open() -> Option<bool> {
+    let entries = fs::read_dir(LID_PATH).ok()?;
+    for entry in entries.flatten() {
+        let path = entry.path().join("state");
+        let Ok(value) = fs::read_to_string(path) else {
+            continue;
+        };
+        if value.contains("open") {
+            return Some(true);
+        }
+        if value.contains("closed") {
+            return Some(false);
+        }
+    }
+    None
+}
+
+/// Returns whether or not the device is docked from ACPI. E.g.
+/// "/sys/devices/platform/dock.0/docked"
+fn get_acpi_docked() -> Option<bool> {
+    let entries = fs::read_dir(DOCK_PATH).ok()?;
+    let mut docked = None;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().starts_with("dock.") {
+            continue;
+        }
+        let path = entry.path().join("docked");
+        let Ok(value) = fs::read_to_string(path) else {
+            continue;
+        };
+        let is_docked = value.trim() == "1";
+        docked = Some(docked.unwrap_or(false) || is_docked);
+    }
+    docked
+}
+
+/// Watch for changes to the [PlatformState] and notify the manager whenever
+/// it changes.
+pub fn watch(cmd_tx: mpsc::Sender<ManagerCommand>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut switch_devices = get_switch_devices();
+    let mut state = get_platform_state(&switch_devices);
+    log::debug!("Initial platform state: {state:?}");
+    cmd_tx.blocking_send(ManagerCommand::PlatformStateChanged {
+        state: state.clone(),
+    })?;
+
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        // If any switch devices have gone away, rediscover them
+        let has_stale_device = switch_devices
+            .iter()
+            .any(|device| device.get_switch_state().is_err());
+        if has_stale_device {
+            log::debug!("Platform switch device went away. Rediscovering switch devices.");
+            switch_devices = get_switch_devices();
+        }
+
+        let new_state = get_platform_state(&switch_devices);
+        if new_state == state {
+            continue;
+        }
+        log::info!("Platform state changed: {new_state:?}");
+        state = new_state;
+        cmd_tx.blocking_send(ManagerCommand::PlatformStateChanged {
+            state: state.clone(),
+        })?;
+    }
+}
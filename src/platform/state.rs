@@ -0,0 +1,52 @@
+use crate::config::PlatformConditions;
+
+/// Container for the current hardware state of the platform, such as whether
+/// the device is in tablet mode, has its lid closed, or is docked. Values
+/// are [None] if the state could not be determined on this system.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlatformState {
+    pub tablet_mode: Option<bool>,
+    pub lid_open: Option<bool>,
+    pub docked: Option<bool>,
+}
+
+impl PlatformState {
+    /// Returns true if the device is in tablet mode. If the state is unknown,
+    /// the device is assumed to not be in tablet mode.
+    pub fn is_tablet_mode(&self) -> bool {
+        self.tablet_mode.unwrap_or(false)
+    }
+
+    /// Returns true if the lid of the device is open. If the state is unknown,
+    /// the lid is assumed to be open.
+    pub fn is_lid_open(&self) -> bool {
+        self.lid_open.unwrap_or(true)
+    }
+
+    /// Returns true if the device is docked. If the state is unknown, the
+    /// device is assumed to not be docked.
+    pub fn is_docked(&self) -> bool {
+        self.docked.unwrap_or(false)
+    }
+
+    /// Returns true if the current platform state satisfies *all* of the
+    /// given conditions. Conditions that are not defined always match.
+    pub fn satisfies(&self, conditions: &PlatformConditions) -> bool {
+        if let Some(tablet_mode) = conditions.tablet_mode {
+            if self.is_tablet_mode() != tablet_mode {
+                return false;
+            }
+        }
+        if let Some(lid_open) = conditions.lid_open {
+            if self.is_lid_open() != lid_open {
+                return false;
+            }
+        }
+        if let Some(docked) = conditions.docked {
+            if self.is_docked() != docked {
+                return false;
+            }
+        }
+        true
+    }
+}
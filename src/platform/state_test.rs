@@ -0,0 +1,55 @@
+use crate::config::PlatformConditions;
+
+use super::state::PlatformState;
+
+#[test]
+fn test_satisfies_empty_conditions() {
+    let state = PlatformState::default();
+    let conditions = PlatformConditions {
+        tablet_mode: None,
+        lid_open: None,
+        docked: None,
+    };
+    assert!(state.satisfies(&conditions));
+}
+
+#[test]
+fn test_satisfies_unknown_state() {
+    // Unknown state should fall back to "not tablet", "lid open", "not docked"
+    let state = PlatformState::default();
+    let conditions = PlatformConditions {
+        tablet_mode: Some(false),
+        lid_open: Some(true),
+        docked: Some(false),
+    };
+    assert!(state.satisfies(&conditions));
+
+    let conditions = PlatformConditions {
+        tablet_mode: None,
+        lid_open: None,
+        docked: Some(true),
+    };
+    assert!(!state.satisfies(&conditions));
+}
+
+#[test]
+fn test_satisfies_all_conditions() {
+    let state = PlatformState {
+        tablet_mode: Some(true),
+        lid_open: Some(true),
+        docked: Some(true),
+    };
+    let conditions = PlatformConditions {
+        tablet_mode: Some(true),
+        lid_open: None,
+        docked: Some(true),
+    };
+    assert!(state.satisfies(&conditions));
+
+    let conditions = PlatformConditions {
+        tablet_mode: Some(false),
+        lid_open: None,
+        docked: Some(true),
+    };
+    assert!(!state.satisfies(&conditions));
+}
@@ -0,0 +1,130 @@
+use std::{error::Error, fs, os::unix::fs::PermissionsExt, path::Path, time::Duration};
+
+use tokio::{
+    net::{UnixListener, UnixStream},
+    sync::mpsc,
+};
+use zbus::Connection;
+
+use crate::{
+    dbus::polkit::{check_process_authorization, ACTION_LOAD_PROFILE},
+    input::manager::ManagerCommand,
+};
+
+use super::{read_message, write_message, SessionRequest, SessionResponse, SESSION_BROKER_SOCKET};
+
+/// The [SessionBroker] runs in the system instance of InputPlumber and accepts
+/// connections from per-user session instances. Session instances can only
+/// load profiles on composite devices if polkit authorizes their process to
+/// load profiles, which by default requires an active login session.
+pub struct SessionBroker {
+    dbus: Connection,
+    manager: mpsc::Sender<ManagerCommand>,
+}
+
+impl SessionBroker {
+    pub fn new(dbus: Connection, manager: mpsc::Sender<ManagerCommand>) -> Self {
+        Self { dbus, manager }
+    }
+
+    /// Listen for connections from session instances
+    pub async fn run(&self) -> Result<(), Box<dyn Error>> {
+        let path = Path::new(SESSION_BROKER_SOCKET);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+
+        // Any user may connect. Requests are authorized using the credentials
+        // of the peer.
+        fs::set_permissions(path, fs::Permissions::from_mode(0o666))?;
+        log::info!("Session broker listening on {SESSION_BROKER_SOCKET}");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let dbus = self.dbus.clone();
+            let manager = self.manager.clone();
+            tokio::task::spawn(async move {
+                if let Err(e) = handle_session(dbus, manager, stream).await {
+                    log::error!("Error handling session connection: {e:?}");
+                }
+            });
+        }
+    }
+}
+
+/// Handle requests from a single session instance until it disconnects
+async fn handle_session(
+    dbus: Connection,
+    manager: mpsc::Sender<ManagerCommand>,
+    mut stream: UnixStream,
+) -> Result<(), Box<dyn Error>> {
+    let cred = stream.peer_cred()?;
+    let uid = cred.uid();
+    let Some(pid) = cred.pid() else {
+        return Err("Unable to determine pid of session instance".into());
+    };
+    let pid = pid as u32;
+    log::info!("Session instance connected for user {uid} (pid {pid})");
+
+    while let Some(request) = read_message::<_, SessionRequest>(&mut stream).await? {
+        log::debug!("Got session request from user {uid}: {request:?}");
+        let response = match request {
+            SessionRequest::LoadProfile {
+                device_path,
+                profile,
+            } => load_profile(&dbus, &manager, pid, uid, device_path, profile).await,
+        };
+        write_message(&mut stream, &response).await?;
+    }
+
+    log::info!("Session instance disconnected for user {uid}");
+    Ok(())
+}
+
+/// Load the given profile on the given composite device on behalf of the
+/// session instance with the given pid and uid.
+async fn load_profile(
+    dbus: &Connection,
+    manager: &mpsc::Sender<ManagerCommand>,
+    pid: u32,
+    uid: u32,
+    device_path: String,
+    profile: String,
+) -> SessionResponse {
+    match check_process_authorization(dbus, pid, uid, ACTION_LOAD_PROFILE).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return SessionResponse::Denied(format!(
+                "User {uid} is not authorized to load profiles"
+            ));
+        }
+        Err(e) => {
+            return SessionResponse::Error(format!("Unable to check authorization: {e}"));
+        }
+    }
+
+    let (sender, mut receiver) = mpsc::channel(1);
+    let command = ManagerCommand::GetCompositeDevice {
+        path: device_path.clone(),
+        sender,
+    };
+    if let Err(e) = manager
+        .send_timeout(command, Duration::from_millis(500))
+        .await
+    {
+        return SessionResponse::Error(e.to_string());
+    }
+    let Some(Some(device)) = receiver.recv().await else {
+        return SessionResponse::Error(format!("No composite device found at {device_path}"));
+    };
+
+    log::info!("Loading profile for user {uid} on {device_path}");
+    match device.load_profile_from_yaml(profile).await {
+        Ok(_) => SessionResponse::Ok,
+        Err(e) => SessionResponse::Error(e.to_string()),
+    }
+}
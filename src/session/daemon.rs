@@ -0,0 +1,209 @@
+use std::{
+    collections::HashSet,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use tokio::net::UnixStream;
+use zbus::Connection;
+
+use crate::{
+    cli::get_managed_objects,
+    config::{path::get_user_config_path, SessionPolicy},
+    dbus::interface::composite_device::CompositeDeviceInterfaceProxy,
+};
+
+use super::{
+    login1::{ManagerProxy, SessionProxy},
+    read_message, write_message, SessionRequest, SessionResponse, SESSION_BROKER_SOCKET,
+};
+
+/// How often to check for new composite devices and session changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Run the per-user session instance. The session instance loads the user's
+/// session policy and asks the session broker to load the user's profiles on
+/// matching composite devices whenever they appear or the user's login
+/// session becomes active.
+pub async fn run() -> Result<(), Box<dyn Error>> {
+    let Some(config_path) = get_user_config_path() else {
+        return Err("Unable to determine user config directory".into());
+    };
+    let policy_path = config_path.join("session.yaml");
+    let policy = SessionPolicy::from_yaml_file(policy_path.display().to_string())?;
+    log::info!("Loaded session policy: {}", policy.name);
+
+    let conn = Connection::system().await?;
+    let session = get_own_session(&conn).await;
+
+    // Connection to the session broker. The broker runs in the system
+    // instance, so the connection is re-established whenever the system
+    // instance restarts.
+    let mut broker: Option<UnixStream> = None;
+
+    // Composite devices that the user's profiles have been applied to
+    let mut applied: HashSet<String> = HashSet::new();
+
+    loop {
+        // Only apply profiles while this login session is active. When the
+        // session becomes active again, profiles are re-applied in case
+        // another user changed them in the meantime.
+        let active = match session.as_ref() {
+            Some(session) => session.active().await.unwrap_or(false),
+            None => true,
+        };
+        if !active {
+            applied.clear();
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+
+        if broker.is_none() {
+            match UnixStream::connect(SESSION_BROKER_SOCKET).await {
+                Ok(stream) => {
+                    log::info!("Connected to session broker at {SESSION_BROKER_SOCKET}");
+                    // Composite devices are re-created when the system
+                    // instance restarts, so apply the profiles again.
+                    applied.clear();
+                    broker = Some(stream);
+                }
+                Err(e) => {
+                    log::debug!("Unable to connect to session broker: {e:?}");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+            }
+        }
+        let Some(stream) = broker.as_mut() else {
+            continue;
+        };
+
+        let device_paths: Vec<String> = match get_managed_objects(conn.clone()).await {
+            Ok(objects) => objects
+                .into_iter()
+                .filter(|obj| obj.contains("/CompositeDevice"))
+                .collect(),
+            Err(e) => {
+                log::debug!("Unable to get composite devices: {e:?}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+        applied.retain(|path| device_paths.contains(path));
+
+        for path in device_paths {
+            if applied.contains(&path) {
+                continue;
+            }
+            let request = match get_policy_request(&conn, &policy, &config_path, &path).await {
+                Ok(Some(request)) => request,
+                Ok(None) => {
+                    applied.insert(path);
+                    continue;
+                }
+                Err(e) => {
+                    log::error!("Failed to apply session policy to {path}: {e:?}");
+                    applied.insert(path);
+                    continue;
+                }
+            };
+
+            // Retry the device after reconnecting if the broker went away
+            let response = match send_request(stream, &request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Lost connection to session broker: {e:?}");
+                    broker = None;
+                    break;
+                }
+            };
+            applied.insert(path.clone());
+            match response {
+                SessionResponse::Ok => {
+                    log::info!("Loaded session profile on device: {path}");
+                }
+                SessionResponse::Denied(reason) => {
+                    log::debug!("Session broker denied loading profile: {reason}");
+                }
+                SessionResponse::Error(e) => {
+                    log::error!("Failed to apply session policy to {path}: {e}");
+                }
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Returns the request to load the first profile in the policy that matches
+/// the composite device at the given path, if any profile matches.
+async fn get_policy_request(
+    conn: &Connection,
+    policy: &SessionPolicy,
+    config_path: &Path,
+    device_path: &str,
+) -> Result<Option<SessionRequest>, Box<dyn Error>> {
+    let device = CompositeDeviceInterfaceProxy::builder(conn)
+        .path(device_path.to_string())?
+        .build()
+        .await?;
+    let name = device.name().await?;
+
+    let Some(entry) = policy
+        .profiles
+        .iter()
+        .find(|entry| glob_match::glob_match(entry.device.as_str(), name.as_str()))
+    else {
+        log::debug!("No session profile matches device: {name}");
+        return Ok(None);
+    };
+
+    // Resolve relative profile paths from the user's profiles directory
+    let mut profile_path = PathBuf::from(&entry.profile);
+    if profile_path.is_relative() {
+        profile_path = config_path.join("profiles").join(profile_path);
+    }
+    let profile = fs::read_to_string(&profile_path)?;
+    log::debug!("Requesting profile {profile_path:?} for device: {name}");
+
+    Ok(Some(SessionRequest::LoadProfile {
+        device_path: device_path.to_string(),
+        profile,
+    }))
+}
+
+/// Send the given request to the session broker and wait for its response.
+/// Returns an error if the connection to the broker was lost.
+async fn send_request(
+    stream: &mut UnixStream,
+    request: &SessionRequest,
+) -> Result<SessionResponse, Box<dyn Error>> {
+    write_message(stream, request).await?;
+    let Some(response) = read_message::<_, SessionResponse>(stream).await? else {
+        return Err("Session broker closed the connection".into());
+    };
+    Ok(response)
+}
+
+/// Returns a proxy to the logind session this session instance is running
+/// in, if it can be determined.
+async fn get_own_session(conn: &Connection) -> Option<SessionProxy<'static>> {
+    let session_id = env::var("XDG_SESSION_ID").ok()?;
+    let login = ManagerProxy::new(conn).await.ok()?;
+    let path = match login.get_session(session_id.as_str()).await {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Unable to find login session {session_id}: {e:?}");
+            return None;
+        }
+    };
+    SessionProxy::builder(conn)
+        .path(path)
+        .ok()?
+        .build()
+        .await
+        .ok()
+}
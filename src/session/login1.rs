@@ -0,0 +1,49 @@
+//! # D-Bus interface proxies for: `org.freedesktop.login1.Manager` and
+//! `org.freedesktop.login1.Session`
+//!
+//! Only the parts of the logind API used to track active user sessions and
+//! to perform power actions while respecting inhibitors are included.
+use zbus::{proxy, zvariant::OwnedObjectPath};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    /// GetSession method
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// ListInhibitors method
+    #[allow(clippy::type_complexity)]
+    fn list_inhibitors(&self) -> zbus::Result<Vec<(String, String, String, String, u32, u32)>>;
+
+    /// ListSessions method
+    #[allow(clippy::type_complexity)]
+    fn list_sessions(&self) -> zbus::Result<Vec<(String, u32, String, String, OwnedObjectPath)>>;
+
+    /// Hibernate method
+    fn hibernate(&self, interactive: bool) -> zbus::Result<()>;
+
+    /// PowerOff method
+    fn power_off(&self, interactive: bool) -> zbus::Result<()>;
+
+    /// Reboot method
+    fn reboot(&self, interactive: bool) -> zbus::Result<()>;
+
+    /// Suspend method
+    fn suspend(&self, interactive: bool) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    /// Lock method
+    fn lock(&self) -> zbus::Result<()>;
+
+    /// Active property
+    #[zbus(property)]
+    fn active(&self) -> zbus::Result<bool>;
+}
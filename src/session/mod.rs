@@ -0,0 +1,106 @@
+//! Per-user session support
+//!
+//! When started with `--session-broker`, the system InputPlumber instance
+//! keeps ownership of all input devices, but listens on a private socket for
+//! per-user session instances (`inputplumber session`). Session instances own
+//! the profile policy for their user and ask the broker to load the user's
+//! profiles on composite devices. The broker only honors requests from users
+//! with an active login session, so multiple users on the same system can
+//! have their own mappings without editing root-owned configuration.
+
+pub mod broker;
+pub mod daemon;
+pub mod login1;
+pub mod power;
+
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use self::login1::{ManagerProxyBlocking, SessionProxyBlocking};
+
+/// Path to the private socket the session broker listens on
+pub const SESSION_BROKER_SOCKET: &str = "/run/inputplumber/session.sock";
+
+/// Seat whose active session actions are performed for
+pub const SEAT: &str = "seat0";
+
+/// Maximum size of a single message sent over the broker socket
+const MAX_MESSAGE_SIZE: u32 = 1024 * 1024;
+
+/// Requests sent from a session instance to the session broker
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SessionRequest {
+    /// Load the given profile YAML on the composite device at the given
+    /// DBus path.
+    LoadProfile {
+        device_path: String,
+        profile: String,
+    },
+}
+
+/// Responses sent from the session broker to a session instance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SessionResponse {
+    Ok,
+    Denied(String),
+    Error(String),
+}
+
+/// Returns the uid of the user of the active session on the seat, if any.
+/// This blocks on the system bus, so it must not be called from async code.
+pub fn get_active_session_user() -> Result<Option<u32>, Box<dyn Error + Send + Sync>> {
+    let conn = zbus::blocking::Connection::system()?;
+    let login = ManagerProxyBlocking::new(&conn)?;
+    for (_, uid, _, seat, path) in login.list_sessions()? {
+        if seat != SEAT {
+            continue;
+        }
+        let session = SessionProxyBlocking::builder(&conn).path(path)?.build()?;
+        if session.active()? {
+            return Ok(Some(uid));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Write the given message to the given stream. Messages are serialized as
+/// YAML and prefixed with their length.
+pub async fn write_message<W, T>(stream: &mut W, message: &T) -> Result<(), Box<dyn Error>>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let data = serde_yaml::to_string(message)?;
+    let len = data.len() as u32;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(format!("Message too large: {len} bytes").into());
+    }
+    stream.write_u32(len).await?;
+    stream.write_all(data.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read a message from the given stream. Returns [None] if the stream was
+/// closed.
+pub async fn read_message<R, T>(stream: &mut R) -> Result<Option<T>, Box<dyn Error>>
+where
+    R: AsyncRead + Unpin,
+    T: for<'de> Deserialize<'de>,
+{
+    let len = match stream.read_u32().await {
+        Ok(len) => len,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if len > MAX_MESSAGE_SIZE {
+        return Err(format!("Message too large: {len} bytes").into());
+    }
+    let mut data = vec![0; len as usize];
+    stream.read_exact(&mut data).await?;
+    let message = serde_yaml::from_slice(&data)?;
+    Ok(Some(message))
+}
@@ -0,0 +1,147 @@
+//! Power actions performed through systemd-logind
+//!
+//! Capability map mappings can suspend, power off, or lock the active session
+//! directly (e.g. on a long press of the Guide button), without an external
+//! listener on the DBus target device. Since the action is triggered by input
+//! instead of a DBus request, it is performed on behalf of the user of the
+//! active session on the seat, and only if polkit authorizes that user.
+//! Since InputPlumber itself is privileged, logind would override blocking
+//! inhibitors for it, so actions that are blocked by an inhibitor (e.g. a
+//! running update) are refused instead.
+
+use std::{error::Error, fmt, str::FromStr};
+
+use zbus::{zvariant::OwnedObjectPath, Connection};
+
+use crate::dbus::polkit::{check_session_authorization, ACTION_POWER};
+
+use super::{
+    login1::{ManagerProxy, SessionProxy},
+    SEAT,
+};
+
+/// Power action that can be requested from logind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction {
+    Suspend,
+    Hibernate,
+    PowerOff,
+    Reboot,
+    LockSession,
+}
+
+impl FromStr for PowerAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "suspend" => Ok(PowerAction::Suspend),
+            "hibernate" => Ok(PowerAction::Hibernate),
+            "power_off" => Ok(PowerAction::PowerOff),
+            "reboot" => Ok(PowerAction::Reboot),
+            "lock_session" => Ok(PowerAction::LockSession),
+            _ => Err(format!("Invalid power action: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for PowerAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PowerAction::Suspend => write!(f, "suspend"),
+            PowerAction::Hibernate => write!(f, "hibernate"),
+            PowerAction::PowerOff => write!(f, "power_off"),
+            PowerAction::Reboot => write!(f, "reboot"),
+            PowerAction::LockSession => write!(f, "lock_session"),
+        }
+    }
+}
+
+impl PowerAction {
+    /// Returns the logind inhibitor lock type that blocks this action, if
+    /// the action can be inhibited
+    fn inhibit_what(&self) -> Option<&'static str> {
+        match self {
+            PowerAction::Suspend | PowerAction::Hibernate => Some("sleep"),
+            PowerAction::PowerOff | PowerAction::Reboot => Some("shutdown"),
+            PowerAction::LockSession => None,
+        }
+    }
+}
+
+/// Returns the name of the program holding an inhibitor lock that blocks
+/// the given action, if any. Weak blocking locks are honored as well, since
+/// the action is performed on behalf of an unprivileged user.
+async fn get_blocking_inhibitor(
+    conn: &Connection,
+    action: PowerAction,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let Some(what) = action.inhibit_what() else {
+        return Ok(None);
+    };
+    let login = ManagerProxy::new(conn).await?;
+    for (locks, who, why, mode, _, _) in login.list_inhibitors().await? {
+        if !matches!(mode.as_str(), "block" | "block-weak") {
+            continue;
+        }
+        if locks.split(':').any(|lock| lock == what) {
+            return Ok(Some(format!("{who} ({why})")));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the id and object path of the active session on the seat
+async fn get_active_session(
+    conn: &Connection,
+) -> Result<Option<(String, OwnedObjectPath)>, Box<dyn Error + Send + Sync>> {
+    let login = ManagerProxy::new(conn).await?;
+    for (id, _, _, seat, path) in login.list_sessions().await? {
+        if seat != SEAT {
+            continue;
+        }
+        let session = SessionProxy::builder(conn)
+            .path(path.clone())?
+            .build()
+            .await?;
+        if session.active().await? {
+            return Ok(Some((id, path)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Perform the given power action for the active session on the seat. Fails
+/// if there is no active session, its user is not authorized to perform
+/// power actions with a controller, or the action is blocked by an inhibitor.
+pub async fn request(
+    conn: &Connection,
+    action: PowerAction,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some((session_id, path)) = get_active_session(conn).await? else {
+        return Err(format!("No active session on {SEAT} to {action} for").into());
+    };
+    if !check_session_authorization(conn, session_id.as_str(), ACTION_POWER).await? {
+        return Err(format!("Session {session_id} is not authorized to {action}").into());
+    }
+    if let Some(inhibitor) = get_blocking_inhibitor(conn, action).await? {
+        return Err(format!("Unable to {action}, inhibited by {inhibitor}").into());
+    }
+
+    log::info!("Requesting {action} for session {session_id}");
+    let login = ManagerProxy::new(conn).await?;
+    match action {
+        PowerAction::Suspend => login.suspend(false).await?,
+        PowerAction::Hibernate => login.hibernate(false).await?,
+        PowerAction::PowerOff => login.power_off(false).await?,
+        PowerAction::Reboot => login.reboot(false).await?,
+        PowerAction::LockSession => {
+            let session = SessionProxy::builder(conn).path(path)?.build().await?;
+            session.lock().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -6,6 +6,9 @@ use std::{
     path::Path,
 };
 
+/// Bus type of bluetooth devices (BUS_BLUETOOTH in linux/input.h)
+pub const BUS_BLUETOOTH: u16 = 0x05;
+
 pub trait AttributeGetter {
     /// Looks for the given attribute at the given path using sysfs.
     fn get_attribute_from_sysfs(&self, path: &str, attribute: &str) -> Option<String>;
@@ -346,6 +349,8 @@ pub struct UdevDevice {
     vendor_id: Option<u16>,
     product_id: Option<u16>,
     bus_type: Option<u16>,
+    /// Port of a multi-port adapter this device represents, starting at 1
+    port: Option<u8>,
 }
 
 impl UdevDevice {
@@ -381,6 +386,43 @@ impl UdevDevice {
             vendor_id: None,
             product_id: None,
             bus_type: None,
+            port: None,
+        }
+    }
+
+    /// Returns a UdevDevice object for a virtual source device with the given
+    /// name that is not backed by any hardware. The devnode is the path to the
+    /// script the device replays, which may be empty.
+    /// e.g. UdevDevice::new_virtual("demo", "/etc/inputplumber/demo.yaml");
+    pub fn new_virtual(name: &str, script: &str) -> Self {
+        Self {
+            devnode: script.to_string(),
+            subsystem: "virtual".to_string(),
+            syspath: "".to_string(),
+            sysname: name.to_string(),
+            name: Some(name.to_string()),
+            vendor_id: None,
+            product_id: None,
+            bus_type: None,
+            port: None,
+        }
+    }
+
+    /// Returns a UdevDevice object for an external source device with the
+    /// given name that is implemented by a userspace driver. The devnode is the
+    /// name of the allow-listed driver executable to run.
+    /// e.g. UdevDevice::new_external("mypad", "mypad-driver");
+    pub fn new_external(name: &str, driver: &str) -> Self {
+        Self {
+            devnode: driver.to_string(),
+            subsystem: "external".to_string(),
+            syspath: "".to_string(),
+            sysname: name.to_string(),
+            name: Some(name.to_string()),
+            vendor_id: None,
+            product_id: None,
+            bus_type: None,
+            port: None,
         }
     }
 
@@ -537,19 +579,62 @@ impl UdevDevice {
         device.uniq()
     }
 
+    /// Returns true if the device is connected using the bluetooth bus,
+    /// including bluetooth LE devices using HID over GATT.
+    pub fn is_bluetooth(&self) -> bool {
+        self.id_bustype() == BUS_BLUETOOTH
+    }
+
+    /// Returns the bluetooth address of the device from its uniq property.
+    /// Unlike the sysname, the address stays the same when the device
+    /// reconnects. E.g. "aa:bb:cc:dd:ee:ff"
+    pub fn bluetooth_address(&self) -> Option<String> {
+        if !self.is_bluetooth() {
+            return None;
+        }
+        let uniq = self.uniq();
+        if uniq.is_empty() {
+            return None;
+        }
+        Some(uniq.to_lowercase())
+    }
+
+    /// Returns a copy of this device that represents the controller connected
+    /// to the given port of a multi-port adapter, starting at 1. Each port is
+    /// managed as a separate source device with its own id.
+    pub fn with_port(mut self, port: u8) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Returns the port of the multi-port adapter this device represents, if
+    /// any
+    pub fn port(&self) -> Option<u8> {
+        self.port
+    }
+
     /// Return a unique identifier for the device based on the subsystem and
-    /// sysname. E.g. "evdev://event3", "hidraw://hidraw0"
+    /// sysname. E.g. "evdev://event3", "hidraw://hidraw0". Ports of multi-port
+    /// adapters include the port number. E.g. "hidraw://hidraw0/port1"
+    /// Virtual and external source devices use their name. E.g. "virtual://demo"
     pub fn get_id(&self) -> String {
         match self.subsystem().as_str() {
             "input" => {
                 format!("evdev://{}", self.sysname)
             }
-            "hidraw" => {
-                format!("hidraw://{}", self.sysname)
-            }
+            "hidraw" => match self.port {
+                Some(port) => format!("hidraw://{}/port{port}", self.sysname),
+                None => format!("hidraw://{}", self.sysname),
+            },
             "iio" => {
                 format!("iio://{}", self.sysname)
             }
+            "virtual" => {
+                format!("virtual://{}", self.sysname)
+            }
+            "external" => {
+                format!("external://{}", self.sysname)
+            }
             _ => "".to_string(),
         }
     }
@@ -616,6 +701,7 @@ impl From<::udev::Device> for UdevDevice {
             vendor_id: Some(device.id_vendor()),
             product_id: Some(device.id_product()),
             bus_type: Some(device.id_bustype()),
+            port: None,
         }
     }
 }
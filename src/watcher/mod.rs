@@ -11,12 +11,16 @@ pub enum WatchEvent {
 /// Watch for filesystem changes on the given path, sending [WatchEvent]
 /// to the given channel.
 pub fn watch(path: String, tx: Sender<WatchEvent>) {
+    watch_with_mask(path, WatchMask::CREATE | WatchMask::DELETE, tx)
+}
+
+/// Watch for the given filesystem changes on the given path, sending
+/// [WatchEvent] to the given channel. Files moved into the path are sent as
+/// [WatchEvent::Create] and files moved out of it as [WatchEvent::Delete].
+pub fn watch_with_mask(path: String, mask: WatchMask, tx: Sender<WatchEvent>) {
     let mut inotify = Inotify::init().expect("Failed to initialize inotify");
 
-    if let Err(e) = inotify
-        .watches()
-        .add(path.clone(), WatchMask::CREATE | WatchMask::DELETE)
-    {
+    if let Err(e) = inotify.watches().add(path.clone(), mask) {
         log::error!(
             "Unable to add inotify wather for path: {path}. Got error {:?}",
             e
@@ -37,7 +41,10 @@ pub fn watch(path: String, tx: Sender<WatchEvent>) {
             // Send the event over our channel
             let name = String::from(event.name.unwrap().to_str().unwrap());
 
-            if event.mask.contains(EventMask::CREATE) {
+            if event
+                .mask
+                .intersects(EventMask::CREATE | EventMask::MOVED_TO)
+            {
                 log::debug!("inotify CREATE: {:?}", event.name);
                 let value = WatchEvent::Create {
                     name,
@@ -52,7 +59,10 @@ pub fn watch(path: String, tx: Sender<WatchEvent>) {
                 //} else {
                 //    println!("File created: {:?}", event.name);
                 //}
-            } else if event.mask.contains(EventMask::DELETE) {
+            } else if event
+                .mask
+                .intersects(EventMask::DELETE | EventMask::MOVED_FROM)
+            {
                 log::debug!("inotify DELETE: {:?}", event.name);
                 let value = WatchEvent::Delete {
                     name,
@@ -62,7 +72,10 @@ pub fn watch(path: String, tx: Sender<WatchEvent>) {
                     Ok(_) => (),
                     Err(e) => log::error!("Error sending event: {}", e),
                 }
-            } else if event.mask.contains(EventMask::MODIFY) {
+            } else if event
+                .mask
+                .intersects(EventMask::MODIFY | EventMask::CLOSE_WRITE)
+            {
                 log::trace!("inotify MODIFY: {:?}", event.name);
                 let value = WatchEvent::Modify {
                     name,